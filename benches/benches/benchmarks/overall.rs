@@ -48,6 +48,8 @@ fn block_assembler_config() -> BlockAssemblerConfig {
         notify: vec![],
         notify_scripts: vec![],
         notify_timeout_millis: 800,
+        weight: 1,
+        reward_targets: vec![],
     }
 }
 
@@ -494,6 +494,47 @@ fn test_chain_fork_by_total_difficulty() {
     );
 }
 
+#[test]
+fn test_chain_fork_notify_chain_reorg() {
+    let (chain_controller, shared, parent) = start_chain(None);
+    let mut reorg_receiver = shared
+        .async_handle()
+        .block_on(shared.notify_controller().subscribe_chain_reorg("test"));
+
+    let mock_store = MockStore::new(&parent, shared.store());
+    let mut chain1 = MockChain::new(parent.clone(), shared.consensus());
+    let mut chain2 = MockChain::new(parent.clone(), shared.consensus());
+
+    for _ in 0..3 {
+        chain1.gen_empty_block_with_diff(100u64, &mock_store);
+    }
+    for _ in 0..4 {
+        chain2.gen_empty_block_with_diff(100u64, &mock_store);
+    }
+
+    for block in chain1.blocks().iter().chain(chain2.blocks()) {
+        chain_controller
+            .blocking_process_block_with_switch(Arc::new(block.clone()), Switch::DISABLE_ALL)
+            .expect("process block ok");
+    }
+
+    let reorg = shared
+        .async_handle()
+        .block_on(reorg_receiver.recv())
+        .expect("chain reorg notified");
+    assert_eq!(reorg.fork_point.number(), parent.number());
+    assert_eq!(reorg.fork_point.hash(), parent.hash());
+    assert_eq!(
+        reorg.detached_blocks,
+        chain1.blocks().iter().map(|b| b.hash()).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        reorg.attached_blocks,
+        chain2.blocks().iter().map(|b| b.hash()).collect::<Vec<_>>()
+    );
+    assert!(reorg_receiver.try_recv().is_err());
+}
+
 #[test]
 fn test_chain_fork_by_first_received() {
     let (chain_controller, shared, parent) = start_chain(None);
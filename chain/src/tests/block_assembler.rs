@@ -37,6 +37,8 @@ fn start_chain(consensus: Option<Consensus>) -> (ChainController, Shared) {
         notify: vec![],
         notify_scripts: vec![],
         notify_timeout_millis: 800,
+        weight: 1,
+        reward_targets: vec![],
     };
     let (shared, mut pack) = builder
         .block_assembler_config(Some(config))
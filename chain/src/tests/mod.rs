@@ -9,6 +9,7 @@ mod load_input_cell_data;
 mod load_input_data_hash_cell;
 mod non_contextual_block_txs_verify;
 mod orphan_block_pool;
+mod prune;
 mod reward;
 mod truncate;
 mod uncle;
@@ -0,0 +1,55 @@
+use crate::start_chain_services;
+use ckb_chain_spec::consensus::Consensus;
+use ckb_shared::SharedBuilder;
+use ckb_store::ChainStore;
+use ckb_test_chain_utils::{MockChain, MockStore};
+use ckb_verification_traits::Switch;
+use std::sync::Arc;
+
+#[test]
+fn test_prune() {
+    let builder = SharedBuilder::with_temp_db();
+
+    let (shared, mut pack) = builder.consensus(Consensus::default()).build().unwrap();
+    let chain_controller = start_chain_services(pack.take_chain_services_builder());
+
+    let genesis = shared
+        .store()
+        .get_block_header(&shared.store().get_block_hash(0).unwrap())
+        .unwrap();
+
+    let mock_store = MockStore::new(&genesis, shared.store());
+    let mut mock = MockChain::new(genesis, shared.consensus());
+
+    for _ in 0..10 {
+        mock.gen_empty_block_with_diff(40u64, &mock_store);
+    }
+
+    for blk in mock.blocks() {
+        chain_controller
+            .blocking_process_block_with_switch(Arc::new(blk.clone()), Switch::DISABLE_ALL)
+            .unwrap();
+    }
+
+    // leave the initial block download
+    let faketime_guard = ckb_systemtime::faketime();
+    faketime_guard.set_faketime(mock.tip_header().timestamp());
+
+    shared.prune(3).unwrap();
+    let store = shared.store();
+    assert_eq!(store.get_pruned_block_number(), 7);
+    for blk in mock.blocks() {
+        let pruned = blk.number() < 7;
+        assert_eq!(store.is_block_pruned(blk.number()), pruned);
+        assert_eq!(store.get_block_body(&blk.hash()).is_empty(), pruned);
+        // the headers are kept
+        assert_eq!(store.get_block_header(&blk.hash()), Some(blk.header()));
+    }
+    assert!(!store
+        .get_block_body(&shared.consensus().genesis_hash())
+        .is_empty());
+
+    // nothing more to prune
+    shared.prune(3).unwrap();
+    assert_eq!(store.get_pruned_block_number(), 7);
+}
@@ -74,6 +74,8 @@ pub(crate) fn start_chain_with_tx_pool_config(
         notify: vec![],
         notify_scripts: vec![],
         notify_timeout_millis: 800,
+        weight: 1,
+        reward_targets: vec![],
     };
 
     let (shared, mut pack) = builder
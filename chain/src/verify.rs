@@ -15,10 +15,13 @@ use ckb_tx_pool::TxPoolController;
 use ckb_types::core::cell::{
     resolve_transaction, BlockCellProvider, HeaderChecker, OverlayCellProvider, ResolvedTransaction,
 };
-use ckb_types::core::{service::Request, BlockExt, BlockNumber, BlockView, Cycle, HeaderView};
+use ckb_types::core::{
+    service::{ChainReorg, Request},
+    BlockExt, BlockNumber, BlockView, Cycle, HeaderView,
+};
 use ckb_types::packed::Byte32;
 use ckb_types::utilities::merkle_mountain_range::ChainRootMMR;
-use ckb_types::H256;
+use ckb_types::{BlockNumberAndHash, H256};
 use ckb_verification::cache::Completed;
 use ckb_verification::InvalidParentError;
 use ckb_verification_contextual::{ContextualBlockVerifier, VerifyContext};
@@ -383,6 +386,11 @@ impl ConsumeUnverifiedBlockProcessor {
                 }
             }
 
+            if fork.has_detached() {
+                self.shared
+                    .notify_controller()
+                    .notify_chain_reorg(chain_reorg(&fork));
+            }
             self.shared
                 .notify_controller()
                 .notify_new_block(block.to_owned());
@@ -903,6 +911,21 @@ impl ConsumeUnverifiedBlockProcessor {
     }
 }
 
+fn chain_reorg(fork: &ForkChanges) -> ChainReorg {
+    let first_attached = fork
+        .attached_blocks()
+        .front()
+        .expect("attached_blocks is not empty");
+    ChainReorg {
+        fork_point: BlockNumberAndHash::new(
+            first_attached.number() - 1,
+            first_attached.parent_hash(),
+        ),
+        detached_blocks: fork.detached_blocks().iter().map(|b| b.hash()).collect(),
+        attached_blocks: fork.attached_blocks().iter().map(|b| b.hash()).collect(),
+    }
+}
+
 #[cfg(debug_assertions)]
 fn is_sorted_assert(fork: &ForkChanges) {
     assert!(fork.is_sorted())
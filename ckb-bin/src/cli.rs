@@ -27,6 +27,10 @@ pub const CMD_FROM_SECRET: &str = "from-secret";
 pub const CMD_MIGRATE: &str = "migrate";
 /// Subcommand `daemon`
 pub const CMD_DAEMON: &str = "daemon";
+/// Subcommand `snapshot`.
+pub const CMD_SNAPSHOT: &str = "snapshot";
+/// Subcommand `backup`.
+pub const CMD_BACKUP: &str = "backup";
 /// Command line argument `--config-dir`.
 pub const ARG_CONFIG_DIR: &str = "config-dir";
 /// Command line argument `--format`.
@@ -35,6 +39,8 @@ pub const ARG_FORMAT: &str = "format";
 pub const ARG_TARGET: &str = "target";
 /// Command line argument `--source`.
 pub const ARG_SOURCE: &str = "source";
+/// Command line argument `--compression`.
+pub const ARG_COMPRESSION: &str = "compression";
 /// Command line argument `--data`.
 pub const ARG_DATA: &str = "data";
 /// Command line argument `--list-chains`.
@@ -109,6 +115,14 @@ pub const ARG_SKIP_CHAIN_SPEC_CHECK: &str = "skip-spec-check";
 pub const ARG_OVERWRITE_CHAIN_SPEC: &str = "overwrite-spec";
 /// Command line argument `--assume-valid-target`.
 pub const ARG_ASSUME_VALID_TARGET: &str = "assume-valid-target";
+/// Command line argument `--cell-snapshot`.
+pub const ARG_CELL_SNAPSHOT: &str = "cell-snapshot";
+/// Command line argument `--cell-snapshot-hash`.
+pub const ARG_CELL_SNAPSHOT_HASH: &str = "cell-snapshot-hash";
+/// Command line argument `--backfill`.
+pub const ARG_BACKFILL: &str = "backfill";
+/// Command line argument `--restore`.
+pub const ARG_RESTORE: &str = "restore";
 /// Command line argument `--check`.
 pub const ARG_MIGRATE_CHECK: &str = "check";
 /// Command line argument `daemon --check`
@@ -147,7 +161,9 @@ pub fn basic_app() -> Command {
         .subcommand(stats())
         .subcommand(reset_data())
         .subcommand(peer_id())
-        .subcommand(migrate());
+        .subcommand(migrate())
+        .subcommand(snapshot())
+        .subcommand(backup());
 
     #[cfg(not(target_os = "windows"))]
     let command = command.subcommand(daemon());
@@ -219,6 +235,34 @@ to disable the default behavior and execute full verification for all blocks, \
             .long(ARG_RICH_INDEXER)
             .action(clap::ArgAction::SetTrue)
             .help("Start the built-in rich-indexer service"),
+        )
+        .arg(
+            Arg::new(ARG_CELL_SNAPSHOT)
+                .long(ARG_CELL_SNAPSHOT)
+                .value_name("path")
+                .value_parser(clap::builder::PathBufValueParser::new())
+                .requires(ARG_CELL_SNAPSHOT_HASH)
+                .help(
+                    "Bootstrap an empty database from the cell snapshot exported by `ckb snapshot`, \
+                     the node starts syncing from the snapshot block without replaying history",
+                ),
+        )
+        .arg(
+            Arg::new(ARG_CELL_SNAPSHOT_HASH)
+                .long(ARG_CELL_SNAPSHOT_HASH)
+                .value_parser(is_h256)
+                .requires(ARG_CELL_SNAPSHOT)
+                .help("The trusted hash of the cell snapshot, the snapshot is rejected on mismatch"),
+        )
+        .arg(
+            Arg::new(ARG_BACKFILL)
+                .long(ARG_BACKFILL)
+                .value_name("path")
+                .value_parser(clap::builder::PathBufValueParser::new())
+                .help(
+                    "Back-fill the historical blocks missing after bootstrapping from a cell snapshot \
+                     in the background, from the archive exported by `ckb export --format bin`",
+                ),
         );
 
     #[cfg(not(target_os = "windows"))]
@@ -373,15 +417,95 @@ fn replay() -> Command {
 }
 
 fn export() -> Command {
-    Command::new(CMD_EXPORT).about("Export CKB data").arg(
-        Arg::new(ARG_TARGET)
-            .short('t')
-            .long(ARG_TARGET)
-            .value_name("path")
-            .value_parser(clap::builder::PathBufValueParser::new())
-            .required(true)
-            .help("Specify the export target path"),
-    )
+    Command::new(CMD_EXPORT)
+        .about("Export CKB data")
+        .arg(
+            Arg::new(ARG_TARGET)
+                .short('t')
+                .long(ARG_TARGET)
+                .value_name("path")
+                .value_parser(clap::builder::PathBufValueParser::new())
+                .required(true)
+                .help("Specify the export target path"),
+        )
+        .arg(
+            Arg::new(ARG_FORMAT)
+                .short('f')
+                .long(ARG_FORMAT)
+                .value_parser(["json", "bin"])
+                .default_value("json")
+                .help(
+                    "Set the format of the exported file. \
+                     `json` writes one JSON block per line, \
+                     `bin` writes a molecule encoded archive with per-block checksums",
+                ),
+        )
+        .arg(
+            Arg::new(ARG_COMPRESSION)
+                .long(ARG_COMPRESSION)
+                .value_parser(["none", "snappy"])
+                .default_value("none")
+                .help("Set the compression of blocks, only works with `--format bin`"),
+        )
+        .arg(
+            Arg::new(ARG_FROM)
+                .long(ARG_FROM)
+                .value_parser(clap::value_parser!(u64))
+                .action(clap::ArgAction::Set)
+                .help("Specify from block number, defaults to the genesis block"),
+        )
+        .arg(
+            Arg::new(ARG_TO)
+                .long(ARG_TO)
+                .value_parser(clap::value_parser!(u64))
+                .action(clap::ArgAction::Set)
+                .help("Specify to block number (inclusive), defaults to the tip block"),
+        )
+}
+
+fn snapshot() -> Command {
+    Command::new(CMD_SNAPSHOT)
+        .about("Export a trusted snapshot of the live cell set at the tip block")
+        .arg(
+            Arg::new(ARG_TARGET)
+                .short('t')
+                .long(ARG_TARGET)
+                .value_name("path")
+                .value_parser(clap::builder::PathBufValueParser::new())
+                .required(true)
+                .help("Specify the snapshot target path"),
+        )
+}
+
+fn backup() -> Command {
+    Command::new(CMD_BACKUP)
+        .about("Back up the database and the freezer, or restore them from a backup")
+        .arg(
+            Arg::new(ARG_TARGET)
+                .short('t')
+                .long(ARG_TARGET)
+                .value_name("path")
+                .value_parser(clap::builder::PathBufValueParser::new())
+                .help(
+                    "Specify the backup directory to create, it must not exist. \
+                     Use the RPC method `create_backup` instead when the node is running",
+                ),
+        )
+        .arg(
+            Arg::new(ARG_RESTORE)
+                .long(ARG_RESTORE)
+                .value_name("path")
+                .value_parser(clap::builder::PathBufValueParser::new())
+                .help(
+                    "Restore the database and the freezer from the backup directory \
+                     after validating its manifest, the current data must be removed first",
+                ),
+        )
+        .group(
+            ArgGroup::new("backup-or-restore")
+                .args([ARG_TARGET, ARG_RESTORE])
+                .required(true),
+        )
 }
 
 fn import() -> Command {
@@ -391,7 +515,11 @@ fn import() -> Command {
             .value_name("path")
             .value_parser(clap::builder::PathBufValueParser::new())
             .required(true)
-            .help("Specify the exported data path"),
+            .help(
+                "Specify the exported data path. \
+                 Both JSON and binary archives are accepted, \
+                 blocks already in the database are skipped so an interrupted import can be resumed",
+            ),
     )
 }
 
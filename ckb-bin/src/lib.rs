@@ -140,6 +140,8 @@ fn run_app_inner(
         cli::CMD_EXPORT => subcommand::export(setup.export(matches)?, handle.clone()),
         cli::CMD_IMPORT => subcommand::import(setup.import(matches)?, handle.clone()),
         cli::CMD_STATS => subcommand::stats(setup.stats(matches)?, handle.clone()),
+        cli::CMD_SNAPSHOT => subcommand::snapshot(setup.snapshot(matches)?, handle.clone()),
+        cli::CMD_BACKUP => subcommand::backup(setup.backup(matches)?, handle.clone()),
         cli::CMD_RESET_DATA => subcommand::reset_data(setup.reset_data(matches)?),
         cli::CMD_MIGRATE => subcommand::migrate(setup.migrate(matches)?),
         #[cfg(not(target_os = "windows"))]
@@ -176,6 +178,8 @@ fn is_silent_logging(cmd: &str) -> Silent {
         cli::CMD_EXPORT
             | cli::CMD_IMPORT
             | cli::CMD_STATS
+            | cli::CMD_SNAPSHOT
+            | cli::CMD_BACKUP
             | cli::CMD_MIGRATE
             | cli::CMD_RESET_DATA
             | cli::CMD_DAEMON
@@ -1,9 +1,10 @@
 #[cfg(not(target_os = "windows"))]
 use ckb_app_config::DaemonArgs;
 use ckb_app_config::{
-    generate_random_key, read_secret_key, write_secret_to_file, AppConfig, CustomizeSpec, ExitCode,
-    ExportArgs, ImportArgs, InitArgs, MigrateArgs, MinerArgs, PeerIDArgs, ReplayArgs,
-    ResetDataArgs, RunArgs, StatsArgs,
+    generate_random_key, read_secret_key, write_secret_to_file, AppConfig, BackupArgs,
+    CellSnapshotArgs, CustomizeSpec, ExitCode, ExportArgs, ExportCompression, ExportFormat,
+    ImportArgs, InitArgs, MigrateArgs, MinerArgs, PeerIDArgs, ReplayArgs, ResetDataArgs, RunArgs,
+    SnapshotArgs, StatsArgs,
 };
 use ckb_chain_spec::{consensus::Consensus, ChainSpec};
 use ckb_jsonrpc_types::ScriptHashType;
@@ -115,6 +116,20 @@ H256::from_str(&target[2..]).expect("default assume_valid_target for testnet mus
             }
         }
 
+        let cell_snapshot = match (
+            matches.get_one::<PathBuf>(cli::ARG_CELL_SNAPSHOT),
+            matches.get_one::<String>(cli::ARG_CELL_SNAPSHOT_HASH),
+        ) {
+            (Some(path), Some(hash)) => Some(CellSnapshotArgs {
+                path: path.clone(),
+                hash: H256::from_str(&hash[2..]).map_err(|err| {
+                    eprintln!("Args Error: invalid cell snapshot hash: {err}");
+                    ExitCode::Cli
+                })?,
+            }),
+            _ => None,
+        };
+
         Ok(RunArgs {
             config,
             consensus,
@@ -126,6 +141,8 @@ H256::from_str(&target[2..]).expect("default assume_valid_target for testnet mus
             rich_indexer: matches.get_flag(cli::ARG_RICH_INDEXER),
             #[cfg(not(target_os = "windows"))]
             daemon: matches.get_flag(cli::ARG_DAEMON),
+            cell_snapshot,
+            backfill: matches.get_one::<PathBuf>(cli::ARG_BACKFILL).cloned(),
         })
     }
 
@@ -210,6 +227,38 @@ H256::from_str(&target[2..]).expect("default assume_valid_target for testnet mus
         })
     }
 
+    /// Executes `ckb snapshot`.
+    pub fn snapshot(self, matches: &ArgMatches) -> Result<SnapshotArgs, ExitCode> {
+        let consensus = self.consensus()?;
+        let config = self.config.into_ckb()?;
+        let target = matches
+            .get_one::<PathBuf>(cli::ARG_TARGET)
+            .ok_or_else(|| {
+                eprintln!("Args Error: {:?} no found", cli::ARG_TARGET);
+                ExitCode::Cli
+            })?
+            .clone();
+
+        Ok(SnapshotArgs {
+            config,
+            consensus,
+            target,
+        })
+    }
+
+    /// Executes `ckb backup`.
+    pub fn backup(self, matches: &ArgMatches) -> Result<BackupArgs, ExitCode> {
+        let consensus = self.consensus()?;
+        let config = self.config.into_ckb()?;
+
+        Ok(BackupArgs {
+            config,
+            consensus,
+            target: matches.get_one::<PathBuf>(cli::ARG_TARGET).cloned(),
+            restore: matches.get_one::<PathBuf>(cli::ARG_RESTORE).cloned(),
+        })
+    }
+
     /// Executes `ckb import`.
     pub fn import(self, matches: &ArgMatches) -> Result<ImportArgs, ExitCode> {
         let consensus = self.consensus()?;
@@ -240,11 +289,41 @@ H256::from_str(&target[2..]).expect("default assume_valid_target for testnet mus
                 ExitCode::Cli
             })?
             .clone();
+        let format = match matches
+            .get_one::<String>(cli::ARG_FORMAT)
+            .map(String::as_str)
+        {
+            Some("bin") => ExportFormat::Binary,
+            _ => ExportFormat::Json,
+        };
+        let compression = match matches
+            .get_one::<String>(cli::ARG_COMPRESSION)
+            .map(String::as_str)
+        {
+            Some("snappy") => ExportCompression::Snappy,
+            _ => ExportCompression::None,
+        };
+        if format == ExportFormat::Json && compression != ExportCompression::None {
+            eprintln!("Args Error: --compression only works with `--format bin`");
+            return Err(ExitCode::Cli);
+        }
+        let from = matches.get_one::<u64>(cli::ARG_FROM).cloned();
+        let to = matches.get_one::<u64>(cli::ARG_TO).cloned();
+        if let (Some(from), Some(to)) = (from, to) {
+            if from > to {
+                eprintln!("Args Error: --from {from} is greater than --to {to}");
+                return Err(ExitCode::Cli);
+            }
+        }
 
         Ok(ExportArgs {
             config,
             consensus,
             target,
+            format,
+            compression,
+            from,
+            to,
         })
     }
 
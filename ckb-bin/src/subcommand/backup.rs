@@ -0,0 +1,118 @@
+use ckb_app_config::{BackupArgs, ExitCode};
+use ckb_async_runtime::Handle;
+use ckb_migrate::migrate::Migrate;
+use ckb_shared::SharedBuilder;
+use ckb_store::{BackupManifest, BACKUP_ANCIENT_DIR, BACKUP_DB_DIR};
+use ckb_types::{prelude::*, H256};
+use std::cmp::Ordering;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+pub fn backup(args: BackupArgs, async_handle: Handle) -> Result<(), ExitCode> {
+    match (args.target.clone(), args.restore.clone()) {
+        (Some(target), _) => create(args, &target, async_handle),
+        (None, Some(source)) => restore(args, &source),
+        (None, None) => {
+            eprintln!("Backup error: one of --target and --restore is required");
+            Err(ExitCode::Cli)
+        }
+    }
+}
+
+fn create(args: BackupArgs, target: &Path, async_handle: Handle) -> Result<(), ExitCode> {
+    let builder = SharedBuilder::new(
+        &args.config.bin_name,
+        args.config.root_dir.as_path(),
+        &args.config.db,
+        Some(args.config.ancient.clone()),
+        async_handle,
+        args.consensus,
+    )?;
+    let (shared, _) = builder.build()?;
+
+    let manifest = shared.store().create_backup(target).map_err(|err| {
+        eprintln!("Backup error: {err}");
+        ExitCode::Failure
+    })?;
+
+    println!("backup: {}", target.display());
+    println!("block: {} {:#x}", manifest.tip_number, manifest.tip_hash);
+    println!("migration version: {}", manifest.migration_version);
+    Ok(())
+}
+
+fn restore(args: BackupArgs, source: &Path) -> Result<(), ExitCode> {
+    let manifest = BackupManifest::load(source).map_err(restore_error)?;
+
+    let genesis_hash: H256 = args.consensus.genesis_hash().unpack();
+    if manifest.genesis_hash != genesis_hash {
+        eprintln!(
+            "Restore error: the backup genesis hash {:#x} does not match the chain spec {:#x}",
+            manifest.genesis_hash, genesis_hash
+        );
+        return Err(ExitCode::Failure);
+    }
+    manifest.verify(source).map_err(restore_error)?;
+
+    let migrate = Migrate::new(
+        source.join(BACKUP_DB_DIR),
+        args.consensus.hardfork_switch.clone(),
+    );
+    if let Some(db) = migrate.open_read_only_db().map_err(restore_error)? {
+        if matches!(migrate.check(&db, true), Ordering::Greater) {
+            eprintln!(
+                "Restore error: the backup was created by a higher version CKB executable binary \n\
+                 and cannot be opened by the current binary.\n\
+                 Please download the latest CKB executable binary."
+            );
+            return Err(ExitCode::Failure);
+        }
+    }
+
+    let db_path = &args.config.db.path;
+    let ancient_path = &args.config.ancient;
+    for dir in [db_path, ancient_path] {
+        if !is_empty_dir(dir)? {
+            eprintln!(
+                "Restore error: {} is not empty, remove it before restoring",
+                dir.display()
+            );
+            return Err(ExitCode::Failure);
+        }
+    }
+    copy_dir(&source.join(BACKUP_DB_DIR), db_path)?;
+    if manifest.frozen_number.is_some() {
+        copy_dir(&source.join(BACKUP_ANCIENT_DIR), ancient_path)?;
+    }
+
+    println!("restored: {}", source.display());
+    println!("block: {} {:#x}", manifest.tip_number, manifest.tip_hash);
+    Ok(())
+}
+
+fn restore_error<E: fmt::Display>(err: E) -> ExitCode {
+    eprintln!("Restore error: {err}");
+    ExitCode::Failure
+}
+
+fn is_empty_dir(dir: &Path) -> io::Result<bool> {
+    match fs::read_dir(dir) {
+        Ok(mut entries) => Ok(entries.next().is_none()),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(true),
+        Err(err) => Err(err),
+    }
+}
+
+// Both the database checkpoint and the freezer are flat directories
+fn copy_dir(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        if entry.file_type()?.is_file() {
+            fs::copy(entry.path(), to.join(entry.file_name()))?;
+        }
+    }
+    Ok(())
+}
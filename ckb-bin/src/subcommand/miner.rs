@@ -19,7 +19,11 @@ pub fn miner(args: MinerArgs, async_handle: Handle) -> Result<(), ExitCode> {
         new_work_rx,
         &workers,
         args.limit,
-    );
+    )
+    .map_err(|err| {
+        eprintln!("miner config error: {err}");
+        ExitCode::Config
+    })?;
 
     ckb_memory_tracker::track_current_process_simple(args.memory_tracker.interval);
 
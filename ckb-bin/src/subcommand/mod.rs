@@ -1,3 +1,4 @@
+mod backup;
 #[cfg(not(target_os = "windows"))]
 mod daemon;
 mod export;
@@ -10,8 +11,10 @@ mod peer_id;
 mod replay;
 mod reset_data;
 mod run;
+mod snapshot;
 mod stats;
 
+pub use self::backup::backup;
 #[cfg(not(target_os = "windows"))]
 pub use self::daemon::{check_process, daemon};
 pub use self::export::export;
@@ -24,4 +27,5 @@ pub use self::peer_id::peer_id;
 pub use self::replay::replay;
 pub use self::reset_data::reset_data;
 pub use self::run::run;
+pub use self::snapshot::snapshot;
 pub use self::stats::stats;
@@ -1,15 +1,18 @@
-use std::thread::available_parallelism;
+use std::path::PathBuf;
+use std::thread::{self, available_parallelism};
 
 use crate::helper::deadlock_detection;
 use ckb_app_config::{ExitCode, RunArgs};
 use ckb_async_runtime::{new_global_runtime, Handle};
 use ckb_build_info::Version;
+use ckb_instrument::Backfill;
 use ckb_launcher::Launcher;
-use ckb_logger::info;
 use ckb_logger::warn;
+use ckb_logger::{error, info};
 use ckb_resource::{Resource, TemplateContext};
+use ckb_shared::Shared;
 
-use ckb_stop_handler::{broadcast_exit_signals, wait_all_ckb_services_exit};
+use ckb_stop_handler::{broadcast_exit_signals, register_thread, wait_all_ckb_services_exit};
 
 use ckb_types::core::cell::setup_system_cell_cache;
 
@@ -33,6 +36,14 @@ pub fn run(args: RunArgs, version: Version, async_handle: Handle) -> Result<(),
     // spawn freezer background process
     let _freezer = shared.spawn_freeze();
 
+    if let Some(prune_depth) = launcher.args.config.store.prune_depth {
+        shared.spawn_prune(prune_depth);
+    }
+
+    if let Some(source) = launcher.args.backfill.clone() {
+        spawn_backfill(shared.clone(), source);
+    }
+
     setup_system_cell_cache(
         shared.consensus().genesis_block(),
         shared.snapshot().as_ref(),
@@ -59,7 +70,7 @@ pub fn run(args: RunArgs, version: Version, async_handle: Handle) -> Result<(),
         chain_controller,
         miner_enable,
         pack.take_relay_tx_receiver(),
-    );
+    )?;
 
     let tx_pool_builder = pack.take_tx_pool_builder();
     tx_pool_builder.start(network_controller);
@@ -77,6 +88,20 @@ pub fn run(args: RunArgs, version: Version, async_handle: Handle) -> Result<(),
     Ok(())
 }
 
+fn spawn_backfill(shared: Shared, source: PathBuf) {
+    let backfill_thread = thread::Builder::new()
+        .name("backfill".to_string())
+        .spawn(move || {
+            info!("back-filling historical blocks from {}", source.display());
+            match Backfill::new(shared, source).execute() {
+                Ok(count) => info!("back-filled {} historical blocks", count),
+                Err(err) => error!("back-fill historical blocks error: {}", err),
+            }
+        })
+        .expect("start backfill thread should ok");
+    register_thread("backfill", backfill_thread);
+}
+
 fn calc_rpc_threads_num(args: &RunArgs) -> usize {
     let system_parallelism: usize = available_parallelism().unwrap().into();
     let default_num = usize::max(system_parallelism, 1);
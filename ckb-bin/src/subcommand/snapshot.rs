@@ -0,0 +1,45 @@
+use ckb_app_config::{ExitCode, SnapshotArgs};
+use ckb_async_runtime::Handle;
+use ckb_shared::SharedBuilder;
+use ckb_store::export_cell_snapshot;
+use std::fs;
+use std::io;
+
+pub fn snapshot(args: SnapshotArgs, async_handle: Handle) -> Result<(), ExitCode> {
+    let builder = SharedBuilder::new(
+        &args.config.bin_name,
+        args.config.root_dir.as_path(),
+        &args.config.db,
+        None,
+        async_handle,
+        args.consensus,
+    )?;
+    let (shared, _) = builder.build()?;
+
+    let consensus = shared.consensus();
+    // enough blocks to verify the rewards, the proposals and the median time of the next blocks
+    let recent_blocks = consensus.finalization_delay_length()
+        + consensus.tx_proposal_window().farthest()
+        + consensus.median_time_block_count() as u64;
+    let snapshot = shared.snapshot();
+    let tip_number = snapshot.tip_number();
+
+    fs::create_dir_all(&args.target)?;
+    let path = args
+        .target
+        .join(format!("{}-{}.ckbcells", consensus.id, tip_number));
+    let f = fs::OpenOptions::new()
+        .create_new(true)
+        .write(true)
+        .open(&path)?;
+    let info = export_cell_snapshot(snapshot.as_ref(), io::BufWriter::new(f), recent_blocks)
+        .map_err(|err| {
+            eprintln!("Snapshot error: {err:?}");
+            ExitCode::Failure
+        })?;
+
+    println!("snapshot: {}", path.display());
+    println!("block: {} {:#x}", info.number, info.block_hash);
+    println!("hash: {:#x}", info.hash);
+    Ok(())
+}
@@ -22,11 +22,32 @@ function ckb_import { #@test
   [ "$status" -eq 0 ]
 }
 
+_export_bin() {
+  bash -c "ckb export -C ${CKB_DIRNAME} -t ${TMP_DIR} --format bin --compression snappy"
+}
+
+function export_bin { #@test
+  run _export_bin
+  [ "$status" -eq 0 ]
+}
+
+_import_bin() {
+  bash -c "ckb init -C ${TMP_DIR}/import_bin"
+  bash -c "ckb import -C ${TMP_DIR}/import_bin ${TMP_DIR}/ckb*.ckbblocks"
+  # importing again resumes from the imported tip
+  bash -c "ckb import -C ${TMP_DIR}/import_bin ${TMP_DIR}/ckb*.ckbblocks"
+}
+
+function ckb_import_bin { #@test
+  run _import_bin
+  [ "$status" -eq 0 ]
+}
+
 setup_file() {
-  rm -f ${TMP_DIR}/ckb*.json
+  rm -f ${TMP_DIR}/ckb*.json ${TMP_DIR}/ckb*.ckbblocks
 }
 
 teardown_file() {
-  rm -f ${TMP_DIR}/ckb*.json
-  rm -rvf ${TMP_DIR}/import
+  rm -f ${TMP_DIR}/ckb*.json ${TMP_DIR}/ckb*.ckbblocks
+  rm -rvf ${TMP_DIR}/import ${TMP_DIR}/import_bin
 }
@@ -51,6 +51,8 @@ pub const META_TIP_HEADER_KEY: &[u8] = b"TIP_HEADER";
 pub const META_CURRENT_EPOCH_KEY: &[u8] = b"CURRENT_EPOCH";
 /// META_FILTER_DATA_KEY tracks the latest built filter data block hash
 pub const META_LATEST_BUILT_FILTER_DATA_KEY: &[u8] = b"LATEST_BUILT_FILTER_DATA";
+/// META_PRUNED_BLOCK_NUMBER_KEY tracks the number of the first block whose body is not pruned
+pub const META_PRUNED_BLOCK_NUMBER_KEY: &[u8] = b"PRUNED_BLOCK_NUMBER";
 
 /// CHAIN_SPEC_HASH_KEY tracks the hash of chain spec which created current database
 pub const CHAIN_SPEC_HASH_KEY: &[u8] = b"chain-spec-hash";
@@ -7,8 +7,8 @@ use ckb_app_config::DBConfig;
 use ckb_db_schema::Col;
 use ckb_logger::info;
 use rocksdb::ops::{
-    CompactRangeCF, CreateCF, DropCF, GetColumnFamilys, GetPinned, GetPinnedCF, IterateCF, OpenCF,
-    Put, SetOptions, WriteOps,
+    CompactRangeCF, CreateCF, CreateCheckpointObject, DropCF, GetColumnFamilys, GetPinned,
+    GetPinnedCF, IterateCF, OpenCF, Put, SetOptions, WriteOps,
 };
 use rocksdb::{
     ffi, BlockBasedIndexType, BlockBasedOptions, Cache, ColumnFamily, ColumnFamilyDescriptor,
@@ -310,6 +310,20 @@ impl RocksDB {
         }
     }
 
+    /// Creates an openable snapshot of the database in the directory `path`, which must not exist.
+    ///
+    /// The SST files are hard linked when `path` is on the same filesystem, so a checkpoint is cheap
+    /// to take while the database is being written.
+    pub fn create_checkpoint<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let checkpoint = self
+            .inner
+            .create_checkpoint_object()
+            .map_err(internal_error)?;
+        checkpoint
+            .create_checkpoint(path.as_ref())
+            .map_err(internal_error)
+    }
+
     /// Return rocksdb `OptimisticTransactionDB`.
     pub fn inner(&self) -> Arc<OptimisticTransactionDB> {
         Arc::clone(&self.inner)
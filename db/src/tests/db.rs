@@ -137,3 +137,35 @@ fn write_and_partial_read() {
 
     assert!(vec![4u8, 3, 2].as_slice() == &ret.as_ref()[1..4]);
 }
+
+#[test]
+fn create_checkpoint() {
+    let db = setup_db("create_checkpoint", 2);
+    let txn = db.transaction();
+    txn.put("0", &[0, 0], &[0, 0, 0]).unwrap();
+    txn.commit().unwrap();
+
+    let tmp_dir = tempfile::Builder::new()
+        .prefix("create_checkpoint_target")
+        .tempdir()
+        .unwrap();
+    let path = tmp_dir.path().join("checkpoint");
+    db.create_checkpoint(&path).unwrap();
+    // the target must not exist
+    assert!(db.create_checkpoint(&path).is_err());
+
+    let txn = db.transaction();
+    txn.put("0", &[1, 1], &[1, 1, 1]).unwrap();
+    txn.commit().unwrap();
+
+    let checkpoint = RocksDB::open_in(&path, 2);
+    assert!(
+        vec![0u8, 0, 0].as_slice()
+            == checkpoint
+                .get_pinned("0", &[0, 0])
+                .unwrap()
+                .unwrap()
+                .as_ref()
+    );
+    assert!(checkpoint.get_pinned("0", &[1, 1]).unwrap().is_none());
+}
@@ -153,6 +153,18 @@ impl Freezer {
         self.number.load(Ordering::SeqCst)
     }
 
+    /// Copies the first `number` items into an empty directory while the freezer keeps running.
+    pub fn backup<P: AsRef<Path>>(&self, target: P, number: BlockNumber) -> Result<(), Error> {
+        self.inner
+            .lock()
+            .files
+            .copy_to(target.as_ref(), number)
+            .map_err(internal_error)?;
+        // repair the copy, which drops the data appended after the copied items
+        let files = FreezerFiles::open(target.as_ref().to_path_buf()).map_err(internal_error)?;
+        files.sync_all().map_err(internal_error)
+    }
+
     /// Truncate discards any recent data above the provided threshold number.
     pub fn truncate(&self, item: u64) -> Result<(), Error> {
         if item > 0 && ((item + 1) < self.number()) {
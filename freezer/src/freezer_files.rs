@@ -284,6 +284,35 @@ impl FreezerFiles {
         Ok(())
     }
 
+    /// Copies the first `number` items into the directory `target`.
+    ///
+    /// Only the index entries of the copied items are written, the data appended to the head file
+    /// after them is truncated when the copy is opened.
+    pub fn copy_to(&self, target: &Path, number: u64) -> Result<(), IoError> {
+        // the first index entry is the default one
+        let entries = number.max(1);
+        if entries > self.number() {
+            return Err(IoError::new(
+                IoErrorKind::Other,
+                format!("copy {} items beyond the frozen {}", entries, self.number()),
+            ));
+        }
+        self.sync_all()?;
+        fs::create_dir_all(target)?;
+
+        let mut index = Vec::with_capacity((entries * INDEX_ENTRY_SIZE) as usize);
+        File::open(self.file_path.join(INDEX_FILE_NAME))?
+            .take(entries * INDEX_ENTRY_SIZE)
+            .read_to_end(&mut index)?;
+        let last = IndexEntry::decode(&index[index.len() - INDEX_ENTRY_SIZE as usize..])?;
+        for id in self.tail_id..=last.file_id {
+            let name = helper::file_name(id);
+            fs::copy(self.file_path.join(&name), target.join(&name))?;
+        }
+        fs::write(target.join(INDEX_FILE_NAME), index)?;
+        Ok(())
+    }
+
     /// Attempts to open files, initialize fd map
     pub fn preopen(&mut self) -> Result<(), IoError> {
         self.release_all();
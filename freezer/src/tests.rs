@@ -256,6 +256,44 @@ fn truncate() {
     assert_eq!(None, retrieve_out_of_bound);
 }
 
+#[test]
+fn copy_to() {
+    let tempdir = tempfile::Builder::new().tempdir().unwrap();
+    let target = tempfile::Builder::new().tempdir().unwrap();
+    let mut freezer = FreezerFilesBuilder::new(tempdir.path().to_path_buf())
+        .enable_compression(false)
+        .max_file_size(50)
+        .build()
+        .unwrap();
+    freezer.preopen().unwrap();
+    for i in 1..30 {
+        let data = make_bytes(15, i);
+        freezer.append(i.into(), &data).unwrap();
+    }
+
+    assert!(freezer.copy_to(target.path(), 31).is_err());
+    freezer.copy_to(target.path(), 11).unwrap();
+
+    let mut copy = FreezerFilesBuilder::new(target.path().to_path_buf())
+        .enable_compression(false)
+        .max_file_size(50)
+        .build()
+        .unwrap();
+    copy.preopen().unwrap();
+    assert_eq!(copy.number(), 11);
+    for i in 1..11 {
+        let expect = make_bytes(15, i);
+        let actual = copy.retrieve(i.into()).unwrap();
+        assert_eq!(Some(expect), actual);
+    }
+    assert_eq!(None, copy.retrieve(11).unwrap());
+
+    // the copy can be appended again
+    let data = make_bytes(15, 11);
+    copy.append(11, &data).unwrap();
+    assert_eq!(Some(data), copy.retrieve(11).unwrap());
+}
+
 #[test]
 fn open_files_limit_retrieve() {
     let tempdir = tempfile::Builder::new().tempdir().unwrap();
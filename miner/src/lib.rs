@@ -1,6 +1,7 @@
 //! TODO(doc): @quake
 mod client;
 mod miner;
+mod stratum;
 mod worker;
 
 pub use crate::client::Client;
@@ -34,21 +34,21 @@ pub struct Miner {
 }
 
 impl Miner {
-    /// TODO(doc): @quake
+    /// Creates the miner and starts the workers, returns an error if any worker can't be started.
     pub fn new(
         pow: Arc<dyn PowEngine>,
         client: Client,
         work_rx: Receiver<Works>,
         workers: &[MinerWorkerConfig],
         limit: u128,
-    ) -> Miner {
+    ) -> Result<Miner, String> {
         let (nonce_tx, nonce_rx) = unbounded();
         let mp = MultiProgress::new();
 
         let worker_controllers = workers
             .iter()
             .map(|config| start_worker(Arc::clone(&pow), config, nonce_tx.clone(), &mp))
-            .collect();
+            .collect::<Result<_, _>>()?;
 
         let pb = mp.add(ProgressBar::new(100));
         pb.set_style(ProgressStyle::default_bar().template("{msg:.green}"));
@@ -59,7 +59,7 @@ impl Miner {
             mp.join().expect("MultiProgress join failed");
         });
 
-        Miner {
+        Ok(Miner {
             legacy_work: LruCache::new(WORK_CACHE_SIZE),
             nonces_found: 0,
             _pow: pow,
@@ -70,7 +70,7 @@ impl Miner {
             pb,
             stderr_is_tty,
             limit,
-        }
+        })
     }
 
     /// TODO(doc): @quake
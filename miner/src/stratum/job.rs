@@ -0,0 +1,150 @@
+use crate::Work;
+use ckb_pow::PowEngine;
+use ckb_types::{
+    packed::{Byte32, Header},
+    prelude::*,
+    U256,
+};
+use std::collections::{HashSet, VecDeque};
+
+// The jobs of the same parent kept for the late shares
+const MAX_JOBS: usize = 8;
+
+pub(crate) struct Job {
+    pub(crate) id: u64,
+    pub(crate) pow_hash: Byte32,
+    pub(crate) work: Work,
+    /// The block target
+    pub(crate) target: U256,
+    submitted: HashSet<u128>,
+}
+
+impl Job {
+    pub(crate) fn parent_hash(&self) -> Byte32 {
+        self.work.block.header().raw().parent_hash()
+    }
+
+    pub(crate) fn number(&self) -> u64 {
+        self.work.block.header().raw().number().unpack()
+    }
+}
+
+/// An accepted share.
+pub(crate) enum Share {
+    /// The share only meets the share target.
+    Accepted,
+    /// The share meets the block target, which should be submitted.
+    Block {
+        pow_hash: Byte32,
+        work: Work,
+        nonce: u128,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ShareError {
+    JobNotFound,
+    Duplicate,
+    LowDifficulty,
+}
+
+impl ShareError {
+    /// The stratum error code and message.
+    pub(crate) fn code(self) -> (i64, &'static str) {
+        match self {
+            ShareError::JobNotFound => (21, "Job not found"),
+            ShareError::Duplicate => (22, "Duplicate share"),
+            ShareError::LowDifficulty => (23, "Low difficulty share"),
+        }
+    }
+}
+
+/// Turns the works into the stratum jobs, and checks the shares submitted for them.
+pub(crate) struct JobManager {
+    jobs: VecDeque<Job>,
+    next_id: u64,
+    share_target: U256,
+}
+
+impl JobManager {
+    pub(crate) fn new(share_target: U256) -> Self {
+        JobManager {
+            jobs: VecDeque::new(),
+            next_id: 0,
+            share_target,
+        }
+    }
+
+    /// Adds a job for the new work, returns whether the former jobs are dropped,
+    /// which is the `clean_jobs` flag of `mining.notify`.
+    pub(crate) fn add_job(&mut self, pow_hash: Byte32, work: Work, target: U256) -> bool {
+        let parent_hash = work.block.header().raw().parent_hash();
+        let clean = self
+            .jobs
+            .back()
+            .map_or(true, |job| job.parent_hash() != parent_hash);
+        if clean {
+            self.jobs.clear();
+        } else if self.jobs.len() >= MAX_JOBS {
+            self.jobs.pop_front();
+        }
+        self.next_id += 1;
+        self.jobs.push_back(Job {
+            id: self.next_id,
+            pow_hash,
+            work,
+            target,
+            submitted: HashSet::new(),
+        });
+        clean
+    }
+
+    pub(crate) fn latest_job(&self) -> Option<&Job> {
+        self.jobs.back()
+    }
+
+    /// The target the shares of the job must meet, the block target is used if it is easier.
+    pub(crate) fn share_target(&self, job: &Job) -> U256 {
+        if job.target > self.share_target {
+            job.target.clone()
+        } else {
+            self.share_target.clone()
+        }
+    }
+
+    /// Checks the share of the job.
+    pub(crate) fn submit(
+        &mut self,
+        pow: &dyn PowEngine,
+        job_id: u64,
+        nonce: u128,
+    ) -> Result<Share, ShareError> {
+        let share_target = self.share_target.clone();
+        let job = self
+            .jobs
+            .iter_mut()
+            .find(|job| job.id == job_id)
+            .ok_or(ShareError::JobNotFound)?;
+        if job.submitted.contains(&nonce) {
+            return Err(ShareError::Duplicate);
+        }
+
+        let header = Header::new_builder()
+            .raw(job.work.block.header().raw())
+            .nonce(nonce.pack())
+            .build();
+        let share = if pow.verify(&header) {
+            Share::Block {
+                pow_hash: job.pow_hash.clone(),
+                work: job.work.clone(),
+                nonce,
+            }
+        } else if pow.verify_with_target(&header, &share_target) {
+            Share::Accepted
+        } else {
+            return Err(ShareError::LowDifficulty);
+        };
+        job.submitted.insert(nonce);
+        Ok(share)
+    }
+}
@@ -0,0 +1,465 @@
+//! A stratum server which hands out the work to the external mining hardware.
+//!
+//! It speaks the CKB flavour of Stratum v1, line delimited JSON over TCP:
+//!
+//! * `mining.subscribe` returns `[null, extranonce1, extranonce2_size]`, the nonce of a share
+//!   is the 16 bytes `extranonce1 || extranonce2` in big endian.
+//! * `mining.authorize` with `[worker, password]`, the password is ignored.
+//! * `mining.set_target` with `[target]` and `mining.notify` with
+//!   `[job_id, pow_hash, height, parent_hash, clean_jobs]` are pushed to the subscribed miners.
+//! * `mining.submit` with `[worker, job_id, extranonce2]`.
+//!
+//! The shares meeting the block target are sent to the miner as the found nonces, which then
+//! submits the blocks via `submit_block`.
+mod job;
+mod session;
+#[cfg(test)]
+mod tests;
+
+use self::job::{Job, JobManager, Share, ShareError};
+use self::session::Session;
+use crate::worker::{WorkerController, WorkerMessage};
+use crate::Work;
+use ckb_app_config::StratumConfig;
+use ckb_channel::{bounded, unbounded, Receiver, Sender, TrySendError};
+use ckb_logger::{debug, error, info, warn};
+use ckb_pow::PowEngine;
+use ckb_types::{packed::Byte32, U256};
+use ckb_util::Mutex;
+use indicatif::ProgressBar;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::io::{self, BufWriter, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+const NONCE_SIZE: usize = 16;
+// a miner submits the shares far more often than this, an idle connection is closed
+const READ_TIMEOUT: Duration = Duration::from_secs(600);
+const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+// the pending message batches of a session, a session falling behind is closed
+const MAX_PENDING_MESSAGES: usize = 16;
+
+/// Starts the stratum server as a worker of the miner.
+pub(crate) fn start_stratum(
+    pow: Arc<dyn PowEngine>,
+    config: &StratumConfig,
+    nonce_tx: Sender<(Byte32, Work, u128)>,
+    progress_bar: ProgressBar,
+) -> Result<WorkerController, String> {
+    let extranonce1_size = config.extranonce1_size.clamp(1, 8);
+    if extranonce1_size != config.extranonce1_size {
+        warn!(
+            "stratum extranonce1_size {} is out of range 1..=8, use {} instead",
+            config.extranonce1_size, extranonce1_size
+        );
+    }
+    // every connection is assigned a distinct extranonce1
+    if config.max_connections as u128 > 1u128 << (extranonce1_size * 8) {
+        return Err(format!(
+            "stratum max_connections {} exceeds the {} extranonce1 values of {} bytes",
+            config.max_connections,
+            1u128 << (extranonce1_size * 8),
+            extranonce1_size
+        ));
+    }
+    let share_target =
+        U256::from_big_endian(config.share_target.as_bytes()).expect("bound checked");
+    let listener = TcpListener::bind(config.listen)
+        .map_err(|err| format!("stratum server listen on {} error: {}", config.listen, err))?;
+    info!("stratum server listening on {}", config.listen);
+
+    Ok(spawn_server(
+        listener,
+        pow,
+        share_target,
+        extranonce1_size,
+        config.max_connections,
+        nonce_tx,
+        progress_bar,
+    ))
+}
+
+pub(crate) fn spawn_server(
+    listener: TcpListener,
+    pow: Arc<dyn PowEngine>,
+    share_target: U256,
+    extranonce1_size: usize,
+    max_connections: usize,
+    nonce_tx: Sender<(Byte32, Work, u128)>,
+    progress_bar: ProgressBar,
+) -> WorkerController {
+    let server = Arc::new(Server {
+        pow,
+        jobs: Mutex::new(JobManager::new(share_target)),
+        sessions: Mutex::new(HashMap::new()),
+        nonce_tx,
+        extranonce1_size,
+        max_connections,
+        connections: Mutex::new(Extranonces::new(extranonce1_size)),
+        next_session_id: AtomicU64::new(0),
+        accepted: AtomicU64::new(0),
+        rejected: AtomicU64::new(0),
+        progress_bar,
+    });
+
+    let (worker_tx, worker_rx) = unbounded();
+    let dispatcher = Arc::clone(&server);
+    thread::Builder::new()
+        .name("Stratum-Dispatcher".to_string())
+        .spawn(move || dispatcher.dispatch(worker_rx))
+        .expect("Start stratum dispatcher thread failed");
+    thread::Builder::new()
+        .name("Stratum-Listener".to_string())
+        .spawn(move || server.listen(listener))
+        .expect("Start stratum listener thread failed");
+    WorkerController::new(vec![worker_tx])
+}
+
+// The extranonce1 values assigned to the open connections, a value is reused only after its
+// connection is closed.
+pub(crate) struct Extranonces {
+    // session id => extranonce1
+    assigned: HashMap<u64, u64>,
+    in_use: HashSet<u64>,
+    next: u64,
+    max: u64,
+}
+
+impl Extranonces {
+    pub(crate) fn new(extranonce1_size: usize) -> Self {
+        let max = if extranonce1_size >= 8 {
+            u64::MAX
+        } else {
+            (1u64 << (extranonce1_size * 8)) - 1
+        };
+        Extranonces {
+            assigned: HashMap::new(),
+            in_use: HashSet::new(),
+            next: 0,
+            max,
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.assigned.len()
+    }
+
+    /// Assigns a free extranonce1 to the session, returns `None` if all the values are in use.
+    pub(crate) fn open(&mut self, id: u64) -> Option<u64> {
+        if self.in_use.len() as u64 > self.max {
+            return None;
+        }
+        loop {
+            let extranonce1 = self.next;
+            self.next = if self.next == self.max {
+                0
+            } else {
+                self.next + 1
+            };
+            if self.in_use.insert(extranonce1) {
+                self.assigned.insert(id, extranonce1);
+                return Some(extranonce1);
+            }
+        }
+    }
+
+    /// Releases the extranonce1 of the closed session.
+    pub(crate) fn close(&mut self, id: u64) {
+        if let Some(extranonce1) = self.assigned.remove(&id) {
+            self.in_use.remove(&extranonce1);
+        }
+    }
+}
+
+pub(crate) struct Server {
+    pow: Arc<dyn PowEngine>,
+    jobs: Mutex<JobManager>,
+    // the writers of the subscribed sessions
+    sessions: Mutex<HashMap<u64, SessionWriter>>,
+    nonce_tx: Sender<(Byte32, Work, u128)>,
+    extranonce1_size: usize,
+    max_connections: usize,
+    // the open connections, kept until their session threads exit
+    connections: Mutex<Extranonces>,
+    next_session_id: AtomicU64,
+    accepted: AtomicU64,
+    rejected: AtomicU64,
+    progress_bar: ProgressBar,
+}
+
+impl Server {
+    fn dispatch(&self, worker_rx: Receiver<WorkerMessage>) {
+        for message in worker_rx {
+            match message {
+                WorkerMessage::NewWork {
+                    pow_hash,
+                    work,
+                    target,
+                } => self.new_job(pow_hash, work, target),
+                // the external miners keep working on the latest job until a new one is notified
+                WorkerMessage::Stop | WorkerMessage::Start => {}
+            }
+        }
+    }
+
+    fn listen(self: Arc<Self>, listener: TcpListener) {
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(err) => {
+                    debug!("stratum accept error: {}", err);
+                    continue;
+                }
+            };
+            if self.connections.lock().len() >= self.max_connections {
+                debug!(
+                    "stratum connection from {:?} is closed, reached max connections {}",
+                    stream.peer_addr(),
+                    self.max_connections
+                );
+                continue;
+            }
+            if let Err(err) = self.spawn_session(stream) {
+                error!("stratum start session error: {}", err);
+            }
+        }
+    }
+
+    fn spawn_session(self: &Arc<Self>, stream: TcpStream) -> io::Result<()> {
+        stream.set_read_timeout(Some(READ_TIMEOUT))?;
+        stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+        let id = self.next_session_id.fetch_add(1, Ordering::SeqCst);
+        let writer = SessionWriter::spawn(id, stream.try_clone()?)?;
+        let extranonce1 = match self.connections.lock().open(id) {
+            Some(extranonce1) => extranonce1,
+            None => {
+                writer.close();
+                return Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    "all the extranonce1 values are in use",
+                ));
+            }
+        };
+        let extranonce1 = extranonce1.to_be_bytes()[8 - self.extranonce1_size..].to_vec();
+        debug!(
+            "stratum session {} connected from {:?}",
+            id,
+            stream.peer_addr()
+        );
+        let session = Session::new(id, extranonce1, Arc::clone(self), writer.clone());
+        if let Err(err) = thread::Builder::new()
+            .name(format!("Stratum-Session-{id}"))
+            .spawn(move || session.run(stream))
+        {
+            self.connections.lock().close(id);
+            writer.close();
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    fn new_job(&self, pow_hash: Byte32, work: Work, target: U256) {
+        let messages = {
+            let mut jobs = self.jobs.lock();
+            let clean = jobs.add_job(pow_hash, work, target);
+            let job = jobs.latest_job().expect("job added");
+            job_messages(&jobs, job, clean)
+        };
+        // the messages are only queued here, the writer threads of the sessions send them
+        let mut sessions = self.sessions.lock();
+        sessions.retain(|id, writer| match writer.try_send(messages.to_vec()) {
+            Ok(()) => true,
+            Err(err) => {
+                debug!("stratum session {} notify error: {}", id, err);
+                writer.close();
+                false
+            }
+        });
+        self.update_progress(sessions.len());
+    }
+
+    /// Registers the subscribed session, and sends it the latest job.
+    pub(crate) fn subscribe(&self, id: u64, writer: SessionWriter) -> io::Result<()> {
+        let mut sessions = self.sessions.lock();
+        // read the latest job in the sessions lock, so a new job is either read here or notified
+        let messages = {
+            let jobs = self.jobs.lock();
+            jobs.latest_job().map(|job| job_messages(&jobs, job, true))
+        };
+        if let Some(messages) = messages {
+            writer.try_send(messages.to_vec())?;
+        }
+        sessions.insert(id, writer);
+        self.update_progress(sessions.len());
+        Ok(())
+    }
+
+    /// Removes the session whose connection is closed.
+    pub(crate) fn remove_session(&self, id: u64) {
+        let mut sessions = self.sessions.lock();
+        sessions.remove(&id);
+        self.connections.lock().close(id);
+        self.update_progress(sessions.len());
+    }
+
+    /// Checks the share, and sends the nonce to the miner if it solves the block.
+    pub(crate) fn submit(&self, job_id: u64, nonce: u128) -> Result<(), ShareError> {
+        let ret = self.jobs.lock().submit(self.pow.as_ref(), job_id, nonce);
+        match ret {
+            Ok(Share::Block {
+                pow_hash,
+                work,
+                nonce,
+            }) => {
+                if let Err(err) = self.nonce_tx.send((pow_hash, work, nonce)) {
+                    error!("nonce_tx send error {:?}", err);
+                }
+                self.accepted.fetch_add(1, Ordering::SeqCst);
+            }
+            Ok(Share::Accepted) => {
+                self.accepted.fetch_add(1, Ordering::SeqCst);
+            }
+            Err(_) => {
+                self.rejected.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+        self.update_progress(self.sessions.lock().len());
+        ret.map(|_| ())
+    }
+
+    fn update_progress(&self, sessions: usize) {
+        self.progress_bar.set_message(format!(
+            "miners: {:>5} / shares accepted: {:>10} / rejected: {:>10}",
+            sessions,
+            self.accepted.load(Ordering::SeqCst),
+            self.rejected.load(Ordering::SeqCst),
+        ));
+        self.progress_bar.inc(1);
+    }
+}
+
+fn job_messages(jobs: &JobManager, job: &Job, clean: bool) -> [Value; 2] {
+    [
+        notification(
+            "mining.set_target",
+            json!([target_hex(&jobs.share_target(job))]),
+        ),
+        notification(
+            "mining.notify",
+            json!([
+                format!("{:x}", job.id),
+                format!("{:x}", job.pow_hash),
+                job.number(),
+                format!("{:x}", job.parent_hash()),
+                clean
+            ]),
+        ),
+    ]
+}
+
+fn notification(method: &str, params: Value) -> Value {
+    json!({
+        "id": null,
+        "method": method,
+        "params": params,
+    })
+}
+
+/// Queues the messages of a session, which are sent by its writer thread.
+///
+/// The queue is bounded, so a miner reading slowly only stalls its own writer thread, and its
+/// connection is closed once the queue is full.
+#[derive(Clone)]
+pub(crate) struct SessionWriter {
+    queue: Sender<Vec<Value>>,
+    stream: Arc<TcpStream>,
+}
+
+impl SessionWriter {
+    fn spawn(id: u64, stream: TcpStream) -> io::Result<Self> {
+        let (queue, messages_rx) = bounded(MAX_PENDING_MESSAGES);
+        let writer = SessionWriter {
+            queue,
+            stream: Arc::new(stream.try_clone()?),
+        };
+        thread::Builder::new()
+            .name(format!("Stratum-Writer-{id}"))
+            .spawn(move || {
+                let mut stream = BufWriter::new(stream);
+                for messages in messages_rx {
+                    if let Err(err) = write_messages(&mut stream, &messages) {
+                        debug!("stratum session {} write error: {}", id, err);
+                        let _ignore = stream.get_ref().shutdown(Shutdown::Both);
+                        break;
+                    }
+                }
+            })?;
+        Ok(writer)
+    }
+
+    /// Queues the messages, waits if the queue is full.
+    pub(crate) fn send(&self, messages: Vec<Value>) -> io::Result<()> {
+        self.queue
+            .send(messages)
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "session writer stopped"))
+    }
+
+    /// Queues the messages, fails if the queue is full.
+    fn try_send(&self, messages: Vec<Value>) -> io::Result<()> {
+        self.queue.try_send(messages).map_err(|err| match err {
+            TrySendError::Full(_) => {
+                io::Error::new(io::ErrorKind::WouldBlock, "too many pending messages")
+            }
+            TrySendError::Disconnected(_) => {
+                io::Error::new(io::ErrorKind::BrokenPipe, "session writer stopped")
+            }
+        })
+    }
+
+    /// Closes the connection, which stops both the reading and the writing of the session.
+    pub(crate) fn close(&self) {
+        let _ignore = self.stream.shutdown(Shutdown::Both);
+    }
+}
+
+fn write_messages<W: Write>(stream: &mut W, messages: &[Value]) -> io::Result<()> {
+    for message in messages {
+        writeln!(stream, "{message}")?;
+    }
+    stream.flush()
+}
+
+/// Composes the nonce of a share, which is `extranonce1 || extranonce2` in big endian.
+pub(crate) fn compose_nonce(extranonce1: &[u8], extranonce2: &[u8]) -> Option<u128> {
+    if extranonce1.len() + extranonce2.len() != NONCE_SIZE {
+        return None;
+    }
+    let mut nonce = [0u8; NONCE_SIZE];
+    nonce[..extranonce1.len()].copy_from_slice(extranonce1);
+    nonce[extranonce1.len()..].copy_from_slice(extranonce2);
+    Some(u128::from_be_bytes(nonce))
+}
+
+pub(crate) fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    let hex = hex.strip_prefix("0x").unwrap_or(hex);
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    let mut bytes = vec![0u8; hex.len() / 2];
+    faster_hex::hex_decode(hex.as_bytes(), &mut bytes).ok()?;
+    Some(bytes)
+}
+
+// the 32 bytes target in big endian
+pub(crate) fn target_hex(target: &U256) -> String {
+    target
+        .0
+        .iter()
+        .rev()
+        .map(|limb| format!("{limb:016x}"))
+        .collect()
+}
@@ -0,0 +1,143 @@
+use super::{compose_nonce, decode_hex, Server, SessionWriter};
+use ckb_logger::debug;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader};
+use std::net::TcpStream;
+use std::sync::Arc;
+
+type StratumError = (i64, &'static str);
+
+const OTHER_ERROR: StratumError = (20, "Other/Unknown");
+const INVALID_PARAMS: StratumError = (20, "Invalid params");
+const UNAUTHORIZED: StratumError = (24, "Unauthorized worker");
+const NOT_SUBSCRIBED: StratumError = (25, "Not subscribed");
+
+#[derive(Deserialize)]
+struct Request {
+    #[serde(default)]
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: Vec<Value>,
+}
+
+/// A connection of the stratum server.
+pub(crate) struct Session {
+    id: u64,
+    extranonce1: Vec<u8>,
+    server: Arc<Server>,
+    writer: SessionWriter,
+    subscribed: bool,
+    worker: Option<String>,
+}
+
+impl Session {
+    pub(crate) fn new(
+        id: u64,
+        extranonce1: Vec<u8>,
+        server: Arc<Server>,
+        writer: SessionWriter,
+    ) -> Self {
+        Session {
+            id,
+            extranonce1,
+            server,
+            writer,
+            subscribed: false,
+            worker: None,
+        }
+    }
+
+    pub(crate) fn run(mut self, stream: TcpStream) {
+        for line in BufReader::new(stream).lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(err) => {
+                    debug!("stratum session {} read error: {}", self.id, err);
+                    break;
+                }
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            let request: Request = match serde_json::from_str(&line) {
+                Ok(request) => request,
+                Err(err) => {
+                    debug!("stratum session {} invalid request: {}", self.id, err);
+                    break;
+                }
+            };
+
+            let was_subscribed = self.subscribed;
+            let response = match self.handle(&request.method, &request.params) {
+                Ok(result) => json!({"id": request.id, "result": result, "error": null}),
+                Err((code, message)) => {
+                    json!({"id": request.id, "result": null, "error": [code, message, null]})
+                }
+            };
+            let ret = self.writer.send(vec![response]).and_then(|_| {
+                if !was_subscribed && self.subscribed {
+                    self.server.subscribe(self.id, Arc::clone(&self.writer))
+                } else {
+                    Ok(())
+                }
+            });
+            if let Err(err) = ret {
+                debug!("stratum session {} write error: {}", self.id, err);
+                break;
+            }
+        }
+        debug!("stratum session {} disconnected", self.id);
+        self.writer.close();
+        self.server.remove_session(self.id);
+    }
+
+    fn handle(&mut self, method: &str, params: &[Value]) -> Result<Value, StratumError> {
+        match method {
+            "mining.subscribe" => {
+                self.subscribed = true;
+                Ok(json!([
+                    null,
+                    faster_hex::hex_string(&self.extranonce1),
+                    super::NONCE_SIZE - self.extranonce1.len()
+                ]))
+            }
+            "mining.authorize" => {
+                let worker = params
+                    .first()
+                    .and_then(Value::as_str)
+                    .ok_or(INVALID_PARAMS)?;
+                debug!("stratum session {} authorized {}", self.id, worker);
+                self.worker = Some(worker.to_owned());
+                Ok(json!(true))
+            }
+            "mining.submit" => {
+                if !self.subscribed {
+                    return Err(NOT_SUBSCRIBED);
+                }
+                if self.worker.is_none() {
+                    return Err(UNAUTHORIZED);
+                }
+                let job_id = params
+                    .get(1)
+                    .and_then(Value::as_str)
+                    .and_then(|job_id| u64::from_str_radix(job_id, 16).ok())
+                    .ok_or(INVALID_PARAMS)?;
+                let nonce = params
+                    .get(2)
+                    .and_then(Value::as_str)
+                    .and_then(decode_hex)
+                    .and_then(|extranonce2| compose_nonce(&self.extranonce1, &extranonce2))
+                    .ok_or(INVALID_PARAMS)?;
+                self.server
+                    .submit(job_id, nonce)
+                    .map(|_| json!(true))
+                    .map_err(|err| err.code())
+            }
+            // the extranonce of a session never changes
+            "mining.extranonce.subscribe" => Ok(json!(true)),
+            _ => Err(OTHER_ERROR),
+        }
+    }
+}
@@ -0,0 +1,249 @@
+use super::job::{JobManager, Share, ShareError};
+use super::{compose_nonce, decode_hex, spawn_server, start_stratum, target_hex, Extranonces};
+use crate::worker::WorkerMessage;
+use crate::Work;
+use ckb_app_config::StratumConfig;
+use ckb_channel::unbounded;
+use ckb_pow::{DummyPowEngine, EaglesongPowEngine};
+use ckb_types::{
+    h256,
+    packed::{Block, Byte32, Header, RawHeader},
+    prelude::*,
+    U256,
+};
+use indicatif::ProgressBar;
+use serde_json::Value;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn build_work(parent_hash: Byte32, number: u64) -> Work {
+    let raw = RawHeader::new_builder()
+        .parent_hash(parent_hash)
+        .number(number.pack())
+        .build();
+    let block = Block::new_builder()
+        .header(Header::new_builder().raw(raw).build())
+        .build();
+    Work { work_id: 0, block }
+}
+
+#[test]
+fn test_compose_nonce() {
+    assert_eq!(decode_hex("0x0aff"), Some(vec![0x0a, 0xff]));
+    assert_eq!(decode_hex("0af"), None);
+    assert_eq!(decode_hex("zz"), None);
+
+    let nonce = compose_nonce(&[0, 0, 0, 1], &[0; 12]).unwrap();
+    assert_eq!(nonce, 1u128 << 96);
+    assert_eq!(compose_nonce(&[0; 4], &[0; 8]), None);
+
+    assert_eq!(target_hex(&U256::one()), format!("{:064x}", 1));
+}
+
+#[test]
+fn test_jobs() {
+    let mut jobs = JobManager::new(U256::max_value());
+    let parent1 = h256!("0x1").pack();
+    let parent2 = h256!("0x2").pack();
+
+    assert!(jobs.add_job(Byte32::zero(), build_work(parent1.clone(), 1), U256::zero()));
+    let first_job = jobs.latest_job().unwrap().id;
+    // the jobs of the same parent are kept
+    assert!(!jobs.add_job(Byte32::zero(), build_work(parent1, 1), U256::zero()));
+    let job = jobs.latest_job().unwrap();
+    assert_eq!(job.number(), 1);
+    // the share target is easier than the block target
+    assert_eq!(jobs.share_target(job), U256::max_value());
+    let job_id = job.id;
+
+    let pow = EaglesongPowEngine;
+    assert!(matches!(
+        jobs.submit(&pow, first_job, 1),
+        Ok(Share::Accepted)
+    ));
+    assert!(matches!(jobs.submit(&pow, job_id, 1), Ok(Share::Accepted)));
+    assert!(matches!(
+        jobs.submit(&pow, job_id, 1),
+        Err(ShareError::Duplicate)
+    ));
+
+    // the jobs of the former parent are stale
+    assert!(jobs.add_job(Byte32::zero(), build_work(parent2, 2), U256::zero()));
+    assert!(matches!(
+        jobs.submit(&pow, job_id, 2),
+        Err(ShareError::JobNotFound)
+    ));
+
+    let mut jobs = JobManager::new(U256::zero());
+    jobs.add_job(Byte32::zero(), build_work(Byte32::zero(), 1), U256::zero());
+    let job_id = jobs.latest_job().unwrap().id;
+    assert!(matches!(
+        jobs.submit(&pow, job_id, 1),
+        Err(ShareError::LowDifficulty)
+    ));
+    // a share meeting the block target is a block
+    assert!(matches!(
+        jobs.submit(&DummyPowEngine, job_id, 1),
+        Ok(Share::Block { nonce: 1, .. })
+    ));
+}
+
+#[test]
+fn test_stratum_session() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (nonce_tx, nonce_rx) = unbounded();
+    let controller = spawn_server(
+        listener,
+        Arc::new(DummyPowEngine),
+        U256::max_value(),
+        4,
+        8,
+        nonce_tx,
+        ProgressBar::hidden(),
+    );
+
+    let stream = TcpStream::connect(addr).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(10)))
+        .unwrap();
+    let mut writer = stream.try_clone().unwrap();
+    let mut lines = BufReader::new(stream).lines();
+    let mut send = |request: &str| writeln!(writer, "{request}").unwrap();
+    let mut recv = || -> Value { serde_json::from_str(&lines.next().unwrap().unwrap()).unwrap() };
+
+    send(r#"{"id":1,"method":"mining.submit","params":["w","1","00"]}"#);
+    assert_eq!(recv()["error"][0], 25);
+
+    send(r#"{"id":2,"method":"mining.subscribe","params":[]}"#);
+    let response = recv();
+    assert_eq!(response["result"][1], "00000000");
+    assert_eq!(response["result"][2], 12);
+
+    // the job is sent to the subscribed session
+    controller.send_message(WorkerMessage::NewWork {
+        pow_hash: h256!("0x3").pack(),
+        work: build_work(Byte32::zero(), 1),
+        target: U256::zero(),
+    });
+    let set_target = recv();
+    assert_eq!(set_target["method"], "mining.set_target");
+    assert_eq!(set_target["params"][0], target_hex(&U256::max_value()));
+    let notify = recv();
+    assert_eq!(notify["method"], "mining.notify");
+    assert_eq!(notify["params"][0], "1");
+    assert_eq!(notify["params"][2], 1);
+    assert_eq!(notify["params"][4], true);
+
+    let extranonce2 = "00".repeat(12);
+    let submit =
+        format!(r#"{{"id":3,"method":"mining.submit","params":["w","1","{extranonce2}"]}}"#);
+    send(&submit);
+    assert_eq!(recv()["error"][0], 24);
+
+    send(r#"{"id":4,"method":"mining.authorize","params":["w","x"]}"#);
+    assert_eq!(recv()["result"], true);
+    send(&submit);
+    assert_eq!(recv()["result"], true);
+    let (pow_hash, _, nonce) = nonce_rx.recv_timeout(Duration::from_secs(10)).unwrap();
+    assert_eq!(pow_hash, h256!("0x3").pack());
+    assert_eq!(nonce, 0);
+
+    send(&submit);
+    assert_eq!(recv()["error"][0], 22);
+}
+
+#[test]
+fn test_stratum_max_connections() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (nonce_tx, _nonce_rx) = unbounded();
+    let _controller = spawn_server(
+        listener,
+        Arc::new(DummyPowEngine),
+        U256::max_value(),
+        4,
+        1,
+        nonce_tx,
+        ProgressBar::hidden(),
+    );
+
+    let connect = || {
+        let stream = TcpStream::connect(addr).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(10)))
+            .unwrap();
+        let writer = stream.try_clone().unwrap();
+        (writer, BufReader::new(stream).lines())
+    };
+    let (mut writer, mut lines) = connect();
+    writeln!(
+        writer,
+        r#"{{"id":1,"method":"mining.subscribe","params":[]}}"#
+    )
+    .unwrap();
+    assert!(lines.next().unwrap().is_ok());
+
+    // the connection beyond the limit is closed
+    let (_writer, mut lines) = connect();
+    assert!(!matches!(lines.next(), Some(Ok(_))));
+}
+
+#[test]
+fn test_extranonces() {
+    let mut extranonces = Extranonces::new(1);
+    for id in 0..256 {
+        assert_eq!(extranonces.open(id), Some(id));
+    }
+    assert_eq!(extranonces.len(), 256);
+    assert_eq!(extranonces.open(256), None);
+
+    // a value is reused only after its connection is closed
+    extranonces.close(3);
+    extranonces.close(3);
+    assert_eq!(extranonces.open(257), Some(3));
+    assert_eq!(extranonces.open(258), None);
+    extranonces.close(0);
+    extranonces.close(257);
+    assert_eq!(extranonces.open(259), Some(0));
+    assert_eq!(extranonces.open(260), Some(3));
+    assert_eq!(extranonces.len(), 256);
+
+    let mut extranonces = Extranonces::new(8);
+    assert_eq!(extranonces.open(0), Some(0));
+    assert_eq!(extranonces.open(1), Some(1));
+}
+
+#[test]
+fn test_start_stratum_config_errors() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let config = StratumConfig {
+        listen: listener.local_addr().unwrap(),
+        share_target: h256!("0x0"),
+        extranonce1_size: 1,
+        max_connections: 256,
+    };
+    let start = |config: &StratumConfig| {
+        let (nonce_tx, _nonce_rx) = unbounded();
+        start_stratum(
+            Arc::new(DummyPowEngine),
+            config,
+            nonce_tx,
+            ProgressBar::hidden(),
+        )
+    };
+
+    // the address is in use
+    let err = start(&config).err().unwrap();
+    assert!(err.contains("listen"), "{err}");
+
+    // more connections than the extranonce1 values
+    let config = StratumConfig {
+        max_connections: 257,
+        ..config
+    };
+    let err = start(&config).err().unwrap();
+    assert!(err.contains("max_connections"), "{err}");
+}
@@ -1,6 +1,7 @@
 mod dummy;
 mod eaglesong_simple;
 
+use crate::stratum::start_stratum;
 use crate::Work;
 use ckb_app_config::MinerWorkerConfig;
 use ckb_channel::{unbounded, Sender};
@@ -63,12 +64,13 @@ fn nonce_generator(range: Range<u128>) -> impl FnMut() -> u128 {
 
 const PROGRESS_BAR_TEMPLATE: &str = "{prefix:.bold.dim} {spinner:.green} [{elapsed_precise}] {msg}";
 
+/// Starts the worker, returns an error if the worker can't be started with the config.
 pub fn start_worker(
     pow: Arc<dyn PowEngine>,
     config: &MinerWorkerConfig,
     nonce_tx: Sender<(Byte32, Work, u128)>,
     mp: &MultiProgress,
-) -> WorkerController {
+) -> Result<WorkerController, String> {
     match config {
         MinerWorkerConfig::Dummy(config) => {
             if pow.as_any().downcast_ref::<DummyPowEngine>().is_some() {
@@ -87,7 +89,7 @@ pub fn start_worker(
                         worker.run(random, pb);
                     })
                     .expect("Start `Dummy` worker thread failed");
-                WorkerController::new(vec![worker_tx])
+                Ok(WorkerController::new(vec![worker_tx]))
             } else {
                 panic!("incompatible pow engine and worker type");
             }
@@ -125,11 +127,18 @@ pub fn start_worker(
                     })
                     .collect();
 
-                WorkerController::new(worker_txs)
+                Ok(WorkerController::new(worker_txs))
             } else {
                 panic!("incompatible pow engine and worker type");
             }
         }
+        MinerWorkerConfig::Stratum(config) => {
+            // the shares are verified by the pow engine, so it works with any engine
+            let pb = mp.add(ProgressBar::new(100));
+            pb.set_style(ProgressStyle::default_bar().template(PROGRESS_BAR_TEMPLATE));
+            pb.set_prefix("Stratum-Server");
+            start_stratum(pow, config, nonce_tx, pb)
+        }
     }
 }
 
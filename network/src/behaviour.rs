@@ -1,12 +1,15 @@
 use crate::Score;
+use serde::{Deserialize, Serialize};
 
 /// Peers behaviours
 /// we maintain a score to each peer
 /// report peer behaviour will affects peer's score
-///
-/// Currently this feature is disabled, maybe someday we will add it back or totally remove it.
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub enum Behaviour {
+    /// The peer does not send the identify message in time
+    Timeout,
+    /// The peer sends invalid, duplicated or too many items in the identify or discovery protocol
+    Misbehave,
     #[cfg(test)]
     TestGood,
     #[cfg(test)]
@@ -16,12 +19,13 @@ pub enum Behaviour {
 impl Behaviour {
     /// Behaviour score
     pub fn score(self) -> Score {
-        #[cfg(test)]
         match self {
+            Behaviour::Timeout => -10,
+            Behaviour::Misbehave => -20,
+            #[cfg(test)]
             Behaviour::TestGood => 10,
+            #[cfg(test)]
             Behaviour::TestBad => -10,
         }
-        #[cfg(not(test))]
-        0
     }
 }
@@ -16,6 +16,8 @@ mod peer;
 pub mod peer_registry;
 pub mod peer_store;
 mod protocols;
+#[cfg(not(target_family = "wasm"))]
+pub mod proxy;
 mod services;
 
 #[cfg(test)]
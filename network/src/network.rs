@@ -4,8 +4,8 @@ use crate::errors::Error;
 use crate::errors::P2PError;
 use crate::peer_registry::{ConnectionStatus, PeerRegistry};
 use crate::peer_store::{
-    types::{AddrInfo, BannedAddr},
-    PeerStore,
+    types::{AddrInfo, BanCause, BannedAddr},
+    PeerStore, ReportResult,
 };
 use crate::protocols::{
     disconnect_message::DisconnectMessageProtocol,
@@ -19,6 +19,11 @@ use crate::services::{
     dump_peer_store::DumpPeerStoreService, outbound_peer::OutboundPeerService,
     protocol_type_checker::ProtocolTypeCheckerService,
 };
+#[cfg(not(target_family = "wasm"))]
+use crate::{
+    proxy::{ProxyDialer, Socks5Proxy},
+    services::proxy_dial::ProxyDialService,
+};
 use crate::{Behaviour, CKBProtocol, Peer, PeerIndex, ProtocolId, ServiceControl};
 use ckb_app_config::{default_support_all_protocols, NetworkConfig, SupportProtocol};
 use ckb_logger::{debug, error, info, trace, warn};
@@ -90,6 +95,9 @@ pub struct NetworkState {
     pub(crate) required_flags: Flags,
 
     pub(crate) ckb2023: AtomicBool,
+    /// Dial the outbound connections through the proxy
+    #[cfg(not(target_family = "wasm"))]
+    pub(crate) proxy_dialer: Option<ProxyDialer>,
 }
 
 impl NetworkState {
@@ -104,6 +112,7 @@ impl NetworkState {
             .listen_addresses
             .iter()
             .chain(config.public_addresses.iter())
+            .chain(config.onion.external_address.iter())
             .cloned()
             .filter_map(|mut addr| match multiaddr_to_socketaddr(&addr) {
                 Some(socket_addr) if !is_reachable(socket_addr.ip()) => None,
@@ -121,6 +130,7 @@ impl NetworkState {
             config.peer_store_path(),
         ));
         let bootnodes = config.bootnodes();
+        let proxy_dialer = Socks5Proxy::from_config(&config.proxy)?.map(ProxyDialer::new);
 
         let peer_registry = PeerRegistry::new(
             config.max_inbound_peers(),
@@ -144,6 +154,7 @@ impl NetworkState {
             protocols: RwLock::new(Vec::new()),
             required_flags: Flags::SYNC | Flags::DISCOVERY | Flags::RELAY,
             ckb2023: AtomicBool::new(false),
+            proxy_dialer,
         })
     }
 
@@ -214,23 +225,29 @@ impl NetworkState {
         session_id: SessionId,
         behaviour: Behaviour,
     ) {
+        if self.report(session_id, behaviour).is_banned() {
+            if let Err(err) = disconnect_with_message(p2p_control, session_id, "banned") {
+                debug!("Disconnect failed {:?}, error: {:?}", session_id, err);
+            }
+        }
+    }
+
+    /// Report the behaviour of the session to the peer store, the caller should disconnect the
+    /// session when it's banned
+    pub(crate) fn report(&self, session_id: SessionId, behaviour: Behaviour) -> ReportResult {
         if let Some(addr) = self.with_peer_registry(|reg| {
             reg.get_peer(session_id)
                 .filter(|peer| !peer.is_whitelist)
                 .map(|peer| peer.connected_addr.clone())
         }) {
             trace!("Report {:?} because {:?}", addr, behaviour);
-            let report_result = self.peer_store.lock().report(&addr, behaviour);
-            if report_result.is_banned() {
-                if let Err(err) = disconnect_with_message(p2p_control, session_id, "banned") {
-                    debug!("Disconnect failed {:?}, error: {:?}", session_id, err);
-                }
-            }
+            self.peer_store.lock().report(&addr, behaviour)
         } else {
             debug!(
                 "Report {} failure: not found in peer registry or it is on the whitelist",
                 session_id
             );
+            ReportResult::Ok
         }
     }
 
@@ -261,6 +278,7 @@ impl NetworkState {
                     &peer.connected_addr,
                     duration.as_millis() as u64,
                     reason,
+                    BanCause::Misbehave,
                 );
                 if let Err(err) =
                     disconnect_with_message(p2p_control, peer.session_id, message.as_str())
@@ -464,6 +482,19 @@ impl NetworkState {
         }
 
         debug!("Dialing {addr}");
+        #[cfg(not(target_family = "wasm"))]
+        match self.proxy_dialer {
+            Some(ref dialer) if dialer.proxy.should_proxy(&addr) => {
+                dialer.dial(addr.clone(), target)?
+            }
+            None if is_onion(&addr) => {
+                return Err(Error::Dial(format!(
+                    "ignore dialing onion addr {addr} without a proxy"
+                )));
+            }
+            _ => p2p_control.dial(addr.clone(), target)?,
+        }
+        #[cfg(target_family = "wasm")]
         p2p_control.dial(addr.clone(), target)?;
         self.dialing_addrs.write().insert(
             extract_peer_id(&addr).expect("verified addr"),
@@ -498,9 +529,27 @@ impl NetworkState {
         }
     }
 
+    /// Whether the onion addresses can be dialed, they are only dialed through the proxy
+    pub(crate) fn can_dial_onion(&self) -> bool {
+        #[cfg(not(target_family = "wasm"))]
+        {
+            self.proxy_dialer.is_some()
+        }
+        #[cfg(target_family = "wasm")]
+        {
+            false
+        }
+    }
+
     /// this method is intent to check observed addr by dial to self
     pub(crate) fn try_dial_observed_addrs(&self, p2p_control: &ServiceControl) {
         let mut pending_observed_addrs = self.pending_observed_addrs.write();
+        // the observed addrs are the ones of the proxy, and dialing them directly leaks the ip
+        #[cfg(not(target_family = "wasm"))]
+        if self.proxy_dialer.is_some() {
+            pending_observed_addrs.clear();
+            return;
+        }
         if pending_observed_addrs.is_empty() {
             let addrs = self.public_addrs.read();
             if addrs.is_empty() {
@@ -1084,6 +1133,19 @@ impl NetworkService {
             bg_services.push(Box::pin(outbound_peer_service) as Pin<Box<_>>);
         };
 
+        #[cfg(not(target_family = "wasm"))]
+        if let Some(ref dialer) = network_state.proxy_dialer {
+            if let Some(receiver) = dialer.take_receiver() {
+                let proxy_dial_service = ProxyDialService::new(
+                    Arc::clone(&network_state),
+                    p2p_service.control().to_owned().into(),
+                    dialer.proxy.clone(),
+                    receiver,
+                );
+                bg_services.push(Box::pin(proxy_dial_service.start()) as Pin<Box<_>>);
+            }
+        };
+
         #[cfg(feature = "with_dns_seeding")]
         if config.dns_seeding_service_enabled() {
             let dns_seeding_service = crate::services::dns_seeding::DnsSeedingService::new(
@@ -1360,10 +1422,40 @@ impl NetworkController {
     /// Ban an ip
     pub fn ban(&self, address: IpNetwork, ban_until: u64, ban_reason: String) {
         self.disconnect_peers_in_ip_range(address, &ban_reason);
+        self.network_state.peer_store.lock().ban_network(
+            address,
+            ban_until,
+            ban_reason,
+            BanCause::Manual,
+        )
+    }
+
+    /// Get the ban entry which covers the address
+    pub fn get_banned_addr(&self, addr: &Multiaddr) -> Option<BannedAddr> {
         self.network_state
             .peer_store
             .lock()
-            .ban_network(address, ban_until, ban_reason)
+            .ban_list()
+            .get_banned_addr(addr)
+    }
+
+    /// Export the unexpired ban entries
+    pub fn export_ban_list(&self) -> Vec<BannedAddr> {
+        self.network_state.peer_store.lock().export_ban_list()
+    }
+
+    /// Import the ban entries, and disconnect the peers in them, returns the number of the
+    /// imported entries
+    pub fn import_ban_list(&self, banned_addrs: Vec<BannedAddr>) -> usize {
+        let banned_addrs = self
+            .network_state
+            .peer_store
+            .lock()
+            .import_ban_list(banned_addrs);
+        for banned_addr in &banned_addrs {
+            self.disconnect_peers_in_ip_range(banned_addr.address, &banned_addr.ban_reason);
+        }
+        banned_addrs.len()
     }
 
     /// Unban an ip
@@ -1547,3 +1639,9 @@ pub(crate) fn find_type(addr: &Multiaddr) -> TransportType {
     })
     .unwrap_or(TransportType::Tcp)
 }
+
+/// Checks whether the address is an onion address
+pub(crate) fn is_onion(addr: &Multiaddr) -> bool {
+    addr.iter()
+        .any(|proto| matches!(proto, Protocol::Onion3(_)))
+}
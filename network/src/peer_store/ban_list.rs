@@ -1,6 +1,6 @@
 //! Ban list
 use crate::peer_store::types::{ip_to_network, BannedAddr};
-use crate::peer_store::Multiaddr;
+use crate::peer_store::{Multiaddr, MAX_REPORTS};
 use ckb_systemtime::unix_time_as_millis;
 use ipnetwork::IpNetwork;
 use p2p::utils::multiaddr_to_socketaddr;
@@ -31,7 +31,20 @@ impl BanList {
     }
 
     /// Ban address
-    pub fn ban(&mut self, banned_addr: BannedAddr) {
+    ///
+    /// The reported behaviours of the previous ban of the same address are kept.
+    pub fn ban(&mut self, mut banned_addr: BannedAddr) {
+        if let Some(previous) = self.inner.remove(&banned_addr.address) {
+            let mut reports = previous.reports;
+            for report in banned_addr.reports {
+                if !reports.contains(&report) {
+                    reports.push(report);
+                }
+            }
+            let overflow = reports.len().saturating_sub(MAX_REPORTS);
+            reports.drain(..overflow);
+            banned_addr.reports = reports;
+        }
         self.inner.insert(banned_addr.address, banned_addr);
         let (insert_count, _) = self.insert_count.overflowing_add(1);
         self.insert_count = insert_count;
@@ -71,6 +84,18 @@ impl BanList {
             .unwrap_or_default()
     }
 
+    /// Get the ban entry which covers the address
+    pub fn get_banned_addr(&self, addr: &Multiaddr) -> Option<BannedAddr> {
+        let ip = multiaddr_to_socketaddr(addr)?.ip();
+        let now_ms = unix_time_as_millis();
+        self.inner
+            .iter()
+            .find(|(ip_network, banned_addr)| {
+                banned_addr.ban_until.gt(&now_ms) && ip_network.contains(ip)
+            })
+            .map(|(_, banned_addr)| banned_addr.to_owned())
+    }
+
     /// Get banned address list
     pub fn get_banned_addrs(&self) -> Vec<BannedAddr> {
         self.inner.values().map(ToOwned::to_owned).collect()
@@ -32,6 +32,8 @@ pub(crate) const ADDR_TRY_TIMEOUT_MS: u64 = 3 * 24 * 3600 * 1000;
 pub(crate) const DIAL_INTERVAL: u64 = 15 * 1000;
 const ADDR_MAX_RETRIES: u32 = 3;
 const ADDR_MAX_FAILURES: u32 = 10;
+/// The max number of the reported behaviours kept for each address
+pub(crate) const MAX_REPORTS: usize = 32;
 
 /// Alias score
 pub type Score = i32;
@@ -93,11 +95,7 @@ pub(crate) fn base_addr(addr: &Multiaddr) -> Multiaddr {
         .filter_map(|p| {
             if matches!(
                 p,
-                Protocol::Ws
-                    | Protocol::Wss
-                    | Protocol::Memory(_)
-                    | Protocol::Tls(_)
-                    | Protocol::Onion3(_)
+                Protocol::Ws | Protocol::Wss | Protocol::Memory(_) | Protocol::Tls(_)
             ) {
                 None
             } else {
@@ -6,7 +6,7 @@ use crate::{
         addr_manager::AddrManager,
         ban_list::BanList,
         base_addr,
-        types::{ip_to_network, AddrInfo, BannedAddr, PeerInfo},
+        types::{ip_to_network, AddrInfo, BanCause, BannedAddr, BehaviourReport, PeerInfo},
         Behaviour, Multiaddr, PeerScoreConfig, ReportResult, Status, ADDR_COUNT_LIMIT,
         ADDR_TIMEOUT_MS, ADDR_TRY_TIMEOUT_MS, DIAL_INTERVAL,
     },
@@ -130,13 +130,13 @@ impl PeerStore {
     /// Report peer behaviours
     pub fn report(&mut self, addr: &Multiaddr, behaviour: Behaviour) -> ReportResult {
         if let Some(peer_addr) = self.addr_manager.get_mut(addr) {
-            let score = peer_addr.score.saturating_add(behaviour.score());
-            peer_addr.score = score;
+            let score = peer_addr.report(behaviour, ckb_systemtime::unix_time_as_millis());
             if score < self.score_config.ban_score {
                 self.ban_addr(
                     addr,
                     self.score_config.ban_timeout_ms,
                     format!("report behaviour {behaviour:?}"),
+                    BanCause::Behaviour(behaviour),
                 );
                 return ReportResult::Banned;
             }
@@ -234,22 +234,52 @@ impl PeerStore {
         self.addr_manager.fetch_random(count, filter)
     }
 
-    /// Ban an addr
-    pub(crate) fn ban_addr(&mut self, addr: &Multiaddr, timeout_ms: u64, ban_reason: String) {
-        if let Some(addr) = multiaddr_to_socketaddr(addr) {
-            let network = ip_to_network(addr.ip());
-            self.ban_network(network, timeout_ms, ban_reason)
+    /// Ban an addr, the reported behaviours of the addr are kept in the ban list
+    pub(crate) fn ban_addr(
+        &mut self,
+        addr: &Multiaddr,
+        timeout_ms: u64,
+        ban_reason: String,
+        cause: BanCause,
+    ) {
+        if let Some(socket_addr) = multiaddr_to_socketaddr(addr) {
+            let network = ip_to_network(socket_addr.ip());
+            let reports = self
+                .addr_manager
+                .get(addr)
+                .map(|info| info.reports.clone())
+                .unwrap_or_default();
+            self.ban_network_with_reports(network, timeout_ms, ban_reason, cause, reports)
         }
         self.addr_manager.remove(addr);
     }
 
-    pub(crate) fn ban_network(&mut self, network: IpNetwork, timeout_ms: u64, ban_reason: String) {
+    pub(crate) fn ban_network(
+        &mut self,
+        network: IpNetwork,
+        timeout_ms: u64,
+        ban_reason: String,
+        cause: BanCause,
+    ) {
+        self.ban_network_with_reports(network, timeout_ms, ban_reason, cause, Vec::new())
+    }
+
+    fn ban_network_with_reports(
+        &mut self,
+        network: IpNetwork,
+        timeout_ms: u64,
+        ban_reason: String,
+        cause: BanCause,
+        reports: Vec<BehaviourReport>,
+    ) {
         let now_ms = ckb_systemtime::unix_time_as_millis();
         let ban_addr = BannedAddr {
             address: network,
             ban_until: now_ms + timeout_ms,
             created_at: now_ms,
             ban_reason,
+            cause,
+            reports,
         };
         self.mut_ban_list().ban(ban_addr);
     }
@@ -269,6 +299,31 @@ impl PeerStore {
         &mut self.ban_list
     }
 
+    /// Export the unexpired ban entries, which can be imported by other nodes
+    pub fn export_ban_list(&self) -> Vec<BannedAddr> {
+        let now_ms = ckb_systemtime::unix_time_as_millis();
+        self.ban_list()
+            .get_banned_addrs()
+            .into_iter()
+            .filter(|banned_addr| banned_addr.ban_until > now_ms)
+            .collect()
+    }
+
+    /// Import the ban entries exported by `export_ban_list`, returns the imported entries,
+    /// the expired ones are skipped
+    pub fn import_ban_list(&mut self, banned_addrs: Vec<BannedAddr>) -> Vec<BannedAddr> {
+        let now_ms = ckb_systemtime::unix_time_as_millis();
+        let banned_addrs: Vec<BannedAddr> = banned_addrs
+            .into_iter()
+            .filter(|banned_addr| banned_addr.ban_until > now_ms)
+            .collect();
+        let ban_list = self.mut_ban_list();
+        for banned_addr in &banned_addrs {
+            ban_list.ban(banned_addr.clone());
+        }
+        banned_addrs
+    }
+
     /// Clear ban list
     pub fn clear_ban_list(&mut self) {
         std::mem::take(&mut self.ban_list);
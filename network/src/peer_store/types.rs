@@ -1,7 +1,8 @@
 //! Type used on peer store
 use crate::{
     peer_store::{
-        base_addr, Score, SessionType, ADDR_MAX_FAILURES, ADDR_MAX_RETRIES, ADDR_TIMEOUT_MS,
+        base_addr, Behaviour, Score, SessionType, ADDR_MAX_FAILURES, ADDR_MAX_RETRIES,
+        ADDR_TIMEOUT_MS, MAX_REPORTS,
     },
     Flags,
 };
@@ -54,6 +55,9 @@ pub struct AddrInfo {
     /// Flags
     #[serde(default = "default_flags")]
     pub flags: u64,
+    /// The reported behaviours, the oldest first
+    #[serde(default)]
+    pub reports: Vec<BehaviourReport>,
 }
 
 fn default_flags() -> u64 {
@@ -72,6 +76,7 @@ impl AddrInfo {
             attempts_count: 0,
             random_id_pos: 0,
             flags,
+            reports: Vec::new(),
         }
     }
 
@@ -121,6 +126,46 @@ impl AddrInfo {
     pub fn flags(&mut self, flags: Flags) {
         self.flags = flags.bits();
     }
+
+    /// Apply the reported behaviour to the score and record it, returns the new score
+    pub fn report(&mut self, behaviour: Behaviour, reported_at: u64) -> Score {
+        self.score = self.score.saturating_add(behaviour.score());
+        if self.reports.len() >= MAX_REPORTS {
+            self.reports.remove(0);
+        }
+        self.reports.push(BehaviourReport {
+            behaviour,
+            score: self.score,
+            reported_at,
+        });
+        self.score
+    }
+}
+
+/// Reported behaviour of an address
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct BehaviourReport {
+    /// The reported behaviour
+    pub behaviour: Behaviour,
+    /// The score after the report
+    pub score: Score,
+    /// Report time
+    pub reported_at: u64,
+}
+
+/// Ban cause
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum BanCause {
+    /// The reported behaviour dropped the score below the ban score
+    Behaviour(Behaviour),
+    /// The peer misbehaved in the protocols
+    Misbehave,
+    /// Banned manually
+    Manual,
+    /// The ban is saved before the causes are recorded
+    #[default]
+    Unknown,
 }
 
 /// Banned addr info
@@ -134,6 +179,12 @@ pub struct BannedAddr {
     pub ban_reason: String,
     /// Ban time
     pub created_at: u64,
+    /// Ban cause
+    #[serde(default)]
+    pub cause: BanCause,
+    /// The reported behaviours of the address before the ban, the oldest first
+    #[serde(default)]
+    pub reports: Vec<BehaviourReport>,
 }
 
 /// Convert multiaddr to IpNetwork
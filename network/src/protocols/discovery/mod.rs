@@ -21,7 +21,7 @@ use self::{
     protocol::{decode, encode},
     state::RemoteAddress,
 };
-use crate::{Flags, NetworkState, ProtocolId};
+use crate::{Behaviour, Flags, NetworkState, ProtocolId};
 
 mod addr;
 pub(crate) mod protocol;
@@ -361,8 +361,7 @@ impl AddressManager for DiscoveryAddressManager {
             "DiscoveryProtocol detects abnormal behavior, session: {:?}, behavior: {:?}",
             session, behavior
         );
-
-        // FIXME:
+        self.network_state.report(session.id, Behaviour::Misbehave);
         MisbehaveResult::Disconnect
     }
 
@@ -17,7 +17,10 @@ use p2p::{
 
 mod protocol;
 
-use crate::{peer_store::required_flags_filter, NetworkState, PeerIdentifyInfo, SupportProtocols};
+use crate::{
+    network::is_onion, peer_store::required_flags_filter, Behaviour, NetworkState,
+    PeerIdentifyInfo, SupportProtocols,
+};
 use ckb_types::{packed, prelude::*};
 
 use protocol::IdentifyMessage;
@@ -228,7 +231,7 @@ impl<T: Callback> ServiceProtocol for IdentifyProtocol<T> {
             .filter(|addr| {
                 multiaddr_to_socketaddr(addr)
                     .map(|socket_addr| !self.global_ip_only || is_reachable(socket_addr.ip()))
-                    .unwrap_or(false)
+                    .unwrap_or_else(|| is_onion(addr))
             })
             .take(MAX_ADDRS)
             .cloned()
@@ -573,6 +576,11 @@ impl Callback for IdentifyCallback {
             "IdentifyProtocol detects abnormal behavior, session: {:?}, reason: {:?}",
             session, reason
         );
+        let behaviour = match reason {
+            Misbehavior::Timeout => Behaviour::Timeout,
+            _ => Behaviour::Misbehave,
+        };
+        self.network_state.report(session.id, behaviour);
         MisbehaveResult::Disconnect
     }
 }
@@ -638,5 +646,9 @@ bitflags::bitflags! {
         const LIGHT_CLIENT = 0b10000;
         /// Client-side block filter protocol can provide BlockFilter download service
         const BLOCK_FILTER = 0b100000;
+        /// Pruned node, which only provides the download service of recent blocks
+        const LIMITED = 0b1000000;
+        /// Relay protocol accepts `RelayTransactionPackage`, which relays low fee parents together with the children paying for them
+        const PACKAGE_RELAY = 0b10000000;
     }
 }
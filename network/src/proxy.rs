@@ -0,0 +1,305 @@
+//! SOCKS5 proxy for the outbound connections
+//!
+//! The outbound connections can be dialed through a SOCKS5 proxy, such as the one of a Tor client,
+//! to hide the IP address of the node. The onion addresses can only be dialed through the proxy.
+use crate::errors::Error;
+use crate::network::{find_type, is_onion, TransportType};
+use ckb_app_config::ProxyConfig;
+use p2p::{
+    multiaddr::{Multiaddr, Protocol},
+    service::TargetProtocol,
+};
+use std::{
+    io,
+    net::{IpAddr, SocketAddr},
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+    sync::mpsc,
+};
+
+const DEFAULT_PROXY_PORT: u16 = 1080;
+// Tor circuits may take a while to build
+const PROXY_CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+const SOCKS5_VERSION: u8 = 0x05;
+const METHOD_NO_AUTH: u8 = 0x00;
+const METHOD_USER_PASS: u8 = 0x02;
+const USER_PASS_VERSION: u8 = 0x01;
+const CMD_CONNECT: u8 = 0x01;
+const ATYP_IPV4: u8 = 0x01;
+const ATYP_DOMAIN: u8 = 0x03;
+const ATYP_IPV6: u8 = 0x04;
+
+/// The destination in the SOCKS5 connect request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum TargetAddr {
+    Ip(IpAddr, u16),
+    Domain(String, u16),
+}
+
+impl TargetAddr {
+    /// Gets the destination of a tcp address, the onion address is resolved by the proxy.
+    ///
+    /// Returns `None` if the address is neither a tcp address nor an onion address.
+    pub(crate) fn from_multiaddr(addr: &Multiaddr) -> Option<Self> {
+        if find_type(addr) != TransportType::Tcp {
+            return None;
+        }
+        let mut iter = addr.iter();
+        match iter.next()? {
+            proto @ Protocol::Onion3(_) => {
+                // formatted as `/onion3/<service-id>:<port>`
+                let proto = proto.to_string();
+                let (service_id, port) = proto.strip_prefix("/onion3/")?.split_once(':')?;
+                Some(TargetAddr::Domain(
+                    format!("{service_id}.onion"),
+                    port.parse().ok()?,
+                ))
+            }
+            Protocol::Ip4(ip) => match iter.next()? {
+                Protocol::Tcp(port) => Some(TargetAddr::Ip(ip.into(), port)),
+                _ => None,
+            },
+            Protocol::Ip6(ip) => match iter.next()? {
+                Protocol::Tcp(port) => Some(TargetAddr::Ip(ip.into(), port)),
+                _ => None,
+            },
+            Protocol::Dns4(host) | Protocol::Dns6(host) => match iter.next()? {
+                Protocol::Tcp(port) => Some(TargetAddr::Domain(host.to_string(), port)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn encode(&self, buf: &mut Vec<u8>) -> io::Result<()> {
+        let port = match self {
+            TargetAddr::Ip(IpAddr::V4(ip), port) => {
+                buf.push(ATYP_IPV4);
+                buf.extend_from_slice(&ip.octets());
+                port
+            }
+            TargetAddr::Ip(IpAddr::V6(ip), port) => {
+                buf.push(ATYP_IPV6);
+                buf.extend_from_slice(&ip.octets());
+                port
+            }
+            TargetAddr::Domain(domain, port) => {
+                let len = u8::try_from(domain.len())
+                    .map_err(|_| invalid_input("domain name is too long"))?;
+                buf.push(ATYP_DOMAIN);
+                buf.push(len);
+                buf.extend_from_slice(domain.as_bytes());
+                port
+            }
+        };
+        buf.extend_from_slice(&port.to_be_bytes());
+        Ok(())
+    }
+}
+
+/// SOCKS5 proxy client
+#[derive(Debug, Clone)]
+pub struct Socks5Proxy {
+    server: SocketAddr,
+    auth: Option<(String, String)>,
+    onion_only: bool,
+}
+
+impl Socks5Proxy {
+    /// Init from config, returns `None` if the proxy is not configured.
+    pub fn from_config(config: &ProxyConfig) -> Result<Option<Self>, Error> {
+        let url = match config.url {
+            Some(ref url) => url,
+            None => return Ok(None),
+        };
+        if !matches!(url.scheme(), "socks5" | "socks5h") {
+            return Err(Error::Io(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "unsupported proxy scheme {}, only socks5 is supported",
+                    url.scheme()
+                ),
+            )));
+        }
+        let server = url
+            .socket_addrs(|| Some(DEFAULT_PROXY_PORT))?
+            .into_iter()
+            .next()
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("can't resolve the proxy address {url}"),
+                )
+            })?;
+        let auth = if url.username().is_empty() {
+            None
+        } else {
+            Some((
+                url.username().to_owned(),
+                url.password().unwrap_or_default().to_owned(),
+            ))
+        };
+        Ok(Some(Socks5Proxy {
+            server,
+            auth,
+            onion_only: config.onion_only,
+        }))
+    }
+
+    /// Checks whether the address should be dialed through the proxy
+    pub(crate) fn should_proxy(&self, addr: &Multiaddr) -> bool {
+        !self.onion_only || is_onion(addr)
+    }
+
+    /// Connects to the address through the proxy
+    pub(crate) async fn connect(&self, addr: &Multiaddr) -> io::Result<TcpStream> {
+        let target = TargetAddr::from_multiaddr(addr).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("can't dial {addr} through the proxy"),
+            )
+        })?;
+        tokio::time::timeout(PROXY_CONNECT_TIMEOUT, self.handshake(&target))
+            .await
+            .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "proxy connect timeout"))?
+    }
+
+    async fn handshake(&self, target: &TargetAddr) -> io::Result<TcpStream> {
+        let mut stream = TcpStream::connect(self.server).await?;
+
+        // method selection, RFC 1928
+        let methods: &[u8] = if self.auth.is_some() {
+            &[METHOD_NO_AUTH, METHOD_USER_PASS]
+        } else {
+            &[METHOD_NO_AUTH]
+        };
+        let mut request = vec![SOCKS5_VERSION, methods.len() as u8];
+        request.extend_from_slice(methods);
+        stream.write_all(&request).await?;
+
+        let mut reply = [0u8; 2];
+        stream.read_exact(&mut reply).await?;
+        if reply[0] != SOCKS5_VERSION {
+            return Err(invalid_data("invalid proxy version"));
+        }
+        match (reply[1], self.auth.as_ref()) {
+            (METHOD_NO_AUTH, _) => {}
+            (METHOD_USER_PASS, Some((username, password))) => {
+                // username/password authentication, RFC 1929
+                let username_len = u8::try_from(username.len())
+                    .map_err(|_| invalid_input("proxy username is too long"))?;
+                let password_len = u8::try_from(password.len())
+                    .map_err(|_| invalid_input("proxy password is too long"))?;
+                let mut request = vec![USER_PASS_VERSION, username_len];
+                request.extend_from_slice(username.as_bytes());
+                request.push(password_len);
+                request.extend_from_slice(password.as_bytes());
+                stream.write_all(&request).await?;
+
+                let mut reply = [0u8; 2];
+                stream.read_exact(&mut reply).await?;
+                if reply[1] != 0 {
+                    return Err(io::Error::new(
+                        io::ErrorKind::PermissionDenied,
+                        "proxy authentication failed",
+                    ));
+                }
+            }
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    "no acceptable proxy authentication method",
+                ));
+            }
+        }
+
+        // connect request
+        let mut request = vec![SOCKS5_VERSION, CMD_CONNECT, 0x00];
+        target.encode(&mut request)?;
+        stream.write_all(&request).await?;
+
+        let mut reply = [0u8; 4];
+        stream.read_exact(&mut reply).await?;
+        if reply[0] != SOCKS5_VERSION {
+            return Err(invalid_data("invalid proxy version"));
+        }
+        if reply[1] != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::ConnectionRefused,
+                format!("proxy connect failed: {}", reply_message(reply[1])),
+            ));
+        }
+        // skip the bound address
+        let bound_len = match reply[3] {
+            ATYP_IPV4 => 4,
+            ATYP_IPV6 => 16,
+            ATYP_DOMAIN => stream.read_u8().await? as usize,
+            _ => return Err(invalid_data("invalid proxy address type")),
+        };
+        let mut bound = vec![0u8; bound_len + 2];
+        stream.read_exact(&mut bound).await?;
+
+        Ok(stream)
+    }
+}
+
+fn reply_message(rep: u8) -> &'static str {
+    match rep {
+        0x01 => "general failure",
+        0x02 => "connection not allowed by ruleset",
+        0x03 => "network unreachable",
+        0x04 => "host unreachable",
+        0x05 => "connection refused",
+        0x06 => "TTL expired",
+        0x07 => "command not supported",
+        0x08 => "address type not supported",
+        _ => "unknown error",
+    }
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_owned())
+}
+
+fn invalid_input(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg.to_owned())
+}
+
+/// The handle to dial the addresses through the proxy
+///
+/// The addresses are sent to the `ProxyDialService`, which connects to them through the proxy.
+pub(crate) struct ProxyDialer {
+    pub(crate) proxy: Socks5Proxy,
+    sender: mpsc::UnboundedSender<(Multiaddr, TargetProtocol)>,
+    receiver: ckb_util::Mutex<Option<mpsc::UnboundedReceiver<(Multiaddr, TargetProtocol)>>>,
+}
+
+impl ProxyDialer {
+    pub(crate) fn new(proxy: Socks5Proxy) -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        ProxyDialer {
+            proxy,
+            sender,
+            receiver: ckb_util::Mutex::new(Some(receiver)),
+        }
+    }
+
+    pub(crate) fn dial(&self, addr: Multiaddr, target: TargetProtocol) -> Result<(), Error> {
+        if TargetAddr::from_multiaddr(&addr).is_none() {
+            return Err(Error::Dial(format!("can't dial {addr} through the proxy")));
+        }
+        self.sender
+            .send((addr, target))
+            .map_err(|err| Error::Dial(format!("proxy dial service is closed: {}", (err.0).0)))
+    }
+
+    pub(crate) fn take_receiver(
+        &self,
+    ) -> Option<mpsc::UnboundedReceiver<(Multiaddr, TargetProtocol)>> {
+        self.receiver.lock().take()
+    }
+}
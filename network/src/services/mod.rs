@@ -3,3 +3,5 @@ pub(crate) mod dns_seeding;
 pub(crate) mod dump_peer_store;
 pub(crate) mod outbound_peer;
 pub(crate) mod protocol_type_checker;
+#[cfg(not(target_family = "wasm"))]
+pub(crate) mod proxy_dial;
@@ -1,5 +1,5 @@
 use crate::{
-    network::TransportType,
+    network::{is_onion, TransportType},
     peer_store::{types::AddrInfo, PeerStore},
     NetworkState,
 };
@@ -55,8 +55,9 @@ impl OutboundPeerService {
 
     fn dial_feeler(&mut self) {
         let now_ms = unix_time_as_millis();
+        let can_dial_onion = self.network_state.can_dial_onion();
         let filter = |peer_addr: &AddrInfo| match self.transport_type {
-            TransportType::Tcp => true,
+            TransportType::Tcp => can_dial_onion || !is_onion(&peer_addr.addr),
             TransportType::Ws => peer_addr
                 .addr
                 .iter()
@@ -108,8 +109,9 @@ impl OutboundPeerService {
 
         let target = &self.network_state.required_flags;
 
+        let can_dial_onion = self.network_state.can_dial_onion();
         let filter = |peer_addr: &AddrInfo| match self.transport_type {
-            TransportType::Tcp => true,
+            TransportType::Tcp => can_dial_onion || !is_onion(&peer_addr.addr),
             TransportType::Ws => peer_addr
                 .addr
                 .iter()
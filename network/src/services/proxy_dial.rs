@@ -0,0 +1,63 @@
+use crate::{proxy::Socks5Proxy, NetworkState, ServiceControl};
+use ckb_logger::debug;
+use p2p::{
+    multiaddr::Multiaddr,
+    service::{RawSessionInfo, TargetProtocol},
+};
+use std::sync::Arc;
+use tokio::sync::mpsc::UnboundedReceiver;
+
+/// Dial the addresses through the SOCKS5 proxy
+///
+/// The connected streams are handed over to tentacle as the outbound raw sessions, which open
+/// the protocols targeted by the dials.
+pub(crate) struct ProxyDialService {
+    network_state: Arc<NetworkState>,
+    p2p_control: ServiceControl,
+    proxy: Arc<Socks5Proxy>,
+    receiver: UnboundedReceiver<(Multiaddr, TargetProtocol)>,
+}
+
+impl ProxyDialService {
+    pub(crate) fn new(
+        network_state: Arc<NetworkState>,
+        p2p_control: ServiceControl,
+        proxy: Socks5Proxy,
+        receiver: UnboundedReceiver<(Multiaddr, TargetProtocol)>,
+    ) -> Self {
+        ProxyDialService {
+            network_state,
+            p2p_control,
+            proxy: Arc::new(proxy),
+            receiver,
+        }
+    }
+
+    pub(crate) async fn start(mut self) {
+        // The sender is held by the network state, so the receiver never closes while the
+        // service is alive
+        while let Some((addr, target)) = self.receiver.recv().await {
+            let network_state = Arc::clone(&self.network_state);
+            let p2p_control = self.p2p_control.clone();
+            let proxy = Arc::clone(&self.proxy);
+            p2p::runtime::spawn(async move {
+                match proxy.connect(&addr).await {
+                    Ok(stream) => {
+                        if let Err(err) = p2p_control
+                            .raw_session(stream, RawSessionInfo::outbound(addr.clone(), target))
+                        {
+                            debug!("Open the proxied session to {} failed: {:?}", addr, err);
+                            network_state.dial_failed(&addr);
+                        }
+                    }
+                    Err(err) => {
+                        debug!("Dial {} through the proxy failed: {}", addr, err);
+                        network_state.dial_failed(&addr);
+                    }
+                }
+            });
+        }
+        // keep pending like the other background services
+        futures::future::pending::<()>().await
+    }
+}
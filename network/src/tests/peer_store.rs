@@ -3,8 +3,9 @@ use crate::{
     extract_peer_id,
     multiaddr::Multiaddr,
     peer_store::{
-        ban_list::CLEAR_INTERVAL_COUNTER, types::multiaddr_to_ip_network, PeerStore, Status,
-        ADDR_COUNT_LIMIT, ADDR_TRY_TIMEOUT_MS,
+        ban_list::CLEAR_INTERVAL_COUNTER,
+        types::{multiaddr_to_ip_network, BanCause, BannedAddr},
+        PeerStore, Status, ADDR_COUNT_LIMIT, ADDR_TRY_TIMEOUT_MS,
     },
     Behaviour, Flags, PeerId, SessionType,
 };
@@ -71,6 +72,70 @@ fn test_report() {
     assert!(peer_store.addr_manager().get(&addr).is_none())
 }
 
+#[test]
+fn test_report_history() {
+    let mut peer_store: PeerStore = Default::default();
+    let addr = random_addr_v6();
+    peer_store
+        .add_addr(addr.clone(), Flags::COMPATIBILITY)
+        .unwrap();
+    for _ in 0..6 {
+        assert!(peer_store.report(&addr, Behaviour::TestBad).is_ok());
+    }
+    let reports = &peer_store.addr_manager().get(&addr).unwrap().reports;
+    assert_eq!(reports.len(), 6);
+    assert_eq!(reports.last().unwrap().score, 40);
+
+    // the reports are moved to the ban list
+    assert!(peer_store.report(&addr, Behaviour::TestBad).is_banned());
+    let banned_addr = peer_store.ban_list().get_banned_addr(&addr).unwrap();
+    assert_eq!(banned_addr.cause, BanCause::Behaviour(Behaviour::TestBad));
+    assert_eq!(
+        banned_addr
+            .reports
+            .iter()
+            .map(|report| report.score)
+            .collect::<Vec<_>>(),
+        vec![90, 80, 70, 60, 50, 40, 30]
+    );
+
+    // ban again keeps the history
+    peer_store.ban_addr(&addr, 10_000, "no reason".into(), BanCause::Manual);
+    let banned_addr = peer_store.ban_list().get_banned_addr(&addr).unwrap();
+    assert_eq!(banned_addr.cause, BanCause::Manual);
+    assert_eq!(banned_addr.reports.len(), 7);
+}
+
+#[test]
+fn test_report_misbehave() {
+    let mut peer_store: PeerStore = Default::default();
+    let addr = random_addr_v6();
+    peer_store
+        .add_addr(addr.clone(), Flags::COMPATIBILITY)
+        .unwrap();
+    assert!(peer_store.report(&addr, Behaviour::Timeout).is_ok());
+    for _ in 0..2 {
+        assert!(peer_store.report(&addr, Behaviour::Misbehave).is_ok());
+    }
+    let reports = &peer_store.addr_manager().get(&addr).unwrap().reports;
+    assert_eq!(
+        reports
+            .iter()
+            .map(|report| (report.behaviour, report.score))
+            .collect::<Vec<_>>(),
+        vec![
+            (Behaviour::Timeout, 90),
+            (Behaviour::Misbehave, 70),
+            (Behaviour::Misbehave, 50)
+        ]
+    );
+
+    assert!(peer_store.report(&addr, Behaviour::Misbehave).is_banned());
+    let banned_addr = peer_store.ban_list().get_banned_addr(&addr).unwrap();
+    assert_eq!(banned_addr.cause, BanCause::Behaviour(Behaviour::Misbehave));
+    assert_eq!(banned_addr.reports.len(), 4);
+}
+
 #[test]
 fn test_update_status() {
     let mut peer_store: PeerStore = Default::default();
@@ -90,7 +155,7 @@ fn test_ban_peer() {
     let mut peer_store: PeerStore = Default::default();
     let addr = random_addr();
     peer_store.add_connected_peer(addr.clone(), SessionType::Inbound);
-    peer_store.ban_addr(&addr, 10_000, "no reason".into());
+    peer_store.ban_addr(&addr, 10_000, "no reason".into(), BanCause::Manual);
     assert!(peer_store.is_addr_banned(&addr));
     peer_store
         .mut_ban_list()
@@ -110,14 +175,14 @@ fn test_ban_peer() {
                 break Multiaddr::from(addr);
             }
         };
-        peer_store.ban_addr(&addr, 10_000, "no reason".into());
+        peer_store.ban_addr(&addr, 10_000, "no reason".into(), BanCause::Manual);
     }
 
     _faketime_guard.set_faketime(30_000);
 
     // Cleanup will be performed every 1024 inserts
     let addr = random_addr_v6();
-    peer_store.ban_addr(&addr, 10_000, "no reason".into());
+    peer_store.ban_addr(&addr, 10_000, "no reason".into(), BanCause::Manual);
     assert_eq!(peer_store.ban_list().count(), 1)
 }
 
@@ -145,7 +210,7 @@ fn test_attempt_ban() {
             .len(),
         1
     );
-    peer_store.ban_addr(&addr, 10_000, "no reason".into());
+    peer_store.ban_addr(&addr, 10_000, "no reason".into(), BanCause::Manual);
     assert_eq!(
         peer_store
             .fetch_addrs_to_attempt(2, Flags::COMPATIBILITY, |_| true)
@@ -621,3 +686,47 @@ fn test_support_dns_store() {
     assert_eq!(peer_store.fetch_addrs_to_feeler(2, |_| true).len(), 1);
     assert_eq!(peer_store.fetch_addrs_to_feeler(1, |_| true)[0].addr, addr);
 }
+
+#[test]
+fn test_ban_list_import_export() {
+    let now_ms = ckb_systemtime::unix_time_as_millis();
+    let mut peer_store = PeerStore::default();
+    let ban1 = BannedAddr {
+        address: multiaddr_to_ip_network(&"/ip4/127.0.0.1/tcp/42".parse().unwrap()).unwrap(),
+        ban_until: now_ms + 10_000,
+        ban_reason: "test1".into(),
+        created_at: now_ms,
+        cause: BanCause::Misbehave,
+        reports: Vec::new(),
+    };
+    let expired = BannedAddr {
+        address: multiaddr_to_ip_network(&"/ip4/127.0.0.2/tcp/42".parse().unwrap()).unwrap(),
+        ban_until: now_ms.saturating_sub(1),
+        ban_reason: "test2".into(),
+        created_at: now_ms.saturating_sub(10_000),
+        cause: BanCause::Manual,
+        reports: Vec::new(),
+    };
+    peer_store.mut_ban_list().ban(ban1.clone());
+    peer_store.mut_ban_list().ban(expired.clone());
+
+    // the expired entries are skipped
+    assert_eq!(vec![ban1.clone()], peer_store.export_ban_list());
+    let mut peer_store2 = PeerStore::default();
+    assert_eq!(
+        vec![ban1.clone()],
+        peer_store2.import_ban_list(vec![ban1.clone(), expired])
+    );
+    assert_eq!(vec![ban1], peer_store2.ban_list().get_banned_addrs());
+
+    // the entries saved before the causes are recorded
+    let legacy: Vec<BannedAddr> = serde_json::from_str(&format!(
+        r#"[{{"address":"192.168.0.2/32","ban_until":{},"ban_reason":"test","created_at":0}}]"#,
+        now_ms + 10_000
+    ))
+    .unwrap();
+    let banned_addrs = peer_store2.import_ban_list(legacy);
+    assert_eq!(1, banned_addrs.len());
+    assert_eq!(BanCause::Unknown, banned_addrs[0].cause);
+    assert_eq!(2, peer_store2.ban_list().count());
+}
@@ -1,7 +1,7 @@
 use crate::{
     multiaddr::Multiaddr,
     peer_store::{
-        types::{multiaddr_to_ip_network, AddrInfo, BannedAddr},
+        types::{multiaddr_to_ip_network, AddrInfo, BanCause, BannedAddr},
         PeerStore,
     },
     PeerId,
@@ -45,18 +45,24 @@ fn test_peer_store_persistent() {
         ban_until: now_ms + 10_000,
         ban_reason: "test1".into(),
         created_at: now_ms,
+        cause: BanCause::Manual,
+        reports: Vec::new(),
     };
     let ban2 = BannedAddr {
         address: addr4,
         ban_until: now_ms + 20_000,
         ban_reason: "test2".into(),
         created_at: now_ms + 1,
+        cause: BanCause::Manual,
+        reports: Vec::new(),
     };
     let ban3 = BannedAddr {
         address: addr5,
         ban_until: now_ms + 30_000,
         ban_reason: "test3".into(),
         created_at: now_ms + 2,
+        cause: BanCause::Manual,
+        reports: Vec::new(),
     };
     ban_list.ban(ban1.clone());
     ban_list.ban(ban2.clone());
@@ -178,6 +184,8 @@ fn test_peer_store_dump_with_broken_tmp_file_should_be_ok() {
         ban_until: now_ms + 10_000,
         ban_reason: "test".into(),
         created_at: now_ms,
+        cause: BanCause::Manual,
+        reports: Vec::new(),
     });
     peer_store.dump_to_dir(dir.as_ref()).unwrap();
 
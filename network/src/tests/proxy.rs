@@ -0,0 +1,174 @@
+use crate::{
+    multiaddr::Multiaddr,
+    peer_store::PeerStore,
+    proxy::{Socks5Proxy, TargetAddr},
+    Flags,
+};
+use ckb_app_config::ProxyConfig;
+use std::net::{IpAddr, Ipv4Addr};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+const ONION_SERVICE_ID: &str = "vww6ybal4bd7szmgncyruucpgfkqahzddi37ktceo3ah7ngmcopnpyyd";
+
+fn onion_addr() -> Multiaddr {
+    format!(
+        "/onion3/{}:8115/p2p/{}",
+        ONION_SERVICE_ID,
+        crate::PeerId::random().to_base58()
+    )
+    .parse()
+    .unwrap()
+}
+
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+async fn start_proxy() -> (TcpListener, String) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    (listener, addr)
+}
+
+async fn read_bytes(stream: &mut TcpStream, len: usize) -> Vec<u8> {
+    let mut buf = vec![0u8; len];
+    stream.read_exact(&mut buf).await.unwrap();
+    buf
+}
+
+#[test]
+fn test_target_addr() {
+    assert_eq!(
+        TargetAddr::from_multiaddr(&onion_addr()),
+        Some(TargetAddr::Domain(
+            format!("{ONION_SERVICE_ID}.onion"),
+            8115
+        ))
+    );
+    assert_eq!(
+        TargetAddr::from_multiaddr(&"/ip4/1.2.3.4/tcp/8115".parse().unwrap()),
+        Some(TargetAddr::Ip(IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4)), 8115))
+    );
+    assert_eq!(
+        TargetAddr::from_multiaddr(&"/dns4/example.com/tcp/8115".parse().unwrap()),
+        Some(TargetAddr::Domain("example.com".to_owned(), 8115))
+    );
+    assert_eq!(
+        TargetAddr::from_multiaddr(&"/ip4/1.2.3.4/tcp/8115/ws".parse().unwrap()),
+        None
+    );
+}
+
+#[test]
+fn test_peer_store_keeps_onion_addr() {
+    let mut peer_store: PeerStore = Default::default();
+    let addr = onion_addr();
+    peer_store
+        .add_addr(addr.clone(), Flags::COMPATIBILITY)
+        .unwrap();
+    let addrs = peer_store.fetch_addrs_to_feeler(2, |_| true);
+    assert_eq!(addrs.len(), 1);
+    assert_eq!(addrs[0].addr, addr);
+}
+
+#[test]
+fn test_should_proxy() {
+    let config = ProxyConfig {
+        url: Some("socks5://127.0.0.1:9050".parse().unwrap()),
+        onion_only: true,
+    };
+    let proxy = Socks5Proxy::from_config(&config).unwrap().unwrap();
+    assert!(proxy.should_proxy(&onion_addr()));
+    assert!(!proxy.should_proxy(&"/ip4/1.2.3.4/tcp/8115".parse().unwrap()));
+
+    let config = ProxyConfig {
+        url: Some("socks5://127.0.0.1:9050".parse().unwrap()),
+        onion_only: false,
+    };
+    let proxy = Socks5Proxy::from_config(&config).unwrap().unwrap();
+    assert!(proxy.should_proxy(&"/ip4/1.2.3.4/tcp/8115".parse().unwrap()));
+
+    let config = ProxyConfig {
+        url: Some("http://127.0.0.1:8080".parse().unwrap()),
+        onion_only: false,
+    };
+    assert!(Socks5Proxy::from_config(&config).is_err());
+    assert!(Socks5Proxy::from_config(&ProxyConfig::default())
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn test_socks5_connect_onion() {
+    block_on(async {
+        let (listener, proxy_addr) = start_proxy().await;
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            // no auth and username/password
+            assert_eq!(read_bytes(&mut stream, 4).await, [5, 2, 0, 2]);
+            stream.write_all(&[5, 2]).await.unwrap();
+            assert_eq!(read_bytes(&mut stream, 11).await, b"\x01\x04user\x04pass");
+            stream.write_all(&[1, 0]).await.unwrap();
+
+            let domain = format!("{ONION_SERVICE_ID}.onion");
+            let mut request = vec![5, 1, 0, 3, domain.len() as u8];
+            request.extend_from_slice(domain.as_bytes());
+            request.extend_from_slice(&8115u16.to_be_bytes());
+            assert_eq!(read_bytes(&mut stream, request.len()).await, request);
+            stream
+                .write_all(&[5, 0, 0, 1, 0, 0, 0, 0, 0, 0])
+                .await
+                .unwrap();
+
+            // echo
+            let data = read_bytes(&mut stream, 4).await;
+            stream.write_all(&data).await.unwrap();
+        });
+
+        let config = ProxyConfig {
+            url: Some(format!("socks5://user:pass@{proxy_addr}").parse().unwrap()),
+            onion_only: true,
+        };
+        let proxy = Socks5Proxy::from_config(&config).unwrap().unwrap();
+        let mut stream = proxy.connect(&onion_addr()).await.unwrap();
+        stream.write_all(b"ping").await.unwrap();
+        assert_eq!(read_bytes(&mut stream, 4).await, b"ping");
+        server.await.unwrap();
+    });
+}
+
+#[test]
+fn test_socks5_connect_refused() {
+    block_on(async {
+        let (listener, proxy_addr) = start_proxy().await;
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            assert_eq!(read_bytes(&mut stream, 3).await, [5, 1, 0]);
+            stream.write_all(&[5, 0]).await.unwrap();
+            let mut request = vec![5, 1, 0, 1, 1, 2, 3, 4];
+            request.extend_from_slice(&8115u16.to_be_bytes());
+            assert_eq!(read_bytes(&mut stream, request.len()).await, request);
+            // host unreachable
+            stream
+                .write_all(&[5, 4, 0, 1, 0, 0, 0, 0, 0, 0])
+                .await
+                .unwrap();
+        });
+
+        let config = ProxyConfig {
+            url: Some(format!("socks5://{proxy_addr}").parse().unwrap()),
+            onion_only: false,
+        };
+        let proxy = Socks5Proxy::from_config(&config).unwrap().unwrap();
+        let addr = "/ip4/1.2.3.4/tcp/8115".parse().unwrap();
+        assert!(proxy.connect(&addr).await.is_err());
+        server.await.unwrap();
+    });
+}
@@ -1,5 +1,5 @@
 //! TODO(doc): @quake
-use ckb_app_config::NotifyConfig;
+use ckb_app_config::{NotifyConfig, WebhookTopic};
 use ckb_async_runtime::Handle;
 use ckb_logger::{debug, error, info, trace};
 use ckb_stop_handler::{new_tokio_exit_rx, CancellationToken};
@@ -17,7 +17,12 @@ use tokio::sync::{
 };
 use tokio::time::timeout;
 
-pub use ckb_types::core::service::PoolTransactionEntry;
+mod webhook;
+
+use webhook::Webhooks;
+pub use webhook::{sign as sign_webhook_payload, WebhookEvent, SIGNATURE_HEADER};
+
+pub use ckb_types::core::service::{ChainReorg, PoolTransactionEntry};
 
 /// Asynchronous request sent to the service.
 pub struct Request<A, R> {
@@ -91,6 +96,8 @@ pub struct NotifyController {
     new_block_register: NotifyRegister<BlockView>,
     new_block_watcher: NotifyWatcher<Byte32>,
     new_block_notifier: Sender<BlockView>,
+    chain_reorg_register: NotifyRegister<ChainReorg>,
+    chain_reorg_notifier: Sender<ChainReorg>,
     new_transaction_register: NotifyRegister<PoolTransactionEntry>,
     new_transaction_notifier: Sender<PoolTransactionEntry>,
     proposed_transaction_register: NotifyRegister<PoolTransactionEntry>,
@@ -107,10 +114,12 @@ pub struct NotifyService {
     config: NotifyConfig,
     new_block_subscribers: HashMap<String, Sender<BlockView>>,
     new_block_watchers: HashMap<String, watch::Sender<Byte32>>,
+    chain_reorg_subscribers: HashMap<String, Sender<ChainReorg>>,
     new_transaction_subscribers: HashMap<String, Sender<PoolTransactionEntry>>,
     proposed_transaction_subscribers: HashMap<String, Sender<PoolTransactionEntry>>,
     reject_transaction_subscribers: HashMap<String, Sender<(PoolTransactionEntry, Reject)>>,
     network_alert_subscribers: HashMap<String, Sender<Alert>>,
+    webhooks: Option<Webhooks>,
     timeout: NotifyTimeout,
     handle: Handle,
 }
@@ -124,10 +133,12 @@ impl NotifyService {
             config,
             new_block_subscribers: HashMap::default(),
             new_block_watchers: HashMap::default(),
+            chain_reorg_subscribers: HashMap::default(),
             new_transaction_subscribers: HashMap::default(),
             proposed_transaction_subscribers: HashMap::default(),
             reject_transaction_subscribers: HashMap::default(),
             network_alert_subscribers: HashMap::default(),
+            webhooks: None,
             timeout,
             handle,
         }
@@ -137,6 +148,7 @@ impl NotifyService {
     pub fn start(mut self) -> NotifyController {
         let signal_receiver: CancellationToken = new_tokio_exit_rx();
         let handle = self.handle.clone();
+        self.webhooks = Webhooks::start(&self.config, &handle);
 
         let (new_block_register, mut new_block_register_receiver) =
             mpsc::channel(REGISTER_CHANNEL_SIZE);
@@ -144,6 +156,10 @@ impl NotifyService {
             mpsc::channel(REGISTER_CHANNEL_SIZE);
         let (new_block_sender, mut new_block_receiver) = mpsc::channel(NOTIFY_CHANNEL_SIZE);
 
+        let (chain_reorg_register, mut chain_reorg_register_receiver) =
+            mpsc::channel(REGISTER_CHANNEL_SIZE);
+        let (chain_reorg_sender, mut chain_reorg_receiver) = mpsc::channel(NOTIFY_CHANNEL_SIZE);
+
         let (new_transaction_register, mut new_transaction_register_receiver) =
             mpsc::channel(REGISTER_CHANNEL_SIZE);
         let (new_transaction_sender, mut new_transaction_receiver) =
@@ -169,6 +185,8 @@ impl NotifyService {
                     Some(msg) = new_block_register_receiver.recv() => { self.handle_register_new_block(msg) },
                     Some(msg) = new_block_watcher_receiver.recv() => { self.handle_watch_new_block(msg) },
                     Some(msg) = new_block_receiver.recv() => { self.handle_notify_new_block(msg) },
+                    Some(msg) = chain_reorg_register_receiver.recv() => { self.handle_register_chain_reorg(msg) },
+                    Some(msg) = chain_reorg_receiver.recv() => { self.handle_notify_chain_reorg(msg) },
                     Some(msg) = new_transaction_register_receiver.recv() => { self.handle_register_new_transaction(msg) },
                     Some(msg) = new_transaction_receiver.recv() => { self.handle_notify_new_transaction(msg) },
                     Some(msg) = proposed_transaction_register_receiver.recv() => { self.handle_register_proposed_transaction(msg) },
@@ -190,6 +208,8 @@ impl NotifyService {
             new_block_register,
             new_block_watcher,
             new_block_notifier: new_block_sender,
+            chain_reorg_register,
+            chain_reorg_notifier: chain_reorg_sender,
             new_transaction_register,
             new_transaction_notifier: new_transaction_sender,
             proposed_transaction_register,
@@ -224,6 +244,23 @@ impl NotifyService {
         let _ = responder.send(receiver);
     }
 
+    fn publish_webhook<T, F>(&self, topic: WebhookTopic, data: F)
+    where
+        T: serde::Serialize,
+        F: FnOnce() -> T,
+    {
+        // only serialize the event when some webhook subscribes it
+        if let Some(webhooks) = self.webhooks.as_ref().filter(|w| w.is_subscribed(topic)) {
+            match serde_json::to_value(data()) {
+                Ok(value) => webhooks.publish(topic, value),
+                Err(e) => error!(
+                    "Failed to serialize the {:?} webhook event, error: {}",
+                    topic, e
+                ),
+            }
+        }
+    }
+
     fn handle_notify_new_block(&self, block: BlockView) {
         trace!("New block event {:?}", block);
         let block_hash = block.hash();
@@ -245,6 +282,11 @@ impl NotifyService {
             }
         }
 
+        // notify webhooks
+        self.publish_webhook(WebhookTopic::NewTip, || {
+            ckb_jsonrpc_types::HeaderView::from(block.header())
+        });
+
         // notify script
         if let Some(script) = self.config.new_block_notify_script.clone() {
             let script_timeout = self.timeout.script;
@@ -264,6 +306,67 @@ impl NotifyService {
         }
     }
 
+    fn handle_register_chain_reorg(&mut self, msg: Request<String, Receiver<ChainReorg>>) {
+        let Request {
+            responder,
+            arguments: name,
+        } = msg;
+        debug!("Register chain_reorg {:?}", name);
+        let (sender, receiver) = mpsc::channel(NOTIFY_CHANNEL_SIZE);
+        self.chain_reorg_subscribers.insert(name, sender);
+        let _ = responder.send(receiver);
+    }
+
+    fn handle_notify_chain_reorg(&self, reorg: ChainReorg) {
+        trace!("Chain reorg event {:?}", reorg);
+        // notify all subscribers
+        for subscriber in self.chain_reorg_subscribers.values() {
+            let reorg = reorg.clone();
+            let subscriber = subscriber.clone();
+            self.handle.spawn(async move {
+                if let Err(e) = subscriber.send(reorg).await {
+                    error!("Failed to notify chain reorg, error: {}", e);
+                }
+            });
+        }
+
+        // notify webhooks
+        self.publish_webhook(WebhookTopic::ChainReorg, || {
+            ckb_jsonrpc_types::ChainReorg::from(reorg.clone())
+        });
+
+        // notify script
+        if let Some(script) = self.config.chain_reorg_notify_script.clone() {
+            let script_timeout = self.timeout.script;
+            let join_hashes = |hashes: &[Byte32]| {
+                hashes
+                    .iter()
+                    .map(|hash| format!("{hash:#x}"))
+                    .collect::<Vec<_>>()
+                    .join(",")
+            };
+            let args = [
+                format!("{:#x}", reorg.fork_point.hash()),
+                join_hashes(&reorg.detached_blocks),
+                join_hashes(&reorg.attached_blocks),
+            ];
+            self.handle.spawn(async move {
+                match timeout(script_timeout, Command::new(&script).args(&args).status()).await {
+                    Ok(ret) => match ret {
+                        Ok(status) => {
+                            debug!("The chain_reorg_notify script exited with: {status}")
+                        }
+                        Err(e) => error!(
+                            "Failed to run chain_reorg_notify_script: {} {:?}, error: {}",
+                            script, args[0], e
+                        ),
+                    },
+                    Err(_) => ckb_logger::warn!("chain_reorg_notify_script {script} timed out"),
+                }
+            });
+        }
+    }
+
     fn handle_register_new_transaction(
         &mut self,
         msg: Request<String, Receiver<PoolTransactionEntry>>,
@@ -292,6 +395,11 @@ impl NotifyService {
                 }
             });
         }
+
+        // notify webhooks
+        self.publish_webhook(WebhookTopic::NewTransaction, || {
+            ckb_jsonrpc_types::PoolTransactionEntry::from(tx_entry)
+        });
     }
 
     fn handle_register_proposed_transaction(
@@ -352,6 +460,15 @@ impl NotifyService {
                 }
             });
         }
+
+        // notify webhooks
+        self.publish_webhook(WebhookTopic::RejectedTransaction, || {
+            let (entry, reject) = tx_entry;
+            (
+                ckb_jsonrpc_types::PoolTransactionEntry::from(entry),
+                ckb_jsonrpc_types::PoolTransactionReject::from(reject),
+            )
+        });
     }
 
     fn handle_register_network_alert(&mut self, msg: Request<String, Receiver<Alert>>) {
@@ -386,6 +503,11 @@ impl NotifyService {
             });
         }
 
+        // notify webhooks
+        self.publish_webhook(WebhookTopic::NetworkAlert, || {
+            ckb_jsonrpc_types::Alert::from(alert)
+        });
+
         // notify script
         if let Some(script) = self.config.network_alert_notify_script.clone() {
             let script_timeout = self.timeout.script;
@@ -433,6 +555,23 @@ impl NotifyController {
         });
     }
 
+    /// Subscribes the chain reorganizations.
+    pub async fn subscribe_chain_reorg<S: ToString>(&self, name: S) -> Receiver<ChainReorg> {
+        Request::call(&self.chain_reorg_register, name.to_string())
+            .await
+            .expect("Subscribe chain reorg should be OK")
+    }
+
+    /// Notifies a chain reorganization.
+    pub fn notify_chain_reorg(&self, reorg: ChainReorg) {
+        let chain_reorg_notifier = self.chain_reorg_notifier.clone();
+        self.handle.spawn(async move {
+            if let Err(e) = chain_reorg_notifier.send(reorg).await {
+                error!("notify_chain_reorg channel is closed: {}", e);
+            }
+        });
+    }
+
     /// TODO(doc): @quake
     pub async fn subscribe_new_transaction<S: ToString>(
         &self,
@@ -0,0 +1,479 @@
+//! Webhooks which POST the JSON payloads of the events to the configured HTTP endpoints.
+//!
+//! The events of an endpoint are delivered one by one in the order they happened, and a failed
+//! delivery is retried with exponential backoff, except that an event rejected with a 4xx status
+//! other than 408 and 429 is dropped at once. The events which are not delivered yet are kept in a
+//! bounded outbox, it is saved to a file when changed and on exit so they survive restarts.
+use ckb_app_config::{NotifyConfig, WebhookConfig, WebhookTopic};
+use ckb_async_runtime::Handle;
+use ckb_logger::{debug, error, info, warn};
+use ckb_stop_handler::{new_tokio_exit_rx, CancellationToken};
+use ckb_types::bytes::Bytes;
+use hmac::{Hmac, Mac};
+use http_body_util::Full;
+use hyper::{Method, Request, StatusCode};
+use hyper_util::client::legacy::{connect::HttpConnector, Client};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::{fs, io};
+use tokio::sync::Notify;
+use tokio::time::{interval, sleep, timeout, MissedTickBehavior};
+
+/// The header of the HMAC-SHA256 signature of the payload, in the form of `sha256=<hex>`
+pub const SIGNATURE_HEADER: &str = "X-CKB-Signature";
+
+const DEFAULT_OUTBOX_SIZE: usize = 1000;
+const DEFAULT_WEBHOOK_TIMEOUT: Duration = Duration::from_millis(10_000);
+const DEFAULT_MAX_RETRIES: u32 = 10;
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(1);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(300);
+const OUTBOX_SAVE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The payload POSTed to the webhooks.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebhookEvent {
+    /// The sequence number of the event, it keeps increasing across restarts
+    pub id: u64,
+    /// The topic of the event
+    pub topic: WebhookTopic,
+    /// The milliseconds since the Unix epoch when the event happened
+    pub timestamp: u64,
+    /// The event data, which is the same as the result of the corresponding subscription topic
+    pub data: serde_json::Value,
+}
+
+/// The events which are not delivered yet, indexed by the webhook URL.
+///
+/// The events are shared by the queues, so a clone of the outbox is cheap.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub(crate) struct Outbox {
+    next_id: u64,
+    pending: HashMap<String, VecDeque<Arc<WebhookEvent>>>,
+}
+
+impl Outbox {
+    // Appends an event to the queues of the webhooks subscribing the topic, returns the indexes
+    // of these webhooks
+    fn push(
+        &mut self,
+        webhooks: &[WebhookConfig],
+        max_size: usize,
+        topic: WebhookTopic,
+        timestamp: u64,
+        data: serde_json::Value,
+    ) -> Vec<usize> {
+        let event = Arc::new(WebhookEvent {
+            id: self.next_id,
+            topic,
+            timestamp,
+            data,
+        });
+        self.next_id += 1;
+
+        let mut subscribed = Vec::new();
+        for (index, webhook) in webhooks.iter().enumerate() {
+            if !is_subscribed(webhook, topic) {
+                continue;
+            }
+            let queue = self.pending.entry(webhook.url.clone()).or_default();
+            if queue.len() >= max_size {
+                if let Some(dropped) = queue.pop_front() {
+                    warn!(
+                        "Webhook {} outbox is full, drop event {}",
+                        webhook.url, dropped.id
+                    );
+                }
+            }
+            queue.push_back(Arc::clone(&event));
+            subscribed.push(index);
+        }
+        subscribed
+    }
+
+    fn front(&self, url: &str) -> Option<Arc<WebhookEvent>> {
+        self.pending
+            .get(url)
+            .and_then(|queue| queue.front())
+            .cloned()
+    }
+
+    // Removes the first event if it is still the given one, it may have been dropped when the
+    // outbox is full
+    fn remove(&mut self, url: &str, id: u64) {
+        if let Some(queue) = self.pending.get_mut(url) {
+            if queue.front().map(|event| event.id) == Some(id) {
+                queue.pop_front();
+            }
+        }
+    }
+}
+
+fn is_subscribed(webhook: &WebhookConfig, topic: WebhookTopic) -> bool {
+    webhook.topics.is_empty() || webhook.topics.contains(&topic)
+}
+
+/// Signs the payload with HMAC-SHA256, returns the value of the signature header.
+pub fn sign(secret: &str, payload: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(payload);
+    format!(
+        "sha256={}",
+        faster_hex::hex_string(&mac.finalize().into_bytes())
+    )
+}
+
+struct OutboxState {
+    outbox: Outbox,
+    dirty: bool,
+}
+
+/// The webhooks which the events are published to.
+#[derive(Clone)]
+pub(crate) struct Webhooks {
+    webhooks: Arc<Vec<WebhookConfig>>,
+    wakers: Arc<Vec<Notify>>,
+    state: Arc<Mutex<OutboxState>>,
+    outbox_path: PathBuf,
+    outbox_size: usize,
+}
+
+impl Webhooks {
+    /// Loads the outbox and starts delivering the events, returns None if no webhook is configured.
+    pub(crate) fn start(config: &NotifyConfig, handle: &Handle) -> Option<Self> {
+        if config.webhooks.is_empty() {
+            return None;
+        }
+
+        let mut outbox = match load_outbox(&config.webhook_outbox) {
+            Ok(outbox) => outbox,
+            Err(e) => {
+                error!(
+                    "Failed to load the webhook outbox {}, error: {}",
+                    config.webhook_outbox.display(),
+                    e
+                );
+                Outbox::default()
+            }
+        };
+        // the events of the removed webhooks are discarded
+        outbox
+            .pending
+            .retain(|url, _| config.webhooks.iter().any(|webhook| &webhook.url == url));
+
+        let webhooks = Webhooks {
+            webhooks: Arc::new(config.webhooks.clone()),
+            wakers: Arc::new(config.webhooks.iter().map(|_| Notify::new()).collect()),
+            state: Arc::new(Mutex::new(OutboxState {
+                outbox,
+                dirty: false,
+            })),
+            outbox_path: config.webhook_outbox.clone(),
+            outbox_size: config.webhook_outbox_size.unwrap_or(DEFAULT_OUTBOX_SIZE),
+        };
+
+        let client = Client::builder(hyper_util::rt::TokioExecutor::new())
+            .build::<_, Full<Bytes>>(HttpConnector::new());
+        for index in 0..webhooks.webhooks.len() {
+            handle.spawn(webhooks.clone().deliver(index, client.clone()));
+        }
+        handle.spawn(webhooks.clone().save_periodically());
+
+        Some(webhooks)
+    }
+
+    /// Returns true if any webhook subscribes the topic.
+    pub(crate) fn is_subscribed(&self, topic: WebhookTopic) -> bool {
+        self.webhooks
+            .iter()
+            .any(|webhook| is_subscribed(webhook, topic))
+    }
+
+    /// Appends an event to the outbox of the webhooks subscribing the topic.
+    pub(crate) fn publish(&self, topic: WebhookTopic, data: serde_json::Value) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as u64)
+            .unwrap_or_default();
+        let subscribed = {
+            let mut state = self.state.lock().expect("acquire lock");
+            let subscribed =
+                state
+                    .outbox
+                    .push(&self.webhooks, self.outbox_size, topic, timestamp, data);
+            state.dirty |= !subscribed.is_empty();
+            subscribed
+        };
+        for index in subscribed {
+            self.wakers[index].notify_one();
+        }
+    }
+
+    async fn deliver(self, index: usize, client: Client<HttpConnector, Full<Bytes>>) {
+        let stop: CancellationToken = new_tokio_exit_rx();
+        let webhook = &self.webhooks[index];
+        let max_retries = webhook.max_retries.unwrap_or(DEFAULT_MAX_RETRIES);
+        let mut failures = 0;
+        loop {
+            let event = self
+                .state
+                .lock()
+                .expect("acquire lock")
+                .outbox
+                .front(&webhook.url);
+            let event = match event {
+                Some(event) => event,
+                None => {
+                    tokio::select! {
+                        _ = self.wakers[index].notified() => continue,
+                        _ = stop.cancelled() => break,
+                    }
+                }
+            };
+
+            match post(&client, webhook, &event).await {
+                Ok(()) => {
+                    debug!("Webhook {} delivered event {}", webhook.url, event.id);
+                    failures = 0;
+                }
+                Err(DeliveryError::Rejected(status)) => {
+                    warn!(
+                        "Webhook {} rejected event {} with status {}, drop it",
+                        webhook.url, event.id, status
+                    );
+                    failures = 0;
+                }
+                Err(DeliveryError::Failed(e)) if failures < max_retries => {
+                    failures += 1;
+                    let delay = retry_delay(failures);
+                    debug!(
+                        "Failed to deliver event {} to webhook {}, retry in {:?}, error: {}",
+                        event.id, webhook.url, delay, e
+                    );
+                    tokio::select! {
+                        _ = sleep(delay) => continue,
+                        _ = stop.cancelled() => break,
+                    }
+                }
+                Err(DeliveryError::Failed(e)) => {
+                    warn!(
+                        "Failed to deliver event {} to webhook {} after {} retries, drop it, error: {}",
+                        event.id, webhook.url, failures, e
+                    );
+                    failures = 0;
+                }
+            }
+            let mut state = self.state.lock().expect("acquire lock");
+            state.outbox.remove(&webhook.url, event.id);
+            state.dirty = true;
+        }
+        info!("Webhook {} received exit signal, exit now", webhook.url);
+    }
+
+    async fn save_periodically(self) {
+        let stop: CancellationToken = new_tokio_exit_rx();
+        let mut interval = interval(OUTBOX_SAVE_INTERVAL);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => self.save(),
+                _ = stop.cancelled() => {
+                    self.save();
+                    break;
+                }
+            }
+        }
+    }
+
+    // Saves a snapshot of the outbox if it is changed, the snapshot is serialized outside the lock
+    // so the publishing is not blocked
+    fn save(&self) {
+        let outbox = {
+            let mut state = self.state.lock().expect("acquire lock");
+            if !state.dirty {
+                return;
+            }
+            state.dirty = false;
+            state.outbox.clone()
+        };
+        if let Err(e) = save_outbox(&self.outbox_path, &outbox) {
+            error!(
+                "Failed to save the webhook outbox {}, error: {}",
+                self.outbox_path.display(),
+                e
+            );
+            self.state.lock().expect("acquire lock").dirty = true;
+        }
+    }
+}
+
+enum DeliveryError {
+    // the endpoint rejected the event, retrying it won't help
+    Rejected(StatusCode),
+    // the delivery failed, it may succeed if retried
+    Failed(String),
+}
+
+// The client errors are permanent, except that the request timed out or is rate limited
+fn is_rejected(status: StatusCode) -> bool {
+    status.is_client_error()
+        && status != StatusCode::REQUEST_TIMEOUT
+        && status != StatusCode::TOO_MANY_REQUESTS
+}
+
+async fn post(
+    client: &Client<HttpConnector, Full<Bytes>>,
+    webhook: &WebhookConfig,
+    event: &WebhookEvent,
+) -> Result<(), DeliveryError> {
+    let payload = serde_json::to_vec(event).expect("serialize webhook event should be OK");
+    let mut builder = Request::builder()
+        .method(Method::POST)
+        .uri(&webhook.url)
+        .header("content-type", "application/json");
+    if let Some(secret) = &webhook.secret {
+        builder = builder.header(SIGNATURE_HEADER, sign(secret, &payload));
+    }
+    let request = builder
+        .body(Full::new(payload.into()))
+        .map_err(|e| DeliveryError::Failed(e.to_string()))?;
+    let request_timeout = webhook
+        .timeout
+        .map(Duration::from_millis)
+        .unwrap_or(DEFAULT_WEBHOOK_TIMEOUT);
+    match timeout(request_timeout, client.request(request)).await {
+        Ok(Ok(response)) if response.status().is_success() => Ok(()),
+        Ok(Ok(response)) if is_rejected(response.status()) => {
+            Err(DeliveryError::Rejected(response.status()))
+        }
+        Ok(Ok(response)) => Err(DeliveryError::Failed(format!(
+            "response status {}",
+            response.status()
+        ))),
+        Ok(Err(e)) => Err(DeliveryError::Failed(e.to_string())),
+        Err(_) => Err(DeliveryError::Failed("timed out".to_owned())),
+    }
+}
+
+fn retry_delay(failures: u32) -> Duration {
+    INITIAL_RETRY_DELAY
+        .saturating_mul(2u32.saturating_pow(failures.saturating_sub(1)))
+        .min(MAX_RETRY_DELAY)
+}
+
+fn load_outbox(path: &Path) -> Result<Outbox, io::Error> {
+    match fs::read(path) {
+        Ok(content) => serde_json::from_slice(&content).map_err(io::Error::from),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Outbox::default()),
+        Err(e) => Err(e),
+    }
+}
+
+fn save_outbox(path: &Path, outbox: &Outbox) -> Result<(), io::Error> {
+    let content = serde_json::to_vec(outbox).map_err(io::Error::from)?;
+    // write to a temporary file first, so the outbox is never left half written
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, content)?;
+    fs::rename(tmp_path, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn webhook(url: &str, topics: Vec<WebhookTopic>) -> WebhookConfig {
+        WebhookConfig {
+            url: url.to_owned(),
+            topics,
+            secret: None,
+            timeout: None,
+            max_retries: None,
+        }
+    }
+
+    #[test]
+    fn outbox_push_and_remove() {
+        let webhooks = vec![
+            webhook("http://127.0.0.1:8001", vec![]),
+            webhook("http://127.0.0.1:8002", vec![WebhookTopic::ChainReorg]),
+        ];
+        let mut outbox = Outbox::default();
+
+        let subscribed = outbox.push(&webhooks, 2, WebhookTopic::NewTip, 1, 1.into());
+        assert_eq!(vec![0], subscribed);
+        let subscribed = outbox.push(&webhooks, 2, WebhookTopic::ChainReorg, 2, 2.into());
+        assert_eq!(vec![0, 1], subscribed);
+        // the oldest event is dropped when the outbox is full
+        outbox.push(&webhooks, 2, WebhookTopic::NewTip, 3, 3.into());
+        assert_eq!(
+            Some(1),
+            outbox.front(&webhooks[0].url).map(|event| event.id)
+        );
+        assert_eq!(
+            Some(1),
+            outbox.front(&webhooks[1].url).map(|event| event.id)
+        );
+
+        // the dropped event is not removed again
+        outbox.remove(&webhooks[0].url, 0);
+        assert_eq!(
+            Some(1),
+            outbox.front(&webhooks[0].url).map(|event| event.id)
+        );
+        outbox.remove(&webhooks[0].url, 1);
+        assert_eq!(
+            Some(2),
+            outbox.front(&webhooks[0].url).map(|event| event.id)
+        );
+        outbox.remove(&webhooks[1].url, 1);
+        assert_eq!(None, outbox.front(&webhooks[1].url));
+    }
+
+    #[test]
+    fn outbox_save_and_load() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let path = tmp_dir.path().join("webhook_outbox.json");
+        assert_eq!(0, load_outbox(&path).unwrap().next_id);
+
+        let webhooks = vec![webhook("http://127.0.0.1:8001", vec![])];
+        let mut outbox = Outbox::default();
+        outbox.push(&webhooks, 10, WebhookTopic::NetworkAlert, 1, "alert".into());
+        save_outbox(&path, &outbox).unwrap();
+
+        let loaded = load_outbox(&path).unwrap();
+        assert_eq!(1, loaded.next_id);
+        assert_eq!(
+            outbox.front(&webhooks[0].url),
+            loaded.front(&webhooks[0].url)
+        );
+    }
+
+    #[test]
+    fn sign_payload() {
+        assert_eq!(
+            "sha256=f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8",
+            sign("key", b"The quick brown fox jumps over the lazy dog")
+        );
+    }
+
+    #[test]
+    fn rejected_status() {
+        assert!(is_rejected(StatusCode::BAD_REQUEST));
+        assert!(is_rejected(StatusCode::NOT_FOUND));
+        assert!(!is_rejected(StatusCode::REQUEST_TIMEOUT));
+        assert!(!is_rejected(StatusCode::TOO_MANY_REQUESTS));
+        assert!(!is_rejected(StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(!is_rejected(StatusCode::SERVICE_UNAVAILABLE));
+    }
+
+    #[test]
+    fn retry_delay_backoff() {
+        assert_eq!(Duration::from_secs(1), retry_delay(1));
+        assert_eq!(Duration::from_secs(8), retry_delay(4));
+        assert_eq!(MAX_RETRY_DELAY, retry_delay(20));
+    }
+}
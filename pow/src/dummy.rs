@@ -1,5 +1,5 @@
 use super::PowEngine;
-use ckb_types::packed::Header;
+use ckb_types::{packed::Header, U256};
 
 /// A mock Pow Engine, mostly for development&test purpose, and may not used in real world verification
 pub struct DummyPowEngine;
@@ -9,4 +9,9 @@ impl PowEngine for DummyPowEngine {
     fn verify(&self, _header: &Header) -> bool {
         true
     }
+
+    /// This result will always be true
+    fn verify_with_target(&self, _header: &Header, _target: &U256) -> bool {
+        true
+    }
 }
@@ -3,6 +3,7 @@ use byteorder::{ByteOrder, LittleEndian};
 use ckb_types::{
     packed::{Byte32, Header},
     prelude::*,
+    U256,
 };
 use serde::{Deserialize, Serialize};
 use std::any::Any;
@@ -72,6 +73,14 @@ pub fn pow_message(pow_hash: &Byte32, nonce: u128) -> [u8; 48] {
 pub trait PowEngine: Send + Sync + AsAny {
     /// Verify header
     fn verify(&self, header: &Header) -> bool;
+
+    /// Verify header against the `target` instead of the one in the header, which is used to
+    /// check the shares submitted by the miners of a mining pool.
+    ///
+    /// Returns false if the engine does not support it.
+    fn verify_with_target(&self, _header: &Header, _target: &U256) -> bool {
+        false
+    }
 }
 
 /// A trait for casting to trait `Any`
@@ -17,3 +17,19 @@ fn test_pow_message() {
         .to_vec()
     );
 }
+
+#[test]
+fn test_verify_with_target() {
+    use crate::{EaglesongBlake2bPowEngine, EaglesongPowEngine, PowEngine};
+    use ckb_types::{packed::Header, U256};
+
+    let header = Header::default();
+    let engines: Vec<Box<dyn PowEngine>> = vec![
+        Box::new(EaglesongPowEngine),
+        Box::new(EaglesongBlake2bPowEngine),
+    ];
+    for engine in engines {
+        assert!(engine.verify_with_target(&header, &U256::max_value()));
+        assert!(!engine.verify_with_target(&header, &U256::zero()));
+    }
+}
@@ -0,0 +1,198 @@
+use crate::rate_limit::{RateLimits, Throttler};
+use crate::{IoHandler, RPCError};
+use base64::Engine;
+use ckb_app_config::{RpcAuthConfig, RpcModule, RpcRateLimitConfig};
+use jsonrpc_core::RemoteProcedure;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+// Methods granted to all the authenticated requests.
+const PUBLIC_METHODS: &[&str] = &["ping"];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Credential {
+    Bearer(String),
+    // base64 encoded `username:password`
+    Basic(String),
+}
+
+impl Credential {
+    fn from_config(config: &RpcAuthConfig) -> Option<Self> {
+        match (&config.token, &config.username) {
+            (Some(token), None) => Some(Credential::Bearer(token.to_owned())),
+            (None, Some(username)) => {
+                let password = config.password.as_deref().unwrap_or_default();
+                Some(Credential::Basic(
+                    base64::prelude::BASE64_STANDARD.encode(format!("{username}:{password}")),
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    /// Parses the value of the `Authorization` header, e.g. `Bearer <token>`.
+    fn parse(value: &str) -> Option<Self> {
+        let (scheme, credential) = value.trim().split_once(' ')?;
+        let credential = credential.trim().to_owned();
+        if scheme.eq_ignore_ascii_case("bearer") {
+            Some(Credential::Bearer(credential))
+        } else if scheme.eq_ignore_ascii_case("basic") {
+            Some(Credential::Basic(credential))
+        } else {
+            None
+        }
+    }
+
+    // Compares in constant time to not leak the secret via timing
+    fn matches(&self, other: &Self) -> bool {
+        let (expected, actual) = match (self, other) {
+            (Credential::Bearer(expected), Credential::Bearer(actual))
+            | (Credential::Basic(expected), Credential::Basic(actual)) => (expected, actual),
+            _ => return false,
+        };
+        expected.len() == actual.len()
+            && expected
+                .bytes()
+                .zip(actual.bytes())
+                .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+                == 0
+    }
+}
+
+struct Rule {
+    credential: Credential,
+    methods: HashSet<String>,
+}
+
+/// RPC access control and rate limit rules resolved against the mounted methods.
+///
+/// Built by [ServiceBuilder::access_control](../service_builder/struct.ServiceBuilder.html).
+/// An empty access control allows all the requests.
+#[doc(hidden)]
+#[derive(Default)]
+pub struct AccessControl {
+    rules: Vec<Rule>,
+    rate_limits: RateLimits,
+}
+
+impl AccessControl {
+    /// Resolves the `rpc.auth` rules.
+    ///
+    /// Returns an error if any rule is invalid, the RPC server must not start with the rule
+    /// silently dropped, which may leave the server without authentication.
+    pub(crate) fn new(
+        configs: &[RpcAuthConfig],
+        method_modules: &HashMap<String, RpcModule>,
+    ) -> Result<Self, String> {
+        let rules = configs
+            .iter()
+            .map(|config| {
+                let credential = Credential::from_config(config).ok_or_else(|| {
+                    format!(
+                        "Invalid rpc.auth rule which grants modules {:?} and methods {:?}, \
+                         exactly one of `token` and `username` is required",
+                        config.modules, config.methods
+                    )
+                })?;
+                let methods = method_modules
+                    .iter()
+                    .filter(|(_, module)| config.modules.contains(module))
+                    .map(|(name, _)| name.to_owned())
+                    .chain(config.methods.iter().cloned())
+                    .chain(PUBLIC_METHODS.iter().map(|name| name.to_string()))
+                    .collect();
+                Ok(Rule {
+                    credential,
+                    methods,
+                })
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(AccessControl {
+            rules,
+            rate_limits: RateLimits::default(),
+        })
+    }
+
+    pub(crate) fn with_rate_limits(
+        mut self,
+        configs: &[RpcRateLimitConfig],
+        method_modules: &HashMap<String, RpcModule>,
+    ) -> Result<Self, String> {
+        self.rate_limits = RateLimits::new(configs, method_modules)?;
+        Ok(self)
+    }
+
+    /// Returns true if requests must be authenticated.
+    pub(crate) fn is_enabled(&self) -> bool {
+        !self.rules.is_empty()
+    }
+
+    /// Returns the throttler if there are rate limits.
+    pub(crate) fn throttler(self) -> Option<Throttler> {
+        if self.rate_limits.is_empty() {
+            None
+        } else {
+            Some(Throttler::new(self.rate_limits))
+        }
+    }
+
+    /// Builds a methods handler for each rule, which only contains the granted methods of `io`.
+    pub(crate) fn authenticator(&self, io: &IoHandler) -> Authenticator {
+        let handlers = self
+            .rules
+            .iter()
+            .map(|rule| {
+                let mut restricted = IoHandler::with_compatibility(jsonrpc_core::Compatibility::V2);
+                restricted.extend_with(io.iter().map(|(name, method)| {
+                    if rule.methods.contains(name) {
+                        (name.to_owned(), method.clone())
+                    } else {
+                        let error = RPCError::rpc_method_is_unauthorized(name);
+                        (
+                            name.to_owned(),
+                            RemoteProcedure::Method(Arc::new(move |_param, _meta| {
+                                let error = error.clone();
+                                async move { Err(error) }
+                            })),
+                        )
+                    }
+                }));
+                (rule.credential.clone(), Arc::new(restricted))
+            })
+            .collect();
+        Authenticator { handlers }
+    }
+}
+
+/// Selects the methods handler by the credential of a request.
+#[derive(Clone)]
+pub(crate) struct Authenticator {
+    handlers: Vec<(Credential, Arc<IoHandler>)>,
+}
+
+impl Authenticator {
+    /// Returns the identity, which is the index of the matched rule, and the methods handler
+    /// granted to the `Authorization` header value, or `None` if the credential is missing or
+    /// unknown.
+    pub(crate) fn authenticate(
+        &self,
+        authorization: Option<&str>,
+    ) -> Option<(usize, Arc<IoHandler>)> {
+        let credential = Credential::parse(authorization?)?;
+        self.handlers
+            .iter()
+            .position(|(expected, _)| expected.matches(&credential))
+            .map(|index| (index, Arc::clone(&self.handlers[index].1)))
+    }
+}
+
+/// Parses the header line `Authorization: <scheme> <credential>`, which is sent as the first line
+/// of a TCP connection, returns the header value.
+pub(crate) fn parse_authorization_line(line: &str) -> Option<&str> {
+    let (name, value) = line.split_once(':')?;
+    if name.trim().eq_ignore_ascii_case("authorization") {
+        Some(value)
+    } else {
+        None
+    }
+}
@@ -57,6 +57,15 @@ pub enum RPCError {
     ///
     /// Users have to edit the config file to fix the error.
     ConfigError = -7,
+    /// (-8): The credential of the request is not granted to call the RPC method.
+    ///
+    /// The access control rules are configured in `rpc.auth`, each rule grants a list of modules
+    /// and individual methods.
+    RPCMethodIsUnauthorized = -8,
+    /// (-9): The client has exceeded the rate limit of the RPC method.
+    ///
+    /// The rate limits are configured in `rpc.rate_limits`. Retry later.
+    RPCMethodIsThrottled = -9,
     /// (-101): The CKB local node failed to broadcast a message to its peers.
     P2PFailedToBroadcast = -101,
     /// (-200): Internal database error.
@@ -77,6 +86,11 @@ pub enum RPCError {
     /// This is a fatal error usually caused by the underlying database used by CKB. Please back up
     /// the data directory and re-sync the chain from scratch.
     DatabaseIsCorrupt = -202,
+    /// (-203): The requested block body has been pruned.
+    ///
+    /// The node runs in the pruned mode configured by `store.prune_depth` and only keeps the
+    /// bodies of the recent blocks. Query a full node for historical blocks and transactions.
+    BlockPruned = -203,
     /// (-301): Failed to resolve the referenced cells and headers used in the transaction, as inputs or
     /// dependencies.
     TransactionFailedToResolve = -301,
@@ -117,8 +131,14 @@ pub enum RPCError {
     PoolRejectedRBF = -1111,
     /// (-1112): The transaction is rejected for ref cell consuming.
     PoolRejectedInvalidated = -1112,
+    /// (-1113): The transaction is rejected by the tx-pool policies in the config section `tx_pool.policy`.
+    PoolRejectedByPolicy = -1113,
     /// (-1200): The indexer error.
     Indexer = -1200,
+    /// (-1300): Some messages of the subscription are missed.
+    ///
+    /// The subscription can not be resumed by `since_seq`, subscribe again without it.
+    SubscriptionGap = -1300,
 }
 
 /// Removes the backtrace portion from an error string.
@@ -190,6 +210,7 @@ impl RPCError {
             Reject::Verification(_) => RPCError::TransactionFailedToVerify,
             Reject::RBFRejected(_) => RPCError::PoolRejectedRBF,
             Reject::Invalidated(_) => RPCError::PoolRejectedInvalidated,
+            Reject::Policy(_) => RPCError::PoolRejectedByPolicy,
             Reject::ExceededTransactionSizeLimit(_, _) => {
                 RPCError::PoolRejectedTransactionBySizeLimit
             }
@@ -259,6 +280,31 @@ impl RPCError {
         )
     }
 
+    /// RPC error which indicates that the credential of the request is not granted to call the
+    /// method.
+    pub fn rpc_method_is_unauthorized(method: &str) -> Error {
+        Self::custom(
+            RPCError::RPCMethodIsUnauthorized,
+            format!(
+                "The credential is not granted to call the RPC method `{method}`. \
+                 Please check the `rpc.auth` rules in ckb.toml."
+            ),
+        )
+    }
+
+    /// RPC error which indicates that the client has exceeded the rate limit of the method.
+    pub fn rpc_method_is_throttled(method: &str) -> Error {
+        Self::custom(
+            RPCError::RPCMethodIsThrottled,
+            format!("Too many requests of the RPC method `{method}`, please retry later."),
+        )
+    }
+
+    /// RPC error which indicates that some messages of the subscription are missed.
+    pub fn subscription_gap<T: Display>(message: T) -> Error {
+        Self::custom(RPCError::SubscriptionGap, message)
+    }
+
     /// RPC error which indicates that the method is deprecated.
     ///
     /// Deprecated methods are disabled by default unless they are enabled via the config options
@@ -1,8 +1,12 @@
 //! See [module](module/index.html) for the RPC methods documentation.
 
+pub(crate) mod auth;
 pub(crate) mod error;
+pub(crate) mod rate_limit;
+pub(crate) mod replay;
 pub(crate) mod server;
 pub(crate) mod service_builder;
+pub(crate) mod tx_watch;
 pub(crate) mod util;
 
 pub mod module;
@@ -13,6 +17,7 @@ mod tests;
 use jsonrpc_core::MetaIoHandler;
 use jsonrpc_utils::pub_sub::Session;
 
+pub use crate::auth::AccessControl;
 pub use crate::error::RPCError;
 pub use crate::server::RpcServer;
 pub use crate::service_builder::ServiceBuilder;
@@ -81,6 +81,10 @@ pub trait ChainRpc {
     /// When `verbosity` is 0, it returns a 0x-prefixed hex string as the `result`. The string
     /// encodes the block serialized by molecule using schema `table Block`.
     ///
+    /// ## Errors
+    ///
+    /// * [`BlockPruned (-203)`](../enum.RPCError.html#variant.BlockPruned) - The block body has been pruned by this node.
+    ///
     /// ## Examples
     ///
     /// Request
@@ -214,6 +218,7 @@ pub trait ChainRpc {
     ///
     /// * [`ChainIndexIsInconsistent (-201)`](../enum.RPCError.html#variant.ChainIndexIsInconsistent) - The index is inconsistent. It says a block hash is in the main chain, but cannot read it from the database.
     /// * [`DatabaseIsCorrupt (-202)`](../enum.RPCError.html#variant.DatabaseIsCorrupt) - The data read from database is dirty. Please report it as a bug.
+    /// * [`BlockPruned (-203)`](../enum.RPCError.html#variant.BlockPruned) - The block body has been pruned by this node.
     ///
     /// ## Examples
     ///
@@ -555,6 +560,10 @@ pub trait ChainRpc {
     /// When verbosity is 2: if tx_status.status is pending, proposed, or committed,
     /// the RPC returns the transaction content as field transaction, otherwise the field is null.
     ///
+    /// ## Errors
+    ///
+    /// * [`BlockPruned (-203)`](../enum.RPCError.html#variant.BlockPruned) - The transaction is committed in a block whose body has been pruned by this node.
+    ///
     /// ## Examples
     ///
     /// Request
@@ -2215,6 +2224,18 @@ impl ChainRpcImpl {
                 None,
             ));
         }
+        if let Some(tx_info) = snapshot
+            .get_transaction_info(&tx_hash)
+            .filter(|tx_info| snapshot.is_block_pruned(tx_info.block_number))
+        {
+            return Err(RPCError::custom(
+                RPCError::BlockPruned,
+                format!(
+                    "transaction {:#x} is committed in the pruned block #{}",
+                    tx_hash, tx_info.block_number
+                ),
+            ));
+        }
 
         if only_committed {
             return Ok(TransactionWithStatus::with_unknown());
@@ -2246,6 +2267,15 @@ impl ChainRpcImpl {
         if !snapshot.is_main_chain(block_hash) {
             return Ok(None);
         }
+        if let Some(block_number) = snapshot
+            .get_block_number(block_hash)
+            .filter(|number| snapshot.is_block_pruned(*number))
+        {
+            return Err(RPCError::custom(
+                RPCError::BlockPruned,
+                format!("block #{block_number} {block_hash:#x} has been pruned"),
+            ));
+        }
 
         let verbosity = verbosity
             .map(|v| v.value())
@@ -1,8 +1,34 @@
+use crate::error::RPCError;
 use async_trait::async_trait;
-use ckb_jsonrpc_types::{ExtraLoggerConfig, MainLoggerConfig};
+use ckb_chain_spec::consensus::TYPE_ID_CODE_HASH;
+use ckb_jsonrpc_types::{
+    BackupInfo, ExtraLoggerConfig, MainLoggerConfig, ProcessTrace, ProgramLocation, ProgramSource,
+    ScriptGroupTrace, TransactionTrace,
+};
 use ckb_logger_service::Logger;
+use ckb_script::{
+    DataLocation, DataPieceId, ProcessEvent, ScriptGroupType, TransactionScriptsVerifier,
+    ROOT_VM_ID,
+};
+use ckb_shared::{shared::Shared, Snapshot};
+use ckb_store::{data_loader_wrapper::AsDataLoader, ChainStore};
+use ckb_types::{
+    core::{
+        cell::{resolve_transaction, CellMetaBuilder, CellProvider, CellStatus, HeaderChecker},
+        error::OutPointError,
+        ScriptHashType,
+    },
+    packed,
+    prelude::*,
+    H256,
+};
+use ckb_util::Mutex;
+use ckb_verification::TxVerifyEnv;
 use jsonrpc_core::{Error, ErrorCode::InternalError, Result};
 use jsonrpc_utils::rpc;
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Arc;
 use std::time;
 /// RPC Module Debug for internal RPC methods.
 ///
@@ -33,10 +59,165 @@ pub trait DebugRpc {
     /// Removes the logger when this is null.
     #[rpc(name = "set_extra_logger")]
     fn set_extra_logger(&self, name: String, config_opt: Option<ExtraLoggerConfig>) -> Result<()>;
+
+    /// Replays the scripts of a committed transaction and returns the execution trace of every
+    /// script group.
+    ///
+    /// The input cells and the cell deps are loaded from the chain store even if they have been
+    /// consumed, and the scripts are verified in the environment of the block which has
+    /// committed the transaction.
+    ///
+    /// ## Params
+    ///
+    /// * `tx_hash` - Hash of a committed transaction.
+    ///
+    /// ## Returns
+    ///
+    /// The consumed cycles, the exit code, the messages printed via the debug syscall, and the
+    /// processes created via spawn and exec of each script group.
+    ///
+    /// ## Errors
+    ///
+    /// * [`Invalid (-3)`](../enum.RPCError.html#variant.Invalid) - The transaction is not committed in the main chain.
+    /// * [`BlockPruned (-203)`](../enum.RPCError.html#variant.BlockPruned) - The transaction is committed in a block whose body has been pruned by this node.
+    /// * [`TransactionFailedToResolve (-301)`](../enum.RPCError.html#variant.TransactionFailedToResolve) - The referenced cells or headers are not found, e.g., they are in pruned blocks.
+    ///
+    /// ## Examples
+    ///
+    /// Request
+    ///
+    /// ```json
+    /// {
+    ///   "id": 42,
+    ///   "jsonrpc": "2.0",
+    ///   "method": "debug_trace_transaction",
+    ///   "params": [
+    ///     "0xa4037a893eb48e18ed4ef61034ce26eba9c585f15c9cee102ae58505565eccc3"
+    ///   ]
+    /// }
+    /// ```
+    ///
+    /// Response
+    ///
+    /// ```json
+    /// {
+    ///   "id": 42,
+    ///   "jsonrpc": "2.0",
+    ///   "result": {
+    ///     "transaction_hash": "0xa4037a893eb48e18ed4ef61034ce26eba9c585f15c9cee102ae58505565eccc3",
+    ///     "block_hash": "0x7978ec7ce5b507cfb52e149e36b1a23f6062ed150503c85bbf825da3599095ed",
+    ///     "script_groups": [
+    ///       {
+    ///         "script_hash": "0x8d3e3b1fa1c8d8b1e5e8bdf1a1f6d5c1b9b2e5f7a9c0d2b4e6f8a0c2e4f6a8b0",
+    ///         "group_type": "type",
+    ///         "script": {
+    ///           "code_hash": "0x28e83a1277d48add8e72fadaa9248559e1b632bab2bd60b27955ebc4c03800a5",
+    ///           "hash_type": "data",
+    ///           "args": "0x"
+    ///         },
+    ///         "input_indices": [],
+    ///         "output_indices": ["0x0"],
+    ///         "cycles": "0x2d5c",
+    ///         "exit_code": 0,
+    ///         "error": null,
+    ///         "debug_messages": ["spawned"],
+    ///         "processes": [
+    ///           {
+    ///             "process_id": "0x0",
+    ///             "parent_id": null,
+    ///             "program": null,
+    ///             "execs": [],
+    ///             "exit_code": 0
+    ///           },
+    ///           {
+    ///             "process_id": "0x1",
+    ///             "parent_id": "0x0",
+    ///             "program": {
+    ///               "source": "cell_dep",
+    ///               "index": "0x1",
+    ///               "offset": "0x0",
+    ///               "length": "0x0"
+    ///             },
+    ///             "execs": [],
+    ///             "exit_code": 0
+    ///           }
+    ///         ]
+    ///       }
+    ///     ]
+    ///   }
+    /// }
+    /// ```
+    #[rpc(name = "debug_trace_transaction")]
+    fn debug_trace_transaction(&self, tx_hash: H256) -> Result<TransactionTrace>;
+
+    /// Backs up the chain database and the freezer while the node is running.
+    ///
+    /// The backup consists of a RocksDB checkpoint, a copy of the freezer truncated to the
+    /// blocks missing from the checkpoint, and a manifest. Restore it with
+    /// `ckb backup --restore <path>` after stopping the node.
+    ///
+    /// The SST files of the checkpoint are hard links when the backup directory is on the same
+    /// filesystem as the database, so the backup is fast but shares the disk with the node.
+    ///
+    /// ## Params
+    ///
+    /// * `path` - The backup directory on the server running the CKB node, it must not exist.
+    /// A relative path is resolved against the working directory of the node.
+    ///
+    /// ## Returns
+    ///
+    /// The manifest of the backup.
+    ///
+    /// ## Errors
+    ///
+    /// * [`Invalid (-3)`](../enum.RPCError.html#variant.Invalid) - The backup directory already exists.
+    /// * [`CKBInternalError (-1)`](../enum.RPCError.html#variant.CKBInternalError) - Failed to create the backup.
+    ///
+    /// ## Examples
+    ///
+    /// Request
+    ///
+    /// ```json
+    /// {
+    ///   "id": 42,
+    ///   "jsonrpc": "2.0",
+    ///   "method": "create_backup",
+    ///   "params": [
+    ///     "/var/lib/ckb/backups/20241018"
+    ///   ]
+    /// }
+    /// ```
+    ///
+    /// Response
+    ///
+    /// ```json
+    /// {
+    ///   "id": 42,
+    ///   "jsonrpc": "2.0",
+    ///   "result": {
+    ///     "path": "/var/lib/ckb/backups/20241018",
+    ///     "genesis_hash": "0x92b197aa1fba0f63633922c61c92375c9c074a93e85963554f5499fe1450d0e5",
+    ///     "tip_number": "0x400",
+    ///     "tip_hash": "0xa5f5c85987a15de25661e5a214f2c1449cd803f071acc7999820f25246471f40",
+    ///     "migration_version": "20231101000000",
+    ///     "frozen_number": null
+    ///   }
+    /// }
+    /// ```
+    #[rpc(name = "create_backup")]
+    fn create_backup(&self, path: String) -> Result<BackupInfo>;
 }
 
 #[derive(Clone)]
-pub(crate) struct DebugRpcImpl {}
+pub(crate) struct DebugRpcImpl {
+    shared: Shared,
+}
+
+impl DebugRpcImpl {
+    pub fn new(shared: Shared) -> Self {
+        DebugRpcImpl { shared }
+    }
+}
 
 #[async_trait]
 impl DebugRpc for DebugRpcImpl {
@@ -92,4 +273,241 @@ impl DebugRpc for DebugRpcImpl {
             data: None,
         })
     }
+
+    fn debug_trace_transaction(&self, tx_hash: H256) -> Result<TransactionTrace> {
+        let snapshot = self.shared.cloned_snapshot();
+        let packed_tx_hash = tx_hash.pack();
+        let (tx, tx_info) = match snapshot.get_transaction_with_info(&packed_tx_hash) {
+            Some(found) => found,
+            None => {
+                return Err(match snapshot.get_transaction_info(&packed_tx_hash) {
+                    Some(tx_info) if snapshot.is_block_pruned(tx_info.block_number) => {
+                        RPCError::custom(
+                            RPCError::BlockPruned,
+                            format!(
+                                "transaction {:#x} is committed in the pruned block #{}",
+                                tx_hash, tx_info.block_number
+                            ),
+                        )
+                    }
+                    _ => RPCError::custom(
+                        RPCError::Invalid,
+                        format!("transaction {tx_hash:#x} is not committed in the main chain"),
+                    ),
+                });
+            }
+        };
+        let header = snapshot
+            .get_block_header(&tx_info.block_hash)
+            .ok_or_else(|| {
+                RPCError::custom(
+                    RPCError::ChainIndexIsInconsistent,
+                    format!(
+                        "transaction {:#x} is committed in block {:#x} which header is not found",
+                        tx_hash, tx_info.block_hash
+                    ),
+                )
+            })?;
+
+        let provider = HistoricalCellProvider {
+            snapshot: &snapshot,
+        };
+        let resolved =
+            resolve_transaction(tx, &mut HashSet::new(), &provider, &provider).map_err(|err| {
+                RPCError::custom_with_error(RPCError::TransactionFailedToResolve, err)
+            })?;
+
+        let consensus = snapshot.cloned_consensus();
+        let max_cycles = consensus.max_block_cycles;
+        let mut verifier = TransactionScriptsVerifier::new(
+            Arc::new(resolved),
+            snapshot.as_data_loader(),
+            consensus,
+            Arc::new(TxVerifyEnv::new_commit(&header)),
+        );
+        // script groups run one by one, so the recorded output always belongs to the current one
+        let debug_messages = Arc::new(Mutex::new(Vec::new()));
+        let process_events = Arc::new(Mutex::new(Vec::new()));
+        {
+            let debug_messages = Arc::clone(&debug_messages);
+            verifier.set_debug_printer(move |_hash: &packed::Byte32, message: &str| {
+                debug_messages.lock().push(message.to_owned());
+            });
+            let process_events = Arc::clone(&process_events);
+            verifier.set_process_tracer(move |_hash: &packed::Byte32, event: &ProcessEvent| {
+                process_events.lock().push(event.clone());
+            });
+        }
+
+        let script_groups = verifier
+            .groups_with_type()
+            .map(|(group_type, script_hash, group)| {
+                let is_type_id = group.script.code_hash() == TYPE_ID_CODE_HASH.pack()
+                    && Into::<u8>::into(group.script.hash_type())
+                        == Into::<u8>::into(ScriptHashType::Type);
+                let (cycles, exit_code, error) = if is_type_id {
+                    match verifier.verify_single(group_type, script_hash, max_cycles) {
+                        Ok(cycles) => (Some(cycles), None, None),
+                        Err(err) => (None, None, Some(err.to_string())),
+                    }
+                } else {
+                    match verifier.detailed_run(group, max_cycles) {
+                        Ok((exit_code, cycles)) => (Some(cycles), Some(exit_code), None),
+                        Err(err) => (None, None, Some(err.to_string())),
+                    }
+                };
+                let processes = if is_type_id {
+                    Vec::new()
+                } else {
+                    process_tree(&std::mem::take(&mut *process_events.lock()))
+                };
+                ScriptGroupTrace {
+                    script_hash: script_hash.unpack(),
+                    group_type: match group_type {
+                        ScriptGroupType::Lock => ckb_jsonrpc_types::ScriptGroupType::Lock,
+                        ScriptGroupType::Type => ckb_jsonrpc_types::ScriptGroupType::Type,
+                    },
+                    script: group.script.clone().into(),
+                    input_indices: group
+                        .input_indices
+                        .iter()
+                        .map(|index| (*index as u32).into())
+                        .collect(),
+                    output_indices: group
+                        .output_indices
+                        .iter()
+                        .map(|index| (*index as u32).into())
+                        .collect(),
+                    cycles: cycles.map(Into::into),
+                    exit_code,
+                    error,
+                    debug_messages: std::mem::take(&mut *debug_messages.lock()),
+                    processes,
+                }
+            })
+            .collect();
+
+        Ok(TransactionTrace {
+            transaction_hash: tx_hash,
+            block_hash: tx_info.block_hash.unpack(),
+            script_groups,
+        })
+    }
+
+    fn create_backup(&self, path: String) -> Result<BackupInfo> {
+        let target = Path::new(&path);
+        if target.exists() {
+            return Err(RPCError::custom(
+                RPCError::Invalid,
+                format!("{path} already exists"),
+            ));
+        }
+        let manifest = self
+            .shared
+            .store()
+            .create_backup(target)
+            .map_err(RPCError::ckb_internal_error)?;
+        Ok(BackupInfo {
+            path,
+            genesis_hash: manifest.genesis_hash,
+            tip_number: manifest.tip_number.into(),
+            tip_hash: manifest.tip_hash,
+            migration_version: manifest.migration_version,
+            frozen_number: manifest.frozen_number.map(Into::into),
+        })
+    }
+}
+
+// Resolves the cells created by the committed transactions, no matter whether they are live or
+// dead, so the consumed inputs of a committed transaction can be loaded again.
+struct HistoricalCellProvider<'a> {
+    snapshot: &'a Snapshot,
+}
+
+impl<'a> CellProvider for HistoricalCellProvider<'a> {
+    fn cell(&self, out_point: &packed::OutPoint, _eager_load: bool) -> CellStatus {
+        let index: usize = out_point.index().unpack();
+        self.snapshot
+            .get_transaction_with_info(&out_point.tx_hash())
+            .and_then(|(tx, tx_info)| {
+                tx.output_with_data(index).map(|(output, data)| {
+                    // always load the data eagerly, the data of dead cells are not in the store
+                    CellMetaBuilder::from_cell_output(output, data)
+                        .out_point(out_point.clone())
+                        .transaction_info(tx_info)
+                        .build()
+                })
+            })
+            .map(CellStatus::live_cell)
+            .unwrap_or(CellStatus::Unknown)
+    }
+}
+
+impl<'a> HeaderChecker for HistoricalCellProvider<'a> {
+    fn check_valid(&self, block_hash: &packed::Byte32) -> std::result::Result<(), OutPointError> {
+        self.snapshot.check_valid(block_hash)
+    }
+}
+
+// Builds the process list from the events of a script group, the root process comes first and
+// the others follow in the spawning order.
+fn process_tree(events: &[ProcessEvent]) -> Vec<ProcessTrace> {
+    let mut processes = vec![ProcessTrace {
+        process_id: ROOT_VM_ID.into(),
+        parent_id: None,
+        program: None,
+        execs: Vec::new(),
+        exit_code: None,
+    }];
+    for event in events {
+        match event {
+            ProcessEvent::Spawn {
+                parent,
+                child,
+                location,
+            } => processes.push(ProcessTrace {
+                process_id: (*child).into(),
+                parent_id: Some((*parent).into()),
+                program: Some(program_location(location)),
+                execs: Vec::new(),
+                exit_code: None,
+            }),
+            ProcessEvent::Exec { vm_id, location } => {
+                if let Some(process) = find_process(&mut processes, *vm_id) {
+                    process.execs.push(program_location(location));
+                }
+            }
+            ProcessEvent::Terminate { vm_id, exit_code } => {
+                if let Some(process) = find_process(&mut processes, *vm_id) {
+                    process.exit_code = Some(*exit_code);
+                }
+            }
+        }
+    }
+    processes
+}
+
+fn find_process(processes: &mut [ProcessTrace], vm_id: u64) -> Option<&mut ProcessTrace> {
+    processes
+        .iter_mut()
+        .find(|process| process.process_id.value() == vm_id)
+}
+
+fn program_location(location: &DataLocation) -> ProgramLocation {
+    let (source, index) = match location.data_piece_id {
+        DataPieceId::Input(index) => (ProgramSource::Input, index),
+        DataPieceId::Output(index) => (ProgramSource::Output, index),
+        DataPieceId::CellDep(index) => (ProgramSource::CellDep, index),
+        DataPieceId::GroupInput(index) => (ProgramSource::GroupInput, index),
+        DataPieceId::GroupOutput(index) => (ProgramSource::GroupOutput, index),
+        DataPieceId::Witness(index) => (ProgramSource::Witness, index),
+        DataPieceId::WitnessGroupInput(index) => (ProgramSource::WitnessGroupInput, index),
+        DataPieceId::WitnessGroupOutput(index) => (ProgramSource::WitnessGroupOutput, index),
+    };
+    ProgramLocation {
+        source,
+        index: index.into(),
+        offset: location.offset.into(),
+        length: location.length.into(),
+    }
 }
@@ -2,8 +2,8 @@ use crate::error::RPCError;
 use async_trait::async_trait;
 use ckb_indexer::IndexerHandle;
 use ckb_jsonrpc_types::{
-    IndexerCell, IndexerCellsCapacity, IndexerOrder, IndexerPagination, IndexerSearchKey,
-    IndexerTip, IndexerTx, JsonBytes, Uint32,
+    BlockNumber, IndexerCell, IndexerCellsCapacity, IndexerOrder, IndexerPagination,
+    IndexerSearchKey, IndexerTip, IndexerTx, IndexerTxHistory, JsonBytes, Uint32,
 };
 use jsonrpc_core::Result;
 use jsonrpc_utils::rpc;
@@ -51,6 +51,7 @@ pub trait IndexerRpc {
     ///
     /// * search_key:
     ///     - script - Script, supports prefix search
+    ///     - scripts - [Script], optional, more scripts searched together with `script`, at most 1000 scripts in total, only in the exact `script_search_mode`, the results of all the scripts are merged in the order of block position
     ///     - script_type - enum, lock | type
     ///     - script_search_mode - enum, prefix | exact
     ///     - filter - filter cells by following conditions, all conditions are optional
@@ -65,6 +66,11 @@ pub trait IndexerRpc {
     /// * order: enum, asc | desc
     /// * limit: result size limit
     /// * after: pagination parameter, optional
+    /// * as_of_block: block number, optional, returns the cells which were live at this block
+    ///     instead of the current live cells. The cells consumed by the transactions in the
+    ///     tx-pool are not excluded then. It must not be greater than the indexer tip, and
+    ///     must be greater than the block the indexer has been pruned to, which is about 100
+    ///     blocks behind the tip.
     ///
     /// ## Returns
     ///
@@ -400,12 +406,14 @@ pub trait IndexerRpc {
         order: IndexerOrder,
         limit: Uint32,
         after: Option<JsonBytes>,
+        as_of_block: Option<BlockNumber>,
     ) -> Result<IndexerPagination<IndexerCell>>;
 
     /// Returns the transactions collection by the lock or type script.
     ///
     /// * search_key:
     ///     - script - Script, supports prefix search when group_by_transaction is false
+    ///     - scripts - [Script], optional, more scripts searched together with `script`, at most 1000 scripts in total, only in the exact `script_search_mode`, the results of all the scripts are merged in the order of block position, and the cells of a transaction are grouped together when `group_by_transaction` is set
     ///     - script_type - enum, lock | type
     ///     - script_search_mode - enum, prefix | exact
     ///     - filter - filter cells by following conditions, all conditions are optional
@@ -824,6 +832,7 @@ pub trait IndexerRpc {
     ///
     /// * search_key:
     ///     - script - Script
+    ///     - scripts - [Script], optional, more scripts searched together with `script`, at most 1000 scripts in total, only in the exact `script_search_mode`
     ///     - script_type - enum, lock | type
     ///     - script_search_mode - enum, prefix | exact
     ///     - filter - filter cells by following conditions, all conditions are optional
@@ -834,12 +843,14 @@ pub trait IndexerRpc {
     ///         - output_data_len_range: [u64; 2], filter cells by output data len range, [inclusive, exclusive]
     ///         - output_capacity_range: [u64; 2], filter cells by output capacity range, [inclusive, exclusive]
     ///         - block_range: [u64; 2], filter cells by block number range, [inclusive, exclusive]
+    /// * as_of_block: block number, optional, returns the capacity of the cells which were live
+    ///     at this block, the limits are the same as in `get_cells`
     ///
     /// ## Returns
     ///
     ///  * capacity - total capacity
-    ///  * block_hash - indexed tip block hash
-    ///  * block_number - indexed tip block number
+    ///  * block_hash - indexed tip block hash, or the hash of `as_of_block`
+    ///  * block_number - indexed tip block number, or `as_of_block`
     ///
     /// ## Examples
     ///
@@ -880,7 +891,111 @@ pub trait IndexerRpc {
     fn get_cells_capacity(
         &self,
         search_key: IndexerSearchKey,
+        as_of_block: Option<BlockNumber>,
     ) -> Result<Option<IndexerCellsCapacity>>;
+
+    /// Returns the transactions of the lock or type script with the capacity and UDT amounts they moved.
+    ///
+    /// Only the rich-indexer is supported, the indexer prunes the consumed cells after a few blocks,
+    /// so it can't compute the amounts of the transaction inputs. See the `get_transaction_history`
+    /// of the rich-indexer for the params and the returns.
+    ///
+    /// ## Errors
+    ///
+    /// * [`Indexer (-1200)`](../enum.RPCError.html#variant.Indexer) - It is always returned by the indexer.
+    ///
+    /// ## Examples
+    ///
+    /// Request
+    ///
+    /// ```json
+    /// {
+    ///   "id": 2,
+    ///   "jsonrpc": "2.0",
+    ///   "method": "get_transaction_history",
+    ///   "params": [
+    ///     {
+    ///       "script": {
+    ///         "code_hash": "0x9bd7e06f3ecf4be0f2fcd2188b23f1b9fcc88e5d4b65a8637b17723bbda3cce8",
+    ///         "hash_type": "type",
+    ///         "args": "0x8211f1b938a107cd53b6302cc752a6fc3965638d"
+    ///       },
+    ///       "script_type": "lock"
+    ///     },
+    ///     "asc",
+    ///     "0x1"
+    ///   ]
+    /// }
+    /// ```
+    ///
+    /// Response
+    ///
+    /// ```json
+    /// {
+    ///   "jsonrpc": "2.0",
+    ///   "error": {
+    ///     "code": -1200,
+    ///     "data": null,
+    ///     "message": "Indexer: get_transaction_history is only supported by the rich-indexer"
+    ///   },
+    ///   "id": 2
+    /// }
+    /// ```
+    #[rpc(name = "get_transaction_history")]
+    fn get_transaction_history(
+        &self,
+        search_key: IndexerSearchKey,
+        order: IndexerOrder,
+        limit: Uint32,
+        after: Option<JsonBytes>,
+    ) -> Result<IndexerPagination<IndexerTxHistory>>;
+
+    /// Re-indexes the blocks in a range in the background, while the indexer keeps following the tip.
+    ///
+    /// The cells and transactions in the range which are not indexed yet are merged into the
+    /// indexer store with the current `block_filter` and `cell_filter`, so it can extend the indexer
+    /// to the blocks before `init_tip_hash`, or index the blocks again after the filters are
+    /// changed. The blocks after the range are then scanned to mark the merged cells consumed by
+    /// them. It returns immediately, the progress is reported in the node logs.
+    ///
+    /// The rich-indexer indexes all the blocks from `from` again instead, see its `reindex_blocks`.
+    ///
+    /// ## Params
+    ///
+    /// * from: the first block number of the range
+    /// * to: the last block number of the range, it must not be greater than the indexer tip
+    ///
+    /// ## Errors
+    ///
+    /// * [`Indexer (-1200)`](../enum.RPCError.html#variant.Indexer) - The range is invalid, or a re-indexing is running already.
+    ///
+    /// ## Examples
+    ///
+    /// Request
+    ///
+    /// ```json
+    /// {
+    ///     "id": 2,
+    ///     "jsonrpc": "2.0",
+    ///     "method": "reindex_blocks",
+    ///     "params": [
+    ///         "0x0",
+    ///         "0x5b513e"
+    ///     ]
+    /// }
+    /// ```
+    ///
+    /// Response
+    ///
+    /// ```json
+    /// {
+    ///     "jsonrpc": "2.0",
+    ///     "result": null,
+    ///     "id": 2
+    /// }
+    /// ```
+    #[rpc(name = "reindex_blocks")]
+    fn reindex_blocks(&self, from: BlockNumber, to: BlockNumber) -> Result<()>;
 }
 
 #[derive(Clone)]
@@ -908,9 +1023,10 @@ impl IndexerRpc for IndexerRpcImpl {
         order: IndexerOrder,
         limit: Uint32,
         after: Option<JsonBytes>,
+        as_of_block: Option<BlockNumber>,
     ) -> Result<IndexerPagination<IndexerCell>> {
         self.handle
-            .get_cells(search_key, order, limit, after)
+            .get_cells(search_key, order, limit, after, as_of_block)
             .map_err(|e| RPCError::custom(RPCError::Indexer, e))
     }
 
@@ -929,9 +1045,29 @@ impl IndexerRpc for IndexerRpcImpl {
     fn get_cells_capacity(
         &self,
         search_key: IndexerSearchKey,
+        as_of_block: Option<BlockNumber>,
     ) -> Result<Option<IndexerCellsCapacity>> {
         self.handle
-            .get_cells_capacity(search_key)
+            .get_cells_capacity(search_key, as_of_block)
+            .map_err(|e| RPCError::custom(RPCError::Indexer, e))
+    }
+
+    fn get_transaction_history(
+        &self,
+        _search_key: IndexerSearchKey,
+        _order: IndexerOrder,
+        _limit: Uint32,
+        _after: Option<JsonBytes>,
+    ) -> Result<IndexerPagination<IndexerTxHistory>> {
+        Err(RPCError::custom(
+            RPCError::Indexer,
+            "get_transaction_history is only supported by the rich-indexer",
+        ))
+    }
+
+    fn reindex_blocks(&self, from: BlockNumber, to: BlockNumber) -> Result<()> {
+        self.handle
+            .reindex(from.into(), to.into())
             .map_err(|e| RPCError::custom(RPCError::Indexer, e))
     }
 }
@@ -15,6 +15,11 @@ use jsonrpc_utils::rpc;
 use std::collections::HashSet;
 use std::fmt::Debug;
 use std::sync::Arc;
+use std::time::Duration;
+
+// The default and the max time to wait in wait_block_template, unit: Millisecond
+const DEFAULT_WAIT_BLOCK_TEMPLATE_TIMEOUT: u64 = 30_000;
+const MAX_WAIT_BLOCK_TEMPLATE_TIMEOUT: u64 = 120_000;
 
 /// RPC Module Miner for miners.
 ///
@@ -141,6 +146,135 @@ pub trait MinerRpc {
         max_version: Option<Version>,
     ) -> Result<BlockTemplate>;
 
+    /// Waits until the block template is updated, and returns the new one.
+    ///
+    /// It is the long-polling variant of
+    /// [`get_block_template`](#tymethod.get_block_template). The template is returned immediately
+    /// if its work ID is not `work_id` already, otherwise the RPC waits until the block assembler
+    /// updates the template or `timeout` is reached, and returns the current template in both
+    /// cases. Miners can call it in a loop with the work ID of the last template, so they get the
+    /// fresh work without the polling latency.
+    ///
+    /// ## Params
+    ///
+    /// * `work_id` - the work ID of the template the miner is working on.
+    /// * `timeout` - the max time to wait in milliseconds.
+    ///     (**Optional:** the default is 30000, and the value is capped at 120000.)
+    /// * `bytes_limit` - the max serialization size in bytes of the block.
+    ///     (**Optional:** the default is the consensus limit.)
+    /// * `proposals_limit` - the max count of proposals.
+    ///     (**Optional:** the default is the consensus limit.)
+    /// * `max_version` - the max block version.
+    ///     (**Optional:** the default is one configured in the current client version.)
+    ///
+    /// ## Examples
+    ///
+    /// Request
+    ///
+    /// ```json
+    /// {
+    ///   "id": 42,
+    ///   "jsonrpc": "2.0",
+    ///   "method": "wait_block_template",
+    ///   "params": [
+    ///     "0x0",
+    ///     "0x7530",
+    ///     null,
+    ///     null,
+    ///     null
+    ///   ]
+    /// }
+    /// ```
+    ///
+    /// Response
+    ///
+    /// ```json
+    /// {
+    ///   "id": 42,
+    ///   "jsonrpc": "2.0",
+    ///   "result": {
+    ///     "bytes_limit": "0x91c08",
+    ///     "cellbase": {
+    ///       "cycles": null,
+    ///       "data": {
+    ///         "cell_deps": [],
+    ///         "header_deps": [],
+    ///         "inputs": [
+    ///           {
+    ///             "previous_output": {
+    ///               "index": "0xffffffff",
+    ///               "tx_hash": "0x0000000000000000000000000000000000000000000000000000000000000000"
+    ///             },
+    ///             "since": "0x401"
+    ///           }
+    ///         ],
+    ///         "outputs": [
+    ///           {
+    ///             "capacity": "0x18e64efc04",
+    ///             "lock": {
+    ///               "code_hash": "0x28e83a1277d48add8e72fadaa9248559e1b632bab2bd60b27955ebc4c03800a5",
+    ///               "hash_type": "data",
+    ///               "args": "0x"
+    ///             },
+    ///             "type": null
+    ///           }
+    ///         ],
+    ///         "outputs_data": [
+    ///           "0x"
+    ///         ],
+    ///         "version": "0x0",
+    ///         "witnesses": [
+    ///           "0x6a0000000c00000055000000490000001000000030000000310000001892ea40d82b53c678ff88312450bbb17e164d7a3e0a90941aa58839f56f8df20114000000b2e61ff569acf041b3c2c17724e2379c581eeac311000000000000002054455354206d657373616765"
+    ///         ]
+    ///       },
+    ///       "hash": "0xbaf7e4db2fd002f19a597ca1a31dfe8cfe26ed8cebc91f52b75b16a7a5ec8bab"
+    ///     },
+    ///     "compact_target": "0x1e083126",
+    ///     "current_time": "0x174c45e17a3",
+    ///     "cycles_limit": "0xd09dc300",
+    ///     "dao": "0xd495a106684401001e47c0ae1d5930009449d26e32380000000721efd0030000",
+    ///     "epoch": "0x7080019000001",
+    ///     "extension": "0xb0a0079f3778c0ba0d89d88b389c602cc18b8a0355d16c0713f8bfcee64b5f84",
+    ///     "number": "0x401",
+    ///     "parent_hash": "0xa5f5c85987a15de25661e5a214f2c1449cd803f071acc7999820f25246471f40",
+    ///     "proposals": ["0xa0ef4eb5f4ceeb08a4c8"],
+    ///     "transactions": [],
+    ///     "uncles": [
+    ///       {
+    ///         "hash": "0xdca341a42890536551f99357612cef7148ed471e3b6419d0844a4e400be6ee94",
+    ///         "header": {
+    ///           "compact_target": "0x1e083126",
+    ///           "dao": "0xb5a3e047474401001bc476b9ee573000c0c387962a38000000febffacf030000",
+    ///           "epoch": "0x7080018000001",
+    ///           "extra_hash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+    ///           "nonce": "0x0",
+    ///           "number": "0x400",
+    ///           "parent_hash": "0xae003585fa15309b30b31aed3dcf385e9472c3c3e93746a6c4540629a6a1ed2d",
+    ///           "proposals_hash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+    ///           "timestamp": "0x5cd2b118",
+    ///           "transactions_root": "0xc47d5b78b3c4c4c853e2a32810818940d0ee403423bea9ec7b8e566d9595206c",
+    ///           "version":"0x0"
+    ///         },
+    ///         "proposals": [],
+    ///         "required": false
+    ///       }
+    ///     ],
+    ///     "uncles_count_limit": "0x2",
+    ///     "version": "0x0",
+    ///     "work_id": "0x1"
+    ///   }
+    /// }
+    /// ```
+    #[rpc(name = "wait_block_template")]
+    async fn wait_block_template(
+        &self,
+        work_id: Uint64,
+        timeout: Option<Uint64>,
+        bytes_limit: Option<Uint64>,
+        proposals_limit: Option<Uint64>,
+        max_version: Option<Version>,
+    ) -> Result<BlockTemplate>;
+
     /// Submit new block to the network.
     ///
     /// ## Params
@@ -257,6 +391,34 @@ impl MinerRpc for MinerRpcImpl {
             })
     }
 
+    async fn wait_block_template(
+        &self,
+        work_id: Uint64,
+        timeout: Option<Uint64>,
+        bytes_limit: Option<Uint64>,
+        proposals_limit: Option<Uint64>,
+        max_version: Option<Version>,
+    ) -> Result<BlockTemplate> {
+        let work_id: u64 = work_id.into();
+        let timeout = timeout
+            .map(Into::into)
+            .unwrap_or(DEFAULT_WAIT_BLOCK_TEMPLATE_TIMEOUT)
+            .min(MAX_WAIT_BLOCK_TEMPLATE_TIMEOUT);
+
+        // the disabled block assembler is reported by get_block_template
+        if let Some(mut template_updated) =
+            self.shared.tx_pool_controller().subscribe_block_template()
+        {
+            // the current template is returned on timeout as well
+            let _ = tokio::time::timeout(
+                Duration::from_millis(timeout),
+                template_updated.wait_for(|current| *current != work_id),
+            )
+            .await;
+        }
+        self.get_block_template(bytes_limit, proposals_limit, max_version)
+    }
+
     fn submit_block(&self, work_id: String, block: Block) -> Result<H256> {
         let block: packed::Block = block.into();
         let block: Arc<core::BlockView> = Arc::new(block.into_view());
@@ -2,10 +2,12 @@ use crate::error::RPCError;
 use async_trait::async_trait;
 use ckb_chain::ChainController;
 use ckb_jsonrpc_types::{
-    BannedAddr, LocalNode, LocalNodeProtocol, NodeAddress, PeerSyncState, RemoteNode,
-    RemoteNodeProtocol, SyncState, Timestamp,
+    BanCause, BannedAddr, LocalNode, LocalNodeProtocol, NodeAddress, PeerBehaviour, PeerReport,
+    PeerReputation, PeerSyncState, RemoteNode, RemoteNodeProtocol, SyncState, Timestamp, Uint64,
+};
+use ckb_network::{
+    extract_peer_id, multiaddr::Multiaddr, peer_store::types, Behaviour, NetworkController,
 };
-use ckb_network::{extract_peer_id, multiaddr::Multiaddr, NetworkController};
 use ckb_sync::SyncShared;
 use ckb_systemtime::unix_time_as_millis;
 use ckb_types::prelude::{Pack, Unpack};
@@ -251,7 +253,9 @@ pub trait NetRpc {
     ///       "address": "192.168.0.2/32",
     ///       "ban_reason": "",
     ///       "ban_until": "0x1ac89236180",
-    ///       "created_at": "0x16bde533338"
+    ///       "cause": "manual",
+    ///       "created_at": "0x16bde533338",
+    ///       "reports": []
     ///     }
     ///   ]
     /// }
@@ -342,6 +346,155 @@ pub trait NetRpc {
         reason: Option<String>,
     ) -> Result<()>;
 
+    /// Returns the reputation of a peer address, including its score, the history of the
+    /// reported behaviours and the ban which covers it.
+    ///
+    /// ## Params
+    ///
+    /// * `address` - The P2P address with the peer ID, the same as the one in the result of
+    ///   `get_peers`.
+    ///
+    /// ## Errors
+    ///
+    /// * [`InvalidParams (-32602)`](../enum.RPCError.html#variant.InvalidParams)
+    ///     * Expected `address` to be a valid P2P address.
+    ///
+    /// ## Examples
+    ///
+    /// Request
+    ///
+    /// ```json
+    /// {
+    ///   "id": 42,
+    ///   "jsonrpc": "2.0",
+    ///   "method": "get_peer_reputation",
+    ///   "params": [
+    ///     "/ip4/192.168.0.2/tcp/8112/p2p/QmTRHCdrRtgUzYLNCin69zEvPvLYdxUZLLfLYyHVY3DZAS"
+    ///   ]
+    /// }
+    /// ```
+    ///
+    /// Response
+    ///
+    /// ```json
+    /// {
+    ///   "id": 42,
+    ///   "jsonrpc": "2.0",
+    ///   "result": {
+    ///     "address": "/ip4/192.168.0.2/tcp/8112/p2p/QmTRHCdrRtgUzYLNCin69zEvPvLYdxUZLLfLYyHVY3DZAS",
+    ///     "banned": {
+    ///       "address": "192.168.0.2/32",
+    ///       "ban_reason": "ProtocolError id=100",
+    ///       "ban_until": "0x1ac89236180",
+    ///       "cause": "misbehave",
+    ///       "created_at": "0x16bde533338",
+    ///       "reports": []
+    ///     },
+    ///     "reports": [],
+    ///     "score": null
+    ///   }
+    /// }
+    /// ```
+    #[rpc(name = "get_peer_reputation")]
+    fn get_peer_reputation(&self, address: String) -> Result<PeerReputation>;
+
+    /// Exports the banned IPs/Subnets which are not expired yet.
+    ///
+    /// The result can be passed to `import_banned_addresses` on other nodes.
+    ///
+    /// ## Returns
+    ///
+    /// The unexpired banned IPs/Subnets, with their causes and reported behaviours.
+    ///
+    /// ## Examples
+    ///
+    /// Request
+    ///
+    /// ```json
+    /// {
+    ///   "id": 42,
+    ///   "jsonrpc": "2.0",
+    ///   "method": "export_banned_addresses",
+    ///   "params": []
+    /// }
+    /// ```
+    ///
+    /// Response
+    ///
+    /// ```json
+    /// {
+    ///   "id": 42,
+    ///   "jsonrpc": "2.0",
+    ///   "result": [
+    ///     {
+    ///       "address": "192.168.0.2/32",
+    ///       "ban_reason": "",
+    ///       "ban_until": "0x1ac89236180",
+    ///       "cause": "manual",
+    ///       "created_at": "0x16bde533338",
+    ///       "reports": []
+    ///     }
+    ///   ]
+    /// }
+    /// ```
+    #[rpc(name = "export_banned_addresses")]
+    fn export_banned_addresses(&self) -> Result<Vec<BannedAddr>>;
+
+    /// Imports the banned IPs/Subnets exported by `export_banned_addresses`.
+    ///
+    /// The imported bans are merged into the banned list and the connected peers in them are
+    /// disconnected. The expired bans are skipped.
+    ///
+    /// ## Params
+    ///
+    /// * `banned_addresses` - The banned IPs/Subnets, such as the result of
+    ///   `export_banned_addresses` on other nodes.
+    ///
+    /// ## Returns
+    ///
+    /// The number of the imported banned IPs/Subnets.
+    ///
+    /// ## Errors
+    ///
+    /// * [`InvalidParams (-32602)`](../enum.RPCError.html#variant.InvalidParams)
+    ///     * Expected the `address` of each ban to be a valid IP address or subnet.
+    ///
+    /// ## Examples
+    ///
+    /// Request
+    ///
+    /// ```json
+    /// {
+    ///   "id": 42,
+    ///   "jsonrpc": "2.0",
+    ///   "method": "import_banned_addresses",
+    ///   "params": [
+    ///     [
+    ///       {
+    ///         "address": "192.168.0.2/32",
+    ///         "ban_reason": "",
+    ///         "ban_until": "0x1ac89236180",
+    ///         "cause": "manual",
+    ///         "created_at": "0x16bde533338",
+    ///         "reports": []
+    ///       }
+    ///     ]
+    ///   ]
+    /// }
+    /// ```
+    ///
+    /// Response
+    ///
+    /// ```json
+    /// {
+    ///   "id": 42,
+    ///   "jsonrpc": "2.0",
+    ///   "result": "0x1"
+    /// }
+    /// ```
+    #[rpc(name = "import_banned_addresses")]
+    fn import_banned_addresses(&self, banned_addresses: Vec<BannedAddr>) -> Result<Uint64>;
+
     /// Returns chain synchronization state of this node.
     ///
     /// ## Examples
@@ -674,12 +827,7 @@ impl NetRpc for NetRpcImpl {
             .network_controller
             .get_banned_addrs()
             .into_iter()
-            .map(|banned| BannedAddr {
-                address: banned.address.to_string(),
-                ban_until: banned.ban_until.into(),
-                ban_reason: banned.ban_reason,
-                created_at: banned.created_at.into(),
-            })
+            .map(banned_addr_to_json)
             .collect())
     }
 
@@ -726,6 +874,46 @@ impl NetRpc for NetRpcImpl {
         }
     }
 
+    fn get_peer_reputation(&self, address: String) -> Result<PeerReputation> {
+        let addr: Multiaddr = address.parse().map_err(|_| {
+            RPCError::invalid_params(format!(
+                "Expected `params[0]` to be a valid P2P address, got {address}"
+            ))
+        })?;
+        let addr_info = self.network_controller.addr_info(&addr);
+        let banned = self
+            .network_controller
+            .get_banned_addr(&addr)
+            .map(banned_addr_to_json);
+
+        Ok(PeerReputation {
+            address,
+            score: addr_info.as_ref().map(|info| info.score),
+            reports: addr_info
+                .map(|info| info.reports.into_iter().map(report_to_json).collect())
+                .unwrap_or_default(),
+            banned,
+        })
+    }
+
+    fn export_banned_addresses(&self) -> Result<Vec<BannedAddr>> {
+        Ok(self
+            .network_controller
+            .export_ban_list()
+            .into_iter()
+            .map(banned_addr_to_json)
+            .collect())
+    }
+
+    fn import_banned_addresses(&self, banned_addresses: Vec<BannedAddr>) -> Result<Uint64> {
+        let banned_addrs = banned_addresses
+            .into_iter()
+            .map(banned_addr_from_json)
+            .collect::<Result<Vec<_>>>()?;
+        let count = self.network_controller.import_ban_list(banned_addrs);
+        Ok((count as u64).into())
+    }
+
     fn sync_state(&self) -> Result<SyncState> {
         let chain = self.sync_shared.active_chain();
         let shared = chain.shared();
@@ -796,3 +984,75 @@ impl NetRpc for NetRpcImpl {
         Ok(())
     }
 }
+
+fn banned_addr_to_json(banned: types::BannedAddr) -> BannedAddr {
+    BannedAddr {
+        address: banned.address.to_string(),
+        ban_until: banned.ban_until.into(),
+        ban_reason: banned.ban_reason,
+        created_at: banned.created_at.into(),
+        cause: match banned.cause {
+            types::BanCause::Behaviour(behaviour) => {
+                BanCause::Behaviour(behaviour_to_json(behaviour))
+            }
+            types::BanCause::Misbehave => BanCause::Misbehave,
+            types::BanCause::Manual => BanCause::Manual,
+            types::BanCause::Unknown => BanCause::Unknown,
+        },
+        reports: banned.reports.into_iter().map(report_to_json).collect(),
+    }
+}
+
+fn banned_addr_from_json(banned: BannedAddr) -> Result<types::BannedAddr> {
+    let address = banned.address.parse().map_err(|_| {
+        RPCError::invalid_params(format!(
+            "Expected `address` to be a valid IP address, got {}",
+            banned.address
+        ))
+    })?;
+    Ok(types::BannedAddr {
+        address,
+        ban_until: banned.ban_until.into(),
+        ban_reason: banned.ban_reason,
+        created_at: banned.created_at.into(),
+        cause: match banned.cause {
+            BanCause::Behaviour(behaviour) => {
+                types::BanCause::Behaviour(behaviour_from_json(behaviour))
+            }
+            BanCause::Misbehave => types::BanCause::Misbehave,
+            BanCause::Manual => types::BanCause::Manual,
+            BanCause::Unknown => types::BanCause::Unknown,
+        },
+        reports: banned
+            .reports
+            .into_iter()
+            .map(|report| types::BehaviourReport {
+                behaviour: behaviour_from_json(report.behaviour),
+                score: report.score,
+                reported_at: report.reported_at.into(),
+            })
+            .collect(),
+    })
+}
+
+fn report_to_json(report: types::BehaviourReport) -> PeerReport {
+    PeerReport {
+        behaviour: behaviour_to_json(report.behaviour),
+        score: report.score,
+        reported_at: report.reported_at.into(),
+    }
+}
+
+fn behaviour_to_json(behaviour: Behaviour) -> PeerBehaviour {
+    match behaviour {
+        Behaviour::Timeout => PeerBehaviour::Timeout,
+        Behaviour::Misbehave => PeerBehaviour::Misbehave,
+    }
+}
+
+fn behaviour_from_json(behaviour: PeerBehaviour) -> Behaviour {
+    match behaviour {
+        PeerBehaviour::Timeout => Behaviour::Timeout,
+        PeerBehaviour::Misbehave => Behaviour::Misbehave,
+    }
+}
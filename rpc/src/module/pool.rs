@@ -110,6 +110,109 @@ pub trait PoolRpc {
         outputs_validator: Option<OutputsValidator>,
     ) -> Result<H256>;
 
+    /// Submits a package of transactions into the transaction pool, parents first.
+    ///
+    /// The package is verified and accepted as a whole. Instead of each transaction, it is the
+    /// fee rate of the package that must reach `tx_pool.min_fee_rate`, so a child transaction can
+    /// pay for its parents which are stuck because of the low fee. The transactions already in
+    /// the pool are skipped. Replacing the conflicted transactions in the pool is not supported.
+    ///
+    /// The package is relayed as a whole to the peers supporting package relay.
+    ///
+    /// ## Params
+    ///
+    /// * `transactions` - The transactions, parents first. Every transaction except the last one must be spent by a later one. At most 25 transactions.
+    /// * `outputs_validator` - Validates the transactions outputs before entering the tx-pool. (**Optional**, default is "passthrough").
+    ///
+    /// ## Returns
+    ///
+    /// The hashes of the transactions in the package.
+    ///
+    /// ## Errors
+    ///
+    /// * [`PoolRejectedTransactionByOutputsValidator (-1102)`](../enum.RPCError.html#variant.PoolRejectedTransactionByOutputsValidator) - A transaction is rejected by the validator specified by `outputs_validator`.
+    /// * [`PoolRejectedTransactionByMinFeeRate (-1104)`](../enum.RPCError.html#variant.PoolRejectedTransactionByMinFeeRate) - The package fee rate must be greater than or equal to the config option `tx_pool.min_fee_rate`.
+    /// * [`PoolRejectedTransactionByMaxAncestorsCountLimit (-1105)`](../enum.RPCError.html#variant.PoolRejectedTransactionByMaxAncestorsCountLimit) - The ancestors count must be greater than or equal to the config option `tx_pool.max_ancestors_count`.
+    /// * [`PoolIsFull (-1106)`](../enum.RPCError.html#variant.PoolIsFull) - Pool is full.
+    /// * [`PoolRejectedDuplicatedTransaction (-1107)`](../enum.RPCError.html#variant.PoolRejectedDuplicatedTransaction) - All the transactions are already in the pool.
+    /// * [`PoolRejectedMalformedTransaction (-1108)`](../enum.RPCError.html#variant.PoolRejectedMalformedTransaction) - The package is not parents first or not connected.
+    /// * [`TransactionFailedToResolve (-301)`](../enum.RPCError.html#variant.TransactionFailedToResolve) - Failed to resolve the referenced cells and headers, or a transaction conflicts with the pool.
+    /// * [`TransactionFailedToVerify (-302)`](../enum.RPCError.html#variant.TransactionFailedToVerify) - Failed to verify a transaction.
+    ///
+    /// ## Examples
+    ///
+    /// Request
+    ///
+    /// ```json
+    /// {
+    ///   "id": 42,
+    ///   "jsonrpc": "2.0",
+    ///   "method": "send_transaction_package",
+    ///   "params": [
+    ///     [
+    ///       {
+    ///         "cell_deps": [
+    ///           {
+    ///             "dep_type": "code",
+    ///             "out_point": {
+    ///               "index": "0x0",
+    ///               "tx_hash": "0xa4037a893eb48e18ed4ef61034ce26eba9c585f15c9cee102ae58505565eccc3"
+    ///             }
+    ///           }
+    ///         ],
+    ///         "header_deps": [
+    ///           "0x7978ec7ce5b507cfb52e149e36b1a23f6062ed150503c85bbf825da3599095ed"
+    ///         ],
+    ///         "inputs": [
+    ///           {
+    ///             "previous_output": {
+    ///               "index": "0x0",
+    ///               "tx_hash": "0x365698b50ca0da75dca2c87f9e7b563811d3b5813736b8cc62cc3b106faceb17"
+    ///             },
+    ///             "since": "0x0"
+    ///           }
+    ///         ],
+    ///         "outputs": [
+    ///           {
+    ///             "capacity": "0x2540be400",
+    ///             "lock": {
+    ///               "code_hash": "0x28e83a1277d48add8e72fadaa9248559e1b632bab2bd60b27955ebc4c03800a5",
+    ///               "hash_type": "data",
+    ///               "args": "0x"
+    ///             },
+    ///             "type": null
+    ///           }
+    ///         ],
+    ///         "outputs_data": [
+    ///           "0x"
+    ///         ],
+    ///         "version": "0x0",
+    ///         "witnesses": []
+    ///       }
+    ///     ],
+    ///     "passthrough"
+    ///   ]
+    /// }
+    /// ```
+    ///
+    /// Response
+    ///
+    /// ```json
+    /// {
+    ///   "id": 42,
+    ///   "jsonrpc": "2.0",
+    ///   "result": [
+    ///     "0xa0ef4eb5f4ceeb08a4c8524d84c5da95dce2f608e0ca2ec8091191b0f330c6e3"
+    ///   ]
+    /// }
+    /// ```
+    #[rpc(name = "send_transaction_package")]
+    fn send_transaction_package(
+        &self,
+        txs: Vec<Transaction>,
+        outputs_validator: Option<OutputsValidator>,
+    ) -> Result<Vec<H256>>;
+
     /// Test if a transaction can be accepted by the transaction pool without inserting it into the pool or rebroadcasting it to peers.
     /// The parameters and errors of this method are the same as `send_transaction`.
     ///
@@ -632,6 +735,31 @@ impl PoolRpc for PoolRpcImpl {
         }
     }
 
+    fn send_transaction_package(
+        &self,
+        txs: Vec<Transaction>,
+        outputs_validator: Option<OutputsValidator>,
+    ) -> Result<Vec<H256>> {
+        let txs: Vec<core::TransactionView> = txs
+            .into_iter()
+            .map(|tx| packed::Transaction::from(tx).into_view())
+            .collect();
+        for tx in &txs {
+            self.check_output_validator(outputs_validator.clone(), tx)?;
+        }
+
+        let tx_hashes: Vec<H256> = txs.iter().map(|tx| tx.hash().unpack()).collect();
+        let tx_pool = self.shared.tx_pool_controller();
+        let submit_package = tx_pool.submit_local_package(txs).map_err(|e| {
+            error!("Send submit_package request error {}", e);
+            RPCError::ckb_internal_error(e)
+        })?;
+
+        submit_package
+            .map(|_| tx_hashes)
+            .map_err(|reject| RPCError::from_submit_transaction_reject(&reject))
+    }
+
     fn test_tx_pool_accept(
         &self,
         tx: Transaction,
@@ -1,8 +1,9 @@
 use crate::error::RPCError;
 use async_trait::async_trait;
 use ckb_jsonrpc_types::{
-    IndexerCell, IndexerCellsCapacity, IndexerOrder, IndexerPagination, IndexerSearchKey,
-    IndexerTip, IndexerTx, JsonBytes, Uint32,
+    BlockNumber, IndexerCell, IndexerCellsCapacity, IndexerOrder, IndexerPagination,
+    IndexerSearchKey, IndexerTip, IndexerTx, IndexerTxHistory, IndexerUdtBalance, IndexerUdtHolder,
+    JsonBytes, Script, Uint32,
 };
 use ckb_rich_indexer::AsyncRichIndexerHandle;
 use jsonrpc_core::Result;
@@ -32,6 +33,7 @@ pub trait RichIndexerRpc {
     ///
     /// * search_key:
     ///     - script - Script, supports prefix search
+    ///     - scripts - [Script], optional, more scripts searched together with `script`, at most 1000 scripts in total, the results are ordered by block position
     ///     - script_type - enum, lock | type
     ///     - script_search_mode - enum, prefix | exact | partial
     ///     - filter - filter cells by following conditions, all conditions are optional
@@ -46,6 +48,10 @@ pub trait RichIndexerRpc {
     /// * order: enum, asc | desc
     /// * limit: result size limit
     /// * after: pagination parameter, optional
+    /// * as_of_block: block number, optional, returns the cells which were live at this block
+    ///     instead of the current live cells. The cells consumed by the transactions in the
+    ///     tx-pool are not excluded then. It must not be greater than the indexer tip, nor less
+    ///     than the first block the rich-indexer has indexed.
     ///
     /// ## Returns
     ///
@@ -69,6 +75,7 @@ pub trait RichIndexerRpc {
         order: IndexerOrder,
         limit: Uint32,
         after: Option<JsonBytes>,
+        as_of_block: Option<BlockNumber>,
     ) -> Result<IndexerPagination<IndexerCell>>;
 
     /// Returns the transactions collection by the lock or type script.
@@ -77,6 +84,7 @@ pub trait RichIndexerRpc {
     ///
     /// * search_key:
     ///     - script - Script, supports prefix search when group_by_transaction is false
+    ///     - scripts - [Script], optional, more scripts searched together with `script`, at most 1000 scripts in total, the results are ordered by block position
     ///     - script_type - enum, lock | type
     ///     - script_search_mode - enum, prefix | exact | partial
     ///     - filter - filter cells by following conditions, all conditions are optional
@@ -130,6 +138,7 @@ pub trait RichIndexerRpc {
     ///
     /// * search_key:
     ///     - script - Script
+    ///     - scripts - [Script], optional, more scripts searched together with `script`, at most 1000 scripts in total
     ///     - script_type - enum, lock | type
     ///     - script_search_mode - enum, prefix | exact | partial
     ///     - filter - filter cells by following conditions, all conditions are optional
@@ -140,12 +149,14 @@ pub trait RichIndexerRpc {
     ///         - output_data_len_range: [u64; 2], filter cells by output data len range, [inclusive, exclusive]
     ///         - output_capacity_range: [u64; 2], filter cells by output capacity range, [inclusive, exclusive]
     ///         - block_range: [u64; 2], filter cells by block number range, [inclusive, exclusive]
+    /// * as_of_block: block number, optional, returns the capacity of the cells which were live
+    ///     at this block, the limits are the same as in `get_cells`
     ///
     /// ## Returns
     ///
     ///  * capacity - total capacity
-    ///  * block_hash - indexed tip block hash
-    ///  * block_number - indexed tip block number
+    ///  * block_hash - indexed tip block hash, or the hash of `as_of_block`
+    ///  * block_number - indexed tip block number, or `as_of_block`
     ///
     /// ## Examples
     ///
@@ -154,7 +165,291 @@ pub trait RichIndexerRpc {
     async fn get_cells_capacity(
         &self,
         search_key: IndexerSearchKey,
+        as_of_block: Option<BlockNumber>,
     ) -> Result<Option<IndexerCellsCapacity>>;
+
+    /// Returns the UDT balances of a lock script.
+    ///
+    /// The rich-indexer aggregates the live cells of the UDT type scripts configured by
+    /// `udt_scripts` in `[indexer_v2.rich_indexer]`, which are the sUDT and xUDT deployments on the
+    /// mainnet and the testnet by default. The amount of a UDT cell is the first 16 bytes of the
+    /// cell data as a little-endian u128, the cells with less data are skipped.
+    ///
+    /// ## Params
+    ///
+    /// * lock_script - Script, the exact lock script
+    /// * order - enum, asc | desc, the order of the UDT type scripts in the rich-indexer
+    /// * limit - result size limit
+    /// * after - pagination parameter, optional
+    ///
+    /// ## Returns
+    ///
+    /// If the number of objects is less than the requested `limit`, it indicates that these are the last page of get_udt_balances.
+    ///
+    /// * objects:
+    ///     - type_script - the UDT type script
+    ///     - amount - the total amount of the live cells, saturated at u128::MAX
+    ///     - cell_count - the number of the live cells
+    /// * last_cursor - pagination parameter
+    ///
+    /// ## Examples
+    ///
+    /// Request
+    ///
+    /// ```json
+    /// {
+    ///   "id": 42,
+    ///   "jsonrpc": "2.0",
+    ///   "method": "get_udt_balances",
+    ///   "params": [
+    ///     {
+    ///       "code_hash": "0x9bd7e06f3ecf4be0f2fcd2188b23f1b9fcc88e5d4b65a8637b17723bbda3cce8",
+    ///       "hash_type": "type",
+    ///       "args": "0x8211f1b938a107cd53b6302cc752a6fc3965638d"
+    ///     },
+    ///     "asc",
+    ///     "0x64"
+    ///   ]
+    /// }
+    /// ```
+    ///
+    /// Response
+    ///
+    /// ```json
+    /// {
+    ///   "jsonrpc": "2.0",
+    ///   "result": {
+    ///     "last_cursor": "0x0500000000000000",
+    ///     "objects": [
+    ///       {
+    ///         "amount": "0x2540be400",
+    ///         "cell_count": "0x2",
+    ///         "type_script": {
+    ///           "args": "0x57dd0067814dab356e05c6def0d094bb79776711e68ffdfad2df6a7f877f7db6",
+    ///           "code_hash": "0x50bd8d6680b8b9cf98b73f3c08faf8b2a21914311954118ad6609be6e78a1b95",
+    ///           "hash_type": "data1"
+    ///         }
+    ///       }
+    ///     ]
+    ///   },
+    ///   "id": 42
+    /// }
+    /// ```
+    #[rpc(name = "get_udt_balances")]
+    async fn get_udt_balances(
+        &self,
+        lock_script: Script,
+        order: IndexerOrder,
+        limit: Uint32,
+        after: Option<JsonBytes>,
+    ) -> Result<IndexerPagination<IndexerUdtBalance>>;
+
+    /// Returns the holders of a UDT ordered by their amounts.
+    ///
+    /// The balances are aggregated in the same way as `get_udt_balances`, a holder is a lock
+    /// script owning at least one live cell of the UDT.
+    ///
+    /// ## Params
+    ///
+    /// * type_script - Script, the exact UDT type script
+    /// * order - enum, asc | desc, the order of the amounts
+    /// * limit - result size limit
+    /// * after - pagination parameter, optional
+    ///
+    /// ## Returns
+    ///
+    /// If the number of objects is less than the requested `limit`, it indicates that these are the last page of get_udt_holders.
+    ///
+    /// * objects:
+    ///     - lock_script - the holder lock script
+    ///     - amount - the total amount of the live cells, saturated at u128::MAX
+    ///     - cell_count - the number of the live cells
+    /// * last_cursor - pagination parameter
+    ///
+    /// ## Examples
+    ///
+    /// Request
+    ///
+    /// ```json
+    /// {
+    ///   "id": 42,
+    ///   "jsonrpc": "2.0",
+    ///   "method": "get_udt_holders",
+    ///   "params": [
+    ///     {
+    ///       "code_hash": "0x50bd8d6680b8b9cf98b73f3c08faf8b2a21914311954118ad6609be6e78a1b95",
+    ///       "hash_type": "data1",
+    ///       "args": "0x57dd0067814dab356e05c6def0d094bb79776711e68ffdfad2df6a7f877f7db6"
+    ///     },
+    ///     "desc",
+    ///     "0x1"
+    ///   ]
+    /// }
+    /// ```
+    ///
+    /// Response
+    ///
+    /// ```json
+    /// {
+    ///   "jsonrpc": "2.0",
+    ///   "result": {
+    ///     "last_cursor": "0x00000000000000000000000000000000000000000000000000000002540be4000300000000000000",
+    ///     "objects": [
+    ///       {
+    ///         "amount": "0x2540be400",
+    ///         "cell_count": "0x2",
+    ///         "lock_script": {
+    ///           "args": "0x8211f1b938a107cd53b6302cc752a6fc3965638d",
+    ///           "code_hash": "0x9bd7e06f3ecf4be0f2fcd2188b23f1b9fcc88e5d4b65a8637b17723bbda3cce8",
+    ///           "hash_type": "type"
+    ///         }
+    ///       }
+    ///     ]
+    ///   },
+    ///   "id": 42
+    /// }
+    /// ```
+    #[rpc(name = "get_udt_holders")]
+    async fn get_udt_holders(
+        &self,
+        type_script: Script,
+        order: IndexerOrder,
+        limit: Uint32,
+        after: Option<JsonBytes>,
+    ) -> Result<IndexerPagination<IndexerUdtHolder>>;
+
+    /// Returns the transactions of the lock or type script with the capacity and UDT amounts they moved.
+    ///
+    /// The amounts are computed from the cells stored by the rich-indexer, the clients don't need to
+    /// fetch the previous outputs of the transactions.
+    ///
+    /// ## Params
+    ///
+    /// * search_key - the same as `get_transactions`, the transactions are always grouped by the tx hash
+    /// * order - enum, asc | desc, the order of the block positions of the transactions
+    /// * limit - result size limit
+    /// * after - pagination parameter, optional
+    ///
+    /// ## Returns
+    ///
+    /// If the number of objects is less than the requested `limit`, it indicates that these are the last page of get_transaction_history.
+    ///
+    /// * objects:
+    ///     - tx_hash - transaction hash
+    ///     - block_number - the number of the transaction committed in the block
+    ///     - tx_index - the position index of the transaction committed in the block
+    ///     - block_timestamp - the timestamp of the block
+    ///     - input_capacity - the total capacity of the matched input cells
+    ///     - output_capacity - the total capacity of the matched output cells
+    ///     - capacity_change - the net capacity change `output_capacity - input_capacity`, `{"increase": amount}` or `{"decrease": amount}`
+    ///     - udt - the UDT amounts of the matched cells, only returned when the cells are selected by a type script, i.e. `script_type` is type or `filter.script` is given, and some of them are cells of the UDT scripts configured in `rich_indexer.udt_scripts`
+    ///         - input_amount - the total UDT amount of the matched input cells
+    ///         - output_amount - the total UDT amount of the matched output cells
+    ///         - change - the net UDT amount change `output_amount - input_amount`, in the same format as `capacity_change`
+    /// * last_cursor - pagination parameter
+    ///
+    /// ## Examples
+    ///
+    /// Request
+    ///
+    /// ```json
+    /// {
+    ///   "id": 2,
+    ///   "jsonrpc": "2.0",
+    ///   "method": "get_transaction_history",
+    ///   "params": [
+    ///     {
+    ///       "script": {
+    ///         "code_hash": "0x9bd7e06f3ecf4be0f2fcd2188b23f1b9fcc88e5d4b65a8637b17723bbda3cce8",
+    ///         "hash_type": "type",
+    ///         "args": "0x8211f1b938a107cd53b6302cc752a6fc3965638d"
+    ///       },
+    ///       "script_type": "lock"
+    ///     },
+    ///     "asc",
+    ///     "0x1"
+    ///   ]
+    /// }
+    /// ```
+    ///
+    /// Response
+    ///
+    /// ```json
+    /// {
+    ///   "jsonrpc": "2.0",
+    ///   "result": {
+    ///     "last_cursor": "0x0500000000000000",
+    ///     "objects": [
+    ///       {
+    ///         "block_number": "0x5b",
+    ///         "block_timestamp": "0x172a0c2a4c5",
+    ///         "capacity_change": {
+    ///           "increase": "0x2ca86f2642"
+    ///         },
+    ///         "input_capacity": "0x0",
+    ///         "output_capacity": "0x2ca86f2642",
+    ///         "tx_hash": "0x4ce3a7ea9a4a9e4c3ffb5d0e1d7cb7c0b64fd7e7cd0e8a31a8a89b58e1c6b2b4",
+    ///         "tx_index": "0x0",
+    ///         "udt": null
+    ///       }
+    ///     ]
+    ///   },
+    ///   "id": 2
+    /// }
+    /// ```
+    #[rpc(name = "get_transaction_history")]
+    async fn get_transaction_history(
+        &self,
+        search_key: IndexerSearchKey,
+        order: IndexerOrder,
+        limit: Uint32,
+        after: Option<JsonBytes>,
+    ) -> Result<IndexerPagination<IndexerTxHistory>>;
+
+    /// Re-indexes the blocks from a block number in the background, while the rich-indexer keeps following the tip.
+    ///
+    /// The rich-indexer stores its rows in the chain order, so the rows of the blocks from `from` are
+    /// removed, then the blocks are indexed again with the current `block_filter` and `cell_filter`.
+    /// It can extend the rich-indexer to the blocks before `init_tip_hash`, or index the blocks again
+    /// after the filters are changed. The indexer tip goes back to `from` until the blocks are indexed
+    /// again. It returns immediately, the progress is reported in the node logs.
+    ///
+    /// ## Params
+    ///
+    /// * from: the first block number to index again, the blocks after it up to the tip are indexed again too
+    /// * to: it must not be greater than the indexer tip, it's kept to be compatible with the CKB Indexer
+    ///
+    /// ## Errors
+    ///
+    /// * [`Indexer (-1200)`](../enum.RPCError.html#variant.Indexer) - The range is invalid, or a re-indexing is running already.
+    ///
+    /// ## Examples
+    ///
+    /// Request
+    ///
+    /// ```json
+    /// {
+    ///     "id": 2,
+    ///     "jsonrpc": "2.0",
+    ///     "method": "reindex_blocks",
+    ///     "params": [
+    ///         "0x0",
+    ///         "0x5b513e"
+    ///     ]
+    /// }
+    /// ```
+    ///
+    /// Response
+    ///
+    /// ```json
+    /// {
+    ///     "jsonrpc": "2.0",
+    ///     "result": null,
+    ///     "id": 2
+    /// }
+    /// ```
+    #[rpc(name = "reindex_blocks")]
+    async fn reindex_blocks(&self, from: BlockNumber, to: BlockNumber) -> Result<()>;
 }
 
 #[derive(Clone)]
@@ -183,9 +478,10 @@ impl RichIndexerRpc for RichIndexerRpcImpl {
         order: IndexerOrder,
         limit: Uint32,
         after: Option<JsonBytes>,
+        as_of_block: Option<BlockNumber>,
     ) -> Result<IndexerPagination<IndexerCell>> {
         self.handle
-            .get_cells(search_key, order, limit, after)
+            .get_cells(search_key, order, limit, after, as_of_block)
             .await
             .map_err(|e| RPCError::custom(RPCError::Indexer, e))
     }
@@ -206,9 +502,56 @@ impl RichIndexerRpc for RichIndexerRpcImpl {
     async fn get_cells_capacity(
         &self,
         search_key: IndexerSearchKey,
+        as_of_block: Option<BlockNumber>,
     ) -> Result<Option<IndexerCellsCapacity>> {
         self.handle
-            .get_cells_capacity(search_key)
+            .get_cells_capacity(search_key, as_of_block)
+            .await
+            .map_err(|e| RPCError::custom(RPCError::Indexer, e))
+    }
+
+    async fn get_udt_balances(
+        &self,
+        lock_script: Script,
+        order: IndexerOrder,
+        limit: Uint32,
+        after: Option<JsonBytes>,
+    ) -> Result<IndexerPagination<IndexerUdtBalance>> {
+        self.handle
+            .get_udt_balances(lock_script, order, limit, after)
+            .await
+            .map_err(|e| RPCError::custom(RPCError::Indexer, e))
+    }
+
+    async fn get_udt_holders(
+        &self,
+        type_script: Script,
+        order: IndexerOrder,
+        limit: Uint32,
+        after: Option<JsonBytes>,
+    ) -> Result<IndexerPagination<IndexerUdtHolder>> {
+        self.handle
+            .get_udt_holders(type_script, order, limit, after)
+            .await
+            .map_err(|e| RPCError::custom(RPCError::Indexer, e))
+    }
+
+    async fn get_transaction_history(
+        &self,
+        search_key: IndexerSearchKey,
+        order: IndexerOrder,
+        limit: Uint32,
+        after: Option<JsonBytes>,
+    ) -> Result<IndexerPagination<IndexerTxHistory>> {
+        self.handle
+            .get_transaction_history(search_key, order, limit, after)
+            .await
+            .map_err(|e| RPCError::custom(RPCError::Indexer, e))
+    }
+
+    async fn reindex_blocks(&self, from: BlockNumber, to: BlockNumber) -> Result<()> {
+        self.handle
+            .reindex(from.into(), to.into())
             .await
             .map_err(|e| RPCError::custom(RPCError::Indexer, e))
     }
@@ -1,15 +1,24 @@
+use crate::error::RPCError;
+use crate::replay::TopicLog;
+use crate::tx_watch::{TxStatusUpdate, TxStatusWatch};
 use async_trait::async_trait;
 use broadcast::error::RecvError;
 use ckb_async_runtime::Handle;
-use ckb_jsonrpc_types::Topic;
+use ckb_indexer::{BlockCellEvents, CellEventFilter, IndexerHandle};
+use ckb_jsonrpc_types::{SubscriptionOptions, Topic, TxStatus};
 use ckb_logger::error;
-use ckb_notify::NotifyController;
 use ckb_notify::NOTIFY_CHANNEL_SIZE;
+use ckb_shared::Shared;
 use ckb_stop_handler::new_tokio_exit_rx;
-use futures_util::{stream::BoxStream, Stream};
+use ckb_store::ChainStore;
+use ckb_types::{packed::Byte32, prelude::*};
+use ckb_util::Mutex;
+use futures_util::{stream::BoxStream, Stream, StreamExt};
 use jsonrpc_core::Result;
 use jsonrpc_utils::{pub_sub::PublishMsg, rpc};
+use std::sync::Arc;
 use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
 
 /// RPC Module Subscription that CKB node will push new messages to subscribers, support with WebSocket or TCP.
 ///
@@ -60,7 +69,9 @@ pub trait SubscriptionRpc {
     ///
     /// ###### Params
     ///
-    /// * `topic` - Subscription topic (enum: new_tip_header | new_tip_block | new_transaction | proposed_transaction | rejected_transaction)
+    /// * `topic` - Subscription topic (enum: new_tip_header | new_tip_block | new_transaction | proposed_transaction | rejected_transaction | chain_reorg | cells | transaction_status | new_block_template)
+    /// * `options` - Optional, see [`SubscriptionOptions`](../../ckb_jsonrpc_types/struct.SubscriptionOptions.html). The topic `cells` requires the fields of
+    ///   [`IndexerSearchKey`](../../ckb_jsonrpc_types/struct.IndexerSearchKey.html) in it, and the topic `transaction_status` requires `tx_hash` in it.
     ///
     /// ###### Returns
     ///
@@ -81,6 +92,37 @@ pub trait SubscriptionRpc {
     /// }
     /// ```
     ///
+    /// ##### Sequence Numbers
+    ///
+    /// The messages of each topic are numbered by a monotonically increasing sequence number, and
+    /// the latest messages are kept in memory, the number of which is configured by
+    /// `rpc.subscription_replay_size`. The messages of the topic `cells` are numbered by block,
+    /// so the events of the same block share a sequence number.
+    ///
+    /// When `options.with_seq` is true, each message is pushed with its sequence number:
+    ///
+    /// ```json+skip
+    /// {
+    ///   "jsonrpc": "2.0",
+    ///   "method": "subscribe",
+    ///   "params": {
+    ///     "result": {
+    ///       "seq": "0x1f",
+    ///       "result": { ... }
+    ///     },
+    ///     "subscription": "0x2a"
+    ///   }
+    /// }
+    /// ```
+    ///
+    /// A client which is disconnected can subscribe again with `options.since_seq` set to the
+    /// last sequence number it has received, the missed messages are pushed first. If some of the
+    /// missed messages are no longer kept, for example, because the node has restarted, the RPC
+    /// fails with the error `SubscriptionGap`, and the client must reload the state by other RPCs
+    /// and subscribe without `since_seq`. A sequenced subscription
+    /// which lags behind is ended with an error message `SubscriptionGap`, the client can resume
+    /// it by `since_seq`.
+    ///
     /// ##### Topics
     ///
     /// ###### `new_tip_header`
@@ -120,6 +162,55 @@ pub trait SubscriptionRpc {
     /// -   the first item type is [`PoolTransactionEntry`](../../ckb_jsonrpc_types/struct.PoolTransactionEntry.html), and
     /// -   the second item type is [`PoolTransactionReject`](../../ckb_jsonrpc_types/struct.PoolTransactionReject.html).
     ///
+    /// ###### `chain_reorg`
+    ///
+    /// Subscribers will get notified when the canonical chain is reorganized, that is, some blocks
+    /// are detached from the canonical chain and the blocks of a better fork are attached. The
+    /// message is delivered independently of the `new_tip_header` and `new_tip_block` messages,
+    /// so it may arrive after them. The last one of `attached_blocks` is the new tip.
+    ///
+    /// The type of the `params.result` in the push message is [`ChainReorg`](../../ckb_jsonrpc_types/struct.ChainReorg.html).
+    ///
+    /// ###### `cells`
+    ///
+    /// Subscribers will get notified when a cell matching the `search_key` is created or consumed
+    /// by a block indexed by the built-in indexer, and when such a block is rolled back. This topic
+    /// requires the module `Indexer`.
+    ///
+    /// The `search_key` is interpreted the same way as in the RPC `get_cells`, except that the
+    /// `script_search_mode` `partial` is not supported.
+    ///
+    /// The type of the `params.result` in the push message is [`IndexerCellEvent`](../../ckb_jsonrpc_types/struct.IndexerCellEvent.html).
+    /// When `rollback` is true, the event is reverted: a created cell no longer exists, and a
+    /// consumed cell is live again.
+    ///
+    /// ###### `transaction_status`
+    ///
+    /// Subscribers will get notified when the status of the transaction `tx_hash` changes. The
+    /// current status is pushed first, then each transition, such as pending → proposed →
+    /// committed, and rejected with the reason when the transaction is expired, replaced by RBF
+    /// or evicted from tx-pool. After the transaction is committed, the number of confirmations
+    /// is pushed for each new tip block, until it reaches `confirmations` (default 24), then the
+    /// subscription ends. If the block committing the transaction is detached by a chain
+    /// reorganization, the status is pushed again from tx-pool.
+    ///
+    /// This topic does not support `with_seq` and `since_seq`, the current status is always
+    /// pushed first instead.
+    ///
+    /// The type of the `params.result` in the push message is [`TransactionStatusEvent`](../../ckb_jsonrpc_types/struct.TransactionStatusEvent.html).
+    ///
+    /// ###### `new_block_template`
+    ///
+    /// Subscribers will get notified when the block assembler updates the block template, so
+    /// miners get the fresh work without polling `get_block_template`. The current template is
+    /// pushed first. The updates are coalesced when the subscriber is slow, so only the latest
+    /// template is pushed. This topic requires the block assembler to be enabled.
+    ///
+    /// This topic does not support `with_seq` and `since_seq`, the current template is always
+    /// pushed first instead.
+    ///
+    /// The type of the `params.result` in the push message is [`BlockTemplate`](../../ckb_jsonrpc_types/struct.BlockTemplate.html).
+    ///
     /// ###### Examples
     ///
     /// Subscribe Request
@@ -145,6 +236,104 @@ pub trait SubscriptionRpc {
     /// }
     /// ```
     ///
+    /// Subscribe Cells Request
+    ///
+    /// ```json+skip
+    /// {
+    ///   "id": 42,
+    ///   "jsonrpc": "2.0",
+    ///   "method": "subscribe",
+    ///   "params": [
+    ///     "cells",
+    ///     {
+    ///       "script": {
+    ///         "code_hash": "0x9bd7e06f3ecf4be0f2fcd2188b23f1b9fcc88e5d4b65a8637b17723bbda3cce8",
+    ///         "hash_type": "type",
+    ///         "args": "0x8211f1b938a107cd53b6302cc752a6fc3965638d"
+    ///       },
+    ///       "script_type": "lock"
+    ///     }
+    ///   ]
+    /// }
+    /// ```
+    ///
+    /// Subscribe Transaction Status Request
+    ///
+    /// ```json+skip
+    /// {
+    ///   "id": 42,
+    ///   "jsonrpc": "2.0",
+    ///   "method": "subscribe",
+    ///   "params": [
+    ///     "transaction_status",
+    ///     {
+    ///       "tx_hash": "0xa0ef4eb5f4ceeb08a4c8524d84c5da95dce2f608e0ca2ec8091191b0f330c6e3",
+    ///       "confirmations": "0x3"
+    ///     }
+    ///   ]
+    /// }
+    /// ```
+    ///
+    /// Transaction Status Push Message
+    ///
+    /// ```json+skip
+    /// {
+    ///   "jsonrpc": "2.0",
+    ///   "method": "subscribe",
+    ///   "params": {
+    ///     "result": {
+    ///       "tx_hash": "0xa0ef4eb5f4ceeb08a4c8524d84c5da95dce2f608e0ca2ec8091191b0f330c6e3",
+    ///       "tx_status": {
+    ///         "status": "committed",
+    ///         "block_number": "0x400",
+    ///         "block_hash": "0xa5f5c85987a15de25661e5a214f2c1449cd803f071acc7999820f25246471f40",
+    ///         "tx_index": "0x1",
+    ///         "reason": null
+    ///       },
+    ///       "confirmations": "0x1"
+    ///     },
+    ///     "subscription": "0xf5"
+    ///   }
+    /// }
+    /// ```
+    ///
+    /// Cells Push Message
+    ///
+    /// ```json+skip
+    /// {
+    ///   "jsonrpc": "2.0",
+    ///   "method": "subscribe",
+    ///   "params": {
+    ///     "result": {
+    ///       "event_type": "created",
+    ///       "rollback": false,
+    ///       "block_hash": "0xa5f5c85987a15de25661e5a214f2c1449cd803f071acc7999820f25246471f40",
+    ///       "block_number": "0x400",
+    ///       "tx_hash": "0xa0ef4eb5f4ceeb08a4c8524d84c5da95dce2f608e0ca2ec8091191b0f330c6e3",
+    ///       "cell": {
+    ///         "block_number": "0x400",
+    ///         "out_point": {
+    ///           "index": "0x0",
+    ///           "tx_hash": "0xa0ef4eb5f4ceeb08a4c8524d84c5da95dce2f608e0ca2ec8091191b0f330c6e3"
+    ///         },
+    ///         "output": {
+    ///           "capacity": "0x2ca7071b9e",
+    ///           "lock": {
+    ///             "args": "0x8211f1b938a107cd53b6302cc752a6fc3965638d",
+    ///             "code_hash": "0x9bd7e06f3ecf4be0f2fcd2188b23f1b9fcc88e5d4b65a8637b17723bbda3cce8",
+    ///             "hash_type": "type"
+    ///           },
+    ///           "type": null
+    ///         },
+    ///         "output_data": "0x",
+    ///         "tx_index": "0x0"
+    ///       }
+    ///     },
+    ///     "subscription": "0xf4"
+    ///   }
+    /// }
+    /// ```
+    ///
     /// #### Method `unsubscribe`
     /// * `unsubscribe(id)`
     ///     * `id`: `string`
@@ -181,59 +370,58 @@ pub trait SubscriptionRpc {
     /// ```
     ///
     #[rpc(pub_sub(notify = "subscribe", unsubscribe = "unsubscribe"))]
-    fn subscribe(&self, topic: Topic) -> Result<Self::S>;
+    fn subscribe(&self, topic: Topic, options: Option<SubscriptionOptions>) -> Result<Self::S>;
 }
 
 #[derive(Clone)]
 pub struct SubscriptionRpcImpl {
-    pub new_tip_header_sender: broadcast::Sender<PublishMsg<String>>,
-    pub new_tip_block_sender: broadcast::Sender<PublishMsg<String>>,
-    pub new_transaction_sender: broadcast::Sender<PublishMsg<String>>,
-    pub proposed_transaction_sender: broadcast::Sender<PublishMsg<String>>,
-    pub new_reject_transaction_sender: broadcast::Sender<PublishMsg<String>>,
+    shared: Shared,
+    new_tip_header_log: TopicLog<String>,
+    new_tip_block_log: TopicLog<String>,
+    new_transaction_log: TopicLog<String>,
+    proposed_transaction_log: TopicLog<String>,
+    new_reject_transaction_log: TopicLog<String>,
+    chain_reorg_log: TopicLog<String>,
+    cells: Option<CellsTopic>,
+    tx_status_updates: broadcast::Sender<TxStatusUpdate>,
 }
 
+// The default number of confirmations to end the topic `transaction_status`
+const DEFAULT_CONFIRMATIONS: u64 = 24;
+
 macro_rules! publiser_send {
-    ($ty:ty, $info:expr, $sender:ident) => {{
+    ($ty:ty, $info:expr, $log:ident) => {{
         let msg: $ty = $info.into();
         let json_string = serde_json::to_string(&msg).expect("serialization should be ok");
-        drop($sender.send(PublishMsg::result(&json_string)));
+        $log.publish(json_string);
     }};
 }
 
 #[async_trait]
 impl SubscriptionRpc for SubscriptionRpcImpl {
     type S = BoxStream<'static, PublishMsg<String>>;
-    fn subscribe(&self, topic: Topic) -> Result<Self::S> {
-        let tx = match topic {
-            Topic::NewTipHeader => self.new_tip_header_sender.clone(),
-            Topic::NewTipBlock => self.new_tip_block_sender.clone(),
-            Topic::NewTransaction => self.new_transaction_sender.clone(),
-            Topic::ProposedTransaction => self.proposed_transaction_sender.clone(),
-            Topic::RejectedTransaction => self.new_reject_transaction_sender.clone(),
+    fn subscribe(&self, topic: Topic, options: Option<SubscriptionOptions>) -> Result<Self::S> {
+        let options = options.unwrap_or_default();
+        let log = match topic {
+            Topic::NewTipHeader => &self.new_tip_header_log,
+            Topic::NewTipBlock => &self.new_tip_block_log,
+            Topic::NewTransaction => &self.new_transaction_log,
+            Topic::ProposedTransaction => &self.proposed_transaction_log,
+            Topic::RejectedTransaction => &self.new_reject_transaction_log,
+            Topic::ChainReorg => &self.chain_reorg_log,
+            Topic::Cells => return self.subscribe_cells(options),
+            Topic::TransactionStatus => return self.subscribe_transaction_status(options),
+            Topic::NewBlockTemplate => return self.subscribe_block_template(options),
         };
-        let mut rx = tx.subscribe();
-        Ok(Box::pin(async_stream::stream! {
-                loop {
-                    match rx.recv().await {
-                        Ok(msg) => {
-                            yield msg;
-                        }
-                        Err(RecvError::Lagged(cnt)) => {
-                            error!("subscription lagged error: {:?}", cnt);
-                        }
-                        Err(RecvError::Closed) => {
-                            break;
-                        }
-                    }
-                }
-        }))
+        subscribe_log(log, &options, |json: &String| vec![json.to_owned()])
     }
 }
 
 impl SubscriptionRpcImpl {
-    pub fn new(notify_controller: NotifyController, handle: Handle) -> Self {
+    pub fn new(shared: Shared, indexer_handle: Option<IndexerHandle>, replay_size: usize) -> Self {
         const SUBSCRIBER_NAME: &str = "TcpSubscription";
+        let notify_controller = shared.notify_controller().clone();
+        let handle = shared.async_handle().clone();
 
         let mut new_block_receiver =
             handle.block_on(notify_controller.subscribe_new_block(SUBSCRIBER_NAME.to_string()));
@@ -244,38 +432,59 @@ impl SubscriptionRpcImpl {
         );
         let mut reject_transaction_receiver = handle
             .block_on(notify_controller.subscribe_reject_transaction(SUBSCRIBER_NAME.to_string()));
+        let mut chain_reorg_receiver =
+            handle.block_on(notify_controller.subscribe_chain_reorg(SUBSCRIBER_NAME.to_string()));
 
-        let (new_tip_header_sender, _) = broadcast::channel(NOTIFY_CHANNEL_SIZE);
-        let (new_tip_block_sender, _) = broadcast::channel(NOTIFY_CHANNEL_SIZE);
-        let (proposed_transaction_sender, _) = broadcast::channel(NOTIFY_CHANNEL_SIZE);
-        let (new_transaction_sender, _) = broadcast::channel(NOTIFY_CHANNEL_SIZE);
-        let (new_reject_transaction_sender, _) = broadcast::channel(NOTIFY_CHANNEL_SIZE);
+        let new_tip_header_log = TopicLog::new(replay_size, NOTIFY_CHANNEL_SIZE);
+        let new_tip_block_log = TopicLog::new(replay_size, NOTIFY_CHANNEL_SIZE);
+        let proposed_transaction_log = TopicLog::new(replay_size, NOTIFY_CHANNEL_SIZE);
+        let new_transaction_log = TopicLog::new(replay_size, NOTIFY_CHANNEL_SIZE);
+        let new_reject_transaction_log = TopicLog::new(replay_size, NOTIFY_CHANNEL_SIZE);
+        let chain_reorg_log = TopicLog::new(replay_size, NOTIFY_CHANNEL_SIZE);
+        let (tx_status_updates, _) = broadcast::channel(NOTIFY_CHANNEL_SIZE);
 
         let stop_rx = new_tokio_exit_rx();
         handle.spawn({
-            let new_tip_header_sender = new_tip_header_sender.clone();
-            let new_tip_block_sender = new_tip_block_sender.clone();
-            let new_transaction_sender = new_transaction_sender.clone();
-            let proposed_transaction_sender = proposed_transaction_sender.clone();
-            let new_reject_transaction_sender = new_reject_transaction_sender.clone();
+            let new_tip_header_log = new_tip_header_log.clone();
+            let new_tip_block_log = new_tip_block_log.clone();
+            let new_transaction_log = new_transaction_log.clone();
+            let proposed_transaction_log = proposed_transaction_log.clone();
+            let new_reject_transaction_log = new_reject_transaction_log.clone();
+            let chain_reorg_log = chain_reorg_log.clone();
+            let tx_status_updates = tx_status_updates.clone();
             async move {
                 loop {
                     tokio::select! {
                         Some(block) = new_block_receiver.recv() => {
-                            publiser_send!(ckb_jsonrpc_types::HeaderView, block.header(), new_tip_header_sender);
-                            publiser_send!(ckb_jsonrpc_types::BlockView, block, new_tip_block_sender);
+                            publiser_send!(ckb_jsonrpc_types::HeaderView, block.header(), new_tip_header_log);
+                            publiser_send!(ckb_jsonrpc_types::BlockView, block, new_tip_block_log);
+                            // it is fine that no one watches the transactions
+                            let _ = tx_status_updates.send(TxStatusUpdate::Chain);
                         },
                         Some(tx_entry) = new_transaction_receiver.recv() => {
-                            publiser_send!(ckb_jsonrpc_types::PoolTransactionEntry, tx_entry, new_transaction_sender);
+                            let tx_hash = tx_entry.transaction.hash();
+                            publiser_send!(ckb_jsonrpc_types::PoolTransactionEntry, tx_entry, new_transaction_log);
+                            let _ = tx_status_updates.send(TxStatusUpdate::Pool(tx_hash, TxStatus::pending()));
                         },
                         Some(tx_entry) = proposed_transaction_receiver.recv() => {
-                            publiser_send!(ckb_jsonrpc_types::PoolTransactionEntry, tx_entry, proposed_transaction_sender);
+                            let tx_hash = tx_entry.transaction.hash();
+                            publiser_send!(ckb_jsonrpc_types::PoolTransactionEntry, tx_entry, proposed_transaction_log);
+                            let _ = tx_status_updates.send(TxStatusUpdate::Pool(tx_hash, TxStatus::proposed()));
                         },
                         Some((tx_entry, reject)) = reject_transaction_receiver.recv() => {
+                            let tx_hash = tx_entry.transaction.hash();
+                            let reject: ckb_jsonrpc_types::PoolTransactionReject = reject.into();
+                            // the same reason as the rejected status of `get_transaction`
+                            let reason = serde_json::to_string(&reject).expect("serialization should be ok");
                             publiser_send!((ckb_jsonrpc_types::PoolTransactionEntry, ckb_jsonrpc_types::PoolTransactionReject),
-                                            (tx_entry.into(), reject.into()),
-                                            new_reject_transaction_sender);
+                                            (tx_entry.into(), reject),
+                                            new_reject_transaction_log);
+                            let _ = tx_status_updates.send(TxStatusUpdate::Pool(tx_hash, TxStatus::rejected(reason)));
                         }
+                        Some(reorg) = chain_reorg_receiver.recv() => {
+                            publiser_send!(ckb_jsonrpc_types::ChainReorg, reorg, chain_reorg_log);
+                            let _ = tx_status_updates.send(TxStatusUpdate::Chain);
+                        },
                         _ = stop_rx.cancelled() => {
                             break;
                         },
@@ -288,12 +497,334 @@ impl SubscriptionRpcImpl {
             }
         });
 
+        let cells = indexer_handle.map(|indexer_handle| CellsTopic {
+            indexer_handle,
+            log: TopicLog::new(replay_size, NOTIFY_CHANNEL_SIZE),
+            handle,
+            state: Default::default(),
+        });
+
         Self {
-            new_tip_header_sender,
-            new_tip_block_sender,
-            new_transaction_sender,
-            proposed_transaction_sender,
-            new_reject_transaction_sender,
+            shared,
+            new_tip_header_log,
+            new_tip_block_log,
+            new_transaction_log,
+            proposed_transaction_log,
+            new_reject_transaction_log,
+            chain_reorg_log,
+            cells,
+            tx_status_updates,
         }
     }
+
+    fn subscribe_cells(
+        &self,
+        mut options: SubscriptionOptions,
+    ) -> Result<BoxStream<'static, PublishMsg<String>>> {
+        let cells = self
+            .cells
+            .as_ref()
+            .ok_or_else(|| RPCError::rpc_module_is_disabled("Indexer"))?;
+        let search_key = options.search_key.take().ok_or_else(|| {
+            RPCError::invalid_params("the topic cells requires a valid search_key")
+        })?;
+        let filter =
+            CellEventFilter::new(search_key).map_err(|e| RPCError::custom(RPCError::Indexer, e))?;
+        let subscriber = cells.subscribe();
+        let mut stream =
+            subscribe_log(&cells.log, &options, move |block: &Arc<BlockCellEvents>| {
+                filter
+                    .filter(block)
+                    .iter()
+                    .map(|event| serde_json::to_string(event).expect("serialization should be ok"))
+                    .collect()
+            })?;
+        Ok(Box::pin(async_stream::stream! {
+            // unsubscribe from the indexer after the last subscriber leaves
+            let _subscriber = subscriber;
+            while let Some(msg) = stream.next().await {
+                yield msg;
+            }
+        }))
+    }
+
+    fn subscribe_transaction_status(
+        &self,
+        options: SubscriptionOptions,
+    ) -> Result<BoxStream<'static, PublishMsg<String>>> {
+        let tx_hash: Byte32 = options
+            .tx_hash
+            .ok_or_else(|| {
+                RPCError::invalid_params("the topic transaction_status requires a tx_hash")
+            })?
+            .pack();
+        if options.with_seq || options.since_seq.is_some() {
+            return Err(RPCError::invalid_params(
+                "the topic transaction_status does not support with_seq and since_seq",
+            ));
+        }
+        let depth = options
+            .confirmations
+            .map(Into::into)
+            .unwrap_or(DEFAULT_CONFIRMATIONS);
+        if depth == 0 {
+            return Err(RPCError::invalid_params(
+                "confirmations must be greater than 0",
+            ));
+        }
+
+        // subscribe before querying the current status, so no transition is missed
+        let mut rx = self.tx_status_updates.subscribe();
+        let shared = self.shared.clone();
+        let mut watch = TxStatusWatch::new(tx_hash, depth);
+        Ok(Box::pin(async_stream::stream! {
+            let (tx_status, confirmations) = query_tx_status(&shared, watch.tx_hash());
+            if let Some(event) = watch.update(tx_status, confirmations) {
+                yield tx_status_msg(&event);
+            }
+            while !watch.is_finished() {
+                let (tx_status, confirmations) = match rx.recv().await {
+                    Ok(TxStatusUpdate::Pool(tx_hash, tx_status)) => {
+                        // the chain is the source of truth after the transaction is committed
+                        if &tx_hash != watch.tx_hash() || watch.is_committed() {
+                            continue;
+                        }
+                        (tx_status, None)
+                    }
+                    Ok(TxStatusUpdate::Chain) => {
+                        match query_committed_status(&shared, watch.tx_hash()) {
+                            Some((tx_status, confirmations)) => (tx_status, Some(confirmations)),
+                            // the block committing the transaction is detached
+                            None if watch.is_committed() => {
+                                (query_pool_status(&shared, watch.tx_hash()), None)
+                            }
+                            None => continue,
+                        }
+                    }
+                    Err(RecvError::Lagged(cnt)) => {
+                        error!("subscription transaction_status lagged error: {:?}", cnt);
+                        query_tx_status(&shared, watch.tx_hash())
+                    }
+                    Err(RecvError::Closed) => {
+                        break;
+                    }
+                };
+                if let Some(event) = watch.update(tx_status, confirmations) {
+                    yield tx_status_msg(&event);
+                }
+            }
+        }))
+    }
+
+    fn subscribe_block_template(
+        &self,
+        options: SubscriptionOptions,
+    ) -> Result<BoxStream<'static, PublishMsg<String>>> {
+        if options.with_seq || options.since_seq.is_some() {
+            return Err(RPCError::invalid_params(
+                "the topic new_block_template does not support with_seq and since_seq",
+            ));
+        }
+        let mut template_updated = self
+            .shared
+            .tx_pool_controller()
+            .subscribe_block_template()
+            .ok_or_else(|| {
+                RPCError::invalid_params(
+                    "the topic new_block_template requires the block assembler to be enabled",
+                )
+            })?;
+
+        let shared = self.shared.clone();
+        Ok(Box::pin(async_stream::stream! {
+            loop {
+                // mark the current template as seen before querying it, so no update is missed
+                template_updated.borrow_and_update();
+                match shared.get_block_template(None, None, None) {
+                    Ok(Ok(template)) => {
+                        let json = serde_json::to_string(&template)
+                            .expect("serialization should be ok");
+                        yield PublishMsg::result(&json);
+                    }
+                    Ok(Err(e)) | Err(e) => {
+                        error!("get_block_template for new_block_template error {}", e);
+                    }
+                }
+                if template_updated.changed().await.is_err() {
+                    break;
+                }
+            }
+        }))
+    }
+}
+
+// Queries the status of the transaction, and the number of confirmations if it is committed.
+fn query_tx_status(shared: &Shared, tx_hash: &Byte32) -> (TxStatus, Option<u64>) {
+    match query_committed_status(shared, tx_hash) {
+        Some((tx_status, confirmations)) => (tx_status, Some(confirmations)),
+        None => (query_pool_status(shared, tx_hash), None),
+    }
+}
+
+// Returns the committed status and the number of confirmations if the transaction is committed
+// in the canonical chain.
+fn query_committed_status(shared: &Shared, tx_hash: &Byte32) -> Option<(TxStatus, u64)> {
+    let snapshot = shared.snapshot();
+    snapshot.get_transaction_info(tx_hash).map(|tx_info| {
+        let confirmations = snapshot.tip_number().saturating_sub(tx_info.block_number) + 1;
+        let tx_status = TxStatus::committed(
+            tx_info.block_number.into(),
+            tx_info.block_hash.unpack(),
+            (tx_info.index as u32).into(),
+        );
+        (tx_status, confirmations)
+    })
+}
+
+fn query_pool_status(shared: &Shared, tx_hash: &Byte32) -> TxStatus {
+    match shared.tx_pool_controller().get_tx_status(tx_hash.clone()) {
+        Ok(Ok((tx_status, _))) => tx_status.into(),
+        Ok(Err(e)) | Err(e) => {
+            error!("get_tx_status for transaction_status error {}", e);
+            TxStatus::unknown()
+        }
+    }
+}
+
+fn tx_status_msg(event: &ckb_jsonrpc_types::TransactionStatusEvent) -> PublishMsg<String> {
+    let json = serde_json::to_string(event).expect("serialization should be ok");
+    PublishMsg::result(&json)
+}
+
+// Streams the messages of the topic, which are rendered to JSON strings by `render`.
+#[derive(Default)]
+struct CellsTopicState {
+    subscribers: usize,
+    forwarder: Option<CancellationToken>,
+}
+
+/// The topic `cells`.
+///
+/// The indexer only collects the cell events while they are received, so the topic only
+/// receives them from the indexer while there are subscribers.
+#[derive(Clone)]
+struct CellsTopic {
+    indexer_handle: IndexerHandle,
+    log: TopicLog<Arc<BlockCellEvents>>,
+    handle: Handle,
+    state: Arc<Mutex<CellsTopicState>>,
+}
+
+impl CellsTopic {
+    fn subscribe(&self) -> CellsSubscriber {
+        let mut state = self.state.lock();
+        if state.subscribers == 0 {
+            let forwarder = new_tokio_exit_rx().child_token();
+            let mut cell_events_receiver = self.indexer_handle.subscribe_cell_events();
+            let cells_log = self.log.clone();
+            let stop_rx = forwarder.clone();
+            self.handle.spawn(async move {
+                loop {
+                    tokio::select! {
+                        biased;
+                        _ = stop_rx.cancelled() => {
+                            break;
+                        },
+                        ret = cell_events_receiver.recv() => match ret {
+                            Ok(block) => {
+                                cells_log.publish(block);
+                            }
+                            Err(RecvError::Lagged(cnt)) => {
+                                error!("subscription cells lagged error: {:?}", cnt);
+                            }
+                            Err(RecvError::Closed) => {
+                                break;
+                            }
+                        },
+                    }
+                }
+            });
+            state.forwarder = Some(forwarder);
+        }
+        state.subscribers += 1;
+        CellsSubscriber {
+            topic: self.clone(),
+        }
+    }
+}
+
+struct CellsSubscriber {
+    topic: CellsTopic,
+}
+
+impl Drop for CellsSubscriber {
+    fn drop(&mut self) {
+        let mut state = self.topic.state.lock();
+        state.subscribers -= 1;
+        if state.subscribers == 0 {
+            if let Some(forwarder) = state.forwarder.take() {
+                forwarder.cancel();
+            }
+            // the events are not received until the next subscriber arrives
+            self.topic.log.reset();
+        }
+    }
+}
+
+fn subscribe_log<T, F>(
+    log: &TopicLog<T>,
+    options: &SubscriptionOptions,
+    render: F,
+) -> Result<BoxStream<'static, PublishMsg<String>>>
+where
+    T: Clone + Send + 'static,
+    F: Fn(&T) -> Vec<String> + Send + 'static,
+{
+    let with_seq = options.with_seq || options.since_seq.is_some();
+    let (replay, mut rx) = log
+        .subscribe(options.since_seq.map(Into::into))
+        .map_err(|gap| {
+            RPCError::subscription_gap(format!(
+                "the messages before {:#x} are dropped, please subscribe without since_seq",
+                gap.oldest_seq
+            ))
+        })?;
+    Ok(Box::pin(async_stream::stream! {
+            for (seq, message) in replay {
+                for json in render(&message) {
+                    yield publish_msg(seq, json, with_seq);
+                }
+            }
+            loop {
+                match rx.recv().await {
+                    Ok((seq, message)) => {
+                        for json in render(&message) {
+                            yield publish_msg(seq, json, with_seq);
+                        }
+                    }
+                    Err(RecvError::Lagged(cnt)) => {
+                        error!("subscription lagged error: {:?}", cnt);
+                        if with_seq {
+                            // end the subscription, the client can resume from the last
+                            // received sequence number
+                            yield PublishMsg::error(&RPCError::subscription_gap(format!(
+                                "{cnt} messages are skipped because the subscriber lagged"
+                            )));
+                            break;
+                        }
+                    }
+                    Err(RecvError::Closed) => {
+                        break;
+                    }
+                }
+            }
+    }))
+}
+
+fn publish_msg(seq: u64, json: String, with_seq: bool) -> PublishMsg<String> {
+    if with_seq {
+        PublishMsg::result(&format!(r#"{{"seq":"{seq:#x}","result":{json}}}"#))
+    } else {
+        PublishMsg::result(&json)
+    }
 }
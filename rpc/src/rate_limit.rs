@@ -0,0 +1,169 @@
+use crate::{IoHandler, RPCError};
+use ckb_app_config::{RpcModule, RpcRateLimitConfig};
+use ckb_util::Mutex;
+use jsonrpc_core::{BoxFuture, RemoteProcedure, RpcMethod, Value};
+use lru::LruCache;
+use std::collections::{HashMap, HashSet};
+use std::net::IpAddr;
+use std::num::NonZeroU32;
+use std::sync::Arc;
+
+// The token buckets of the least recently seen clients are dropped beyond this limit
+const MAX_TRACKED_CLIENTS: usize = 4096;
+
+type DirectRateLimiter = governor::RateLimiter<
+    governor::state::direct::NotKeyed,
+    governor::state::InMemoryState,
+    governor::clock::DefaultClock,
+>;
+
+/// The key of the token buckets.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub(crate) enum ClientKey {
+    /// The index of the matched `rpc.auth` rule
+    Identity(usize),
+    Address(IpAddr),
+}
+
+struct Limit {
+    quota: governor::Quota,
+    methods: HashSet<String>,
+}
+
+/// RPC rate limit rules resolved against the mounted methods.
+#[derive(Default)]
+pub(crate) struct RateLimits {
+    // method rules first, so they take priority over the module rules
+    limits: Vec<Limit>,
+}
+
+impl RateLimits {
+    /// Resolves the `rpc.rate_limits` rules.
+    ///
+    /// Returns an error if any rule is invalid, the RPC server must not start with a weaker
+    /// limit than the configured one.
+    pub(crate) fn new(
+        configs: &[RpcRateLimitConfig],
+        method_modules: &HashMap<String, RpcModule>,
+    ) -> Result<Self, String> {
+        let (method_configs, module_configs): (Vec<_>, Vec<_>) =
+            configs.iter().partition(|config| config.method.is_some());
+        let limits = method_configs
+            .into_iter()
+            .chain(module_configs)
+            .map(|config| {
+                let quota = Self::quota(config).ok_or_else(|| {
+                    format!(
+                        "Invalid rpc.rate_limits rule for module {:?} method {:?}, \
+                         `requests_per_second` and `burst` must be positive",
+                        config.module, config.method
+                    )
+                })?;
+                let methods = match (&config.method, config.module) {
+                    (Some(method), _) => vec![method.to_owned()].into_iter().collect(),
+                    (None, Some(module)) => method_modules
+                        .iter()
+                        .filter(|(_, m)| **m == module)
+                        .map(|(name, _)| name.to_owned())
+                        .collect(),
+                    (None, None) => {
+                        return Err(
+                            "Invalid rpc.rate_limits rule, one of `module` and `method` is required"
+                                .to_string(),
+                        );
+                    }
+                };
+                Ok(Limit { quota, methods })
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(RateLimits { limits })
+    }
+
+    fn quota(config: &RpcRateLimitConfig) -> Option<governor::Quota> {
+        let rate = NonZeroU32::new(config.requests_per_second)?;
+        let burst = match config.burst {
+            Some(burst) => NonZeroU32::new(burst)?,
+            None => rate,
+        };
+        Some(governor::Quota::per_second(rate).allow_burst(burst))
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.limits.is_empty()
+    }
+}
+
+/// Assigns each client its own token buckets.
+#[derive(Clone)]
+pub(crate) struct Throttler {
+    limits: Arc<RateLimits>,
+    clients: Arc<Mutex<LruCache<ClientKey, Arc<IoHandler>>>>,
+}
+
+impl Throttler {
+    pub(crate) fn new(limits: RateLimits) -> Self {
+        Throttler {
+            limits: Arc::new(limits),
+            clients: Arc::new(Mutex::new(LruCache::new(MAX_TRACKED_CLIENTS))),
+        }
+    }
+
+    /// Returns the methods handler of the client, which wraps the limited methods of `io` with
+    /// the token buckets of the client.
+    ///
+    /// The client must always use the same `io`.
+    pub(crate) fn handler(&self, client: ClientKey, io: &IoHandler) -> Arc<IoHandler> {
+        let mut clients = self.clients.lock();
+        if let Some(handler) = clients.get(&client) {
+            return Arc::clone(handler);
+        }
+        let handler = Arc::new(self.build(io));
+        clients.put(client, Arc::clone(&handler));
+        handler
+    }
+
+    fn build(&self, io: &IoHandler) -> IoHandler {
+        let buckets: Vec<_> = self
+            .limits
+            .limits
+            .iter()
+            .map(|limit| Arc::new(DirectRateLimiter::direct(limit.quota)))
+            .collect();
+        let mut handler = IoHandler::with_compatibility(jsonrpc_core::Compatibility::V2);
+        handler.extend_with(io.iter().map(|(name, method)| {
+            let limit = self
+                .limits
+                .limits
+                .iter()
+                .position(|limit| limit.methods.contains(name));
+            match (limit, method) {
+                (Some(index), RemoteProcedure::Method(inner)) => {
+                    let bucket = Arc::clone(&buckets[index]);
+                    let inner = Arc::clone(inner);
+                    let name = name.to_owned();
+                    (
+                        name.clone(),
+                        RemoteProcedure::Method(Arc::new(
+                            move |params, meta| -> BoxFuture<jsonrpc_core::Result<Value>> {
+                                if bucket.check().is_ok() {
+                                    inner.call(params, meta)
+                                } else {
+                                    if let Some(metrics) = ckb_metrics::handle() {
+                                        metrics
+                                            .ckb_rpc_throttled_calls
+                                            .with_label_values(&[&name])
+                                            .inc();
+                                    }
+                                    let error = RPCError::rpc_method_is_throttled(&name);
+                                    Box::pin(async move { Err(error) })
+                                }
+                            },
+                        )),
+                    )
+                }
+                _ => (name.to_owned(), method.clone()),
+            }
+        }));
+        handler
+    }
+}
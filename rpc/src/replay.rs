@@ -0,0 +1,111 @@
+use ckb_systemtime::unix_time;
+use ckb_util::Mutex;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+/// Default number of the latest messages kept for each subscription topic.
+pub(crate) const DEFAULT_REPLAY_SIZE: usize = 256;
+
+/// The subscription can not be resumed, because the messages after `since_seq` are dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Gap {
+    /// The sequence number of the oldest kept message, or of the next message when none is kept
+    pub(crate) oldest_seq: u64,
+}
+
+struct History<T> {
+    next_seq: u64,
+    messages: VecDeque<(u64, T)>,
+}
+
+/// A broadcast channel which numbers the messages and keeps the latest ones, so a subscriber can
+/// resume from the last sequence number it has received.
+///
+/// The sequence numbers start from the creation time in microseconds, so they keep increasing
+/// across node restarts as long as there is less than one message per microsecond.
+pub(crate) struct TopicLog<T> {
+    capacity: usize,
+    history: Arc<Mutex<History<T>>>,
+    sender: broadcast::Sender<(u64, T)>,
+}
+
+impl<T> Clone for TopicLog<T> {
+    fn clone(&self) -> Self {
+        TopicLog {
+            capacity: self.capacity,
+            history: Arc::clone(&self.history),
+            sender: self.sender.clone(),
+        }
+    }
+}
+
+impl<T: Clone> TopicLog<T> {
+    pub(crate) fn new(capacity: usize, channel_size: usize) -> Self {
+        let (sender, _) = broadcast::channel(channel_size);
+        TopicLog {
+            capacity,
+            history: Arc::new(Mutex::new(History {
+                next_seq: unix_time().as_micros() as u64,
+                messages: VecDeque::with_capacity(capacity),
+            })),
+            sender,
+        }
+    }
+
+    /// Numbers and publishes the message, returns its sequence number.
+    pub(crate) fn publish(&self, message: T) -> u64 {
+        let mut history = self.history.lock();
+        let seq = history.next_seq;
+        history.next_seq += 1;
+        if self.capacity > 0 {
+            if history.messages.len() == self.capacity {
+                history.messages.pop_front();
+            }
+            history.messages.push_back((seq, message.clone()));
+        }
+        // send while holding the lock, so subscribers never see a message twice or miss one
+        // between the replayed messages and the live ones
+        let _ = self.sender.send((seq, message));
+        seq
+    }
+
+    /// Drops the kept messages and skips a sequence number.
+    ///
+    /// It is called when the messages stop being published for a while, so the subscribers
+    /// resuming from before the reset get a gap instead of silently missing the messages.
+    pub(crate) fn reset(&self) {
+        let mut history = self.history.lock();
+        history.messages.clear();
+        history.next_seq += 1;
+    }
+
+    /// Subscribes the live messages, and returns the kept messages after `since_seq` which must
+    /// be replayed first.
+    pub(crate) fn subscribe(
+        &self,
+        since_seq: Option<u64>,
+    ) -> Result<(Vec<(u64, T)>, broadcast::Receiver<(u64, T)>), Gap> {
+        let history = self.history.lock();
+        let replay = match since_seq {
+            None => Vec::new(),
+            Some(since_seq) => {
+                let oldest_seq = history
+                    .messages
+                    .front()
+                    .map(|(seq, _)| *seq)
+                    .unwrap_or(history.next_seq);
+                if since_seq.saturating_add(1) < oldest_seq || since_seq >= history.next_seq {
+                    return Err(Gap { oldest_seq });
+                }
+                history
+                    .messages
+                    .iter()
+                    .filter(|(seq, _)| *seq > since_seq)
+                    .cloned()
+                    .collect()
+            }
+        };
+        Ok((replay, self.sender.subscribe()))
+    }
+}
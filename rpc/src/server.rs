@@ -1,4 +1,9 @@
-use crate::IoHandler;
+use crate::auth::{parse_authorization_line, AccessControl, Authenticator};
+use crate::rate_limit::{ClientKey, Throttler};
+use crate::{IoHandler, RPCError};
+use axum::extract::{ConnectInfo, Request as HttpRequest, State};
+use axum::http::header::{AUTHORIZATION, WWW_AUTHENTICATE};
+use axum::middleware::{self, Next};
 use axum::response::IntoResponse;
 use axum::routing::{get, post};
 use axum::{Extension, Router};
@@ -18,11 +23,11 @@ use jsonrpc_core::types::error::ErrorCode;
 use jsonrpc_core::types::Response as RpcResponse;
 use jsonrpc_core::Error;
 
-use futures_util::{SinkExt, TryStreamExt};
+use futures_util::{SinkExt, StreamExt, TryStreamExt};
 use jsonrpc_utils::axum_utils::handle_jsonrpc_ws;
 use jsonrpc_utils::pub_sub::Session;
 use jsonrpc_utils::stream::{serve_stream_sink, StreamMsg, StreamServerConfig};
-use std::net::{SocketAddr, ToSocketAddrs};
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
 use std::sync::Arc;
 use std::sync::OnceLock;
 use std::time::Duration;
@@ -48,16 +53,42 @@ impl RpcServer {
     ///
     /// * `config` - RPC config options.
     /// * `io_handler` - RPC methods handler. See [ServiceBuilder](../service_builder/struct.ServiceBuilder.html).
+    /// * `access_control` - RPC access control rules. See [ServiceBuilder::access_control](../service_builder/struct.ServiceBuilder.html#method.access_control).
     /// * `handler` - Tokio runtime handle.
-    pub fn new(config: RpcConfig, io_handler: IoHandler, handler: Handle) -> Self {
+    pub fn new(
+        config: RpcConfig,
+        io_handler: IoHandler,
+        access_control: AccessControl,
+        handler: Handle,
+    ) -> Self {
         if let Some(jsonrpc_batch_limit) = config.rpc_batch_limit {
             let _ = JSONRPC_BATCH_LIMIT.get_or_init(|| jsonrpc_batch_limit);
         }
 
+        let authenticator = if access_control.is_enabled() {
+            info!("RPC access control is enabled");
+            Some(access_control.authenticator(&io_handler))
+        } else {
+            None
+        };
+        let throttler = access_control.throttler();
+        if throttler.is_some() {
+            info!("RPC rate limits are enabled");
+        }
         let rpc = Arc::new(io_handler);
+        let gatekeeper = if authenticator.is_some() || throttler.is_some() {
+            Some(Gatekeeper {
+                rpc: Arc::clone(&rpc),
+                authenticator,
+                throttler,
+            })
+        } else {
+            None
+        };
 
         let http_address = Self::start_server(
             &rpc,
+            gatekeeper.clone(),
             config.listen_address.to_owned(),
             handler.clone(),
             false,
@@ -69,16 +100,23 @@ impl RpcServer {
 
         let ws_address = if let Some(addr) = config.ws_listen_address {
             let local_addr =
-                Self::start_server(&rpc, addr, handler.clone(), true).inspect(|&addr| {
-                    info!("Listen WebSocket RPCServer on address: {}", addr);
-                });
+                Self::start_server(&rpc, gatekeeper.clone(), addr, handler.clone(), true).inspect(
+                    |&addr| {
+                        info!("Listen WebSocket RPCServer on address: {}", addr);
+                    },
+                );
             local_addr.ok()
         } else {
             None
         };
 
         let tcp_address = if let Some(addr) = config.tcp_listen_address {
-            let local_addr = handler.block_on(Self::start_tcp_server(rpc, addr, handler.clone()));
+            let local_addr = handler.block_on(Self::start_tcp_server(
+                rpc,
+                gatekeeper,
+                addr,
+                handler.clone(),
+            ));
             if let Ok(addr) = &local_addr {
                 info!("Listen TCP RPCServer on address: {}", addr);
             };
@@ -96,6 +134,7 @@ impl RpcServer {
 
     fn start_server(
         rpc: &Arc<MetaIoHandler<Option<Session>>>,
+        gatekeeper: Option<Gatekeeper>,
         address: String,
         handler: Handle,
         enable_websocket: bool,
@@ -116,9 +155,14 @@ impl RpcServer {
         };
         let method_router = post_router.merge(get_router);
 
-        let app = Router::new()
+        let mut app = Router::new()
             .route("/", method_router.clone())
-            .route("/*path", method_router)
+            .route("/*path", method_router);
+        if let Some(gatekeeper) = gatekeeper {
+            // Only applies to the routes above, the health endpoint `/ping` stays public.
+            app = app.layer(middleware::from_fn_with_state(gatekeeper, admit));
+        }
+        let app = app
             .route("/ping", get(ping_handler))
             .layer(Extension(Arc::clone(rpc)))
             .layer(CorsLayer::permissive())
@@ -137,7 +181,10 @@ impl RpcServer {
             )
             .await
             .unwrap();
-            let server = axum::serve(listener, app.into_make_service());
+            let server = axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<SocketAddr>(),
+            );
 
             let _ = tx_addr.send(server.local_addr().unwrap());
             let graceful = server.with_graceful_shutdown(async move {
@@ -152,6 +199,7 @@ impl RpcServer {
 
     async fn start_tcp_server(
         rpc: Arc<MetaIoHandler<Option<Session>>>,
+        gatekeeper: Option<Gatekeeper>,
         tcp_listen_address: String,
         handler: Handle,
     ) -> Result<SocketAddr, AnyError> {
@@ -170,14 +218,40 @@ impl RpcServer {
             let exit_signal: CancellationToken = new_tokio_exit_rx();
             tokio::select! {
                 _ = async {
-                        while let Ok((stream, _)) = listener.accept().await {
+                        while let Ok((stream, peer_address)) = listener.accept().await {
                             let rpc = Arc::clone(&rpc);
+                            let gatekeeper = gatekeeper.clone();
                             let stream_config = stream_config.clone();
                             let codec = codec.clone();
                             tokio::spawn(async move {
                                 let (r, w) = stream.into_split();
-                                let r = FramedRead::new(r, codec.clone()).map_ok(StreamMsg::Str);
-                                let w = FramedWrite::new(w, codec).with(|msg| async move {
+                                let mut r = FramedRead::new(r, codec.clone());
+                                let mut w = FramedWrite::new(w, codec);
+                                let rpc = match gatekeeper {
+                                    Some(gatekeeper) => {
+                                        // The first line must be the `Authorization` header line
+                                        let line = if gatekeeper.requires_auth() {
+                                            match r.next().await {
+                                                Some(Ok(line)) => Some(line),
+                                                _ => return,
+                                            }
+                                        } else {
+                                            None
+                                        };
+                                        let authorization =
+                                            line.as_deref().and_then(parse_authorization_line);
+                                        match gatekeeper.admit(authorization, peer_address.ip()) {
+                                            Some(io) => io,
+                                            None => {
+                                                let _ = w.send(unauthorized_failure()).await;
+                                                return;
+                                            }
+                                        }
+                                    }
+                                    None => rpc,
+                                };
+                                let r = r.map_ok(StreamMsg::Str);
+                                let w = w.with(|msg| async move {
                                     Ok::<_, LinesCodecError>(match msg {
                                         StreamMsg::Str(msg) => msg,
                                         _ => "".into(),
@@ -204,6 +278,73 @@ async fn ping_handler() -> impl IntoResponse {
     "pong"
 }
 
+/// Selects the methods handler of a client by its credential and address.
+#[derive(Clone)]
+struct Gatekeeper {
+    rpc: Arc<IoHandler>,
+    authenticator: Option<Authenticator>,
+    throttler: Option<Throttler>,
+}
+
+impl Gatekeeper {
+    fn requires_auth(&self) -> bool {
+        self.authenticator.is_some()
+    }
+
+    /// Returns `None` if the client fails the authentication.
+    fn admit(&self, authorization: Option<&str>, address: IpAddr) -> Option<Arc<IoHandler>> {
+        let (client, io) = match &self.authenticator {
+            Some(authenticator) => {
+                let (identity, io) = authenticator.authenticate(authorization)?;
+                (ClientKey::Identity(identity), io)
+            }
+            None => (ClientKey::Address(address), Arc::clone(&self.rpc)),
+        };
+        match &self.throttler {
+            Some(throttler) => Some(throttler.handler(client, &io)),
+            None => Some(io),
+        }
+    }
+}
+
+/// rejects the requests without a valid credential and applies the rate limits
+async fn admit(
+    State(gatekeeper): State<Gatekeeper>,
+    ConnectInfo(address): ConnectInfo<SocketAddr>,
+    mut req: HttpRequest,
+    next: Next,
+) -> Response {
+    let authorization = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok());
+    match gatekeeper.admit(authorization, address.ip()) {
+        Some(io) => {
+            // overrides the default methods handler with the client's one
+            req.extensions_mut().insert(io);
+            next.run(req).await
+        }
+        None => (
+            StatusCode::UNAUTHORIZED,
+            [(WWW_AUTHENTICATE, "Bearer, Basic")],
+            "A valid credential is required in the Authorization header",
+        )
+            .into_response(),
+    }
+}
+
+fn unauthorized_failure() -> String {
+    serde_json::to_string(&jsonrpc_core::Failure {
+        jsonrpc: Some(jsonrpc_core::Version::V2),
+        id: jsonrpc_core::Id::Null,
+        error: RPCError::custom(
+            RPCError::RPCMethodIsUnauthorized,
+            "A valid credential is required, send `Authorization: <scheme> <credential>` as the first line",
+        ),
+    })
+    .unwrap_or_default()
+}
+
 /// used for compatible with old PRC error response for GET
 async fn get_error_handler() -> impl IntoResponse {
     (
@@ -1,4 +1,5 @@
 #![allow(deprecated)]
+use crate::auth::AccessControl;
 use crate::module::{
     add_alert_rpc_methods, add_chain_rpc_methods, add_debug_rpc_methods,
     add_experiment_rpc_methods, add_indexer_rpc_methods, add_integration_test_rpc_methods,
@@ -7,10 +8,11 @@ use crate::module::{
     ExperimentRpcImpl, IndexerRpcImpl, IntegrationTestRpcImpl, MinerRpcImpl, NetRpcImpl,
     PoolRpcImpl, RichIndexerRpcImpl, StatsRpcImpl, SubscriptionRpcImpl,
 };
+use crate::replay::DEFAULT_REPLAY_SIZE;
 use crate::{IoHandler, RPCError};
-use ckb_app_config::{DBConfig, IndexerConfig, RpcConfig};
+use ckb_app_config::{DBConfig, IndexerConfig, RpcConfig, RpcModule};
 use ckb_chain::ChainController;
-use ckb_indexer::IndexerService;
+use ckb_indexer::{IndexerHandle, IndexerService};
 use ckb_indexer_sync::{new_secondary_db, PoolService};
 use ckb_network::NetworkController;
 use ckb_network_alert::{notifier::Notifier as AlertNotifier, verifier::Verifier as AlertVerifier};
@@ -22,6 +24,7 @@ use ckb_types::packed::Script;
 use ckb_util::Mutex;
 use jsonrpc_core::{MetaIoHandler, RemoteProcedure};
 use jsonrpc_utils::pub_sub::Session;
+use std::collections::HashMap;
 use std::sync::Arc;
 
 const DEPRECATED_RPC_PREFIX: &str = "deprecated.";
@@ -30,16 +33,18 @@ const DEPRECATED_RPC_PREFIX: &str = "deprecated.";
 pub struct ServiceBuilder<'a> {
     config: &'a RpcConfig,
     io_handler: IoHandler,
+    method_modules: HashMap<String, RpcModule>,
+    indexer_handle: Option<IndexerHandle>,
 }
 
 macro_rules! set_rpc_module_methods {
-    ($self:ident, $name:expr, $check:ident, $add_methods:ident, $methods:expr) => {{
+    ($self:ident, $module:expr, $check:ident, $add_methods:ident, $methods:expr) => {{
         let mut meta_io = MetaIoHandler::default();
         $add_methods(&mut meta_io, $methods);
         if $self.config.$check() {
-            $self.add_methods(meta_io);
+            $self.add_methods($module, meta_io);
         } else {
-            $self.update_disabled_methods($name, meta_io);
+            $self.update_disabled_methods($module, meta_io);
         }
         $self
     }};
@@ -51,13 +56,21 @@ impl<'a> ServiceBuilder<'a> {
         Self {
             config,
             io_handler: IoHandler::with_compatibility(jsonrpc_core::Compatibility::V2),
+            method_modules: HashMap::new(),
+            indexer_handle: None,
         }
     }
 
     /// Mounts methods from module Chain if it is enabled in the config.
     pub fn enable_chain(mut self, shared: Shared) -> Self {
         let methods = ChainRpcImpl { shared };
-        set_rpc_module_methods!(self, "Chain", chain_enable, add_chain_rpc_methods, methods)
+        set_rpc_module_methods!(
+            self,
+            RpcModule::Chain,
+            chain_enable,
+            add_chain_rpc_methods,
+            methods
+        )
     }
 
     /// Mounts methods from module Pool if it is enabled in the config.
@@ -72,7 +85,13 @@ impl<'a> ServiceBuilder<'a> {
             extra_well_known_lock_scripts,
             extra_well_known_type_scripts,
         );
-        set_rpc_module_methods!(self, "Pool", pool_enable, add_pool_rpc_methods, methods)
+        set_rpc_module_methods!(
+            self,
+            RpcModule::Pool,
+            pool_enable,
+            add_pool_rpc_methods,
+            methods
+        )
     }
 
     /// Mounts methods from module Miner if `enable` is `true` and it is enabled in the config.
@@ -91,9 +110,9 @@ impl<'a> ServiceBuilder<'a> {
         };
         add_miner_rpc_methods(&mut meta_io, methods);
         if enable && self.config.miner_enable() {
-            self.add_methods(meta_io);
+            self.add_methods(RpcModule::Miner, meta_io);
         } else {
-            self.update_disabled_methods("Miner", meta_io);
+            self.update_disabled_methods(RpcModule::Miner, meta_io);
         }
         self
     }
@@ -110,7 +129,13 @@ impl<'a> ServiceBuilder<'a> {
             sync_shared,
             chain_controller,
         };
-        set_rpc_module_methods!(self, "Net", net_enable, add_net_rpc_methods, methods)
+        set_rpc_module_methods!(
+            self,
+            RpcModule::Net,
+            net_enable,
+            add_net_rpc_methods,
+            methods
+        )
     }
 
     /// Mounts methods from module Stats if it is enabled in the config.
@@ -123,7 +148,13 @@ impl<'a> ServiceBuilder<'a> {
             shared,
             alert_notifier,
         };
-        set_rpc_module_methods!(self, "Stats", stats_enable, add_stats_rpc_methods, methods)
+        set_rpc_module_methods!(
+            self,
+            RpcModule::Stats,
+            stats_enable,
+            add_stats_rpc_methods,
+            methods
+        )
     }
 
     /// Mounts methods from module Experiment if it is enabled in the config.
@@ -131,7 +162,7 @@ impl<'a> ServiceBuilder<'a> {
         let methods = ExperimentRpcImpl { shared };
         set_rpc_module_methods!(
             self,
-            "Experiment",
+            RpcModule::Experiment,
             experiment_enable,
             add_experiment_rpc_methods,
             methods
@@ -164,7 +195,7 @@ impl<'a> ServiceBuilder<'a> {
         };
         set_rpc_module_methods!(
             self,
-            "IntegrationTest",
+            RpcModule::IntegrationTest,
             integration_test_enable,
             add_integration_test_rpc_methods,
             methods
@@ -179,13 +210,25 @@ impl<'a> ServiceBuilder<'a> {
         network_controller: NetworkController,
     ) -> Self {
         let methods = AlertRpcImpl::new(alert_verifier, alert_notifier, network_controller);
-        set_rpc_module_methods!(self, "Alert", alert_enable, add_alert_rpc_methods, methods)
+        set_rpc_module_methods!(
+            self,
+            RpcModule::Alert,
+            alert_enable,
+            add_alert_rpc_methods,
+            methods
+        )
     }
 
     /// Mounts methods from module Debug if it is enabled in the config.
-    pub fn enable_debug(mut self) -> Self {
-        let methods = DebugRpcImpl {};
-        set_rpc_module_methods!(self, "Debug", debug_enable, add_debug_rpc_methods, methods)
+    pub fn enable_debug(mut self, shared: Shared) -> Self {
+        let methods = DebugRpcImpl::new(shared);
+        set_rpc_module_methods!(
+            self,
+            RpcModule::Debug,
+            debug_enable,
+            add_debug_rpc_methods,
+            methods
+        )
     }
 
     /// Mounts methods from module Indexer if it is enabled in the config.
@@ -214,10 +257,11 @@ impl<'a> ServiceBuilder<'a> {
             }
 
             let indexer_handle = indexer.handle();
+            self.indexer_handle = Some(indexer_handle.clone());
             let methods = IndexerRpcImpl::new(indexer_handle);
             self = set_rpc_module_methods!(
                 self,
-                "Indexer",
+                RpcModule::Indexer,
                 indexer_enable,
                 add_indexer_rpc_methods,
                 methods
@@ -241,7 +285,7 @@ impl<'a> ServiceBuilder<'a> {
             let rich_indexer_methods = RichIndexerRpcImpl::new(rich_indexer_handle);
             self = set_rpc_module_methods!(
                 self,
-                "RichIndexer",
+                RpcModule::RichIndexer,
                 rich_indexer_enable,
                 add_rich_indexer_rpc_methods,
                 rich_indexer_methods
@@ -253,23 +297,28 @@ impl<'a> ServiceBuilder<'a> {
     pub fn enable_subscription(&mut self, shared: Shared) {
         if self.config.subscription_enable() {
             let methods = SubscriptionRpcImpl::new(
-                shared.notify_controller().clone(),
-                shared.async_handle().clone(),
+                shared,
+                self.indexer_handle.clone(),
+                self.config
+                    .subscription_replay_size
+                    .unwrap_or(DEFAULT_REPLAY_SIZE),
             );
             let mut meta_io = MetaIoHandler::default();
             add_subscription_rpc_methods(&mut meta_io, methods);
-            self.add_methods(meta_io);
+            self.add_methods(RpcModule::Subscription, meta_io);
         }
     }
 
-    fn add_methods<I>(&mut self, rpc_methods: I)
+    fn add_methods<I>(&mut self, module: RpcModule, rpc_methods: I)
     where
         I: IntoIterator<Item = (String, RemoteProcedure<Option<Session>>)>,
     {
         let enable_deprecated_rpc = self.config.enable_deprecated_rpc;
+        let method_modules = &mut self.method_modules;
         self.io_handler
             .extend_with(rpc_methods.into_iter().map(|(name, method)| {
                 if let Some(striped_method_name) = name.strip_prefix(DEPRECATED_RPC_PREFIX) {
+                    method_modules.insert(striped_method_name.to_owned(), module);
                     (
                         striped_method_name.to_owned(),
                         if enable_deprecated_rpc {
@@ -281,27 +330,41 @@ impl<'a> ServiceBuilder<'a> {
                         },
                     )
                 } else {
+                    method_modules.insert(name.clone(), module);
                     (name, method)
                 }
             }));
     }
 
-    fn update_disabled_methods<I, M>(&mut self, module: &str, rpc_methods: I)
+    fn update_disabled_methods<I, M>(&mut self, module: RpcModule, rpc_methods: I)
     where
         I: IntoIterator<Item = (String, M)>,
     {
+        let module_name = format!("{module:?}");
         rpc_methods.into_iter().for_each(|(name, _method)| {
-            let error = Err(RPCError::rpc_module_is_disabled(module));
-            self.io_handler.add_sync_method(
-                name.split(DEPRECATED_RPC_PREFIX)
-                    .collect::<Vec<&str>>()
-                    .last()
-                    .unwrap(),
-                move |_param| error.clone(),
-            )
+            let error = Err(RPCError::rpc_module_is_disabled(&module_name));
+            let name = name
+                .split(DEPRECATED_RPC_PREFIX)
+                .collect::<Vec<&str>>()
+                .last()
+                .unwrap()
+                .to_string();
+            self.io_handler
+                .add_sync_method(&name, move |_param| error.clone());
+            self.method_modules.insert(name, module);
         });
     }
 
+    /// Resolves the access control rules `rpc.auth` and the rate limit rules `rpc.rate_limits`
+    /// against the mounted methods.
+    ///
+    /// It must be called after all the modules are mounted. Returns an error if any access
+    /// control rule or rate limit rule is invalid.
+    pub fn access_control(&self) -> Result<AccessControl, String> {
+        AccessControl::new(&self.config.auth, &self.method_modules)?
+            .with_rate_limits(&self.config.rate_limits, &self.method_modules)
+    }
+
     /// Builds the RPC methods handler used in the RPC server.
     pub fn build(self) -> IoHandler {
         let mut io_handler = self.io_handler;
@@ -0,0 +1,101 @@
+use crate::auth::{parse_authorization_line, AccessControl};
+use crate::IoHandler;
+use ckb_app_config::{RpcAuthConfig, RpcModule};
+use jsonrpc_core::{Compatibility, Value};
+use std::collections::HashMap;
+
+const GET_TIP: &str = r#"{"jsonrpc":"2.0","id":1,"method":"get_tip_block_number","params":[]}"#;
+const CLEAR_POOL: &str = r#"{"jsonrpc":"2.0","id":1,"method":"clear_tx_pool","params":[]}"#;
+
+fn io_handler() -> IoHandler {
+    let mut io = IoHandler::with_compatibility(Compatibility::V2);
+    io.add_sync_method("get_tip_block_number", |_| Ok("0x0".into()));
+    io.add_sync_method("clear_tx_pool", |_| Ok(Value::Null));
+    io
+}
+
+fn method_modules() -> HashMap<String, RpcModule> {
+    vec![
+        ("get_tip_block_number".to_string(), RpcModule::Chain),
+        ("clear_tx_pool".to_string(), RpcModule::Pool),
+    ]
+    .into_iter()
+    .collect()
+}
+
+fn auth_config(token: Option<&str>, username: Option<&str>) -> RpcAuthConfig {
+    RpcAuthConfig {
+        token: token.map(ToString::to_string),
+        username: username.map(ToString::to_string),
+        password: username.map(|_| "secret".to_string()),
+        modules: vec![RpcModule::Chain],
+        methods: vec![],
+    }
+}
+
+fn is_unauthorized(response: &str) -> bool {
+    let response: Value = serde_json::from_str(response).unwrap();
+    response["error"]["code"] == Value::from(-8)
+}
+
+#[test]
+fn test_access_control_disabled_without_rules() {
+    let access_control = AccessControl::new(&[], &method_modules()).unwrap();
+    assert!(!access_control.is_enabled());
+}
+
+#[test]
+fn test_access_control_rejects_invalid_rules() {
+    // rules without credential fail to load instead of disabling the authentication
+    assert!(AccessControl::new(&[auth_config(None, None)], &method_modules()).is_err());
+    // a single invalid rule fails the whole config
+    let valid = auth_config(Some("reader"), None);
+    let mut both = auth_config(Some("token"), Some("admin"));
+    both.modules.push(RpcModule::Pool);
+    assert!(AccessControl::new(&[valid, both], &method_modules()).is_err());
+}
+
+#[test]
+fn test_access_control_grants() {
+    let mut admin = auth_config(None, Some("admin"));
+    admin.modules.push(RpcModule::Pool);
+    let reader = auth_config(Some("reader"), None);
+    let access_control = AccessControl::new(&[reader, admin], &method_modules()).unwrap();
+    assert!(access_control.is_enabled());
+    let authenticator = access_control.authenticator(&io_handler());
+
+    assert!(authenticator.authenticate(None).is_none());
+    assert!(authenticator.authenticate(Some("Bearer unknown")).is_none());
+    assert!(authenticator.authenticate(Some("reader")).is_none());
+
+    let (identity, reader) = authenticator.authenticate(Some("Bearer reader")).unwrap();
+    assert_eq!(identity, 0);
+    let response = reader.handle_request_sync(GET_TIP, None).unwrap();
+    assert!(!is_unauthorized(&response));
+    let response = reader.handle_request_sync(CLEAR_POOL, None).unwrap();
+    assert!(is_unauthorized(&response));
+
+    // base64("admin:secret")
+    let (identity, admin) = authenticator
+        .authenticate(Some("basic YWRtaW46c2VjcmV0"))
+        .unwrap();
+    assert_eq!(identity, 1);
+    let response = admin.handle_request_sync(GET_TIP, None).unwrap();
+    assert!(!is_unauthorized(&response));
+    let response = admin.handle_request_sync(CLEAR_POOL, None).unwrap();
+    assert!(!is_unauthorized(&response));
+}
+
+#[test]
+fn test_parse_authorization_line() {
+    assert_eq!(
+        parse_authorization_line("Authorization: Bearer reader"),
+        Some(" Bearer reader")
+    );
+    assert_eq!(
+        parse_authorization_line("authorization:Bearer reader"),
+        Some("Bearer reader")
+    );
+    assert_eq!(parse_authorization_line("Bearer reader"), None);
+    assert_eq!(parse_authorization_line(GET_TIP), None);
+}
@@ -350,7 +350,9 @@ where
 // * Use replace_rpc_response to skip the response matching assertions.
 // * Fix timestamp related fields.
 fn mock_rpc_response(example: &RpcTestExample, response: &mut RpcTestResponse) {
-    use ckb_jsonrpc_types::{BannedAddr, Capacity, LocalNode, RemoteNode, Uint64};
+    use ckb_jsonrpc_types::{
+        BannedAddr, Capacity, LocalNode, PeerReputation, RemoteNode, TransactionTrace, Uint64,
+    };
 
     let example_tx_hash = format!("{EXAMPLE_TX_HASH:#x}");
 
@@ -358,6 +360,8 @@ fn mock_rpc_response(example: &RpcTestExample, response: &mut RpcTestResponse) {
         "local_node_info" => replace_rpc_response::<LocalNode>(example, response),
         "get_peers" => replace_rpc_response::<Vec<RemoteNode>>(example, response),
         "get_banned_addresses" => replace_rpc_response::<Vec<BannedAddr>>(example, response),
+        "export_banned_addresses" => replace_rpc_response::<Vec<BannedAddr>>(example, response),
+        "get_peer_reputation" => replace_rpc_response::<PeerReputation>(example, response),
         "calculate_dao_maximum_withdraw" => replace_rpc_response::<Capacity>(example, response),
         "subscribe" => replace_rpc_response::<Uint64>(example, response),
         "unsubscribe" => replace_rpc_response::<bool>(example, response),
@@ -390,6 +394,7 @@ fn mock_rpc_response(example: &RpcTestExample, response: &mut RpcTestResponse) {
             response.result["timestamp"] = example.response.result["timestamp"].clone()
         }
         "estimate_fee_rate" => replace_rpc_response::<Uint64>(example, response),
+        "debug_trace_transaction" => replace_rpc_response::<TransactionTrace>(example, response),
         _ => {}
     }
 }
@@ -413,7 +418,10 @@ fn before_rpc_example(suite: &RpcTestSuite, example: &mut RpcTestExample) -> boo
         ("generate_block_with_template", 42) => return false,
         ("process_block_without_verify", 42) => return false,
         ("notify_transaction", 42) => return false,
+        ("send_transaction_package", 42) => return false,
+        ("create_backup", 42) => return false,
         ("truncate", 42) => return false,
+        ("wait_block_template", 42) => return false,
         ("get_block_template", 42) => suite.wait_block_template_update(),
         _ => return true,
     }
@@ -23,11 +23,15 @@ use tokio::net::TcpStream;
 
 use self::setup::setup_rpc_test_suite;
 
+mod auth;
 mod error;
 mod examples;
 mod fee_rate;
 mod module;
+mod rate_limit;
+mod replay;
 mod setup;
+mod tx_watch;
 
 #[derive(Debug, Deserialize, Serialize, Clone, Eq, PartialEq, Default)]
 struct RpcTestRequest {
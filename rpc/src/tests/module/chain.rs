@@ -0,0 +1,41 @@
+use ckb_store::ChainStore;
+use ckb_test_chain_utils::always_success_consensus;
+use ckb_types::prelude::*;
+
+use crate::{
+    error::RPCError,
+    tests::{setup, RpcTestRequest},
+};
+
+#[test]
+fn test_block_pruned() {
+    let suite = setup(always_success_consensus());
+    let store = suite.shared.store();
+    let pruned = store.get_block_hash(9).unwrap();
+    let kept = store.get_block_hash(10).unwrap();
+    let pruned_cellbase = store.get_block_body(&pruned)[0].hash();
+
+    let mut batch = store.new_write_batch();
+    batch.delete_block_body(9, &pruned, 1).unwrap();
+    batch.insert_pruned_block_number(10).unwrap();
+    store.write(&batch).unwrap();
+    suite.shared.refresh_snapshot();
+    let request = |method: &str, hash: &ckb_types::packed::Byte32| RpcTestRequest {
+        id: 42,
+        jsonrpc: "2.0".to_string(),
+        method: method.to_string(),
+        params: vec![format!("{hash:#x}").into()],
+    };
+
+    let response = suite.rpc(&request("get_block", &pruned));
+    assert_eq!(response.error["code"], RPCError::BlockPruned as i64);
+    let response = suite.rpc(&request("get_block", &kept));
+    assert!(response.error.is_null());
+    assert!(response.result.is_object());
+
+    let response = suite.rpc(&request("get_transaction", &pruned_cellbase));
+    assert_eq!(response.error["code"], RPCError::BlockPruned as i64);
+    let cellbase = store.get_block_body(&kept)[0].hash();
+    let response = suite.rpc(&request("get_transaction", &cellbase));
+    assert!(response.error.is_null());
+}
@@ -99,3 +99,56 @@ fn test_get_block_template_cache() {
         assert_ne!(response_new.result["proposals"].to_string(), "[]");
     }
 }
+
+#[test]
+fn test_wait_block_template() {
+    let suite = setup(always_success_consensus());
+    let wait_block_template = |work_id: &str, timeout: &str| {
+        suite
+            .rpc(&RpcTestRequest {
+                id: 42,
+                jsonrpc: "2.0".to_string(),
+                method: "wait_block_template".to_string(),
+                params: vec![json!(work_id), json!(timeout)],
+            })
+            .result
+    };
+
+    // the template is returned immediately when the miner is working on a former one
+    let template = wait_block_template("0xffffffff", "0x0");
+    let work_id = template["work_id"].as_str().unwrap().to_owned();
+
+    // the current template is returned on timeout
+    let template = wait_block_template(&work_id, "0x64");
+    assert_eq!(template["work_id"], json!(work_id));
+
+    // a new transaction in the pool updates the template
+    let store = suite.shared.store();
+    let tip = store.get_tip_header().unwrap();
+    let tip_block = store.get_block(&tip.hash()).unwrap();
+    let previous_output = OutPoint::new(tip_block.transactions().first().unwrap().hash(), 0);
+    let tx = TransactionBuilder::default()
+        .input(CellInput::new(previous_output, 0))
+        .output(
+            CellOutputBuilder::default()
+                .capacity(capacity_bytes!(100).pack())
+                .lock(always_success_cell().2.clone())
+                .build(),
+        )
+        .output_data(Default::default())
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(OutPoint::new(always_success_transaction().hash(), 0))
+                .build(),
+        )
+        .build();
+    let new_tx: ckb_jsonrpc_types::Transaction = tx.data().into();
+    suite.rpc(&RpcTestRequest {
+        id: 42,
+        jsonrpc: "2.0".to_string(),
+        method: "send_transaction".to_string(),
+        params: vec![json!(new_tx), json!("passthrough")],
+    });
+    let template = wait_block_template(&work_id, "0x2710");
+    assert_ne!(template["work_id"], json!(work_id));
+}
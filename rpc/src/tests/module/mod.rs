@@ -1,3 +1,4 @@
+mod chain;
 mod miner;
 mod pool;
 mod test;
@@ -0,0 +1,112 @@
+use crate::rate_limit::{ClientKey, RateLimits, Throttler};
+use crate::IoHandler;
+use ckb_app_config::{RpcModule, RpcRateLimitConfig};
+use jsonrpc_core::{Compatibility, Value};
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr};
+use std::sync::Arc;
+
+const GET_TIP: &str = r#"{"jsonrpc":"2.0","id":1,"method":"get_tip_block_number","params":[]}"#;
+const GET_HEADER: &str = r#"{"jsonrpc":"2.0","id":1,"method":"get_tip_header","params":[]}"#;
+
+fn io_handler() -> IoHandler {
+    let mut io = IoHandler::with_compatibility(Compatibility::V2);
+    io.add_sync_method("get_tip_block_number", |_| Ok("0x0".into()));
+    io.add_sync_method("get_tip_header", |_| Ok(Value::Null));
+    io
+}
+
+fn method_modules() -> HashMap<String, RpcModule> {
+    vec![
+        ("get_tip_block_number".to_string(), RpcModule::Chain),
+        ("get_tip_header".to_string(), RpcModule::Chain),
+    ]
+    .into_iter()
+    .collect()
+}
+
+fn is_throttled(io: &IoHandler, request: &str) -> bool {
+    let response: Value = serde_json::from_str(&io.handle_request_sync(request, None).unwrap())
+        .expect("valid response");
+    response["error"]["code"] == Value::from(-9)
+}
+
+#[test]
+fn test_rate_limits_per_client() {
+    let configs = vec![
+        RpcRateLimitConfig {
+            module: Some(RpcModule::Chain),
+            method: None,
+            requests_per_second: 1,
+            burst: Some(2),
+        },
+        RpcRateLimitConfig {
+            module: None,
+            method: Some("get_tip_block_number".to_string()),
+            requests_per_second: 1,
+            burst: None,
+        },
+    ];
+    let limits = RateLimits::new(&configs, &method_modules()).unwrap();
+    assert!(!limits.is_empty());
+    let throttler = Throttler::new(limits);
+    let io = io_handler();
+
+    let alice = ClientKey::Address(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)));
+    let bob = ClientKey::Identity(0);
+    let handler = throttler.handler(alice, &io);
+    assert!(Arc::ptr_eq(&handler, &throttler.handler(alice, &io)));
+
+    // the method rule overrides the module rule
+    assert!(!is_throttled(&handler, GET_TIP));
+    assert!(is_throttled(&handler, GET_TIP));
+
+    assert!(!is_throttled(&handler, GET_HEADER));
+    assert!(!is_throttled(&handler, GET_HEADER));
+    assert!(is_throttled(&handler, GET_HEADER));
+
+    // other clients have their own buckets
+    let handler = throttler.handler(bob, &io);
+    assert!(!is_throttled(&handler, GET_TIP));
+    assert!(!is_throttled(&handler, GET_HEADER));
+}
+
+fn rate_limit_config(
+    module: Option<RpcModule>,
+    method: Option<&str>,
+    requests_per_second: u32,
+    burst: Option<u32>,
+) -> RpcRateLimitConfig {
+    RpcRateLimitConfig {
+        module,
+        method: method.map(ToString::to_string),
+        requests_per_second,
+        burst,
+    }
+}
+
+#[test]
+fn test_reject_zero_rate_limits() {
+    let module_rule = rate_limit_config(Some(RpcModule::Chain), None, 0, None);
+    assert!(RateLimits::new(&[module_rule], &method_modules()).is_err());
+
+    let method_rule = rate_limit_config(None, Some("get_tip_header"), 0, Some(1));
+    assert!(RateLimits::new(&[method_rule], &method_modules()).is_err());
+}
+
+#[test]
+fn test_reject_zero_burst_rate_limits() {
+    let rule = rate_limit_config(Some(RpcModule::Chain), None, 1, Some(0));
+    assert!(RateLimits::new(&[rule], &method_modules()).is_err());
+}
+
+#[test]
+fn test_reject_rate_limits_without_target() {
+    let rule = rate_limit_config(None, None, 1, None);
+    assert!(RateLimits::new(&[rule], &method_modules()).is_err());
+
+    // a single invalid rule fails the whole config
+    let valid = rate_limit_config(Some(RpcModule::Chain), None, 1, None);
+    let invalid = rate_limit_config(None, None, 1, None);
+    assert!(RateLimits::new(&[valid, invalid], &method_modules()).is_err());
+}
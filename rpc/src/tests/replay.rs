@@ -0,0 +1,72 @@
+use crate::replay::{Gap, TopicLog};
+
+fn messages(messages: &[(u64, u32)]) -> Vec<u32> {
+    messages.iter().map(|(_, message)| *message).collect()
+}
+
+#[test]
+fn test_topic_log_replay_since_seq() {
+    let log = TopicLog::new(3, 16);
+    let first = log.publish(0u32);
+    for message in 1..5 {
+        assert_eq!(first + message as u64, log.publish(message));
+    }
+
+    // the latest 3 messages are kept
+    let (replay, _) = log.subscribe(Some(first + 1)).unwrap();
+    assert_eq!(messages(&replay), vec![2, 3, 4]);
+    let (replay, _) = log.subscribe(Some(first + 3)).unwrap();
+    assert_eq!(messages(&replay), vec![4]);
+    let (replay, _) = log.subscribe(Some(first + 4)).unwrap();
+    assert!(replay.is_empty());
+    let (replay, _) = log.subscribe(None).unwrap();
+    assert!(replay.is_empty());
+}
+
+#[test]
+fn test_topic_log_gap() {
+    let log = TopicLog::new(3, 16);
+    let first = log.publish(0u32);
+    for message in 1..5 {
+        log.publish(message);
+    }
+
+    // message 1 is dropped
+    assert_eq!(
+        log.subscribe(Some(first)).unwrap_err(),
+        Gap {
+            oldest_seq: first + 2
+        }
+    );
+    // the sequence number is from the future, e.g., before the node restarted
+    assert!(log.subscribe(Some(first + 5)).is_err());
+}
+
+#[test]
+fn test_topic_log_live_messages_after_replay() {
+    let log = TopicLog::new(3, 16);
+    let first = log.publish(0u32);
+    log.publish(1);
+
+    let (replay, mut receiver) = log.subscribe(Some(first)).unwrap();
+    assert_eq!(messages(&replay), vec![1]);
+    log.publish(2);
+    assert_eq!(receiver.try_recv().unwrap(), (first + 2, 2));
+    assert!(receiver.try_recv().is_err());
+}
+
+#[test]
+fn test_topic_log_reset() {
+    let log = TopicLog::new(3, 16);
+    let first = log.publish(0u32);
+    log.publish(1);
+    log.reset();
+
+    // the subscribers received all the messages before the reset can not resume either
+    assert!(log.subscribe(Some(first + 1)).is_err());
+    let (replay, mut receiver) = log.subscribe(None).unwrap();
+    assert!(replay.is_empty());
+    let seq = log.publish(2);
+    assert_eq!(seq, first + 3);
+    assert_eq!(receiver.try_recv().unwrap(), (seq, 2));
+}
@@ -85,6 +85,8 @@ pub(crate) fn setup_rpc_test_suite(height: u64, consensus: Option<Consensus>) ->
             notify: vec![],
             notify_scripts: vec![],
             notify_timeout_millis: 800,
+            weight: 1,
+            reward_targets: vec![],
         }))
         .build()
         .unwrap();
@@ -197,6 +199,9 @@ pub(crate) fn setup_rpc_test_suite(height: u64, consensus: Option<Consensus>) ->
         enable_deprecated_rpc: true,
         extra_well_known_lock_scripts: vec![],
         extra_well_known_type_scripts: vec![],
+        auth: vec![],
+        rate_limits: vec![],
+        subscription_replay_size: None,
     };
 
     let builder = ServiceBuilder::new(&rpc_config)
@@ -222,13 +227,14 @@ pub(crate) fn setup_rpc_test_suite(height: u64, consensus: Option<Consensus>) ->
             vec![],
             vec![],
         )
-        .enable_debug()
+        .enable_debug(shared.clone())
         .enable_alert(alert_verifier, alert_notifier, network_controller);
 
+    let access_control = builder.access_control().unwrap();
     let io_handler = builder.build();
     let shared_clone = shared.clone();
     let handler = shared_clone.async_handle().clone();
-    let rpc_server = RpcServer::new(rpc_config, io_handler, handler);
+    let rpc_server = RpcServer::new(rpc_config, io_handler, access_control, handler);
 
     let rpc_client = reqwest::blocking::Client::new();
     let rpc_uri = format!(
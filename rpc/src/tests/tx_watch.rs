@@ -0,0 +1,58 @@
+use crate::tx_watch::TxStatusWatch;
+use ckb_jsonrpc_types::TxStatus;
+use ckb_types::{h256, packed::Byte32, prelude::*};
+
+fn committed(number: u64) -> TxStatus {
+    TxStatus::committed(number.into(), h256!("0x1"), 1u32.into())
+}
+
+#[test]
+fn test_tx_status_watch_transitions() {
+    let tx_hash: Byte32 = h256!("0x2").pack();
+    let mut watch = TxStatusWatch::new(tx_hash.clone(), 3);
+
+    let event = watch.update(TxStatus::pending(), None).unwrap();
+    assert_eq!(event.tx_hash, h256!("0x2"));
+    assert_eq!(event.tx_status, TxStatus::pending());
+    assert!(event.confirmations.is_none());
+    // the same status is not pushed again
+    assert!(watch.update(TxStatus::pending(), None).is_none());
+
+    assert!(watch.update(TxStatus::proposed(), None).is_some());
+    assert!(!watch.is_committed());
+
+    let event = watch.update(committed(10), Some(1)).unwrap();
+    assert_eq!(event.confirmations, Some(1u64.into()));
+    assert!(watch.is_committed());
+    assert!(!watch.is_finished());
+
+    // the committed block is detached, and the transaction returns to tx-pool
+    assert!(watch.update(TxStatus::pending(), None).is_some());
+    assert!(!watch.is_committed());
+
+    assert!(watch.update(committed(11), Some(1)).is_some());
+    assert!(watch.update(committed(11), Some(1)).is_none());
+    assert!(watch.update(committed(11), Some(2)).is_some());
+    assert!(!watch.is_finished());
+    let event = watch.update(committed(11), Some(3)).unwrap();
+    assert_eq!(event.confirmations, Some(3u64.into()));
+    assert!(watch.is_finished());
+}
+
+#[test]
+fn test_tx_status_watch_rejected() {
+    let mut watch = TxStatusWatch::new(h256!("0x2").pack(), 1);
+    assert!(watch.update(TxStatus::pending(), None).is_some());
+
+    let reason = r#"{"type":"Expiry","description":"expired"}"#.to_owned();
+    let event = watch
+        .update(TxStatus::rejected(reason.clone()), None)
+        .unwrap();
+    assert_eq!(event.tx_status.reason, Some(reason));
+    assert!(!watch.is_finished());
+
+    // the transaction is submitted again
+    assert!(watch.update(TxStatus::pending(), None).is_some());
+    assert!(watch.update(committed(5), Some(1)).is_some());
+    assert!(watch.is_finished());
+}
@@ -0,0 +1,65 @@
+//! The status tracking of the transactions watched by the subscription topic `transaction_status`.
+use ckb_jsonrpc_types::{Status, TransactionStatusEvent, TxStatus};
+use ckb_types::{packed::Byte32, prelude::*};
+
+/// The events which may change the status of the watched transactions.
+#[derive(Clone, Debug)]
+pub(crate) enum TxStatusUpdate {
+    /// The tx-pool has accepted, proposed or rejected the transaction.
+    Pool(Byte32, TxStatus),
+    /// The canonical chain has changed, by a new tip block or a reorganization.
+    Chain,
+}
+
+/// Tracks the status of a transaction, and the number of confirmations after it is committed.
+pub(crate) struct TxStatusWatch {
+    tx_hash: Byte32,
+    depth: u64,
+    last: Option<(TxStatus, Option<u64>)>,
+}
+
+impl TxStatusWatch {
+    /// Watches the transaction until it is committed with `depth` confirmations.
+    pub(crate) fn new(tx_hash: Byte32, depth: u64) -> Self {
+        TxStatusWatch {
+            tx_hash,
+            depth,
+            last: None,
+        }
+    }
+
+    pub(crate) fn tx_hash(&self) -> &Byte32 {
+        &self.tx_hash
+    }
+
+    /// Returns true if the last pushed status is committed.
+    pub(crate) fn is_committed(&self) -> bool {
+        matches!(&self.last, Some((tx_status, _)) if tx_status.status == Status::Committed)
+    }
+
+    /// Returns true if the transaction has been committed with enough confirmations.
+    pub(crate) fn is_finished(&self) -> bool {
+        matches!(self.last, Some((_, Some(confirmations))) if confirmations >= self.depth)
+    }
+
+    /// Updates the status, returns the event to push if the status or the number of
+    /// confirmations has changed.
+    pub(crate) fn update(
+        &mut self,
+        tx_status: TxStatus,
+        confirmations: Option<u64>,
+    ) -> Option<TransactionStatusEvent> {
+        let current = Some((tx_status, confirmations));
+        if self.last == current {
+            return None;
+        }
+        self.last = current;
+        self.last
+            .clone()
+            .map(|(tx_status, confirmations)| TransactionStatusEvent {
+                tx_hash: self.tx_hash.unpack(),
+                tx_status,
+                confirmations: confirmations.map(Into::into),
+            })
+    }
+}
@@ -12,8 +12,9 @@ pub use crate::error::{ScriptError, TransactionScriptError};
 pub use crate::scheduler::{Scheduler, ROOT_VM_ID};
 pub use crate::syscalls::generator::generate_ckb_syscalls;
 pub use crate::types::{
-    ChunkCommand, CoreMachine, DataLocation, DataPieceId, RunMode, ScriptGroup, ScriptGroupType,
-    ScriptVersion, TransactionState, TxData, VerifyResult, VmArgs, VmIsa, VmState, VmVersion,
+    ChunkCommand, CoreMachine, DataLocation, DataPieceId, ProcessEvent, RunMode, ScriptGroup,
+    ScriptGroupType, ScriptVersion, TransactionState, TxData, VerifyResult, VmArgs, VmId, VmIsa,
+    VmState, VmVersion,
 };
 pub use crate::verify::TransactionScriptsVerifier;
 pub use crate::verify_env::TxVerifyEnv;
@@ -6,8 +6,8 @@ use crate::syscalls::{
 
 use crate::types::{
     CoreMachineType, DataLocation, DataPieceId, DebugContext, Fd, FdArgs, FullSuspendedState,
-    Machine, Message, ReadState, RunMode, SgData, VmArgs, VmContext, VmId, VmState, WriteState,
-    FIRST_FD_SLOT, FIRST_VM_ID,
+    Machine, Message, ProcessEvent, ReadState, RunMode, SgData, VmArgs, VmContext, VmId, VmState,
+    WriteState, FIRST_FD_SLOT, FIRST_VM_ID,
 };
 use ckb_traits::{CellDataProvider, ExtensionProvider, HeaderProvider};
 use ckb_types::core::Cycle;
@@ -301,6 +301,10 @@ where
         match result {
             Ok(code) => {
                 self.terminated_vms.insert(vm_id_to_run, code);
+                self.trace_process(ProcessEvent::Terminate {
+                    vm_id: vm_id_to_run,
+                    exit_code: code,
+                });
                 // When root VM terminates, the execution stops immediately, we will purge
                 // all non-root VMs, and only keep root VM in states.
                 // When non-root VM terminates, we only purge the VM's own states.
@@ -370,6 +374,12 @@ where
         self.iterate_process_results(id, result)
     }
 
+    fn trace_process(&self, event: ProcessEvent) {
+        if let Some(tracer) = &self.debug_context.process_tracer {
+            tracer(&self.sg_data.sg_info.script_hash, &event);
+        }
+    }
+
     fn process_message_box(&mut self) -> Result<(), Error> {
         let messages: Vec<Message> = self.message_box.lock().expect("lock").drain(..).collect();
         for message in messages {
@@ -410,6 +420,10 @@ where
                     // The insert operation removes the old vm instance and adds the new vm instance.
                     debug_assert!(self.instantiated.contains_key(&vm_id));
                     self.instantiated.insert(vm_id, (context, new_machine));
+                    self.trace_process(ProcessEvent::Exec {
+                        vm_id,
+                        location: args.location,
+                    });
                 }
                 Message::Spawn(vm_id, args) => {
                     // All fds must belong to the correct owner
@@ -438,6 +452,11 @@ where
                     // Here we keep the original version of file descriptors.
                     // If one fd is moved afterward, this inherited file descriptors doesn't change.
                     self.inherited_fd.insert(spawned_vm_id, args.fds.clone());
+                    self.trace_process(ProcessEvent::Spawn {
+                        parent: vm_id,
+                        child: spawned_vm_id,
+                        location: args.location.clone(),
+                    });
 
                     let (_, machine) = self.ensure_get_instantiated(&vm_id)?;
                     machine
@@ -64,9 +64,11 @@ pub(crate) type Machine = AsmMachine;
 pub(crate) type Machine = TraceMachine<CoreMachine>;
 
 pub(crate) type DebugPrinter = Arc<dyn Fn(&Byte32, &str) + Send + Sync>;
+pub(crate) type ProcessTracer = Arc<dyn Fn(&Byte32, &ProcessEvent) + Send + Sync>;
 
 pub struct DebugContext {
     pub debug_printer: DebugPrinter,
+    pub process_tracer: Option<ProcessTracer>,
     #[cfg(test)]
     pub skip_pause: Arc<std::sync::atomic::AtomicBool>,
 }
@@ -357,6 +359,34 @@ pub struct DataLocation {
     pub length: u64,
 }
 
+/// A process lifecycle event of the VMs running a script group.
+#[derive(Clone, Debug)]
+pub enum ProcessEvent {
+    /// A VM spawned a new VM.
+    Spawn {
+        /// The spawning VM.
+        parent: VmId,
+        /// The spawned VM.
+        child: VmId,
+        /// The program of the spawned VM.
+        location: DataLocation,
+    },
+    /// A VM replaced its program via exec.
+    Exec {
+        /// The VM calling exec.
+        vm_id: VmId,
+        /// The new program.
+        location: DataLocation,
+    },
+    /// A VM terminated.
+    Terminate {
+        /// The terminated VM.
+        vm_id: VmId,
+        /// The exit code.
+        exit_code: i8,
+    },
+}
+
 #[derive(Clone, Debug)]
 pub struct ExecV2Args {
     pub location: DataLocation,
@@ -5,8 +5,9 @@ use crate::{
     error::{ScriptError, TransactionScriptError},
     type_id::TypeIdSystemScript,
     types::{
-        DebugContext, DebugPrinter, FullSuspendedState, RunMode, ScriptGroup, ScriptGroupType,
-        ScriptVersion, SgData, TransactionState, TxData, VerifyResult,
+        DebugContext, DebugPrinter, FullSuspendedState, ProcessEvent, ProcessTracer, RunMode,
+        ScriptGroup, ScriptGroupType, ScriptVersion, SgData, TransactionState, TxData,
+        VerifyResult,
     },
     verify_env::TxVerifyEnv,
 };
@@ -58,6 +59,7 @@ pub struct TransactionScriptsVerifier<DL> {
     tx_data: Arc<TxData<DL>>,
 
     debug_printer: DebugPrinter,
+    process_tracer: Option<ProcessTracer>,
     #[cfg(test)]
     skip_pause: Arc<AtomicBool>,
 }
@@ -94,6 +96,7 @@ where
         TransactionScriptsVerifier {
             tx_data,
             debug_printer,
+            process_tracer: None,
             #[cfg(test)]
             skip_pause,
         }
@@ -112,6 +115,19 @@ where
         self.debug_printer = Arc::new(func);
     }
 
+    /// Sets a callback to trace the spawn, exec and termination of the VMs.
+    ///
+    /// The callback receives two parameters:
+    ///
+    /// * `hash: &Byte32`: this is the script hash of currently running script group.
+    /// * `event: &ProcessEvent`: the process lifecycle event.
+    pub fn set_process_tracer<F: Fn(&Byte32, &ProcessEvent) + Sync + Send + 'static>(
+        &mut self,
+        func: F,
+    ) {
+        self.process_tracer = Some(Arc::new(func));
+    }
+
     #[cfg(test)]
     pub(crate) fn set_skip_pause(&self, skip_pause: bool) {
         self.skip_pause.store(skip_pause, Ordering::SeqCst);
@@ -554,6 +570,7 @@ where
         let sg_data = SgData::new(&self.tx_data, script_group)?;
         let debug_context = DebugContext {
             debug_printer: Arc::clone(&self.debug_printer),
+            process_tracer: self.process_tracer.clone(),
             #[cfg(test)]
             skip_pause: Arc::clone(&self.skip_pause),
         };
@@ -569,6 +586,7 @@ where
         let sg_data = SgData::new(&self.tx_data, script_group)?;
         let debug_context = DebugContext {
             debug_printer: Arc::clone(&self.debug_printer),
+            process_tracer: self.process_tracer.clone(),
             #[cfg(test)]
             skip_pause: Arc::clone(&self.skip_pause),
         };
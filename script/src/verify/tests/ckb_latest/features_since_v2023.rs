@@ -251,6 +251,69 @@ fn check_spawn_exec() {
     assert_eq!(result.is_ok(), script_version >= ScriptVersion::V2);
 }
 
+#[test]
+fn check_spawn_exec_process_trace() {
+    let script_version = SCRIPT_VERSION;
+
+    let (spawn_caller_cell, spawn_caller_data_hash) =
+        load_cell_from_path("testdata/spawn_caller_exec");
+    let (spawn_callee_caller_cell, _) = load_cell_from_path("testdata/spawn_callee_exec_caller");
+    let (spawn_callee_callee_cell, _) = load_cell_from_path("testdata/spawn_callee_exec_callee");
+
+    let spawn_caller_script = Script::new_builder()
+        .hash_type(script_version.data_hash_type().into())
+        .code_hash(spawn_caller_data_hash)
+        .build();
+    let output = CellOutputBuilder::default()
+        .capacity(capacity_bytes!(100).pack())
+        .lock(spawn_caller_script.clone())
+        .build();
+    let input = CellInput::new(OutPoint::null(), 0);
+
+    let transaction = TransactionBuilder::default().input(input).build();
+    let dummy_cell = create_dummy_cell(output);
+
+    let rtx = ResolvedTransaction {
+        transaction,
+        resolved_cell_deps: vec![
+            spawn_caller_cell,
+            spawn_callee_caller_cell,
+            spawn_callee_callee_cell,
+        ],
+        resolved_inputs: vec![dummy_cell],
+        resolved_dep_groups: vec![],
+    };
+    let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let verifier = TransactionScriptsVerifierWithEnv::new();
+    let result = verifier.verify_map(script_version, &rtx, |mut verifier| {
+        let events = Arc::clone(&events);
+        verifier.set_process_tracer(move |hash: &Byte32, event: &ProcessEvent| {
+            events
+                .lock()
+                .unwrap()
+                .push((hash.clone(), format!("{:?}", event)));
+        });
+        verifier.verify(0xffffff)
+    });
+    if script_version < ScriptVersion::V2 {
+        assert!(result.is_err());
+        return;
+    }
+    assert!(result.is_ok());
+
+    let events = events.lock().unwrap();
+    let script_hash = spawn_caller_script.calc_script_hash();
+    assert!(events.iter().all(|(hash, _)| hash == &script_hash));
+    let events: Vec<_> = events.iter().map(|(_, event)| event.as_str()).collect();
+    assert_eq!(events.len(), 4);
+    assert!(events[0].starts_with("Spawn { parent: 0, child: 1,"));
+    assert!(events[0].contains("CellDep(1)"));
+    assert!(events[1].starts_with("Exec { vm_id: 1,"));
+    assert!(events[1].contains("CellDep(2)"));
+    assert_eq!(events[2], "Terminate { vm_id: 1, exit_code: 0 }");
+    assert_eq!(events[3], "Terminate { vm_id: 0, exit_code: 0 }");
+}
+
 #[test]
 fn check_spawn_strcat_wrap() {
     let script_version = SCRIPT_VERSION;
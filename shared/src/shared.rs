@@ -6,7 +6,7 @@ use arc_swap::{ArcSwap, Guard};
 use ckb_async_runtime::Handle;
 use ckb_chain_spec::consensus::Consensus;
 use ckb_constant::store::TX_INDEX_UPPER_BOUND;
-use ckb_constant::sync::MAX_TIP_AGE;
+use ckb_constant::sync::{MAX_TIP_AGE, MIN_PRUNE_DEPTH};
 use ckb_db::{Direction, IteratorMode};
 use ckb_db_schema::{COLUMN_BLOCK_BODY, COLUMN_NUMBER_HASH};
 use ckb_error::{AnyError, Error};
@@ -36,6 +36,8 @@ use std::time::Duration;
 const FREEZER_INTERVAL: Duration = Duration::from_secs(60);
 const THRESHOLD_EPOCH: EpochNumber = 2;
 const MAX_FREEZE_LIMIT: BlockNumber = 30_000;
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+const MAX_PRUNE_LIMIT: BlockNumber = 10_000;
 
 pub const SHRINK_THRESHOLD: usize = 300;
 
@@ -201,6 +203,97 @@ impl Shared {
         Ok(())
     }
 
+    /// Spawn prune background thread that periodically deletes the bodies of the blocks which are
+    /// deeper than `prune_depth`.
+    ///
+    /// Headers, block exts and transaction infos are kept, so the pruned blocks and transactions
+    /// can still be located.
+    pub fn spawn_prune(&self, prune_depth: BlockNumber) {
+        let prune_depth = if prune_depth < MIN_PRUNE_DEPTH {
+            ckb_logger::warn!(
+                "prune_depth {} is less than the minimum {}, use the minimum instead",
+                prune_depth,
+                MIN_PRUNE_DEPTH
+            );
+            MIN_PRUNE_DEPTH
+        } else {
+            prune_depth
+        };
+        ckb_logger::info!("Pruned mode enabled, keep {} recent blocks", prune_depth);
+        let signal_receiver = new_crossbeam_exit_rx();
+        let shared = self.clone();
+        let prune_jh = thread::Builder::new()
+            .name("prune".to_string())
+            .spawn(move || loop {
+                match signal_receiver.recv_timeout(PRUNE_INTERVAL) {
+                    Err(_) => {
+                        if let Err(e) = shared.prune(prune_depth) {
+                            ckb_logger::error!("Prune error {}", e);
+                            break;
+                        }
+                    }
+                    Ok(_) => {
+                        ckb_logger::info!("Prune closing");
+                        break;
+                    }
+                }
+            })
+            .expect("Start PruneService failed");
+
+        register_thread("prune", prune_jh);
+    }
+
+    /// Deletes the bodies of at most `MAX_PRUNE_LIMIT` blocks which are deeper than `prune_depth`.
+    ///
+    /// It does nothing in the initial block download.
+    pub fn prune(&self, prune_depth: BlockNumber) -> Result<(), Error> {
+        if self.is_initial_block_download() {
+            ckb_logger::trace!("is_initial_block_download prune skip");
+            return Ok(());
+        }
+
+        let snapshot = self.snapshot();
+        let start = cmp::max(snapshot.get_pruned_block_number(), 1);
+        let end = cmp::min(
+            snapshot.tip_number().saturating_sub(prune_depth),
+            start + MAX_PRUNE_LIMIT,
+        );
+        if start >= end {
+            ckb_logger::trace!("Prune idles");
+            return Ok(());
+        }
+
+        let mut pruned = BTreeMap::new();
+        for number in start..end {
+            let pack_number: packed::Uint64 = number.pack();
+            let prefix = pack_number.as_slice();
+            for (key, value) in snapshot
+                .get_iter(
+                    COLUMN_NUMBER_HASH,
+                    IteratorMode::From(prefix, Direction::Forward),
+                )
+                .take_while(|(key, _)| key.starts_with(prefix))
+            {
+                let reader = packed::NumberHashReader::from_slice_should_be_ok(key.as_ref());
+                let txs = packed::Uint32Reader::from_slice_should_be_ok(value.as_ref()).unpack();
+                pruned.insert(reader.block_hash().to_entity(), (number, txs));
+            }
+        }
+
+        let mut batch = self.store.new_write_batch();
+        for (hash, (number, txs)) in &pruned {
+            batch.delete_block_body(*number, hash, *txs)?;
+        }
+        batch.insert_pruned_block_number(end)?;
+        self.store.write_sync(&batch)?;
+        ckb_logger::debug!("Pruned the bodies of blocks [{}, {})", start, end);
+
+        if let (Some(start), Some(end)) = (pruned.keys().next(), pruned.keys().next_back()) {
+            self.compact_block_body(start, end);
+        }
+        Ok(())
+    }
+
     fn wipe_out_frozen_data(
         &self,
         snapshot: &Snapshot,
@@ -2,8 +2,8 @@
 use crate::ChainServicesBuilder;
 use crate::{HeaderMap, Shared};
 use ckb_app_config::{
-    BlockAssemblerConfig, DBConfig, ExitCode, FeeEstimatorAlgo, FeeEstimatorConfig, NotifyConfig,
-    StoreConfig, SyncConfig, TxPoolConfig,
+    BlockAssemblerConfig, CellSnapshotArgs, DBConfig, ExitCode, FeeEstimatorAlgo,
+    FeeEstimatorConfig, NotifyConfig, StoreConfig, SyncConfig, TxPoolConfig,
 };
 use ckb_async_runtime::{new_background_runtime, Handle};
 use ckb_chain_spec::consensus::Consensus;
@@ -34,6 +34,8 @@ use ckb_verification::cache::init_cache;
 use dashmap::DashMap;
 use std::cmp::Ordering;
 use std::collections::HashSet;
+use std::fs::File;
+use std::io::BufReader;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
@@ -51,6 +53,7 @@ pub struct SharedBuilder {
     notify_config: Option<NotifyConfig>,
     async_handle: Handle,
     fee_estimator_config: Option<FeeEstimatorConfig>,
+    cell_snapshot: Option<CellSnapshotArgs>,
 
     header_map_tmp_dir: Option<PathBuf>,
 }
@@ -158,6 +161,7 @@ impl SharedBuilder {
             block_assembler_config: None,
             async_handle,
             fee_estimator_config: None,
+            cell_snapshot: None,
             header_map_tmp_dir: None,
         })
     }
@@ -204,6 +208,7 @@ impl SharedBuilder {
             block_assembler_config: None,
             async_handle: runtime.get_or_init(new_background_runtime).clone(),
             fee_estimator_config: None,
+            cell_snapshot: None,
 
             header_map_tmp_dir: None,
         })
@@ -259,6 +264,14 @@ impl SharedBuilder {
         self
     }
 
+    /// Bootstraps an empty database from the trusted cell snapshot instead of the genesis block.
+    ///
+    /// The snapshot is ignored when the database has been initialized.
+    pub fn cell_snapshot(mut self, snapshot: Option<CellSnapshotArgs>) -> Self {
+        self.cell_snapshot = snapshot;
+        self
+    }
+
     /// specifies the async_handle for the shared
     pub fn async_handle(mut self, async_handle: Handle) -> Self {
         self.async_handle = async_handle;
@@ -293,7 +306,11 @@ impl SharedBuilder {
         (proposal_ids, proposals)
     }
 
-    fn init_store(store: &ChainDB, consensus: &Consensus) -> Result<(HeaderView, EpochExt), Error> {
+    fn init_store(
+        store: &ChainDB,
+        consensus: &Consensus,
+        cell_snapshot: Option<&CellSnapshotArgs>,
+    ) -> Result<(HeaderView, EpochExt), Error> {
         match store
             .get_tip_header()
             .and_then(|header| store.get_current_epoch_ext().map(|epoch| (header, epoch)))
@@ -316,20 +333,42 @@ impl SharedBuilder {
                         .into())
                 }
             }
-            None => store.init(consensus).map(|_| {
-                (
-                    consensus.genesis_block().header(),
-                    consensus.genesis_epoch_ext().to_owned(),
-                )
-            }),
+            None => match cell_snapshot {
+                Some(snapshot) => Self::init_store_from_cell_snapshot(store, consensus, snapshot),
+                None => store.init(consensus).map(|_| {
+                    (
+                        consensus.genesis_block().header(),
+                        consensus.genesis_epoch_ext().to_owned(),
+                    )
+                }),
+            },
         }
     }
 
+    fn init_store_from_cell_snapshot(
+        store: &ChainDB,
+        consensus: &Consensus,
+        snapshot: &CellSnapshotArgs,
+    ) -> Result<(HeaderView, EpochExt), Error> {
+        info!("loading cell snapshot {}", snapshot.path.display());
+        let f = File::open(&snapshot.path).map_err(|err| InternalErrorKind::System.other(err))?;
+        let info =
+            store.init_from_cell_snapshot(consensus, BufReader::new(f), &snapshot.hash.pack())?;
+        info!(
+            "cell snapshot loaded, continue from block {} {}",
+            info.number, info.block_hash
+        );
+        let tip_header = store.get_tip_header().expect("snapshot loaded");
+        let epoch = store.get_current_epoch_ext().expect("snapshot loaded");
+        Ok((tip_header, epoch))
+    }
+
     fn init_snapshot(
         store: &ChainDB,
         consensus: Arc<Consensus>,
+        cell_snapshot: Option<&CellSnapshotArgs>,
     ) -> Result<(Snapshot, ProposalTable), Error> {
-        let (tip_header, epoch) = Self::init_store(store, &consensus)?;
+        let (tip_header, epoch) = Self::init_store(store, &consensus, cell_snapshot)?;
         let total_difficulty = store
             .get_block_ext(&tip_header.hash())
             .ok_or_else(|| InternalErrorKind::Database.other("failed to get tip's block_ext"))?
@@ -361,6 +400,7 @@ impl SharedBuilder {
             notify_config,
             async_handle,
             fee_estimator_config,
+            cell_snapshot,
             header_map_tmp_dir,
         } = self;
 
@@ -391,10 +431,12 @@ impl SharedBuilder {
         let txs_verify_cache = Arc::new(TokioRwLock::new(init_cache()));
 
         let (snapshot, table) =
-            Self::init_snapshot(&store, Arc::clone(&consensus)).map_err(|e| {
-                eprintln!("init_snapshot {e}");
-                ExitCode::Failure
-            })?;
+            Self::init_snapshot(&store, Arc::clone(&consensus), cell_snapshot.as_ref()).map_err(
+                |e| {
+                    eprintln!("init_snapshot {e}");
+                    ExitCode::Failure
+                },
+            )?;
         let snapshot = Arc::new(snapshot);
         let snapshot_mgr = Arc::new(SnapshotMgr::new(Arc::clone(&snapshot)));
 
@@ -527,7 +569,13 @@ fn build_store(
     store_config: StoreConfig,
     ancient_path: Option<PathBuf>,
 ) -> Result<ChainDB, Error> {
-    let store = if store_config.freezer_enable && ancient_path.is_some() {
+    if store_config.freezer_enable && store_config.prune_depth.is_some() {
+        info!("freezer is disabled in the pruned mode");
+    }
+    let store = if store_config.freezer_enable
+        && store_config.prune_depth.is_none()
+        && ancient_path.is_some()
+    {
         let freezer = Freezer::open(ancient_path.expect("exist checked"))?;
         ChainDB::new_with_freezer(db, freezer, store_config)
     } else {
@@ -0,0 +1,180 @@
+//! The online backup of the chain database and the freezer.
+//!
+//! ```text
+//! <backup>/db             RocksDB checkpoint
+//! <backup>/ancient        freezer copy, absent when the freezer is disabled
+//! <backup>/manifest.json  BackupManifest
+//! ```
+//!
+//! The manifest is written last, a directory without it is an incomplete backup.
+use crate::{ChainDB, ChainStore};
+use ckb_db::ReadOnlyDB;
+use ckb_db_schema::{COLUMN_BLOCK_HEADER, COLUMN_META, META_TIP_HEADER_KEY, MIGRATION_VERSION_KEY};
+use ckb_error::{Error, InternalErrorKind};
+use ckb_freezer::Freezer;
+use ckb_types::{
+    core::{BlockNumber, HeaderView},
+    packed,
+    prelude::*,
+    H256,
+};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The file name of the backup manifest.
+pub const BACKUP_MANIFEST_FILE: &str = "manifest.json";
+/// The directory name of the database checkpoint in a backup.
+pub const BACKUP_DB_DIR: &str = "db";
+/// The directory name of the freezer copy in a backup.
+pub const BACKUP_ANCIENT_DIR: &str = "ancient";
+
+fn io_error(err: io::Error) -> Error {
+    InternalErrorKind::System.other(err).into()
+}
+
+fn invalid<S: ToString>(reason: S) -> Error {
+    InternalErrorKind::Database
+        .other(format!("backup: {}", reason.to_string()))
+        .into()
+}
+
+/// Describes the chain state saved in a backup.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupManifest {
+    /// The genesis hash of the chain.
+    pub genesis_hash: H256,
+    /// The tip block number in the database checkpoint.
+    pub tip_number: BlockNumber,
+    /// The tip block hash in the database checkpoint.
+    pub tip_hash: H256,
+    /// The database version recorded by the migrations.
+    pub migration_version: String,
+    /// The blocks before this number are stored in the freezer copy, `None` if the freezer is
+    /// disabled.
+    pub frozen_number: Option<BlockNumber>,
+}
+
+impl BackupManifest {
+    /// Loads the manifest from the backup directory.
+    pub fn load(dir: &Path) -> Result<Self, Error> {
+        let raw = fs::read(dir.join(BACKUP_MANIFEST_FILE)).map_err(io_error)?;
+        serde_json::from_slice(&raw).map_err(invalid)
+    }
+
+    fn save(&self, dir: &Path) -> Result<(), Error> {
+        let raw = serde_json::to_vec_pretty(self).map_err(invalid)?;
+        let tmp = dir.join(format!("{BACKUP_MANIFEST_FILE}.tmp"));
+        fs::write(&tmp, raw).map_err(io_error)?;
+        fs::rename(tmp, dir.join(BACKUP_MANIFEST_FILE)).map_err(io_error)
+    }
+
+    /// Checks the database checkpoint and the freezer copy in the backup directory against the
+    /// manifest.
+    pub fn verify(&self, dir: &Path) -> Result<(), Error> {
+        let db = open_checkpoint(&dir.join(BACKUP_DB_DIR))?;
+        let tip = read_tip_header(&db)?;
+        let tip_hash: H256 = tip.hash().unpack();
+        if tip.number() != self.tip_number || tip_hash != self.tip_hash {
+            return Err(invalid(format!(
+                "the database tip {} {:#x} does not match the manifest",
+                tip.number(),
+                tip_hash
+            )));
+        }
+        let migration_version = read_migration_version(&db)?;
+        if migration_version != self.migration_version {
+            return Err(invalid(format!(
+                "the database version {migration_version} does not match the manifest"
+            )));
+        }
+
+        let ancient = dir.join(BACKUP_ANCIENT_DIR);
+        match self.frozen_number {
+            Some(frozen_number) => {
+                let freezer = Freezer::open(ancient)?;
+                if freezer.number() != frozen_number {
+                    return Err(invalid(format!(
+                        "the freezer has {} items, but the manifest expects {}",
+                        freezer.number(),
+                        frozen_number
+                    )));
+                }
+            }
+            None => {
+                if ancient.exists() {
+                    return Err(invalid("unexpected freezer copy"));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn open_checkpoint(path: &Path) -> Result<ReadOnlyDB, Error> {
+    ReadOnlyDB::open_cf(path, vec![COLUMN_META, COLUMN_BLOCK_HEADER])?
+        .ok_or_else(|| invalid(format!("database {} does not exist", path.display())))
+}
+
+fn read_tip_header(db: &ReadOnlyDB) -> Result<HeaderView, Error> {
+    let tip_hash = db
+        .get_pinned(COLUMN_META, META_TIP_HEADER_KEY)?
+        .ok_or_else(|| invalid("tip header does not exist"))?;
+    let raw = db
+        .get_pinned(COLUMN_BLOCK_HEADER, tip_hash.as_ref())?
+        .ok_or_else(|| invalid("tip header does not exist"))?;
+    let reader = packed::HeaderViewReader::from_slice_should_be_ok(raw.as_ref());
+    Ok(Unpack::<HeaderView>::unpack(&reader))
+}
+
+fn read_migration_version(db: &ReadOnlyDB) -> Result<String, Error> {
+    let raw = db
+        .get_pinned_default(MIGRATION_VERSION_KEY)?
+        .ok_or_else(|| invalid("database version does not exist"))?;
+    String::from_utf8(raw.to_vec()).map_err(invalid)
+}
+
+impl ChainDB {
+    /// Backs up the database and the freezer into the directory `target` while the chain keeps
+    /// growing, `target` must not exist.
+    pub fn create_backup(&self, target: &Path) -> Result<BackupManifest, Error> {
+        if target.exists() {
+            return Err(invalid(format!("{} already exists", target.display())));
+        }
+        fs::create_dir_all(target).map_err(io_error)?;
+
+        let db_path = target.join(BACKUP_DB_DIR);
+        self.db().create_checkpoint(&db_path)?;
+        // Blocks are wiped from the database after being frozen, so reading the number after
+        // the checkpoint ensures the freezer copy contains all the blocks missing from it.
+        let frozen_number = match self.freezer() {
+            Some(freezer) => {
+                let number = freezer.number();
+                freezer.backup(target.join(BACKUP_ANCIENT_DIR), number)?;
+                Some(number)
+            }
+            None => None,
+        };
+
+        let (tip, migration_version) = {
+            let checkpoint = open_checkpoint(&db_path)?;
+            (
+                read_tip_header(&checkpoint)?,
+                read_migration_version(&checkpoint)?,
+            )
+        };
+        let genesis_hash = self
+            .get_block_hash(0)
+            .ok_or_else(|| invalid("genesis block does not exist"))?;
+        let manifest = BackupManifest {
+            genesis_hash: genesis_hash.unpack(),
+            tip_number: tip.number(),
+            tip_hash: tip.hash().unpack(),
+            migration_version,
+            frozen_number,
+        };
+        manifest.save(target)?;
+        Ok(manifest)
+    }
+}
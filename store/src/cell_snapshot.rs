@@ -0,0 +1,468 @@
+//! The trusted snapshot of the live cell set.
+//!
+//! A snapshot carries everything a node needs to continue from a block without replaying
+//! history: the live cells, the main chain headers, the epochs, the chain root MMR and the
+//! most recent blocks.
+//!
+//! ```text
+//! header: magic (8) | version (u32) | block number (u64) | block hash (32)
+//! entry:  kind (u8) | ...
+//!   kind 1, column entry: column (u8) | key length (u32) | key | value length (u32) | value
+//!   kind 2, recent block: block length (u32) | block | block ext length (u32) | block ext
+//!   kind 0, end:          blake2b hash of the header and all the preceding entries (32)
+//! ```
+//!
+//! All integers are little-endian. The tip header and the current epoch are not stored as
+//! entries, they are derived from the snapshot block when loading.
+use crate::{ChainDB, ChainStore, StoreWriteBatch};
+use ckb_chain_spec::consensus::Consensus;
+use ckb_db::iter::IteratorMode;
+use ckb_db_schema::{
+    Col, COLUMN_BLOCK_EPOCH, COLUMN_BLOCK_HEADER, COLUMN_CELL, COLUMN_CELL_DATA,
+    COLUMN_CELL_DATA_HASH, COLUMN_CHAIN_ROOT_MMR, COLUMN_EPOCH, COLUMN_INDEX,
+};
+use ckb_error::{Error, InternalErrorKind};
+use ckb_hash::{new_blake2b, Blake2b};
+use ckb_types::{
+    core::{BlockExt, BlockNumber, BlockView, HeaderView},
+    packed,
+    prelude::*,
+};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+/// The magic bytes identifying a cell snapshot.
+pub const CELL_SNAPSHOT_MAGIC: &[u8; 8] = b"CKBCELLS";
+/// The current version of the cell snapshot.
+pub const CELL_SNAPSHOT_VERSION: u32 = 1;
+
+const KIND_END: u8 = 0;
+const KIND_COLUMN: u8 = 1;
+const KIND_BLOCK: u8 = 2;
+
+// Columns copied as a whole
+const FULL_COLUMNS: [Col; 5] = [
+    COLUMN_CELL,
+    COLUMN_CELL_DATA,
+    COLUMN_CELL_DATA_HASH,
+    COLUMN_CHAIN_ROOT_MMR,
+    COLUMN_EPOCH,
+];
+
+// Flush the write batch every 64MB when loading
+const BATCH_SIZE_LIMIT: usize = 64 * 1024 * 1024;
+
+fn io_error(err: io::Error) -> Error {
+    InternalErrorKind::System.other(err).into()
+}
+
+fn corrupted<S: ToString>(reason: S) -> Error {
+    InternalErrorKind::DataCorrupted
+        .other(format!("cell snapshot: {}", reason.to_string()))
+        .into()
+}
+
+fn column_id(col: Col) -> u8 {
+    col.parse().expect("column is a number")
+}
+
+fn column_by_id(id: u8) -> Option<Col> {
+    [
+        COLUMN_INDEX,
+        COLUMN_BLOCK_HEADER,
+        COLUMN_BLOCK_EPOCH,
+        COLUMN_CELL,
+        COLUMN_CELL_DATA,
+        COLUMN_CELL_DATA_HASH,
+        COLUMN_CHAIN_ROOT_MMR,
+        COLUMN_EPOCH,
+    ]
+    .into_iter()
+    .find(|col| column_id(col) == id)
+}
+
+/// The summary of a cell snapshot.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CellSnapshotInfo {
+    /// The block the snapshot is taken at.
+    pub number: BlockNumber,
+    /// The hash of the block the snapshot is taken at.
+    pub block_hash: packed::Byte32,
+    /// The blake2b hash of the snapshot entries, which is used to verify the snapshot.
+    pub hash: packed::Byte32,
+}
+
+struct HashedWriter<W: Write> {
+    inner: W,
+    hasher: Blake2b,
+}
+
+impl<W: Write> HashedWriter<W> {
+    fn write(&mut self, data: &[u8]) -> Result<(), Error> {
+        self.hasher.update(data);
+        self.inner.write_all(data).map_err(io_error)
+    }
+
+    fn write_bytes(&mut self, data: &[u8]) -> Result<(), Error> {
+        self.write(&(data.len() as u32).to_le_bytes())?;
+        self.write(data)
+    }
+
+    fn write_column(&mut self, col: Col, key: &[u8], value: &[u8]) -> Result<(), Error> {
+        self.write(&[KIND_COLUMN, column_id(col)])?;
+        self.write_bytes(key)?;
+        self.write_bytes(value)
+    }
+}
+
+struct HashedReader<R: Read> {
+    inner: R,
+    hasher: Blake2b,
+}
+
+impl<R: Read> HashedReader<R> {
+    // The end marker and the trailing hash are not hashed
+    fn read_kind(&mut self) -> Result<u8, Error> {
+        let mut kind = [0u8; 1];
+        self.inner.read_exact(&mut kind).map_err(io_error)?;
+        if kind[0] != KIND_END {
+            self.hasher.update(&kind);
+        }
+        Ok(kind[0])
+    }
+
+    fn read<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        let mut buf = [0u8; N];
+        self.inner.read_exact(&mut buf).map_err(io_error)?;
+        self.hasher.update(&buf);
+        Ok(buf)
+    }
+
+    fn read_bytes(&mut self) -> Result<Vec<u8>, Error> {
+        let len = u32::from_le_bytes(self.read()?) as usize;
+        let mut buf = vec![0u8; len];
+        self.inner.read_exact(&mut buf).map_err(io_error)?;
+        self.hasher.update(&buf);
+        Ok(buf)
+    }
+}
+
+/// Writes a snapshot of the store at its current tip.
+///
+/// Besides the live cells and the main chain headers, the last `recent_blocks` blocks are
+/// included with their block ext, so the node is able to verify the following blocks.
+pub fn export_cell_snapshot<S: ChainStore, W: Write>(
+    store: &S,
+    writer: W,
+    recent_blocks: BlockNumber,
+) -> Result<CellSnapshotInfo, Error> {
+    let tip = store
+        .get_tip_header()
+        .ok_or_else(|| InternalErrorKind::Database.other("tip header does not exist"))?;
+    let mut writer = HashedWriter {
+        inner: writer,
+        hasher: new_blake2b(),
+    };
+    for data in [
+        &CELL_SNAPSHOT_MAGIC[..],
+        &CELL_SNAPSHOT_VERSION.to_le_bytes()[..],
+        &tip.number().to_le_bytes()[..],
+        tip.hash().as_slice(),
+    ] {
+        writer.write(data)?;
+    }
+
+    for number in 0..=tip.number() {
+        let hash = store
+            .get_block_hash(number)
+            .ok_or_else(|| InternalErrorKind::Database.other(format!("block {number} missing")))?;
+        let packed_number: packed::Uint64 = number.pack();
+        let header = store
+            .get(COLUMN_BLOCK_HEADER, hash.as_slice())
+            .ok_or_else(|| InternalErrorKind::Database.other(format!("header {hash} missing")))?;
+        writer.write_column(COLUMN_INDEX, packed_number.as_slice(), hash.as_slice())?;
+        writer.write_column(COLUMN_INDEX, hash.as_slice(), packed_number.as_slice())?;
+        writer.write_column(COLUMN_BLOCK_HEADER, hash.as_slice(), &header)?;
+        if let Some(epoch_hash) = store.get(COLUMN_BLOCK_EPOCH, hash.as_slice()) {
+            writer.write_column(COLUMN_BLOCK_EPOCH, hash.as_slice(), &epoch_hash)?;
+        }
+    }
+
+    for col in FULL_COLUMNS {
+        for (key, value) in store.get_iter(col, IteratorMode::Start) {
+            writer.write_column(col, &key, &value)?;
+        }
+    }
+
+    // the tip block is always included, even when it is the genesis block
+    let start = tip
+        .number()
+        .saturating_sub(recent_blocks)
+        .max(1)
+        .min(tip.number());
+    for number in start..=tip.number() {
+        let hash = store.get_block_hash(number).expect("checked above");
+        let block = store
+            .get_block(&hash)
+            .ok_or_else(|| InternalErrorKind::Database.other(format!("block {hash} missing")))?;
+        let ext = store.get_block_ext(&hash).ok_or_else(|| {
+            InternalErrorKind::Database.other(format!("block ext {hash} missing"))
+        })?;
+        let ext: packed::BlockExtV1 = ext.pack();
+        writer.write(&[KIND_BLOCK])?;
+        writer.write_bytes(block.data().as_slice())?;
+        writer.write_bytes(ext.as_slice())?;
+    }
+
+    let HashedWriter { mut inner, hasher } = writer;
+    let mut hash = [0u8; 32];
+    hasher.finalize(&mut hash);
+    inner.write_all(&[KIND_END]).map_err(io_error)?;
+    inner.write_all(&hash).map_err(io_error)?;
+    inner.flush().map_err(io_error)?;
+
+    Ok(CellSnapshotInfo {
+        number: tip.number(),
+        block_hash: tip.hash(),
+        hash: hash.pack(),
+    })
+}
+
+enum SnapshotEntry {
+    Column(Col, Vec<u8>, Vec<u8>),
+    Block(BlockView, BlockExt),
+}
+
+/// Reads a whole snapshot, passing each entry to `on_entry`.
+///
+/// The snapshot is only complete when its trailing hash matches the entries, which is checked
+/// after all the entries have been read.
+fn read_cell_snapshot<R, F>(reader: R, mut on_entry: F) -> Result<CellSnapshotInfo, Error>
+where
+    R: Read,
+    F: FnMut(SnapshotEntry) -> Result<(), Error>,
+{
+    let mut reader = HashedReader {
+        inner: reader,
+        hasher: new_blake2b(),
+    };
+    let magic: [u8; 8] = reader.read()?;
+    if &magic != CELL_SNAPSHOT_MAGIC {
+        return Err(corrupted("not a cell snapshot"));
+    }
+    let version = u32::from_le_bytes(reader.read()?);
+    if version != CELL_SNAPSHOT_VERSION {
+        return Err(corrupted(format!("unsupported version {version}")));
+    }
+    let number = BlockNumber::from_le_bytes(reader.read()?);
+    let block_hash: [u8; 32] = reader.read()?;
+    let block_hash = block_hash.pack();
+
+    loop {
+        match reader.read_kind()? {
+            KIND_COLUMN => {
+                let [id] = reader.read()?;
+                let col =
+                    column_by_id(id).ok_or_else(|| corrupted(format!("unknown column {id}")))?;
+                let key = reader.read_bytes()?;
+                let value = reader.read_bytes()?;
+                on_entry(SnapshotEntry::Column(col, key, value))?;
+            }
+            KIND_BLOCK => {
+                let block = packed::BlockReader::from_compatible_slice(&reader.read_bytes()?)
+                    .map_err(corrupted)?
+                    .to_entity()
+                    .into_view();
+                let ext: BlockExt = packed::BlockExtV1Reader::from_slice(&reader.read_bytes()?)
+                    .map_err(corrupted)?
+                    .unpack();
+                on_entry(SnapshotEntry::Block(block, ext))?;
+            }
+            KIND_END => break,
+            other => return Err(corrupted(format!("unknown entry kind {other}"))),
+        }
+    }
+    let HashedReader { mut inner, hasher } = reader;
+    let mut hash = [0u8; 32];
+    hasher.finalize(&mut hash);
+    let mut trailer = [0u8; 32];
+    inner.read_exact(&mut trailer).map_err(io_error)?;
+    if trailer != hash {
+        return Err(corrupted("hash does not match the snapshot content"));
+    }
+    Ok(CellSnapshotInfo {
+        number,
+        block_hash,
+        hash: hash.pack(),
+    })
+}
+
+/// The chain of a snapshot, collected in the first pass to check it before any write.
+#[derive(Default)]
+struct SnapshotChain {
+    genesis_hash: Option<packed::Byte32>,
+    // the indexed main chain block with the highest number
+    last_indexed: Option<(BlockNumber, packed::Byte32)>,
+    last_block: Option<packed::Byte32>,
+}
+
+impl SnapshotChain {
+    fn collect(&mut self, entry: &SnapshotEntry) {
+        match entry {
+            // the index from the block number to the block hash
+            SnapshotEntry::Column(col, key, value) if *col == COLUMN_INDEX && key.len() == 8 => {
+                let number =
+                    BlockNumber::from_le_bytes(key[..].try_into().expect("checked length"));
+                if let Ok(hash) = packed::Byte32::from_slice(value) {
+                    if number == 0 {
+                        self.genesis_hash = Some(hash.clone());
+                    }
+                    if self
+                        .last_indexed
+                        .as_ref()
+                        .map_or(true, |(last, _)| number > *last)
+                    {
+                        self.last_indexed = Some((number, hash));
+                    }
+                }
+            }
+            SnapshotEntry::Column(..) => {}
+            SnapshotEntry::Block(block, _) => self.last_block = Some(block.hash()),
+        }
+    }
+
+    fn check(&self, consensus: &Consensus, info: &CellSnapshotInfo) -> Result<(), Error> {
+        if self.genesis_hash != Some(consensus.genesis_hash()) {
+            return Err(corrupted("genesis hash does not match the chain spec"));
+        }
+        if self.last_indexed != Some((info.number, info.block_hash.clone())) {
+            return Err(corrupted(format!(
+                "block {} is not the last block {}",
+                info.number, info.block_hash
+            )));
+        }
+        if self.last_block.as_ref() != Some(&info.block_hash) {
+            return Err(corrupted("tip block missing"));
+        }
+        Ok(())
+    }
+}
+
+impl ChainDB {
+    /// Initializes an empty store from a trusted cell snapshot.
+    ///
+    /// The snapshot is read twice. The first pass verifies it against `expected_hash`, the
+    /// chain spec genesis and its tip, and nothing is written to the store until it passes, so
+    /// a truncated, forged or foreign snapshot never leaves cells behind. The tip is only set
+    /// after the second pass has imported the whole snapshot.
+    pub fn init_from_cell_snapshot<R: Read + Seek>(
+        &self,
+        consensus: &Consensus,
+        mut reader: R,
+        expected_hash: &packed::Byte32,
+    ) -> Result<CellSnapshotInfo, Error> {
+        if self.get_tip_header().is_some() {
+            return Err(InternalErrorKind::Database
+                .other("cell snapshot can only be loaded into an empty database")
+                .into());
+        }
+
+        let start = reader.stream_position().map_err(io_error)?;
+        let mut chain = SnapshotChain::default();
+        let verified = read_cell_snapshot(&mut reader, |entry| {
+            chain.collect(&entry);
+            Ok(())
+        })?;
+        if &verified.hash != expected_hash {
+            return Err(corrupted(format!(
+                "hash {} does not match the trusted hash {expected_hash}",
+                verified.hash
+            )));
+        }
+        chain.check(consensus, &verified)?;
+
+        reader.seek(SeekFrom::Start(start)).map_err(io_error)?;
+        let mut batch = self.new_write_batch();
+        let mut recent_blocks = Vec::new();
+        let info = read_cell_snapshot(&mut reader, |entry| {
+            match entry {
+                SnapshotEntry::Column(col, key, value) => {
+                    batch.put(col, &key, &value)?;
+                    self.flush_batch_if_full(&mut batch)?;
+                }
+                SnapshotEntry::Block(block, ext) => recent_blocks.push((block, ext)),
+            }
+            Ok(())
+        })?;
+        // the snapshot must not change between the two passes
+        if info != verified {
+            return Err(corrupted("snapshot changed while loading"));
+        }
+        self.write(&batch)?;
+
+        self.finish_cell_snapshot(consensus, info.number, &info.block_hash, recent_blocks)?;
+        Ok(info)
+    }
+
+    fn flush_batch_if_full(&self, batch: &mut StoreWriteBatch) -> Result<(), Error> {
+        if batch.size_in_bytes() > BATCH_SIZE_LIMIT {
+            self.write(batch)?;
+            batch.clear()?;
+        }
+        Ok(())
+    }
+
+    fn finish_cell_snapshot(
+        &self,
+        consensus: &Consensus,
+        number: BlockNumber,
+        block_hash: &packed::Byte32,
+        recent_blocks: Vec<(BlockView, BlockExt)>,
+    ) -> Result<(), Error> {
+        let genesis = consensus.genesis_block();
+        if self.get_block_hash(0).as_ref() != Some(&genesis.hash()) {
+            return Err(corrupted("genesis hash does not match the chain spec"));
+        }
+        if self.get_block_hash(number).as_ref() != Some(block_hash) {
+            return Err(corrupted(format!("block {number} is not {block_hash}")));
+        }
+        let tip_header: HeaderView = self
+            .get_block_header(block_hash)
+            .ok_or_else(|| corrupted("tip header missing"))?;
+        let epoch = self
+            .get_block_epoch_index(block_hash)
+            .and_then(|index| self.get_epoch_ext(&index))
+            .ok_or_else(|| corrupted("tip epoch missing"))?;
+
+        if recent_blocks.last().map(|(block, _)| block.hash()).as_ref() != Some(block_hash) {
+            return Err(corrupted("tip block missing"));
+        }
+
+        let db_txn = self.begin_transaction();
+        let genesis_ext = BlockExt {
+            received_at: genesis.timestamp(),
+            total_difficulty: genesis.difficulty(),
+            total_uncles_count: 0,
+            verified: Some(true),
+            txs_fees: vec![],
+            cycles: Some(vec![]),
+            txs_sizes: Some(vec![]),
+        };
+        db_txn.insert_block(genesis)?;
+        db_txn.insert_block_ext(&genesis.hash(), &genesis_ext)?;
+        db_txn.attach_block(genesis)?;
+        for (block, ext) in recent_blocks {
+            if self.get_block_hash(block.number()).as_ref() != Some(&block.hash()) {
+                return Err(corrupted(format!(
+                    "block {} is not on the main chain",
+                    block.number()
+                )));
+            }
+            db_txn.insert_block(&block)?;
+            db_txn.insert_block_ext(&block.hash(), &ext)?;
+            db_txn.attach_block(&block)?;
+        }
+        db_txn.insert_current_epoch_ext(&epoch)?;
+        db_txn.insert_tip_header(&tip_header)?;
+        db_txn.commit()
+    }
+}
@@ -3,8 +3,10 @@
 //! This Library contains the `ChainStore` traits
 //! which provides chain data store interface
 
+mod backup;
 mod cache;
 mod cell;
+mod cell_snapshot;
 pub mod data_loader_wrapper;
 mod db;
 mod snapshot;
@@ -15,8 +17,12 @@ mod write_batch;
 #[cfg(test)]
 mod tests;
 
+pub use backup::{BackupManifest, BACKUP_ANCIENT_DIR, BACKUP_DB_DIR, BACKUP_MANIFEST_FILE};
 pub use cache::StoreCache;
 pub use cell::{attach_block_cell, detach_block_cell};
+pub use cell_snapshot::{
+    export_cell_snapshot, CellSnapshotInfo, CELL_SNAPSHOT_MAGIC, CELL_SNAPSHOT_VERSION,
+};
 pub use db::ChainDB;
 pub use snapshot::StoreSnapshot;
 pub use store::ChainStore;
@@ -9,7 +9,8 @@ use ckb_db_schema::{
     COLUMN_BLOCK_FILTER, COLUMN_BLOCK_FILTER_HASH, COLUMN_BLOCK_HEADER, COLUMN_BLOCK_PROPOSAL_IDS,
     COLUMN_BLOCK_UNCLE, COLUMN_CELL, COLUMN_CELL_DATA, COLUMN_CELL_DATA_HASH,
     COLUMN_CHAIN_ROOT_MMR, COLUMN_EPOCH, COLUMN_INDEX, COLUMN_META, COLUMN_TRANSACTION_INFO,
-    COLUMN_UNCLES, META_CURRENT_EPOCH_KEY, META_LATEST_BUILT_FILTER_DATA_KEY, META_TIP_HEADER_KEY,
+    COLUMN_UNCLES, META_CURRENT_EPOCH_KEY, META_LATEST_BUILT_FILTER_DATA_KEY,
+    META_PRUNED_BLOCK_NUMBER_KEY, META_TIP_HEADER_KEY,
 };
 use ckb_freezer::Freezer;
 use ckb_types::{
@@ -49,13 +50,10 @@ pub trait ChainStore: Send + Sync + Sized {
                 return Some(raw_block.into_view());
             }
         }
+        // the bodies of blocks before a cell snapshot are absent until back-filled
+        let uncles = self.get_block_uncles(h)?;
+        let proposals = self.get_block_proposal_txs_ids(h)?;
         let body = self.get_block_body(h);
-        let uncles = self
-            .get_block_uncles(h)
-            .expect("block uncles must be stored");
-        let proposals = self
-            .get_block_proposal_txs_ids(h)
-            .expect("block proposal_ids must be stored");
         let extension_opt = self.get_block_extension(h);
 
         let block = if let Some(extension) = extension_opt {
@@ -111,24 +109,19 @@ pub trait ChainStore: Send + Sync + Sized {
                 Unpack::<HeaderView>::unpack(&reader)
             })?;
 
-        let body = self.get_block_body(hash);
-
-        let uncles = self
-            .get(COLUMN_BLOCK_UNCLE, hash.as_slice())
-            .map(|slice| {
-                let reader =
-                    packed::UncleBlockVecViewReader::from_slice_should_be_ok(slice.as_ref());
-                Unpack::<UncleBlockVecView>::unpack(&reader)
-            })
-            .expect("block uncles must be stored");
+        let uncles = self.get(COLUMN_BLOCK_UNCLE, hash.as_slice()).map(|slice| {
+            let reader = packed::UncleBlockVecViewReader::from_slice_should_be_ok(slice.as_ref());
+            Unpack::<UncleBlockVecView>::unpack(&reader)
+        })?;
 
         let proposals = self
             .get(COLUMN_BLOCK_PROPOSAL_IDS, hash.as_slice())
             .map(|slice| {
                 packed::ProposalShortIdVecReader::from_slice_should_be_ok(slice.as_ref())
                     .to_entity()
-            })
-            .expect("block proposal_ids must be stored");
+            })?;
+
+        let body = self.get_block_body(hash);
 
         let extension_opt = self
             .get(COLUMN_BLOCK_EXTENSION, hash.as_slice())
@@ -476,6 +469,20 @@ pub trait ChainStore: Send + Sync + Sized {
             .map(|raw| packed::Byte32Reader::from_slice_should_be_ok(raw.as_ref()).to_entity())
     }
 
+    /// Gets the number of the first block whose body is not pruned
+    ///
+    /// Returns 0 when nothing has been pruned.
+    fn get_pruned_block_number(&self) -> BlockNumber {
+        self.get(COLUMN_META, META_PRUNED_BLOCK_NUMBER_KEY)
+            .map(|raw| packed::Uint64Reader::from_slice_should_be_ok(raw.as_ref()).unpack())
+            .unwrap_or(0)
+    }
+
+    /// Returns true if the body of the block has been pruned, the genesis block is never pruned
+    fn is_block_pruned(&self, number: BlockNumber) -> bool {
+        number > 0 && number < self.get_pruned_block_number()
+    }
+
     /// Gets block filter data by block hash
     fn get_block_filter(&self, hash: &packed::Byte32) -> Option<packed::Bytes> {
         self.get(COLUMN_BLOCK_FILTER, hash.as_slice())
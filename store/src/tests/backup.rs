@@ -0,0 +1,53 @@
+use ckb_chain_spec::consensus::ConsensusBuilder;
+use ckb_db::RocksDB;
+use ckb_db_schema::{COLUMNS, MIGRATION_VERSION_KEY};
+use ckb_freezer::Freezer;
+use ckb_types::prelude::*;
+use tempfile::TempDir;
+
+use super::cell_snapshot::insert_next_block;
+use crate::{db::ChainDB, store::ChainStore, BackupManifest, BACKUP_DB_DIR};
+
+#[test]
+fn create_and_verify_backup() {
+    let consensus = ConsensusBuilder::default().build();
+    let db_dir = TempDir::new().unwrap();
+    let ancient_dir = TempDir::new().unwrap();
+    let db = RocksDB::open_in(&db_dir, COLUMNS);
+    db.put_default(MIGRATION_VERSION_KEY, "20250101000000")
+        .unwrap();
+    let store = ChainDB::new_with_freezer(
+        db,
+        Freezer::open_in(&ancient_dir).unwrap(),
+        Default::default(),
+    );
+    store.init(&consensus).unwrap();
+    let tip = insert_next_block(&store, &consensus);
+
+    let backup_dir = TempDir::new().unwrap();
+    let target = backup_dir.path().join("backup");
+    let manifest = store.create_backup(&target).unwrap();
+    assert_eq!(manifest.genesis_hash, consensus.genesis_hash().unpack());
+    assert_eq!(manifest.tip_number, tip.number());
+    assert_eq!(manifest.tip_hash, tip.hash().unpack());
+    assert_eq!(manifest.migration_version, "20250101000000");
+    assert_eq!(manifest.frozen_number, Some(1));
+    // the target must not exist
+    assert!(store.create_backup(&target).is_err());
+
+    // the backup is not affected by the following blocks
+    insert_next_block(&store, &consensus);
+    let loaded = BackupManifest::load(&target).unwrap();
+    assert_eq!(loaded, manifest);
+    loaded.verify(&target).unwrap();
+
+    let restored = ChainDB::new(
+        RocksDB::open_in(target.join(BACKUP_DB_DIR), COLUMNS),
+        Default::default(),
+    );
+    assert_eq!(restored.get_tip_header(), Some(tip.header()));
+
+    let mut tampered = manifest;
+    tampered.tip_number += 1;
+    assert!(tampered.verify(&target).is_err());
+}
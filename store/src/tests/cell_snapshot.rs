@@ -0,0 +1,226 @@
+use ckb_chain_spec::consensus::{Consensus, ConsensusBuilder};
+use ckb_db::iter::IteratorMode;
+use ckb_db::RocksDB;
+use ckb_db_schema::{COLUMNS, COLUMN_CELL};
+use ckb_types::{
+    core::{BlockBuilder, BlockExt, BlockView},
+    packed,
+    prelude::*,
+};
+use std::io::Cursor;
+use tempfile::TempDir;
+
+use crate::{cell::attach_block_cell, db::ChainDB, export_cell_snapshot, store::ChainStore};
+
+fn new_store(tmp_dir: &TempDir) -> ChainDB {
+    ChainDB::new(RocksDB::open_in(tmp_dir, COLUMNS), Default::default())
+}
+
+pub(super) fn insert_next_block(store: &ChainDB, consensus: &Consensus) -> BlockView {
+    let parent = store.get_tip_header().unwrap();
+    let cellbase = packed::Transaction::new_builder()
+        .raw(
+            packed::RawTransaction::new_builder()
+                .outputs(vec![packed::CellOutput::default()].pack())
+                .outputs_data(vec![packed::Bytes::default()].pack())
+                .build(),
+        )
+        .build()
+        .into_view();
+    let block = BlockBuilder::default()
+        .parent_hash(parent.hash())
+        .number((parent.number() + 1).pack())
+        .epoch(parent.epoch().pack())
+        .transaction(cellbase)
+        .build();
+    let ext = BlockExt {
+        received_at: block.timestamp(),
+        total_difficulty: block.difficulty(),
+        total_uncles_count: 0,
+        verified: Some(true),
+        txs_fees: vec![],
+        cycles: None,
+        txs_sizes: None,
+    };
+    let epoch_index = consensus
+        .genesis_epoch_ext()
+        .last_block_hash_in_previous_epoch();
+
+    let txn = store.begin_transaction();
+    attach_block_cell(&txn, &block).unwrap();
+    txn.insert_block(&block).unwrap();
+    txn.insert_block_ext(&block.hash(), &ext).unwrap();
+    txn.insert_block_epoch_index(&block.hash(), &epoch_index)
+        .unwrap();
+    txn.attach_block(&block).unwrap();
+    txn.insert_tip_header(&block.header()).unwrap();
+    txn.commit().unwrap();
+    block
+}
+
+#[test]
+fn export_and_load_cell_snapshot() {
+    let consensus = ConsensusBuilder::default().build();
+    let source_dir = TempDir::new().unwrap();
+    let source = new_store(&source_dir);
+    source.init(&consensus).unwrap();
+    let blocks: Vec<_> = (0..3)
+        .map(|_| insert_next_block(&source, &consensus))
+        .collect();
+    let tip = blocks.last().unwrap();
+
+    let mut data = Vec::new();
+    let info = export_cell_snapshot(&source, &mut data, 1).unwrap();
+    assert_eq!(info.number, 3);
+    assert_eq!(info.block_hash, tip.hash());
+
+    let target_dir = TempDir::new().unwrap();
+    let target = new_store(&target_dir);
+    let loaded = target
+        .init_from_cell_snapshot(&consensus, Cursor::new(&data), &info.hash)
+        .unwrap();
+    assert_eq!(loaded, info);
+    assert_eq!(target.get_tip_header(), Some(tip.header()));
+    assert_eq!(
+        target.get_current_epoch_ext(),
+        source.get_current_epoch_ext()
+    );
+    for block in &blocks {
+        assert_eq!(target.get_block_hash(block.number()), Some(block.hash()));
+        let out_point = packed::OutPoint::new(block.transactions()[0].hash(), 0);
+        assert!(target.have_cell(&out_point));
+    }
+    // only the recent blocks carry their bodies
+    assert!(target.get_block(&blocks[0].hash()).is_none());
+    assert_eq!(target.get_block(&tip.hash()), Some(tip.clone()));
+}
+
+#[test]
+fn reject_untrusted_cell_snapshot() {
+    let consensus = ConsensusBuilder::default().build();
+    let source_dir = TempDir::new().unwrap();
+    let source = new_store(&source_dir);
+    source.init(&consensus).unwrap();
+    insert_next_block(&source, &consensus);
+
+    let mut data = Vec::new();
+    let info = export_cell_snapshot(&source, &mut data, 1).unwrap();
+
+    let target_dir = TempDir::new().unwrap();
+    let target = new_store(&target_dir);
+    assert!(target
+        .init_from_cell_snapshot(&consensus, Cursor::new(&data), &packed::Byte32::zero())
+        .is_err());
+    assert!(target.get_tip_header().is_none());
+
+    // a tampered snapshot fails the hash check, flip the last byte before the end marker
+    let index = data.len() - 34;
+    data[index] ^= 0xff;
+    let target_dir = TempDir::new().unwrap();
+    let target = new_store(&target_dir);
+    assert!(target
+        .init_from_cell_snapshot(&consensus, Cursor::new(&data), &info.hash)
+        .is_err());
+    assert!(target.get_tip_header().is_none());
+}
+
+#[test]
+fn truncated_cell_snapshot_leaves_nothing_behind() {
+    let consensus = ConsensusBuilder::default().build();
+    let source_dir = TempDir::new().unwrap();
+    let source = new_store(&source_dir);
+    source.init(&consensus).unwrap();
+    insert_next_block(&source, &consensus);
+
+    let mut data = Vec::new();
+    let info = export_cell_snapshot(&source, &mut data, 1).unwrap();
+    // drop the end marker and the trailing hash
+    data.truncate(data.len() - 33);
+
+    let target_dir = TempDir::new().unwrap();
+    let target = new_store(&target_dir);
+    assert!(target
+        .init_from_cell_snapshot(&consensus, Cursor::new(&data), &info.hash)
+        .is_err());
+    assert!(target.get_tip_header().is_none());
+    assert!(target
+        .get_iter(COLUMN_CELL, IteratorMode::Start)
+        .next()
+        .is_none());
+}
+
+#[test]
+fn reject_cell_snapshot_with_tampered_header() {
+    let consensus = ConsensusBuilder::default().build();
+    let source_dir = TempDir::new().unwrap();
+    let source = new_store(&source_dir);
+    source.init(&consensus).unwrap();
+    insert_next_block(&source, &consensus);
+
+    let mut data = Vec::new();
+    let info = export_cell_snapshot(&source, &mut data, 1).unwrap();
+    // rewrite the header to the genesis block, after the magic and the version
+    data[12..20].copy_from_slice(&0u64.to_le_bytes());
+    data[20..52].copy_from_slice(consensus.genesis_hash().as_slice());
+
+    let target_dir = TempDir::new().unwrap();
+    let target = new_store(&target_dir);
+    assert!(target
+        .init_from_cell_snapshot(&consensus, Cursor::new(&data), &info.hash)
+        .is_err());
+    assert!(target.get_tip_header().is_none());
+}
+
+#[test]
+fn reject_cell_snapshot_of_other_chain_before_writing() {
+    let consensus = ConsensusBuilder::default().build();
+    let source_dir = TempDir::new().unwrap();
+    let source = new_store(&source_dir);
+    source.init(&consensus).unwrap();
+    insert_next_block(&source, &consensus);
+
+    let mut data = Vec::new();
+    let info = export_cell_snapshot(&source, &mut data, 1).unwrap();
+
+    let other_genesis = consensus
+        .genesis_block()
+        .as_advanced_builder()
+        .timestamp((consensus.genesis_block().timestamp() + 1).pack())
+        .build();
+    let other_consensus = ConsensusBuilder::default()
+        .genesis_block(other_genesis)
+        .build();
+    let target_dir = TempDir::new().unwrap();
+    let target = new_store(&target_dir);
+    assert!(target
+        .init_from_cell_snapshot(&other_consensus, Cursor::new(&data), &info.hash)
+        .is_err());
+    assert!(target.get_tip_header().is_none());
+    assert!(target
+        .get_iter(COLUMN_CELL, IteratorMode::Start)
+        .next()
+        .is_none());
+}
+
+#[test]
+fn export_and_load_genesis_cell_snapshot() {
+    let consensus = ConsensusBuilder::default().build();
+    let source_dir = TempDir::new().unwrap();
+    let source = new_store(&source_dir);
+    source.init(&consensus).unwrap();
+
+    let mut data = Vec::new();
+    let info = export_cell_snapshot(&source, &mut data, 1).unwrap();
+    assert_eq!(info.number, 0);
+    assert_eq!(info.block_hash, consensus.genesis_hash());
+
+    let target_dir = TempDir::new().unwrap();
+    let target = new_store(&target_dir);
+    target
+        .init_from_cell_snapshot(&consensus, Cursor::new(&data), &info.hash)
+        .unwrap();
+    assert_eq!(
+        target.get_tip_header(),
+        Some(consensus.genesis_block().header())
+    );
+}
@@ -159,3 +159,22 @@ fn freeze_blockv1_with_extension() {
     let block = store.get_block(&block_hash).expect("get_block");
     assert_eq!(store.get_block(&block_hash), Some(block));
 }
+
+#[test]
+fn pruned_block_number() {
+    let tmp_dir = TempDir::new().unwrap();
+    let db = RocksDB::open_in(&tmp_dir, COLUMNS);
+    let store = ChainDB::new(db, Default::default());
+    assert_eq!(store.get_pruned_block_number(), 0);
+    assert!(!store.is_block_pruned(1));
+
+    let mut batch = store.new_write_batch();
+    batch.insert_pruned_block_number(5).unwrap();
+    store.write(&batch).unwrap();
+    assert_eq!(store.get_pruned_block_number(), 5);
+    // the genesis block is never pruned
+    assert!(!store.is_block_pruned(0));
+    assert!(store.is_block_pruned(1));
+    assert!(store.is_block_pruned(4));
+    assert!(!store.is_block_pruned(5));
+}
@@ -1 +1,3 @@
+mod backup;
+mod cell_snapshot;
 mod db;
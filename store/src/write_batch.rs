@@ -1,7 +1,8 @@
 use ckb_db::RocksDBWriteBatch;
 use ckb_db_schema::{
     Col, COLUMN_BLOCK_BODY, COLUMN_BLOCK_EXTENSION, COLUMN_BLOCK_HEADER, COLUMN_BLOCK_PROPOSAL_IDS,
-    COLUMN_BLOCK_UNCLE, COLUMN_CELL, COLUMN_CELL_DATA, COLUMN_CELL_DATA_HASH, COLUMN_NUMBER_HASH,
+    COLUMN_BLOCK_UNCLE, COLUMN_CELL, COLUMN_CELL_DATA, COLUMN_CELL_DATA_HASH, COLUMN_META,
+    COLUMN_NUMBER_HASH, META_PRUNED_BLOCK_NUMBER_KEY,
 };
 use ckb_error::Error;
 use ckb_types::{core::BlockNumber, packed, prelude::*};
@@ -117,6 +118,13 @@ impl StoreWriteBatch {
         Ok(())
     }
 
+    /// Records the number of the first block whose body is not pruned
+    pub fn insert_pruned_block_number(&mut self, number: BlockNumber) -> Result<(), Error> {
+        let number: packed::Uint64 = number.pack();
+        self.inner
+            .put(COLUMN_META, META_PRUNED_BLOCK_NUMBER_KEY, number.as_slice())
+    }
+
     /// Removes the entire block from database with corresponding hash, number and txs number
     pub fn delete_block(
         &mut self,
@@ -10,6 +10,7 @@ mod get_transactions_process;
 #[cfg(test)]
 pub(crate) mod tests;
 mod transaction_hashes_process;
+mod transaction_package_process;
 mod transactions_process;
 
 use self::block_proposal_process::BlockProposalProcess;
@@ -19,6 +20,7 @@ use self::get_block_proposal_process::GetBlockProposalProcess;
 use self::get_block_transactions_process::GetBlockTransactionsProcess;
 use self::get_transactions_process::GetTransactionsProcess;
 use self::transaction_hashes_process::TransactionHashesProcess;
+use self::transaction_package_process::TransactionPackageProcess;
 use self::transactions_process::TransactionsProcess;
 use crate::types::{post_sync_process, ActiveChain, SyncShared};
 use crate::utils::{metric_ckb_message_bytes, send_message_to, MetricDirection};
@@ -31,7 +33,7 @@ use ckb_logger::{
     debug, debug_target, error, error_target, info_target, trace_target, warn_target,
 };
 use ckb_network::{
-    async_trait, bytes::Bytes, tokio, CKBProtocolContext, CKBProtocolHandler, PeerIndex,
+    async_trait, bytes::Bytes, tokio, CKBProtocolContext, CKBProtocolHandler, Flags, PeerIndex,
     SupportProtocols, TargetSession,
 };
 use ckb_shared::block_status::BlockStatus;
@@ -188,6 +190,22 @@ impl Relayer {
             packed::RelayMessageUnionReader::BlockProposal(reader) => {
                 BlockProposalProcess::new(reader, self).execute()
             }
+            packed::RelayMessageUnionReader::RelayTransactionPackage(reader) => {
+                // after ckb2023, v2 doesn't work with relay tx
+                // before ckb2023, v3 doesn't work with relay tx
+                match RelaySwitch::new(&nc, self.v3) {
+                    RelaySwitch::Ckb2023RelayV2 | RelaySwitch::Ckb2021RelayV3 => {
+                        return Status::ignored()
+                    }
+                    RelaySwitch::Ckb2023RelayV3 | RelaySwitch::Ckb2021RelayV2 => (),
+                }
+                if reader.check_data() {
+                    TransactionPackageProcess::new(reader, self, nc, peer).execute()
+                } else {
+                    StatusCode::ProtocolMessageIsMalformed
+                        .with_context("RelayTransactionPackage is invalid")
+                }
+            }
         }
     }
 
@@ -668,6 +686,7 @@ impl Relayer {
             .state()
             .take_relay_tx_verify_results(MAX_RELAY_TXS_NUM_PER_BATCH);
         let mut selected: HashMap<PeerIndex, Vec<Byte32>> = HashMap::default();
+        let mut packages = Vec::new();
         {
             for tx_verify_result in tx_verify_results {
                 match tx_verify_result {
@@ -702,6 +721,22 @@ impl Relayer {
                             }
                         }
                     }
+                    TxVerificationResult::PackageOk {
+                        original_peer,
+                        with_vm_2023,
+                        tx_hashes,
+                    } => {
+                        // must all fork or all no-fork
+                        if ckb2023 != with_vm_2023 {
+                            continue;
+                        }
+                        if original_peer.is_none() {
+                            self.shared
+                                .state()
+                                .mark_as_known_txs(tx_hashes.iter().cloned());
+                        }
+                        packages.push((original_peer, tx_hashes));
+                    }
                     TxVerificationResult::Reject { tx_hash } => {
                         self.shared.state().remove_from_known_txs(&tx_hash);
                     }
@@ -719,6 +754,16 @@ impl Relayer {
                 }
             }
         }
+        for (original_peer, tx_hashes) in packages {
+            let hashes_peers =
+                self.broadcast_package(nc, &connected_peers, original_peer, &tx_hashes);
+            for peer in hashes_peers {
+                selected
+                    .entry(peer)
+                    .or_insert_with(|| Vec::with_capacity(BUFFER_SIZE))
+                    .extend(tx_hashes.iter().cloned());
+            }
+        }
         for (peer, hashes) in selected {
             let content = packed::RelayTransactionHashes::new_builder()
                 .tx_hashes(hashes.pack())
@@ -734,6 +779,78 @@ impl Relayer {
             }
         }
     }
+
+    /// Sends the package to the peers supporting package relay, returns the other peers which the
+    /// tx hashes should be announced to.
+    fn broadcast_package(
+        &self,
+        nc: &dyn CKBProtocolContext,
+        connected_peers: &[PeerIndex],
+        original_peer: Option<PeerIndex>,
+        tx_hashes: &[Byte32],
+    ) -> Vec<PeerIndex> {
+        let targets = connected_peers
+            .iter()
+            .copied()
+            .filter(|target| Some(*target) != original_peer);
+        let (package_peers, hashes_peers): (Vec<PeerIndex>, Vec<PeerIndex>) =
+            targets.partition(|target| {
+                nc.get_peer(*target)
+                    .and_then(|peer| peer.identify_info)
+                    .map_or(false, |info| info.flags.contains(Flags::PACKAGE_RELAY))
+            });
+        if package_peers.is_empty() {
+            return hashes_peers;
+        }
+
+        let short_ids = tx_hashes
+            .iter()
+            .map(ProposalShortId::from_tx_hash)
+            .collect();
+        let fetched: HashMap<_, _> = match self
+            .shared
+            .shared()
+            .tx_pool_controller()
+            .fetch_txs_with_cycles(short_ids)
+        {
+            Ok(txs) => txs.into_iter().collect(),
+            Err(err) => {
+                debug_target!(
+                    crate::LOG_TARGET_RELAY,
+                    "relayer tx_pool_controller send fetch_txs_with_cycles error: {:?}",
+                    err,
+                );
+                return hashes_peers;
+            }
+        };
+        // keep the package order, parents first
+        let txs: Vec<_> = tx_hashes
+            .iter()
+            .filter_map(|tx_hash| fetched.get(&ProposalShortId::from_tx_hash(tx_hash)))
+            .map(|(tx, cycles)| {
+                packed::RelayTransaction::new_builder()
+                    .cycles(cycles.pack())
+                    .transaction(tx.data())
+                    .build()
+            })
+            .collect();
+        let content = packed::RelayTransactionPackage::new_builder()
+            .transactions(packed::RelayTransactionVec::new_builder().set(txs).build())
+            .build();
+        let message = packed::RelayMessage::new_builder().set(content).build();
+
+        if let Err(err) = nc.filter_broadcast(
+            TargetSession::Multi(Box::new(package_peers.into_iter())),
+            message.as_bytes(),
+        ) {
+            debug_target!(
+                crate::LOG_TARGET_RELAY,
+                "relayer send TransactionPackage error: {:?}",
+                err,
+            );
+        }
+        hashes_peers
+    }
 }
 
 fn build_and_broadcast_compact_block(
@@ -8,3 +8,4 @@ mod get_block_proposal_process;
 mod get_transactions_process;
 pub(crate) mod helper;
 mod reconstruct_block;
+mod transaction_package_process;
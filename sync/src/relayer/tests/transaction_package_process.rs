@@ -0,0 +1,193 @@
+use crate::relayer::tests::helper::{build_chain, MockProtocolContext};
+use crate::relayer::transaction_package_process::TransactionPackageProcess;
+use crate::relayer::Relayer;
+use crate::{Status, StatusCode};
+use ckb_network::{PeerIndex, SupportProtocols};
+use ckb_store::ChainStore;
+use ckb_test_chain_utils::always_success_cell;
+use ckb_tx_pool::service::TxVerificationResult;
+use ckb_types::{
+    bytes::Bytes,
+    core::{
+        capacity_bytes,
+        tx_pool::{Reject, MAX_PACKAGE_TXS},
+        Capacity, TransactionBuilder, TransactionView,
+    },
+    packed::{self, CellDep, CellInput, CellOutputBuilder, OutPoint},
+    prelude::*,
+};
+use std::sync::Arc;
+
+// Spends the first output of `previous_output`'s tx, pays `fee` and keeps the always success lock
+fn build_tx(
+    previous_output: OutPoint,
+    input_capacity: Capacity,
+    fee: Capacity,
+    always_success_out_point: &OutPoint,
+) -> TransactionView {
+    let (_, _, always_success_script) = always_success_cell();
+    TransactionBuilder::default()
+        .input(CellInput::new(previous_output, 0))
+        .output(
+            CellOutputBuilder::default()
+                .capacity(input_capacity.safe_sub(fee).unwrap().pack())
+                .lock(always_success_script.to_owned())
+                .build(),
+        )
+        .output_data(Bytes::new().pack())
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(always_success_out_point.to_owned())
+                .build(),
+        )
+        .build()
+}
+
+// Builds a parent paying no fee and a child paying for both of them
+fn build_package(
+    relayer: &Relayer,
+    always_success_out_point: &OutPoint,
+    child_fee: Capacity,
+) -> (TransactionView, TransactionView) {
+    let cellbase_output = {
+        let shared = relayer.shared.shared();
+        let tip_hash = shared.snapshot().tip_header().hash();
+        let block = shared
+            .store()
+            .get_block(&tip_hash)
+            .expect("getting tip block");
+        block.transactions()[0].output_pts()[0].clone()
+    };
+    let capacity = capacity_bytes!(50000);
+    let parent = build_tx(
+        cellbase_output,
+        capacity,
+        Capacity::zero(),
+        always_success_out_point,
+    );
+    let child = build_tx(
+        OutPoint::new(parent.hash(), 0),
+        capacity,
+        child_fee,
+        always_success_out_point,
+    );
+    (parent, child)
+}
+
+fn build_message(txs: Vec<TransactionView>) -> packed::RelayTransactionPackage {
+    let txs: Vec<_> = txs
+        .into_iter()
+        .map(|tx| {
+            packed::RelayTransaction::new_builder()
+                .cycles(1000u64.pack())
+                .transaction(tx.data())
+                .build()
+        })
+        .collect();
+    packed::RelayTransactionPackage::new_builder()
+        .transactions(packed::RelayTransactionVec::new_builder().set(txs).build())
+        .build()
+}
+
+#[test]
+fn test_package_size() {
+    let (relayer, always_success_out_point) = build_chain(5);
+    let (parent, _) = build_package(&relayer, &always_success_out_point, capacity_bytes!(1));
+    let nc = Arc::new(MockProtocolContext::new(SupportProtocols::RelayV3));
+    let peer_index: PeerIndex = 1.into();
+
+    let content = build_message(vec![]);
+    let process =
+        TransactionPackageProcess::new(content.as_reader(), &relayer, nc.clone(), peer_index);
+    assert_eq!(
+        process.execute(),
+        StatusCode::ProtocolMessageIsMalformed
+            .with_context("RelayTransactionPackage contains 0 transactions")
+    );
+
+    let content = build_message(vec![parent; MAX_PACKAGE_TXS + 1]);
+    let process = TransactionPackageProcess::new(content.as_reader(), &relayer, nc, peer_index);
+    assert_eq!(
+        process.execute(),
+        StatusCode::ProtocolMessageIsMalformed.with_context(format!(
+            "RelayTransactionPackage contains {} transactions",
+            MAX_PACKAGE_TXS + 1
+        ))
+    );
+}
+
+#[test]
+fn test_known_package() {
+    let (relayer, always_success_out_point) = build_chain(5);
+    let (parent, child) = build_package(&relayer, &always_success_out_point, capacity_bytes!(1));
+    let nc = Arc::new(MockProtocolContext::new(SupportProtocols::RelayV3));
+    let peer_index: PeerIndex = 1.into();
+    let content = build_message(vec![parent.clone(), child.clone()]);
+
+    let process =
+        TransactionPackageProcess::new(content.as_reader(), &relayer, nc.clone(), peer_index);
+    assert_eq!(process.execute(), Status::ok());
+    {
+        let tx_filter = relayer.shared().state().tx_filter();
+        assert!(tx_filter.contains(&parent.hash()));
+        assert!(tx_filter.contains(&child.hash()));
+    }
+
+    // the same package from another peer
+    let process = TransactionPackageProcess::new(content.as_reader(), &relayer, nc, 2.into());
+    assert_eq!(process.execute(), Status::ignored());
+}
+
+#[test]
+fn test_submit_local_package() {
+    let (relayer, always_success_out_point) = build_chain(5);
+    let (parent, child) = build_package(&relayer, &always_success_out_point, capacity_bytes!(1));
+    let tx_pool = relayer.shared.shared().tx_pool_controller();
+
+    // the parent alone pays no fee
+    let ret = tx_pool.submit_local_tx(parent.clone()).unwrap();
+    assert!(matches!(ret, Err(Reject::LowFeeRate(..))), "{ret:?}");
+
+    // parents first
+    let ret = tx_pool
+        .submit_local_package(vec![child.clone(), parent.clone()])
+        .unwrap();
+    assert!(matches!(ret, Err(Reject::Malformed(..))), "{ret:?}");
+
+    let ret = tx_pool
+        .submit_local_package(vec![parent.clone(), child.clone()])
+        .unwrap();
+    assert!(ret.is_ok(), "{ret:?}");
+    let short_ids = [&parent, &child]
+        .iter()
+        .map(|tx| tx.proposal_short_id())
+        .collect();
+    assert_eq!(tx_pool.fetch_txs(short_ids).unwrap().len(), 2);
+
+    // the package is relayed as a whole
+    let results = relayer.shared().state().take_relay_tx_verify_results(10);
+    assert!(results.iter().any(|result| matches!(
+        result,
+        TxVerificationResult::PackageOk { original_peer: None, tx_hashes, .. }
+            if *tx_hashes == [parent.hash(), child.hash()]
+    )));
+}
+
+#[test]
+fn test_reject_low_fee_package() {
+    let (relayer, always_success_out_point) = build_chain(5);
+    let (parent, child) = build_package(&relayer, &always_success_out_point, Capacity::zero());
+    let tx_pool = relayer.shared.shared().tx_pool_controller();
+
+    let ret = tx_pool
+        .submit_local_package(vec![parent.clone(), child.clone()])
+        .unwrap();
+    assert!(matches!(ret, Err(Reject::LowFeeRate(..))), "{ret:?}");
+
+    // nothing is added
+    let short_ids = [&parent, &child]
+        .iter()
+        .map(|tx| tx.proposal_short_id())
+        .collect();
+    assert!(tx_pool.fetch_txs(short_ids).unwrap().is_empty());
+}
@@ -0,0 +1,99 @@
+use crate::relayer::Relayer;
+use crate::{Status, StatusCode};
+use ckb_logger::error;
+use ckb_network::{CKBProtocolContext, PeerIndex};
+use ckb_types::{
+    core::{tx_pool::MAX_PACKAGE_TXS, Cycle, TransactionView},
+    packed,
+    prelude::*,
+};
+use std::sync::Arc;
+use std::time::Duration;
+
+const DEFAULT_BAN_TIME: Duration = Duration::from_secs(3600 * 24 * 3);
+
+/// Submits a package pushed by the peer, in which the children pay for the low fee parents.
+///
+/// Unlike `RelayTransactions`, the package is not requested, it's sent instead of the hashes to
+/// the peers setting the `PACKAGE_RELAY` flag, because the parents alone would be rejected.
+pub struct TransactionPackageProcess<'a> {
+    message: packed::RelayTransactionPackageReader<'a>,
+    relayer: &'a Relayer,
+    nc: Arc<dyn CKBProtocolContext + Sync>,
+    peer: PeerIndex,
+}
+
+impl<'a> TransactionPackageProcess<'a> {
+    pub fn new(
+        message: packed::RelayTransactionPackageReader<'a>,
+        relayer: &'a Relayer,
+        nc: Arc<dyn CKBProtocolContext + Sync>,
+        peer: PeerIndex,
+    ) -> Self {
+        TransactionPackageProcess {
+            message,
+            relayer,
+            nc,
+            peer,
+        }
+    }
+
+    pub fn execute(self) -> Status {
+        let transactions = self.message.transactions();
+        if transactions.is_empty() || transactions.len() > MAX_PACKAGE_TXS {
+            return StatusCode::ProtocolMessageIsMalformed.with_context(format!(
+                "RelayTransactionPackage contains {} transactions",
+                transactions.len()
+            ));
+        }
+
+        let txs: Vec<(TransactionView, Cycle)> = transactions
+            .iter()
+            .map(|tx| {
+                (
+                    tx.transaction().to_entity().into_view(),
+                    tx.cycles().unpack(),
+                )
+            })
+            .collect();
+
+        let shared_state = self.relayer.shared().state();
+        {
+            // the package has been received from another peer
+            let mut tx_filter = shared_state.tx_filter();
+            tx_filter.remove_expired();
+            if txs.iter().all(|(tx, _)| tx_filter.contains(&tx.hash())) {
+                return Status::ignored();
+            }
+        }
+
+        let max_block_cycles = self.relayer.shared().consensus().max_block_cycles();
+        if txs
+            .iter()
+            .any(|(_, declared_cycles)| declared_cycles > &max_block_cycles)
+        {
+            self.nc.ban_peer(
+                self.peer,
+                DEFAULT_BAN_TIME,
+                String::from("relay declared cycles greater than max_block_cycles"),
+            );
+            return Status::ok();
+        }
+
+        shared_state.mark_as_known_txs(txs.iter().map(|(tx, _)| tx.hash()));
+
+        let tx_pool = self.relayer.shared.shared().tx_pool_controller().clone();
+        let peer = self.peer;
+        self.relayer
+            .shared
+            .shared()
+            .async_handle()
+            .spawn(async move {
+                if let Err(e) = tx_pool.submit_remote_package(txs, peer).await {
+                    error!("submit_package error {}", e);
+                }
+            });
+
+        Status::ok()
+    }
+}
@@ -1,7 +1,7 @@
 use crate::types::{ActiveChain, IBDState};
 use crate::SyncShared;
 use ckb_constant::sync::{
-    BLOCK_DOWNLOAD_WINDOW, CHECK_POINT_WINDOW, INIT_BLOCKS_IN_TRANSIT_PER_PEER,
+    BLOCK_DOWNLOAD_WINDOW, CHECK_POINT_WINDOW, INIT_BLOCKS_IN_TRANSIT_PER_PEER, MIN_PRUNE_DEPTH,
 };
 use ckb_logger::{debug, trace};
 use ckb_metrics::HistogramTimer;
@@ -15,6 +15,12 @@ use ckb_types::BlockNumberAndHash;
 use std::cmp::min;
 use std::sync::Arc;
 
+/// Returns true if a pruned peer, whose best known block is `peer_best_known`, still keeps the
+/// body of block `number`
+pub(crate) fn limited_peer_keeps_block(peer_best_known: BlockNumber, number: BlockNumber) -> bool {
+    number.saturating_add(MIN_PRUNE_DEPTH) >= peer_best_known
+}
+
 pub struct BlockFetcher {
     sync_shared: Arc<SyncShared>,
     peer: PeerIndex,
@@ -187,6 +193,19 @@ impl BlockFetcher {
                 IBDState::Out => last_common.number() + 1,
             }
         };
+        let is_limited = state
+            .peers()
+            .get_flag(self.peer)
+            .map_or(false, |flags| flags.is_limited);
+        if is_limited && !limited_peer_keeps_block(best_known.number(), start) {
+            debug!(
+                "Peer {} is a pruned node, block {} may have been pruned by it, best_known: {}",
+                self.peer,
+                start,
+                best_known.number()
+            );
+            return None;
+        }
         let mut end = min(
             fetch_end,
             min(best_known.number(), start + BLOCK_DOWNLOAD_WINDOW),
@@ -5,6 +5,7 @@ use ckb_constant::sync::{INIT_BLOCKS_IN_TRANSIT_PER_PEER, MAX_HEADERS_LEN};
 use ckb_logger::debug;
 use ckb_network::{CKBProtocolContext, PeerIndex};
 use ckb_shared::block_status::BlockStatus;
+use ckb_store::ChainStore;
 use ckb_types::{packed, prelude::*};
 use std::collections::HashSet;
 
@@ -41,6 +42,7 @@ impl<'a> GetBlocksProcess<'a> {
             ));
         }
         let active_chain = self.synchronizer.shared.active_chain();
+        let store = self.synchronizer.shared.store();
 
         let iter = block_hashes.iter().take(INIT_BLOCKS_IN_TRANSIT_PER_PEER);
 
@@ -83,7 +85,14 @@ impl<'a> GetBlocksProcess<'a> {
                 // We expect that `block_hashes` is sorted descending by height.
                 // So if we cannot find the current one from local, we cannot find
                 // the next either.
-                debug!("Stopping getblocks, since {} is not found", block_hash);
+                if store
+                    .get_block_number(&block_hash)
+                    .is_some_and(|number| store.is_block_pruned(number))
+                {
+                    debug!("Stopping getblocks, since {} is pruned", block_hash);
+                } else {
+                    debug!("Stopping getblocks, since {} is not found", block_hash);
+                }
                 break;
             }
         }
@@ -13,7 +13,7 @@ mod get_headers_process;
 mod headers_process;
 mod in_ibd_process;
 
-pub(crate) use self::block_fetcher::BlockFetcher;
+pub(crate) use self::block_fetcher::{limited_peer_keeps_block, BlockFetcher};
 pub(crate) use self::block_process::BlockProcess;
 pub(crate) use self::get_blocks_process::GetBlocksProcess;
 pub(crate) use self::get_headers_process::GetHeadersProcess;
@@ -35,7 +35,7 @@ use ckb_constant::sync::{
 use ckb_logger::{debug, error, info, trace, warn};
 use ckb_metrics::HistogramTimer;
 use ckb_network::{
-    async_trait, bytes::Bytes, tokio, CKBProtocolContext, CKBProtocolHandler, PeerIndex,
+    async_trait, bytes::Bytes, tokio, CKBProtocolContext, CKBProtocolHandler, Flags, PeerIndex,
     ServiceControl, SupportProtocols,
 };
 use ckb_shared::types::HeaderIndexView;
@@ -486,19 +486,22 @@ impl Synchronizer {
 
     pub(crate) fn on_connected(&self, nc: &dyn CKBProtocolContext, peer: PeerIndex) {
         let pid = SupportProtocols::Sync.protocol_id();
-        let (is_outbound, is_whitelist, is_2023edition) = nc
+        let (is_outbound, is_whitelist, is_2023edition, is_limited) = nc
             .get_peer(peer)
             .map(|peer| {
                 (
                     peer.is_outbound(),
                     peer.is_whitelist,
                     peer.protocols.get(&pid).map(|v| v == "3").unwrap_or(false),
+                    peer.identify_info
+                        .as_ref()
+                        .map_or(false, |info| info.flags.contains(Flags::LIMITED)),
                 )
             })
-            .unwrap_or((false, false, false));
+            .unwrap_or((false, false, false, false));
 
         self.peers()
-            .sync_connected(peer, is_outbound, is_whitelist, is_2023edition);
+            .sync_connected(peer, is_outbound, is_whitelist, is_2023edition, is_limited);
     }
 
     /// Regularly check and eject some nodes that do not respond in time
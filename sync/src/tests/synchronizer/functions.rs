@@ -1,11 +1,13 @@
 use ckb_chain::{start_chain_services, ChainController};
 use ckb_chain_spec::consensus::{Consensus, ConsensusBuilder};
-use ckb_constant::sync::{CHAIN_SYNC_TIMEOUT, EVICTION_HEADERS_RESPONSE_TIME, MAX_TIP_AGE};
+use ckb_constant::sync::{
+    CHAIN_SYNC_TIMEOUT, EVICTION_HEADERS_RESPONSE_TIME, MAX_TIP_AGE, MIN_PRUNE_DEPTH,
+};
 use ckb_dao::DaoCalculator;
 use ckb_error::InternalErrorKind;
 use ckb_network::{
-    async_trait, bytes::Bytes, Behaviour, CKBProtocolContext, Peer, PeerId, PeerIndex, ProtocolId,
-    SessionType, TargetSession,
+    async_trait, bytes::Bytes, Behaviour, CKBProtocolContext, Flags, Peer, PeerId,
+    PeerIdentifyInfo, PeerIndex, ProtocolId, SessionType, TargetSession,
 };
 use ckb_reward_calculator::RewardCalculator;
 use ckb_shared::types::HeaderIndex;
@@ -36,7 +38,10 @@ use std::{
 };
 
 use crate::{
-    synchronizer::{BlockFetcher, BlockProcess, GetBlocksProcess, HeadersProcess, Synchronizer},
+    synchronizer::{
+        limited_peer_keeps_block, BlockFetcher, BlockProcess, GetBlocksProcess, HeadersProcess,
+        Synchronizer,
+    },
     types::{HeadersSyncController, IBDState, PeerState},
     Status, StatusCode, SyncShared,
 };
@@ -1244,3 +1249,24 @@ fn test_internal_db_error() {
 
     assert!(is_internal_db_error(&status.err().unwrap()));
 }
+
+#[test]
+fn test_limited_peer() {
+    assert!(limited_peer_keeps_block(MIN_PRUNE_DEPTH + 10, 10));
+    assert!(!limited_peer_keeps_block(MIN_PRUNE_DEPTH + 10, 9));
+    assert!(limited_peer_keeps_block(MIN_PRUNE_DEPTH, 0));
+
+    let (_, _, synchronizer) = start_chain(Some(Consensus::default()));
+    let mut nc = mock_network_context(2);
+    let limited: PeerIndex = 1.into();
+    nc.peers.get_mut(&limited).unwrap().identify_info = Some(PeerIdentifyInfo {
+        client_version: "test".to_string(),
+        flags: Flags::COMPATIBILITY | Flags::LIMITED,
+    });
+    synchronizer.on_connected(&nc, 0.into());
+    synchronizer.on_connected(&nc, limited);
+
+    let peers = synchronizer.peers();
+    assert!(!peers.get_flag(0.into()).unwrap().is_limited);
+    assert!(peers.get_flag(limited).unwrap().is_limited);
+}
@@ -131,6 +131,8 @@ pub struct PeerFlags {
     pub is_protect: bool,
     pub is_whitelist: bool,
     pub is_2023edition: bool,
+    /// The peer is a pruned node, which only keeps the bodies of its recent blocks
+    pub is_limited: bool,
 }
 
 #[derive(Clone, Default, Debug, Copy)]
@@ -825,6 +827,7 @@ impl Peers {
         is_outbound: bool,
         is_whitelist: bool,
         is_2023edition: bool,
+        is_limited: bool,
     ) {
         let protect_outbound = is_outbound
             && self
@@ -842,6 +845,7 @@ impl Peers {
             is_outbound,
             is_whitelist,
             is_2023edition,
+            is_limited,
             is_protect: protect_outbound,
         };
         self.state
@@ -66,6 +66,8 @@ impl Spec for BootstrapCellbase {
             notify: vec![],
             notify_scripts: vec![],
             notify_timeout_millis: 800,
+            weight: 1,
+            reward_targets: vec![],
         });
     }
 }
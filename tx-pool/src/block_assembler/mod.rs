@@ -2,6 +2,7 @@
 
 mod candidate_uncles;
 mod process;
+mod reward_targets;
 
 #[cfg(test)]
 mod tests;
@@ -36,6 +37,7 @@ use ckb_types::{
 use http_body_util::Full;
 use hyper::{Method, Request};
 use hyper_util::client::legacy::{connect::HttpConnector, Client};
+pub use reward_targets::MAX_REWARD_TARGETS_WEIGHT;
 use std::collections::HashSet;
 use std::sync::{
     atomic::{AtomicU64, Ordering},
@@ -44,12 +46,13 @@ use std::sync::{
 use std::time::Duration;
 use std::{cmp, iter};
 use tokio::process::Command;
-use tokio::sync::{Mutex, RwLock};
+use tokio::sync::{watch, Mutex, RwLock};
 use tokio::task::block_in_place;
 use tokio::time::timeout;
 
 use crate::TxPool;
 pub(crate) use process::process;
+use reward_targets::RewardTargets;
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub(crate) struct TemplateSize {
@@ -99,10 +102,14 @@ pub(crate) struct CurrentTemplate {
 #[derive(Clone)]
 pub struct BlockAssembler {
     pub(crate) config: Arc<BlockAssemblerConfig>,
+    pub(crate) reward_targets: Arc<RewardTargets>,
     pub(crate) work_id: Arc<AtomicU64>,
     pub(crate) candidate_uncles: Arc<Mutex<CandidateUncles>>,
     pub(crate) current: Arc<Mutex<CurrentTemplate>>,
     pub(crate) poster: Arc<Client<HttpConnector, Full<bytes::Bytes>>>,
+    /// The `work_id` of the current template, watched by the long-polling miners and the
+    /// `new_block_template` subscribers
+    pub(crate) template_updated: Arc<watch::Sender<u64>>,
 }
 
 impl BlockAssembler {
@@ -116,7 +123,8 @@ impl BlockAssembler {
             .epoch();
         let mut builder = BlockTemplateBuilder::new(&snapshot, &current_epoch);
 
-        let cellbase = Self::build_cellbase(&config, &snapshot)
+        let reward_targets = RewardTargets::new(&config);
+        let cellbase = Self::build_cellbase(&config, &reward_targets, &snapshot)
             .expect("build cellbase for BlockAssembler initial");
 
         let extension =
@@ -141,6 +149,7 @@ impl BlockAssembler {
             builder.extension(data);
         }
         let template = builder.build();
+        let (template_updated, _) = watch::channel(template.work_id);
 
         let size = TemplateSize {
             txs: 0,
@@ -158,6 +167,7 @@ impl BlockAssembler {
 
         Self {
             config: Arc::new(config),
+            reward_targets: Arc::new(reward_targets),
             work_id: Arc::new(work_id),
             candidate_uncles: Arc::new(Mutex::new(CandidateUncles::new())),
             current: Arc::new(Mutex::new(current)),
@@ -165,6 +175,7 @@ impl BlockAssembler {
                 Client::builder(hyper_util::rt::TokioExecutor::new())
                     .build::<_, Full<bytes::Bytes>>(HttpConnector::new()),
             ),
+            template_updated: Arc::new(template_updated),
         }
     }
 
@@ -230,6 +241,7 @@ impl BlockAssembler {
             .dao(dao);
 
         current.template = builder.build();
+        self.template_updated.send_replace(current.template.work_id);
         current.size.txs = txs_size;
         current.size.total = total_size;
         current.size.proposals = proposals_size;
@@ -254,7 +266,7 @@ impl BlockAssembler {
             .epoch();
         let mut builder = BlockTemplateBuilder::new(&snapshot, &current_epoch);
 
-        let cellbase = Self::build_cellbase(&self.config, &snapshot)?;
+        let cellbase = Self::build_cellbase(&self.config, &self.reward_targets, &snapshot)?;
         let uncles = self.prepare_uncles(&snapshot, &current_epoch).await;
         let uncles_size = uncles.len() * UncleBlockView::serialized_size_in_block();
 
@@ -293,6 +305,7 @@ impl BlockAssembler {
             total: basic_block_size,
         };
 
+        let work_id = template.work_id;
         let new_blank = CurrentTemplate {
             template,
             size,
@@ -301,6 +314,7 @@ impl BlockAssembler {
         };
 
         *self.current.lock().await = new_blank;
+        self.template_updated.send_replace(work_id);
         Ok(())
     }
 
@@ -329,6 +343,7 @@ impl BlockAssembler {
                             current.template.current_time,
                         ));
                     current.template = builder.build();
+                    self.template_updated.send_replace(current.template.work_id);
                     current.size.uncles = new_uncle_size;
                     current.size.total = new_total_size;
 
@@ -370,6 +385,7 @@ impl BlockAssembler {
                     current.template.current_time,
                 ));
             current.template = builder.build();
+            self.template_updated.send_replace(current.template.work_id);
             current.size.proposals = new_proposals_size;
             current.size.total = new_total_size;
 
@@ -439,6 +455,7 @@ impl BlockAssembler {
                 builder.extension(data);
             }
             current.template = builder.build();
+            self.template_updated.send_replace(current.template.work_id);
             current.size.txs = new_txs_size;
             current.size.total = new_total_size;
 
@@ -461,14 +478,9 @@ impl BlockAssembler {
 
     pub(crate) fn build_cellbase_witness(
         config: &BlockAssemblerConfig,
+        cellbase_lock: Script,
         snapshot: &Snapshot,
     ) -> CellbaseWitness {
-        let hash_type: ScriptHashType = config.hash_type.clone().into();
-        let cellbase_lock = Script::new_builder()
-            .args(config.args.as_bytes().pack())
-            .code_hash(config.code_hash.pack())
-            .hash_type(hash_type.into())
-            .build();
         let tip = snapshot.tip_header();
 
         let mut message = vec![];
@@ -491,16 +503,18 @@ impl BlockAssembler {
     }
 
     /// Miner mined block H(c), the block reward will be finalized at H(c + w_far + 1).
-    /// Miner specify own lock in cellbase witness.
+    /// Miner specify own lock in cellbase witness, which is rotated among the reward targets.
     /// The cellbase have only one output,
     /// miner should collect the block reward for finalize target H(max(0, c - w_far - 1))
     pub(crate) fn build_cellbase(
         config: &BlockAssemblerConfig,
+        reward_targets: &RewardTargets,
         snapshot: &Snapshot,
     ) -> Result<TransactionView, AnyError> {
         let tip = snapshot.tip_header();
         let candidate_number = tip.number() + 1;
-        let cellbase_witness = Self::build_cellbase_witness(config, snapshot);
+        let cellbase_lock = reward_targets.lock(candidate_number).clone();
+        let cellbase_witness = Self::build_cellbase_witness(config, cellbase_lock, snapshot);
 
         let tx = {
             let (target_lock, block_reward) = block_in_place(|| {
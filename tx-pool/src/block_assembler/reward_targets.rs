@@ -0,0 +1,89 @@
+use ckb_app_config::BlockAssemblerConfig;
+use ckb_types::{
+    core::{BlockNumber, ScriptHashType},
+    packed::Script,
+    prelude::*,
+};
+
+/// The max total weight of the reward targets, which is the length of the rotation schedule.
+pub const MAX_REWARD_TARGETS_WEIGHT: u64 = 10_000;
+
+/// The lock scripts to claim the miner rewards.
+///
+/// The consensus only allows one cellbase output, which goes to the lock script named in the
+/// cellbase witness of the finalized block, so the rewards are split by rotating the lock scripts
+/// over the block numbers. The rotation is a smooth weighted round-robin, which interleaves the
+/// targets instead of naming the same one in a run of blocks.
+pub(crate) struct RewardTargets {
+    locks: Vec<Script>,
+    // the index of the lock for each block number modulo the schedule length
+    schedule: Vec<usize>,
+}
+
+impl RewardTargets {
+    pub(crate) fn new(config: &BlockAssemblerConfig) -> Self {
+        let (locks, weights): (Vec<_>, Vec<_>) = config
+            .all_reward_targets()
+            .into_iter()
+            .map(|target| {
+                let hash_type: ScriptHashType = target.hash_type.into();
+                let lock = Script::new_builder()
+                    .args(target.args.as_bytes().pack())
+                    .code_hash(target.code_hash.pack())
+                    .hash_type(hash_type.into())
+                    .build();
+                (lock, u64::from(target.weight))
+            })
+            .unzip();
+        RewardTargets {
+            schedule: weighted_schedule(&weights),
+            locks,
+        }
+    }
+
+    /// The lock script named in the cellbase witness of the block `number`.
+    pub(crate) fn lock(&self, number: BlockNumber) -> &Script {
+        let index = self.schedule[(number % self.schedule.len() as u64) as usize];
+        &self.locks[index]
+    }
+}
+
+/// Builds the smooth weighted round-robin schedule, in which the target `i` appears `weights[i]`
+/// times out of every `sum(weights)` slots, after the weights are divided by their gcd.
+///
+/// The targets of zero weight never appear, unless all the weights are zero, then the schedule
+/// only has the first target. The schedule is truncated to `MAX_REWARD_TARGETS_WEIGHT` slots.
+pub(crate) fn weighted_schedule(weights: &[u64]) -> Vec<usize> {
+    let divisor = weights
+        .iter()
+        .fold(0, |acc, weight| gcd(acc, *weight))
+        .max(1);
+    let weights: Vec<u64> = weights.iter().map(|weight| weight / divisor).collect();
+    let total: u64 = weights.iter().sum();
+    let len = total.clamp(1, MAX_REWARD_TARGETS_WEIGHT);
+
+    let mut current = vec![0i64; weights.len()];
+    (0..len)
+        .map(|_| {
+            for (current, weight) in current.iter_mut().zip(&weights) {
+                *current += *weight as i64;
+            }
+            let (index, _) = current
+                .iter()
+                .enumerate()
+                .rev()
+                .max_by_key(|(_, current)| **current)
+                .expect("weights are not empty");
+            current[index] -= total as i64;
+            index
+        })
+        .collect()
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
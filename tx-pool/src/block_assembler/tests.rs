@@ -1,11 +1,15 @@
+use ckb_app_config::{BlockAssemblerConfig, RewardTarget};
+use ckb_jsonrpc_types::{JsonBytes, ScriptHashType};
 use ckb_types::{
     core::{BlockBuilder, BlockNumber, EpochNumberWithFraction},
+    h256,
     prelude::*,
 };
 
 use crate::block_assembler::candidate_uncles::{
     CandidateUncles, MAX_CANDIDATE_UNCLES, MAX_PER_HEIGHT,
 };
+use crate::block_assembler::reward_targets::{weighted_schedule, RewardTargets};
 
 #[test]
 fn test_candidate_uncles_basic() {
@@ -72,3 +76,57 @@ fn test_candidate_uncles_max_per_height() {
     assert_eq!(candidate_uncles.map.len(), 1);
     assert_eq!(candidate_uncles.len(), MAX_PER_HEIGHT);
 }
+
+#[test]
+fn test_weighted_schedule() {
+    assert_eq!(weighted_schedule(&[1]), vec![0]);
+    assert_eq!(weighted_schedule(&[1, 1, 1]), vec![0, 1, 2]);
+    // the targets are interleaved
+    assert_eq!(weighted_schedule(&[2, 1]), vec![0, 1, 0]);
+    assert_eq!(weighted_schedule(&[4, 2]), vec![0, 1, 0]);
+    assert_eq!(weighted_schedule(&[0, 1]), vec![1]);
+    assert_eq!(weighted_schedule(&[0, 0]), vec![0]);
+
+    let schedule = weighted_schedule(&[98, 2]);
+    assert_eq!(schedule.len(), 50);
+    assert_eq!(schedule.iter().filter(|index| **index == 1).count(), 1);
+}
+
+#[test]
+fn test_reward_targets() {
+    let config = BlockAssemblerConfig {
+        code_hash: h256!("0x1"),
+        args: JsonBytes::default(),
+        hash_type: ScriptHashType::Type,
+        message: JsonBytes::default(),
+        use_binary_version_as_message_prefix: true,
+        binary_version: "TEST".to_string(),
+        update_interval_millis: 800,
+        notify: vec![],
+        notify_scripts: vec![],
+        notify_timeout_millis: 800,
+        weight: 2,
+        reward_targets: vec![RewardTarget {
+            code_hash: h256!("0x2"),
+            args: JsonBytes::default(),
+            hash_type: ScriptHashType::Type,
+            weight: 1,
+        }],
+    };
+    let reward_targets = RewardTargets::new(&config);
+    let code_hashes: Vec<_> = (0..6)
+        .map(|number| reward_targets.lock(number).code_hash())
+        .collect();
+    let (first, second) = (h256!("0x1").pack(), h256!("0x2").pack());
+    assert_eq!(
+        code_hashes,
+        vec![
+            first.clone(),
+            second.clone(),
+            first.clone(),
+            first.clone(),
+            second,
+            first
+        ]
+    );
+}
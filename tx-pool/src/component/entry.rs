@@ -1,4 +1,5 @@
 use crate::component::sort_key::{AncestorsScoreSortKey, EvictKey};
+use crate::policy::EvictionClass;
 use ckb_systemtime::unix_time_as_millis;
 use ckb_types::{
     core::{
@@ -41,6 +42,8 @@ pub struct TxEntry {
     pub descendants_count: usize,
     /// The unix timestamp when entering the Txpool, unit: Millisecond
     pub timestamp: u64,
+    /// The eviction class assigned by the tx-pool policy when entering the Txpool
+    pub eviction_class: EvictionClass,
 }
 
 impl TxEntry {
@@ -71,6 +74,7 @@ impl TxEntry {
             descendants_cycles: cycles,
             descendants_count: 1,
             ancestors_count: 1,
+            eviction_class: EvictionClass::Normal,
         }
     }
 
@@ -6,6 +6,8 @@ use crate::component::edges::Edges;
 use crate::component::links::{Relation, TxLinksMap};
 use crate::component::sort_key::{AncestorsScoreSortKey, EvictKey};
 use crate::error::Reject;
+use crate::persisted::{JournalOp, PersistedEntry};
+use crate::policy::{sender_lock_hashes, EvictionClass};
 use crate::TxEntry;
 use ckb_logger::{debug, error, trace};
 use ckb_types::core::error::OutPointError;
@@ -18,7 +20,7 @@ use ckb_types::{
     packed::{Byte32, CellOutput, ProposalShortId},
 };
 use multi_index_map::MultiIndexMap;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 type ConflictEntry = (TxEntry, Reject);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -73,6 +75,12 @@ pub struct PoolMap {
     pub(crate) pending_count: usize,
     pub(crate) gap_count: usize,
     pub(crate) proposed_count: usize,
+    /// The in-pool transactions count of each lock script spent by them
+    pub(crate) sender_locks: HashMap<Byte32, usize>,
+    // sum of the virtual sizes of the `Kept` txs
+    pub(crate) kept_tx_size: usize,
+    /// The changes not appended to the journal yet, `None` if the journal is disabled
+    pub(crate) journal: Option<Vec<JournalOp>>,
 }
 
 impl PoolMap {
@@ -87,6 +95,9 @@ impl PoolMap {
             pending_count: 0,
             gap_count: 0,
             proposed_count: 0,
+            sender_locks: HashMap::new(),
+            kept_tx_size: 0,
+            journal: None,
         }
     }
 
@@ -214,7 +225,17 @@ impl PoolMap {
         self.insert_entry(&entry, status);
         self.record_entry_descendants(&entry);
         self.track_entry_statics(None, Some(status));
+        self.track_entry_senders(&entry, EntryOp::Add);
+        self.track_entry_kept(&entry, EntryOp::Add);
         self.update_stat_for_add_tx(entry.size, entry.cycles);
+        self.record_journal(|| {
+            JournalOp::Add(PersistedEntry {
+                status,
+                timestamp: entry.timestamp,
+                cycles: entry.cycles,
+                tx: entry.transaction().clone(),
+            })
+        });
         Ok((true, evicts))
     }
 
@@ -228,6 +249,7 @@ impl PoolMap {
             })
             .expect("unconsistent pool");
         self.track_entry_statics(old_status, Some(status));
+        self.record_journal(|| JournalOp::SetStatus(short_id.clone(), status));
     }
 
     pub(crate) fn remove_entry(&mut self, id: &ProposalShortId) -> Option<TxEntry> {
@@ -242,7 +264,10 @@ impl PoolMap {
             self.remove_entry_edges(&entry.inner);
             self.remove_entry_links(id);
             self.track_entry_statics(Some(entry.status), None);
+            self.track_entry_senders(&entry.inner, EntryOp::Remove);
+            self.track_entry_kept(&entry.inner, EntryOp::Remove);
             self.update_stat_for_remove_tx(entry.inner.size, entry.inner.cycles);
+            self.record_journal(|| JournalOp::Remove(id.clone()));
             entry.inner
         })
     }
@@ -362,26 +387,72 @@ impl PoolMap {
         limit: usize,
         exclusion: &HashSet<ProposalShortId>,
     ) -> HashSet<ProposalShortId> {
-        self.score_sorted_iter_by_status(Status::Pending)
-            .filter_map(|entry| {
-                let id = entry.proposal_short_id();
-                (!exclusion.contains(&id)).then_some(id)
-            })
-            .take(limit)
-            .collect()
+        let mut proposals = HashSet::with_capacity(limit);
+        for entry in self.score_sorted_iter_by_status(Status::Pending) {
+            if proposals.len() >= limit {
+                break;
+            }
+            let id = entry.proposal_short_id();
+            if exclusion.contains(&id) || proposals.contains(&id) {
+                continue;
+            }
+            // The score of a child counts in its ancestors, propose the pending ancestors
+            // together, otherwise a child paying for a low fee parent is proposed alone.
+            let ancestors: Vec<_> = self
+                .calc_ancestors(&id)
+                .into_iter()
+                .filter(|ancestor| {
+                    !exclusion.contains(ancestor)
+                        && !proposals.contains(ancestor)
+                        && self
+                            .get_by_id(ancestor)
+                            .map_or(false, |e| e.status == Status::Pending)
+                })
+                .collect();
+            if proposals.len() + ancestors.len() + 1 > limit {
+                continue;
+            }
+            proposals.extend(ancestors);
+            proposals.insert(id);
+        }
+        proposals
     }
 
     pub(crate) fn iter(&self) -> impl Iterator<Item = &PoolEntry> {
         self.entries.iter().map(|(_, entry)| entry)
     }
 
-    pub(crate) fn next_evict_entry(&self, status: Status) -> Option<ProposalShortId> {
+    /// Returns the entry of the status and eviction class with the lowest evict key,
+    /// skipping the ones that the kept entries depend on.
+    pub(crate) fn next_evict_entry(
+        &self,
+        status: Status,
+        class: EvictionClass,
+    ) -> Option<ProposalShortId> {
         self.entries
             .iter_by_evict_key()
-            .find(move |entry| entry.status == status)
+            .find(move |entry| {
+                entry.status == status
+                    && entry.inner.eviction_class == class
+                    && !self.has_kept_descendants(entry)
+            })
             .map(|entry| entry.id.clone())
     }
 
+    fn has_kept_descendants(&self, entry: &PoolEntry) -> bool {
+        // descendants_count counts the entry itself
+        entry.inner.descendants_count > 1
+            && self.calc_descendants(&entry.id).iter().any(|id| {
+                self.get(id)
+                    .map_or(false, |e| e.eviction_class == EvictionClass::Kept)
+            })
+    }
+
+    /// Returns the count of the in-pool transactions which spend the cells of the lock script.
+    pub(crate) fn sender_lock_count(&self, lock_hash: &Byte32) -> usize {
+        self.sender_locks.get(lock_hash).copied().unwrap_or(0)
+    }
+
     pub(crate) fn clear(&mut self) {
         self.entries = MultiIndexPoolEntryMap::default();
         self.edges.clear();
@@ -391,6 +462,15 @@ impl PoolMap {
         self.pending_count = 0;
         self.gap_count = 0;
         self.proposed_count = 0;
+        self.sender_locks.clear();
+        self.kept_tx_size = 0;
+        self.record_journal(|| JournalOp::Clear);
+    }
+
+    fn record_journal<F: FnOnce() -> JournalOp>(&mut self, op: F) {
+        if let Some(ops) = self.journal.as_mut() {
+            ops.push(op());
+        }
     }
 
     pub(crate) fn score_sorted_iter_by_status(
@@ -663,6 +743,32 @@ impl PoolMap {
         });
     }
 
+    fn track_entry_senders(&mut self, entry: &TxEntry, op: EntryOp) {
+        for lock_hash in sender_lock_hashes(entry) {
+            match op {
+                EntryOp::Add => *self.sender_locks.entry(lock_hash).or_default() += 1,
+                EntryOp::Remove => {
+                    if let Some(count) = self.sender_locks.get_mut(&lock_hash) {
+                        *count -= 1;
+                        if *count == 0 {
+                            self.sender_locks.remove(&lock_hash);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn track_entry_kept(&mut self, entry: &TxEntry, op: EntryOp) {
+        if entry.eviction_class != EvictionClass::Kept {
+            return;
+        }
+        match op {
+            EntryOp::Add => self.kept_tx_size += entry.size,
+            EntryOp::Remove => self.kept_tx_size = self.kept_tx_size.saturating_sub(entry.size),
+        }
+    }
+
     fn track_entry_statics(&mut self, remove: Option<Status>, add: Option<Status>) {
         match remove {
             Some(Status::Pending) => self.pending_count -= 1,
@@ -3,7 +3,10 @@ mod entry;
 mod estimate;
 mod links;
 mod orphan;
+mod package;
 mod pending;
+mod persisted;
+mod policy;
 mod proposed;
 mod recent_reject;
 mod score_key;
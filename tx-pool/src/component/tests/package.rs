@@ -0,0 +1,191 @@
+use crate::component::entry::TxEntry;
+use crate::component::tests::util::{build_tx, build_tx_with_dep, MOCK_CYCLES};
+use crate::error::Reject;
+use crate::pool::TxPool;
+use crate::process::{TxStatus, _submit_package};
+use crate::util::check_package_topology;
+use ckb_app_config::TxPoolConfig;
+use ckb_chain_spec::consensus::ConsensusBuilder;
+use ckb_db::RocksDB;
+use ckb_db_schema::COLUMNS;
+use ckb_proposal_table::ProposalView;
+use ckb_snapshot::Snapshot;
+use ckb_store::ChainDB;
+use ckb_types::{
+    core::{tx_pool::MAX_PACKAGE_TXS, Capacity, TransactionView},
+    h256,
+    packed::Byte32,
+    prelude::*,
+    U256,
+};
+use std::sync::Arc;
+use tempfile::TempDir;
+
+fn build_chain_txs(len: usize) -> Vec<TransactionView> {
+    let mut txs: Vec<TransactionView> = Vec::with_capacity(len);
+    for _ in 0..len {
+        let tx = match txs.last() {
+            Some(parent) => build_tx(vec![(&parent.hash(), 0)], 1),
+            None => build_tx(vec![(&Byte32::zero(), 0)], 1),
+        };
+        txs.push(tx);
+    }
+    txs
+}
+
+fn assert_malformed(txs: &[TransactionView]) {
+    let ret = check_package_topology(txs);
+    assert!(
+        matches!(ret, Err(Reject::Malformed(_, _))),
+        "unexpected {ret:?}"
+    );
+}
+
+#[test]
+fn test_package_topology() {
+    let parent1 = build_tx(vec![(&Byte32::zero(), 0)], 2);
+    let parent2 = build_tx(vec![(&Byte32::zero(), 1)], 1);
+    // the child also spends a cell out of the package
+    let child = build_tx(
+        vec![
+            (&parent1.hash(), 0),
+            (&parent2.hash(), 0),
+            (&h256!("0x1").pack(), 0),
+        ],
+        1,
+    );
+    assert!(check_package_topology(&[parent1.clone(), parent2, child]).is_ok());
+
+    let child2 = build_tx(vec![(&parent1.hash(), 1)], 1);
+    assert!(check_package_topology(&[parent1.clone()]).is_ok());
+    assert!(check_package_topology(&[parent1, child2]).is_ok());
+    assert!(check_package_topology(&build_chain_txs(MAX_PACKAGE_TXS)).is_ok());
+}
+
+#[test]
+fn test_package_topology_size() {
+    assert_malformed(&[]);
+    assert_malformed(&build_chain_txs(MAX_PACKAGE_TXS + 1));
+}
+
+#[test]
+fn test_package_topology_duplicated() {
+    let parent = build_tx(vec![(&Byte32::zero(), 0)], 1);
+    let child = build_tx(vec![(&parent.hash(), 0)], 1);
+    assert_malformed(&[parent.clone(), parent.clone(), child.clone()]);
+    assert_malformed(&[parent, child.clone(), child]);
+}
+
+#[test]
+fn test_package_topology_parent_after_child() {
+    let txs = build_chain_txs(3);
+    assert_malformed(&[txs[1].clone(), txs[0].clone(), txs[2].clone()]);
+    assert_malformed(&[txs[0].clone(), txs[2].clone(), txs[1].clone()]);
+}
+
+#[test]
+fn test_package_topology_not_spent() {
+    let parent = build_tx(vec![(&Byte32::zero(), 0)], 1);
+    let unrelated = build_tx(vec![(&Byte32::zero(), 1)], 1);
+    let child = build_tx(vec![(&parent.hash(), 0)], 1);
+    assert_malformed(&[parent.clone(), unrelated.clone()]);
+    assert_malformed(&[unrelated, parent, child]);
+}
+
+fn new_tx_pool(max_ancestors_count: usize) -> (TxPool, Arc<Snapshot>, TempDir) {
+    let tmp_dir = TempDir::new().unwrap();
+    let store = ChainDB::new(RocksDB::open_in(&tmp_dir, COLUMNS), Default::default());
+    let consensus = ConsensusBuilder::default().build();
+    let snapshot = Arc::new(Snapshot::new(
+        consensus.genesis_block().header(),
+        U256::zero(),
+        consensus.genesis_epoch_ext().to_owned(),
+        store.get_snapshot(),
+        ProposalView::default(),
+        Arc::new(consensus),
+    ));
+    let config = TxPoolConfig {
+        max_ancestors_count,
+        ..Default::default()
+    };
+    (
+        TxPool::new(config, Arc::clone(&snapshot)),
+        snapshot,
+        tmp_dir,
+    )
+}
+
+fn fresh_entries(txs: &[&TransactionView]) -> Vec<(TxEntry, TxStatus)> {
+    txs.iter()
+        .map(|tx| {
+            let entry = TxEntry::dummy_resolve((*tx).clone(), MOCK_CYCLES, Capacity::zero(), 100);
+            (entry, TxStatus::Fresh)
+        })
+        .collect()
+}
+
+// The parent spends a cell referenced as the dep by two pool txs, with the max ancestors count 2
+// one of them is evicted to add the parent, then the child exceeds the max ancestors count.
+fn setup_evicting_package() -> (
+    TxPool,
+    Arc<Snapshot>,
+    TempDir,
+    Vec<TransactionView>,
+    Vec<TransactionView>,
+) {
+    let (mut tx_pool, snapshot, tmp_dir) = new_tx_pool(2);
+    let dep_hash = h256!("0x1").pack();
+    let deps: Vec<_> = (0..2)
+        .map(|i| build_tx_with_dep(vec![(&Byte32::zero(), i)], vec![(&dep_hash, 0)], 1))
+        .collect();
+    for (i, tx) in deps.iter().enumerate() {
+        let fee = Capacity::shannons(100 * (i as u64 + 1));
+        let entry = TxEntry::dummy_resolve(tx.clone(), MOCK_CYCLES, fee, 100);
+        assert!(tx_pool.add_pending(entry).unwrap().0);
+    }
+
+    let parent = build_tx(vec![(&dep_hash, 0)], 1);
+    let child = build_tx(vec![(&parent.hash(), 0)], 1);
+    (tx_pool, snapshot, tmp_dir, deps, vec![parent, child])
+}
+
+#[test]
+fn test_submit_package_evicts() {
+    let (mut tx_pool, snapshot, _tmp_dir, deps, txs) = setup_evicting_package();
+    let entries = fresh_entries(&[&txs[0]]);
+
+    let (ret, rejected) = _submit_package(&mut tx_pool, &snapshot, &snapshot.tip_hash(), &entries);
+    let added = ret.unwrap();
+    assert_eq!(added.len(), 1);
+    assert_eq!(added[0].1, TxStatus::Fresh);
+    assert!(tx_pool.contains_proposal_id(&txs[0].proposal_short_id()));
+
+    // the evicted one is notified
+    assert_eq!(rejected.len(), 1);
+    let (evicted, reject) = &rejected[0];
+    assert!(matches!(reject, Reject::Invalidated(_)));
+    assert!(!tx_pool.contains_proposal_id(&evicted.proposal_short_id()));
+    let kept = deps
+        .iter()
+        .filter(|tx| tx_pool.contains_proposal_id(&tx.proposal_short_id()))
+        .count();
+    assert_eq!(kept, 1);
+}
+
+#[test]
+fn test_submit_package_rollback_restores_evicted() {
+    let (mut tx_pool, snapshot, _tmp_dir, deps, txs) = setup_evicting_package();
+    let entries = fresh_entries(&[&txs[0], &txs[1]]);
+
+    let (ret, rejected) = _submit_package(&mut tx_pool, &snapshot, &snapshot.tip_hash(), &entries);
+    assert!(matches!(ret, Err(Reject::ExceededMaximumAncestorsCount)));
+    assert!(rejected.is_empty());
+
+    for tx in &txs {
+        assert!(!tx_pool.contains_proposal_id(&tx.proposal_short_id()));
+    }
+    for tx in &deps {
+        assert!(tx_pool.contains_proposal_id(&tx.proposal_short_id()));
+    }
+    assert_eq!(tx_pool.pool_map.size(), deps.len());
+}
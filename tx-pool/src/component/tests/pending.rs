@@ -6,6 +6,7 @@ use crate::component::{
     entry::TxEntry,
     pool_map::{PoolMap, Status},
 };
+use crate::policy::EvictionClass;
 use ckb_types::core::Capacity;
 use ckb_types::packed::OutPoint;
 use ckb_types::{h256, packed::Byte32, prelude::*};
@@ -256,6 +257,48 @@ fn test_get_proposals_with_high_score() {
     assert_eq!(ret, HashSet::from_iter(vec![id1, id3]));
 }
 
+#[test]
+fn test_get_proposals_with_ancestors() {
+    let mut pool = PoolMap::new(1000);
+    let parent = build_tx(vec![(&Byte32::zero(), 1)], 1);
+    let child = build_tx(vec![(&parent.hash(), 0)], 1);
+    let other = build_tx(vec![(&h256!("0x1").pack(), 1)], 1);
+
+    // the child pays for the low fee parent
+    let parent_entry = TxEntry::dummy_resolve(parent.clone(), 2, Capacity::shannons(10), 2);
+    let child_entry = TxEntry::dummy_resolve(child.clone(), 2, Capacity::shannons(500), 2);
+    let other_entry = TxEntry::dummy_resolve(other.clone(), 2, Capacity::shannons(100), 2);
+    assert!(pool.add_entry(parent_entry, Status::Pending).is_ok());
+    assert!(pool.add_entry(child_entry, Status::Pending).is_ok());
+    assert!(pool.add_entry(other_entry, Status::Pending).is_ok());
+
+    let parent_id = parent.proposal_short_id();
+    let child_id = child.proposal_short_id();
+    let other_id = other.proposal_short_id();
+
+    // the child is never proposed without its parent
+    let ret = pool.get_proposals(1, &HashSet::new());
+    assert_eq!(ret, HashSet::from_iter(vec![other_id.clone()]));
+
+    let ret = pool.get_proposals(2, &HashSet::new());
+    assert_eq!(
+        ret,
+        HashSet::from_iter(vec![parent_id.clone(), child_id.clone()])
+    );
+
+    let ret = pool.get_proposals(3, &HashSet::new());
+    assert_eq!(
+        ret,
+        HashSet::from_iter(vec![parent_id.clone(), child_id.clone(), other_id.clone()])
+    );
+
+    // the excluded parent is already proposed
+    let mut exclusion = HashSet::new();
+    exclusion.insert(parent_id);
+    let ret = pool.get_proposals(2, &exclusion);
+    assert_eq!(ret, HashSet::from_iter(vec![child_id, other_id]));
+}
+
 #[test]
 fn test_edges() {
     let tx1 = build_tx(vec![(&Byte32::zero(), 1), (&h256!("0x1").pack(), 1)], 1);
@@ -297,19 +340,27 @@ fn test_pool_evict() {
     assert!(pool.add_entry(entry2, Status::Pending).is_ok());
     assert!(pool.add_entry(entry3, Status::Pending).is_ok());
 
-    let e1 = pool.next_evict_entry(Status::Pending).unwrap();
+    let e1 = pool
+        .next_evict_entry(Status::Pending, EvictionClass::Normal)
+        .unwrap();
     assert_eq!(e1, tx1.proposal_short_id());
     pool.remove_entry(&e1);
 
-    let e2 = pool.next_evict_entry(Status::Pending).unwrap();
+    let e2 = pool
+        .next_evict_entry(Status::Pending, EvictionClass::Normal)
+        .unwrap();
     assert_eq!(e2, tx2.proposal_short_id());
     pool.remove_entry(&e2);
 
-    let e3 = pool.next_evict_entry(Status::Pending).unwrap();
+    let e3 = pool
+        .next_evict_entry(Status::Pending, EvictionClass::Normal)
+        .unwrap();
     assert_eq!(e3, tx3.proposal_short_id());
     pool.remove_entry(&e3);
 
-    assert!(pool.next_evict_entry(Status::Pending).is_none());
+    assert!(pool
+        .next_evict_entry(Status::Pending, EvictionClass::Normal)
+        .is_none());
 }
 
 #[test]
@@ -335,19 +386,27 @@ fn test_pool_min_weight_evict() {
     assert!(pool.add_entry(entry2, Status::Pending).is_ok());
     assert!(pool.add_entry(entry3, Status::Pending).is_ok());
 
-    let e1 = pool.next_evict_entry(Status::Pending).unwrap();
+    let e1 = pool
+        .next_evict_entry(Status::Pending, EvictionClass::Normal)
+        .unwrap();
     assert_eq!(e1, tx3.proposal_short_id());
     pool.remove_entry(&e1);
 
-    let e2 = pool.next_evict_entry(Status::Pending).unwrap();
+    let e2 = pool
+        .next_evict_entry(Status::Pending, EvictionClass::Normal)
+        .unwrap();
     assert_eq!(e2, tx2.proposal_short_id());
     pool.remove_entry(&e2);
 
-    let e3 = pool.next_evict_entry(Status::Pending).unwrap();
+    let e3 = pool
+        .next_evict_entry(Status::Pending, EvictionClass::Normal)
+        .unwrap();
     assert_eq!(e3, tx1.proposal_short_id());
     pool.remove_entry(&e3);
 
-    assert!(pool.next_evict_entry(Status::Pending).is_none());
+    assert!(pool
+        .next_evict_entry(Status::Pending, EvictionClass::Normal)
+        .is_none());
 }
 
 #[test]
@@ -373,19 +432,27 @@ fn test_pool_max_size_evict() {
     assert!(pool.add_entry(entry2, Status::Pending).is_ok());
     assert!(pool.add_entry(entry3, Status::Pending).is_ok());
 
-    let e1 = pool.next_evict_entry(Status::Pending).unwrap();
+    let e1 = pool
+        .next_evict_entry(Status::Pending, EvictionClass::Normal)
+        .unwrap();
     assert_eq!(e1, tx1.proposal_short_id());
     pool.remove_entry(&e1);
 
-    let e2 = pool.next_evict_entry(Status::Pending).unwrap();
+    let e2 = pool
+        .next_evict_entry(Status::Pending, EvictionClass::Normal)
+        .unwrap();
     assert_eq!(e2, tx2.proposal_short_id());
     pool.remove_entry(&e2);
 
-    let e3 = pool.next_evict_entry(Status::Pending).unwrap();
+    let e3 = pool
+        .next_evict_entry(Status::Pending, EvictionClass::Normal)
+        .unwrap();
     assert_eq!(e3, tx3.proposal_short_id());
     pool.remove_entry(&e3);
 
-    assert!(pool.next_evict_entry(Status::Pending).is_none());
+    assert!(pool
+        .next_evict_entry(Status::Pending, EvictionClass::Normal)
+        .is_none());
 }
 
 #[test]
@@ -404,17 +471,25 @@ fn test_pool_min_descendants_evict() {
     assert!(pool.add_entry(entry2, Status::Pending).is_ok());
     assert!(pool.add_entry(entry3, Status::Pending).is_ok());
 
-    let e1 = pool.next_evict_entry(Status::Pending).unwrap();
+    let e1 = pool
+        .next_evict_entry(Status::Pending, EvictionClass::Normal)
+        .unwrap();
     assert_eq!(e1, tx3.proposal_short_id());
     pool.remove_entry(&e1);
 
-    let e2 = pool.next_evict_entry(Status::Pending).unwrap();
+    let e2 = pool
+        .next_evict_entry(Status::Pending, EvictionClass::Normal)
+        .unwrap();
     assert_eq!(e2, tx2.proposal_short_id());
     pool.remove_entry(&e2);
 
-    let e3 = pool.next_evict_entry(Status::Pending).unwrap();
+    let e3 = pool
+        .next_evict_entry(Status::Pending, EvictionClass::Normal)
+        .unwrap();
     assert_eq!(e3, tx1.proposal_short_id());
     pool.remove_entry(&e3);
 
-    assert!(pool.next_evict_entry(Status::Pending).is_none());
+    assert!(pool
+        .next_evict_entry(Status::Pending, EvictionClass::Normal)
+        .is_none());
 }
@@ -0,0 +1,134 @@
+use crate::component::entry::TxEntry;
+use crate::component::pool_map::{PoolMap, Status};
+use crate::component::tests::util::{
+    build_tx, DEFAULT_MAX_ANCESTORS_COUNT, MOCK_CYCLES, MOCK_FEE, MOCK_SIZE,
+};
+use crate::persisted::{decode_ops, replay_ops, JournalOp, PersistedEntry};
+use ckb_types::{h256, prelude::*};
+use ckb_util::LinkedHashMap;
+
+fn persisted_entry(seed: u32, status: Status, timestamp: u64) -> PersistedEntry {
+    let tx_hash = h256!("0x1").pack();
+    PersistedEntry {
+        status,
+        timestamp,
+        cycles: MOCK_CYCLES,
+        tx: build_tx(vec![(&tx_hash, seed)], 1),
+    }
+}
+
+fn encode_ops(ops: &[JournalOp]) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    for op in ops {
+        op.encode(&mut buffer);
+    }
+    buffer
+}
+
+#[test]
+fn test_journal_ops_roundtrip() {
+    let entry = persisted_entry(0, Status::Proposed, 42);
+    let id = entry.tx.proposal_short_id();
+    let ops = vec![
+        JournalOp::Add(entry),
+        JournalOp::SetStatus(id.clone(), Status::Gap),
+        JournalOp::Remove(id),
+        JournalOp::Clear,
+    ];
+
+    assert_eq!(decode_ops(&encode_ops(&ops)), (ops, true));
+}
+
+#[test]
+fn test_journal_broken_tail() {
+    let ops = vec![
+        JournalOp::Add(persisted_entry(0, Status::Pending, 1)),
+        JournalOp::Add(persisted_entry(1, Status::Pending, 2)),
+    ];
+    let buffer = encode_ops(&ops);
+
+    // an interrupted write leaves a partial op at the tail
+    let (decoded, complete) = decode_ops(&buffer[..buffer.len() - 1]);
+    assert!(!complete);
+    assert_eq!(decoded, ops[..1]);
+
+    // an unknown op tag
+    let mut buffer = encode_ops(&ops[..1]);
+    buffer.push(u8::MAX);
+    let (decoded, complete) = decode_ops(&buffer);
+    assert!(!complete);
+    assert_eq!(decoded, ops[..1]);
+}
+
+#[test]
+fn test_replay_journal_ops() {
+    let entry1 = persisted_entry(0, Status::Pending, 1);
+    let entry2 = persisted_entry(1, Status::Pending, 2);
+    let entry3 = persisted_entry(2, Status::Gap, 3);
+    let id1 = entry1.tx.proposal_short_id();
+    let id2 = entry2.tx.proposal_short_id();
+
+    let mut entries = LinkedHashMap::default();
+    replay_ops(
+        &mut entries,
+        vec![
+            JournalOp::Add(entry1.clone()),
+            JournalOp::Add(entry2),
+            JournalOp::Clear,
+            JournalOp::Add(entry1),
+            JournalOp::Add(entry3.clone()),
+            JournalOp::SetStatus(id1.clone(), Status::Proposed),
+            // the ops of the removed entries are ignored
+            JournalOp::SetStatus(id2.clone(), Status::Proposed),
+            JournalOp::Remove(id2),
+        ],
+    );
+
+    let entries: Vec<_> = entries.into_iter().map(|(_, entry)| entry).collect();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].tx.proposal_short_id(), id1);
+    assert_eq!(entries[0].status, Status::Proposed);
+    assert_eq!(entries[0].timestamp, 1);
+    assert_eq!(entries[1], entry3);
+}
+
+#[test]
+fn test_pool_map_records_journal() {
+    let tx_hash = h256!("0x1").pack();
+    let tx1 = build_tx(vec![(&tx_hash, 0)], 1);
+    let tx2 = build_tx(vec![(&tx_hash, 1)], 1);
+    let entry1 = TxEntry::dummy_resolve(tx1.clone(), MOCK_CYCLES, MOCK_FEE, MOCK_SIZE);
+    let entry2 = TxEntry::dummy_resolve(tx2, MOCK_CYCLES, MOCK_FEE, MOCK_SIZE);
+    let id1 = tx1.proposal_short_id();
+
+    // nothing is recorded when the journal is disabled
+    let mut pool = PoolMap::new(DEFAULT_MAX_ANCESTORS_COUNT);
+    assert!(pool.add_entry(entry1.clone(), Status::Pending).is_ok());
+    assert!(pool.journal.is_none());
+
+    let mut pool = PoolMap::new(DEFAULT_MAX_ANCESTORS_COUNT);
+    pool.journal = Some(Vec::new());
+    assert!(pool.add_entry(entry1.clone(), Status::Pending).is_ok());
+    // the duplicated entry is not recorded
+    assert!(pool.add_entry(entry1.clone(), Status::Pending).is_ok());
+    pool.set_entry(&id1, Status::Proposed);
+    assert!(pool.add_entry(entry2, Status::Pending).is_ok());
+    pool.remove_entry(&id1);
+    pool.clear();
+
+    let ops = pool.journal.take().unwrap();
+    assert_eq!(ops.len(), 5);
+    assert_eq!(
+        ops[0],
+        JournalOp::Add(PersistedEntry {
+            status: Status::Pending,
+            timestamp: entry1.timestamp,
+            cycles: MOCK_CYCLES,
+            tx: tx1,
+        })
+    );
+    assert_eq!(ops[1], JournalOp::SetStatus(id1.clone(), Status::Proposed));
+    assert!(matches!(ops[2], JournalOp::Add(_)));
+    assert_eq!(ops[3], JournalOp::Remove(id1));
+    assert_eq!(ops[4], JournalOp::Clear);
+}
@@ -0,0 +1,213 @@
+use crate::component::entry::TxEntry;
+use crate::component::pool_map::{PoolMap, Status};
+use crate::component::tests::util::{
+    DEFAULT_MAX_ANCESTORS_COUNT, MOCK_CYCLES, MOCK_FEE, MOCK_SIZE,
+};
+use crate::error::Reject;
+use crate::policy::{sender_lock_hashes, ConfigPolicy, EvictionClass, PeerQuotas, TxPoolPolicy};
+use ckb_app_config::{PolicyScript, TxPoolPolicyConfig};
+use ckb_jsonrpc_types::JsonBytes;
+use ckb_network::PeerIndex;
+use ckb_types::{
+    bytes::Bytes,
+    core::{
+        cell::{CellMetaBuilder, ResolvedTransaction},
+        Capacity, ScriptHashType, TransactionBuilder,
+    },
+    h256,
+    packed::{Byte32, CellInput, CellOutput, OutPoint, Script},
+    prelude::*,
+    H256,
+};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const PARTNER_CODE_HASH: H256 =
+    h256!("0x1111111111111111111111111111111111111111111111111111111111111111");
+const PRIORITY_CODE_HASH: H256 =
+    h256!("0x2222222222222222222222222222222222222222222222222222222222222222");
+
+fn script(code_hash: &H256, args: &[u8]) -> Script {
+    Script::new_builder()
+        .code_hash(code_hash.pack())
+        .hash_type(ScriptHashType::Type.into())
+        .args(Bytes::from(args.to_vec()).pack())
+        .build()
+}
+
+fn output(lock: Script) -> CellOutput {
+    CellOutput::new_builder()
+        .capacity(Capacity::bytes(100).unwrap().pack())
+        .lock(lock)
+        .build()
+}
+
+// builds an entry spending the cells `(tx_hash, index, lock)`, with an output of the lock
+fn build_entry(inputs: Vec<(&Byte32, u32, Script)>, lock: Script) -> TxEntry {
+    let tx = TransactionBuilder::default()
+        .inputs(inputs.iter().map(|(tx_hash, index, _)| {
+            CellInput::new(OutPoint::new((*tx_hash).clone(), *index), 0)
+        }))
+        .output(output(lock))
+        .output_data(Bytes::new().pack())
+        .build();
+    let resolved_inputs = inputs
+        .into_iter()
+        .map(|(tx_hash, index, lock)| {
+            CellMetaBuilder::from_cell_output(output(lock), Bytes::new())
+                .out_point(OutPoint::new(tx_hash.clone(), index))
+                .build()
+        })
+        .collect();
+    let rtx = ResolvedTransaction {
+        transaction: tx,
+        resolved_cell_deps: vec![],
+        resolved_inputs,
+        resolved_dep_groups: vec![],
+    };
+    TxEntry::new(Arc::new(rtx), MOCK_CYCLES, MOCK_FEE, MOCK_SIZE)
+}
+
+fn policy_config() -> TxPoolPolicyConfig {
+    TxPoolPolicyConfig {
+        keep_scripts: vec![PolicyScript {
+            code_hash: PARTNER_CODE_HASH,
+            hash_type: ckb_jsonrpc_types::ScriptHashType::Type,
+            args: Some(JsonBytes::from_vec(vec![1])),
+        }],
+        priority_scripts: vec![PolicyScript {
+            code_hash: PRIORITY_CODE_HASH,
+            hash_type: ckb_jsonrpc_types::ScriptHashType::Type,
+            args: None,
+        }],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_eviction_class_by_scripts() {
+    let policy = ConfigPolicy::new(&policy_config());
+    let other = script(&h256!("0x3"), &[]);
+
+    // the partner script matches the input lock with the same args only
+    let kept = build_entry(
+        vec![(&Byte32::zero(), 0, script(&PARTNER_CODE_HASH, &[1]))],
+        other.clone(),
+    );
+    assert_eq!(policy.eviction_class(&kept), EvictionClass::Kept);
+    let normal = build_entry(
+        vec![(&Byte32::zero(), 0, script(&PARTNER_CODE_HASH, &[2]))],
+        other.clone(),
+    );
+    assert_eq!(policy.eviction_class(&normal), EvictionClass::Normal);
+    // paying an output to the partner script doesn't keep the tx
+    let normal = build_entry(
+        vec![(&Byte32::zero(), 0, other.clone())],
+        script(&PARTNER_CODE_HASH, &[1]),
+    );
+    assert_eq!(policy.eviction_class(&normal), EvictionClass::Normal);
+
+    // the priority script matches the input or output lock with any args
+    let priority = build_entry(
+        vec![(&Byte32::zero(), 0, script(&PRIORITY_CODE_HASH, &[9]))],
+        other.clone(),
+    );
+    assert_eq!(policy.eviction_class(&priority), EvictionClass::Priority);
+    let priority = build_entry(vec![], script(&PRIORITY_CODE_HASH, &[9]));
+    assert_eq!(policy.eviction_class(&priority), EvictionClass::Priority);
+
+    let normal = build_entry(vec![(&Byte32::zero(), 1, other.clone())], other);
+    assert_eq!(policy.eviction_class(&normal), EvictionClass::Normal);
+}
+
+#[test]
+fn test_evict_by_class() {
+    let mut pool = PoolMap::new(DEFAULT_MAX_ANCESTORS_COUNT);
+    let lock = script(&h256!("0x3"), &[]);
+
+    let mut priority = build_entry(vec![(&Byte32::zero(), 0, lock.clone())], lock.clone());
+    priority.eviction_class = EvictionClass::Priority;
+    let normal = build_entry(vec![(&Byte32::zero(), 1, lock.clone())], lock.clone());
+    let normal_id = normal.proposal_short_id();
+    assert!(pool.add_entry(priority, Status::Pending).is_ok());
+    assert!(pool.add_entry(normal.clone(), Status::Pending).is_ok());
+
+    // a normal parent is not evicted when a kept child depends on it
+    let mut kept = build_entry(vec![(&normal.transaction().hash(), 0, lock.clone())], lock);
+    kept.eviction_class = EvictionClass::Kept;
+    kept.size = 100;
+    assert!(pool.add_entry(kept.clone(), Status::Pending).is_ok());
+    assert_eq!(pool.kept_tx_size, 100);
+
+    assert_eq!(
+        pool.next_evict_entry(Status::Pending, EvictionClass::Normal),
+        None
+    );
+    pool.remove_entry(&kept.proposal_short_id());
+    assert_eq!(pool.kept_tx_size, 0);
+    assert_eq!(
+        pool.next_evict_entry(Status::Pending, EvictionClass::Normal),
+        Some(normal_id.clone())
+    );
+    pool.remove_entry(&normal_id);
+    assert!(pool
+        .next_evict_entry(Status::Pending, EvictionClass::Priority)
+        .is_some());
+    assert_eq!(
+        pool.next_evict_entry(Status::Proposed, EvictionClass::Priority),
+        None
+    );
+    assert_eq!(
+        pool.iter()
+            .map(|entry| entry.inner.eviction_class)
+            .collect::<Vec<_>>(),
+        vec![EvictionClass::Priority]
+    );
+}
+
+#[test]
+fn test_sender_lock_count() {
+    let mut pool = PoolMap::new(DEFAULT_MAX_ANCESTORS_COUNT);
+    let sender = script(&h256!("0x3"), &[1]);
+    let receiver = script(&h256!("0x3"), &[2]);
+    let sender_hash = sender.calc_script_hash();
+
+    // a tx spending two cells of the same lock is counted once
+    let entry1 = build_entry(
+        vec![
+            (&Byte32::zero(), 0, sender.clone()),
+            (&Byte32::zero(), 1, sender.clone()),
+        ],
+        receiver.clone(),
+    );
+    assert_eq!(sender_lock_hashes(&entry1).len(), 1);
+    let entry2 = build_entry(vec![(&Byte32::zero(), 2, sender)], receiver.clone());
+
+    assert!(pool.add_entry(entry1.clone(), Status::Pending).is_ok());
+    assert!(pool.add_entry(entry2, Status::Pending).is_ok());
+    assert_eq!(pool.sender_lock_count(&sender_hash), 2);
+    assert_eq!(pool.sender_lock_count(&receiver.calc_script_hash()), 0);
+
+    pool.remove_entry(&entry1.proposal_short_id());
+    assert_eq!(pool.sender_lock_count(&sender_hash), 1);
+    pool.clear();
+    assert_eq!(pool.sender_lock_count(&sender_hash), 0);
+}
+
+#[test]
+fn test_peer_quotas() {
+    let mut quotas = PeerQuotas::new(2, Duration::from_secs(60));
+    let now = Instant::now();
+    let peer = PeerIndex::new(1);
+
+    assert!(quotas.check(peer, now).is_ok());
+    assert!(quotas.check(peer, now).is_ok());
+    assert!(matches!(quotas.check(peer, now), Err(Reject::Policy(_))));
+    // the quota of the other peers is not affected
+    assert!(quotas.check(PeerIndex::new(2), now).is_ok());
+
+    // the quota is reset in the next window, and the idle peers are pruned
+    let later = now + Duration::from_secs(60);
+    assert!(quotas.check(peer, later).is_ok());
+    assert_eq!(quotas.peers_len(), 1);
+}
@@ -6,6 +6,7 @@ mod callback;
 mod component;
 pub mod error;
 mod persisted;
+pub mod policy;
 pub mod pool;
 mod pool_cell;
 mod process;
@@ -15,6 +16,7 @@ mod verify_mgr;
 
 pub use ckb_jsonrpc_types::BlockTemplate;
 pub use component::entry::TxEntry;
+pub use policy::{ConfigPolicy, EvictionClass, TxPoolPolicy};
 pub use pool::TxPool;
 pub use process::PlugTarget;
 pub use service::{TxPoolController, TxPoolServiceBuilder};
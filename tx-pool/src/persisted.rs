@@ -1,91 +1,365 @@
+//! Persists the tx-pool entries with their pool states.
+//!
+//! The entries are saved into the persisted data file when the tx-pool is shut down, and the
+//! pool changes are appended to a journal periodically, so the pool can be restored after a crash
+//! by replaying the journal over the persisted data.
+use crate::component::pool_map::Status;
 use crate::TxPool;
 use ckb_error::{AnyError, OtherError};
+use ckb_logger::warn;
 use ckb_types::{
-    core::TransactionView,
-    packed::{TransactionVec, TransactionVecReader},
+    core::{Cycle, TransactionView},
+    packed::{ProposalShortId, Transaction, TransactionVecReader},
     prelude::*,
 };
+use ckb_util::LinkedHashMap;
 use std::{
-    fs::OpenOptions,
+    fs::{self, OpenOptions},
     io::{Read as _, Write as _},
+    path::{Path, PathBuf},
 };
 
 /// The version of the persisted tx-pool data.
-pub(crate) const VERSION: u32 = 1;
+pub(crate) const VERSION: u32 = 2;
+// The version which persisted the transactions only, they are submitted again as local txs.
+const LEGACY_VERSION: u32 = 1;
+// Compacts the journal into the persisted data when it has this many records more than the pool.
+const COMPACT_JOURNAL_THRESHOLD: usize = 10_000;
+
+const OP_ADD: u8 = 0;
+const OP_REMOVE: u8 = 1;
+const OP_SET_STATUS: u8 = 2;
+const OP_CLEAR: u8 = 3;
+
+/// A pool entry persisted with its pool state.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct PersistedEntry {
+    pub(crate) status: Status,
+    /// The unix timestamp when the tx entered the pool, unit: Millisecond
+    pub(crate) timestamp: u64,
+    pub(crate) cycles: Cycle,
+    pub(crate) tx: TransactionView,
+}
+
+/// A change of the pool recorded in the journal.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum JournalOp {
+    Add(PersistedEntry),
+    Remove(ProposalShortId),
+    SetStatus(ProposalShortId, Status),
+    Clear,
+}
+
+/// The data loaded from the persisted files.
+#[derive(Default)]
+pub(crate) struct PersistedData {
+    /// The txs persisted by the legacy version, without their pool states.
+    pub(crate) legacy_txs: Vec<TransactionView>,
+    /// The entries to restore, parents first.
+    pub(crate) entries: Vec<PersistedEntry>,
+}
+
+impl JournalOp {
+    pub(crate) fn encode(&self, buf: &mut Vec<u8>) {
+        match self {
+            JournalOp::Add(entry) => {
+                let tx = entry.tx.data();
+                buf.push(OP_ADD);
+                buf.push(encode_status(entry.status));
+                buf.extend_from_slice(&entry.timestamp.to_le_bytes());
+                buf.extend_from_slice(&entry.cycles.to_le_bytes());
+                buf.extend_from_slice(&(tx.as_slice().len() as u32).to_le_bytes());
+                buf.extend_from_slice(tx.as_slice());
+            }
+            JournalOp::Remove(id) => {
+                buf.push(OP_REMOVE);
+                buf.extend_from_slice(id.as_slice());
+            }
+            JournalOp::SetStatus(id, status) => {
+                buf.push(OP_SET_STATUS);
+                buf.extend_from_slice(id.as_slice());
+                buf.push(encode_status(*status));
+            }
+            JournalOp::Clear => buf.push(OP_CLEAR),
+        }
+    }
+}
+
+/// Decodes the ops, stops at the first broken one, which may be left by an interrupted write.
+///
+/// Returns the decoded ops, and whether all the data is decoded.
+pub(crate) fn decode_ops(data: &[u8]) -> (Vec<JournalOp>, bool) {
+    let mut decoder = Decoder { data };
+    let mut ops = Vec::new();
+    while !decoder.data.is_empty() {
+        match decoder.op() {
+            Some(op) => ops.push(op),
+            None => return (ops, false),
+        }
+    }
+    (ops, true)
+}
+
+/// Applies the ops to the entries, in the order they happened.
+pub(crate) fn replay_ops(
+    entries: &mut LinkedHashMap<ProposalShortId, PersistedEntry>,
+    ops: Vec<JournalOp>,
+) {
+    for op in ops {
+        match op {
+            JournalOp::Add(entry) => {
+                entries.insert(entry.tx.proposal_short_id(), entry);
+            }
+            JournalOp::Remove(id) => {
+                entries.remove(&id);
+            }
+            JournalOp::SetStatus(id, status) => {
+                if let Some(entry) = entries.get_mut(&id) {
+                    entry.status = status;
+                }
+            }
+            JournalOp::Clear => entries.clear(),
+        }
+    }
+}
+
+fn encode_status(status: Status) -> u8 {
+    match status {
+        Status::Pending => 0,
+        Status::Gap => 1,
+        Status::Proposed => 2,
+    }
+}
+
+struct Decoder<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.data.len() < len {
+            return None;
+        }
+        let (head, tail) = self.data.split_at(len);
+        self.data = tail;
+        Some(head)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|bytes| bytes[0])
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take(4)
+            .map(|bytes| u32::from_le_bytes(bytes.try_into().expect("checked length")))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        self.take(8)
+            .map(|bytes| u64::from_le_bytes(bytes.try_into().expect("checked length")))
+    }
+
+    fn status(&mut self) -> Option<Status> {
+        match self.u8()? {
+            0 => Some(Status::Pending),
+            1 => Some(Status::Gap),
+            2 => Some(Status::Proposed),
+            _ => None,
+        }
+    }
+
+    fn short_id(&mut self) -> Option<ProposalShortId> {
+        ProposalShortId::from_slice(self.take(10)?).ok()
+    }
+
+    fn op(&mut self) -> Option<JournalOp> {
+        let op = match self.u8()? {
+            OP_ADD => {
+                let status = self.status()?;
+                let timestamp = self.u64()?;
+                let cycles = self.u64()?;
+                let len = self.u32()? as usize;
+                let tx = Transaction::from_slice(self.take(len)?).ok()?.into_view();
+                JournalOp::Add(PersistedEntry {
+                    status,
+                    timestamp,
+                    cycles,
+                    tx,
+                })
+            }
+            OP_REMOVE => JournalOp::Remove(self.short_id()?),
+            OP_SET_STATUS => JournalOp::SetStatus(self.short_id()?, self.status()?),
+            OP_CLEAR => JournalOp::Clear,
+            _ => return None,
+        };
+        Some(op)
+    }
+}
 
 impl TxPool {
-    pub(crate) fn load_from_file(&self) -> Result<Vec<TransactionView>, AnyError> {
+    fn persisted_data_file(&self, version: u32) -> PathBuf {
         let mut persisted_data_file = self.config.persisted_data.clone();
-        persisted_data_file.set_extension(format!("v{VERSION}"));
+        persisted_data_file.set_extension(format!("v{version}"));
+        persisted_data_file
+    }
 
-        if persisted_data_file.exists() {
-            let mut file = OpenOptions::new()
-                .read(true)
-                .open(&persisted_data_file)
-                .map_err(|err| {
-                    let errmsg = format!(
-                        "Failed to open the tx-pool persisted data file [{persisted_data_file:?}], cause: {err}"
-                    );
-                    OtherError::new(errmsg)
-                })?;
-            let mut buffer = Vec::new();
-            file.read_to_end(&mut buffer).map_err(|err| {
-                let errmsg = format!(
-                    "Failed to read the tx-pool persisted data file [{persisted_data_file:?}], cause: {err}"
-                );
-                OtherError::new(errmsg)
-            })?;
+    fn journal_file(&self) -> PathBuf {
+        let mut journal_file = self.config.persisted_data.clone();
+        journal_file.set_extension(format!("v{VERSION}.journal"));
+        journal_file
+    }
 
-            let persisted_data = TransactionVecReader::from_slice(&buffer)
+    pub(crate) fn load_from_file(&self) -> Result<PersistedData, AnyError> {
+        let legacy_file = self.persisted_data_file(LEGACY_VERSION);
+        let legacy_txs = match read_file(&legacy_file)? {
+            Some(buffer) => TransactionVecReader::from_slice(&buffer)
                 .map_err(|err| {
                     let errmsg = format!(
-                        "The tx-pool persisted data file [{persisted_data_file:?}] is broken, cause: {err}"
+                        "The tx-pool persisted data file [{legacy_file:?}] is broken, cause: {err}"
                     );
                     OtherError::new(errmsg)
                 })?
-                .to_entity();
-
-            Ok(persisted_data
+                .to_entity()
                 .into_iter()
                 .map(|tx| tx.into_view())
-                .collect())
-        } else {
-            Ok(Vec::new())
+                .collect(),
+            None => Vec::new(),
+        };
+
+        let mut entries = LinkedHashMap::default();
+        let persisted_data_file = self.persisted_data_file(VERSION);
+        if let Some(buffer) = read_file(&persisted_data_file)? {
+            let (ops, complete) = decode_ops(&buffer);
+            if !complete {
+                let errmsg =
+                    format!("The tx-pool persisted data file [{persisted_data_file:?}] is broken");
+                return Err(OtherError::new(errmsg).into());
+            }
+            replay_ops(&mut entries, ops);
         }
+        let journal_file = self.journal_file();
+        if let Some(buffer) = read_file(&journal_file)? {
+            let (ops, complete) = decode_ops(&buffer);
+            if !complete {
+                warn!(
+                    "The tail of the tx-pool journal file [{:?}] is broken, \
+                    the changes after {} records are ignored",
+                    journal_file,
+                    ops.len()
+                );
+            }
+            replay_ops(&mut entries, ops);
+        }
+
+        Ok(PersistedData {
+            legacy_txs,
+            entries: entries.into_iter().map(|(_, entry)| entry).collect(),
+        })
     }
 
+    /// Saves all the entries into the persisted data file, and truncates the journal.
     pub(crate) fn save_into_file(&mut self) -> Result<(), AnyError> {
-        let mut persisted_data_file = self.config.persisted_data.clone();
-        persisted_data_file.set_extension(format!("v{VERSION}"));
-
-        let mut file = OpenOptions::new()
-            .create(true)
-            .write(true)
-            .truncate(true)
-            .open(&persisted_data_file)
-            .map_err(|err| {
+        let mut entries: Vec<_> = self.pool_map.iter().collect();
+        // a tx always has more ancestors than its parents
+        entries.sort_unstable_by_key(|entry| (entry.inner.ancestors_count, entry.inner.timestamp));
+        let mut buffer = Vec::new();
+        for entry in entries {
+            let op = JournalOp::Add(PersistedEntry {
+                status: entry.status,
+                timestamp: entry.inner.timestamp,
+                cycles: entry.inner.cycles,
+                tx: entry.inner.transaction().clone(),
+            });
+            op.encode(&mut buffer);
+        }
+
+        // write a temporary file and rename it, so the persisted data is never half written
+        let persisted_data_file = self.persisted_data_file(VERSION);
+        let mut temp_file = persisted_data_file.clone();
+        temp_file.set_extension(format!("v{VERSION}.tmp"));
+        write_file(&temp_file, &buffer, false)?;
+        fs::rename(&temp_file, &persisted_data_file).map_err(|err| {
+            let errmsg = format!(
+                "Failed to rename the tx-pool persisted data file [{persisted_data_file:?}], cause: {err}"
+            );
+            OtherError::new(errmsg)
+        })?;
+
+        // the journal and the legacy data are included in the persisted data now
+        write_file(&self.journal_file(), &[], false)?;
+        let legacy_file = self.persisted_data_file(LEGACY_VERSION);
+        if legacy_file.exists() {
+            fs::remove_file(&legacy_file).map_err(|err| {
                 let errmsg = format!(
-                    "Failed to open the tx-pool persisted data file [{persisted_data_file:?}], cause: {err}"
+                    "Failed to remove the legacy tx-pool persisted data file [{legacy_file:?}], cause: {err}"
                 );
                 OtherError::new(errmsg)
             })?;
+        }
+        if let Some(ops) = self.pool_map.journal.as_mut() {
+            ops.clear();
+        }
+        self.journal_records = 0;
+        Ok(())
+    }
 
-        let txs = TransactionVec::new_builder()
-            .extend(self.drain_all_transactions().iter().map(|tx| tx.data()))
-            .build();
+    /// Appends the pool changes since the last flush to the journal.
+    ///
+    /// The journal is compacted into the persisted data file when it grows too large.
+    pub(crate) fn flush_journal(&mut self) -> Result<(), AnyError> {
+        let ops = match self.pool_map.journal.as_mut() {
+            Some(ops) if !ops.is_empty() => std::mem::take(ops),
+            _ => return Ok(()),
+        };
+        self.journal_records += ops.len();
+        if self.journal_records > self.pool_map.size() + COMPACT_JOURNAL_THRESHOLD {
+            return self.save_into_file();
+        }
 
-        file.write_all(txs.as_slice()).map_err(|err| {
-            let errmsg = format!(
-                "Failed to write the tx-pool persisted data into file [{persisted_data_file:?}], cause: {err}"
-            );
+        let mut buffer = Vec::new();
+        for op in &ops {
+            op.encode(&mut buffer);
+        }
+        write_file(&self.journal_file(), &buffer, true)
+    }
+}
+
+fn read_file(file: &Path) -> Result<Option<Vec<u8>>, AnyError> {
+    if !file.exists() {
+        return Ok(None);
+    }
+    let mut buffer = Vec::new();
+    OpenOptions::new()
+        .read(true)
+        .open(file)
+        .and_then(|mut f| f.read_to_end(&mut buffer))
+        .map_err(|err| {
+            let errmsg =
+                format!("Failed to read the tx-pool persisted data file [{file:?}], cause: {err}");
             OtherError::new(errmsg)
         })?;
-        file.sync_all().map_err(|err| {
+    Ok(Some(buffer))
+}
+
+fn write_file(file: &Path, data: &[u8], append: bool) -> Result<(), AnyError> {
+    let mut options = OpenOptions::new();
+    options.create(true);
+    if append {
+        options.append(true);
+    } else {
+        options.write(true).truncate(true);
+    }
+    options
+        .open(file)
+        .and_then(|mut f| {
+            f.write_all(data)?;
+            f.sync_all()
+        })
+        .map_err(|err| {
             let errmsg = format!(
-                "Failed to sync the tx-pool persisted data file [{persisted_data_file:?}], cause: {err}"
+                "Failed to write the tx-pool persisted data into file [{file:?}], cause: {err}"
             );
             OtherError::new(errmsg)
         })?;
-        Ok(())
-    }
+    Ok(())
 }
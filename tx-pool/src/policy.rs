@@ -0,0 +1,250 @@
+//! The tx-pool policies, which decide the transactions admitted into the pool,
+//! and the order to evict them when the pool is full.
+use crate::component::entry::TxEntry;
+use crate::error::Reject;
+use crate::pool::TxPool;
+use ckb_app_config::{PolicyScript, TxPoolPolicyConfig};
+use ckb_network::PeerIndex;
+use ckb_types::{
+    bytes::Bytes,
+    core::{ScriptHashType, TransactionView},
+    packed::{self, Byte32, Script},
+    prelude::*,
+};
+use ckb_util::Mutex;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+const DEFAULT_PEER_QUOTA_INTERVAL_SECS: u64 = 60;
+
+/// The eviction class of a pool entry.
+///
+/// When the pool is full, the entries of `Normal` are evicted first, then `Priority`.
+/// The entries of `Kept` are never evicted for the pool size, nor expired. The pool only keeps
+/// them up to `max_kept_tx_size` bytes, the others are added as `Normal`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EvictionClass {
+    /// Evicted by the fee rate.
+    #[default]
+    Normal,
+    /// Evicted after all the `Normal` entries.
+    Priority,
+    /// Never evicted by the pool size limit or expired.
+    Kept,
+}
+
+/// A pluggable tx-pool policy.
+///
+/// The default implementation of each method accepts everything, so a policy only overrides
+/// the checks it cares about.
+pub trait TxPoolPolicy: Send + Sync {
+    /// Classifies the entry for eviction, invoked each time the entry is added into the pool.
+    ///
+    /// The class must not be decided by the outputs alone for `Kept`, anyone can pay an output
+    /// to any script.
+    fn eviction_class(&self, _entry: &TxEntry) -> EvictionClass {
+        EvictionClass::Normal
+    }
+
+    /// Checks whether a newly verified entry can be admitted into the pool.
+    fn check_admission(&self, _tx_pool: &TxPool, _entry: &TxEntry) -> Result<(), Reject> {
+        Ok(())
+    }
+
+    /// Checks whether a transaction relayed by the peer can be accepted, invoked before the
+    /// transaction is verified.
+    fn check_peer(&self, _peer: PeerIndex, _tx: &TransactionView) -> Result<(), Reject> {
+        Ok(())
+    }
+}
+
+/// The policy built from the config section `tx_pool.policy`.
+pub struct ConfigPolicy {
+    keep_scripts: Vec<ScriptPattern>,
+    priority_scripts: Vec<ScriptPattern>,
+    max_txs_per_sender_lock: Option<usize>,
+    peer_quotas: Option<Mutex<PeerQuotas>>,
+}
+
+impl ConfigPolicy {
+    /// Creates the policy from the config.
+    pub fn new(config: &TxPoolPolicyConfig) -> Self {
+        let peer_quotas = config.max_txs_per_peer.map(|max_txs| {
+            let interval = Duration::from_secs(
+                config
+                    .peer_quota_interval_secs
+                    .unwrap_or(DEFAULT_PEER_QUOTA_INTERVAL_SECS),
+            );
+            Mutex::new(PeerQuotas::new(max_txs, interval))
+        });
+        ConfigPolicy {
+            keep_scripts: config.keep_scripts.iter().map(Into::into).collect(),
+            priority_scripts: config.priority_scripts.iter().map(Into::into).collect(),
+            max_txs_per_sender_lock: config.max_txs_per_sender_lock,
+            peer_quotas,
+        }
+    }
+}
+
+impl TxPoolPolicy for ConfigPolicy {
+    fn eviction_class(&self, entry: &TxEntry) -> EvictionClass {
+        // only the senders are matched, the outputs can be paid to the kept scripts by anyone
+        if !self.keep_scripts.is_empty() && inputs_match(&self.keep_scripts, entry) {
+            EvictionClass::Kept
+        } else if !self.priority_scripts.is_empty() && entry_matches(&self.priority_scripts, entry)
+        {
+            EvictionClass::Priority
+        } else {
+            EvictionClass::Normal
+        }
+    }
+
+    fn check_admission(&self, tx_pool: &TxPool, entry: &TxEntry) -> Result<(), Reject> {
+        let max_txs = match self.max_txs_per_sender_lock {
+            Some(max_txs) => max_txs,
+            None => return Ok(()),
+        };
+        if self.eviction_class(entry) == EvictionClass::Kept && tx_pool.has_kept_room(entry.size) {
+            return Ok(());
+        }
+        for lock_hash in sender_lock_hashes(entry) {
+            if tx_pool.sender_lock_count(&lock_hash) >= max_txs {
+                return Err(Reject::Policy(format!(
+                    "the sender lock {lock_hash} already has {max_txs} transactions in the pool"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    // the senders are unknown before the transaction is resolved, so no transaction is exempted
+    fn check_peer(&self, peer: PeerIndex, _tx: &TransactionView) -> Result<(), Reject> {
+        match &self.peer_quotas {
+            Some(quotas) => quotas.lock().check(peer, Instant::now()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The hashes of the lock scripts of the resolved input cells, without duplicates.
+pub(crate) fn sender_lock_hashes(entry: &TxEntry) -> HashSet<Byte32> {
+    entry
+        .rtx
+        .resolved_inputs
+        .iter()
+        .map(|cell| cell.cell_output.calc_lock_hash())
+        .collect()
+}
+
+fn inputs_match(patterns: &[ScriptPattern], entry: &TxEntry) -> bool {
+    entry
+        .rtx
+        .resolved_inputs
+        .iter()
+        .any(|cell| output_matches(patterns, &cell.cell_output))
+}
+
+fn entry_matches(patterns: &[ScriptPattern], entry: &TxEntry) -> bool {
+    inputs_match(patterns, entry)
+        || entry
+            .transaction()
+            .outputs()
+            .into_iter()
+            .any(|output| output_matches(patterns, &output))
+}
+
+fn output_matches(patterns: &[ScriptPattern], output: &packed::CellOutput) -> bool {
+    let lock = output.lock();
+    let type_ = output.type_().to_opt();
+    patterns.iter().any(|pattern| {
+        pattern.matches(&lock)
+            || type_
+                .as_ref()
+                .map_or(false, |script| pattern.matches(script))
+    })
+}
+
+struct ScriptPattern {
+    code_hash: Byte32,
+    hash_type: packed::Byte,
+    args: Option<Bytes>,
+}
+
+impl ScriptPattern {
+    fn matches(&self, script: &Script) -> bool {
+        script.code_hash() == self.code_hash
+            && script.hash_type() == self.hash_type
+            && self
+                .args
+                .as_ref()
+                .map_or(true, |args| script.args().raw_data() == *args)
+    }
+}
+
+impl From<&PolicyScript> for ScriptPattern {
+    fn from(script: &PolicyScript) -> Self {
+        ScriptPattern {
+            code_hash: script.code_hash.pack(),
+            hash_type: ScriptHashType::from(script.hash_type.clone()).into(),
+            args: script.args.as_ref().map(|args| args.clone().into_bytes()),
+        }
+    }
+}
+
+/// Counts the transactions relayed by each peer in a fixed time window.
+pub(crate) struct PeerQuotas {
+    max_txs: usize,
+    interval: Duration,
+    peers: HashMap<PeerIndex, (Instant, usize)>,
+    last_pruned: Option<Instant>,
+}
+
+impl PeerQuotas {
+    pub(crate) fn new(max_txs: usize, interval: Duration) -> Self {
+        PeerQuotas {
+            max_txs,
+            interval,
+            peers: HashMap::new(),
+            last_pruned: None,
+        }
+    }
+
+    /// Counts a transaction relayed by the peer at `now`, rejects it if the quota is used up.
+    pub(crate) fn check(&mut self, peer: PeerIndex, now: Instant) -> Result<(), Reject> {
+        self.prune(now);
+        let interval = self.interval;
+        let (start, count) = self.peers.entry(peer).or_insert((now, 0));
+        if now.saturating_duration_since(*start) >= interval {
+            *start = now;
+            *count = 0;
+        }
+        if *count >= self.max_txs {
+            return Err(Reject::Policy(format!(
+                "peer {peer} exceeded the quota of {} transactions in {} seconds",
+                self.max_txs,
+                interval.as_secs()
+            )));
+        }
+        *count += 1;
+        Ok(())
+    }
+
+    // remove the peers whose windows have ended, including the disconnected ones
+    fn prune(&mut self, now: Instant) {
+        let interval = self.interval;
+        if self
+            .last_pruned
+            .map_or(false, |last| now.saturating_duration_since(last) < interval)
+        {
+            return;
+        }
+        self.peers
+            .retain(|_, (start, _)| now.saturating_duration_since(*start) < interval);
+        self.last_pruned = Some(now);
+    }
+
+    #[cfg(test)]
+    pub(crate) fn peers_len(&self) -> usize {
+        self.peers.len()
+    }
+}
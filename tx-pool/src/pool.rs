@@ -6,6 +6,7 @@ use crate::callback::Callbacks;
 use crate::component::pool_map::{PoolEntry, PoolMap, Status};
 use crate::component::recent_reject::RecentReject;
 use crate::error::Reject;
+use crate::policy::{ConfigPolicy, EvictionClass, TxPoolPolicy};
 use crate::pool_cell::PoolCell;
 use ckb_app_config::TxPoolConfig;
 use ckb_fee_estimator::Error as FeeEstimatorError;
@@ -17,7 +18,10 @@ use ckb_types::core::{BlockNumber, CapacityError, FeeRate};
 use ckb_types::packed::OutPoint;
 use ckb_types::{
     core::{
-        cell::{resolve_transaction, OverlayCellChecker, OverlayCellProvider, ResolvedTransaction},
+        cell::{
+            resolve_transaction, OverlayCellChecker, OverlayCellProvider, ResolvedTransaction,
+            TransactionsProvider,
+        },
         tx_pool::{TxPoolEntryInfo, TxPoolIds},
         Capacity, Cycle, TransactionView, UncleBlockView,
     },
@@ -48,6 +52,10 @@ pub struct TxPool {
     pub(crate) conflicts_cache: lru::LruCache<ProposalShortId, TransactionView>,
     // conflicted transaction outputs cache, input -> tx_short_id
     pub(crate) conflicts_outputs_cache: lru::LruCache<OutPoint, ProposalShortId>,
+    // the admission and eviction policy
+    pub(crate) policy: Arc<dyn TxPoolPolicy>,
+    // the count of the records in the journal file
+    pub(crate) journal_records: usize,
 }
 
 impl TxPool {
@@ -55,8 +63,13 @@ impl TxPool {
     pub fn new(config: TxPoolConfig, snapshot: Arc<Snapshot>) -> TxPool {
         let recent_reject = Self::build_recent_reject(&config);
         let expiry = config.expiry_hours as u64 * 60 * 60 * 1000;
+        let policy = Arc::new(ConfigPolicy::new(&config.policy));
+        let mut pool_map = PoolMap::new(config.max_ancestors_count);
+        if config.journal_interval_secs > 0 {
+            pool_map.journal = Some(Vec::new());
+        }
         TxPool {
-            pool_map: PoolMap::new(config.max_ancestors_count),
+            pool_map,
             committed_txs_hash_cache: LruCache::new(COMMITTED_HASH_CACHE_SIZE),
             config,
             snapshot,
@@ -64,6 +77,8 @@ impl TxPool {
             expiry,
             conflicts_cache: LruCache::new(CONFLICTES_CACHE_SIZE),
             conflicts_outputs_cache: lru::LruCache::new(CONFLICTES_INPUTS_CACHE_SIZE),
+            policy,
+            journal_records: 0,
         }
     }
 
@@ -132,12 +147,12 @@ impl TxPool {
         &mut self,
         entry: TxEntry,
     ) -> Result<(bool, HashSet<TxEntry>), Reject> {
-        self.pool_map.add_entry(entry, Status::Pending)
+        self.add_entry(entry, Status::Pending)
     }
 
     /// Add tx which proposed but still uncommittable to gap
     pub(crate) fn add_gap(&mut self, entry: TxEntry) -> Result<(bool, HashSet<TxEntry>), Reject> {
-        self.pool_map.add_entry(entry, Status::Gap)
+        self.add_entry(entry, Status::Gap)
     }
 
     /// Add tx with proposed status
@@ -145,7 +160,44 @@ impl TxPool {
         &mut self,
         entry: TxEntry,
     ) -> Result<(bool, HashSet<TxEntry>), Reject> {
-        self.pool_map.add_entry(entry, Status::Proposed)
+        self.add_entry(entry, Status::Proposed)
+    }
+
+    fn add_entry(
+        &mut self,
+        mut entry: TxEntry,
+        status: Status,
+    ) -> Result<(bool, HashSet<TxEntry>), Reject> {
+        entry.eviction_class = self.policy.eviction_class(&entry);
+        if entry.eviction_class == EvictionClass::Kept && !self.has_kept_room(entry.size) {
+            entry.eviction_class = EvictionClass::Normal;
+        }
+        self.pool_map.add_entry(entry, status)
+    }
+
+    /// Returns true if the kept txs can take `size` more bytes within `max_kept_tx_size`.
+    pub fn has_kept_room(&self, size: usize) -> bool {
+        let max_kept_tx_size = self
+            .config
+            .policy
+            .max_kept_tx_size
+            .unwrap_or(self.config.max_tx_pool_size / 4);
+        self.pool_map.kept_tx_size.saturating_add(size) <= max_kept_tx_size
+    }
+
+    /// Checks whether a new tx can be admitted by the policy.
+    ///
+    /// It is only checked for the newly submitted txs, not for the txs re-added into the pool.
+    pub(crate) fn check_admission(&self, entry: &TxEntry) -> Result<(), Reject> {
+        if self.contains_proposal_id(&entry.proposal_short_id()) {
+            return Ok(());
+        }
+        self.policy.check_admission(self, entry)
+    }
+
+    /// Returns the count of the in-pool txs which spend the cells of the lock script.
+    pub fn sender_lock_count(&self, lock_hash: &Byte32) -> usize {
+        self.pool_map.sender_lock_count(lock_hash)
     }
 
     /// Returns true if the tx-pool contains a tx with specified id.
@@ -274,7 +326,10 @@ impl TxPool {
         let removed: Vec<_> = self
             .pool_map
             .iter()
-            .filter(|&entry| self.expiry + entry.inner.timestamp < now_ms)
+            .filter(|&entry| {
+                entry.inner.eviction_class != EvictionClass::Kept
+                    && self.expiry + entry.inner.timestamp < now_ms
+            })
             .map(|entry| entry.inner.clone())
             .collect();
 
@@ -287,7 +342,8 @@ impl TxPool {
         }
     }
 
-    // Remove transactions from the pool until total size <= size_limit.
+    // Remove transactions from the pool until total size <= size_limit,
+    // evicting the `Normal` entries first, then the `Priority` ones, the `Kept` ones are never evicted.
     // Return a `Reject` for current inserting entry if it's removed
     pub(crate) fn limit_size(
         &mut self,
@@ -297,10 +353,14 @@ impl TxPool {
         let mut ret = None;
         while self.pool_map.total_tx_size > self.config.max_tx_pool_size {
             let next_evict_entry = || {
-                self.pool_map
-                    .next_evict_entry(Status::Pending)
-                    .or_else(|| self.pool_map.next_evict_entry(Status::Gap))
-                    .or_else(|| self.pool_map.next_evict_entry(Status::Proposed))
+                [EvictionClass::Normal, EvictionClass::Priority]
+                    .into_iter()
+                    .find_map(|class| {
+                        self.pool_map
+                            .next_evict_entry(Status::Pending, class)
+                            .or_else(|| self.pool_map.next_evict_entry(Status::Gap, class))
+                            .or_else(|| self.pool_map.next_evict_entry(Status::Proposed, class))
+                    })
             };
 
             if let Some(id) = next_evict_entry() {
@@ -322,6 +382,12 @@ impl TxPool {
                     }
                     callbacks.call_reject(self, &entry, reject);
                 }
+            } else {
+                warn!(
+                    "The tx-pool size {} exceeds the limit, but the remaining txs are kept by the policy",
+                    self.pool_map.total_tx_size
+                );
+                break;
             }
         }
         self.pool_map.entries.shrink_to_fit();
@@ -383,6 +449,23 @@ impl TxPool {
             .map_err(Reject::Resolve)
     }
 
+    /// Resolves a package transaction, which may spend the outputs of the earlier transactions in
+    /// the package. The `seen_inputs` is shared by the package to detect the double spending.
+    pub(crate) fn resolve_tx_from_package(
+        &self,
+        tx: TransactionView,
+        package: &TransactionsProvider,
+        seen_inputs: &mut HashSet<OutPoint>,
+    ) -> Result<Arc<ResolvedTransaction>, Reject> {
+        let snapshot = self.snapshot();
+        let pool_cell = PoolCell::new(&self.pool_map, false);
+        let pool_provider = OverlayCellProvider::new(&pool_cell, snapshot);
+        let provider = OverlayCellProvider::new(package, &pool_provider);
+        resolve_transaction(tx, seen_inputs, &provider, snapshot)
+            .map(Arc::new)
+            .map_err(Reject::Resolve)
+    }
+
     pub(crate) fn gap_rtx(&mut self, short_id: &ProposalShortId) -> Result<(), Reject> {
         match self.get_pool_entry(short_id) {
             Some(entry) => {
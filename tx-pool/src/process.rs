@@ -3,12 +3,14 @@ use crate::component::entry::TxEntry;
 use crate::component::orphan::Entry as OrphanEntry;
 use crate::component::pool_map::Status;
 use crate::error::Reject;
+use crate::persisted::PersistedEntry;
 use crate::pool::TxPool;
 use crate::service::{BlockAssemblerMessage, TxPoolService, TxVerificationResult};
 use crate::try_or_return_with_snapshot;
 use crate::util::{
-    after_delay_window, check_tx_fee, check_txid_collision, is_missing_input,
-    non_contextual_verify, time_relative_verify, verify_rtx,
+    after_delay_window, calculate_tx_fee, check_package_topology, check_tx_fee,
+    check_txid_collision, is_missing_input, non_contextual_verify, time_relative_verify,
+    verify_rtx,
 };
 use ckb_chain_spec::consensus::MAX_BLOCK_PROPOSALS_LIMIT;
 use ckb_error::{AnyError, InternalErrorKind};
@@ -22,8 +24,8 @@ use ckb_snapshot::Snapshot;
 use ckb_types::core::error::OutPointError;
 use ckb_types::{
     core::{
-        cell::ResolvedTransaction, BlockView, Capacity, Cycle, EstimateMode, FeeRate, HeaderView,
-        TransactionView,
+        cell::{ResolvedTransaction, TransactionsProvider},
+        BlockView, Capacity, Cycle, EstimateMode, FeeRate, HeaderView, TransactionView,
     },
     packed::{Byte32, ProposalShortId},
 };
@@ -136,6 +138,11 @@ impl TxPoolService {
                     time_relative_verify(snapshot, Arc::clone(&entry.rtx), tx_env)?;
                 }
 
+                if let Err(reject) = tx_pool.check_admission(&entry) {
+                    self.callbacks.call_reject(tx_pool, &entry, reject.clone());
+                    return Err(reject);
+                }
+
                 let may_recovered_txs = self.process_rbf(tx_pool, &entry, &conflicts);
                 let evicted = _submit_entry(tx_pool, status, entry.clone(), &self.callbacks)?;
 
@@ -362,9 +369,27 @@ impl TxPoolService {
         if self.verify_queue_contains(&tx).await {
             return Err(Reject::Duplicated(tx.hash()));
         }
+        if let Some((_, peer)) = remote {
+            self.check_peer_policy(&tx, peer).await?;
+        }
         self.enqueue_verify_queue(tx, remote).await
     }
 
+    // Checks the policy for the tx relayed by the peer before verifying it,
+    // the reject is notified with a dummy entry as the tx is not resolved yet.
+    async fn check_peer_policy(&self, tx: &TransactionView, peer: PeerIndex) -> Result<(), Reject> {
+        if let Err(reject) = self.policy.check_peer(peer, tx) {
+            debug!("reject tx {} from peer {}: {}", tx.hash(), peer, reject);
+            let tx_size = tx.data().serialized_size_in_block();
+            let entry = TxEntry::dummy_resolve(tx.clone(), 0, Capacity::zero(), tx_size);
+            let mut tx_pool = self.tx_pool.write().await;
+            self.callbacks
+                .call_reject(&mut tx_pool, &entry, reject.clone());
+            return Err(reject);
+        }
+        Ok(())
+    }
+
     pub(crate) async fn test_accept_tx(&self, tx: TransactionView) -> Result<Completed, Reject> {
         // non contextual verify first
         self.non_contextual_verify(&tx, None)?;
@@ -391,6 +416,9 @@ impl TxPoolService {
         if self.verify_queue_contains(&tx).await || self.orphan_contains(&tx).await {
             return Err(Reject::Duplicated(tx.hash()));
         }
+        if let Some((_, peer)) = remote {
+            self.check_peer_policy(&tx, peer).await?;
+        }
 
         if let Some((ret, snapshot)) = self
             ._process_tx(tx.clone(), remote.map(|r| r.0), None)
@@ -448,17 +476,7 @@ impl TxPoolService {
 
         // The network protocol is switched after tx-pool confirms the cache,
         // there will be no problem with the current state as the choice of the broadcast protocol.
-        let with_vm_2023 = {
-            let epoch = snapshot
-                .tip_header()
-                .epoch()
-                .minimum_epoch_number_after_n_blocks(1);
-
-            self.consensus
-                .hardfork_switch
-                .ckb2023
-                .is_vm_version_2_and_syscalls_3_enabled(epoch)
-        };
+        let with_vm_2023 = self.with_vm_2023(snapshot);
 
         // log tx verification result for monitor node
         if log_enabled_target!("ckb_tx_monitor", Trace) {
@@ -553,6 +571,210 @@ impl TxPoolService {
         }
     }
 
+    /// Processes a package of transactions, parents first.
+    ///
+    /// The package is verified and added to the pool as a whole, and it is the package fee rate
+    /// that must reach `min_fee_rate`, so a child can pay for its low fee parents. Transactions
+    /// already in the pool are skipped. Replacing the conflicted transactions is not supported.
+    pub(crate) async fn process_package(
+        &self,
+        txs: Vec<(TransactionView, Option<Cycle>)>,
+        peer: Option<PeerIndex>,
+    ) -> Result<Vec<Completed>, Reject> {
+        let ret = self._process_package(&txs, peer).await;
+        match (&ret, peer) {
+            (Ok((_, snapshot)), _) => {
+                let tx_hashes: Vec<_> = txs.iter().map(|(tx, _)| tx.hash()).collect();
+                debug!(
+                    "after_process_package send_result_to_relayer {:?}",
+                    tx_hashes
+                );
+                self.send_result_to_relayer(TxVerificationResult::PackageOk {
+                    original_peer: peer,
+                    with_vm_2023: self.with_vm_2023(snapshot),
+                    tx_hashes,
+                });
+                for (tx, _) in &txs {
+                    self.process_orphan_tx(tx).await;
+                }
+            }
+            (Err(reject), Some(peer)) => {
+                info!("after_process_package {} remote reject: {}", peer, reject);
+                if reject.is_malformed_tx() {
+                    self.ban_malformed(peer, format!("reject package {reject}"));
+                }
+            }
+            (Err(reject), None) => {
+                debug!("after_process_package reject: {}", reject);
+            }
+        }
+        ret.map(|(completed, _)| completed)
+    }
+
+    async fn _process_package(
+        &self,
+        txs: &[(TransactionView, Option<Cycle>)],
+        peer: Option<PeerIndex>,
+    ) -> Result<(Vec<Completed>, Arc<Snapshot>), Reject> {
+        for (tx, declared_cycles) in txs {
+            // banning is left to the caller, which knows the peer
+            non_contextual_verify(&self.consensus, tx)?;
+            if self.verify_queue_contains(tx).await || self.orphan_contains(tx).await {
+                return Err(Reject::Duplicated(tx.hash()));
+            }
+            // the package is verified at once without suspending,
+            // a large remote package has to be relayed one by one
+            if peer.is_some()
+                && declared_cycles.unwrap_or_default() > self.tx_pool_config.max_tx_verify_cycles
+            {
+                return Err(Reject::Full(format!(
+                    "the declared cycles of package tx {} exceed max_tx_verify_cycles",
+                    tx.hash()
+                )));
+            }
+        }
+        if let Some(peer) = peer {
+            for (tx, _) in txs {
+                self.check_peer_policy(tx, peer).await?;
+            }
+        }
+        let package: Vec<_> = txs.iter().map(|(tx, _)| tx.clone()).collect();
+        check_package_topology(&package)?;
+
+        let (ret, snapshot) = self.pre_check_package(&package).await;
+        let (tip_hash, checked) = ret?;
+
+        if self.is_in_delay_window(&snapshot) {
+            let mut delay = self.delay.write().await;
+            for tx in package {
+                if delay.len() < DELAY_LIMIT {
+                    delay.insert(tx.proposal_short_id(), tx);
+                }
+            }
+            return Ok((Vec::new(), snapshot));
+        }
+
+        let tip_header = snapshot.tip_header();
+        let mut entries = Vec::with_capacity(checked.len());
+        let mut completed = Vec::with_capacity(checked.len());
+        for ((tx, declared_cycles), checked) in txs.iter().zip(checked) {
+            let (rtx, status, fee, tx_size) = match checked {
+                Some(checked) => checked,
+                None => continue,
+            };
+            let verify_cache = self.fetch_tx_verify_cache(tx).await;
+            let max_cycles = declared_cycles.unwrap_or_else(|| self.consensus.max_block_cycles());
+            let tx_env = Arc::new(status.with_env(tip_header));
+            let verified = verify_rtx(
+                Arc::clone(&snapshot),
+                Arc::clone(&rtx),
+                tx_env,
+                &verify_cache,
+                max_cycles,
+                None,
+            )
+            .await?;
+            if let Some(declared) = declared_cycles {
+                if *declared != verified.cycles {
+                    return Err(Reject::DeclaredWrongCycles(*declared, verified.cycles));
+                }
+            }
+            if verify_cache.is_none() {
+                let txs_verify_cache = Arc::clone(&self.txs_verify_cache);
+                let wtx_hash = tx.witness_hash();
+                tokio::spawn(async move {
+                    let mut guard = txs_verify_cache.write().await;
+                    guard.put(wtx_hash, verified);
+                });
+            }
+            entries.push((TxEntry::new(rtx, verified.cycles, fee, tx_size), status));
+            completed.push(verified);
+        }
+
+        let (ret, submit_snapshot) = self.submit_package(tip_hash, entries).await;
+        let statuses = ret?;
+        for status in [TxStatus::Fresh, TxStatus::Proposed] {
+            if statuses.contains(&status) {
+                self.notify_block_assembler(status).await;
+            }
+        }
+        Ok((completed, submit_snapshot))
+    }
+
+    async fn pre_check_package(
+        &self,
+        txs: &[TransactionView],
+    ) -> (Result<PreCheckedPackage, Reject>, Arc<Snapshot>) {
+        self.with_tx_pool_read_lock(|tx_pool, snapshot| {
+            let tip_hash = snapshot.tip_hash();
+            let mut provider = TransactionsProvider::new(std::iter::empty());
+            let mut seen_inputs = HashSet::new();
+            let mut checked = Vec::with_capacity(txs.len());
+            let (mut package_fee, mut package_size) = (0u64, 0usize);
+            for tx in txs {
+                let short_id = tx.proposal_short_id();
+                if tx_pool.contains_proposal_id(&short_id) {
+                    // e.g. a parent which has been accepted alone
+                    checked.push(None);
+                    continue;
+                }
+                let rtx =
+                    tx_pool.resolve_tx_from_package(tx.clone(), &provider, &mut seen_inputs)?;
+                let fee = calculate_tx_fee(&snapshot, &rtx)?;
+                let tx_size = tx.data().serialized_size_in_block();
+                package_fee = package_fee.saturating_add(fee.as_u64());
+                package_size += tx_size;
+                provider.insert(tx);
+                let status = get_tx_status(&snapshot, &short_id);
+                checked.push(Some((rtx, status, fee, tx_size)));
+            }
+            if checked.iter().all(Option::is_none) {
+                let child = txs.last().expect("checked package is not empty");
+                return Err(Reject::Duplicated(child.hash()));
+            }
+            // the fee rate of the new transactions as a whole
+            let min_fee_rate = tx_pool.config.min_fee_rate;
+            let min_fee = min_fee_rate.fee(package_size as u64);
+            if package_fee < min_fee.as_u64() {
+                let reject = Reject::LowFeeRate(min_fee_rate, min_fee.as_u64(), package_fee);
+                debug!("Reject package {}", reject);
+                return Err(reject);
+            }
+            Ok((tip_hash, checked))
+        })
+        .await
+    }
+
+    async fn submit_package(
+        &self,
+        pre_resolve_tip: Byte32,
+        entries: Vec<(TxEntry, TxStatus)>,
+    ) -> (Result<Vec<TxStatus>, Reject>, Arc<Snapshot>) {
+        self.with_tx_pool_write_lock(move |tx_pool, snapshot| {
+            let (ret, rejected) = _submit_package(tx_pool, &snapshot, &pre_resolve_tip, &entries);
+            for (entry, reject) in rejected {
+                self.callbacks.call_reject(tx_pool, &entry, reject);
+            }
+            let added = ret?;
+
+            // call the callbacks only after the whole package is added
+            for (entry, status) in &added {
+                match status {
+                    TxStatus::Fresh | TxStatus::Gap => self.callbacks.call_pending(entry),
+                    TxStatus::Proposed => self.callbacks.call_proposed(entry),
+                }
+                tx_pool.remove_conflict(&entry.proposal_short_id());
+            }
+            if let Some((child, _)) = added.last() {
+                tx_pool
+                    .limit_size(&self.callbacks, Some(&child.proposal_short_id()))
+                    .map_or(Ok(()), Err)?;
+            }
+            Ok(added.into_iter().map(|(_, status)| status).collect())
+        })
+        .await
+    }
+
     pub(crate) async fn add_orphan(
         &self,
         tx: TransactionView,
@@ -610,17 +832,7 @@ impl TxPoolService {
                 {
                     match ret {
                         Ok(_) => {
-                            let with_vm_2023 = {
-                                let epoch = snapshot
-                                    .tip_header()
-                                    .epoch()
-                                    .minimum_epoch_number_after_n_blocks(1);
-
-                                self.consensus
-                                    .hardfork_switch
-                                    .ckb2023
-                                    .is_vm_version_2_and_syscalls_3_enabled(epoch)
-                            };
+                            let with_vm_2023 = self.with_vm_2023(&snapshot);
                             self.send_result_to_relayer(TxVerificationResult::Ok {
                                 original_peer: Some(orphan.peer),
                                 with_vm_2023,
@@ -1005,6 +1217,91 @@ impl TxPoolService {
         }
     }
 
+    pub(crate) async fn flush_journal(&self) {
+        let mut tx_pool = self.tx_pool.write().await;
+        if let Err(err) = tx_pool.flush_journal() {
+            error!("failed to flush tx-pool journal, error: {:?}", err)
+        }
+    }
+
+    /// Restores the persisted entries with their original timestamps. The entries are verified
+    /// again as the new ones, since the persisted files are not trusted, the persisted cycles are
+    /// only used as the cycles limit. The statuses are derived from the proposals of the new tip as usual, which are the
+    /// persisted ones unless the proposal windows moved while the node was down.
+    pub(crate) async fn restore_entries(&self, entries: Vec<PersistedEntry>) {
+        if entries.is_empty() {
+            return;
+        }
+        let total = entries.len();
+        info!("Restoring persistent tx-pool entries, total {} txs", total);
+        let mut pending = entries;
+        let mut stale = 0;
+        // a child may be restored before its parent if the parent was replaced,
+        // retry the ones missing inputs as long as some entries are restored in the last round
+        loop {
+            let mut missing_inputs = Vec::new();
+            let mut restored = 0;
+            for entry in pending {
+                match self.restore_entry(&entry).await {
+                    Ok(()) => restored += 1,
+                    Err(reject) if is_missing_input(&reject) => missing_inputs.push(entry),
+                    Err(reject) => {
+                        debug!("restore tx {} failed, reject: {}", entry.tx.hash(), reject);
+                        stale += 1;
+                    }
+                }
+            }
+            if restored == 0 || missing_inputs.is_empty() {
+                stale += missing_inputs.len();
+                break;
+            }
+            pending = missing_inputs;
+        }
+        if stale == 0 {
+            info!("Persistent tx-pool entries are restored");
+        } else {
+            info!(
+                "Persistent tx-pool entries are restored, {} stale txs are ignored",
+                stale
+            );
+        }
+        // compact the journal, which has the stale entries and the restored ones
+        self.save_pool().await;
+    }
+
+    async fn restore_entry(&self, persisted: &PersistedEntry) -> Result<(), Reject> {
+        non_contextual_verify(&self.consensus, &persisted.tx)?;
+        let (ret, snapshot) = self.pre_check(&persisted.tx).await;
+        let (tip_hash, rtx, status, fee, tx_size) = ret?;
+
+        if self.is_in_delay_window(&snapshot) {
+            let mut delay = self.delay.write().await;
+            if delay.len() < DELAY_LIMIT {
+                delay.insert(persisted.tx.proposal_short_id(), persisted.tx.clone());
+            }
+            return Ok(());
+        }
+
+        let tx_env = Arc::new(status.with_env(snapshot.tip_header()));
+        let max_cycles = persisted.cycles.min(self.consensus.max_block_cycles());
+        let verified = verify_rtx(
+            Arc::clone(&snapshot),
+            Arc::clone(&rtx),
+            tx_env,
+            &None,
+            max_cycles,
+            None,
+        )
+        .await?;
+
+        let entry =
+            TxEntry::new_with_timestamp(rtx, verified.cycles, fee, tx_size, persisted.timestamp);
+        let (ret, _) = self.submit_entry(tip_hash, entry, status).await;
+        ret?;
+        self.notify_block_assembler(status).await;
+        Ok(())
+    }
+
     pub(crate) async fn update_ibd_state(&self, in_ibd: bool) {
         self.fee_estimator.update_ibd_state(in_ibd);
     }
@@ -1057,6 +1354,18 @@ impl TxPoolService {
         }
     }
 
+    fn with_vm_2023(&self, snapshot: &Snapshot) -> bool {
+        let epoch = snapshot
+            .tip_header()
+            .epoch()
+            .minimum_epoch_number_after_n_blocks(1);
+
+        self.consensus
+            .hardfork_switch
+            .ckb2023
+            .is_vm_version_2_and_syscalls_3_enabled(epoch)
+    }
+
     pub(crate) fn is_in_delay_window(&self, snapshot: &Snapshot) -> bool {
         let epoch = snapshot.tip_header().epoch();
         self.consensus.is_in_delay_window(&epoch)
@@ -1071,6 +1380,12 @@ type PreCheckedTx = (
     usize,                    // tx size
 );
 
+type PreCheckedPackage = (
+    Byte32, // tip_hash
+    // rtx, status, tx fee and tx size, or none if the tx is already in the pool
+    Vec<Option<(Arc<ResolvedTransaction>, TxStatus, Capacity, usize)>>,
+);
+
 type ResolveResult = Result<(Arc<ResolvedTransaction>, TxStatus), Reject>;
 
 fn get_tx_status(snapshot: &Snapshot, short_id: &ProposalShortId) -> TxStatus {
@@ -1129,6 +1444,117 @@ fn _submit_entry(
     Ok(evicts)
 }
 
+type RejectedEntries = Vec<(TxEntry, Reject)>;
+
+// Adds the package entries in order, rolls back the added ones and restores the evicted ones
+// if any entry fails.
+// Also returns the rejected entries to notify, which are the evicted ones if the package is
+// added, and the one rejected by the policy.
+pub(crate) fn _submit_package(
+    tx_pool: &mut TxPool,
+    snapshot: &Arc<Snapshot>,
+    pre_resolve_tip: &Byte32,
+    entries: &[(TxEntry, TxStatus)],
+) -> (Result<Vec<(TxEntry, TxStatus)>, Reject>, RejectedEntries) {
+    let tip_changed = pre_resolve_tip != &snapshot.tip_hash();
+    let mut added = Vec::with_capacity(entries.len());
+    // the evicted entries and the hash of the tx evicting them
+    let mut evicted = Vec::new();
+    for (entry, status) in entries {
+        match add_package_entry(tx_pool, snapshot, tip_changed, entry, *status) {
+            Ok((succ, status, evicts)) => {
+                let tx_hash = entry.transaction().hash();
+                evicted.extend(evicts.into_iter().map(|evict| (evict, tx_hash.clone())));
+                if succ {
+                    added.push((entry.clone(), status));
+                }
+            }
+            Err(reject) => {
+                for (entry, _) in added.iter().rev() {
+                    tx_pool.pool_map.remove_entry(&entry.proposal_short_id());
+                }
+                let mut rejected = restore_evicted(tx_pool, snapshot, evicted);
+                if matches!(reject, Reject::Policy(_)) {
+                    rejected.push((entry.clone(), reject.clone()));
+                }
+                return (Err(reject), rejected);
+            }
+        }
+    }
+    let rejected = evicted
+        .into_iter()
+        .map(|(evict, tx_hash)| {
+            let reject = Reject::Invalidated(format!("invalidated by tx {tx_hash}"));
+            (evict, reject)
+        })
+        .collect();
+    (Ok(added), rejected)
+}
+
+// Re-adds the entries evicted by a rolled back package, parents first.
+// Returns the ones which can't be re-added, which are invalidated.
+fn restore_evicted(
+    tx_pool: &mut TxPool,
+    snapshot: &Snapshot,
+    mut evicted: Vec<(TxEntry, Byte32)>,
+) -> RejectedEntries {
+    // a child always has more ancestors than its parents
+    evicted.sort_by_key(|(entry, _)| entry.ancestors_count);
+    evicted
+        .into_iter()
+        .filter_map(|(mut entry, tx_hash)| {
+            entry.reset_statistic_state();
+            let ret = match get_tx_status(snapshot, &entry.proposal_short_id()) {
+                TxStatus::Fresh => tx_pool.add_pending(entry.clone()),
+                TxStatus::Gap => tx_pool.add_gap(entry.clone()),
+                TxStatus::Proposed => tx_pool.add_proposed(entry.clone()),
+            };
+            match ret {
+                Ok(_) => None,
+                Err(err) => {
+                    debug!(
+                        "restore evicted {} failed: {}",
+                        entry.transaction().hash(),
+                        err
+                    );
+                    let reject = Reject::Invalidated(format!("invalidated by tx {tx_hash}"));
+                    Some((entry, reject))
+                }
+            }
+        })
+        .collect()
+}
+
+fn add_package_entry(
+    tx_pool: &mut TxPool,
+    snapshot: &Arc<Snapshot>,
+    tip_changed: bool,
+    entry: &TxEntry,
+    mut status: TxStatus,
+) -> Result<(bool, TxStatus, HashSet<TxEntry>), Reject> {
+    // RBF is not supported for packages
+    if let Some(outpoint) = tx_pool.pool_map.find_conflict_outpoint(entry.transaction()) {
+        return Err(Reject::Resolve(OutPointError::Dead(outpoint)));
+    }
+    if tip_changed {
+        debug!(
+            "submit_package {} context changed",
+            entry.proposal_short_id()
+        );
+        status = check_rtx(tx_pool, snapshot, &entry.rtx)?;
+        let tx_env = status.with_env(snapshot.tip_header());
+        time_relative_verify(Arc::clone(snapshot), Arc::clone(&entry.rtx), tx_env)?;
+    }
+    tx_pool.check_admission(entry)?;
+    debug!("submit_package {:?} {}", status, entry.transaction().hash());
+    let (succ, evicts) = match status {
+        TxStatus::Fresh => tx_pool.add_pending(entry.clone())?,
+        TxStatus::Gap => tx_pool.add_gap(entry.clone())?,
+        TxStatus::Proposed => tx_pool.add_proposed(entry.clone())?,
+    };
+    Ok((succ, status, evicts))
+}
+
 fn _update_tx_pool_for_reorg(
     tx_pool: &mut TxPool,
     attached: &LinkedHashSet<TransactionView>,
@@ -6,6 +6,8 @@ use crate::component::orphan::OrphanPool;
 use crate::component::pool_map::{PoolEntry, Status};
 use crate::component::verify_queue::VerifyQueue;
 use crate::error::{handle_recv_error, handle_send_cmd_error, handle_try_send_error};
+use crate::persisted::PersistedData;
+use crate::policy::TxPoolPolicy;
 use crate::pool::TxPool;
 use crate::util::after_delay_window;
 use crate::verify_mgr::VerifyMgr;
@@ -104,6 +106,8 @@ pub(crate) enum Message {
     RemoveLocalTx(Request<Byte32, bool>),
     TestAcceptTx(Request<TransactionView, TestAcceptTxResult>),
     SubmitRemoteTx(Request<(TransactionView, Cycle, PeerIndex), ()>),
+    SubmitLocalPackage(Request<Vec<TransactionView>, SubmitTxResult>),
+    SubmitRemotePackage(Request<(Vec<(TransactionView, Cycle)>, PeerIndex), ()>),
     NotifyTxs(Notify<Vec<TransactionView>>),
     FreshProposalsFilter(Request<Vec<ProposalShortId>, Vec<ProposalShortId>>),
     FetchTxs(Request<HashSet<ProposalShortId>, HashMap<ProposalShortId, TransactionView>>),
@@ -149,6 +153,7 @@ pub struct TxPoolController {
     chunk_tx: Arc<watch::Sender<ChunkCommand>>,
     handle: Handle,
     started: Arc<AtomicBool>,
+    template_updated: Option<Arc<watch::Sender<u64>>>,
 }
 
 macro_rules! send_message {
@@ -212,6 +217,16 @@ impl TxPoolController {
         )
     }
 
+    /// Subscribes the updates of the block template, the watched value is the `work_id` of the
+    /// latest template.
+    ///
+    /// Returns `None` if the block assembler is disabled.
+    pub fn subscribe_block_template(&self) -> Option<watch::Receiver<u64>> {
+        self.template_updated
+            .as_ref()
+            .map(|template_updated| template_updated.subscribe())
+    }
+
     /// Notify new uncle
     pub fn notify_new_uncle(&self, uncle: UncleBlockView) -> Result<(), AnyError> {
         send_notify!(self, NewUncle, uncle)
@@ -267,6 +282,24 @@ impl TxPoolController {
         send_message!(self, SubmitRemoteTx, (tx, declared_cycles, peer))
     }
 
+    /// Submit a package of local txs to tx-pool, parents first.
+    /// The package is accepted or rejected as a whole, and the children can pay for the parents.
+    pub fn submit_local_package(
+        &self,
+        txs: Vec<TransactionView>,
+    ) -> Result<SubmitTxResult, AnyError> {
+        send_message!(self, SubmitLocalPackage, txs)
+    }
+
+    /// Submit a package of remote txs with declared cycles and origin to tx-pool
+    pub async fn submit_remote_package(
+        &self,
+        txs: Vec<(TransactionView, Cycle)>,
+        peer: PeerIndex,
+    ) -> Result<(), AnyError> {
+        send_message!(self, SubmitRemotePackage, (txs, peer))
+    }
+
     /// Receive txs from network, try to add txs to tx-pool
     pub fn notify_txs(&self, txs: Vec<TransactionView>) -> Result<(), AnyError> {
         send_notify!(self, NotifyTxs, txs)
@@ -448,6 +481,7 @@ pub struct TxPoolServiceBuilder {
         mpsc::Receiver<BlockAssemblerMessage>,
     ),
     pub(crate) fee_estimator: FeeEstimator,
+    pub(crate) policy: Option<Arc<dyn TxPoolPolicy>>,
 }
 
 impl TxPoolServiceBuilder {
@@ -467,6 +501,8 @@ impl TxPoolServiceBuilder {
         let signal_receiver: CancellationToken = new_tokio_exit_rx();
         let (chunk_tx, chunk_rx) = watch::channel(ChunkCommand::Resume);
         let started = Arc::new(AtomicBool::new(false));
+        let block_assembler =
+            block_assembler_config.map(|config| BlockAssembler::new(config, Arc::clone(&snapshot)));
 
         let controller = TxPoolController {
             sender,
@@ -474,10 +510,11 @@ impl TxPoolServiceBuilder {
             handle: handle.clone(),
             chunk_tx: Arc::new(chunk_tx),
             started: Arc::clone(&started),
+            template_updated: block_assembler
+                .as_ref()
+                .map(|block_assembler| Arc::clone(&block_assembler.template_updated)),
         };
 
-        let block_assembler =
-            block_assembler_config.map(|config| BlockAssembler::new(config, Arc::clone(&snapshot)));
         let builder = TxPoolServiceBuilder {
             tx_pool_config,
             tx_pool_controller: controller.clone(),
@@ -494,6 +531,7 @@ impl TxPoolServiceBuilder {
             started,
             block_assembler_channel,
             fee_estimator,
+            policy: None,
         };
 
         (builder, controller)
@@ -519,6 +557,11 @@ impl TxPoolServiceBuilder {
         self.callbacks.register_reject(callback);
     }
 
+    /// Register a policy to replace the one built from the config section `tx_pool.policy`
+    pub fn register_policy(&mut self, policy: Arc<dyn TxPoolPolicy>) {
+        self.policy = Some(policy);
+    }
+
     /// Start a background thread tx-pool service by taking ownership of the Builder, and returns a TxPoolController.
     pub fn start(self, network: NetworkController) {
         let consensus = self.snapshot.cloned_consensus();
@@ -528,15 +571,20 @@ impl TxPoolServiceBuilder {
             self.tx_pool_config.max_tx_verify_cycles,
         )));
 
-        let tx_pool = TxPool::new(self.tx_pool_config, self.snapshot);
-        let txs = match tx_pool.load_from_file() {
-            Ok(txs) => txs,
+        let mut tx_pool = TxPool::new(self.tx_pool_config, self.snapshot);
+        if let Some(policy) = self.policy {
+            tx_pool.policy = policy;
+        }
+        let policy = Arc::clone(&tx_pool.policy);
+        let persisted = match tx_pool.load_from_file() {
+            Ok(persisted) => persisted,
             Err(e) => {
                 error!("{}", e.to_string());
                 error!("Failed to load txs from tx-pool persistent data file, all txs are ignored");
-                Vec::new()
+                PersistedData::default()
             }
         };
+        let journal_interval = Duration::from_secs(tx_pool.config.journal_interval_secs);
 
         let (block_assembler_sender, mut block_assembler_receiver) = self.block_assembler_channel;
         let service = TxPoolService {
@@ -554,6 +602,7 @@ impl TxPoolServiceBuilder {
             delay: Arc::new(RwLock::new(LinkedHashMap::new())),
             after_delay: Arc::new(AtomicBool::new(after_delay_window)),
             fee_estimator: self.fee_estimator,
+            policy,
         };
 
         let mut verify_mgr =
@@ -648,6 +697,27 @@ impl TxPoolServiceBuilder {
             }
         }
 
+        if !journal_interval.is_zero() {
+            let journal_service = service.clone();
+            let signal_receiver = self.signal_receiver.clone();
+            self.handle.spawn(async move {
+                let mut interval = tokio::time::interval(journal_interval);
+                interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+                loop {
+                    tokio::select! {
+                        _ = interval.tick() => {
+                            journal_service.flush_journal().await;
+                        }
+                        _ = signal_receiver.cancelled() => {
+                            info!("TxPool journal service received exit signal, exit now");
+                            break
+                        },
+                    }
+                }
+            });
+        }
+
+        let restore_service = service.clone();
         let signal_receiver = self.signal_receiver;
         self.handle.spawn(async move {
             loop {
@@ -684,7 +754,13 @@ impl TxPoolServiceBuilder {
             }
         });
         self.started.store(true, Ordering::Release);
-        if let Err(err) = self.tx_pool_controller.load_persisted_data(txs) {
+        self.handle.spawn(async move {
+            restore_service.restore_entries(persisted.entries).await;
+        });
+        if let Err(err) = self
+            .tx_pool_controller
+            .load_persisted_data(persisted.legacy_txs)
+        {
             error!("Failed to import persistent txs, cause: {}", err);
         }
     }
@@ -706,6 +782,7 @@ pub(crate) struct TxPoolService {
     pub(crate) delay: Arc<RwLock<LinkedHashMap<ProposalShortId, TransactionView>>>,
     pub(crate) after_delay: Arc<AtomicBool>,
     pub(crate) fee_estimator: FeeEstimator,
+    pub(crate) policy: Arc<dyn TxPoolPolicy>,
 }
 
 /// tx verification result
@@ -726,6 +803,15 @@ pub enum TxVerificationResult {
         /// parents hashes
         parents: HashSet<Byte32>,
     },
+    /// package is verified, the txs are parents first
+    PackageOk {
+        /// original peer
+        original_peer: Option<PeerIndex>,
+        /// verified by ckb vm version
+        with_vm_2023: bool,
+        /// transaction hashes
+        tx_hashes: Vec<Byte32>,
+    },
     /// tx is rejected
     Reject {
         /// transaction hash
@@ -807,6 +893,29 @@ async fn process(mut service: TxPoolService, message: Message) {
                 error!("Responder sending submit_tx result failed {:?}", e);
             };
         }
+        Message::SubmitLocalPackage(Request {
+            responder,
+            arguments: txs,
+        }) => {
+            let txs = txs.into_iter().map(|tx| (tx, None)).collect();
+            let result = service.process_package(txs, None).await.map(|_| ());
+            if let Err(e) = responder.send(result) {
+                error!("Responder sending submit_package result failed {:?}", e);
+            };
+        }
+        Message::SubmitRemotePackage(Request {
+            responder,
+            arguments: (txs, peer),
+        }) => {
+            let txs = txs
+                .into_iter()
+                .map(|(tx, declared_cycles)| (tx, Some(declared_cycles)))
+                .collect();
+            let _result = service.process_package(txs, Some(peer)).await;
+            if let Err(e) = responder.send(()) {
+                error!("Responder sending submit_package result failed {:?}", e);
+            };
+        }
         Message::NotifyTxs(Notify { arguments: txs }) => {
             for tx in txs {
                 let _ret = service.resumeble_process_tx(tx, None).await;
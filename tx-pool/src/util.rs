@@ -7,14 +7,17 @@ use ckb_snapshot::Snapshot;
 use ckb_store::data_loader_wrapper::AsDataLoader;
 use ckb_store::ChainStore;
 use ckb_types::core::{
-    cell::ResolvedTransaction, tx_pool::TRANSACTION_SIZE_LIMIT, Capacity, Cycle, EpochNumber,
-    TransactionView,
+    cell::ResolvedTransaction,
+    tx_pool::{MAX_PACKAGE_TXS, TRANSACTION_SIZE_LIMIT},
+    Capacity, Cycle, EpochNumber, TransactionView,
 };
+use ckb_types::packed::Byte32;
 use ckb_verification::{
     cache::{CacheEntry, Completed},
     ContextualTransactionVerifier, DaoScriptSizeVerifier, NonContextualTransactionVerifier,
     TimeRelativeTransactionVerifier, TxVerifyEnv,
 };
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::{sync::watch, task::block_in_place};
 
@@ -26,20 +29,27 @@ pub(crate) fn check_txid_collision(tx_pool: &TxPool, tx: &TransactionView) -> Re
     Ok(())
 }
 
-pub(crate) fn check_tx_fee(
-    tx_pool: &TxPool,
+pub(crate) fn calculate_tx_fee(
     snapshot: &Snapshot,
     rtx: &ResolvedTransaction,
-    tx_size: usize,
 ) -> Result<Capacity, Reject> {
-    let fee = DaoCalculator::new(snapshot.consensus(), &snapshot.borrow_as_data_loader())
+    DaoCalculator::new(snapshot.consensus(), &snapshot.borrow_as_data_loader())
         .transaction_fee(rtx)
         .map_err(|err| {
             Reject::Malformed(
                 format!("{err}"),
                 "expect (outputs capacity) <= (inputs capacity)".to_owned(),
             )
-        })?;
+        })
+}
+
+pub(crate) fn check_tx_fee(
+    tx_pool: &TxPool,
+    snapshot: &Snapshot,
+    rtx: &ResolvedTransaction,
+    tx_size: usize,
+) -> Result<Capacity, Reject> {
+    let fee = calculate_tx_fee(snapshot, rtx)?;
     // Theoretically we cannot use size as weight directly to calculate fee_rate,
     // here min fee rate is used as a cheap check,
     // so we will use size to calculate fee_rate directly
@@ -83,6 +93,50 @@ pub(crate) fn non_contextual_verify(
     Ok(())
 }
 
+/// Checks the package is parents first and connected, every transaction but the last one must be
+/// spent by a later one in the package.
+pub(crate) fn check_package_topology(txs: &[TransactionView]) -> Result<(), Reject> {
+    let malformed = |reason: String| Reject::Malformed(reason, "invalid package".to_owned());
+    if txs.is_empty() || txs.len() > MAX_PACKAGE_TXS {
+        return Err(malformed(format!(
+            "package contains {} transactions, expect 1 to {}",
+            txs.len(),
+            MAX_PACKAGE_TXS
+        )));
+    }
+    let hashes: HashMap<Byte32, usize> = txs
+        .iter()
+        .enumerate()
+        .map(|(index, tx)| (tx.hash(), index))
+        .collect();
+    if hashes.len() != txs.len() {
+        return Err(malformed(
+            "package contains duplicated transactions".to_owned(),
+        ));
+    }
+    let mut has_child = vec![false; txs.len()];
+    for (index, tx) in txs.iter().enumerate() {
+        for parent in tx.unique_parents() {
+            if let Some(&parent_index) = hashes.get(&parent) {
+                if parent_index > index {
+                    return Err(malformed(format!(
+                        "parent {parent} is after the child {} in package",
+                        tx.hash()
+                    )));
+                }
+                has_child[parent_index] = true;
+            }
+        }
+    }
+    if let Some(index) = has_child[..txs.len() - 1].iter().position(|c| !c) {
+        return Err(malformed(format!(
+            "transaction {} is not spent by the package",
+            txs[index].hash()
+        )));
+    }
+    Ok(())
+}
+
 pub(crate) async fn verify_rtx(
     snapshot: Arc<Snapshot>,
     rtx: Arc<ResolvedTransaction>,
@@ -300,6 +300,8 @@ impl CKBAppConfig {
         let indexer_path = mkdir(self.data_dir.join("indexer"))?;
         self.indexer.adjust(root_dir, indexer_path);
 
+        self.notify.adjust(root_dir, &self.data_dir);
+
         if subcommand_name == cli::CMD_RESET_DATA {
             return Ok(self);
         }
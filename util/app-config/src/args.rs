@@ -3,7 +3,7 @@ use ckb_chain_spec::consensus::Consensus;
 use ckb_jsonrpc_types::ScriptHashType;
 use ckb_pow::PowEngine;
 use ckb_systemtime::unix_time_as_millis;
-use ckb_types::packed::Byte32;
+use ckb_types::{packed::Byte32, H256};
 use std::path::PathBuf;
 use std::sync::Arc;
 
@@ -15,6 +15,34 @@ pub struct ExportArgs {
     pub consensus: Consensus,
     /// The target directory to save the exported file.
     pub target: PathBuf,
+    /// The format of the exported file.
+    pub format: ExportFormat,
+    /// The compression applied to each block when exporting in the binary format.
+    pub compression: ExportCompression,
+    /// Export blocks starting from this block number, defaults to the genesis block.
+    pub from: Option<u64>,
+    /// Export blocks up to this block number (inclusive), defaults to the tip block.
+    pub to: Option<u64>,
+}
+
+/// The format of the file written by `ckb export`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// One JSON encoded block per line.
+    #[default]
+    Json,
+    /// Molecule encoded blocks in a binary archive with per-block checksums.
+    Binary,
+}
+
+/// The compression applied to each block of a binary archive.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ExportCompression {
+    /// Blocks are stored as is.
+    #[default]
+    None,
+    /// Blocks are compressed with snappy.
+    Snappy,
 }
 
 #[derive(Debug)]
@@ -61,6 +89,41 @@ pub struct RunArgs {
     /// Whether start in daemon mode
     #[cfg(not(target_os = "windows"))]
     pub daemon: bool,
+    /// The trusted cell snapshot to bootstrap an empty database from.
+    pub cell_snapshot: Option<CellSnapshotArgs>,
+    /// The block archive to back-fill historical block bodies from in the background.
+    pub backfill: Option<PathBuf>,
+}
+
+/// The trusted cell snapshot to bootstrap an empty database from.
+#[derive(Clone, Debug)]
+pub struct CellSnapshotArgs {
+    /// The snapshot file written by `ckb snapshot`.
+    pub path: PathBuf,
+    /// The trusted hash of the snapshot.
+    pub hash: H256,
+}
+
+/// Parsed command line arguments for `ckb snapshot`.
+pub struct SnapshotArgs {
+    /// Parsed `ckb.toml`.
+    pub config: Box<CKBAppConfig>,
+    /// Loaded consensus.
+    pub consensus: Consensus,
+    /// The target directory to save the snapshot file.
+    pub target: PathBuf,
+}
+
+/// Parsed command line arguments for `ckb backup`.
+pub struct BackupArgs {
+    /// Parsed `ckb.toml`.
+    pub config: Box<CKBAppConfig>,
+    /// Loaded consensus.
+    pub consensus: Consensus,
+    /// The backup directory to create.
+    pub target: Option<PathBuf>,
+    /// The backup directory to restore from.
+    pub restore: Option<PathBuf>,
 }
 
 /// Enable profile on blocks in the range `[from, to]`.
@@ -1,6 +1,10 @@
+use ckb_types::H256;
 use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
 
+const DEFAULT_EXTRANONCE1_SIZE: usize = 4;
+const DEFAULT_STRATUM_MAX_CONNECTIONS: usize = 1024;
+
 /// Miner config options.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -37,6 +41,8 @@ pub enum WorkerConfig {
     Dummy(DummyConfig),
     /// Eaglesong worker which solves Eaglesong PoW.
     EaglesongSimple(EaglesongSimpleConfig),
+    /// Stratum server which hands out the work to the external mining hardware.
+    Stratum(StratumConfig),
 }
 
 /// Dummy worker config options.
@@ -83,6 +89,38 @@ pub struct EaglesongSimpleConfig {
     pub extra_hash_function: Option<ExtraHashFunction>,
 }
 
+/// Stratum server config options.
+///
+/// Each connection is assigned an extranonce of `extranonce1_size` bytes as the prefix of the
+/// 16-byte nonce, and the miner searches the remaining bytes.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct StratumConfig {
+    /// The address to listen on for the stratum connections.
+    pub listen: SocketAddr,
+    /// The target a share must meet.
+    ///
+    /// It should be easier than the block target, so the miners report their progress
+    /// in shares. The block target is used instead if it is easier.
+    pub share_target: H256,
+    /// The size in bytes of the extranonce assigned to each connection, between 1 and 8.
+    #[serde(default = "default_extranonce1_size")]
+    pub extranonce1_size: usize,
+    /// The maximum number of the connections, the new connections beyond it are closed.
+    ///
+    /// It can't exceed the number of the extranonce1 values, `256 ^ extranonce1_size`.
+    #[serde(default = "default_stratum_max_connections")]
+    pub max_connections: usize,
+}
+
+fn default_extranonce1_size() -> usize {
+    DEFAULT_EXTRANONCE1_SIZE
+}
+
+fn default_stratum_max_connections() -> usize {
+    DEFAULT_STRATUM_MAX_CONNECTIONS
+}
+
 /// Specifies the hash function.
 #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, Eq)]
 pub enum ExtraHashFunction {
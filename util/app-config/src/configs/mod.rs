@@ -17,17 +17,22 @@ pub use indexer::{IndexerConfig, IndexerSyncConfig};
 pub use memory_tracker::Config as MemoryTrackerConfig;
 pub use miner::{
     ClientConfig as MinerClientConfig, Config as MinerConfig, DummyConfig, EaglesongSimpleConfig,
-    ExtraHashFunction, WorkerConfig as MinerWorkerConfig,
+    ExtraHashFunction, StratumConfig, WorkerConfig as MinerWorkerConfig,
 };
 pub use network::{
-    default_support_all_protocols, Config as NetworkConfig, HeaderMapConfig, SupportProtocol,
-    SyncConfig,
+    default_support_all_protocols, Config as NetworkConfig, HeaderMapConfig, OnionConfig,
+    ProxyConfig, SupportProtocol, SyncConfig,
 };
 pub use network_alert::Config as NetworkAlertConfig;
-pub use notify::Config as NotifyConfig;
-pub use rich_indexer::{DBDriver, RichIndexerConfig};
-pub use rpc::{Config as RpcConfig, Module as RpcModule};
+pub use notify::{Config as NotifyConfig, WebhookConfig, WebhookTopic};
+pub use rich_indexer::{DBDriver, RichIndexerConfig, RichIndexerUdtScript};
+pub use rpc::{
+    AuthConfig as RpcAuthConfig, Config as RpcConfig, Module as RpcModule,
+    RateLimitConfig as RpcRateLimitConfig,
+};
 pub use store::Config as StoreConfig;
-pub use tx_pool::{BlockAssemblerConfig, TxPoolConfig};
+pub use tx_pool::{
+    BlockAssemblerConfig, PolicyScript, RewardTarget, TxPoolConfig, TxPoolPolicyConfig,
+};
 
 pub use network::{generate_random_key, read_secret_key, write_secret_to_file};
@@ -6,6 +6,7 @@ use std::fs;
 use std::io::{Error, ErrorKind, Read, Write};
 use std::path::PathBuf;
 use ubyte::ByteUnit;
+use url::Url;
 
 // Max data size in send buffer: 24MB (a little larger than max frame length)
 const DEFAULT_SEND_BUFFER: usize = 24 * 1024 * 1024;
@@ -91,6 +92,12 @@ pub struct Config {
     pub sync: SyncConfig,
     /// Tentacle inner channel_size.
     pub channel_size: Option<usize>,
+    /// Proxy config options for the outbound connections.
+    #[serde(default)]
+    pub proxy: ProxyConfig,
+    /// Onion service config options.
+    #[serde(default)]
+    pub onion: OnionConfig,
 
     #[cfg(target_family = "wasm")]
     #[serde(skip)]
@@ -112,6 +119,33 @@ pub struct SyncConfig {
     pub min_chain_work: U256,
 }
 
+/// Proxy config options.
+///
+/// The outbound connections are dialed through the SOCKS5 proxy, such as the one of a Tor client.
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ProxyConfig {
+    /// The SOCKS5 proxy url, such as `socks5://127.0.0.1:9050`.
+    ///
+    /// The username and password in the url are used to authenticate to the proxy.
+    pub url: Option<Url>,
+    /// Only dial the onion addresses through the proxy, and dial the others directly.
+    #[serde(default)]
+    pub onion_only: bool,
+}
+
+/// Onion service config options.
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct OnionConfig {
+    /// The onion address of this node to advertise to the peers, such as
+    /// `/onion3/<service-id>:8115`.
+    ///
+    /// The onion service must be set up in the Tor client to forward to one of the
+    /// `listen_addresses`.
+    pub external_address: Option<Multiaddr>,
+}
+
 /// Header map config options.
 ///
 /// Header map stores the block headers before fully verifying the block.
@@ -346,8 +380,11 @@ impl Config {
     }
 
     /// Checks whether the DNS seeding service should be enabled.
+    ///
+    /// It's disabled when all the connections are dialed through the proxy, since the DNS
+    /// queries are sent directly.
     pub fn dns_seeding_service_enabled(&self) -> bool {
-        !self.dns_seeds.is_empty()
+        !self.dns_seeds.is_empty() && (self.proxy.url.is_none() || self.proxy.onion_only)
     }
 }
 
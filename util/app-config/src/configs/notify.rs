@@ -1,4 +1,7 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
 /// Notify config options.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default, Eq)]
 #[serde(deny_unknown_fields)]
@@ -7,6 +10,12 @@ pub struct Config {
     ///
     /// The script is called with the block hash as the argument.
     pub new_block_notify_script: Option<String>,
+    /// An executable script to be called whenever there's a chain reorganization.
+    ///
+    /// The script is called with the fork point block hash as the first argument, and the comma
+    /// separated hashes of the detached blocks and the attached blocks as the second and the
+    /// third arguments.
+    pub chain_reorg_notify_script: Option<String>,
     /// An executable script to be called whenever there's a new network alert received.
     ///
     /// The script is called with the alert message as the argument.
@@ -23,6 +32,78 @@ pub struct Config {
     /// Notify alert timeout in milliseconds
     #[serde(default, deserialize_with = "at_least_100")]
     pub script_timeout: Option<u64>,
+
+    /// HTTP endpoints to be POSTed the JSON payloads of the events.
+    ///
+    /// A URL configured more than once is only used once, with the options of the first entry.
+    #[serde(default, deserialize_with = "dedup_webhooks")]
+    pub webhooks: Vec<WebhookConfig>,
+    /// The file to save the webhook events which are not delivered yet, so they survive restarts.
+    ///
+    /// Default is `webhook_outbox.json` in the data directory.
+    #[serde(default)]
+    pub webhook_outbox: PathBuf,
+    /// The max number of the undelivered events kept for each webhook, the oldest ones are dropped
+    /// when it is exceeded. Default is 1000.
+    pub webhook_outbox_size: Option<usize>,
+}
+
+/// A webhook endpoint.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookConfig {
+    /// The URL to POST the events to.
+    ///
+    /// Only the `http` scheme is supported, a TLS endpoint has to be served through a local proxy.
+    #[serde(deserialize_with = "http_url")]
+    pub url: String,
+    /// The topics of the events to be posted, all the topics if it is empty.
+    #[serde(default)]
+    pub topics: Vec<WebhookTopic>,
+    /// The secret to sign the payloads with HMAC-SHA256.
+    ///
+    /// The signature is sent in the `X-CKB-Signature` header as `sha256=<hex>`.
+    pub secret: Option<String>,
+    /// The timeout of a request in milliseconds. Default is 10000.
+    #[serde(default, deserialize_with = "at_least_100")]
+    pub timeout: Option<u64>,
+    /// The max number of retries of a failed event, it is dropped after that. Default is 10.
+    ///
+    /// An event rejected with a 4xx status other than 408 and 429 is dropped without retries.
+    pub max_retries: Option<u32>,
+}
+
+/// The topics of the webhook events.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookTopic {
+    /// A new tip block, the data is the header of it.
+    NewTip,
+    /// A chain reorganization, the data has the fork point and the detached and attached blocks.
+    ChainReorg,
+    /// A transaction accepted by the tx-pool, the data is the pool transaction entry.
+    NewTransaction,
+    /// A transaction rejected by the tx-pool, the data is the pool transaction entry and the
+    /// reject reason.
+    RejectedTransaction,
+    /// A network alert, the data is the alert.
+    NetworkAlert,
+}
+
+impl Config {
+    /// Canonicalizes paths in the config options.
+    ///
+    /// If `self.webhook_outbox` is not set, set it to `data_dir / webhook_outbox.json`.
+    ///
+    /// If `self.webhook_outbox` is relative, convert it to absolute path using `root_dir` as
+    /// current working directory.
+    pub fn adjust<P: AsRef<Path>>(&mut self, root_dir: &Path, data_dir: P) {
+        if self.webhook_outbox.to_str().is_none() || self.webhook_outbox.to_str() == Some("") {
+            self.webhook_outbox = data_dir.as_ref().join("webhook_outbox.json");
+        } else if self.webhook_outbox.is_relative() {
+            self.webhook_outbox = root_dir.join(&self.webhook_outbox);
+        }
+    }
 }
 
 fn at_least_100<'de, D>(d: D) -> Result<Option<u64>, D::Error>
@@ -42,6 +123,30 @@ where
     Ok(op)
 }
 
+fn http_url<'de, D>(d: D) -> Result<String, D::Error>
+where
+    D: serde::de::Deserializer<'de>,
+{
+    let value = String::deserialize(d)?;
+    match url::Url::parse(&value) {
+        Ok(url) if url.scheme() == "http" && url.has_host() => Ok(value),
+        _ => Err(serde::de::Error::invalid_value(
+            serde::de::Unexpected::Str(&value),
+            &"an http:// URL, https is not supported",
+        )),
+    }
+}
+
+fn dedup_webhooks<'de, D>(d: D) -> Result<Vec<WebhookConfig>, D::Error>
+where
+    D: serde::de::Deserializer<'de>,
+{
+    let mut webhooks = Vec::<WebhookConfig>::deserialize(d)?;
+    let mut urls = HashSet::new();
+    webhooks.retain(|webhook| urls.insert(webhook.url.clone()));
+    Ok(webhooks)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -65,4 +170,74 @@ mod tests {
         let ret = toml::from_str::<Config>(s);
         assert!(ret.is_ok());
     }
+
+    #[test]
+    fn test_deserialize_webhooks() {
+        let s = r#"
+        [[webhooks]]
+        url = "http://127.0.0.1:8888"
+        topics = ["new_tip", "chain_reorg"]
+        secret = "secret"
+
+        [[webhooks]]
+        url = "http://127.0.0.1:9999"
+        "#;
+        let config = toml::from_str::<Config>(s).unwrap();
+        assert_eq!(2, config.webhooks.len());
+        assert_eq!(
+            vec![WebhookTopic::NewTip, WebhookTopic::ChainReorg],
+            config.webhooks[0].topics
+        );
+        assert_eq!(Some("secret".to_owned()), config.webhooks[0].secret);
+        assert!(config.webhooks[1].topics.is_empty());
+
+        let s = r#"
+        [[webhooks]]
+        url = "http://127.0.0.1:8888"
+        topics = ["new_block"]
+        "#;
+        assert!(toml::from_str::<Config>(s).is_err());
+
+        // the duplicate URLs are removed, the first entry is kept
+        let s = r#"
+        [[webhooks]]
+        url = "http://127.0.0.1:8888"
+        topics = ["new_tip"]
+
+        [[webhooks]]
+        url = "http://127.0.0.1:9999"
+
+        [[webhooks]]
+        url = "http://127.0.0.1:8888"
+        "#;
+        let config = toml::from_str::<Config>(s).unwrap();
+        assert_eq!(
+            vec!["http://127.0.0.1:8888", "http://127.0.0.1:9999"],
+            config
+                .webhooks
+                .iter()
+                .map(|webhook| webhook.url.as_str())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(vec![WebhookTopic::NewTip], config.webhooks[0].topics);
+    }
+
+    #[test]
+    fn test_reject_non_http_webhooks() {
+        for url in [
+            "https://127.0.0.1:8888",
+            "ftp://127.0.0.1",
+            "127.0.0.1:8888",
+            "http:",
+        ] {
+            let s = format!(
+                r#"
+                [[webhooks]]
+                url = "{url}"
+                "#
+            );
+            let err = toml::from_str::<Config>(&s).unwrap_err();
+            assert!(err.to_string().contains("http://"), "{url}: {err}");
+        }
+    }
 }
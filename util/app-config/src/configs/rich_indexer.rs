@@ -1,3 +1,5 @@
+use ckb_jsonrpc_types::ScriptHashType;
+use ckb_types::{h256, H256};
 use serde::{Deserialize, Serialize};
 use std::{default::Default, path::PathBuf};
 
@@ -49,6 +51,19 @@ pub struct RichIndexerConfig {
     /// The database password.
     #[serde(default = "default_db_password")]
     pub db_password: String,
+    /// The UDT type scripts whose balances are aggregated, default the sUDT and xUDT deployments
+    /// on the mainnet and the testnet.
+    #[serde(default = "default_udt_scripts")]
+    pub udt_scripts: Vec<RichIndexerUdtScript>,
+}
+
+/// A UDT type script, the cells of which keep the amount in the first 16 bytes of the data.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct RichIndexerUdtScript {
+    /// The type script code hash.
+    pub code_hash: H256,
+    /// The type script hash type.
+    pub hash_type: ScriptHashType,
 }
 
 impl Default for RichIndexerConfig {
@@ -61,6 +76,7 @@ impl Default for RichIndexerConfig {
             db_port: default_db_port(),
             db_user: default_db_user(),
             db_password: default_db_password(),
+            udt_scripts: default_udt_scripts(),
         }
     }
 }
@@ -84,3 +100,28 @@ fn default_db_user() -> String {
 fn default_db_password() -> String {
     "123456".to_string()
 }
+
+fn default_udt_scripts() -> Vec<RichIndexerUdtScript> {
+    vec![
+        // sUDT, mainnet
+        RichIndexerUdtScript {
+            code_hash: h256!("0x5e7a36a77e68eecc013dfa2fe6a23f3b6c344b04005808694ae6dd45eea4cfd5"),
+            hash_type: ScriptHashType::Type,
+        },
+        // sUDT, testnet
+        RichIndexerUdtScript {
+            code_hash: h256!("0xc5e5dcf215925f7ef4dfaf5f4b4f105bc321c02776d6e7d52a1db3fcd9d011a4"),
+            hash_type: ScriptHashType::Type,
+        },
+        // xUDT, mainnet
+        RichIndexerUdtScript {
+            code_hash: h256!("0x50bd8d6680b8b9cf98b73f3c08faf8b2a21914311954118ad6609be6e78a1b95"),
+            hash_type: ScriptHashType::Data1,
+        },
+        // xUDT, testnet
+        RichIndexerUdtScript {
+            code_hash: h256!("0x25c29dc317811a6f6f3985a7a9ebc4838bd388d19d0feeecf0bcd60f6c0975bb"),
+            hash_type: ScriptHashType::Type,
+        },
+    ]
+}
@@ -57,6 +57,67 @@ pub struct Config {
     /// Customized extra well known type scripts.
     #[serde(default)]
     pub extra_well_known_type_scripts: Vec<Script>,
+    /// Access control rules.
+    ///
+    /// When it is empty, all the enabled modules are accessible without authentication.
+    /// Otherwise every request must carry a credential matching one of the rules, and can only
+    /// call the methods granted by that rule.
+    #[serde(default)]
+    pub auth: Vec<AuthConfig>,
+    /// Rate limit rules.
+    ///
+    /// Each client, identified by the matched `auth` rule or by the IP address when `auth` is
+    /// empty, has its own token buckets.
+    #[serde(default)]
+    pub rate_limits: Vec<RateLimitConfig>,
+    /// Number of the latest messages kept for each subscription topic, so a subscriber can
+    /// resume from the last sequence number it has received.
+    #[serde(default)]
+    pub subscription_replay_size: Option<usize>,
+}
+
+/// RPC access control rule.
+///
+/// A rule authenticates requests either by a bearer token (`Authorization: Bearer <token>`) or
+/// by HTTP basic auth (`Authorization: Basic <base64(username:password)>`).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct AuthConfig {
+    /// Bearer token.
+    #[serde(default)]
+    pub token: Option<String>,
+    /// User name of HTTP basic auth.
+    #[serde(default)]
+    pub username: Option<String>,
+    /// Password of HTTP basic auth.
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Modules whose methods are all granted.
+    #[serde(default)]
+    pub modules: Vec<Module>,
+    /// Individual methods granted in addition to `modules`.
+    #[serde(default)]
+    pub methods: Vec<String>,
+}
+
+/// RPC rate limit rule, a token bucket which applies to a module or a method.
+///
+/// When a method matches both a method rule and a module rule, only the method rule applies.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimitConfig {
+    /// Limited module.
+    #[serde(default)]
+    pub module: Option<Module>,
+    /// Limited method.
+    #[serde(default)]
+    pub method: Option<String>,
+    /// Sustained number of requests per second allowed for each client, must be positive.
+    pub requests_per_second: u32,
+    /// Max number of requests allowed in a burst, defaults to `requests_per_second`, must be
+    /// positive if set.
+    #[serde(default)]
+    pub burst: Option<u32>,
 }
 
 impl Config {
@@ -18,4 +18,9 @@ pub struct Config {
     pub block_extensions_cache_size: usize,
     /// whether enable freezer
     pub freezer_enable: bool,
+    /// Keep only the bodies of the latest `prune_depth` blocks, the older block bodies are
+    /// deleted while headers and the live cell set are kept.
+    ///
+    /// The freezer is disabled in the pruned mode.
+    pub prune_depth: Option<u64>,
 }
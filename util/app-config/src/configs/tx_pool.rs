@@ -2,6 +2,7 @@ use ckb_jsonrpc_types::{FeeRateDef, JsonBytes, ScriptHashType};
 use ckb_types::core::{Cycle, FeeRate};
 use ckb_types::H256;
 use serde::{Deserialize, Serialize};
+use std::iter;
 use std::path::{Path, PathBuf};
 use url::Url;
 
@@ -37,6 +38,47 @@ pub struct TxPoolConfig {
     pub recent_reject: PathBuf,
     /// The expiration time for pool transactions in hours
     pub expiry_hours: u8,
+    /// The interval in seconds to append the tx-pool changes to the journal beside `persisted_data`,
+    /// so the pool can be restored after a crash. 0 disables the journal.
+    pub journal_interval_secs: u64,
+    /// The admission and eviction policies
+    pub policy: TxPoolPolicyConfig,
+}
+
+/// Tx-pool admission and eviction policy options.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct TxPoolPolicyConfig {
+    /// The transactions which have an input matching these scripts are never evicted when the
+    /// pool is full, never expired, and are not limited by `max_txs_per_sender_lock`, until they
+    /// take up `max_kept_tx_size` bytes.
+    #[serde(default)]
+    pub keep_scripts: Vec<PolicyScript>,
+    /// The max total size of the transactions kept by `keep_scripts`, the ones beyond it are
+    /// treated as the others. Default is a quarter of `max_tx_pool_size`.
+    pub max_kept_tx_size: Option<usize>,
+    /// The transactions which have an input or output matching these scripts are evicted after
+    /// the others when the pool is full.
+    #[serde(default)]
+    pub priority_scripts: Vec<PolicyScript>,
+    /// The max number of in-pool transactions which spend the cells of the same lock script.
+    pub max_txs_per_sender_lock: Option<usize>,
+    /// The max number of transactions a peer can relay to the pool in `peer_quota_interval_secs`.
+    pub max_txs_per_peer: Option<usize>,
+    /// The interval of the per-peer quota in seconds, default is 60.
+    pub peer_quota_interval_secs: Option<u64>,
+}
+
+/// A script pattern in the tx-pool policies, it matches both the lock and the type scripts.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct PolicyScript {
+    /// The script code hash.
+    pub code_hash: H256,
+    /// The script hash type.
+    pub hash_type: ScriptHashType,
+    /// The script args, any args match if it is not set.
+    pub args: Option<JsonBytes>,
 }
 
 /// Block assembler config options.
@@ -71,6 +113,45 @@ pub struct BlockAssemblerConfig {
     /// Notify timeout
     #[serde(default = "default_notify_timeout_millis")]
     pub notify_timeout_millis: u64,
+    /// The weight of the miner lock script among the reward targets, default is 1.
+    #[serde(default = "default_reward_weight")]
+    pub weight: u32,
+    /// More lock scripts to claim the miner rewards besides the miner lock script.
+    ///
+    /// A cellbase can only name one lock script to receive the block reward, so the rewards are
+    /// split by rotating the lock scripts over the block numbers, each one is named in `weight`
+    /// blocks out of every total weight blocks.
+    #[serde(default)]
+    pub reward_targets: Vec<RewardTarget>,
+}
+
+/// A lock script to claim the miner rewards, see `BlockAssemblerConfig::reward_targets`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct RewardTarget {
+    /// The lock script code hash.
+    pub code_hash: H256,
+    /// The lock script args.
+    pub args: JsonBytes,
+    /// The lock script hash type.
+    pub hash_type: ScriptHashType,
+    /// The weight of the lock script among the reward targets, default is 1.
+    #[serde(default = "default_reward_weight")]
+    pub weight: u32,
+}
+
+impl BlockAssemblerConfig {
+    /// Returns all the reward targets, the miner lock script first.
+    pub fn all_reward_targets(&self) -> Vec<RewardTarget> {
+        iter::once(RewardTarget {
+            code_hash: self.code_hash.clone(),
+            args: self.args.clone(),
+            hash_type: self.hash_type.clone(),
+            weight: self.weight,
+        })
+        .chain(self.reward_targets.iter().cloned())
+        .collect()
+    }
 }
 
 const fn default_use_binary_version_as_message_prefix() -> bool {
@@ -85,6 +166,10 @@ const fn default_notify_timeout_millis() -> u64 {
     800
 }
 
+const fn default_reward_weight() -> u32 {
+    1
+}
+
 impl TxPoolConfig {
     /// Canonicalizes paths in the config options.
     ///
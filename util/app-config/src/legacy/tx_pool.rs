@@ -1,3 +1,4 @@
+use crate::TxPoolPolicyConfig;
 use ckb_chain_spec::consensus::TWO_IN_TWO_OUT_CYCLES;
 use ckb_jsonrpc_types::FeeRateDef;
 use ckb_types::core::{Cycle, FeeRate};
@@ -17,6 +18,8 @@ const DEFAULT_MAX_ANCESTORS_COUNT: usize = 2_000;
 const DEFAULT_EXPIRY_HOURS: u8 = 12;
 // Default max_tx_pool_size 180mb
 const DEFAULT_MAX_TX_POOL_SIZE: usize = 180_000_000;
+// Default interval to append the tx-pool changes to the journal in seconds
+const DEFAULT_JOURNAL_INTERVAL_SECS: u64 = 10;
 
 #[derive(Clone, Debug, Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -45,6 +48,10 @@ pub(crate) struct TxPoolConfig {
     recent_reject: PathBuf,
     #[serde(default = "default_expiry_hours")]
     expiry_hours: u8,
+    #[serde(default = "default_journal_interval_secs")]
+    journal_interval_secs: u64,
+    #[serde(default)]
+    policy: TxPoolPolicyConfig,
 }
 
 fn default_keep_rejected_tx_hashes_days() -> u8 {
@@ -59,6 +66,10 @@ fn default_expiry_hours() -> u8 {
     DEFAULT_EXPIRY_HOURS
 }
 
+fn default_journal_interval_secs() -> u64 {
+    DEFAULT_JOURNAL_INTERVAL_SECS
+}
+
 fn default_max_tx_pool_size() -> usize {
     DEFAULT_MAX_TX_POOL_SIZE
 }
@@ -91,6 +102,8 @@ impl Default for TxPoolConfig {
             persisted_data: Default::default(),
             recent_reject: Default::default(),
             expiry_hours: DEFAULT_EXPIRY_HOURS,
+            journal_interval_secs: DEFAULT_JOURNAL_INTERVAL_SECS,
+            policy: Default::default(),
         }
     }
 }
@@ -113,6 +126,8 @@ impl From<TxPoolConfig> for crate::TxPoolConfig {
             persisted_data,
             recent_reject,
             expiry_hours,
+            journal_interval_secs,
+            policy,
         } = input;
 
         Self {
@@ -126,6 +141,8 @@ impl From<TxPoolConfig> for crate::TxPoolConfig {
             persisted_data,
             recent_reject,
             expiry_hours,
+            journal_interval_secs,
+            policy,
         }
     }
 }
@@ -15,8 +15,9 @@ pub use app_config::{
     AppConfig, CKBAppConfig, ChainConfig, LogConfig, MetricsConfig, MinerAppConfig,
 };
 pub use args::{
-    CustomizeSpec, DaemonArgs, ExportArgs, ImportArgs, InitArgs, MigrateArgs, MinerArgs,
-    PeerIDArgs, ReplayArgs, ResetDataArgs, RunArgs, StatsArgs,
+    BackupArgs, CellSnapshotArgs, CustomizeSpec, DaemonArgs, ExportArgs, ExportCompression,
+    ExportFormat, ImportArgs, InitArgs, MigrateArgs, MinerArgs, PeerIDArgs, ReplayArgs,
+    ResetDataArgs, RunArgs, SnapshotArgs, StatsArgs,
 };
 
 pub use configs::*;
@@ -7,31 +7,43 @@ use ckb_types::{core::BlockNumber, core::BlockView};
 pub struct ChainIterator<'a, S: ChainStore> {
     store: &'a S,
     current: Option<BlockView>,
+    from: BlockNumber,
     tip: BlockNumber,
 }
 
 impl<'a, S: ChainStore> ChainIterator<'a, S> {
     /// TODO(doc): @quake
     pub fn new(store: &'a S) -> Self {
-        let current = store.get_block_hash(0).and_then(|h| store.get_block(&h));
-        let tip = store.get_tip_header().expect("store inited").number();
+        Self::with_range(store, 0, None)
+    }
+
+    /// Creates an iterator over the main chain blocks from `from` to `to` (inclusive).
+    ///
+    /// `to` is capped at the current tip, and defaults to the tip when it is `None`.
+    pub fn with_range(store: &'a S, from: BlockNumber, to: Option<BlockNumber>) -> Self {
+        let tip_number = store.get_tip_header().expect("store inited").number();
+        let tip = to.map_or(tip_number, |to| to.min(tip_number));
+        let current = if from <= tip {
+            store.get_block_hash(from).and_then(|h| store.get_block(&h))
+        } else {
+            None
+        };
         ChainIterator {
             store,
             current,
+            from,
             tip,
         }
     }
 
     /// TODO(doc): @quake
     pub fn len(&self) -> u64 {
-        self.tip + 1
+        (self.tip + 1).saturating_sub(self.from)
     }
 
     /// Returns true if the ChainIterator has a length of 0.
-    // we always have genesis, this function may be meaningless
-    // but for convention, mute len-without-is-empty lint
     pub fn is_empty(&self) -> bool {
-        false
+        self.len() == 0
     }
 }
 
@@ -42,14 +54,14 @@ impl<'a, S: ChainStore> Iterator for ChainIterator<'a, S> {
         let current = self.current.take();
 
         self.current = match current {
-            Some(ref b) => {
+            Some(ref b) if b.header().number() < self.tip => {
                 if let Some(block_hash) = self.store.get_block_hash(b.header().number() + 1) {
                     self.store.get_block(&block_hash)
                 } else {
                     None
                 }
             }
-            None => None,
+            _ => None,
         };
         current
     }
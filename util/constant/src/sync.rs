@@ -44,6 +44,10 @@ pub const EVICTION_HEADERS_RESPONSE_TIME: u64 = 120 * 1000; // 2 minutes
 /// The maximum number of entries in a locator
 pub const MAX_LOCATOR_SIZE: usize = 101;
 
+/// The minimum number of recent blocks whose bodies are kept by a pruned node, so that the chain
+/// is able to roll back on reorganizations and the peers can download the recent blocks from it.
+pub const MIN_PRUNE_DEPTH: u64 = 10_000;
+
 /// Block download timeout
 pub const BLOCK_DOWNLOAD_TIMEOUT: u64 = 30 * 1000; // 30s
 
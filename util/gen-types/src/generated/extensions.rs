@@ -5892,7 +5892,7 @@ impl RelayMessage {
         0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
         0, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
     ];
-    pub const ITEMS_COUNT: usize = 8;
+    pub const ITEMS_COUNT: usize = 9;
     pub fn item_id(&self) -> molecule::Number {
         molecule::unpack_number(self.as_slice())
     }
@@ -5907,6 +5907,7 @@ impl RelayMessage {
             5 => BlockTransactions::new_unchecked(inner).into(),
             6 => GetBlockProposal::new_unchecked(inner).into(),
             7 => BlockProposal::new_unchecked(inner).into(),
+            8 => RelayTransactionPackage::new_unchecked(inner).into(),
             _ => panic!("{}: invalid data", Self::NAME),
         }
     }
@@ -5963,7 +5964,7 @@ impl<'r> ::core::fmt::Display for RelayMessageReader<'r> {
     }
 }
 impl<'r> RelayMessageReader<'r> {
-    pub const ITEMS_COUNT: usize = 8;
+    pub const ITEMS_COUNT: usize = 9;
     pub fn item_id(&self) -> molecule::Number {
         molecule::unpack_number(self.as_slice())
     }
@@ -5978,6 +5979,7 @@ impl<'r> RelayMessageReader<'r> {
             5 => BlockTransactionsReader::new_unchecked(inner).into(),
             6 => GetBlockProposalReader::new_unchecked(inner).into(),
             7 => BlockProposalReader::new_unchecked(inner).into(),
+            8 => RelayTransactionPackageReader::new_unchecked(inner).into(),
             _ => panic!("{}: invalid data", Self::NAME),
         }
     }
@@ -6011,6 +6013,7 @@ impl<'r> molecule::prelude::Reader<'r> for RelayMessageReader<'r> {
             5 => BlockTransactionsReader::verify(inner_slice, compatible),
             6 => GetBlockProposalReader::verify(inner_slice, compatible),
             7 => BlockProposalReader::verify(inner_slice, compatible),
+            8 => RelayTransactionPackageReader::verify(inner_slice, compatible),
             _ => ve!(Self, UnknownItem, Self::ITEMS_COUNT, item_id),
         }?;
         Ok(())
@@ -6019,7 +6022,7 @@ impl<'r> molecule::prelude::Reader<'r> for RelayMessageReader<'r> {
 #[derive(Debug, Default)]
 pub struct RelayMessageBuilder(pub(crate) RelayMessageUnion);
 impl RelayMessageBuilder {
-    pub const ITEMS_COUNT: usize = 8;
+    pub const ITEMS_COUNT: usize = 9;
     pub fn set<I>(mut self, v: I) -> Self
     where
         I: ::core::convert::Into<RelayMessageUnion>,
@@ -6055,6 +6058,7 @@ pub enum RelayMessageUnion {
     BlockTransactions(BlockTransactions),
     GetBlockProposal(GetBlockProposal),
     BlockProposal(BlockProposal),
+    RelayTransactionPackage(RelayTransactionPackage),
 }
 #[derive(Debug, Clone, Copy)]
 pub enum RelayMessageUnionReader<'r> {
@@ -6066,6 +6070,7 @@ pub enum RelayMessageUnionReader<'r> {
     BlockTransactions(BlockTransactionsReader<'r>),
     GetBlockProposal(GetBlockProposalReader<'r>),
     BlockProposal(BlockProposalReader<'r>),
+    RelayTransactionPackage(RelayTransactionPackageReader<'r>),
 }
 impl ::core::default::Default for RelayMessageUnion {
     fn default() -> Self {
@@ -6117,6 +6122,15 @@ impl ::core::fmt::Display for RelayMessageUnion {
             RelayMessageUnion::BlockProposal(ref item) => {
                 write!(f, "{}::{}({})", Self::NAME, BlockProposal::NAME, item)
             }
+            RelayMessageUnion::RelayTransactionPackage(ref item) => {
+                write!(
+                    f,
+                    "{}::{}({})",
+                    Self::NAME,
+                    RelayTransactionPackage::NAME,
+                    item
+                )
+            }
         }
     }
 }
@@ -6165,6 +6179,15 @@ impl<'r> ::core::fmt::Display for RelayMessageUnionReader<'r> {
             RelayMessageUnionReader::BlockProposal(ref item) => {
                 write!(f, "{}::{}({})", Self::NAME, BlockProposal::NAME, item)
             }
+            RelayMessageUnionReader::RelayTransactionPackage(ref item) => {
+                write!(
+                    f,
+                    "{}::{}({})",
+                    Self::NAME,
+                    RelayTransactionPackage::NAME,
+                    item
+                )
+            }
         }
     }
 }
@@ -6179,6 +6202,7 @@ impl RelayMessageUnion {
             RelayMessageUnion::BlockTransactions(ref item) => write!(f, "{}", item),
             RelayMessageUnion::GetBlockProposal(ref item) => write!(f, "{}", item),
             RelayMessageUnion::BlockProposal(ref item) => write!(f, "{}", item),
+            RelayMessageUnion::RelayTransactionPackage(ref item) => write!(f, "{}", item),
         }
     }
 }
@@ -6193,6 +6217,7 @@ impl<'r> RelayMessageUnionReader<'r> {
             RelayMessageUnionReader::BlockTransactions(ref item) => write!(f, "{}", item),
             RelayMessageUnionReader::GetBlockProposal(ref item) => write!(f, "{}", item),
             RelayMessageUnionReader::BlockProposal(ref item) => write!(f, "{}", item),
+            RelayMessageUnionReader::RelayTransactionPackage(ref item) => write!(f, "{}", item),
         }
     }
 }
@@ -6236,6 +6261,11 @@ impl ::core::convert::From<BlockProposal> for RelayMessageUnion {
         RelayMessageUnion::BlockProposal(item)
     }
 }
+impl ::core::convert::From<RelayTransactionPackage> for RelayMessageUnion {
+    fn from(item: RelayTransactionPackage) -> Self {
+        RelayMessageUnion::RelayTransactionPackage(item)
+    }
+}
 impl<'r> ::core::convert::From<CompactBlockReader<'r>> for RelayMessageUnionReader<'r> {
     fn from(item: CompactBlockReader<'r>) -> Self {
         RelayMessageUnionReader::CompactBlock(item)
@@ -6276,6 +6306,11 @@ impl<'r> ::core::convert::From<BlockProposalReader<'r>> for RelayMessageUnionRea
         RelayMessageUnionReader::BlockProposal(item)
     }
 }
+impl<'r> ::core::convert::From<RelayTransactionPackageReader<'r>> for RelayMessageUnionReader<'r> {
+    fn from(item: RelayTransactionPackageReader<'r>) -> Self {
+        RelayMessageUnionReader::RelayTransactionPackage(item)
+    }
+}
 impl RelayMessageUnion {
     pub const NAME: &'static str = "RelayMessageUnion";
     pub fn as_bytes(&self) -> molecule::bytes::Bytes {
@@ -6288,6 +6323,7 @@ impl RelayMessageUnion {
             RelayMessageUnion::BlockTransactions(item) => item.as_bytes(),
             RelayMessageUnion::GetBlockProposal(item) => item.as_bytes(),
             RelayMessageUnion::BlockProposal(item) => item.as_bytes(),
+            RelayMessageUnion::RelayTransactionPackage(item) => item.as_bytes(),
         }
     }
     pub fn as_slice(&self) -> &[u8] {
@@ -6300,6 +6336,7 @@ impl RelayMessageUnion {
             RelayMessageUnion::BlockTransactions(item) => item.as_slice(),
             RelayMessageUnion::GetBlockProposal(item) => item.as_slice(),
             RelayMessageUnion::BlockProposal(item) => item.as_slice(),
+            RelayMessageUnion::RelayTransactionPackage(item) => item.as_slice(),
         }
     }
     pub fn item_id(&self) -> molecule::Number {
@@ -6312,6 +6349,7 @@ impl RelayMessageUnion {
             RelayMessageUnion::BlockTransactions(_) => 5,
             RelayMessageUnion::GetBlockProposal(_) => 6,
             RelayMessageUnion::BlockProposal(_) => 7,
+            RelayMessageUnion::RelayTransactionPackage(_) => 8,
         }
     }
     pub fn item_name(&self) -> &str {
@@ -6324,6 +6362,7 @@ impl RelayMessageUnion {
             RelayMessageUnion::BlockTransactions(_) => "BlockTransactions",
             RelayMessageUnion::GetBlockProposal(_) => "GetBlockProposal",
             RelayMessageUnion::BlockProposal(_) => "BlockProposal",
+            RelayMessageUnion::RelayTransactionPackage(_) => "RelayTransactionPackage",
         }
     }
     pub fn as_reader<'r>(&'r self) -> RelayMessageUnionReader<'r> {
@@ -6336,6 +6375,7 @@ impl RelayMessageUnion {
             RelayMessageUnion::BlockTransactions(item) => item.as_reader().into(),
             RelayMessageUnion::GetBlockProposal(item) => item.as_reader().into(),
             RelayMessageUnion::BlockProposal(item) => item.as_reader().into(),
+            RelayMessageUnion::RelayTransactionPackage(item) => item.as_reader().into(),
         }
     }
 }
@@ -6351,6 +6391,7 @@ impl<'r> RelayMessageUnionReader<'r> {
             RelayMessageUnionReader::BlockTransactions(item) => item.as_slice(),
             RelayMessageUnionReader::GetBlockProposal(item) => item.as_slice(),
             RelayMessageUnionReader::BlockProposal(item) => item.as_slice(),
+            RelayMessageUnionReader::RelayTransactionPackage(item) => item.as_slice(),
         }
     }
     pub fn item_id(&self) -> molecule::Number {
@@ -6363,6 +6404,7 @@ impl<'r> RelayMessageUnionReader<'r> {
             RelayMessageUnionReader::BlockTransactions(_) => 5,
             RelayMessageUnionReader::GetBlockProposal(_) => 6,
             RelayMessageUnionReader::BlockProposal(_) => 7,
+            RelayMessageUnionReader::RelayTransactionPackage(_) => 8,
         }
     }
     pub fn item_name(&self) -> &str {
@@ -6375,6 +6417,7 @@ impl<'r> RelayMessageUnionReader<'r> {
             RelayMessageUnionReader::BlockTransactions(_) => "BlockTransactions",
             RelayMessageUnionReader::GetBlockProposal(_) => "GetBlockProposal",
             RelayMessageUnionReader::BlockProposal(_) => "BlockProposal",
+            RelayMessageUnionReader::RelayTransactionPackage(_) => "RelayTransactionPackage",
         }
     }
 }
@@ -9497,6 +9540,239 @@ impl molecule::prelude::Builder for BlockProposalBuilder {
     }
 }
 #[derive(Clone)]
+pub struct RelayTransactionPackage(molecule::bytes::Bytes);
+impl ::core::fmt::LowerHex for RelayTransactionPackage {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl ::core::fmt::Debug for RelayTransactionPackage {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl ::core::fmt::Display for RelayTransactionPackage {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "transactions", self.transactions())?;
+        let extra_count = self.count_extra_fields();
+        if extra_count != 0 {
+            write!(f, ", .. ({} fields)", extra_count)?;
+        }
+        write!(f, " }}")
+    }
+}
+impl ::core::default::Default for RelayTransactionPackage {
+    fn default() -> Self {
+        let v = molecule::bytes::Bytes::from_static(&Self::DEFAULT_VALUE);
+        RelayTransactionPackage::new_unchecked(v)
+    }
+}
+impl RelayTransactionPackage {
+    const DEFAULT_VALUE: [u8; 12] = [12, 0, 0, 0, 8, 0, 0, 0, 4, 0, 0, 0];
+    pub const FIELD_COUNT: usize = 1;
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn field_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
+        }
+    }
+    pub fn count_extra_fields(&self) -> usize {
+        self.field_count() - Self::FIELD_COUNT
+    }
+    pub fn has_extra_fields(&self) -> bool {
+        Self::FIELD_COUNT != self.field_count()
+    }
+    pub fn transactions(&self) -> RelayTransactionVec {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[4..]) as usize;
+        if self.has_extra_fields() {
+            let end = molecule::unpack_number(&slice[8..]) as usize;
+            RelayTransactionVec::new_unchecked(self.0.slice(start..end))
+        } else {
+            RelayTransactionVec::new_unchecked(self.0.slice(start..))
+        }
+    }
+    pub fn as_reader<'r>(&'r self) -> RelayTransactionPackageReader<'r> {
+        RelayTransactionPackageReader::new_unchecked(self.as_slice())
+    }
+}
+impl molecule::prelude::Entity for RelayTransactionPackage {
+    type Builder = RelayTransactionPackageBuilder;
+    const NAME: &'static str = "RelayTransactionPackage";
+    fn new_unchecked(data: molecule::bytes::Bytes) -> Self {
+        RelayTransactionPackage(data)
+    }
+    fn as_bytes(&self) -> molecule::bytes::Bytes {
+        self.0.clone()
+    }
+    fn as_slice(&self) -> &[u8] {
+        &self.0[..]
+    }
+    fn from_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        RelayTransactionPackageReader::from_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn from_compatible_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        RelayTransactionPackageReader::from_compatible_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn new_builder() -> Self::Builder {
+        ::core::default::Default::default()
+    }
+    fn as_builder(self) -> Self::Builder {
+        Self::new_builder().transactions(self.transactions())
+    }
+}
+#[derive(Clone, Copy)]
+pub struct RelayTransactionPackageReader<'r>(&'r [u8]);
+impl<'r> ::core::fmt::LowerHex for RelayTransactionPackageReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl<'r> ::core::fmt::Debug for RelayTransactionPackageReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl<'r> ::core::fmt::Display for RelayTransactionPackageReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "transactions", self.transactions())?;
+        let extra_count = self.count_extra_fields();
+        if extra_count != 0 {
+            write!(f, ", .. ({} fields)", extra_count)?;
+        }
+        write!(f, " }}")
+    }
+}
+impl<'r> RelayTransactionPackageReader<'r> {
+    pub const FIELD_COUNT: usize = 1;
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn field_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
+        }
+    }
+    pub fn count_extra_fields(&self) -> usize {
+        self.field_count() - Self::FIELD_COUNT
+    }
+    pub fn has_extra_fields(&self) -> bool {
+        Self::FIELD_COUNT != self.field_count()
+    }
+    pub fn transactions(&self) -> RelayTransactionVecReader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[4..]) as usize;
+        if self.has_extra_fields() {
+            let end = molecule::unpack_number(&slice[8..]) as usize;
+            RelayTransactionVecReader::new_unchecked(&self.as_slice()[start..end])
+        } else {
+            RelayTransactionVecReader::new_unchecked(&self.as_slice()[start..])
+        }
+    }
+}
+impl<'r> molecule::prelude::Reader<'r> for RelayTransactionPackageReader<'r> {
+    type Entity = RelayTransactionPackage;
+    const NAME: &'static str = "RelayTransactionPackageReader";
+    fn to_entity(&self) -> Self::Entity {
+        Self::Entity::new_unchecked(self.as_slice().to_owned().into())
+    }
+    fn new_unchecked(slice: &'r [u8]) -> Self {
+        RelayTransactionPackageReader(slice)
+    }
+    fn as_slice(&self) -> &'r [u8] {
+        self.0
+    }
+    fn verify(slice: &[u8], compatible: bool) -> molecule::error::VerificationResult<()> {
+        use molecule::verification_error as ve;
+        let slice_len = slice.len();
+        if slice_len < molecule::NUMBER_SIZE {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE, slice_len);
+        }
+        let total_size = molecule::unpack_number(slice) as usize;
+        if slice_len != total_size {
+            return ve!(Self, TotalSizeNotMatch, total_size, slice_len);
+        }
+        if slice_len < molecule::NUMBER_SIZE * 2 {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE * 2, slice_len);
+        }
+        let offset_first = molecule::unpack_number(&slice[molecule::NUMBER_SIZE..]) as usize;
+        if offset_first % molecule::NUMBER_SIZE != 0 || offset_first < molecule::NUMBER_SIZE * 2 {
+            return ve!(Self, OffsetsNotMatch);
+        }
+        if slice_len < offset_first {
+            return ve!(Self, HeaderIsBroken, offset_first, slice_len);
+        }
+        let field_count = offset_first / molecule::NUMBER_SIZE - 1;
+        if field_count < Self::FIELD_COUNT {
+            return ve!(Self, FieldCountNotMatch, Self::FIELD_COUNT, field_count);
+        } else if !compatible && field_count > Self::FIELD_COUNT {
+            return ve!(Self, FieldCountNotMatch, Self::FIELD_COUNT, field_count);
+        };
+        let mut offsets: Vec<usize> = slice[molecule::NUMBER_SIZE..offset_first]
+            .chunks_exact(molecule::NUMBER_SIZE)
+            .map(|x| molecule::unpack_number(x) as usize)
+            .collect();
+        offsets.push(total_size);
+        if offsets.windows(2).any(|i| i[0] > i[1]) {
+            return ve!(Self, OffsetsNotMatch);
+        }
+        RelayTransactionVecReader::verify(&slice[offsets[0]..offsets[1]], compatible)?;
+        Ok(())
+    }
+}
+#[derive(Debug, Default)]
+pub struct RelayTransactionPackageBuilder {
+    pub(crate) transactions: RelayTransactionVec,
+}
+impl RelayTransactionPackageBuilder {
+    pub const FIELD_COUNT: usize = 1;
+    pub fn transactions(mut self, v: RelayTransactionVec) -> Self {
+        self.transactions = v;
+        self
+    }
+}
+impl molecule::prelude::Builder for RelayTransactionPackageBuilder {
+    type Entity = RelayTransactionPackage;
+    const NAME: &'static str = "RelayTransactionPackageBuilder";
+    fn expected_length(&self) -> usize {
+        molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1) + self.transactions.as_slice().len()
+    }
+    fn write<W: molecule::io::Write>(&self, writer: &mut W) -> molecule::io::Result<()> {
+        let mut total_size = molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1);
+        let mut offsets = Vec::with_capacity(Self::FIELD_COUNT);
+        offsets.push(total_size);
+        total_size += self.transactions.as_slice().len();
+        writer.write_all(&molecule::pack_number(total_size as molecule::Number))?;
+        for offset in offsets.into_iter() {
+            writer.write_all(&molecule::pack_number(offset as molecule::Number))?;
+        }
+        writer.write_all(self.transactions.as_slice())?;
+        Ok(())
+    }
+    fn build(&self) -> Self::Entity {
+        let mut inner = Vec::with_capacity(self.expected_length());
+        self.write(&mut inner)
+            .unwrap_or_else(|_| panic!("{} build should be ok", Self::NAME));
+        RelayTransactionPackage::new_unchecked(inner.into())
+    }
+}
+#[derive(Clone)]
 pub struct IndexTransaction(molecule::bytes::Bytes);
 impl ::core::fmt::LowerHex for IndexTransaction {
     fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
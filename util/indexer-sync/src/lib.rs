@@ -3,16 +3,18 @@
 pub(crate) mod custom_filters;
 pub(crate) mod error;
 pub(crate) mod pool;
+pub(crate) mod reindex;
 pub(crate) mod store;
 
 pub use crate::custom_filters::CustomFilters;
 pub use crate::error::Error;
 pub use crate::pool::{Pool, PoolService};
+pub use crate::reindex::{ReindexState, Reindexer};
 pub use crate::store::SecondaryDB;
 
 use ckb_app_config::{DBConfig, IndexerSyncConfig};
 use ckb_async_runtime::{
-    tokio::{self, time},
+    tokio::{self, sync::mpsc, time},
     Handle,
 };
 use ckb_db_schema::{
@@ -33,7 +35,7 @@ use rocksdb::prelude::*;
 
 use std::marker::Send;
 use std::num::NonZeroUsize;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
 use std::thread::sleep;
 use std::time::Duration;
 
@@ -52,6 +54,13 @@ pub trait IndexerSync {
     fn get_identity(&self) -> &str;
     /// Set init tip
     fn set_init_tip(&self, init_tip_number: u64, init_tip_hash: &H256);
+    /// Re-index a block at or below the tip, see [`ReindexState`]
+    fn reindex(&self, _block: &BlockView, _state: &mut ReindexState) -> Result<(), Error> {
+        Err(Error::invalid_params(format!(
+            "{} does not support re-indexing",
+            self.get_identity()
+        )))
+    }
 }
 
 /// Construct new secondary db instance
@@ -82,6 +91,10 @@ pub struct IndexerSyncService {
     poll_interval: Duration,
     async_handle: Handle,
     init_tip_hash: Option<H256>,
+    // the tip-following and the re-indexing write the store block by block in turn
+    sync_lock: Arc<Mutex<()>>,
+    reindexer: Reindexer,
+    reindex_requests: Arc<Mutex<Option<mpsc::UnboundedReceiver<(BlockNumber, BlockNumber)>>>>,
 }
 
 impl IndexerSyncService {
@@ -93,12 +106,16 @@ impl IndexerSyncService {
         async_handle: Handle,
         init_tip_hash: Option<H256>,
     ) -> Self {
+        let (reindexer, reindex_requests) = Reindexer::new();
         Self {
             secondary_db,
             pool_service,
             poll_interval: Duration::from_secs(config.poll_interval),
             async_handle,
             init_tip_hash,
+            sync_lock: Arc::new(Mutex::new(())),
+            reindexer,
+            reindex_requests: Arc::new(Mutex::new(Some(reindex_requests))),
         }
     }
 
@@ -129,6 +146,7 @@ impl IndexerSyncService {
                 }
                 if let Some(header) = self.secondary_db.get_block_header(&init_tip_hash.pack()) {
                     let init_tip_number = header.number();
+                    let _sync_guard = self.sync_lock.lock().expect("acquire lock");
                     indexer_service.set_init_tip(init_tip_number, init_tip_hash);
                     break;
                 }
@@ -150,6 +168,7 @@ impl IndexerSyncService {
                 break;
             }
 
+            let _sync_guard = self.sync_lock.lock().expect("acquire lock");
             match indexer.tip() {
                 Ok(Some((tip_number, tip_hash))) => {
                     match self.get_block_by_number(tip_number + 1) {
@@ -206,6 +225,8 @@ impl IndexerSyncService {
     ) where
         I: IndexerSync + Clone + Send + 'static,
     {
+        self.spawn_reindex(indexer_service.clone());
+
         // Initial sync
         let initial_service = self.clone();
         let indexer = indexer_service.clone();
@@ -264,6 +285,112 @@ impl IndexerSyncService {
         self.pool_service.pool()
     }
 
+    /// Returns the controller to re-index block ranges, the requests are processed once
+    /// `spawn_poll` is called
+    pub fn reindexer(&self) -> Reindexer {
+        self.reindexer.clone()
+    }
+
+    fn spawn_reindex<I>(&self, indexer_service: I)
+    where
+        I: IndexerSync + Clone + Send + 'static,
+    {
+        let mut reindex_requests = match self.reindex_requests.lock().expect("acquire lock").take()
+        {
+            Some(reindex_requests) => reindex_requests,
+            None => return,
+        };
+        let stop: CancellationToken = new_tokio_exit_rx();
+        let async_handle = self.async_handle.clone();
+        let reindex_service = self.clone();
+        self.async_handle.spawn(async move {
+            loop {
+                tokio::select! {
+                    Some((from, to)) = reindex_requests.recv() => {
+                        let service = reindex_service.clone();
+                        let indexer = indexer_service.clone();
+                        if let Err(e) = async_handle.spawn_blocking(move || {
+                            service.reindex(&indexer, from, to)
+                        }).await {
+                            error!("{} reindexing join error {:?}", indexer_service.get_identity(), e);
+                        }
+                        reindex_service.reindexer.finish();
+                    },
+                    _ = stop.cancelled() => {
+                        info!("{} reindexing received exit signal, exit now", indexer_service.get_identity());
+                        break
+                    },
+                }
+            }
+        });
+    }
+
+    fn reindex<I>(&self, indexer: &I, from: BlockNumber, to: BlockNumber)
+    where
+        I: IndexerSync,
+    {
+        info!(
+            "{} reindex blocks [{}, {}]",
+            indexer.get_identity(),
+            from,
+            to
+        );
+        if let Err(e) = self.try_reindex(indexer, from, to) {
+            error!(
+                "{} failed to reindex blocks [{}, {}]: {}",
+                indexer.get_identity(),
+                from,
+                to,
+                e
+            );
+        }
+    }
+
+    // Re-index the blocks in [from, to], then the blocks after it until the re-indexed cells are
+    // all consumed or the tip is reached, interleaving with the tip-following block by block
+    fn try_reindex<I>(&self, indexer: &I, from: BlockNumber, to: BlockNumber) -> Result<(), Error>
+    where
+        I: IndexerSync,
+    {
+        let mut state = ReindexState::new(from, to);
+        let mut block_number = from;
+        loop {
+            if has_received_stop_signal() {
+                info!("reindex received exit signal, exit now");
+                return Ok(());
+            }
+
+            let sync_guard = self.sync_lock.lock().expect("acquire lock");
+            let (tip_number, tip_hash) = indexer
+                .tip()?
+                .ok_or_else(|| Error::invalid_params("the indexer has not indexed any block"))?;
+            if block_number > tip_number || (block_number > to && state.live_cells.is_empty()) {
+                break;
+            }
+            // the blocks are read by number, wait for the indexer to roll back to the main chain
+            if self.secondary_db.get_block_hash(tip_number).as_ref() != Some(&tip_hash) {
+                drop(sync_guard);
+                if let Err(e) = self.secondary_db.try_catch_up_with_primary() {
+                    error!("secondary_db try_catch_up_with_primary error {}", e);
+                }
+                sleep(Duration::from_secs(1));
+                continue;
+            }
+            let block = self
+                .get_block_by_number(block_number)
+                .ok_or_else(|| Error::DB(format!("block {block_number} is not found")))?;
+            indexer.reindex(&block, &mut state)?;
+            block_number += 1;
+        }
+        info!(
+            "{} reindexed blocks [{}, {}]",
+            indexer.get_identity(),
+            from,
+            to
+        );
+        Ok(())
+    }
+
     /// Index transaction pool
     pub fn index_tx_pool<I>(&mut self, indexer_service: I, notify_controller: NotifyController)
     where
@@ -0,0 +1,93 @@
+//! Re-index the historical blocks into an existing indexer store
+
+use crate::error::Error;
+
+use ckb_async_runtime::tokio::sync::mpsc;
+use ckb_types::{core::BlockNumber, packed::OutPoint};
+
+use std::collections::HashSet;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// The state of a block range re-indexing, it is passed to `IndexerSync::reindex` for each block,
+/// from the first block of the range on, until the block is beyond the range and the merged cells
+/// are all consumed, or the block is beyond the indexer tip.
+///
+/// The blocks in the range are re-indexed with the current filters, their cells and transactions
+/// which are not indexed yet are merged into the store. The blocks after the range are indexed
+/// already, only their inputs consuming the merged cells are re-indexed, until all of the merged
+/// cells are consumed or the indexer tip is reached.
+///
+/// An indexer which can't merge the cells, such as the rich-indexer storing its rows in the chain
+/// order, removes the blocks from `from` at the first call and appends the blocks again instead,
+/// which moves its tip back to `from` until it catches up.
+pub struct ReindexState {
+    /// The first block number of the range
+    pub from: BlockNumber,
+    /// The last block number of the range
+    pub to: BlockNumber,
+    /// The merged cells which are not consumed yet
+    pub live_cells: HashSet<OutPoint>,
+}
+
+impl ReindexState {
+    /// Construct new ReindexState instance for the range `[from, to]`
+    pub fn new(from: BlockNumber, to: BlockNumber) -> Self {
+        Self {
+            from,
+            to,
+            live_cells: HashSet::new(),
+        }
+    }
+}
+
+/// Requests to re-index block ranges in the background, one range at a time
+#[derive(Clone)]
+pub struct Reindexer {
+    sender: mpsc::UnboundedSender<(BlockNumber, BlockNumber)>,
+    running: Arc<AtomicBool>,
+}
+
+impl Reindexer {
+    pub(crate) fn new() -> (Self, mpsc::UnboundedReceiver<(BlockNumber, BlockNumber)>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        (
+            Self {
+                sender,
+                running: Arc::new(AtomicBool::new(false)),
+            },
+            receiver,
+        )
+    }
+
+    /// Re-index the blocks in `[from, to]`, returns an error if a re-indexing is running
+    pub fn reindex(&self, from: BlockNumber, to: BlockNumber) -> Result<(), Error> {
+        if from > to {
+            return Err(Error::invalid_params(format!(
+                "from {from} must not be greater than to {to}"
+            )));
+        }
+        if self
+            .running
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            return Err(Error::invalid_params("a re-indexing is running already"));
+        }
+        self.sender.send((from, to)).map_err(|_| {
+            self.finish();
+            Error::invalid_params("the re-indexing service is stopped")
+        })
+    }
+
+    /// Return true if a re-indexing is running
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn finish(&self) {
+        self.running.store(false, Ordering::SeqCst);
+    }
+}
@@ -1,6 +1,8 @@
 use crate::service::SUBSCRIBER_NAME;
 use crate::store::{Batch, IteratorDirection, Store};
-use ckb_indexer_sync::{CustomFilters, Error, IndexerSync, Pool};
+use ckb_async_runtime::tokio::sync::broadcast;
+use ckb_indexer_sync::{CustomFilters, Error, IndexerSync, Pool, ReindexState};
+use ckb_jsonrpc_types::IndexerCellEventType;
 use ckb_types::{
     core::{BlockNumber, BlockView},
     packed::{Byte32, Bytes, CellOutput, OutPoint, Script},
@@ -38,6 +40,7 @@ pub enum CellType {
 /// | 160          | TxTypeScript       | TxHash                   |
 /// | 192          | TxHash             | TransactionInputs        | * rollback and prune
 /// | 224          | Header             | Transactions             |
+/// | 255          | PrunedTo           | BlockNumber              |
 /// +--------------+--------------------+--------------------------+
 /// Storage indexer key type enum
 pub enum Key<'a> {
@@ -57,6 +60,8 @@ pub enum Key<'a> {
     TxHash(&'a Byte32),
     /// (block_number, block_hash, txs_filtered)
     Header(BlockNumber, &'a Byte32, bool),
+    /// The consumed cells and headers at or before this block number are pruned
+    PrunedTo,
 }
 
 /// Storage value
@@ -69,6 +74,8 @@ pub enum Value<'a> {
     TransactionInputs(Vec<OutPoint>),
     /// (tx_hash, outputs_len, tx_index)
     Transactions(Vec<(Byte32, u32, Option<u32>)>),
+    /// Block number
+    BlockNumber(BlockNumber),
 }
 
 /// Key prefix
@@ -90,6 +97,8 @@ pub enum KeyPrefix {
     TxHash = 192,
     /// Header
     Header = 224,
+    /// Pruned block number
+    PrunedTo = 255,
 }
 
 impl<'a> Key<'a> {
@@ -149,6 +158,9 @@ impl<'a> From<Key<'a>> for Vec<u8> {
                     encoded.push(1);
                 }
             }
+            Key::PrunedTo => {
+                encoded.push(KeyPrefix::PrunedTo as u8);
+            }
         }
         encoded
     }
@@ -204,6 +216,9 @@ impl<'a> From<Value<'a>> for Vec<u8> {
                     }
                 });
             }
+            Value::BlockNumber(block_number) => {
+                encoded.extend_from_slice(&block_number.to_le_bytes());
+            }
         }
         encoded
     }
@@ -225,6 +240,11 @@ impl<'a> Value<'a> {
         (block_number, tx_index, output, output_data)
     }
 
+    /// Decode block number from binary
+    pub fn parse_block_number_value(slice: &[u8]) -> BlockNumber {
+        BlockNumber::from_le_bytes(slice[0..8].try_into().expect("stored block_number"))
+    }
+
     /// Decode transactions from binary
     pub fn parse_transactions_value(
         slice: &[u8],
@@ -267,6 +287,38 @@ pub struct DetailedLiveCell {
     cell_data: Bytes,
 }
 
+/// A cell created or consumed in a block.
+#[derive(Clone, Debug)]
+pub struct CellEvent {
+    /// Created or consumed
+    pub event_type: IndexerCellEventType,
+    /// The transaction which creates or consumes the cell
+    pub tx_hash: Byte32,
+    /// The cell out point
+    pub out_point: OutPoint,
+    /// The cell output
+    pub output: CellOutput,
+    /// The cell data
+    pub output_data: Bytes,
+    /// The number of the block in which the cell is created
+    pub created_block_number: BlockNumber,
+    /// The index of the transaction which creates the cell
+    pub created_tx_index: TxIndex,
+}
+
+/// The cell events of an appended or rolled back block.
+#[derive(Clone, Debug)]
+pub struct BlockCellEvents {
+    /// Block number
+    pub block_number: BlockNumber,
+    /// Block hash
+    pub block_hash: Byte32,
+    /// True if the block is rolled back, and the events are reverted
+    pub rollback: bool,
+    /// The events in the order of the transactions
+    pub events: Vec<CellEvent>,
+}
+
 /// Indexer store wrapper
 #[derive(Clone)]
 pub(crate) struct Indexer<S> {
@@ -282,6 +334,8 @@ pub(crate) struct Indexer<S> {
     pool: Option<Arc<RwLock<Pool>>>,
     /// custom filters
     custom_filters: CustomFilters,
+    /// An optional channel to publish the cell events of the appended and rolled back blocks
+    cell_events: Option<broadcast::Sender<Arc<BlockCellEvents>>>,
 }
 
 impl<S> Indexer<S> {
@@ -299,9 +353,26 @@ impl<S> Indexer<S> {
             prune_interval,
             pool,
             custom_filters,
+            cell_events: None,
         }
     }
 
+    /// Publishes the cell events to the channel
+    pub(crate) fn with_cell_events(
+        mut self,
+        cell_events: broadcast::Sender<Arc<BlockCellEvents>>,
+    ) -> Self {
+        self.cell_events = Some(cell_events);
+        self
+    }
+
+    // Returns the channel only if there are subscribers, to skip collecting events otherwise
+    fn cell_events_sender(&self) -> Option<&broadcast::Sender<Arc<BlockCellEvents>>> {
+        self.cell_events
+            .as_ref()
+            .filter(|sender| sender.receiver_count() > 0)
+    }
+
     /// Return store reference
     #[allow(dead_code)]
     pub(crate) fn store(&self) -> &S {
@@ -331,6 +402,8 @@ where
 
         let block_number = block.number();
         let mut matched_txs = vec![];
+        let cell_events_sender = self.cell_events_sender();
+        let mut cell_events = vec![];
         for (tx_index, tx) in transactions.iter().enumerate() {
             let tx_index = tx_index as u32;
             let tx_hash = tx.hash();
@@ -416,6 +489,17 @@ where
                             )?;
                         };
                         batch.delete(key_vec)?;
+                        if cell_events_sender.is_some() {
+                            cell_events.push(CellEvent {
+                                event_type: IndexerCellEventType::Consumed,
+                                tx_hash: tx_hash.clone(),
+                                out_point: out_point.clone(),
+                                output,
+                                output_data,
+                                created_block_number: generated_by_block_number,
+                                created_tx_index: generated_by_tx_index,
+                            });
+                        }
                         batch.put_kv(
                             Key::ConsumedOutPoint(block_number, &out_point),
                             stored_live_cell,
@@ -477,6 +561,17 @@ where
                     Key::OutPoint(&out_point),
                     Value::Cell(block_number, tx_index, &output, &output_data),
                 )?;
+                if cell_events_sender.is_some() {
+                    cell_events.push(CellEvent {
+                        event_type: IndexerCellEventType::Created,
+                        tx_hash: tx_hash.clone(),
+                        out_point,
+                        output,
+                        output_data,
+                        created_block_number: block_number,
+                        created_tx_index: tx_index,
+                    });
+                }
             }
 
             if tx_matched {
@@ -513,6 +608,15 @@ where
         }
         batch.commit()?;
 
+        if let Some(sender) = cell_events_sender {
+            let _ = sender.send(Arc::new(BlockCellEvents {
+                block_number,
+                block_hash: block.hash(),
+                rollback: false,
+                events: cell_events,
+            }));
+        }
+
         if let Some(mut pool) = pool {
             pool.transactions_committed(&transactions);
         }
@@ -538,6 +642,8 @@ where
             )
         }) {
             let mut batch = self.store.batch()?;
+            let cell_events_sender = self.cell_events_sender();
+            let mut cell_events = vec![];
             for (tx_index, (tx_hash, outputs_len, stored_tx_index)) in
                 txs.into_iter().enumerate().rev()
             {
@@ -547,15 +653,16 @@ where
                     let out_point = OutPoint::new(tx_hash.clone(), output_index);
                     let out_point_key = Key::OutPoint(&out_point).into_vec();
 
-                    if let Some(output) = match self.store.get(&out_point_key)? {
-                        Some(stored_live_cell) => {
-                            Some(Value::parse_cell_value(&stored_live_cell).2)
-                        }
+                    if let Some((output, output_data)) = match self.store.get(&out_point_key)? {
+                        Some(stored_live_cell) => Some(stored_live_cell),
                         None => self
                             .store
-                            .get(Key::ConsumedOutPoint(block_number, &out_point).into_vec())?
-                            .map(|consumed_cell| Value::parse_cell_value(&consumed_cell).2),
-                    } {
+                            .get(Key::ConsumedOutPoint(block_number, &out_point).into_vec())?,
+                    }
+                    .map(|stored_cell| {
+                        let (_, _, output, output_data) = Value::parse_cell_value(&stored_cell);
+                        (output, output_data)
+                    }) {
                         batch.delete(
                             Key::CellLockScript(
                                 &output.lock(),
@@ -592,6 +699,17 @@ where
                             )?;
                         };
                         batch.delete(out_point_key)?;
+                        if cell_events_sender.is_some() {
+                            cell_events.push(CellEvent {
+                                event_type: IndexerCellEventType::Created,
+                                tx_hash: tx_hash.clone(),
+                                out_point,
+                                output,
+                                output_data,
+                                created_block_number: block_number,
+                                created_tx_index: tx_index,
+                            });
+                        }
                     }
                 }
 
@@ -620,7 +738,7 @@ where
                                 generated_by_block_number,
                                 generated_by_tx_index,
                                 output,
-                                _output_data,
+                                output_data,
                             ) = Value::parse_cell_value(&stored_consumed_cell);
                             let input_index = input_index as u32;
 
@@ -665,6 +783,17 @@ where
                                 )?;
                             }
                             batch.put_kv(Key::OutPoint(&out_point), stored_consumed_cell)?;
+                            if cell_events_sender.is_some() {
+                                cell_events.push(CellEvent {
+                                    event_type: IndexerCellEventType::Consumed,
+                                    tx_hash: tx_hash.clone(),
+                                    out_point,
+                                    output,
+                                    output_data,
+                                    created_block_number: generated_by_block_number,
+                                    created_tx_index: generated_by_tx_index,
+                                });
+                            }
                         }
                     }
                 }
@@ -675,6 +804,15 @@ where
             batch.delete(Key::Header(block_number, &block_hash, filtered).into_vec())?;
 
             batch.commit()?;
+
+            if let Some(sender) = cell_events_sender {
+                let _ = sender.send(Arc::new(BlockCellEvents {
+                    block_number,
+                    block_hash,
+                    rollback: true,
+                    events: cell_events,
+                }));
+            }
         }
         Ok(())
     }
@@ -708,6 +846,222 @@ where
             .expect("insert init tip header should be OK");
         batch.commit().expect("commit batch should be OK");
     }
+
+    /// Merge the cells and transactions of a block at or below the tip which are not indexed yet,
+    /// the indexed ones are left as they are
+    fn reindex(&self, block: &BlockView, state: &mut ReindexState) -> Result<(), Error> {
+        if !self.custom_filters.is_block_filter_match(block) {
+            return Ok(());
+        }
+
+        let block_number = block.number();
+        // the blocks after the range are indexed already, but their inputs consuming the merged
+        // cells are not, since the cells were unknown when the blocks were appended
+        let in_range = block_number <= state.to;
+        let transactions = block.transactions();
+        let mut batch = self.store.batch()?;
+        // the merged cells of this block, which are not committed yet
+        let mut merged_cells = HashMap::new();
+        let mut matched_txs = vec![];
+        for (tx_index, tx) in transactions.iter().enumerate() {
+            let tx_index = tx_index as u32;
+            let tx_hash = tx.hash();
+            let mut tx_matched = false;
+            // skip cellbase
+            if tx_index > 0 {
+                for (input_index, input) in tx.inputs().into_iter().enumerate() {
+                    let input_index = input_index as u32;
+                    let out_point = input.previous_output();
+                    if !state.live_cells.remove(&out_point) && !in_range {
+                        continue;
+                    }
+                    let key_vec = Key::OutPoint(&out_point).into_vec();
+                    let stored_live_cell = match self
+                        .store
+                        .get(&key_vec)?
+                        .or_else(|| merged_cells.remove(&out_point))
+                    {
+                        Some(stored_live_cell) => stored_live_cell,
+                        None => continue,
+                    };
+                    let (generated_by_block_number, generated_by_tx_index, output, output_data) =
+                        Value::parse_cell_value(&stored_live_cell);
+
+                    if !self
+                        .custom_filters
+                        .is_cell_filter_match(&output, &output_data)
+                    {
+                        continue;
+                    } else {
+                        tx_matched = true;
+                    }
+
+                    batch.delete(
+                        Key::CellLockScript(
+                            &output.lock(),
+                            generated_by_block_number,
+                            generated_by_tx_index,
+                            out_point.index().unpack(),
+                        )
+                        .into_vec(),
+                    )?;
+                    batch.put_kv(
+                        Key::TxLockScript(
+                            &output.lock(),
+                            block_number,
+                            tx_index,
+                            input_index,
+                            CellType::Input,
+                        ),
+                        Value::TxHash(&tx_hash),
+                    )?;
+                    if let Some(script) = output.type_().to_opt() {
+                        batch.delete(
+                            Key::CellTypeScript(
+                                &script,
+                                generated_by_block_number,
+                                generated_by_tx_index,
+                                out_point.index().unpack(),
+                            )
+                            .into_vec(),
+                        )?;
+                        batch.put_kv(
+                            Key::TxTypeScript(
+                                &script,
+                                block_number,
+                                tx_index,
+                                input_index,
+                                CellType::Input,
+                            ),
+                            Value::TxHash(&tx_hash),
+                        )?;
+                    };
+                    batch.delete(key_vec)?;
+                    batch.put_kv(
+                        Key::ConsumedOutPoint(block_number, &out_point),
+                        stored_live_cell,
+                    )?;
+                }
+            }
+
+            if in_range {
+                for (output_index, output) in tx.outputs().into_iter().enumerate() {
+                    let output_data = tx
+                        .outputs_data()
+                        .get(output_index)
+                        .expect("outputs_data len should equals outputs len");
+
+                    if !self
+                        .custom_filters
+                        .is_cell_filter_match(&output, &output_data)
+                    {
+                        continue;
+                    } else {
+                        tx_matched = true;
+                    }
+
+                    let output_index = output_index as u32;
+                    // the cell is indexed already, and may have been consumed since then
+                    if self.store.exists(
+                        Key::TxLockScript(
+                            &output.lock(),
+                            block_number,
+                            tx_index,
+                            output_index,
+                            CellType::Output,
+                        )
+                        .into_vec(),
+                    )? {
+                        continue;
+                    }
+
+                    let out_point = OutPoint::new(tx.hash(), output_index);
+                    batch.put_kv(
+                        Key::CellLockScript(&output.lock(), block_number, tx_index, output_index),
+                        Value::TxHash(&tx_hash),
+                    )?;
+                    batch.put_kv(
+                        Key::TxLockScript(
+                            &output.lock(),
+                            block_number,
+                            tx_index,
+                            output_index,
+                            CellType::Output,
+                        ),
+                        Value::TxHash(&tx_hash),
+                    )?;
+                    if let Some(script) = output.type_().to_opt() {
+                        batch.put_kv(
+                            Key::CellTypeScript(&script, block_number, tx_index, output_index),
+                            Value::TxHash(&tx_hash),
+                        )?;
+                        batch.put_kv(
+                            Key::TxTypeScript(
+                                &script,
+                                block_number,
+                                tx_index,
+                                output_index,
+                                CellType::Output,
+                            ),
+                            Value::TxHash(&tx_hash),
+                        )?;
+                    }
+                    let cell: Vec<u8> =
+                        Value::Cell(block_number, tx_index, &output, &output_data).into();
+                    batch.put(Key::OutPoint(&out_point).into_vec(), &cell)?;
+                    merged_cells.insert(out_point.clone(), cell);
+                    state.live_cells.insert(out_point);
+                }
+            }
+
+            if tx_matched {
+                matched_txs.push(tx_index);
+            }
+        }
+
+        // the block transactions are kept until the block is pruned, to roll it back
+        let mut key_prefix_header = vec![KeyPrefix::Header as u8];
+        key_prefix_header.extend_from_slice(&block_number.to_be_bytes());
+        if let Some((header_key, value)) = self
+            .store
+            .iter(&key_prefix_header, IteratorDirection::Forward)?
+            .next()
+            .filter(|(key, _value)| key.starts_with(&key_prefix_header))
+        {
+            if &header_key[9..41] != block.hash().as_slice() {
+                return Err(Error::DB(format!(
+                    "block {} {} is not the indexed one",
+                    block_number,
+                    block.hash()
+                )));
+            }
+            let filtered = header_key.len() == 42 && header_key[41] == 1;
+            if filtered {
+                let mut txs = Value::parse_transactions_value(&value, filtered);
+                for tx_index in matched_txs {
+                    let tx = &transactions[tx_index as usize];
+                    let tx_hash = tx.hash();
+                    if txs.iter().all(|(hash, _, _)| hash != &tx_hash) {
+                        batch.put_kv(
+                            Key::TxHash(&tx_hash),
+                            Value::TransactionInputs(
+                                tx.inputs()
+                                    .into_iter()
+                                    .map(|input| input.previous_output())
+                                    .collect(),
+                            ),
+                        )?;
+                        txs.push((tx_hash, tx.outputs().len() as u32, Some(tx_index)));
+                    }
+                }
+                txs.sort_by_key(|(_, _, tx_index)| *tx_index);
+                batch.put_kv(header_key, Value::Transactions(txs))?;
+            }
+        }
+        batch.commit()?;
+
+        Ok(())
+    }
 }
 
 impl<S> Indexer<S>
@@ -791,6 +1145,15 @@ where
                 batch.delete(header_key)?;
             }
 
+            // historical queries need the consumed cells after the queried block
+            let pruned_to = self
+                .store
+                .get(Key::PrunedTo.into_vec())?
+                .map(|value| Value::parse_block_number_value(&value))
+                .unwrap_or_default()
+                .max(prune_to_block);
+            batch.put_kv(Key::PrunedTo, Value::BlockNumber(pruned_to))?;
+
             batch.commit()?;
         }
         Ok(())
@@ -2298,4 +2661,222 @@ mod tests {
                 .len()
         );
     }
+
+    #[test]
+    fn append_and_rollback_should_send_cell_events() {
+        let (sender, mut receiver) = broadcast::channel(4);
+        let indexer = new_indexer::<RocksdbStore>("append_and_rollback_should_send_cell_events")
+            .with_cell_events(sender);
+
+        let lock_script1 = ScriptBuilder::default()
+            .code_hash(H256(rand::random()).pack())
+            .hash_type(ScriptHashType::Data.into())
+            .args(Bytes::from(b"lock_script1".to_vec()).pack())
+            .build();
+
+        let cellbase0 = TransactionBuilder::default()
+            .input(CellInput::new_cellbase_input(0))
+            .witness(Script::default().into_witness())
+            .output(
+                CellOutputBuilder::default()
+                    .capacity(capacity_bytes!(1000).pack())
+                    .lock(lock_script1.clone())
+                    .build(),
+            )
+            .output_data(Default::default())
+            .build();
+        let block0 = BlockBuilder::default()
+            .transaction(cellbase0.clone())
+            .header(HeaderBuilder::default().number(0.pack()).build())
+            .build();
+
+        let cellbase1 = TransactionBuilder::default()
+            .input(CellInput::new_cellbase_input(1))
+            .witness(Script::default().into_witness())
+            .build();
+        let tx10 = TransactionBuilder::default()
+            .input(CellInput::new(OutPoint::new(cellbase0.hash(), 0), 0))
+            .output(
+                CellOutputBuilder::default()
+                    .capacity(capacity_bytes!(1000).pack())
+                    .lock(lock_script1)
+                    .build(),
+            )
+            .output_data(Default::default())
+            .build();
+        let block1 = BlockBuilder::default()
+            .transaction(cellbase1)
+            .transaction(tx10.clone())
+            .header(
+                HeaderBuilder::default()
+                    .number(1.pack())
+                    .parent_hash(block0.hash())
+                    .epoch(EpochNumberWithFraction::new(0, 1, 10).full_value().pack())
+                    .build(),
+            )
+            .build();
+
+        indexer.append(&block0).unwrap();
+        indexer.append(&block1).unwrap();
+        indexer.rollback().unwrap();
+
+        let events0 = receiver.try_recv().unwrap();
+        assert_eq!(block0.hash(), events0.block_hash);
+        assert!(!events0.rollback);
+        assert_eq!(1, events0.events.len());
+        assert_eq!(IndexerCellEventType::Created, events0.events[0].event_type);
+
+        let events1 = receiver.try_recv().unwrap();
+        assert_eq!(block1.hash(), events1.block_hash);
+        assert!(!events1.rollback);
+        let event_types: Vec<_> = events1
+            .events
+            .iter()
+            .map(|event| (event.event_type, event.tx_hash.clone()))
+            .collect();
+        assert_eq!(
+            vec![
+                (IndexerCellEventType::Consumed, tx10.hash()),
+                (IndexerCellEventType::Created, tx10.hash()),
+            ],
+            event_types
+        );
+        assert_eq!(0, events1.events[0].created_block_number);
+        assert_eq!(1, events1.events[1].created_block_number);
+
+        let reverted = receiver.try_recv().unwrap();
+        assert_eq!(block1.hash(), reverted.block_hash);
+        assert!(reverted.rollback);
+        assert_eq!(2, reverted.events.len());
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn reindex_blocks_before_init_tip() {
+        let indexer = new_indexer::<RocksdbStore>("reindex_blocks_before_init_tip");
+
+        let lock_script1 = ScriptBuilder::default()
+            .code_hash(H256(rand::random()).pack())
+            .hash_type(ScriptHashType::Data.into())
+            .args(Bytes::from(b"lock_script1".to_vec()).pack())
+            .build();
+
+        let lock_script2 = ScriptBuilder::default()
+            .code_hash(H256(rand::random()).pack())
+            .hash_type(ScriptHashType::Type.into())
+            .args(Bytes::from(b"lock_script2".to_vec()).pack())
+            .build();
+
+        let cellbase = |number: BlockNumber| {
+            TransactionBuilder::default()
+                .input(CellInput::new_cellbase_input(number))
+                .witness(Script::default().into_witness())
+                .output(
+                    CellOutputBuilder::default()
+                        .capacity(capacity_bytes!(1000).pack())
+                        .lock(lock_script2.clone())
+                        .build(),
+                )
+                .output_data(Default::default())
+                .build()
+        };
+        let new_block = |number: BlockNumber, tx| {
+            BlockBuilder::default()
+                .transaction(cellbase(number))
+                .transaction(tx)
+                .header(HeaderBuilder::default().number(number.pack()).build())
+                .build()
+        };
+
+        let tx10 = TransactionBuilder::default()
+            .output(
+                CellOutputBuilder::default()
+                    .capacity(capacity_bytes!(1000).pack())
+                    .lock(lock_script1.clone())
+                    .build(),
+            )
+            .output_data(Default::default())
+            .build();
+        let tx20 = TransactionBuilder::default()
+            .output(
+                CellOutputBuilder::default()
+                    .capacity(capacity_bytes!(2000).pack())
+                    .lock(lock_script1.clone())
+                    .build(),
+            )
+            .output_data(Default::default())
+            .build();
+        let tx30 = TransactionBuilder::default()
+            .input(CellInput::new(OutPoint::new(tx10.hash(), 0), 0))
+            .output(
+                CellOutputBuilder::default()
+                    .capacity(capacity_bytes!(1000).pack())
+                    .lock(lock_script2.clone())
+                    .build(),
+            )
+            .output_data(Default::default())
+            .build();
+        let block1 = new_block(1, tx10.clone());
+        let block2 = new_block(2, tx20.clone());
+        let block3 = new_block(3, tx30);
+
+        // starts from block 2, the input of tx30 is unknown when block 3 is appended
+        indexer.set_init_tip(2, &block2.hash().unpack());
+        indexer.append(&block3).unwrap();
+        assert_eq!(
+            0,
+            indexer
+                .get_transactions_by_lock_script(&lock_script1)
+                .unwrap()
+                .len()
+        );
+
+        let mut state = ReindexState::new(1, 2);
+        for block in [&block1, &block2, &block3] {
+            indexer.reindex(block, &mut state).unwrap();
+        }
+        let (tip_number, tip_hash) = indexer.tip().unwrap().unwrap();
+        assert_eq!(3, tip_number);
+        assert_eq!(block3.hash(), tip_hash);
+        assert!(state.live_cells.contains(&OutPoint::new(tx20.hash(), 0)));
+        assert!(!state.live_cells.contains(&OutPoint::new(tx10.hash(), 0)));
+        assert_eq!(
+            1, // tx20
+            indexer
+                .get_live_cells_by_lock_script(&lock_script1)
+                .unwrap()
+                .len()
+        );
+        assert_eq!(
+            3, // tx10 (output), tx20 (output), tx30 (input)
+            indexer
+                .get_transactions_by_lock_script(&lock_script1)
+                .unwrap()
+                .len()
+        );
+
+        // the consumed cell is restored when the consuming block is rolled back
+        indexer.rollback().unwrap();
+        assert_eq!(
+            2, // tx10, tx20
+            indexer
+                .get_live_cells_by_lock_script(&lock_script1)
+                .unwrap()
+                .len()
+        );
+
+        // the indexed cells are left as they are
+        let mut state = ReindexState::new(1, 2);
+        for block in [&block1, &block2] {
+            indexer.reindex(block, &mut state).unwrap();
+        }
+        assert!(state.live_cells.is_empty());
+        assert_eq!(
+            2, // tx10 (output), tx20 (output)
+            indexer
+                .get_transactions_by_lock_script(&lock_script1)
+                .unwrap()
+                .len()
+        );
+    }
 }
@@ -6,4 +6,5 @@ pub(crate) mod store;
 /// The indexer service.
 pub mod service;
 
-pub use service::{IndexerHandle, IndexerService};
+pub use indexer::{BlockCellEvents, CellEvent};
+pub use service::{CellEventFilter, IndexerHandle, IndexerService};
@@ -1,28 +1,37 @@
 //！The indexer service.
 
-use crate::indexer::{self, extract_raw_data, Indexer, Key, KeyPrefix, Value};
+use crate::indexer::{
+    self, extract_raw_data, BlockCellEvents, CellEvent, Indexer, Key, KeyPrefix, Value,
+};
 use crate::store::{IteratorDirection, RocksdbStore, Store};
 
 use ckb_app_config::IndexerConfig;
-use ckb_async_runtime::Handle;
-use ckb_indexer_sync::{CustomFilters, Error, IndexerSyncService, Pool, PoolService, SecondaryDB};
+use ckb_async_runtime::{tokio::sync::broadcast, Handle};
+use ckb_indexer_sync::{
+    CustomFilters, Error, IndexerSyncService, Pool, PoolService, Reindexer, SecondaryDB,
+};
 use ckb_jsonrpc_types::{
-    IndexerCell, IndexerCellType, IndexerCellsCapacity, IndexerOrder, IndexerPagination,
-    IndexerScriptType, IndexerSearchKey, IndexerSearchMode, IndexerTip, IndexerTx,
-    IndexerTxWithCell, IndexerTxWithCells, JsonBytes, Uint32,
+    BlockNumber, IndexerCell, IndexerCellEvent, IndexerCellType, IndexerCellsCapacity,
+    IndexerOrder, IndexerPagination, IndexerScriptType, IndexerSearchKey, IndexerSearchMode,
+    IndexerTip, IndexerTx, IndexerTxWithCell, IndexerTxWithCells, JsonBytes, Uint32,
 };
 use ckb_notify::NotifyController;
 use ckb_types::{core, packed, prelude::*, H256};
 use memchr::memmem;
-use rocksdb::{prelude::*, Direction, IteratorMode};
+use rocksdb::{prelude::*, Direction, IteratorMode, Snapshot};
 
+use std::cmp::Ordering;
 use std::convert::TryInto;
 use std::num::NonZeroUsize;
 use std::sync::{Arc, RwLock};
 
 pub(crate) const SUBSCRIBER_NAME: &str = "Indexer";
+// assume that long fork will not happen >= 100 blocks.
+const KEEP_NUM: u64 = 100;
+const PRUNE_INTERVAL: u64 = 1000;
 const DEFAULT_LOG_KEEP_NUM: usize = 1;
 const DEFAULT_MAX_BACKGROUND_JOBS: usize = 6;
+const CELL_EVENTS_CHANNEL_SIZE: usize = 128;
 
 /// Indexer service
 #[derive(Clone)]
@@ -32,6 +41,7 @@ pub struct IndexerService {
     block_filter: Option<String>,
     cell_filter: Option<String>,
     request_limit: usize,
+    cell_events: broadcast::Sender<Arc<BlockCellEvents>>,
 }
 
 impl IndexerService {
@@ -51,6 +61,7 @@ impl IndexerService {
             async_handle,
             config.init_tip_hash.clone(),
         );
+        let (cell_events, _) = broadcast::channel(CELL_EVENTS_CHANNEL_SIZE);
 
         Self {
             store,
@@ -58,6 +69,7 @@ impl IndexerService {
             block_filter: config.block_filter.clone(),
             cell_filter: config.cell_filter.clone(),
             request_limit: config.request_limit.unwrap_or(usize::MAX),
+            cell_events,
         }
     }
 
@@ -70,6 +82,8 @@ impl IndexerService {
             store: self.store.clone(),
             pool: self.sync.pool(),
             request_limit: self.request_limit,
+            cell_events: self.cell_events.clone(),
+            reindexer: Some(self.sync.reindexer()),
         }
     }
 
@@ -92,15 +106,14 @@ impl IndexerService {
     }
 
     fn get_indexer(&self) -> Indexer<RocksdbStore> {
-        // assume that long fork will not happen >= 100 blocks.
-        let keep_num = 100;
         Indexer::new(
             self.store.clone(),
-            keep_num,
-            1000,
+            KEEP_NUM,
+            PRUNE_INTERVAL,
             self.sync.pool(),
             CustomFilters::new(self.block_filter.as_deref(), self.cell_filter.as_deref()),
         )
+        .with_cell_events(self.cell_events.clone())
     }
 
     /// Processes that handle block cell and expect to be spawned to run in tokio runtime
@@ -128,9 +141,40 @@ pub struct IndexerHandle {
     pub(crate) store: RocksdbStore,
     pub(crate) pool: Option<Arc<RwLock<Pool>>>,
     request_limit: usize,
+    cell_events: broadcast::Sender<Arc<BlockCellEvents>>,
+    reindexer: Option<Reindexer>,
 }
 
 impl IndexerHandle {
+    /// Subscribes the cell events of the blocks appended to or rolled back from the indexer.
+    ///
+    /// Use [CellEventFilter] to select the cells matching a search key. The indexer only collects
+    /// the events while there are receivers, so drop the receiver when it is no longer used.
+    pub fn subscribe_cell_events(&self) -> broadcast::Receiver<Arc<BlockCellEvents>> {
+        self.cell_events.subscribe()
+    }
+
+    /// Re-index the blocks in `[from, to]` in the background, merging the cells and transactions
+    /// which are not indexed yet into the store, while the indexer keeps following the tip.
+    ///
+    /// Returns an error if `to` is beyond the indexer tip or a re-indexing is running.
+    pub fn reindex(&self, from: core::BlockNumber, to: core::BlockNumber) -> Result<(), Error> {
+        let reindexer = self
+            .reindexer
+            .as_ref()
+            .ok_or_else(|| Error::invalid_params("the re-indexing is not enabled"))?;
+        let tip = self
+            .get_indexer_tip()?
+            .ok_or_else(|| Error::invalid_params("the indexer has not indexed any block yet"))?;
+        let tip_number: core::BlockNumber = tip.block_number.into();
+        if to > tip_number {
+            return Err(Error::invalid_params(format!(
+                "to {to} is beyond the indexer tip {tip_number}"
+            )));
+        }
+        reindexer.reindex(from, to)
+    }
+
     /// Get indexer current tip
     pub fn get_indexer_tip(&self) -> Result<Option<IndexerTip>, Error> {
         let mut iter = self
@@ -149,12 +193,16 @@ impl IndexerHandle {
     }
 
     /// Get cells by specified params
+    ///
+    /// If `as_of_block` is given, returns the cells which were live at that block instead of the
+    /// current live cells, the cells consumed by the pool transactions are not excluded then.
     pub fn get_cells(
         &self,
         search_key: IndexerSearchKey,
         order: IndexerOrder,
         limit: Uint32,
         after_cursor: Option<JsonBytes>,
+        as_of_block: Option<BlockNumber>,
     ) -> Result<IndexerPagination<IndexerCell>, Error> {
         if search_key
             .script_search_mode
@@ -179,7 +227,7 @@ impl IndexerHandle {
             )));
         }
 
-        let (prefix, from_key, direction, skip) = build_query_options(
+        let (prefixes, after_key, direction) = build_query_options(
             &search_key,
             KeyPrefix::CellLockScript,
             KeyPrefix::CellTypeScript,
@@ -187,6 +235,7 @@ impl IndexerHandle {
             after_cursor,
         )?;
 
+        let script_type = search_key.script_type.clone();
         let filter_script_type = match search_key.script_type {
             IndexerScriptType::Lock => IndexerScriptType::Type,
             IndexerScriptType::Type => IndexerScriptType::Lock,
@@ -196,136 +245,99 @@ impl IndexerHandle {
             Some(IndexerSearchMode::Exact)
         );
         let filter_options: FilterOptions = search_key.try_into()?;
-        let mode = IteratorMode::From(from_key.as_ref(), direction);
+        let forward = matches!(direction, Direction::Forward);
         let snapshot = self.store.inner().snapshot();
-        let iter = snapshot.iterator(mode).skip(skip);
+        let as_of_block: Option<core::BlockNumber> = as_of_block.map(Into::into);
+        // CellLockScript / CellTypeScript keys end with BlockNumber (8) + TxIndex (4) + OutputIndex (4)
+        let order = KeyOrder::new(&prefixes, 16);
+        let consumed_cells = match as_of_block {
+            Some(as_of_block) => {
+                as_of_block_hash(&snapshot, as_of_block)?;
+                let mut cells = consumed_after(
+                    &snapshot,
+                    as_of_block,
+                    &script_type,
+                    &prefixes,
+                    script_search_exact,
+                    order,
+                );
+                if let Some(after_key) = after_key.as_ref() {
+                    cells.retain(|(key, _)| order.is_after(key, after_key, forward));
+                }
+                if !forward {
+                    cells.reverse();
+                }
+                cells
+            }
+            None => Vec::new(),
+        };
+        // Exact match mode, check key length is equal to full script len + BlockNumber (8) + TxIndex (4) + OutputIndex (4)
+        let iter = iter_prefixes(
+            &snapshot,
+            &prefixes,
+            forward,
+            after_key.as_deref(),
+            16,
+            script_search_exact,
+        );
 
         let mut last_key = Vec::new();
         let pool = self
             .pool
             .as_ref()
+            .filter(|_| as_of_block.is_none())
             .map(|pool| pool.read().expect("acquire lock"));
-        let cells = iter
-            .take_while(|(key, _value)| key.starts_with(&prefix))
-            .filter_map(|(key, value)| {
-                if script_search_exact {
-                    // Exact match mode, check key length is equal to full script len + BlockNumber (8) + TxIndex (4) + OutputIndex (4)
-                    if key.len() != prefix.len() + 16 {
-                        return None;
-                    }
-                }
-                let tx_hash = packed::Byte32::from_slice(&value).expect("stored tx hash");
-                let index =
-                    u32::from_be_bytes(key[key.len() - 4..].try_into().expect("stored index"));
-                let out_point = packed::OutPoint::new(tx_hash, index);
-                if pool
-                    .as_ref()
-                    .map(|pool| pool.is_consumed_by_pool_tx(&out_point))
-                    .unwrap_or_default()
-                {
+        let live_cells = iter.filter_map(|(key, value)| {
+            if let Some(as_of_block) = as_of_block {
+                if cell_key_block_number(&key) > as_of_block {
                     return None;
                 }
-                let (block_number, tx_index, output, output_data) = Value::parse_cell_value(
-                    &snapshot
-                        .get(Key::OutPoint(&out_point).into_vec())
-                        .expect("get OutPoint should be OK")
-                        .expect("stored OutPoint"),
-                );
-
-                if let Some(prefix) = filter_options.script_prefix.as_ref() {
-                    match filter_script_type {
-                        IndexerScriptType::Lock => {
-                            if !extract_raw_data(&output.lock())
-                                .as_slice()
-                                .starts_with(prefix)
-                            {
-                                return None;
-                            }
-                        }
-                        IndexerScriptType::Type => {
-                            if output.type_().is_none()
-                                || !extract_raw_data(&output.type_().to_opt().unwrap())
-                                    .as_slice()
-                                    .starts_with(prefix)
-                            {
-                                return None;
-                            }
-                        }
-                    }
-                }
-
-                if let Some([r0, r1]) = filter_options.script_len_range {
-                    match filter_script_type {
-                        IndexerScriptType::Lock => {
-                            let script_len = extract_raw_data(&output.lock()).len();
-                            if script_len < r0 || script_len >= r1 {
-                                return None;
-                            }
-                        }
-                        IndexerScriptType::Type => {
-                            let script_len = output
-                                .type_()
-                                .to_opt()
-                                .map(|script| extract_raw_data(&script).len())
-                                .unwrap_or_default();
-                            if script_len < r0 || script_len >= r1 {
-                                return None;
-                            }
-                        }
-                    }
-                }
-
-                if let Some((data, mode)) = &filter_options.output_data {
-                    match mode {
-                        IndexerSearchMode::Prefix => {
-                            if !output_data.raw_data().starts_with(data) {
-                                return None;
-                            }
-                        }
-                        IndexerSearchMode::Exact => {
-                            if output_data.raw_data() != data {
-                                return None;
-                            }
-                        }
-                        IndexerSearchMode::Partial => {
-                            memmem::find(&output_data.raw_data(), data)?;
-                        }
-                    }
-                }
-
-                if let Some([r0, r1]) = filter_options.output_data_len_range {
-                    if output_data.len() < r0 || output_data.len() >= r1 {
-                        return None;
-                    }
-                }
-
-                if let Some([r0, r1]) = filter_options.output_capacity_range {
-                    let capacity: core::Capacity = output.capacity().unpack();
-                    if capacity < r0 || capacity >= r1 {
-                        return None;
-                    }
-                }
-
-                if let Some([r0, r1]) = filter_options.block_range {
-                    if block_number < r0 || block_number >= r1 {
+            }
+            let tx_hash = packed::Byte32::from_slice(&value).expect("stored tx hash");
+            let index = u32::from_be_bytes(key[key.len() - 4..].try_into().expect("stored index"));
+            let out_point = packed::OutPoint::new(tx_hash, index);
+            if pool
+                .as_ref()
+                .map(|pool| pool.is_consumed_by_pool_tx(&out_point))
+                .unwrap_or_default()
+            {
+                return None;
+            }
+            let cell = Value::parse_cell_value(
+                &snapshot
+                    .get(Key::OutPoint(&out_point).into_vec())
+                    .expect("get OutPoint should be OK")
+                    .expect("stored OutPoint"),
+            );
+            Some((key, (out_point, cell)))
+        });
+        let cells = merge_by_key(live_cells, consumed_cells.into_iter(), forward, order)
+            .filter_map(
+                |(key, (out_point, (block_number, tx_index, output, output_data)))| {
+                    if !filter_options.is_cell_match(
+                        &filter_script_type,
+                        block_number,
+                        &output,
+                        &output_data,
+                    ) {
                         return None;
                     }
-                }
 
-                last_key = key.to_vec();
+                    last_key = key;
 
-                Some(IndexerCell {
-                    output: output.into(),
-                    output_data: if filter_options.with_data {
-                        Some(output_data.into())
-                    } else {
-                        None
-                    },
-                    out_point: out_point.into(),
-                    block_number: block_number.into(),
-                    tx_index: tx_index.into(),
-                })
-            })
+                    Some(IndexerCell {
+                        output: output.into(),
+                        output_data: if filter_options.with_data {
+                            Some(output_data.into())
+                        } else {
+                            None
+                        },
+                        out_point: out_point.into(),
+                        block_number: block_number.into(),
+                        tx_index: tx_index.into(),
+                    })
+                },
+            )
             .take(limit)
             .collect::<Vec<_>>();
 
@@ -363,7 +375,7 @@ impl IndexerHandle {
             ));
         }
 
-        let (prefix, from_key, direction, skip) = build_query_options(
+        let (prefixes, after_key, direction) = build_query_options(
             &search_key,
             KeyPrefix::TxLockScript,
             KeyPrefix::TxTypeScript,
@@ -412,20 +424,24 @@ impl IndexerHandle {
             Some(IndexerSearchMode::Exact)
         );
 
-        let mode = IteratorMode::From(from_key.as_ref(), direction);
         let snapshot = self.store.inner().snapshot();
-        let iter = snapshot.iterator(mode).skip(skip);
+        // Exact match mode, check key length is equal to full script len + BlockNumber (8) + TxIndex (4) + CellIndex (4) + CellType (1)
+        //
+        // The keys of a transaction are adjacent in the order of the positions, so the cells of a
+        // transaction matched by multiple scripts are grouped together.
+        let iter = iter_prefixes(
+            &snapshot,
+            &prefixes,
+            matches!(direction, Direction::Forward),
+            after_key.as_deref(),
+            17,
+            script_search_exact,
+        );
 
         if search_key.group_by_transaction.unwrap_or_default() {
             let mut tx_with_cells: Vec<IndexerTxWithCells> = Vec::new();
             let mut last_key = Vec::new();
-            for (key, value) in iter.take_while(|(key, _value)| key.starts_with(&prefix)) {
-                if script_search_exact {
-                    // Exact match mode, check key length is equal to full script len + BlockNumber (8) + TxIndex (4) + CellIndex (4) + CellType (1)
-                    if key.len() != prefix.len() + 17 {
-                        continue;
-                    }
-                }
+            for (key, value) in iter {
                 let tx_hash: H256 = packed::Byte32::from_slice(&value)
                     .expect("stored tx hash")
                     .unpack();
@@ -434,7 +450,7 @@ impl IndexerHandle {
                 {
                     break;
                 }
-                last_key = key.to_vec();
+                last_key = key.clone();
                 let block_number = u64::from_be_bytes(
                     key[key.len() - 17..key.len() - 9]
                         .try_into()
@@ -531,14 +547,7 @@ impl IndexerHandle {
         } else {
             let mut last_key = Vec::new();
             let txs = iter
-                .take_while(|(key, _value)| key.starts_with(&prefix))
                 .filter_map(|(key, value)| {
-                    if script_search_exact {
-                        // Exact match mode, check key length is equal to full script len + BlockNumber (8) + TxIndex (4) + CellIndex (4) + CellType (1)
-                        if key.len() != prefix.len() + 17 {
-                            return None;
-                        }
-                    }
                     let tx_hash = packed::Byte32::from_slice(&value).expect("stored tx hash");
                     let block_number = u64::from_be_bytes(
                         key[key.len() - 17..key.len() - 9]
@@ -610,7 +619,7 @@ impl IndexerHandle {
                         }
                     }
 
-                    last_key = key.to_vec();
+                    last_key = key;
                     Some(IndexerTx::Ungrouped(IndexerTxWithCell {
                         tx_hash: tx_hash.unpack(),
                         block_number: block_number.into(),
@@ -627,9 +636,12 @@ impl IndexerHandle {
     }
 
     /// Get cells_capacity by specified search_key
+    ///
+    /// If `as_of_block` is given, returns the capacity of the cells which were live at that block.
     pub fn get_cells_capacity(
         &self,
         search_key: IndexerSearchKey,
+        as_of_block: Option<BlockNumber>,
     ) -> Result<Option<IndexerCellsCapacity>, Error> {
         if search_key
             .script_search_mode
@@ -643,7 +655,7 @@ impl IndexerHandle {
             ));
         }
 
-        let (prefix, from_key, direction, skip) = build_query_options(
+        let (prefixes, ..) = build_query_options(
             &search_key,
             KeyPrefix::CellLockScript,
             KeyPrefix::CellTypeScript,
@@ -658,21 +670,25 @@ impl IndexerHandle {
             search_key.script_search_mode,
             Some(IndexerSearchMode::Exact)
         );
+        let script_type = search_key.script_type.clone();
         let filter_options: FilterOptions = search_key.try_into()?;
-        let mode = IteratorMode::From(from_key.as_ref(), direction);
         let snapshot = self.store.inner().snapshot();
-        let iter = snapshot.iterator(mode).skip(skip);
+        let as_of_block: Option<core::BlockNumber> = as_of_block.map(Into::into);
+        let as_of_hash = as_of_block
+            .map(|as_of_block| as_of_block_hash(&snapshot, as_of_block))
+            .transpose()?;
+        // Exact match mode, check key length is equal to full script len + BlockNumber (8) + TxIndex (4) + OutputIndex (4)
+        let iter = iter_prefixes(&snapshot, &prefixes, true, None, 16, script_search_exact);
         let pool = self
             .pool
             .as_ref()
+            .filter(|_| as_of_block.is_none())
             .map(|pool| pool.read().expect("acquire lock"));
 
         let capacity: u64 = iter
-            .take_while(|(key, _value)| key.starts_with(&prefix))
             .filter_map(|(key, value)| {
-                if script_search_exact {
-                    // Exact match mode, check key length is equal to full script len + BlockNumber (8) + TxIndex (4) + OutputIndex (4)
-                    if key.len() != prefix.len() + 16 {
+                if let Some(as_of_block) = as_of_block {
+                    if cell_key_block_number(&key) > as_of_block {
                         return None;
                     }
                 }
@@ -778,6 +794,37 @@ impl IndexerHandle {
             })
             .sum();
 
+        if let (Some(as_of_block), Some(block_hash)) = (as_of_block, as_of_hash) {
+            let consumed_capacity: u64 = consumed_after(
+                &snapshot,
+                as_of_block,
+                &script_type,
+                &prefixes,
+                script_search_exact,
+                KeyOrder::Key,
+            )
+            .into_iter()
+            .filter(
+                |(_key, (_out_point, (block_number, _tx_index, output, output_data)))| {
+                    filter_options.is_cell_match(
+                        &filter_script_type,
+                        *block_number,
+                        output,
+                        output_data,
+                    )
+                },
+            )
+            .map(|(_key, (_out_point, (_, _, output, _)))| {
+                Unpack::<core::Capacity>::unpack(&output.capacity()).as_u64()
+            })
+            .sum();
+            return Ok(Some(IndexerCellsCapacity {
+                capacity: (capacity + consumed_capacity).into(),
+                block_hash: block_hash.unpack(),
+                block_number: as_of_block.into(),
+            }));
+        }
+
         let tip_mode = IteratorMode::From(&[KeyPrefix::Header as u8 + 1], Direction::Reverse);
         let mut tip_iter = snapshot.iterator(tip_mode);
         Ok(tip_iter.next().map(|(key, _value)| IndexerCellsCapacity {
@@ -793,51 +840,438 @@ impl IndexerHandle {
     }
 }
 
+/// Selects the cell events matching a search key.
+///
+/// The search key is interpreted the same way as in [IndexerHandle::get_cells].
+pub struct CellEventFilter {
+    scripts: Vec<Vec<u8>>,
+    script_type: IndexerScriptType,
+    script_search_exact: bool,
+    filter_script_type: IndexerScriptType,
+    filter_options: FilterOptions,
+}
+
+impl CellEventFilter {
+    /// Creates a filter from the search key.
+    pub fn new(search_key: IndexerSearchKey) -> Result<Self, Error> {
+        if search_key
+            .script_search_mode
+            .as_ref()
+            .map(|mode| *mode == IndexerSearchMode::Partial)
+            .unwrap_or(false)
+        {
+            return Err(Error::invalid_params(
+                "the CKB indexer doesn't support search_key.script_search_mode partial search mode",
+            ));
+        }
+        let scripts = search_scripts_raw_data(&search_key)?;
+        let script_type = search_key.script_type.clone();
+        let filter_script_type = match script_type {
+            IndexerScriptType::Lock => IndexerScriptType::Type,
+            IndexerScriptType::Type => IndexerScriptType::Lock,
+        };
+        let script_search_exact = matches!(
+            search_key.script_search_mode,
+            Some(IndexerSearchMode::Exact)
+        );
+        let filter_options: FilterOptions = search_key.try_into()?;
+        Ok(CellEventFilter {
+            scripts,
+            script_type,
+            script_search_exact,
+            filter_script_type,
+            filter_options,
+        })
+    }
+
+    /// Returns the matched events of the block.
+    pub fn filter(&self, block: &BlockCellEvents) -> Vec<IndexerCellEvent> {
+        let block_hash: H256 = block.block_hash.unpack();
+        block
+            .events
+            .iter()
+            .filter(|event| self.is_match(event))
+            .map(|event| IndexerCellEvent {
+                event_type: event.event_type,
+                rollback: block.rollback,
+                block_hash: block_hash.clone(),
+                block_number: block.block_number.into(),
+                tx_hash: event.tx_hash.unpack(),
+                cell: IndexerCell {
+                    output: event.output.clone().into(),
+                    output_data: if self.filter_options.with_data {
+                        Some(event.output_data.clone().into())
+                    } else {
+                        None
+                    },
+                    out_point: event.out_point.clone().into(),
+                    block_number: event.created_block_number.into(),
+                    tx_index: event.created_tx_index.into(),
+                },
+            })
+            .collect()
+    }
+
+    fn is_match(&self, event: &CellEvent) -> bool {
+        let script = match self.script_type {
+            IndexerScriptType::Lock => Some(event.output.lock()),
+            IndexerScriptType::Type => event.output.type_().to_opt(),
+        };
+        let script_matched = script
+            .map(|script| {
+                let raw_data = extract_raw_data(&script);
+                self.scripts.iter().any(|search_script| {
+                    if self.script_search_exact {
+                        &raw_data == search_script
+                    } else {
+                        raw_data.starts_with(search_script)
+                    }
+                })
+            })
+            .unwrap_or(false);
+        script_matched
+            && self.filter_options.is_cell_match(
+                &self.filter_script_type,
+                event.created_block_number,
+                &event.output,
+                &event.output_data,
+            )
+    }
+}
+
 const MAX_PREFIX_SEARCH_SIZE: usize = u16::MAX as usize;
 
-// a helper fn to build query options from search parameters, returns prefix, from_key, direction and skip offset
+/// The maximum number of scripts in a search key, including `script` and `scripts`.
+const MAX_SEARCH_SCRIPTS: usize = 1000;
+
+// a helper fn to collect the raw data of the search key scripts, sorted and deduplicated, in prefix
+// mode the scripts covered by a shorter prefix are removed so that no key is matched twice
+fn search_scripts_raw_data(search_key: &IndexerSearchKey) -> Result<Vec<Vec<u8>>, Error> {
+    if search_key.search_scripts().count() > MAX_SEARCH_SCRIPTS {
+        return Err(Error::invalid_params(format!(
+            "search_key.scripts len should be less than {MAX_SEARCH_SCRIPTS}"
+        )));
+    }
+    let mut raw_data_list = Vec::new();
+    for script in search_key.search_scripts() {
+        let script: packed::Script = script.clone().into();
+        if script.args().len() > MAX_PREFIX_SEARCH_SIZE {
+            return Err(Error::invalid_params(format!(
+                "search_key.script.args len should be less than {MAX_PREFIX_SEARCH_SIZE}"
+            )));
+        }
+        raw_data_list.push(extract_raw_data(&script));
+    }
+    raw_data_list.sort_unstable();
+    raw_data_list.dedup();
+    if !matches!(
+        search_key.script_search_mode,
+        Some(IndexerSearchMode::Exact)
+    ) {
+        // a prefix sorts right before the raw data it covers
+        raw_data_list.dedup_by(|raw_data, prev| raw_data.starts_with(prev));
+    }
+    Ok(raw_data_list)
+}
+
+// a helper fn to build query options from search parameters, returns the key prefixes of the search
+// scripts, the key to start after and direction
 fn build_query_options(
     search_key: &IndexerSearchKey,
     lock_prefix: KeyPrefix,
     type_prefix: KeyPrefix,
     order: IndexerOrder,
     after_cursor: Option<JsonBytes>,
-) -> Result<(Vec<u8>, Vec<u8>, Direction, usize), Error> {
-    let mut prefix = match search_key.script_type {
-        IndexerScriptType::Lock => vec![lock_prefix as u8],
-        IndexerScriptType::Type => vec![type_prefix as u8],
+) -> Result<(Vec<Vec<u8>>, Option<Vec<u8>>, Direction), Error> {
+    let key_prefix = match search_key.script_type {
+        IndexerScriptType::Lock => lock_prefix as u8,
+        IndexerScriptType::Type => type_prefix as u8,
     };
-    let script: packed::Script = search_key.script.clone().into();
-    let args_len = script.args().len();
-    if args_len > MAX_PREFIX_SEARCH_SIZE {
-        return Err(Error::invalid_params(format!(
-            "search_key.script.args len should be less than {MAX_PREFIX_SEARCH_SIZE}"
-        )));
+    let raw_data_list = search_scripts_raw_data(search_key)?;
+    // the keys of a script prefix are ordered by the matched scripts, the results of multiple
+    // prefixes can't be merged by the positions without loading all of them
+    if raw_data_list.len() > 1
+        && !matches!(
+            search_key.script_search_mode,
+            Some(IndexerSearchMode::Exact)
+        )
+    {
+        return Err(Error::invalid_params(
+            "the CKB indexer only supports search_key.scripts in the exact search mode, \
+            please use the CKB rich-indexer for the prefix search of multiple scripts",
+        ));
+    }
+    let prefixes = raw_data_list
+        .into_iter()
+        .map(|raw_data| [vec![key_prefix], raw_data].concat())
+        .collect();
+    let direction = match order {
+        IndexerOrder::Asc => Direction::Forward,
+        IndexerOrder::Desc => Direction::Reverse,
+    };
+
+    Ok((
+        prefixes,
+        after_cursor.map(|json_bytes| json_bytes.as_bytes().to_vec()),
+        direction,
+    ))
+}
+
+type KeyValueIter<'a, T> = Box<dyn Iterator<Item = (Vec<u8>, T)> + 'a>;
+
+// The order of the results.
+//
+// The keys of a single search script are in the key order, which is the order of the positions
+// for an exact script. The results of multiple exact search scripts are merged in the order of the
+// positions, that is the trailing block number, tx index, io index (and io type) of `suffix_len`
+// bytes, and then the whole key.
+#[derive(Clone, Copy)]
+enum KeyOrder {
+    Key,
+    Position(usize),
+}
+
+impl KeyOrder {
+    fn new(prefixes: &[Vec<u8>], suffix_len: usize) -> Self {
+        if prefixes.len() > 1 {
+            KeyOrder::Position(suffix_len)
+        } else {
+            KeyOrder::Key
+        }
+    }
+
+    fn cmp(self, a: &[u8], b: &[u8]) -> Ordering {
+        match self {
+            KeyOrder::Key => a.cmp(b),
+            KeyOrder::Position(len) => a[a.len().saturating_sub(len)..]
+                .cmp(&b[b.len().saturating_sub(len)..])
+                .then_with(|| a.cmp(b)),
+        }
+    }
+
+    // returns true if `key` comes after `after_key` in the direction
+    fn is_after(self, key: &[u8], after_key: &[u8], forward: bool) -> bool {
+        let ordering = self.cmp(key, after_key);
+        if forward {
+            ordering.is_gt()
+        } else {
+            ordering.is_lt()
+        }
     }
-    prefix.extend_from_slice(extract_raw_data(&script).as_slice());
-
-    let (from_key, direction, skip) = match order {
-        IndexerOrder::Asc => after_cursor.map_or_else(
-            || (prefix.clone(), Direction::Forward, 0),
-            |json_bytes| (json_bytes.as_bytes().into(), Direction::Forward, 1),
-        ),
-        IndexerOrder::Desc => after_cursor.map_or_else(
-            || {
-                (
+}
+
+// iterates the keys starting with any of the prefixes in the `KeyOrder`, skips the keys up to
+// and including `after_key`, and in the exact mode only yields the keys of exactly the prefix
+// length plus the suffix length
+//
+// Multiple prefixes must be searched in the exact mode, see `build_query_options`.
+fn iter_prefixes<'a>(
+    snapshot: &'a Snapshot<'_>,
+    prefixes: &'a [Vec<u8>],
+    forward: bool,
+    after_key: Option<&'a [u8]>,
+    suffix_len: usize,
+    exact: bool,
+) -> KeyValueIter<'a, Box<[u8]>> {
+    debug_assert!(exact || prefixes.len() <= 1);
+    let order = KeyOrder::new(prefixes, suffix_len);
+    let iters = prefixes
+        .iter()
+        .map(move |prefix| {
+            // where to seek to skip the keys up to `after_key`
+            let seek_key = after_key.and_then(|after_key| match order {
+                KeyOrder::Key => Some(after_key.to_vec()),
+                // the keys of an exact script are in the order of the positions
+                KeyOrder::Position(len) => Some(
                     [
-                        prefix.clone(),
-                        vec![0xff; MAX_PREFIX_SEARCH_SIZE - args_len],
+                        prefix.as_slice(),
+                        &after_key[after_key.len().saturating_sub(len)..],
                     ]
                     .concat(),
-                    Direction::Reverse,
-                    0,
-                )
-            },
-            |json_bytes| (json_bytes.as_bytes().into(), Direction::Reverse, 1),
-        ),
-    };
+                ),
+            });
+            let (from_key, direction) = if forward {
+                let from_key = match seek_key {
+                    Some(seek_key) if seek_key > *prefix => seek_key,
+                    _ => prefix.clone(),
+                };
+                (from_key, Direction::Forward)
+            } else {
+                let last_key = [prefix.clone(), vec![0xff; MAX_PREFIX_SEARCH_SIZE]].concat();
+                let from_key = match seek_key {
+                    Some(seek_key) if seek_key < last_key => seek_key,
+                    _ => last_key,
+                };
+                (from_key, Direction::Reverse)
+            };
+            let iter = snapshot
+                .iterator(IteratorMode::From(from_key.as_ref(), direction))
+                .take_while(move |(key, _value)| key.starts_with(prefix))
+                .filter(move |(key, _value)| {
+                    after_key.map_or(true, |after_key| order.is_after(key, after_key, forward))
+                        && (!exact || key.len() == prefix.len() + suffix_len)
+                })
+                .map(|(key, value)| (key.to_vec(), value));
+            Box::new(iter) as KeyValueIter<'a, Box<[u8]>>
+        })
+        .collect();
+    merge_all_by_key(iters, forward, order)
+}
+
+// the block number in a CellLockScript / CellTypeScript key
+fn cell_key_block_number(key: &[u8]) -> core::BlockNumber {
+    core::BlockNumber::from_be_bytes(
+        key[key.len() - 16..key.len() - 8]
+            .try_into()
+            .expect("stored block_number"),
+    )
+}
+
+// checks the block can be queried by the historical cells queries, returns its hash
+fn as_of_block_hash(
+    snapshot: &Snapshot<'_>,
+    as_of_block: core::BlockNumber,
+) -> Result<packed::Byte32, Error> {
+    let tip_mode = IteratorMode::From(&[KeyPrefix::Header as u8 + 1], Direction::Reverse);
+    let tip_number = snapshot
+        .iterator(tip_mode)
+        .next()
+        .map(|(key, _value)| {
+            core::BlockNumber::from_be_bytes(key[1..9].try_into().expect("stored block key"))
+        })
+        .ok_or_else(|| Error::invalid_params("the indexer has not indexed any block yet"))?;
+    if as_of_block > tip_number {
+        return Err(Error::invalid_params(format!(
+            "as_of_block {as_of_block} is beyond the indexer tip {tip_number}"
+        )));
+    }
+
+    let pruned_to = snapshot
+        .get(Key::PrunedTo.into_vec())
+        .expect("get PrunedTo should be OK")
+        .map(|value| Value::parse_block_number_value(&value))
+        // the indexer pruned before recording the pruned block number, assumes it has been
+        // pruned as much as possible until the next pruning records it
+        .or_else(|| {
+            (tip_number >= PRUNE_INTERVAL).then_some(tip_number - KEEP_NUM - 1)
+        });
+    if let Some(pruned_to) = pruned_to {
+        if as_of_block <= pruned_to {
+            return Err(Error::invalid_params(format!(
+                "the indexer has been pruned to block {pruned_to}, \
+                as_of_block must be greater than it"
+            )));
+        }
+    }
+
+    let mut header_prefix = vec![KeyPrefix::Header as u8];
+    header_prefix.extend_from_slice(&as_of_block.to_be_bytes());
+    let header_mode = IteratorMode::From(&header_prefix, Direction::Forward);
+    let block_hash = snapshot
+        .iterator(header_mode)
+        .next()
+        .filter(|(key, _value)| key.starts_with(&header_prefix))
+        .map(|(key, _value)| packed::Byte32::from_slice(&key[9..41]).expect("stored block key"));
+    block_hash.ok_or_else(|| {
+        Error::invalid_params(format!(
+            "block {as_of_block} is not indexed, it is before the indexer init tip"
+        ))
+    })
+}
+
+type CellValue = (
+    core::BlockNumber,
+    indexer::TxIndex,
+    packed::CellOutput,
+    packed::Bytes,
+);
+
+// the cells created at or before the block and consumed after it, keyed by their
+// CellLockScript / CellTypeScript keys when they were live and sorted in the `KeyOrder`
+fn consumed_after(
+    snapshot: &Snapshot<'_>,
+    as_of_block: core::BlockNumber,
+    script_type: &IndexerScriptType,
+    prefixes: &[Vec<u8>],
+    script_search_exact: bool,
+    order: KeyOrder,
+) -> Vec<(Vec<u8>, (packed::OutPoint, CellValue))> {
+    let mut start_key = vec![KeyPrefix::ConsumedOutPoint as u8];
+    start_key.extend_from_slice(&(as_of_block + 1).to_be_bytes());
+    let mode = IteratorMode::From(&start_key, Direction::Forward);
+    let mut cells = snapshot
+        .iterator(mode)
+        .take_while(|(key, _value)| key[0] == KeyPrefix::ConsumedOutPoint as u8)
+        .filter_map(|(key, value)| {
+            let (block_number, tx_index, output, output_data) = Value::parse_cell_value(&value);
+            if block_number > as_of_block {
+                return None;
+            }
+            let out_point = packed::OutPoint::from_slice(&key[9..]).expect("stored out_point");
+            let index = out_point.index().unpack();
+            let key = match script_type {
+                IndexerScriptType::Lock => {
+                    Key::CellLockScript(&output.lock(), block_number, tx_index, index).into_vec()
+                }
+                IndexerScriptType::Type => {
+                    Key::CellTypeScript(&output.type_().to_opt()?, block_number, tx_index, index)
+                        .into_vec()
+                }
+            };
+            if !prefixes.iter().any(|prefix| {
+                key.starts_with(prefix) && (!script_search_exact || key.len() == prefix.len() + 16)
+            }) {
+                return None;
+            }
+            Some((
+                key,
+                (out_point, (block_number, tx_index, output, output_data)),
+            ))
+        })
+        .collect::<Vec<_>>();
+    cells.sort_unstable_by(|a, b| order.cmp(&a.0, &b.0));
+    cells
+}
+
+// merges two iterators sorted in the `KeyOrder` in the same direction
+fn merge_by_key<T>(
+    a: impl Iterator<Item = (Vec<u8>, T)>,
+    b: impl Iterator<Item = (Vec<u8>, T)>,
+    forward: bool,
+    order: KeyOrder,
+) -> impl Iterator<Item = (Vec<u8>, T)> {
+    let (mut a, mut b) = (a.peekable(), b.peekable());
+    std::iter::from_fn(move || {
+        let take_a = match (a.peek(), b.peek()) {
+            (Some((key_a, _)), Some((key_b, _))) => order.cmp(key_a, key_b).is_lt() == forward,
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+        if take_a {
+            a.next()
+        } else {
+            b.next()
+        }
+    })
+}
 
-    Ok((prefix, from_key, direction, skip))
+// merges iterators sorted in the `KeyOrder` in the same direction, pairwise to keep the merge
+// tree shallow
+fn merge_all_by_key<'a, T: 'a>(
+    mut iters: Vec<KeyValueIter<'a, T>>,
+    forward: bool,
+    order: KeyOrder,
+) -> KeyValueIter<'a, T> {
+    if iters.len() <= 1 {
+        return iters.pop().unwrap_or_else(|| Box::new(std::iter::empty()));
+    }
+    let rest = iters.split_off(iters.len() / 2);
+    Box::new(merge_by_key(
+        merge_all_by_key(iters, forward, order),
+        merge_all_by_key(rest, forward, order),
+        forward,
+        order,
+    ))
 }
 
 struct FilterOptions {
@@ -850,10 +1284,105 @@ struct FilterOptions {
     with_data: bool,
 }
 
-impl TryInto<FilterOptions> for IndexerSearchKey {
-    type Error = Error;
-
-    fn try_into(self) -> Result<FilterOptions, Error> {
+impl FilterOptions {
+    // checks the cell against the filter conditions except the search key script
+    fn is_cell_match(
+        &self,
+        filter_script_type: &IndexerScriptType,
+        block_number: core::BlockNumber,
+        output: &packed::CellOutput,
+        output_data: &packed::Bytes,
+    ) -> bool {
+        if let Some(prefix) = self.script_prefix.as_ref() {
+            match filter_script_type {
+                IndexerScriptType::Lock => {
+                    if !extract_raw_data(&output.lock())
+                        .as_slice()
+                        .starts_with(prefix)
+                    {
+                        return false;
+                    }
+                }
+                IndexerScriptType::Type => {
+                    if output.type_().is_none()
+                        || !extract_raw_data(&output.type_().to_opt().unwrap())
+                            .as_slice()
+                            .starts_with(prefix)
+                    {
+                        return false;
+                    }
+                }
+            }
+        }
+
+        if let Some([r0, r1]) = self.script_len_range {
+            match filter_script_type {
+                IndexerScriptType::Lock => {
+                    let script_len = extract_raw_data(&output.lock()).len();
+                    if script_len < r0 || script_len >= r1 {
+                        return false;
+                    }
+                }
+                IndexerScriptType::Type => {
+                    let script_len = output
+                        .type_()
+                        .to_opt()
+                        .map(|script| extract_raw_data(&script).len())
+                        .unwrap_or_default();
+                    if script_len < r0 || script_len >= r1 {
+                        return false;
+                    }
+                }
+            }
+        }
+
+        if let Some((data, mode)) = &self.output_data {
+            match mode {
+                IndexerSearchMode::Prefix => {
+                    if !output_data.raw_data().starts_with(data) {
+                        return false;
+                    }
+                }
+                IndexerSearchMode::Exact => {
+                    if output_data.raw_data() != data {
+                        return false;
+                    }
+                }
+                IndexerSearchMode::Partial => {
+                    if memmem::find(&output_data.raw_data(), data).is_none() {
+                        return false;
+                    }
+                }
+            }
+        }
+
+        if let Some([r0, r1]) = self.output_data_len_range {
+            if output_data.len() < r0 || output_data.len() >= r1 {
+                return false;
+            }
+        }
+
+        if let Some([r0, r1]) = self.output_capacity_range {
+            let capacity: core::Capacity = output.capacity().unpack();
+            if capacity < r0 || capacity >= r1 {
+                return false;
+            }
+        }
+
+        if let Some([r0, r1]) = self.block_range {
+            if block_number < r0 || block_number >= r1 {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+impl TryInto<FilterOptions> for IndexerSearchKey {
+    type Error = Error;
+
+    fn try_into(self) -> Result<FilterOptions, Error> {
         let IndexerSearchKey {
             filter, with_data, ..
         } = self;
@@ -949,6 +1478,8 @@ mod tests {
             store,
             pool: Some(Arc::clone(&pool)),
             request_limit: usize::MAX,
+            cell_events: broadcast::channel(1).0,
+            reindexer: None,
         };
 
         // setup test data
@@ -1096,6 +1627,7 @@ mod tests {
                 IndexerOrder::Asc,
                 150.into(),
                 None,
+                None,
             )
             .unwrap();
         let cells_page_2 = rpc
@@ -1108,6 +1640,7 @@ mod tests {
                 IndexerOrder::Asc,
                 150.into(),
                 Some(cells_page_1.last_cursor),
+                None,
             )
             .unwrap();
 
@@ -1139,6 +1672,7 @@ mod tests {
                 IndexerOrder::Desc,
                 150.into(),
                 None,
+                None,
             )
             .unwrap();
 
@@ -1151,6 +1685,7 @@ mod tests {
                 IndexerOrder::Desc,
                 150.into(),
                 Some(desc_cells_page_1.last_cursor),
+                None,
             )
             .unwrap();
 
@@ -1177,6 +1712,7 @@ mod tests {
                 IndexerOrder::Asc,
                 60.into(),
                 None,
+                None,
             )
             .unwrap();
 
@@ -1193,6 +1729,7 @@ mod tests {
                 IndexerOrder::Asc,
                 60.into(),
                 Some(filter_cells_page_1.last_cursor),
+                None,
             )
             .unwrap();
 
@@ -1215,6 +1752,7 @@ mod tests {
                 IndexerOrder::Asc,
                 150.into(),
                 None,
+                None,
             )
             .unwrap();
 
@@ -1231,6 +1769,7 @@ mod tests {
                 IndexerOrder::Asc,
                 150.into(),
                 Some(filter_empty_type_script_cells_page_1.last_cursor),
+                None,
             )
             .unwrap();
 
@@ -1443,10 +1982,13 @@ mod tests {
 
         // test get_cells_capacity rpc
         let capacity = rpc
-            .get_cells_capacity(IndexerSearchKey {
-                script: lock_script1.clone().into(),
-                ..Default::default()
-            })
+            .get_cells_capacity(
+                IndexerSearchKey {
+                    script: lock_script1.clone().into(),
+                    ..Default::default()
+                },
+                None,
+            )
             .unwrap()
             .unwrap();
 
@@ -1457,10 +1999,13 @@ mod tests {
         );
 
         let capacity = rpc
-            .get_cells_capacity(IndexerSearchKey {
-                script: lock_script2.into(),
-                ..Default::default()
-            })
+            .get_cells_capacity(
+                IndexerSearchKey {
+                    script: lock_script2.into(),
+                    ..Default::default()
+                },
+                None,
+            )
             .unwrap()
             .unwrap();
 
@@ -1493,6 +2038,7 @@ mod tests {
                 IndexerOrder::Asc,
                 150.into(),
                 None,
+                None,
             )
             .unwrap();
         let cells_page_2 = rpc
@@ -1504,6 +2050,7 @@ mod tests {
                 IndexerOrder::Asc,
                 150.into(),
                 Some(cells_page_1.last_cursor),
+                None,
             )
             .unwrap();
 
@@ -1515,10 +2062,13 @@ mod tests {
 
         // test get_cells_capacity rpc with tx-pool overlay
         let capacity = rpc
-            .get_cells_capacity(IndexerSearchKey {
-                script: lock_script1.into(),
-                ..Default::default()
-            })
+            .get_cells_capacity(
+                IndexerSearchKey {
+                    script: lock_script1.into(),
+                    ..Default::default()
+                },
+                None,
+            )
             .unwrap()
             .unwrap();
 
@@ -1537,6 +2087,8 @@ mod tests {
             store,
             pool: None,
             request_limit: usize::MAX,
+            cell_events: broadcast::channel(1).0,
+            reindexer: None,
         };
 
         // setup test data
@@ -1679,6 +2231,7 @@ mod tests {
                 IndexerOrder::Asc,
                 1000.into(),
                 None,
+                None,
             )
             .unwrap();
 
@@ -1699,6 +2252,7 @@ mod tests {
                 IndexerOrder::Asc,
                 1000.into(),
                 None,
+                None,
             )
             .unwrap();
 
@@ -1747,11 +2301,14 @@ mod tests {
 
         // test get_cells_capacity rpc with exact search mode
         let capacity = rpc
-            .get_cells_capacity(IndexerSearchKey {
-                script: lock_script1.clone().into(),
-                script_search_mode: Some(IndexerSearchMode::Exact),
-                ..Default::default()
-            })
+            .get_cells_capacity(
+                IndexerSearchKey {
+                    script: lock_script1.clone().into(),
+                    script_search_mode: Some(IndexerSearchMode::Exact),
+                    ..Default::default()
+                },
+                None,
+            )
             .unwrap()
             .unwrap();
 
@@ -1763,10 +2320,13 @@ mod tests {
 
         // test get_cells_capacity rpc with prefix search mode (by default)
         let capacity = rpc
-            .get_cells_capacity(IndexerSearchKey {
-                script: lock_script1.into(),
-                ..Default::default()
-            })
+            .get_cells_capacity(
+                IndexerSearchKey {
+                    script: lock_script1.into(),
+                    ..Default::default()
+                },
+                None,
+            )
             .unwrap()
             .unwrap();
 
@@ -1776,6 +2336,392 @@ mod tests {
         );
     }
 
+    #[test]
+    fn as_of_block_rpc() {
+        let store = new_store("as_of_block_rpc");
+        let indexer = Indexer::new(store.clone(), 0, 100, None, CustomFilters::new(None, None));
+        let rpc = IndexerHandle {
+            store,
+            pool: None,
+            request_limit: usize::MAX,
+            cell_events: broadcast::channel(1).0,
+            reindexer: None,
+        };
+
+        let lock_script1 = ScriptBuilder::default()
+            .code_hash(H256(rand::random()).pack())
+            .hash_type(ScriptHashType::Data.into())
+            .args(Bytes::from(b"lock_script1".to_vec()).pack())
+            .build();
+        let lock_script2 = ScriptBuilder::default()
+            .code_hash(H256(rand::random()).pack())
+            .hash_type(ScriptHashType::Type.into())
+            .args(Bytes::from(b"lock_script2".to_vec()).pack())
+            .build();
+        let new_output = |capacity: Capacity, lock: &Script| {
+            CellOutputBuilder::default()
+                .capacity(capacity.pack())
+                .lock(lock.clone())
+                .build()
+        };
+
+        // block 0 creates two cells of lock_script1
+        let cellbase0 = TransactionBuilder::default()
+            .input(CellInput::new_cellbase_input(0))
+            .witness(Script::default().into_witness())
+            .output(new_output(capacity_bytes!(1000), &lock_script1))
+            .output_data(Default::default())
+            .build();
+        let tx00 = TransactionBuilder::default()
+            .output(new_output(capacity_bytes!(2000), &lock_script1))
+            .output_data(Default::default())
+            .build();
+        let block0 = BlockBuilder::default()
+            .transaction(cellbase0.clone())
+            .transaction(tx00.clone())
+            .header(HeaderBuilder::default().number(0.pack()).build())
+            .build();
+        // block 1 consumes them and creates one cell of lock_script1
+        let tx10 = TransactionBuilder::default()
+            .input(CellInput::new(OutPoint::new(cellbase0.hash(), 0), 0))
+            .input(CellInput::new(OutPoint::new(tx00.hash(), 0), 0))
+            .output(new_output(capacity_bytes!(500), &lock_script1))
+            .output(new_output(capacity_bytes!(2500), &lock_script2))
+            .outputs_data(vec![Bytes::new().pack(); 2])
+            .build();
+        let cellbase1 = TransactionBuilder::default()
+            .input(CellInput::new_cellbase_input(1))
+            .witness(Script::default().into_witness())
+            .output(new_output(capacity_bytes!(1000), &lock_script2))
+            .output_data(Default::default())
+            .build();
+        let block1 = BlockBuilder::default()
+            .transaction(cellbase1)
+            .transaction(tx10.clone())
+            .header(
+                HeaderBuilder::default()
+                    .number(1.pack())
+                    .parent_hash(block0.hash())
+                    .build(),
+            )
+            .build();
+        // block 2 consumes the cell of lock_script1
+        let tx20 = TransactionBuilder::default()
+            .input(CellInput::new(OutPoint::new(tx10.hash(), 0), 0))
+            .output(new_output(capacity_bytes!(500), &lock_script2))
+            .output_data(Default::default())
+            .build();
+        let cellbase2 = TransactionBuilder::default()
+            .input(CellInput::new_cellbase_input(2))
+            .witness(Script::default().into_witness())
+            .output(new_output(capacity_bytes!(1000), &lock_script2))
+            .output_data(Default::default())
+            .build();
+        let block2 = BlockBuilder::default()
+            .transaction(cellbase2)
+            .transaction(tx20)
+            .header(
+                HeaderBuilder::default()
+                    .number(2.pack())
+                    .parent_hash(block1.hash())
+                    .build(),
+            )
+            .build();
+        for block in [&block0, &block1, &block2] {
+            indexer.append(block).unwrap();
+        }
+
+        let search_key = || IndexerSearchKey {
+            script: lock_script1.clone().into(),
+            ..Default::default()
+        };
+        let get_cells = |order, limit: u32, after, as_of_block: u64| {
+            rpc.get_cells(
+                search_key(),
+                order,
+                limit.into(),
+                after,
+                Some(as_of_block.into()),
+            )
+        };
+        let out_points = |cells: &IndexerPagination<IndexerCell>| {
+            cells
+                .objects
+                .iter()
+                .map(|cell| cell.out_point.clone().into())
+                .collect::<Vec<OutPoint>>()
+        };
+
+        let cells = get_cells(IndexerOrder::Asc, 10, None, 0).unwrap();
+        assert_eq!(
+            vec![
+                OutPoint::new(cellbase0.hash(), 0),
+                OutPoint::new(tx00.hash(), 0)
+            ],
+            out_points(&cells)
+        );
+        let page_1 = get_cells(IndexerOrder::Desc, 1, None, 0).unwrap();
+        assert_eq!(vec![OutPoint::new(tx00.hash(), 0)], out_points(&page_1));
+        let page_2 = get_cells(IndexerOrder::Desc, 1, Some(page_1.last_cursor), 0).unwrap();
+        assert_eq!(
+            vec![OutPoint::new(cellbase0.hash(), 0)],
+            out_points(&page_2)
+        );
+        let cells = get_cells(IndexerOrder::Asc, 10, None, 1).unwrap();
+        assert_eq!(vec![OutPoint::new(tx10.hash(), 0)], out_points(&cells));
+        assert!(get_cells(IndexerOrder::Asc, 10, None, 2)
+            .unwrap()
+            .objects
+            .is_empty());
+        assert!(get_cells(IndexerOrder::Asc, 10, None, 3).is_err());
+
+        let capacity = rpc
+            .get_cells_capacity(search_key(), Some(0.into()))
+            .unwrap()
+            .unwrap();
+        assert_eq!(capacity_bytes!(3000).as_u64(), capacity.capacity.value());
+        assert_eq!(0, capacity.block_number.value());
+        assert_eq!(Unpack::<H256>::unpack(&block0.hash()), capacity.block_hash);
+        let capacity = rpc
+            .get_cells_capacity(search_key(), Some(1.into()))
+            .unwrap()
+            .unwrap();
+        assert_eq!(capacity_bytes!(500).as_u64(), capacity.capacity.value());
+        assert_eq!(Unpack::<H256>::unpack(&block1.hash()), capacity.block_hash);
+
+        // keep_num is 0, the blocks at or before 1 are pruned
+        indexer.prune().unwrap();
+        assert!(get_cells(IndexerOrder::Asc, 10, None, 1).is_err());
+        assert!(rpc
+            .get_cells_capacity(search_key(), Some(1.into()))
+            .is_err());
+        assert!(get_cells(IndexerOrder::Asc, 10, None, 2).is_ok());
+    }
+
+    #[test]
+    fn multiple_scripts_rpc() {
+        let store = new_store("multiple_scripts_rpc");
+        let indexer = Indexer::new(store.clone(), 10, 100, None, CustomFilters::new(None, None));
+        let rpc = IndexerHandle {
+            store,
+            pool: None,
+            request_limit: usize::MAX,
+            cell_events: broadcast::channel(1).0,
+            reindexer: None,
+        };
+
+        let lock_script = |code_hash: u8| {
+            ScriptBuilder::default()
+                .code_hash(H256([code_hash; 32]).pack())
+                .hash_type(ScriptHashType::Data.into())
+                .args(Bytes::from(b"lock_script".to_vec()).pack())
+                .build()
+        };
+        let (lock_script1, lock_script2, lock_script3) =
+            (lock_script(1), lock_script(2), lock_script(3));
+        let new_output = |capacity: Capacity, lock: &Script| {
+            CellOutputBuilder::default()
+                .capacity(capacity.pack())
+                .lock(lock.clone())
+                .build()
+        };
+
+        let cellbase0 = TransactionBuilder::default()
+            .input(CellInput::new_cellbase_input(0))
+            .witness(Script::default().into_witness())
+            .output(new_output(capacity_bytes!(1000), &lock_script1))
+            .output_data(Default::default())
+            .build();
+        let tx00 = TransactionBuilder::default()
+            .output(new_output(capacity_bytes!(2000), &lock_script2))
+            .output(new_output(capacity_bytes!(500), &lock_script1))
+            .output(new_output(capacity_bytes!(300), &lock_script3))
+            .outputs_data(vec![Bytes::new().pack(); 3])
+            .build();
+        let block0 = BlockBuilder::default()
+            .transaction(cellbase0.clone())
+            .transaction(tx00.clone())
+            .header(HeaderBuilder::default().number(0.pack()).build())
+            .build();
+        indexer.append(&block0).unwrap();
+
+        let search_key = || IndexerSearchKey {
+            script: lock_script2.clone().into(),
+            scripts: Some(vec![lock_script1.clone().into()]),
+            script_search_mode: Some(IndexerSearchMode::Exact),
+            ..Default::default()
+        };
+        let out_points = |cells: &IndexerPagination<IndexerCell>| {
+            cells
+                .objects
+                .iter()
+                .map(|cell| cell.out_point.clone().into())
+                .collect::<Vec<OutPoint>>()
+        };
+
+        // the cells of all the scripts are ordered by block position
+        let cells = rpc
+            .get_cells(search_key(), IndexerOrder::Asc, 10.into(), None, None)
+            .unwrap();
+        assert_eq!(
+            vec![
+                OutPoint::new(cellbase0.hash(), 0),
+                OutPoint::new(tx00.hash(), 0),
+                OutPoint::new(tx00.hash(), 1),
+            ],
+            out_points(&cells)
+        );
+        let page_1 = rpc
+            .get_cells(search_key(), IndexerOrder::Desc, 2.into(), None, None)
+            .unwrap();
+        assert_eq!(
+            vec![OutPoint::new(tx00.hash(), 1), OutPoint::new(tx00.hash(), 0)],
+            out_points(&page_1)
+        );
+        let page_2 = rpc
+            .get_cells(
+                search_key(),
+                IndexerOrder::Desc,
+                2.into(),
+                Some(page_1.last_cursor),
+                None,
+            )
+            .unwrap();
+        assert_eq!(
+            vec![OutPoint::new(cellbase0.hash(), 0)],
+            out_points(&page_2)
+        );
+
+        let capacity = rpc.get_cells_capacity(search_key(), None).unwrap().unwrap();
+        assert_eq!(capacity_bytes!(3500).as_u64(), capacity.capacity.value());
+
+        let cellbase1 = TransactionBuilder::default()
+            .input(CellInput::new_cellbase_input(1))
+            .witness(Script::default().into_witness())
+            .output(new_output(capacity_bytes!(1000), &lock_script3))
+            .output_data(Default::default())
+            .build();
+        let tx10 = TransactionBuilder::default()
+            .output(new_output(capacity_bytes!(100), &lock_script1))
+            .output(new_output(capacity_bytes!(200), &lock_script2))
+            .outputs_data(vec![Bytes::new().pack(); 2])
+            .build();
+        let block1 = BlockBuilder::default()
+            .transaction(cellbase1)
+            .transaction(tx10.clone())
+            .header(
+                HeaderBuilder::default()
+                    .number(1.pack())
+                    .parent_hash(block0.hash())
+                    .build(),
+            )
+            .build();
+        indexer.append(&block1).unwrap();
+
+        let tx_hashes = |txs: &IndexerPagination<IndexerTx>| {
+            txs.objects
+                .iter()
+                .map(|tx| tx.tx_hash().pack())
+                .collect::<Vec<packed::Byte32>>()
+        };
+        let txs = rpc
+            .get_transactions(search_key(), IndexerOrder::Asc, 10.into(), None)
+            .unwrap();
+        assert_eq!(
+            vec![
+                cellbase0.hash(),
+                tx00.hash(),
+                tx00.hash(),
+                tx10.hash(),
+                tx10.hash(),
+            ],
+            tx_hashes(&txs)
+        );
+
+        // a transaction matched by multiple scripts is a single group
+        let grouped_search_key = || IndexerSearchKey {
+            group_by_transaction: Some(true),
+            ..search_key()
+        };
+        let txs = rpc
+            .get_transactions(grouped_search_key(), IndexerOrder::Asc, 10.into(), None)
+            .unwrap();
+        assert_eq!(
+            vec![cellbase0.hash(), tx00.hash(), tx10.hash(),],
+            tx_hashes(&txs)
+        );
+        let page_1 = rpc
+            .get_transactions(grouped_search_key(), IndexerOrder::Desc, 1.into(), None)
+            .unwrap();
+        let page_2 = rpc
+            .get_transactions(
+                grouped_search_key(),
+                IndexerOrder::Desc,
+                2.into(),
+                Some(page_1.last_cursor.clone()),
+            )
+            .unwrap();
+        assert_eq!(
+            vec![tx10.hash(), tx00.hash(), cellbase0.hash(),],
+            [tx_hashes(&page_1), tx_hashes(&page_2)].concat()
+        );
+
+        // a script covered by another prefix is not matched twice
+        let prefix_search_key = IndexerSearchKey {
+            script: lock_script1
+                .clone()
+                .as_builder()
+                .args(Bytes::new().pack())
+                .build()
+                .into(),
+            scripts: Some(vec![lock_script1.clone().into()]),
+            ..Default::default()
+        };
+        let cells = rpc
+            .get_cells(prefix_search_key, IndexerOrder::Asc, 10.into(), None, None)
+            .unwrap();
+        assert_eq!(3, cells.objects.len());
+
+        // multiple scripts can't be searched by prefix
+        let prefix_search_key = || IndexerSearchKey {
+            script: lock_script1
+                .clone()
+                .as_builder()
+                .args(Bytes::new().pack())
+                .build()
+                .into(),
+            scripts: Some(vec![lock_script2
+                .clone()
+                .as_builder()
+                .args(Bytes::new().pack())
+                .build()
+                .into()]),
+            ..Default::default()
+        };
+        assert!(rpc
+            .get_cells(
+                prefix_search_key(),
+                IndexerOrder::Asc,
+                10.into(),
+                None,
+                None,
+            )
+            .is_err());
+        assert!(rpc
+            .get_transactions(prefix_search_key(), IndexerOrder::Asc, 10.into(), None)
+            .is_err());
+        assert!(rpc.get_cells_capacity(prefix_search_key(), None).is_err());
+
+        let too_many_scripts = IndexerSearchKey {
+            script: lock_script1.clone().into(),
+            scripts: Some(vec![lock_script1.into(); MAX_SEARCH_SCRIPTS]),
+            ..Default::default()
+        };
+        assert!(rpc
+            .get_cells(too_many_scripts, IndexerOrder::Asc, 10.into(), None, None)
+            .is_err());
+    }
+
     #[test]
     fn test_request_limit() {
         let store = new_store("script_search_mode_rpc");
@@ -1783,6 +2729,8 @@ mod tests {
             store,
             pool: None,
             request_limit: 2,
+            cell_events: broadcast::channel(1).0,
+            reindexer: None,
         };
 
         let lock_script1 = ScriptBuilder::default()
@@ -1804,6 +2752,7 @@ mod tests {
             IndexerOrder::Asc,
             1000.into(),
             None,
+            None,
         );
         assert!(res.is_err())
     }
@@ -1816,6 +2765,8 @@ mod tests {
             store,
             pool: None,
             request_limit: usize::MAX,
+            cell_events: broadcast::channel(1).0,
+            reindexer: None,
         };
 
         // setup test data
@@ -1905,6 +2856,7 @@ mod tests {
                 IndexerOrder::Asc,
                 1000.into(),
                 None,
+                None,
             )
             .unwrap();
         assert_eq!(1, cells.objects.len(),);
@@ -1924,6 +2876,7 @@ mod tests {
                 IndexerOrder::Asc,
                 1000.into(),
                 None,
+                None,
             )
             .unwrap();
         assert_eq!(1, cells.objects.len(),);
@@ -1945,6 +2898,7 @@ mod tests {
                 IndexerOrder::Asc,
                 1000.into(),
                 None,
+                None,
             )
             .unwrap();
         assert_eq!(1, cells.objects.len(),);
@@ -1966,6 +2920,7 @@ mod tests {
                 IndexerOrder::Asc,
                 1000.into(),
                 None,
+                None,
             )
             .unwrap();
         assert_eq!(1, cells.objects.len(),);
@@ -1985,6 +2940,7 @@ mod tests {
                 IndexerOrder::Asc,
                 1000.into(),
                 None,
+                None,
             )
             .unwrap();
         assert_eq!(1, cells.objects.len(),);
@@ -1993,15 +2949,18 @@ mod tests {
         let mut data = [0u8; 7];
         hex_decode("62e907b15cbfaa".as_bytes(), &mut data).unwrap();
         let cells_capacity = rpc
-            .get_cells_capacity(IndexerSearchKey {
-                script: lock_script11.clone().into(),
-                filter: Some(IndexerSearchKeyFilter {
-                    output_data: Some(JsonBytes::from_vec(data.to_vec())),
-                    output_data_filter_mode: Some(IndexerSearchMode::Exact),
+            .get_cells_capacity(
+                IndexerSearchKey {
+                    script: lock_script11.clone().into(),
+                    filter: Some(IndexerSearchKeyFilter {
+                        output_data: Some(JsonBytes::from_vec(data.to_vec())),
+                        output_data_filter_mode: Some(IndexerSearchMode::Exact),
+                        ..Default::default()
+                    }),
                     ..Default::default()
-                }),
-                ..Default::default()
-            })
+                },
+                None,
+            )
             .unwrap();
         let capacity: u64 = cells_capacity.unwrap().capacity.into();
         assert_eq!(200000000000, capacity);
@@ -2010,30 +2969,36 @@ mod tests {
         let mut data = [0u8; 6];
         hex_decode("62e907b15cbf".as_bytes(), &mut data).unwrap();
         let cells_capacity = rpc
-            .get_cells_capacity(IndexerSearchKey {
-                script: lock_script11.clone().into(),
-                filter: Some(IndexerSearchKeyFilter {
-                    output_data: Some(JsonBytes::from_vec(data.to_vec())),
-                    output_data_filter_mode: Some(IndexerSearchMode::Prefix),
+            .get_cells_capacity(
+                IndexerSearchKey {
+                    script: lock_script11.clone().into(),
+                    filter: Some(IndexerSearchKeyFilter {
+                        output_data: Some(JsonBytes::from_vec(data.to_vec())),
+                        output_data_filter_mode: Some(IndexerSearchMode::Prefix),
+                        ..Default::default()
+                    }),
                     ..Default::default()
-                }),
-                ..Default::default()
-            })
+                },
+                None,
+            )
             .unwrap();
         let capacity: u64 = cells_capacity.unwrap().capacity.into();
         assert_eq!(200000000000, capacity);
 
         // test get_cells_capacity rpc with output_data Prefix search mode
         let cells_capacity = rpc
-            .get_cells_capacity(IndexerSearchKey {
-                script: lock_script11.clone().into(),
-                filter: Some(IndexerSearchKeyFilter {
-                    output_data: Some(JsonBytes::from_vec(vec![])),
-                    output_data_filter_mode: Some(IndexerSearchMode::Prefix),
+            .get_cells_capacity(
+                IndexerSearchKey {
+                    script: lock_script11.clone().into(),
+                    filter: Some(IndexerSearchKeyFilter {
+                        output_data: Some(JsonBytes::from_vec(vec![])),
+                        output_data_filter_mode: Some(IndexerSearchMode::Prefix),
+                        ..Default::default()
+                    }),
                     ..Default::default()
-                }),
-                ..Default::default()
-            })
+                },
+                None,
+            )
             .unwrap();
         let capacity: u64 = cells_capacity.unwrap().capacity.into();
         assert_eq!(200000000000, capacity);
@@ -2042,30 +3007,36 @@ mod tests {
         let mut data = [0u8; 2];
         hex_decode("5cbf".as_bytes(), &mut data).unwrap();
         let cells_capacity = rpc
-            .get_cells_capacity(IndexerSearchKey {
-                script: lock_script11.clone().into(),
-                filter: Some(IndexerSearchKeyFilter {
-                    output_data: Some(JsonBytes::from_vec(data.to_vec())),
-                    output_data_filter_mode: Some(IndexerSearchMode::Partial),
+            .get_cells_capacity(
+                IndexerSearchKey {
+                    script: lock_script11.clone().into(),
+                    filter: Some(IndexerSearchKeyFilter {
+                        output_data: Some(JsonBytes::from_vec(data.to_vec())),
+                        output_data_filter_mode: Some(IndexerSearchMode::Partial),
+                        ..Default::default()
+                    }),
                     ..Default::default()
-                }),
-                ..Default::default()
-            })
+                },
+                None,
+            )
             .unwrap();
         let capacity: u64 = cells_capacity.unwrap().capacity.into();
         assert_eq!(200000000000, capacity);
 
         // test get_cells_capacity rpc with output_data Partial search mode
         let cells_capacity = rpc
-            .get_cells_capacity(IndexerSearchKey {
-                script: lock_script11.into(),
-                filter: Some(IndexerSearchKeyFilter {
-                    output_data: Some(JsonBytes::from_vec(vec![])),
-                    output_data_filter_mode: Some(IndexerSearchMode::Partial),
+            .get_cells_capacity(
+                IndexerSearchKey {
+                    script: lock_script11.into(),
+                    filter: Some(IndexerSearchKeyFilter {
+                        output_data: Some(JsonBytes::from_vec(vec![])),
+                        output_data_filter_mode: Some(IndexerSearchMode::Partial),
+                        ..Default::default()
+                    }),
                     ..Default::default()
-                }),
-                ..Default::default()
-            })
+                },
+                None,
+            )
             .unwrap();
         let capacity: u64 = cells_capacity.unwrap().capacity.into();
         assert_eq!(200000000000, capacity);
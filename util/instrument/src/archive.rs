@@ -0,0 +1,257 @@
+//! The binary block archive written by `ckb export --format bin`.
+//!
+//! An archive starts with a header followed by one record per block:
+//!
+//! ```text
+//! header: magic (8) | version (u32) | compression (u8) | genesis hash (32)
+//!         | consensus id length (u32) | consensus id
+//! record: block number (u64) | block hash (32) | checksum (32) | payload length (u32) | payload
+//! ```
+//!
+//! All integers are little-endian. The payload is the molecule encoded block, optionally
+//! compressed, and the checksum is the blake2b hash of the uncompressed payload.
+use ckb_app_config::ExportCompression;
+use ckb_hash::blake2b_256;
+use ckb_types::{core::BlockNumber, core::BlockView, packed, prelude::*};
+use snap::raw::{Decoder as SnappyDecoder, Encoder as SnappyEncoder};
+use std::io::{self, Read, Write};
+
+/// The magic bytes identifying a binary block archive.
+pub const ARCHIVE_MAGIC: &[u8; 8] = b"CKBBLOCK";
+/// The current version of the binary block archive.
+pub const ARCHIVE_VERSION: u32 = 1;
+
+const COMPRESSION_NONE: u8 = 0;
+const COMPRESSION_SNAPPY: u8 = 1;
+// number + hash + checksum + payload length
+const RECORD_HEAD_SIZE: u64 = 8 + 32 + 32 + 4;
+
+fn invalid_data<E: Into<Box<dyn std::error::Error + Send + Sync>>>(err: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+/// The header of a binary block archive.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArchiveHeader {
+    /// The compression applied to each block payload.
+    pub compression: ExportCompression,
+    /// The genesis hash of the exported chain.
+    pub genesis_hash: packed::Byte32,
+    /// The consensus id of the exported chain.
+    pub consensus_id: String,
+}
+
+impl ArchiveHeader {
+    fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let compression = match self.compression {
+            ExportCompression::None => COMPRESSION_NONE,
+            ExportCompression::Snappy => COMPRESSION_SNAPPY,
+        };
+        writer.write_all(ARCHIVE_MAGIC)?;
+        writer.write_all(&ARCHIVE_VERSION.to_le_bytes())?;
+        writer.write_all(&[compression])?;
+        writer.write_all(self.genesis_hash.as_slice())?;
+        writer.write_all(&(self.consensus_id.len() as u32).to_le_bytes())?;
+        writer.write_all(self.consensus_id.as_bytes())
+    }
+
+    fn read_from<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != ARCHIVE_MAGIC {
+            return Err(invalid_data("not a ckb block archive"));
+        }
+        let version = read_u32(reader)?;
+        if version != ARCHIVE_VERSION {
+            return Err(invalid_data(format!(
+                "unsupported archive version {version}, expect {ARCHIVE_VERSION}"
+            )));
+        }
+        let mut compression = [0u8; 1];
+        reader.read_exact(&mut compression)?;
+        let compression = match compression[0] {
+            COMPRESSION_NONE => ExportCompression::None,
+            COMPRESSION_SNAPPY => ExportCompression::Snappy,
+            other => return Err(invalid_data(format!("unknown compression {other}"))),
+        };
+        let genesis_hash = read_byte32(reader)?;
+        let id_len = read_u32(reader)? as usize;
+        let mut consensus_id = vec![0u8; id_len];
+        reader.read_exact(&mut consensus_id)?;
+        let consensus_id = String::from_utf8(consensus_id).map_err(invalid_data)?;
+        Ok(ArchiveHeader {
+            compression,
+            genesis_hash,
+            consensus_id,
+        })
+    }
+}
+
+/// A block record read from a binary block archive, the payload is not decoded yet.
+pub struct ArchiveRecord {
+    /// The block number.
+    pub number: BlockNumber,
+    /// The block hash.
+    pub hash: packed::Byte32,
+    checksum: [u8; 32],
+    payload: Vec<u8>,
+}
+
+impl ArchiveRecord {
+    /// The number of bytes this record occupies in the archive.
+    pub fn size(&self) -> u64 {
+        RECORD_HEAD_SIZE + self.payload.len() as u64
+    }
+}
+
+/// Writes blocks into a binary block archive.
+pub struct ArchiveWriter<W: Write> {
+    writer: W,
+    compression: ExportCompression,
+    encoder: SnappyEncoder,
+}
+
+impl<W: Write> ArchiveWriter<W> {
+    /// Creates a writer and writes the archive header.
+    pub fn new(mut writer: W, header: &ArchiveHeader) -> io::Result<Self> {
+        header.write_to(&mut writer)?;
+        Ok(ArchiveWriter {
+            writer,
+            compression: header.compression,
+            encoder: SnappyEncoder::new(),
+        })
+    }
+
+    /// Appends a block to the archive.
+    pub fn write_block(&mut self, block: &BlockView) -> io::Result<()> {
+        let data = block.data();
+        let raw = data.as_slice();
+        let checksum = blake2b_256(raw);
+        let compressed;
+        let payload = match self.compression {
+            ExportCompression::None => raw,
+            ExportCompression::Snappy => {
+                compressed = self.encoder.compress_vec(raw).map_err(invalid_data)?;
+                &compressed
+            }
+        };
+        self.writer.write_all(&block.number().to_le_bytes())?;
+        self.writer.write_all(block.hash().as_slice())?;
+        self.writer.write_all(&checksum)?;
+        self.writer
+            .write_all(&(payload.len() as u32).to_le_bytes())?;
+        self.writer.write_all(payload)
+    }
+
+    /// Flushes the underlying writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Unwraps this writer, returning the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Reads blocks from a binary block archive.
+pub struct ArchiveReader<R: Read> {
+    reader: R,
+    header: ArchiveHeader,
+    decoder: SnappyDecoder,
+}
+
+impl<R: Read> ArchiveReader<R> {
+    /// Creates a reader and reads the archive header.
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let header = ArchiveHeader::read_from(&mut reader)?;
+        Ok(ArchiveReader {
+            reader,
+            header,
+            decoder: SnappyDecoder::new(),
+        })
+    }
+
+    /// Returns the archive header.
+    pub fn header(&self) -> &ArchiveHeader {
+        &self.header
+    }
+
+    /// Reads the next record, returns `None` at the end of the archive.
+    pub fn next_record(&mut self) -> io::Result<Option<ArchiveRecord>> {
+        let mut number = [0u8; 8];
+        let mut filled = 0;
+        while filled < number.len() {
+            match self.reader.read(&mut number[filled..]) {
+                Ok(0) if filled == 0 => return Ok(None),
+                Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(n) => filled += n,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+        let number = BlockNumber::from_le_bytes(number);
+        let hash = read_byte32(&mut self.reader)?;
+        let mut checksum = [0u8; 32];
+        self.reader.read_exact(&mut checksum)?;
+        let len = read_u32(&mut self.reader)? as usize;
+        let mut payload = vec![0u8; len];
+        self.reader.read_exact(&mut payload)?;
+        Ok(Some(ArchiveRecord {
+            number,
+            hash,
+            checksum,
+            payload,
+        }))
+    }
+
+    /// Decodes the block of a record, verifying its checksum, number and hash.
+    pub fn decode(&mut self, record: &ArchiveRecord) -> io::Result<BlockView> {
+        let raw = match self.header.compression {
+            ExportCompression::None => record.payload.clone(),
+            ExportCompression::Snappy => self
+                .decoder
+                .decompress_vec(&record.payload)
+                .map_err(invalid_data)?,
+        };
+        if blake2b_256(&raw) != record.checksum {
+            return Err(invalid_data(format!(
+                "checksum mismatch for block {}",
+                record.number
+            )));
+        }
+        let block = packed::BlockReader::from_compatible_slice(&raw)
+            .map_err(invalid_data)?
+            .to_entity()
+            .into_view();
+        if block.number() != record.number || block.hash() != record.hash {
+            return Err(invalid_data(format!(
+                "block {} does not match its record",
+                record.number
+            )));
+        }
+        Ok(block)
+    }
+}
+
+/// Returns true if the data starts with the archive magic bytes.
+pub fn is_archive<R: Read>(reader: &mut R) -> io::Result<bool> {
+    let mut magic = [0u8; 8];
+    match reader.read_exact(&mut magic) {
+        Ok(()) => Ok(&magic == ARCHIVE_MAGIC),
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_byte32<R: Read>(reader: &mut R) -> io::Result<packed::Byte32> {
+    let mut buf = [0u8; 32];
+    reader.read_exact(&mut buf)?;
+    Ok(buf.pack())
+}
@@ -0,0 +1,83 @@
+use crate::archive::ArchiveReader;
+use ckb_shared::shared::Shared;
+use ckb_stop_handler::has_received_stop_signal;
+use ckb_store::ChainStore;
+use ckb_types::core::BlockView;
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// Back-fills the bodies of historical blocks from a binary block archive.
+///
+/// A node bootstrapped from a cell snapshot only has the headers of the blocks before the
+/// snapshot. Every block read from the archive must match the trusted header on the main chain,
+/// so the archive itself does not need to be trusted.
+pub struct Backfill {
+    /// source archive contains block data
+    source: PathBuf,
+    shared: Shared,
+}
+
+impl Backfill {
+    /// Creates a new back-fill job.
+    pub fn new(shared: Shared, source: PathBuf) -> Self {
+        Backfill { shared, source }
+    }
+
+    /// Executes the back-fill job, returns the number of back-filled blocks.
+    ///
+    /// The job stops early when the process receives the exit signal, it can be resumed later
+    /// because back-filled blocks are skipped.
+    pub fn execute(self) -> Result<u64, Box<dyn Error>> {
+        let f = fs::File::open(&self.source)?;
+        let mut reader = ArchiveReader::new(io::BufReader::new(f))?;
+        let genesis_hash = self.shared.consensus().genesis_hash();
+        if reader.header().genesis_hash != genesis_hash {
+            return Err(format!(
+                "archive genesis hash {:#x} does not match {:#x}",
+                reader.header().genesis_hash,
+                genesis_hash
+            )
+            .into());
+        }
+
+        let store = self.shared.store();
+        let mut count = 0;
+        while let Some(record) = reader.next_record()? {
+            if has_received_stop_signal() {
+                break;
+            }
+            if store.get_block_hash(record.number).as_ref() != Some(&record.hash) {
+                // beyond the tip or not on the main chain
+                continue;
+            }
+            if store.get_block_uncles(&record.hash).is_some() {
+                continue;
+            }
+            let block = reader.decode(&record)?;
+            verify_body(&block)?;
+
+            let db_txn = store.begin_transaction();
+            db_txn.insert_block(&block)?;
+            db_txn.attach_block(&block)?;
+            db_txn.commit()?;
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
+// The header is trusted, check the body against it
+fn verify_body(block: &BlockView) -> Result<(), String> {
+    if block.transactions_root() != block.calc_transactions_root()
+        || block.proposals_hash() != block.calc_proposals_hash()
+        || block.extra_hash() != block.calc_extra_hash().extra_hash()
+    {
+        return Err(format!(
+            "block {} body does not match its header",
+            block.number()
+        ));
+    }
+    Ok(())
+}
@@ -1,6 +1,9 @@
+use crate::archive::{ArchiveHeader, ArchiveWriter};
+use ckb_app_config::{ExportCompression, ExportFormat};
 use ckb_chain_iter::ChainIterator;
 use ckb_jsonrpc_types::BlockView as JsonBlock;
 use ckb_shared::shared::Shared;
+use ckb_types::core::BlockNumber;
 #[cfg(feature = "progress_bar")]
 use indicatif::{ProgressBar, ProgressStyle};
 use std::error::Error;
@@ -15,36 +18,124 @@ pub struct Export {
     pub target: PathBuf,
     /// CKB shared data.
     pub shared: Shared,
+    /// export file format
+    pub format: ExportFormat,
+    /// block compression, only used by the binary format
+    pub compression: ExportCompression,
+    /// export from this block number
+    pub from: BlockNumber,
+    /// export up to this block number (inclusive), defaults to the tip
+    pub to: Option<BlockNumber>,
 }
 
 impl Export {
-    /// Creates the export job.
+    /// Creates the export job which exports the whole chain into JSON.
     pub fn new(shared: Shared, target: PathBuf) -> Self {
-        Export { shared, target }
+        Export {
+            shared,
+            target,
+            format: ExportFormat::Json,
+            compression: ExportCompression::None,
+            from: 0,
+            to: None,
+        }
+    }
+
+    /// Sets the export format and the block compression of the binary format.
+    pub fn format(mut self, format: ExportFormat, compression: ExportCompression) -> Self {
+        self.format = format;
+        self.compression = compression;
+        self
+    }
+
+    /// Sets the range of exported blocks.
+    pub fn range(mut self, from: Option<BlockNumber>, to: Option<BlockNumber>) -> Self {
+        self.from = from.unwrap_or(0);
+        self.to = to;
+        self
     }
 
     /// export file name
     fn file_name(&self) -> String {
-        format!("{}.{}", self.shared.consensus().id, "json")
+        let extension = match self.format {
+            ExportFormat::Json => "json",
+            ExportFormat::Binary => "ckbblocks",
+        };
+        if self.from == 0 && self.to.is_none() {
+            format!("{}.{}", self.shared.consensus().id, extension)
+        } else {
+            let to = self
+                .to
+                .map(|to| to.to_string())
+                .unwrap_or_else(|| "tip".to_string());
+            format!(
+                "{}-{}-{}.{}",
+                self.shared.consensus().id,
+                self.from,
+                to,
+                extension
+            )
+        }
+    }
+
+    fn create_file(&self) -> Result<fs::File, Box<dyn Error>> {
+        let f = fs::OpenOptions::new()
+            .create_new(true)
+            .read(true)
+            .write(true)
+            .open(self.target.join(self.file_name()))?;
+        Ok(f)
     }
 
     /// Executes the export job.
     pub fn execute(self) -> Result<(), Box<dyn Error>> {
         fs::create_dir_all(&self.target)?;
-        self.write_to_json()
+        match self.format {
+            ExportFormat::Json => self.write_to_json(),
+            ExportFormat::Binary => self.write_to_archive(),
+        }
+    }
+
+    /// Export the chain into a binary block archive.
+    pub fn write_to_archive(self) -> Result<(), Box<dyn Error>> {
+        let f = self.create_file()?;
+        let consensus = self.shared.consensus();
+        let header = ArchiveHeader {
+            compression: self.compression,
+            genesis_hash: consensus.genesis_hash(),
+            consensus_id: consensus.id.clone(),
+        };
+        let mut writer = ArchiveWriter::new(io::BufWriter::new(f), &header)?;
+        let snapshot = self.shared.snapshot();
+        let blocks_iter = ChainIterator::with_range(snapshot.as_ref(), self.from, self.to);
+        #[cfg(feature = "progress_bar")]
+        let progress_bar = {
+            let progress_bar = ProgressBar::new(blocks_iter.len());
+            progress_bar.set_style(
+                ProgressStyle::default_bar()
+                    .template("[{elapsed_precise}] {bar:50.cyan/blue} {pos:>6}/{len:6} {msg}")
+                    .progress_chars("##-"),
+            );
+            progress_bar
+        };
+        for block in blocks_iter {
+            writer.write_block(&block)?;
+            #[cfg(feature = "progress_bar")]
+            progress_bar.inc(1);
+        }
+        writer.flush()?;
+        #[cfg(feature = "progress_bar")]
+        progress_bar.finish_with_message("done!");
+        Ok(())
     }
 
     #[cfg(not(feature = "progress_bar"))]
     pub fn write_to_json(self) -> Result<(), Box<dyn Error>> {
-        let f = fs::OpenOptions::new()
-            .create_new(true)
-            .read(true)
-            .write(true)
-            .open(self.target.join(self.file_name()))?;
+        let f = self.create_file()?;
         let mut writer = io::BufWriter::new(f);
         let snapshot = self.shared.snapshot();
 
-        for block in ChainIterator::new(snapshot.as_ref()) {
+        for block in ChainIterator::with_range(snapshot.as_ref(), self.from, self.to) {
             let block: JsonBlock = block.into();
             let encoded = serde_json::to_vec(&block)?;
             writer.write_all(&encoded)?;
@@ -56,15 +147,11 @@ impl Export {
     /// Export the chain into JSON.
     #[cfg(feature = "progress_bar")]
     pub fn write_to_json(self) -> Result<(), Box<dyn Error>> {
-        let f = fs::OpenOptions::new()
-            .create_new(true)
-            .read(true)
-            .write(true)
-            .open(self.target.join(self.file_name()))?;
+        let f = self.create_file()?;
 
         let mut writer = io::BufWriter::new(f);
         let snapshot = self.shared.snapshot();
-        let blocks_iter = ChainIterator::new(snapshot.as_ref());
+        let blocks_iter = ChainIterator::with_range(snapshot.as_ref(), self.from, self.to);
         let progress_bar = ProgressBar::new(blocks_iter.len());
         progress_bar.set_style(
             ProgressStyle::default_bar()
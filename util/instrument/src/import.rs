@@ -1,12 +1,18 @@
+use crate::archive::{is_archive, ArchiveReader};
 use ckb_chain::ChainController;
 use ckb_jsonrpc_types::BlockView as JsonBlock;
-use ckb_types::core;
+use ckb_shared::shared::Shared;
+use ckb_store::ChainStore;
+use ckb_types::{
+    core::{self, BlockNumber},
+    packed::Byte32,
+};
 #[cfg(feature = "progress_bar")]
 use indicatif::{ProgressBar, ProgressStyle};
 use std::error::Error;
 use std::fs;
 use std::io;
-use std::io::BufRead;
+use std::io::{BufRead, Seek};
 use std::path::PathBuf;
 use std::sync::Arc;
 
@@ -15,17 +21,37 @@ pub struct Import {
     /// source file contains block data
     source: PathBuf,
     chain: ChainController,
+    shared: Shared,
 }
 
 impl Import {
     /// Creates a new import job.
-    pub fn new(chain: ChainController, source: PathBuf) -> Self {
-        Import { chain, source }
+    pub fn new(chain: ChainController, shared: Shared, source: PathBuf) -> Self {
+        Import {
+            chain,
+            shared,
+            source,
+        }
     }
 
     /// Executes the import job.
+    ///
+    /// The format of the source file is detected automatically.
     pub fn execute(self) -> Result<(), Box<dyn Error>> {
-        self.read_from_json()
+        let mut f = fs::File::open(&self.source)?;
+        let archive = is_archive(&mut f)?;
+        f.rewind()?;
+        if archive {
+            self.read_from_archive()
+        } else {
+            self.read_from_json()
+        }
+    }
+
+    /// Returns true if the block is already on the main chain,
+    /// which allows an interrupted import to resume from the current tip.
+    fn is_imported(&self, number: BlockNumber, hash: &Byte32) -> bool {
+        self.shared.snapshot().get_block_hash(number).as_ref() == Some(hash)
     }
 
     #[cfg(not(feature = "progress_bar"))]
@@ -37,7 +63,7 @@ impl Import {
             let s = line?;
             let block: JsonBlock = serde_json::from_str(&s)?;
             let block: Arc<core::BlockView> = Arc::new(block.into());
-            if !block.is_genesis() {
+            if !block.is_genesis() && !self.is_imported(block.number(), &block.hash()) {
                 self.chain
                     .blocking_process_block(block)
                     .expect("import occur malformation data");
@@ -62,7 +88,7 @@ impl Import {
             let s = line?;
             let block: JsonBlock = serde_json::from_str(&s)?;
             let block: Arc<core::BlockView> = Arc::new(block.into());
-            if !block.is_genesis() {
+            if !block.is_genesis() && !self.is_imported(block.number(), &block.hash()) {
                 self.chain
                     .blocking_process_block(block)
                     .expect("import occur malformation data");
@@ -72,4 +98,57 @@ impl Import {
         progress_bar.finish_with_message("done!");
         Ok(())
     }
+
+    /// Imports the chain from a binary block archive.
+    ///
+    /// Blocks already on the main chain are skipped without being decoded.
+    pub fn read_from_archive(&self) -> Result<(), Box<dyn Error>> {
+        let f = fs::File::open(&self.source)?;
+        let mut reader = ArchiveReader::new(io::BufReader::new(f))?;
+
+        let consensus = self.shared.consensus();
+        let header = reader.header();
+        if header.consensus_id != consensus.id {
+            return Err(format!(
+                "archive consensus id {} does not match {}",
+                header.consensus_id, consensus.id
+            )
+            .into());
+        }
+        if header.genesis_hash != consensus.genesis_hash() {
+            return Err(format!(
+                "archive genesis hash {:#x} does not match {:#x}",
+                header.genesis_hash,
+                consensus.genesis_hash()
+            )
+            .into());
+        }
+
+        #[cfg(feature = "progress_bar")]
+        let progress_bar = {
+            let metadata = fs::metadata(&self.source)?;
+            let progress_bar = ProgressBar::new(metadata.len());
+            progress_bar.set_style(
+                ProgressStyle::default_bar()
+                    .template(
+                        "[{elapsed_precise}] {bar:50.cyan/blue} {bytes:>6}/{total_bytes:6} {msg}",
+                    )
+                    .progress_chars("##-"),
+            );
+            progress_bar
+        };
+        while let Some(record) = reader.next_record()? {
+            if record.number != 0 && !self.is_imported(record.number, &record.hash) {
+                let block = reader.decode(&record)?;
+                self.chain
+                    .blocking_process_block(Arc::new(block))
+                    .map_err(|err| format!("failed to import block {}: {}", record.number, err))?;
+            }
+            #[cfg(feature = "progress_bar")]
+            progress_bar.inc(record.size());
+        }
+        #[cfg(feature = "progress_bar")]
+        progress_bar.finish_with_message("done!");
+        Ok(())
+    }
 }
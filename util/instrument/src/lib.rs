@@ -4,10 +4,20 @@
 //!
 //! - [`Export`] provides block data export function.
 //! - [`Import`] imports block data which export from `Export`.
+//! - [`Backfill`] back-fills historical block bodies after bootstrapping from a cell snapshot.
+//! - [`ArchiveWriter`] and [`ArchiveReader`] work with the binary block archive.
 
+mod archive;
+mod backfill;
 mod export;
 mod import;
+#[cfg(test)]
+mod tests;
 
+pub use crate::archive::{
+    ArchiveHeader, ArchiveReader, ArchiveRecord, ArchiveWriter, ARCHIVE_MAGIC, ARCHIVE_VERSION,
+};
+pub use crate::backfill::Backfill;
 pub use crate::export::Export;
 pub use crate::import::Import;
 #[cfg(feature = "progress_bar")]
@@ -0,0 +1,85 @@
+use crate::archive::{is_archive, ArchiveHeader, ArchiveReader, ArchiveWriter};
+use ckb_app_config::ExportCompression;
+use ckb_types::{
+    core::{BlockBuilder, BlockView, EpochNumberWithFraction, HeaderBuilder},
+    prelude::*,
+};
+use std::io::Cursor;
+
+fn blocks() -> Vec<BlockView> {
+    (0..3u64)
+        .map(|number| {
+            BlockBuilder::default()
+                .header(
+                    HeaderBuilder::default()
+                        .number(number.pack())
+                        .epoch(EpochNumberWithFraction::new(0, number, 1000).pack())
+                        .build(),
+                )
+                .build()
+        })
+        .collect()
+}
+
+fn header(compression: ExportCompression) -> ArchiveHeader {
+    ArchiveHeader {
+        compression,
+        genesis_hash: blocks()[0].hash(),
+        consensus_id: "ckb_dev".to_string(),
+    }
+}
+
+fn write_archive(header: &ArchiveHeader, blocks: &[BlockView]) -> Vec<u8> {
+    let mut writer = ArchiveWriter::new(Vec::new(), header).unwrap();
+    for block in blocks {
+        writer.write_block(block).unwrap();
+    }
+    writer.flush().unwrap();
+    writer.into_inner()
+}
+
+#[test]
+fn test_archive_roundtrip() {
+    for compression in [ExportCompression::None, ExportCompression::Snappy] {
+        let header = header(compression);
+        let blocks = blocks();
+        let data = write_archive(&header, &blocks);
+        assert!(is_archive(&mut Cursor::new(&data)).unwrap());
+
+        let mut reader = ArchiveReader::new(Cursor::new(data)).unwrap();
+        assert_eq!(reader.header(), &header);
+        for block in &blocks {
+            let record = reader.next_record().unwrap().unwrap();
+            assert_eq!(record.number, block.number());
+            assert_eq!(record.hash, block.hash());
+            assert_eq!(reader.decode(&record).unwrap().hash(), block.hash());
+        }
+        assert!(reader.next_record().unwrap().is_none());
+    }
+}
+
+#[test]
+fn test_archive_corrupted() {
+    let header = header(ExportCompression::None);
+    let blocks = blocks();
+    let mut data = write_archive(&header, &blocks[..1]);
+
+    // flip the last byte of the block payload
+    let last = data.len() - 1;
+    data[last] ^= 0xff;
+    let mut reader = ArchiveReader::new(Cursor::new(data.clone())).unwrap();
+    let record = reader.next_record().unwrap().unwrap();
+    assert!(reader.decode(&record).is_err());
+
+    // an interrupted export leaves a truncated record
+    data.truncate(last);
+    let mut reader = ArchiveReader::new(Cursor::new(data)).unwrap();
+    assert!(reader.next_record().is_err());
+}
+
+#[test]
+fn test_not_archive() {
+    assert!(!is_archive(&mut Cursor::new(b"{\"header\":{}}\n")).unwrap());
+    assert!(!is_archive(&mut Cursor::new(b"")).unwrap());
+    assert!(ArchiveReader::new(Cursor::new(b"{\"header\":{}}\n".to_vec())).is_err());
+}
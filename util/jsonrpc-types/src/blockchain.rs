@@ -1575,3 +1575,28 @@ pub struct FeeRateStatistics {
     /// median
     pub median: Uint64,
 }
+
+/// The chain reorganization, which detaches blocks from the main chain and attaches the blocks
+/// of a better fork.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, JsonSchema)]
+pub struct ChainReorg {
+    /// The number of the latest common ancestor of the detached and the attached blocks
+    pub fork_block_number: BlockNumber,
+    /// The hash of the latest common ancestor of the detached and the attached blocks
+    pub fork_block_hash: H256,
+    /// The hashes of the detached blocks, in ascending order of block number
+    pub detached_blocks: Vec<H256>,
+    /// The hashes of the attached blocks, in ascending order of block number
+    pub attached_blocks: Vec<H256>,
+}
+
+impl From<core::service::ChainReorg> for ChainReorg {
+    fn from(reorg: core::service::ChainReorg) -> Self {
+        ChainReorg {
+            fork_block_number: reorg.fork_point.number().into(),
+            fork_block_hash: reorg.fork_point.hash().unpack(),
+            detached_blocks: reorg.detached_blocks.iter().map(Unpack::unpack).collect(),
+            attached_blocks: reorg.attached_blocks.iter().map(Unpack::unpack).collect(),
+        }
+    }
+}
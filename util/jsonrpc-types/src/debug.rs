@@ -1,3 +1,5 @@
+use crate::{BlockNumber, Cycle, Script, Uint32, Uint64};
+use ckb_types::H256;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -56,3 +58,120 @@ pub struct MainLoggerConfig {
     /// `null` means keeping the current option unchanged.
     pub color: Option<bool>,
 }
+
+/// The script execution trace of a committed transaction.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug, JsonSchema)]
+pub struct TransactionTrace {
+    /// The transaction hash.
+    pub transaction_hash: H256,
+    /// The hash of the block which has committed the transaction.
+    pub block_hash: H256,
+    /// The traces of the script groups in the verification order.
+    pub script_groups: Vec<ScriptGroupTrace>,
+}
+
+/// The execution trace of a script group.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug, JsonSchema)]
+pub struct ScriptGroupTrace {
+    /// The script hash of the group.
+    pub script_hash: H256,
+    /// Whether the group runs as a lock script or a type script.
+    pub group_type: ScriptGroupType,
+    /// The script of the group.
+    pub script: Script,
+    /// The indices of the inputs in the group.
+    pub input_indices: Vec<Uint32>,
+    /// The indices of the outputs in the group.
+    pub output_indices: Vec<Uint32>,
+    /// The consumed cycles, or null when the execution is aborted by an error.
+    pub cycles: Option<Cycle>,
+    /// The exit code of the root process, or null when it does not terminate.
+    ///
+    /// It is also null for the built-in type id script which does not run in the VM.
+    pub exit_code: Option<i8>,
+    /// The error which aborts the execution.
+    pub error: Option<String>,
+    /// The messages printed via the debug syscall.
+    pub debug_messages: Vec<String>,
+    /// The processes created during the execution, the root process comes first.
+    pub processes: Vec<ProcessTrace>,
+}
+
+/// The type of a script group.
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ScriptGroupType {
+    /// Lock script group
+    Lock,
+    /// Type script group
+    Type,
+}
+
+/// The execution trace of a process spawned in a script group.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug, JsonSchema)]
+pub struct ProcessTrace {
+    /// The process id. The root process id is 0.
+    pub process_id: Uint64,
+    /// The id of the process which spawned this one, or null for the root process.
+    pub parent_id: Option<Uint64>,
+    /// The spawned program, or null for the root process which runs the script code.
+    pub program: Option<ProgramLocation>,
+    /// The programs which replaced the running one via exec, in the calling order.
+    pub execs: Vec<ProgramLocation>,
+    /// The exit code, or null when the process does not terminate before the root process.
+    pub exit_code: Option<i8>,
+}
+
+/// The location of a program loaded via spawn or exec.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, JsonSchema)]
+pub struct ProgramLocation {
+    /// Where the program is loaded from.
+    pub source: ProgramSource,
+    /// The index in the source.
+    pub index: Uint32,
+    /// The offset of the program in the data.
+    pub offset: Uint64,
+    /// The length of the program, `0xffffffffffffffff` means to the end of the data.
+    pub length: Uint64,
+}
+
+/// The data source of a program.
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProgramSource {
+    /// The input cell data
+    Input,
+    /// The output cell data
+    Output,
+    /// The cell dep cell data
+    CellDep,
+    /// The input cell data in the script group
+    GroupInput,
+    /// The output cell data in the script group
+    GroupOutput,
+    /// The witness
+    Witness,
+    /// The witness of the input in the script group
+    WitnessGroupInput,
+    /// The witness of the output in the script group
+    WitnessGroupOutput,
+}
+
+/// The online backup created by the RPC method `create_backup`.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug, JsonSchema)]
+pub struct BackupInfo {
+    /// The backup directory on the server running the CKB node.
+    pub path: String,
+    /// The genesis hash of the chain.
+    pub genesis_hash: H256,
+    /// The tip block number in the backup.
+    pub tip_number: BlockNumber,
+    /// The tip block hash in the backup.
+    pub tip_hash: H256,
+    /// The database version recorded by the migrations.
+    pub migration_version: String,
+    /// The blocks before this number are stored in the freezer copy.
+    ///
+    /// It is null if the freezer is disabled.
+    pub frozen_number: Option<BlockNumber>,
+}
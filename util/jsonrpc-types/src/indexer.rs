@@ -1,5 +1,8 @@
-use crate::{BlockNumber, Capacity, CellOutput, JsonBytes, OutPoint, Script, Uint32, Uint64};
-use ckb_types::H256;
+use crate::{
+    BlockNumber, Capacity, CellOutput, JsonBytes, OutPoint, Script, Timestamp, Uint128, Uint32,
+    Uint64,
+};
+use ckb_types::{H256, U256};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -27,6 +30,33 @@ pub struct IndexerCell {
     pub tx_index: Uint32,
 }
 
+/// Cell event type
+#[derive(Serialize, JsonSchema, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexerCellEventType {
+    /// The cell is created by a transaction output
+    Created,
+    /// The cell is consumed by a transaction input
+    Consumed,
+}
+
+/// A cell created or consumed in a block, which is pushed by the `cells` subscription topic
+#[derive(Serialize, JsonSchema)]
+pub struct IndexerCellEvent {
+    /// created or consumed
+    pub event_type: IndexerCellEventType,
+    /// true if the block is detached from the main chain, and the event must be reverted
+    pub rollback: bool,
+    /// the hash of the block which creates or consumes the cell
+    pub block_hash: H256,
+    /// the number of the block which creates or consumes the cell
+    pub block_number: BlockNumber,
+    /// the hash of the transaction which creates or consumes the cell
+    pub tx_hash: H256,
+    /// the cell, `cell.block_number` and `cell.tx_index` are where the cell is created
+    pub cell: IndexerCell,
+}
+
 /// IndexerPagination wraps objects array and last_cursor to provide paging
 #[derive(Serialize, JsonSchema)]
 pub struct IndexerPagination<T> {
@@ -51,6 +81,9 @@ impl<T> IndexerPagination<T> {
 pub struct IndexerSearchKey {
     /// Script
     pub script: Script,
+    /// Additional scripts, optional, cells and transactions matching any of `script` and `scripts`
+    /// are returned as a single ordered result, all scripts use the same script type and search mode
+    pub scripts: Option<Vec<Script>>,
     /// Script Type
     pub script_type: IndexerScriptType,
     /// Script search mode, optional default is `prefix`, means search script with prefix
@@ -63,10 +96,18 @@ pub struct IndexerSearchKey {
     pub group_by_transaction: Option<bool>,
 }
 
+impl IndexerSearchKey {
+    /// Returns `script` followed by the additional `scripts`.
+    pub fn search_scripts(&self) -> impl Iterator<Item = &Script> {
+        std::iter::once(&self.script).chain(self.scripts.iter().flatten())
+    }
+}
+
 impl Default for IndexerSearchKey {
     fn default() -> Self {
         Self {
             script: Script::default(),
+            scripts: None,
             script_type: IndexerScriptType::Lock,
             script_search_mode: None,
             filter: None,
@@ -151,7 +192,7 @@ pub struct IndexerSearchKeyFilter {
 }
 
 /// ScriptType `Lock` | `Type`
-#[derive(Deserialize, JsonSchema)]
+#[derive(Clone, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum IndexerScriptType {
     /// Lock
@@ -181,6 +222,73 @@ pub struct IndexerCellsCapacity {
     pub block_number: BlockNumber,
 }
 
+/// The UDT balance of a lock script
+#[derive(Serialize, JsonSchema)]
+pub struct IndexerUdtBalance {
+    /// the UDT type script
+    pub type_script: Script,
+    /// the total amount of the live UDT cells
+    pub amount: Uint128,
+    /// the number of the live UDT cells
+    pub cell_count: Uint64,
+}
+
+/// A holder of a UDT
+#[derive(Serialize, JsonSchema)]
+pub struct IndexerUdtHolder {
+    /// the holder lock script
+    pub lock_script: Script,
+    /// the total amount of the live UDT cells
+    pub amount: Uint128,
+    /// the number of the live UDT cells
+    pub cell_count: Uint64,
+}
+
+/// The changes a transaction made to the cells of the searched scripts
+#[derive(Serialize, JsonSchema, Debug)]
+pub struct IndexerTxHistory {
+    /// transaction hash
+    pub tx_hash: H256,
+    /// the number of the block which committed the transaction
+    pub block_number: BlockNumber,
+    /// the position index of the transaction in the committed block
+    pub tx_index: Uint32,
+    /// the timestamp of the block which committed the transaction
+    pub block_timestamp: Timestamp,
+    /// the total capacity of the matched input cells
+    pub input_capacity: Capacity,
+    /// the total capacity of the matched output cells
+    pub output_capacity: Capacity,
+    /// the net capacity change, `output_capacity - input_capacity`
+    pub capacity_change: IndexerAmountChange,
+    /// the UDT amounts of the matched cells, only returned when the cells are selected by a type
+    /// script and some of them are UDT cells
+    pub udt: Option<IndexerTxUdtAmounts>,
+}
+
+/// The UDT amounts a transaction moved in the cells of the searched scripts
+#[derive(Serialize, JsonSchema, Debug)]
+pub struct IndexerTxUdtAmounts {
+    /// the total UDT amount of the matched input cells
+    #[schemars(schema_with = "crate::json_schema::u256_json_schema")]
+    pub input_amount: U256,
+    /// the total UDT amount of the matched output cells
+    #[schemars(schema_with = "crate::json_schema::u256_json_schema")]
+    pub output_amount: U256,
+    /// the net UDT amount change, `output_amount - input_amount`
+    pub change: IndexerAmountChange,
+}
+
+/// The net change of an amount, the difference of the outputs and the inputs
+#[derive(Serialize, JsonSchema, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexerAmountChange {
+    /// the outputs are more than the inputs by the amount, zero when they are equal
+    Increase(#[schemars(schema_with = "crate::json_schema::u256_json_schema")] U256),
+    /// the inputs are more than the outputs by the amount
+    Decrease(#[schemars(schema_with = "crate::json_schema::u256_json_schema")] U256),
+}
+
 /// Indexer Transaction Object
 #[derive(Serialize, JsonSchema, Debug)]
 #[serde(untagged)]
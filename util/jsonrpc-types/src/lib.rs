@@ -28,23 +28,26 @@ pub use self::block_template::{
 };
 pub use self::blockchain::{
     Block, BlockEconomicState, BlockFilter, BlockIssuance, BlockResponse, BlockView,
-    BlockWithCyclesResponse, CellDep, CellInput, CellOutput, Consensus, DepType, Deployment,
-    EpochView, FeeRateStatistics, HardForkFeature, HardForks, Header, HeaderView, MerkleProof,
-    MinerReward, OutPoint, ProposalWindow, Ratio, Script, ScriptHashType, SoftFork, Status,
-    Transaction, TransactionAndWitnessProof, TransactionProof, TransactionView,
+    BlockWithCyclesResponse, CellDep, CellInput, CellOutput, ChainReorg, Consensus, DepType,
+    Deployment, EpochView, FeeRateStatistics, HardForkFeature, HardForks, Header, HeaderView,
+    MerkleProof, MinerReward, OutPoint, ProposalWindow, Ratio, Script, ScriptHashType, SoftFork,
+    Status, Transaction, TransactionAndWitnessProof, TransactionProof, TransactionView,
     TransactionWithStatusResponse, TxStatus, UncleBlock, UncleBlockView,
 };
 pub use self::bytes::JsonBytes;
 pub use self::cell::{CellData, CellInfo, CellWithStatus};
-pub use self::debug::{ExtraLoggerConfig, MainLoggerConfig};
+pub use self::debug::{
+    BackupInfo, ExtraLoggerConfig, MainLoggerConfig, ProcessTrace, ProgramLocation, ProgramSource,
+    ScriptGroupTrace, ScriptGroupType, TransactionTrace,
+};
 pub use self::experiment::{DaoWithdrawingCalculationKind, EstimateCycles};
 pub use self::fee_estimator::EstimateMode;
 pub use self::fee_rate::FeeRateDef;
 pub use self::fixed_bytes::Byte32;
 pub use self::info::{ChainInfo, DeploymentInfo, DeploymentPos, DeploymentState, DeploymentsInfo};
 pub use self::net::{
-    BannedAddr, LocalNode, LocalNodeProtocol, NodeAddress, PeerSyncState, RemoteNode,
-    RemoteNodeProtocol, SyncState,
+    BanCause, BannedAddr, LocalNode, LocalNodeProtocol, NodeAddress, PeerBehaviour, PeerReport,
+    PeerReputation, PeerSyncState, RemoteNode, RemoteNodeProtocol, SyncState,
 };
 pub use self::pool::{
     AncestorsScoreSortKey, EntryCompleted, OutputsValidator, PoolTransactionEntry,
@@ -52,13 +55,15 @@ pub use self::pool::{
     TxPoolInfo,
 };
 pub use self::proposal_short_id::ProposalShortId;
-pub use self::subscription::Topic;
+pub use self::subscription::{SubscriptionOptions, Topic, TransactionStatusEvent};
 pub use self::uints::{Uint128, Uint32, Uint64};
 pub use ckb_types::core::RationalU256;
 pub use indexer::{
-    IndexerCell, IndexerCellType, IndexerCellsCapacity, IndexerOrder, IndexerPagination,
-    IndexerRange, IndexerScriptType, IndexerSearchKey, IndexerSearchKeyFilter, IndexerSearchMode,
-    IndexerTip, IndexerTx, IndexerTxWithCell, IndexerTxWithCells,
+    IndexerAmountChange, IndexerCell, IndexerCellEvent, IndexerCellEventType, IndexerCellType,
+    IndexerCellsCapacity, IndexerOrder, IndexerPagination, IndexerRange, IndexerScriptType,
+    IndexerSearchKey, IndexerSearchKeyFilter, IndexerSearchMode, IndexerTip, IndexerTx,
+    IndexerTxHistory, IndexerTxUdtAmounts, IndexerTxWithCell, IndexerTxWithCells,
+    IndexerUdtBalance, IndexerUdtHolder,
 };
 pub use primitive::{
     AsEpochNumberWithFraction, BlockNumber, Capacity, Cycle, EpochNumber, EpochNumberWithFraction,
@@ -246,6 +246,83 @@ pub struct BannedAddr {
     pub ban_reason: String,
     /// When this address is banned.
     pub created_at: Timestamp,
+    /// The cause of the ban.
+    #[serde(default)]
+    pub cause: BanCause,
+    /// The reported behaviours of the address before the ban, the oldest first.
+    #[serde(default)]
+    pub reports: Vec<PeerReport>,
+}
+
+/// The cause of a ban.
+///
+/// It's a string such as `"manual"`, except that the cause `behaviour` is an object which
+/// carries the reported behaviour, such as `{ "behaviour": <behaviour> }`.
+#[derive(Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BanCause {
+    /// The reported behaviour dropped the peer score below the ban score.
+    Behaviour(PeerBehaviour),
+    /// The peer misbehaved in the P2P protocols.
+    Misbehave,
+    /// Banned manually, for example, by the RPC `set_ban`.
+    Manual,
+    /// The ban is saved by an older version which does not record the causes.
+    #[default]
+    Unknown,
+}
+
+/// A behaviour of a peer which changes its score.
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PeerBehaviour {
+    /// The peer does not send the identify message in time. The score is decreased by 10.
+    Timeout,
+    /// The peer sends invalid, duplicated or too many items in the identify or discovery
+    /// protocol. The score is decreased by 20.
+    Misbehave,
+}
+
+/// A reported behaviour of a peer.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, JsonSchema)]
+pub struct PeerReport {
+    /// The reported behaviour.
+    pub behaviour: PeerBehaviour,
+    /// The peer score after the report, the peer is banned when the score drops below the ban
+    /// score.
+    pub score: i32,
+    /// When the behaviour is reported.
+    pub reported_at: Timestamp,
+}
+
+/// The reputation of a peer address.
+///
+/// ## Examples
+///
+/// ```
+/// # serde_json::from_str::<ckb_jsonrpc_types::PeerReputation>(r#"
+/// {
+///   "address": "/ip4/192.168.0.2/tcp/8112/p2p/QmTRHCdrRtgUzYLNCin69zEvPvLYdxUZLLfLYyHVY3DZAS",
+///   "banned": null,
+///   "reports": [],
+///   "score": 100
+/// }
+/// # "#).unwrap();
+/// ```
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, JsonSchema)]
+pub struct PeerReputation {
+    /// The P2P address.
+    pub address: String,
+    /// The current peer score.
+    ///
+    /// It is null when the address is not in the peer store.
+    pub score: Option<i32>,
+    /// The reported behaviours of the address, the oldest first.
+    pub reports: Vec<PeerReport>,
+    /// The ban which covers the address.
+    ///
+    /// It is null when the address is not banned.
+    pub banned: Option<BannedAddr>,
 }
 
 /// The overall chain synchronization state of this local node.
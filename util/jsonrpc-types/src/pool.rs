@@ -330,6 +330,9 @@ pub enum PoolTransactionReject {
 
     /// Invalidated rejected
     Invalidated(String),
+
+    /// Rejected by the tx-pool policies
+    Policy(String),
 }
 
 impl From<Reject> for PoolTransactionReject {
@@ -351,6 +354,7 @@ impl From<Reject> for PoolTransactionReject {
             Reject::Expiry(_) => Self::Expiry(format!("{reject}")),
             Reject::RBFRejected(_) => Self::RBFRejected(format!("{reject}")),
             Reject::Invalidated(_) => Self::Invalidated(format!("{reject}")),
+            Reject::Policy(_) => Self::Policy(format!("{reject}")),
         }
     }
 }
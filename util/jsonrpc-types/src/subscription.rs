@@ -1,3 +1,6 @@
+use crate::{IndexerSearchKey, TxStatus, Uint64};
+use ckb_types::H256;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// Specifies the topic which to be added as active subscription.
@@ -14,4 +17,54 @@ pub enum Topic {
     ProposedTransaction,
     /// Subscribe transactions which are abandoned by tx-pool.
     RejectedTransaction,
+    /// Subscribe chain reorganizations.
+    ChainReorg,
+    /// Subscribe cells created or consumed in the blocks, which match the search key.
+    ///
+    /// It requires the second param `search_key` and the Indexer module.
+    Cells,
+    /// Subscribe the status transitions of a transaction.
+    ///
+    /// It requires the second param `tx_hash`.
+    TransactionStatus,
+    /// Subscribe the updates of the block template for miners.
+    NewBlockTemplate,
+}
+
+/// The optional second param of `subscribe`.
+#[derive(Deserialize, Default)]
+pub struct SubscriptionOptions {
+    /// Wraps each push message with its sequence number in the topic, default is false.
+    #[serde(default)]
+    pub with_seq: bool,
+    /// Replays the kept messages whose sequence numbers are greater than `since_seq` before the
+    /// new messages. It implies `with_seq`.
+    #[serde(default)]
+    pub since_seq: Option<Uint64>,
+    /// The transaction hash, which is required by the topic `transaction_status`.
+    #[serde(default)]
+    pub tx_hash: Option<H256>,
+    /// The topic `transaction_status` ends after the transaction has been committed with this
+    /// number of confirmations, default is 24.
+    #[serde(default)]
+    pub confirmations: Option<Uint64>,
+    /// The search key, which is required by the topic `cells`.
+    #[serde(flatten)]
+    pub search_key: Option<IndexerSearchKey>,
+}
+
+/// A status transition of a transaction, which is pushed by the `transaction_status`
+/// subscription topic.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug, JsonSchema)]
+pub struct TransactionStatusEvent {
+    /// The transaction hash.
+    pub tx_hash: H256,
+    /// The transaction status.
+    ///
+    /// When the status is "rejected", `reason` is the JSON string of `PoolTransactionReject`,
+    /// which tells why the transaction is removed, for example, it is expired or replaced by RBF.
+    pub tx_status: TxStatus,
+    /// The number of confirmations when the status is "committed", the block committing the
+    /// transaction itself counts as 1 confirmation.
+    pub confirmations: Option<Uint64>,
 }
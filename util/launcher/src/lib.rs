@@ -25,6 +25,7 @@ use ckb_shared::shared_builder::{SharedBuilder, SharedPackage};
 use ckb_shared::{ChainServicesBuilder, Shared};
 use ckb_store::ChainDB;
 use ckb_sync::{BlockFilter, NetTimeProtocol, Relayer, SyncShared, Synchronizer};
+use ckb_tx_pool::block_assembler::MAX_REWARD_TARGETS_WEIGHT;
 use ckb_tx_pool::service::TxVerificationResult;
 use ckb_types::prelude::*;
 use ckb_verification::GenesisVerifier;
@@ -89,15 +90,39 @@ impl Launcher {
                     }))
             };
 
-            if block_assembler.hash_type == ScriptHashType::Data2 {
+            let reward_targets = block_assembler.all_reward_targets();
+            let total_weight: u64 = reward_targets
+                .iter()
+                .map(|target| u64::from(target.weight))
+                .sum();
+            if total_weight == 0 || total_weight > MAX_REWARD_TARGETS_WEIGHT {
+                eprintln!(
+                    "The total weight of the block assembler reward targets must be in 1..={MAX_REWARD_TARGETS_WEIGHT}, \
+                     but it is {total_weight}"
+                );
+                return Err(ExitCode::Config);
+            }
+            // all the reward targets must use the recommended lock unless `--ba-advanced` is set
+            let mut recommended_lock = true;
+            if !self.args.block_assembler_advanced {
+                for target in &reward_targets {
+                    recommended_lock = target.hash_type == ScriptHashType::Type
+                        && target.args.len() == SECP256K1_BLAKE160_SIGHASH_ALL_ARG_LEN
+                        && check_lock_code_hash(&target.code_hash.pack())?;
+                    if !recommended_lock {
+                        break;
+                    }
+                }
+            }
+
+            if reward_targets
+                .iter()
+                .any(|target| target.hash_type == ScriptHashType::Data2)
+            {
                 warn!("Miner is disabled because block assembler uses a non-support lock format.");
 
                 None
-            } else if self.args.block_assembler_advanced
-                || (block_assembler.hash_type == ScriptHashType::Type
-                    && block_assembler.args.len() == SECP256K1_BLAKE160_SIGHASH_ALL_ARG_LEN
-                    && check_lock_code_hash(&block_assembler.code_hash.pack())?)
-            {
+            } else if self.args.block_assembler_advanced || recommended_lock {
                 if block_assembler.use_binary_version_as_message_prefix {
                     block_assembler.binary_version = self.version.long();
                 }
@@ -207,6 +232,7 @@ impl Launcher {
             .header_map_tmp_dir(self.args.config.tmp_dir.clone())
             .block_assembler_config(block_assembler_config)
             .fee_estimator_config(self.args.config.fee_estimator.clone())
+            .cell_snapshot(self.args.cell_snapshot.clone())
             .build()?;
 
         // internal check migrate_version
@@ -273,7 +299,7 @@ impl Launcher {
         chain_controller: ChainController,
         miner_enable: bool,
         relay_tx_receiver: Receiver<TxVerificationResult>,
-    ) -> NetworkController {
+    ) -> Result<NetworkController, ExitCode> {
         let sync_shared = Arc::new(SyncShared::new(
             shared.clone(),
             self.args.config.network.sync.clone(),
@@ -321,7 +347,7 @@ impl Launcher {
                 ))
             }
         } else {
-            flags.remove(Flags::RELAY);
+            flags.remove(Flags::RELAY | Flags::PACKAGE_RELAY);
         }
 
         if support_protocols.contains(&SupportProtocol::Filter) {
@@ -356,6 +382,10 @@ impl Launcher {
             flags.remove(Flags::LIGHT_CLIENT);
         }
 
+        if self.args.config.store.prune_depth.is_none() {
+            flags.remove(Flags::LIMITED);
+        }
+
         let alert_signature_config = self.args.config.alert_signature.clone().unwrap_or_default();
         let alert_relayer = AlertRelayer::new(
             self.version.short(),
@@ -439,12 +469,21 @@ impl Launcher {
                 &self.args.config.db,
                 &self.args.config.indexer,
             )
-            .enable_debug();
+            .enable_debug(shared.clone());
         builder.enable_subscription(shared.clone());
+        let access_control = builder.access_control().map_err(|err| {
+            eprintln!("rpc config error: {err}");
+            ExitCode::Config
+        })?;
         let io_handler = builder.build();
 
-        let _rpc = RpcServer::new(rpc_config, io_handler, self.rpc_handle.clone());
+        let _rpc = RpcServer::new(
+            rpc_config,
+            io_handler,
+            access_control,
+            self.rpc_handle.clone(),
+        );
 
-        network_controller
+        Ok(network_controller)
     }
 }
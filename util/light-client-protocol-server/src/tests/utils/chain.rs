@@ -76,6 +76,8 @@ impl MockChain {
                 notify: vec![],
                 notify_scripts: vec![],
                 notify_timeout_millis: 800,
+                weight: 1,
+                reward_targets: vec![],
             };
             SharedBuilder::with_temp_db()
                 .consensus(consensus)
@@ -126,6 +126,8 @@ pub struct Metrics {
     pub ckb_network_ban_peer: IntCounter,
     pub ckb_inflight_blocks_count: IntGauge,
     pub ckb_inflight_timeout_count: IntCounter,
+    /// Counter for RPC calls rejected by the rate limits, labeled by method
+    pub ckb_rpc_throttled_calls: IntCounterVec,
 }
 
 static METRICS: std::sync::LazyLock<Metrics> = std::sync::LazyLock::new(|| {
@@ -312,6 +314,11 @@ static METRICS: std::sync::LazyLock<Metrics> = std::sync::LazyLock::new(|| {
             "ckb_inflight_timeout_count",
             "The CKB inflight timeout count"
     ).unwrap(),
+    ckb_rpc_throttled_calls: register_int_counter_vec!(
+            "ckb_rpc_throttled_calls",
+            "The CKB RPC calls rejected by the rate limits",
+            &["method"]
+    ).unwrap(),
     }
 });
 
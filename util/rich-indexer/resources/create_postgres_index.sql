@@ -13,3 +13,5 @@ CREATE INDEX IF NOT EXISTS "idx_output_table_lock_script_id" ON "output" ("lock_
 CREATE INDEX IF NOT EXISTS "idx_output_table_type_script_id" ON "output" ("type_script_id");
 
 CREATE INDEX IF NOT EXISTS "idx_input_table_consumed_tx_id" ON "input" ("consumed_tx_id");
+
+CREATE INDEX IF NOT EXISTS "idx_udt_balance_table_type_script_id_amount" ON "udt_balance" ("type_script_id", "amount");
@@ -75,3 +75,18 @@ CREATE TABLE IF NOT EXISTS script(
     args BYTEA,
     UNIQUE(code_hash, hash_type, args)
 );
+
+CREATE TABLE IF NOT EXISTS udt_script(
+    id BIGSERIAL PRIMARY KEY,
+    code_hash BYTEA NOT NULL,
+    hash_type SMALLINT NOT NULL,
+    UNIQUE(code_hash, hash_type)
+);
+
+CREATE TABLE IF NOT EXISTS udt_balance(
+    lock_script_id BIGINT NOT NULL,
+    type_script_id BIGINT NOT NULL,
+    amount BYTEA NOT NULL,
+    cell_count BIGINT NOT NULL,
+    PRIMARY KEY(lock_script_id, type_script_id)
+);
@@ -75,3 +75,18 @@ CREATE TABLE IF NOT EXISTS script(
     args BLOB,
     UNIQUE(code_hash, hash_type, args)
 );
+
+CREATE TABLE IF NOT EXISTS udt_script(
+    id INTEGER PRIMARY KEY,
+    code_hash BLOB NOT NULL,
+    hash_type INTEGER NOT NULL,
+    UNIQUE(code_hash, hash_type)
+);
+
+CREATE TABLE IF NOT EXISTS udt_balance(
+    lock_script_id INTEGER NOT NULL,
+    type_script_id INTEGER NOT NULL,
+    amount BLOB NOT NULL,
+    cell_count INTEGER NOT NULL,
+    PRIMARY KEY(lock_script_id, type_script_id)
+);
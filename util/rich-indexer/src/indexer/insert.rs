@@ -1,6 +1,6 @@
 #![allow(clippy::needless_borrow)]
 
-use super::to_fixed_array;
+use super::{query_tx_id_list_by_block_id, to_fixed_array, update_udt_balances, UdtScripts};
 use crate::store::SQLXPool;
 
 use ckb_indexer_sync::Error;
@@ -400,6 +400,18 @@ pub(crate) async fn bulk_insert_tx_association_cell_dep_table(
     .await
 }
 
+pub(crate) async fn insert_udt_balances(
+    block_id: i64,
+    udt_scripts: &UdtScripts,
+    tx: &mut Transaction<'_, Any>,
+) -> Result<(), Error> {
+    if udt_scripts.is_empty() {
+        return Ok(());
+    }
+    let tx_id_list = query_tx_id_list_by_block_id(block_id, tx).await?;
+    update_udt_balances(udt_scripts, &tx_id_list, false, tx).await
+}
+
 pub(crate) async fn spend_cell(
     out_point: &OutPoint,
     tx: &mut Transaction<'_, Any>,
@@ -1,20 +1,22 @@
 mod insert;
 mod remove;
+mod udt;
 
 pub(crate) use insert::*;
 pub(crate) use remove::*;
+pub(crate) use udt::*;
 
 use crate::{service::SUBSCRIBER_NAME, store::SQLXPool, RichIndexerHandle};
 
 use ckb_async_runtime::Handle;
-use ckb_indexer_sync::{CustomFilters, Error, IndexerSync, Pool};
+use ckb_indexer_sync::{CustomFilters, Error, IndexerSync, Pool, ReindexState};
 use ckb_types::{
     core::{BlockNumber, BlockView, TransactionView},
     packed::Byte32,
     prelude::*,
     H256,
 };
-use sqlx::{Any, Transaction};
+use sqlx::{Any, Row, Transaction};
 
 use std::collections::HashSet;
 use std::sync::{Arc, RwLock};
@@ -26,6 +28,8 @@ use std::sync::{Arc, RwLock};
 /// - input
 /// - output
 /// - script
+/// - udt_script
+/// - udt_balance
 /// - block_association_proposal
 /// - block_association_uncle
 /// - tx_association_header_dep
@@ -46,11 +50,12 @@ impl RichIndexer {
         store: SQLXPool,
         pool: Option<Arc<RwLock<Pool>>>,
         custom_filters: CustomFilters,
+        udt_scripts: UdtScripts,
         async_runtime: Handle,
         request_limit: usize,
     ) -> Self {
         Self {
-            async_rich_indexer: AsyncRichIndexer::new(store, pool, custom_filters),
+            async_rich_indexer: AsyncRichIndexer::new(store, pool, custom_filters, udt_scripts),
             async_runtime,
             request_limit,
         }
@@ -96,6 +101,12 @@ impl IndexerSync for RichIndexer {
             .set_init_tip(init_tip_number, init_tip_hash);
         self.async_runtime.block_on(future)
     }
+
+    /// Remove the blocks from the first block of the range and append them again
+    fn reindex(&self, block: &BlockView, state: &mut ReindexState) -> Result<(), Error> {
+        let future = self.async_rich_indexer.reindex(block, state);
+        self.async_runtime.block_on(future)
+    }
 }
 
 /// Async rich-indexer.
@@ -108,6 +119,8 @@ pub(crate) struct AsyncRichIndexer {
     pub(crate) pool: Option<Arc<RwLock<Pool>>>,
     /// custom filters
     custom_filters: CustomFilters,
+    /// The UDT type scripts whose balances are aggregated
+    udt_scripts: UdtScripts,
 }
 
 impl AsyncRichIndexer {
@@ -116,11 +129,13 @@ impl AsyncRichIndexer {
         store: SQLXPool,
         pool: Option<Arc<RwLock<Pool>>>,
         custom_filters: CustomFilters,
+        udt_scripts: UdtScripts,
     ) -> Self {
         Self {
             store,
             pool,
             custom_filters,
+            udt_scripts,
         }
     }
 }
@@ -135,6 +150,7 @@ impl AsyncRichIndexer {
         if self.custom_filters.is_block_filter_match(block) {
             let block_id = append_block(block, &mut tx).await?;
             self.insert_transactions(block_id, block, &mut tx).await?;
+            insert_udt_balances(block_id, &self.udt_scripts, &mut tx).await?;
         } else {
             let block_headers = vec![(block.hash().raw_data().to_vec(), block.number() as i64)];
             bulk_insert_blocks_simple(block_headers, &mut tx).await?;
@@ -157,11 +173,48 @@ impl AsyncRichIndexer {
             .await
             .map_err(|err| Error::DB(err.to_string()))?;
 
-        rollback_block(&mut tx).await?;
+        rollback_block(&self.udt_scripts, &mut tx).await?;
 
         tx.commit().await.map_err(|err| Error::DB(err.to_string()))
     }
 
+    /// The rows are stored in the chain order, so the blocks from the first block of the range are
+    /// rolled back at the first call, then the blocks are appended again with the current filters,
+    /// by the following calls or by the tip-following, the blocks appended already are skipped.
+    pub(crate) async fn reindex(
+        &self,
+        block: &BlockView,
+        state: &mut ReindexState,
+    ) -> Result<(), Error> {
+        let block_number = block.number();
+        let indexed = |tip_number: Option<BlockNumber>| {
+            tip_number.is_some_and(|tip_number| tip_number >= block_number)
+        };
+        if block_number == state.from {
+            while indexed(self.tip_number().await?) {
+                self.rollback().await?;
+            }
+        } else if indexed(self.tip_number().await?) {
+            return Ok(());
+        }
+        self.append(block).await
+    }
+
+    async fn tip_number(&self) -> Result<Option<BlockNumber>, Error> {
+        let query = SQLXPool::new_query(
+            r#"
+            SELECT block_number FROM block
+            ORDER BY id DESC
+            LIMIT 1
+            "#,
+        );
+        self.store
+            .fetch_optional(query)
+            .await
+            .map(|res| res.map(|row| row.get::<i64, _>("block_number") as BlockNumber))
+            .map_err(|err| Error::DB(err.to_string()))
+    }
+
     pub(crate) async fn insert_transactions(
         &self,
         block_id: i64,
@@ -4,7 +4,10 @@ use ckb_indexer_sync::Error;
 use sql_builder::SqlBuilder;
 use sqlx::{Any, Row, Transaction};
 
-pub(crate) async fn rollback_block(tx: &mut Transaction<'_, Any>) -> Result<(), Error> {
+pub(crate) async fn rollback_block(
+    udt_scripts: &UdtScripts,
+    tx: &mut Transaction<'_, Any>,
+) -> Result<(), Error> {
     let block_id = if let Some(block_id) = query_tip_id(tx).await? {
         block_id
     } else {
@@ -14,6 +17,9 @@ pub(crate) async fn rollback_block(tx: &mut Transaction<'_, Any>) -> Result<(),
     let tx_id_list = query_tx_id_list_by_block_id(block_id, tx).await?;
     let output_lock_type_list = query_outputs_by_tx_id_list(&tx_id_list, tx).await?;
 
+    // revert udt balances before the inputs and outputs are removed
+    update_udt_balances(udt_scripts, &tx_id_list, true, tx).await?;
+
     // update spent cells
     reset_spent_cells(&tx_id_list, tx).await?;
 
@@ -137,7 +143,7 @@ async fn query_tip_id(tx: &mut Transaction<'_, Any>) -> Result<Option<i64>, Erro
     .map_err(|err| Error::DB(err.to_string()))
 }
 
-async fn query_tx_id_list_by_block_id(
+pub(crate) async fn query_tx_id_list_by_block_id(
     block_id: i64,
     tx: &mut Transaction<'_, Any>,
 ) -> Result<Vec<i64>, Error> {
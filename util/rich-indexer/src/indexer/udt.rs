@@ -0,0 +1,308 @@
+use crate::store::SQLXPool;
+
+use ckb_app_config::RichIndexerUdtScript;
+use ckb_indexer_sync::Error;
+use ckb_types::core::ScriptHashType;
+use num_bigint::{BigInt, BigUint, Sign};
+use sqlx::{Any, Row, Transaction};
+
+use std::collections::{HashMap, HashSet};
+
+/// The byte length of the `amount` column in the `udt_balance` table.
+///
+/// The balances are stored as big-endian 256-bit integers, the sum of u128 amounts never overflows
+/// and the byte order of the column matches the numeric order.
+pub(crate) const UDT_AMOUNT_LEN: usize = 32;
+
+/// The type scripts whose cells are aggregated into the `udt_balance` table.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct UdtScripts {
+    scripts: HashSet<(Vec<u8>, i16)>,
+}
+
+impl UdtScripts {
+    pub fn new(udt_scripts: &[RichIndexerUdtScript]) -> Self {
+        let scripts = udt_scripts
+            .iter()
+            .map(|script| {
+                let hash_type: ScriptHashType = script.hash_type.clone().into();
+                (
+                    script.code_hash.as_bytes().to_vec(),
+                    u8::from(hash_type) as i16,
+                )
+            })
+            .collect();
+        Self { scripts }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scripts.is_empty()
+    }
+
+    /// Returns the UDT amount of a cell, `None` if the cell is not a UDT cell.
+    pub fn amount(&self, code_hash: &[u8], hash_type: i16, data: &[u8]) -> Option<u128> {
+        if !self.scripts.contains(&(code_hash.to_vec(), hash_type)) {
+            return None;
+        }
+        parse_udt_amount(data)
+    }
+}
+
+/// Parses the amount in the first 16 bytes of the UDT cell data, `None` if the data is too short.
+fn parse_udt_amount(data: &[u8]) -> Option<u128> {
+    let amount: [u8; 16] = data.get(..16)?.try_into().ok()?;
+    Some(u128::from_le_bytes(amount))
+}
+
+pub(crate) fn encode_udt_amount(amount: &BigUint) -> Vec<u8> {
+    let bytes = amount.to_bytes_be();
+    let mut ret = vec![0u8; UDT_AMOUNT_LEN - bytes.len()];
+    ret.extend_from_slice(&bytes);
+    ret
+}
+
+pub(crate) fn decode_udt_amount(amount: &[u8]) -> BigUint {
+    BigUint::from_bytes_be(amount)
+}
+
+/// The changes of the `udt_balance` table, keyed by (lock_script_id, type_script_id).
+#[derive(Default)]
+pub(crate) struct UdtBalanceDeltas {
+    deltas: HashMap<(i64, i64), (BigInt, i64)>,
+}
+
+impl UdtBalanceDeltas {
+    pub fn credit(&mut self, lock_script_id: i64, type_script_id: i64, amount: u128) {
+        let delta = self
+            .deltas
+            .entry((lock_script_id, type_script_id))
+            .or_default();
+        delta.0 += BigInt::from(amount);
+        delta.1 += 1;
+    }
+
+    pub fn debit(&mut self, lock_script_id: i64, type_script_id: i64, amount: u128) {
+        let delta = self
+            .deltas
+            .entry((lock_script_id, type_script_id))
+            .or_default();
+        delta.0 -= BigInt::from(amount);
+        delta.1 -= 1;
+    }
+
+    /// Applies the changes, the rows without any cells are removed.
+    pub async fn apply(self, tx: &mut Transaction<'_, Any>) -> Result<(), Error> {
+        for ((lock_script_id, type_script_id), (amount_delta, count_delta)) in self.deltas {
+            if count_delta == 0 && amount_delta.sign() == Sign::NoSign {
+                continue;
+            }
+            let row = SQLXPool::new_query(
+                r#"
+                SELECT amount, cell_count FROM udt_balance
+                WHERE lock_script_id = $1 AND type_script_id = $2
+                "#,
+            )
+            .bind(lock_script_id)
+            .bind(type_script_id)
+            .fetch_optional(tx.as_mut())
+            .await
+            .map_err(|err| Error::DB(err.to_string()))?;
+            let (amount, cell_count) = match &row {
+                Some(row) => (
+                    BigInt::from(decode_udt_amount(&row.get::<Vec<u8>, _>("amount"))),
+                    row.get::<i64, _>("cell_count"),
+                ),
+                None => (BigInt::default(), 0),
+            };
+            let amount = amount + amount_delta;
+            let cell_count = cell_count + count_delta;
+
+            if cell_count <= 0 {
+                if row.is_some() {
+                    SQLXPool::new_query(
+                        r#"
+                        DELETE FROM udt_balance
+                        WHERE lock_script_id = $1 AND type_script_id = $2
+                        "#,
+                    )
+                    .bind(lock_script_id)
+                    .bind(type_script_id)
+                    .execute(tx.as_mut())
+                    .await
+                    .map_err(|err| Error::DB(err.to_string()))?;
+                }
+                continue;
+            }
+            let amount = amount.to_biguint().ok_or_else(|| {
+                Error::DB(format!(
+                    "negative udt balance of lock script {} and type script {}",
+                    lock_script_id, type_script_id
+                ))
+            })?;
+            let sql = if row.is_some() {
+                r#"
+                UPDATE udt_balance SET amount = $3, cell_count = $4
+                WHERE lock_script_id = $1 AND type_script_id = $2
+                "#
+            } else {
+                r#"
+                INSERT INTO udt_balance (lock_script_id, type_script_id, amount, cell_count)
+                VALUES ($1, $2, $3, $4)
+                "#
+            };
+            SQLXPool::new_query(sql)
+                .bind(lock_script_id)
+                .bind(type_script_id)
+                .bind(encode_udt_amount(&amount))
+                .bind(cell_count)
+                .execute(tx.as_mut())
+                .await
+                .map_err(|err| Error::DB(err.to_string()))?;
+        }
+        Ok(())
+    }
+}
+
+/// Updates the UDT balances with the cells created and consumed by the transactions,
+/// `revert` reverses the changes when the transactions are rolled back.
+pub(crate) async fn update_udt_balances(
+    udt_scripts: &UdtScripts,
+    tx_id_list: &[i64],
+    revert: bool,
+    tx: &mut Transaction<'_, Any>,
+) -> Result<(), Error> {
+    if udt_scripts.is_empty() || tx_id_list.is_empty() {
+        return Ok(());
+    }
+
+    let mut deltas = UdtBalanceDeltas::default();
+    for tx_id in tx_id_list {
+        let created = SQLXPool::new_query(
+            r#"
+            SELECT output.lock_script_id, output.type_script_id, output.data,
+                script.code_hash, script.hash_type
+            FROM output
+            JOIN script ON output.type_script_id = script.id
+            WHERE output.tx_id = $1
+            "#,
+        )
+        .bind(tx_id)
+        .fetch_all(tx.as_mut())
+        .await
+        .map_err(|err| Error::DB(err.to_string()))?;
+        let consumed = SQLXPool::new_query(
+            r#"
+            SELECT output.lock_script_id, output.type_script_id, output.data,
+                script.code_hash, script.hash_type
+            FROM input
+            JOIN output ON input.output_id = output.id
+            JOIN script ON output.type_script_id = script.id
+            WHERE input.consumed_tx_id = $1
+            "#,
+        )
+        .bind(tx_id)
+        .fetch_all(tx.as_mut())
+        .await
+        .map_err(|err| Error::DB(err.to_string()))?;
+
+        for (rows, is_credit) in [(created, !revert), (consumed, revert)] {
+            for row in rows {
+                let amount = udt_scripts.amount(
+                    &row.get::<Vec<u8>, _>("code_hash"),
+                    row.get::<i16, _>("hash_type"),
+                    &row.get::<Vec<u8>, _>("data"),
+                );
+                if let Some(amount) = amount {
+                    let lock_script_id = row.get::<i64, _>("lock_script_id");
+                    let type_script_id = row.get::<i64, _>("type_script_id");
+                    if is_credit {
+                        deltas.credit(lock_script_id, type_script_id, amount);
+                    } else {
+                        deltas.debit(lock_script_id, type_script_id, amount);
+                    }
+                }
+            }
+        }
+    }
+    deltas.apply(tx).await
+}
+
+/// Brings the `udt_balance` table in line with the configured UDT scripts.
+///
+/// The balances of the newly configured scripts are rebuilt from the live cells, and the balances
+/// of the scripts no longer configured are removed.
+pub(crate) async fn sync_udt_scripts(
+    store: &SQLXPool,
+    udt_scripts: &UdtScripts,
+) -> Result<(), Error> {
+    let mut tx = store
+        .transaction()
+        .await
+        .map_err(|err| Error::DB(err.to_string()))?;
+
+    let indexed: HashSet<(Vec<u8>, i16)> =
+        SQLXPool::new_query("SELECT code_hash, hash_type FROM udt_script")
+            .fetch_all(tx.as_mut())
+            .await
+            .map_err(|err| Error::DB(err.to_string()))?
+            .into_iter()
+            .map(|row| (row.get("code_hash"), row.get("hash_type")))
+            .collect();
+
+    for (code_hash, hash_type) in indexed.difference(&udt_scripts.scripts) {
+        SQLXPool::new_query(
+            r#"
+            DELETE FROM udt_balance
+            WHERE type_script_id IN (
+                SELECT id FROM script WHERE code_hash = $1 AND hash_type = $2
+            )
+            "#,
+        )
+        .bind(code_hash.clone())
+        .bind(*hash_type)
+        .execute(tx.as_mut())
+        .await
+        .map_err(|err| Error::DB(err.to_string()))?;
+        SQLXPool::new_query("DELETE FROM udt_script WHERE code_hash = $1 AND hash_type = $2")
+            .bind(code_hash.clone())
+            .bind(*hash_type)
+            .execute(tx.as_mut())
+            .await
+            .map_err(|err| Error::DB(err.to_string()))?;
+    }
+
+    for (code_hash, hash_type) in udt_scripts.scripts.difference(&indexed) {
+        let rows = SQLXPool::new_query(
+            r#"
+            SELECT output.lock_script_id, output.type_script_id, output.data
+            FROM output
+            JOIN script ON output.type_script_id = script.id
+            WHERE script.code_hash = $1 AND script.hash_type = $2 AND output.is_spent = 0
+            "#,
+        )
+        .bind(code_hash.clone())
+        .bind(*hash_type)
+        .fetch_all(tx.as_mut())
+        .await
+        .map_err(|err| Error::DB(err.to_string()))?;
+
+        let mut deltas = UdtBalanceDeltas::default();
+        for row in rows {
+            if let Some(amount) =
+                udt_scripts.amount(code_hash, *hash_type, &row.get::<Vec<u8>, _>("data"))
+            {
+                deltas.credit(row.get("lock_script_id"), row.get("type_script_id"), amount);
+            }
+        }
+        deltas.apply(&mut tx).await?;
+
+        SQLXPool::new_query("INSERT INTO udt_script (code_hash, hash_type) VALUES ($1, $2)")
+            .bind(code_hash.clone())
+            .bind(*hash_type)
+            .execute(tx.as_mut())
+            .await
+            .map_err(|err| Error::DB(err.to_string()))?;
+    }
+
+    tx.commit().await.map_err(|err| Error::DB(err.to_string()))
+}
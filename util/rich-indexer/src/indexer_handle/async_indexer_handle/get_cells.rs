@@ -5,7 +5,7 @@ use crate::store::SQLXPool;
 
 use ckb_indexer_sync::Error;
 use ckb_jsonrpc_types::{
-    IndexerCell, IndexerOrder, IndexerPagination, IndexerSearchKey, JsonBytes, Uint32,
+    BlockNumber, IndexerCell, IndexerOrder, IndexerPagination, IndexerSearchKey, JsonBytes, Uint32,
 };
 use ckb_jsonrpc_types::{IndexerScriptType, IndexerSearchMode};
 use ckb_types::packed::{CellOutputBuilder, OutPointBuilder, ScriptBuilder};
@@ -15,12 +15,16 @@ use sqlx::{any::AnyRow, Row};
 
 impl AsyncRichIndexerHandle {
     /// Get cells
+    ///
+    /// If `as_of_block` is given, returns the cells which were live at that block instead of the
+    /// current live cells, the cells consumed by the pool transactions are not excluded then.
     pub async fn get_cells(
         &self,
         search_key: IndexerSearchKey,
         order: IndexerOrder,
         limit: Uint32,
         after: Option<JsonBytes>,
+        as_of_block: Option<BlockNumber>,
     ) -> Result<IndexerPagination<IndexerCell>, Error> {
         let limit = limit.value();
         if limit == 0 {
@@ -32,15 +36,16 @@ impl AsyncRichIndexerHandle {
                 self.request_limit,
             )));
         }
+        let as_of_block: Option<u64> = as_of_block.map(Into::into);
+        if let Some(as_of_block) = as_of_block {
+            self.as_of_block_hash(as_of_block).await?;
+        }
 
         let mut param_index = 1;
 
         // sub query for script
-        let script_sub_query_sql = build_query_script_sql(
-            self.store.db_driver,
-            &search_key.script_search_mode,
-            &mut param_index,
-        )?;
+        let script_sub_query_sql =
+            build_query_script_sql(self.store.db_driver, &search_key, &mut param_index)?;
 
         // query output
         let mut query_builder = SqlBuilder::select_from("output");
@@ -103,14 +108,20 @@ impl AsyncRichIndexerHandle {
                 .left()
                 .join(name!("script";"lock_script"))
                 .on("output.lock_script_id = lock_script.id"),
+        };
+        match as_of_block {
+            Some(as_of_block) => add_as_of_block_conditions(&mut query_builder, as_of_block),
+            None => {
+                query_builder.and_where("output.is_spent = 0"); // live cells
+            }
         }
-        .and_where("output.is_spent = 0"); // live cells
 
         // filter cells in pool
         let mut dead_cells = Vec::new();
         if let Some(pool) = self
             .pool
             .as_ref()
+            .filter(|_| as_of_block.is_none())
             .map(|pool| pool.read().expect("acquire lock"))
         {
             dead_cells = pool
@@ -164,28 +175,7 @@ impl AsyncRichIndexerHandle {
 
         // bind
         let mut query = SQLXPool::new_query(&sql);
-        query = query
-            .bind(search_key.script.code_hash.as_bytes())
-            .bind(search_key.script.hash_type as i16);
-        match &search_key.script_search_mode {
-            Some(IndexerSearchMode::Prefix) | None => {
-                query = query
-                    .bind(search_key.script.args.as_bytes())
-                    .bind(get_binary_upper_boundary(search_key.script.args.as_bytes()));
-            }
-            Some(IndexerSearchMode::Exact) => {
-                query = query.bind(search_key.script.args.as_bytes());
-            }
-            Some(IndexerSearchMode::Partial) => match self.store.db_driver {
-                DBDriver::Postgres => {
-                    let new_args = escape_and_wrap_for_postgres_like(&search_key.script.args);
-                    query = query.bind(new_args);
-                }
-                DBDriver::Sqlite => {
-                    query = query.bind(search_key.script.args.as_bytes());
-                }
-            },
-        }
+        query = bind_search_scripts(query, self.store.db_driver, &search_key);
         if let Some(filter) = search_key.filter.as_ref() {
             if let Some(script) = filter.script.as_ref() {
                 query = query
@@ -3,7 +3,7 @@ use super::*;
 use crate::store::SQLXPool;
 
 use ckb_indexer_sync::Error;
-use ckb_jsonrpc_types::{IndexerCellsCapacity, IndexerSearchKey};
+use ckb_jsonrpc_types::{BlockNumber, IndexerCellsCapacity, IndexerSearchKey};
 use ckb_jsonrpc_types::{IndexerScriptType, IndexerSearchMode};
 use ckb_types::prelude::*;
 use sql_builder::{name, name::SqlName, SqlBuilder};
@@ -11,17 +11,23 @@ use sqlx::Row;
 
 impl AsyncRichIndexerHandle {
     /// Get cells_capacity by specified search_key
+    ///
+    /// If `as_of_block` is given, returns the capacity of the cells which were live at that block.
     pub async fn get_cells_capacity(
         &self,
         search_key: IndexerSearchKey,
+        as_of_block: Option<BlockNumber>,
     ) -> Result<Option<IndexerCellsCapacity>, Error> {
+        let as_of_block: Option<u64> = as_of_block.map(Into::into);
+        let as_of_hash = match as_of_block {
+            Some(as_of_block) => Some(self.as_of_block_hash(as_of_block).await?),
+            None => None,
+        };
+
         // sub query for script
         let mut param_index = 1;
-        let script_sub_query_sql = build_query_script_id_sql(
-            self.store.db_driver,
-            &search_key.script_search_mode,
-            &mut param_index,
-        )?;
+        let script_sub_query_sql =
+            build_query_script_id_sql(self.store.db_driver, &search_key, &mut param_index)?;
 
         // query output
         let mut query_builder = SqlBuilder::select_from("output");
@@ -36,15 +42,19 @@ impl AsyncRichIndexerHandle {
             }
         }
         let mut joined_ckb_transaction = false;
-        if let Some(ref filter) = search_key.filter {
-            if filter.block_range.is_some() {
-                query_builder
-                    .join("ckb_transaction")
-                    .on("output.tx_id = ckb_transaction.id")
-                    .join("block")
-                    .on("ckb_transaction.block_id = block.id");
-                joined_ckb_transaction = true;
-            }
+        if search_key
+            .filter
+            .as_ref()
+            .map(|filter| filter.block_range.is_some())
+            .unwrap_or(false)
+            || as_of_block.is_some()
+        {
+            query_builder
+                .join("ckb_transaction")
+                .on("output.tx_id = ckb_transaction.id")
+                .join("block")
+                .on("ckb_transaction.block_id = block.id");
+            joined_ckb_transaction = true;
         }
         if self.pool.is_some() && !joined_ckb_transaction {
             query_builder
@@ -69,13 +79,19 @@ impl AsyncRichIndexerHandle {
                 }
             }
         }
-        query_builder.and_where("output.is_spent = 0"); // live cells
+        match as_of_block {
+            Some(as_of_block) => add_as_of_block_conditions(&mut query_builder, as_of_block),
+            None => {
+                query_builder.and_where("output.is_spent = 0"); // live cells
+            }
+        }
 
         // filter cells in pool
         let mut dead_cells = Vec::new();
         if let Some(pool) = self
             .pool
             .as_ref()
+            .filter(|_| as_of_block.is_none())
             .map(|pool| pool.read().expect("acquire lock"))
         {
             dead_cells = pool
@@ -118,28 +134,7 @@ impl AsyncRichIndexerHandle {
 
         // bind
         let mut query = SQLXPool::new_query(&sql);
-        query = query
-            .bind(search_key.script.code_hash.as_bytes())
-            .bind(search_key.script.hash_type as i16);
-        match &search_key.script_search_mode {
-            Some(IndexerSearchMode::Prefix) | None => {
-                query = query
-                    .bind(search_key.script.args.as_bytes())
-                    .bind(get_binary_upper_boundary(search_key.script.args.as_bytes()));
-            }
-            Some(IndexerSearchMode::Exact) => {
-                query = query.bind(search_key.script.args.as_bytes());
-            }
-            Some(IndexerSearchMode::Partial) => match self.store.db_driver {
-                DBDriver::Postgres => {
-                    let new_args = escape_and_wrap_for_postgres_like(&search_key.script.args);
-                    query = query.bind(new_args);
-                }
-                DBDriver::Sqlite => {
-                    query = query.bind(search_key.script.args.as_bytes());
-                }
-            },
-        }
+        query = bind_search_scripts(query, self.store.db_driver, &search_key);
         if let Some(filter) = search_key.filter.as_ref() {
             if let Some(script) = filter.script.as_ref() {
                 query = query
@@ -194,6 +189,16 @@ impl AsyncRichIndexerHandle {
             Some(capacity) => capacity as u64,
             None => return Ok(None),
         };
+        if let (Some(as_of_block), Some(block_hash)) = (as_of_block, as_of_hash) {
+            tx.commit()
+                .await
+                .map_err(|err| Error::DB(err.to_string()))?;
+            return Ok(Some(IndexerCellsCapacity {
+                capacity: capacity.into(),
+                block_hash,
+                block_number: as_of_block.into(),
+            }));
+        }
 
         let (block_hash, block_number) = SQLXPool::new_query(
             r#"
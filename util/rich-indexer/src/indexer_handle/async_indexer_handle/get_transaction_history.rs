@@ -0,0 +1,210 @@
+use super::get_transactions::get_tx_with_cells;
+use super::*;
+
+use crate::indexer::encode_udt_amount;
+use crate::store::SQLXPool;
+
+use ckb_indexer_sync::Error;
+use ckb_jsonrpc_types::{
+    IndexerAmountChange, IndexerOrder, IndexerPagination, IndexerScriptType, IndexerSearchKey,
+    IndexerTxHistory, IndexerTxUdtAmounts, JsonBytes, Uint32,
+};
+use ckb_types::U256;
+use num_bigint::BigUint;
+use sqlx::Row;
+
+use std::collections::HashMap;
+
+impl AsyncRichIndexerHandle {
+    /// Get the transactions of the scripts with the capacity and UDT amounts they moved
+    ///
+    /// The transactions are selected in the same way as the grouped `get_transactions`,
+    /// `search_key.group_by_transaction` is ignored. The UDT amounts are summed from the cells of
+    /// the configured UDT scripts.
+    pub async fn get_transaction_history(
+        &self,
+        mut search_key: IndexerSearchKey,
+        order: IndexerOrder,
+        limit: Uint32,
+        after: Option<JsonBytes>,
+    ) -> Result<IndexerPagination<IndexerTxHistory>, Error> {
+        let limit = self.check_limit(limit)?;
+        search_key.filter = convert_max_values_in_search_filter(&search_key.filter);
+        // the UDT amounts only make sense when the cells are selected by a type script
+        let with_udt = !self.udt_scripts.is_empty()
+            && match search_key.script_type {
+                IndexerScriptType::Type => true,
+                IndexerScriptType::Lock => search_key
+                    .filter
+                    .as_ref()
+                    .map(|filter| filter.script.is_some())
+                    .unwrap_or_default(),
+            };
+
+        let mut tx = self
+            .store
+            .transaction()
+            .await
+            .map_err(|err| Error::DB(err.to_string()))?;
+
+        let txs = get_tx_with_cells(
+            self.store.db_driver,
+            search_key,
+            &order,
+            limit,
+            after,
+            &mut tx,
+        )
+        .await?;
+        if txs.is_empty() {
+            return Ok(IndexerPagination {
+                objects: Vec::new(),
+                last_cursor: JsonBytes::from_vec(0i64.to_le_bytes().to_vec()),
+            });
+        }
+
+        // the ids are queried from the database, it is safe to put them into the sql directly
+        let tx_ids = txs
+            .iter()
+            .map(|(id, ..)| id.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let (udt_fields, udt_join) = if with_udt {
+            (
+                ", output.data, script.code_hash, script.hash_type",
+                "LEFT JOIN script ON output.type_script_id = script.id",
+            )
+        } else {
+            ("", "")
+        };
+
+        // the values of the created cells and the consumed cells
+        let sql = format!(
+            r#"
+            SELECT output.tx_id AS tx_id, 1 AS io_type, output.output_index AS io_index,
+                output.capacity{udt_fields}
+            FROM output
+            {udt_join}
+            WHERE output.tx_id IN ({tx_ids})
+            UNION ALL
+            SELECT input.consumed_tx_id AS tx_id, 0 AS io_type, input.input_index AS io_index,
+                output.capacity{udt_fields}
+            FROM input
+            JOIN output ON input.output_id = output.id
+            {udt_join}
+            WHERE input.consumed_tx_id IN ({tx_ids})
+            "#
+        );
+        let cells = SQLXPool::new_query(&sql)
+            .fetch_all(tx.as_mut())
+            .await
+            .map_err(|err| Error::DB(err.to_string()))?
+            .iter()
+            .map(|row| {
+                (
+                    (
+                        row.get::<i64, _>("tx_id"),
+                        row.get::<i32, _>("io_type") as u16,
+                        row.get::<i32, _>("io_index") as u32,
+                    ),
+                    (
+                        row.get::<i64, _>("capacity") as u64,
+                        if with_udt {
+                            match (
+                                row.get::<Option<Vec<u8>>, _>("code_hash"),
+                                row.get::<Option<i16>, _>("hash_type"),
+                                row.get::<Option<Vec<u8>>, _>("data"),
+                            ) {
+                                (Some(code_hash), Some(hash_type), Some(data)) => {
+                                    self.udt_scripts.amount(&code_hash, hash_type, &data)
+                                }
+                                _ => None,
+                            }
+                        } else {
+                            None
+                        },
+                    ),
+                )
+            })
+            .collect::<HashMap<_, _>>();
+
+        let sql = format!(
+            r#"
+            SELECT ckb_transaction.id AS tx_id, block.timestamp
+            FROM ckb_transaction
+            JOIN block ON ckb_transaction.block_id = block.id
+            WHERE ckb_transaction.id IN ({tx_ids})
+            "#
+        );
+        let timestamps = SQLXPool::new_query(&sql)
+            .fetch_all(tx.as_mut())
+            .await
+            .map_err(|err| Error::DB(err.to_string()))?
+            .iter()
+            .map(|row| {
+                (
+                    row.get::<i64, _>("tx_id"),
+                    row.get::<Option<i64>, _>("timestamp").unwrap_or_default() as u64,
+                )
+            })
+            .collect::<HashMap<_, _>>();
+
+        let mut last_cursor = 0;
+        let txs = txs
+            .into_iter()
+            .map(|(id, block_number, tx_index, tx_hash, io_pairs)| {
+                last_cursor = id;
+                // indexed by io_type, 0 for inputs and 1 for outputs
+                let mut capacities = [0u64; 2];
+                let mut udt_amounts = [BigUint::default(), BigUint::default()];
+                let mut has_udt_cells = false;
+                for (io_type, io_index) in io_pairs {
+                    if let Some((capacity, udt_amount)) = cells.get(&(id, io_type, io_index)) {
+                        capacities[io_type as usize] += capacity;
+                        if let Some(udt_amount) = udt_amount {
+                            udt_amounts[io_type as usize] += *udt_amount;
+                            has_udt_cells = true;
+                        }
+                    }
+                }
+                let [input_capacity, output_capacity] = capacities;
+                let [input_udt_amount, output_udt_amount] = udt_amounts;
+                IndexerTxHistory {
+                    tx_hash: bytes_to_h256(&tx_hash),
+                    block_number: block_number.into(),
+                    tx_index: tx_index.into(),
+                    block_timestamp: timestamps.get(&id).copied().unwrap_or_default().into(),
+                    input_capacity: input_capacity.into(),
+                    output_capacity: output_capacity.into(),
+                    capacity_change: amount_change(
+                        &BigUint::from(input_capacity),
+                        &BigUint::from(output_capacity),
+                    ),
+                    udt: has_udt_cells.then(|| IndexerTxUdtAmounts {
+                        change: amount_change(&input_udt_amount, &output_udt_amount),
+                        input_amount: to_u256(&input_udt_amount),
+                        output_amount: to_u256(&output_udt_amount),
+                    }),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        Ok(IndexerPagination {
+            objects: txs,
+            last_cursor: JsonBytes::from_vec(last_cursor.to_le_bytes().to_vec()),
+        })
+    }
+}
+
+fn amount_change(input: &BigUint, output: &BigUint) -> IndexerAmountChange {
+    if output >= input {
+        IndexerAmountChange::Increase(to_u256(&(output - input)))
+    } else {
+        IndexerAmountChange::Decrease(to_u256(&(input - output)))
+    }
+}
+
+// the sums of u128 amounts always fit in the 256-bit UDT amounts
+fn to_u256(amount: &BigUint) -> U256 {
+    U256::from_big_endian(&encode_udt_amount(amount)).expect("32 bytes udt amount")
+}
@@ -191,28 +191,7 @@ pub async fn get_tx_with_cell(
     // bind for output and input
     let mut query = SQLXPool::new_query(&sql);
     for _ in 0..2 {
-        query = query
-            .bind(search_key.script.code_hash.as_bytes())
-            .bind(search_key.script.hash_type.clone() as i16);
-        match &search_key.script_search_mode {
-            Some(IndexerSearchMode::Prefix) | None => {
-                query = query
-                    .bind(search_key.script.args.as_bytes())
-                    .bind(get_binary_upper_boundary(search_key.script.args.as_bytes()));
-            }
-            Some(IndexerSearchMode::Exact) => {
-                query = query.bind(search_key.script.args.as_bytes());
-            }
-            Some(IndexerSearchMode::Partial) => match db_driver {
-                DBDriver::Postgres => {
-                    let new_args = escape_and_wrap_for_postgres_like(&search_key.script.args);
-                    query = query.bind(new_args);
-                }
-                DBDriver::Sqlite => {
-                    query = query.bind(search_key.script.args.as_bytes());
-                }
-            },
-        }
+        query = bind_search_scripts(query, db_driver, &search_key);
         if let Some(filter) = search_key.filter.as_ref() {
             if let Some(script) = filter.script.as_ref() {
                 query = query
@@ -341,28 +320,7 @@ pub async fn get_tx_with_cells(
     // bind for output and input
     let mut query = SQLXPool::new_query(&sql);
     for _ in 0..2 {
-        query = query
-            .bind(search_key.script.code_hash.as_bytes())
-            .bind(search_key.script.hash_type.clone() as i16);
-        match &search_key.script_search_mode {
-            Some(IndexerSearchMode::Prefix) | None => {
-                query = query
-                    .bind(search_key.script.args.as_bytes())
-                    .bind(get_binary_upper_boundary(search_key.script.args.as_bytes()));
-            }
-            Some(IndexerSearchMode::Exact) => {
-                query = query.bind(search_key.script.args.as_bytes());
-            }
-            Some(IndexerSearchMode::Partial) => match db_driver {
-                DBDriver::Postgres => {
-                    let new_args = escape_and_wrap_for_postgres_like(&search_key.script.args);
-                    query = query.bind(new_args);
-                }
-                DBDriver::Sqlite => {
-                    query = query.bind(search_key.script.args.as_bytes());
-                }
-            },
-        }
+        query = bind_search_scripts(query, db_driver, &search_key);
         if let Some(filter) = search_key.filter.as_ref() {
             if let Some(script) = filter.script.as_ref() {
                 query = query
@@ -443,7 +401,7 @@ fn build_tx_with_cell_union_sub_query(
         .field("output.output_index AS io_index");
     query_output_builder.join(format!(
         "{} AS query_script",
-        build_query_script_id_sql(db_driver, &search_key.script_search_mode, &mut param_index)?
+        build_query_script_id_sql(db_driver, search_key, &mut param_index)?
     ));
     match search_key.script_type {
         IndexerScriptType::Lock => {
@@ -489,7 +447,7 @@ fn build_tx_with_cell_union_sub_query(
         .on("output.id = input.output_id")
         .join(format!(
             "{} AS query_script",
-            build_query_script_id_sql(db_driver, &search_key.script_search_mode, &mut param_index)?
+            build_query_script_id_sql(db_driver, search_key, &mut param_index)?
         ));
     match search_key.script_type {
         IndexerScriptType::Lock => {
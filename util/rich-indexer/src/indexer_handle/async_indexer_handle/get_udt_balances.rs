@@ -0,0 +1,90 @@
+use super::*;
+
+use crate::store::SQLXPool;
+
+use ckb_indexer_sync::Error;
+use ckb_jsonrpc_types::{
+    IndexerOrder, IndexerPagination, IndexerUdtBalance, JsonBytes, Script, Uint32,
+};
+use sql_builder::{name, name::SqlName, SqlBuilder};
+use sqlx::Row;
+
+impl AsyncRichIndexerHandle {
+    /// Get the UDT balances of a lock script, ordered by the UDT type scripts
+    pub async fn get_udt_balances(
+        &self,
+        lock_script: Script,
+        order: IndexerOrder,
+        limit: Uint32,
+        after: Option<JsonBytes>,
+    ) -> Result<IndexerPagination<IndexerUdtBalance>, Error> {
+        let limit = self.check_limit(limit)?;
+
+        let mut query_builder = SqlBuilder::select_from("udt_balance");
+        query_builder
+            .field("udt_balance.type_script_id")
+            .field("udt_balance.amount")
+            .field("udt_balance.cell_count")
+            .field("type_script.code_hash")
+            .field("type_script.hash_type")
+            .field("type_script.args")
+            .join(name!("script";"type_script"))
+            .on("udt_balance.type_script_id = type_script.id")
+            .and_where(
+                "udt_balance.lock_script_id = \
+                (SELECT id FROM script WHERE code_hash = $1 AND hash_type = $2 AND args = $3)",
+            );
+        if let Some(after) = after {
+            let after = decode_i64(after.as_bytes())?;
+            match order {
+                IndexerOrder::Asc => {
+                    query_builder.and_where_gt("udt_balance.type_script_id", after)
+                }
+                IndexerOrder::Desc => {
+                    query_builder.and_where_lt("udt_balance.type_script_id", after)
+                }
+            };
+        }
+        match order {
+            IndexerOrder::Asc => query_builder.order_by("udt_balance.type_script_id", false),
+            IndexerOrder::Desc => query_builder.order_by("udt_balance.type_script_id", true),
+        };
+        query_builder.limit(limit);
+
+        // sql string
+        let sql = query_builder
+            .sql()
+            .map_err(|err| Error::DB(err.to_string()))?
+            .trim_end_matches(';')
+            .to_string();
+
+        // bind
+        let query = SQLXPool::new_query(&sql)
+            .bind(lock_script.code_hash.as_bytes())
+            .bind(lock_script.hash_type as i16)
+            .bind(lock_script.args.as_bytes());
+
+        // fetch
+        let mut last_cursor = Vec::new();
+        let balances = self
+            .store
+            .fetch_all(query)
+            .await
+            .map_err(|err| Error::DB(err.to_string()))?
+            .iter()
+            .map(|row| {
+                last_cursor = row.get::<i64, _>("type_script_id").to_le_bytes().to_vec();
+                IndexerUdtBalance {
+                    type_script: build_script(row),
+                    amount: decode_udt_balance_amount(&row.get::<Vec<u8>, _>("amount")),
+                    cell_count: (row.get::<i64, _>("cell_count") as u64).into(),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        Ok(IndexerPagination {
+            objects: balances,
+            last_cursor: JsonBytes::from_vec(last_cursor),
+        })
+    }
+}
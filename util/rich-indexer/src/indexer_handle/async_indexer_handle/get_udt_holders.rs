@@ -0,0 +1,115 @@
+use super::*;
+
+use crate::indexer::UDT_AMOUNT_LEN;
+use crate::store::SQLXPool;
+
+use ckb_indexer_sync::Error;
+use ckb_jsonrpc_types::{
+    IndexerOrder, IndexerPagination, IndexerUdtHolder, JsonBytes, Script, Uint32,
+};
+use sql_builder::{name, name::SqlName, SqlBuilder};
+use sqlx::Row;
+
+impl AsyncRichIndexerHandle {
+    /// Get the holders of a UDT, ordered by the amounts
+    ///
+    /// The cursor is the amount of the last holder followed by its lock script id,
+    /// so the holders with the same amount are not skipped across pages.
+    pub async fn get_udt_holders(
+        &self,
+        type_script: Script,
+        order: IndexerOrder,
+        limit: Uint32,
+        after: Option<JsonBytes>,
+    ) -> Result<IndexerPagination<IndexerUdtHolder>, Error> {
+        let limit = self.check_limit(limit)?;
+
+        let mut query_builder = SqlBuilder::select_from("udt_balance");
+        query_builder
+            .field("udt_balance.lock_script_id")
+            .field("udt_balance.amount")
+            .field("udt_balance.cell_count")
+            .field("lock_script.code_hash")
+            .field("lock_script.hash_type")
+            .field("lock_script.args")
+            .join(name!("script";"lock_script"))
+            .on("udt_balance.lock_script_id = lock_script.id")
+            .and_where(
+                "udt_balance.type_script_id = \
+                (SELECT id FROM script WHERE code_hash = $1 AND hash_type = $2 AND args = $3)",
+            );
+        let after = after
+            .map(|after| decode_udt_holder_cursor(after.as_bytes()))
+            .transpose()?;
+        if after.is_some() {
+            let cmp = match order {
+                IndexerOrder::Asc => ">",
+                IndexerOrder::Desc => "<",
+            };
+            query_builder.and_where(format!(
+                "(udt_balance.amount {cmp} $4 \
+                OR (udt_balance.amount = $5 AND udt_balance.lock_script_id {cmp} $6))"
+            ));
+        }
+        match order {
+            IndexerOrder::Asc => query_builder
+                .order_by("udt_balance.amount", false)
+                .order_by("udt_balance.lock_script_id", false),
+            IndexerOrder::Desc => query_builder
+                .order_by("udt_balance.amount", true)
+                .order_by("udt_balance.lock_script_id", true),
+        };
+        query_builder.limit(limit);
+
+        // sql string
+        let sql = query_builder
+            .sql()
+            .map_err(|err| Error::DB(err.to_string()))?
+            .trim_end_matches(';')
+            .to_string();
+
+        // bind
+        let mut query = SQLXPool::new_query(&sql)
+            .bind(type_script.code_hash.as_bytes())
+            .bind(type_script.hash_type as i16)
+            .bind(type_script.args.as_bytes());
+        if let Some((amount, lock_script_id)) = after {
+            query = query.bind(amount.clone()).bind(amount).bind(lock_script_id);
+        }
+
+        // fetch
+        let mut last_cursor = Vec::new();
+        let holders = self
+            .store
+            .fetch_all(query)
+            .await
+            .map_err(|err| Error::DB(err.to_string()))?
+            .iter()
+            .map(|row| {
+                let amount = row.get::<Vec<u8>, _>("amount");
+                last_cursor = amount.clone();
+                last_cursor.extend_from_slice(&row.get::<i64, _>("lock_script_id").to_le_bytes());
+                IndexerUdtHolder {
+                    lock_script: build_script(row),
+                    amount: decode_udt_balance_amount(&amount),
+                    cell_count: (row.get::<i64, _>("cell_count") as u64).into(),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        Ok(IndexerPagination {
+            objects: holders,
+            last_cursor: JsonBytes::from_vec(last_cursor),
+        })
+    }
+}
+
+fn decode_udt_holder_cursor(data: &[u8]) -> Result<(Vec<u8>, i64), Error> {
+    if data.len() != UDT_AMOUNT_LEN + 8 {
+        return Err(Error::Params(
+            "unable to decode the udt holder cursor due to invalid data length".to_string(),
+        ));
+    }
+    let lock_script_id = decode_i64(&data[UDT_AMOUNT_LEN..])?;
+    Ok((data[..UDT_AMOUNT_LEN].to_vec(), lock_script_id))
+}
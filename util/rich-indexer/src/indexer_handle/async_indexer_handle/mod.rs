@@ -1,20 +1,28 @@
 mod get_cells;
 mod get_cells_capacity;
+mod get_transaction_history;
 mod get_transactions;
+mod get_udt_balances;
+mod get_udt_holders;
 
-use crate::indexer::to_fixed_array;
+use crate::indexer::{decode_udt_amount, to_fixed_array};
 use crate::store::SQLXPool;
+use crate::UdtScripts;
 
 use ckb_app_config::DBDriver;
-use ckb_indexer_sync::{Error, Pool};
+use ckb_indexer_sync::{Error, Pool, Reindexer};
 use ckb_jsonrpc_types::{
     IndexerRange, IndexerScriptType, IndexerSearchKey, IndexerSearchKeyFilter, IndexerSearchMode,
-    IndexerTip, JsonBytes,
+    IndexerTip, JsonBytes, Script, Uint128, Uint32,
 };
-use ckb_types::H256;
+use ckb_types::{packed::ScriptBuilder, prelude::*, H256};
 use num_bigint::BigUint;
 use sql_builder::SqlBuilder;
-use sqlx::Row;
+use sqlx::{
+    any::{AnyArguments, AnyRow},
+    query::Query,
+    Any, Row,
+};
 
 use std::sync::{Arc, RwLock};
 
@@ -24,6 +32,8 @@ pub struct AsyncRichIndexerHandle {
     store: SQLXPool,
     pool: Option<Arc<RwLock<Pool>>>,
     request_limit: usize,
+    udt_scripts: UdtScripts,
+    reindexer: Option<Reindexer>,
 }
 
 impl AsyncRichIndexerHandle {
@@ -33,8 +43,22 @@ impl AsyncRichIndexerHandle {
             store,
             pool,
             request_limit,
+            udt_scripts: UdtScripts::default(),
+            reindexer: None,
         }
     }
+
+    // sets the type scripts whose cells are UDT cells, which are empty by default
+    pub(crate) fn with_udt_scripts(mut self, udt_scripts: UdtScripts) -> Self {
+        self.udt_scripts = udt_scripts;
+        self
+    }
+
+    // sets the controller to re-index block ranges, the re-indexing is disabled by default
+    pub(crate) fn with_reindexer(mut self, reindexer: Reindexer) -> Self {
+        self.reindexer = Some(reindexer);
+        self
+    }
 }
 
 impl AsyncRichIndexerHandle {
@@ -58,6 +82,105 @@ impl AsyncRichIndexerHandle {
             })
             .map_err(|err| Error::DB(err.to_string()))
     }
+
+    /// Re-index the blocks from `from` in the background, the rows of the blocks are removed and
+    /// appended again with the current filters, while the rich-indexer keeps following the tip.
+    ///
+    /// Returns an error if `to` is beyond the indexer tip or a re-indexing is running.
+    pub async fn reindex(&self, from: u64, to: u64) -> Result<(), Error> {
+        let reindexer = self
+            .reindexer
+            .as_ref()
+            .ok_or_else(|| Error::invalid_params("the re-indexing is not enabled"))?;
+        let tip = self.get_indexer_tip().await?.ok_or_else(|| {
+            Error::invalid_params("the rich-indexer has not indexed any block yet")
+        })?;
+        let tip_number: u64 = tip.block_number.into();
+        if to > tip_number {
+            return Err(Error::invalid_params(format!(
+                "to {to} is beyond the indexer tip {tip_number}"
+            )));
+        }
+        reindexer.reindex(from, to)
+    }
+}
+
+impl AsyncRichIndexerHandle {
+    // checks the block can be queried by the historical cells queries, returns its hash
+    async fn as_of_block_hash(&self, as_of_block: u64) -> Result<H256, Error> {
+        let tip = self.get_indexer_tip().await?.ok_or_else(|| {
+            Error::invalid_params("the rich-indexer has not indexed any block yet")
+        })?;
+        let tip_number: u64 = tip.block_number.into();
+        if as_of_block > tip_number {
+            return Err(Error::invalid_params(format!(
+                "as_of_block {as_of_block} is beyond the indexer tip {tip_number}"
+            )));
+        }
+
+        let query = SQLXPool::new_query(
+            r#"
+            SELECT block_hash, block_number FROM block
+            ORDER BY id ASC
+            LIMIT 1
+            "#,
+        );
+        let first_number = self
+            .store
+            .fetch_one(query)
+            .await
+            .map(|row| row.get::<i64, _>("block_number") as u64)
+            .map_err(|err| Error::DB(err.to_string()))?;
+        if as_of_block < first_number {
+            return Err(Error::invalid_params(format!(
+                "the rich-indexer starts from block {first_number}, \
+                as_of_block must not be less than it"
+            )));
+        }
+
+        let query = SQLXPool::new_query(
+            r#"
+            SELECT block_hash FROM block
+            WHERE block_number = $1
+            "#,
+        )
+        .bind(as_of_block as i64);
+        self.store
+            .fetch_one(query)
+            .await
+            .map(|row| bytes_to_h256(row.get("block_hash")))
+            .map_err(|err| Error::DB(err.to_string()))
+    }
+}
+
+impl AsyncRichIndexerHandle {
+    fn check_limit(&self, limit: Uint32) -> Result<u32, Error> {
+        let limit = limit.value();
+        if limit == 0 {
+            return Err(Error::invalid_params("limit should be greater than 0"));
+        }
+        if limit as usize > self.request_limit {
+            return Err(Error::invalid_params(format!(
+                "limit must be less than {}",
+                self.request_limit,
+            )));
+        }
+        Ok(limit)
+    }
+}
+
+// the cell is created at or before the block and not consumed at or before it
+fn add_as_of_block_conditions(query_builder: &mut SqlBuilder, as_of_block: u64) {
+    query_builder.and_where_le("block.block_number", as_of_block);
+    query_builder.and_where(format!(
+        r#"NOT EXISTS (
+            SELECT 1 FROM input
+            JOIN ckb_transaction AS consumed_tx ON input.consumed_tx_id = consumed_tx.id
+            JOIN block AS consumed_block ON consumed_tx.block_id = consumed_block.id
+            WHERE input.output_id = output.id AND consumed_block.block_number <= {}
+        )"#,
+        as_of_block
+    ));
 }
 
 fn add_filter_script_len_range_conditions(
@@ -75,9 +198,12 @@ fn add_filter_script_len_range_conditions(
     query_builder.and_where_lt(&condition, range.end());
 }
 
+/// The maximum number of scripts in a search key, including `script` and `scripts`.
+const MAX_SEARCH_SCRIPTS: usize = 1000;
+
 fn build_query_script_sql(
     db_driver: DBDriver,
-    script_search_mode: &Option<IndexerSearchMode>,
+    search_key: &IndexerSearchKey,
     param_index: &mut usize,
 ) -> Result<String, Error> {
     let mut query_builder = SqlBuilder::select_from("script");
@@ -86,33 +212,11 @@ fn build_query_script_sql(
         .field("script.code_hash")
         .field("script.hash_type")
         .field("script.args")
-        .and_where_eq("code_hash", format!("${}", param_index));
-    *param_index += 1;
-    query_builder.and_where_eq("hash_type", format!("${}", param_index));
-    *param_index += 1;
-    match script_search_mode {
-        Some(IndexerSearchMode::Prefix) | None => {
-            query_builder.and_where_ge("args", format!("${}", param_index));
-            *param_index += 1;
-            query_builder.and_where_lt("args", format!("${}", param_index));
-            *param_index += 1;
-        }
-        Some(IndexerSearchMode::Exact) => {
-            query_builder.and_where_eq("args", format!("${}", param_index));
-            *param_index += 1;
-        }
-        Some(IndexerSearchMode::Partial) => {
-            match db_driver {
-                DBDriver::Postgres => {
-                    query_builder.and_where(format!("args LIKE ${}", param_index));
-                }
-                DBDriver::Sqlite => {
-                    query_builder.and_where(format!("instr(args, ${}) > 0", param_index));
-                }
-            }
-            *param_index += 1;
-        }
-    }
+        .and_where(build_query_script_conditions(
+            db_driver,
+            search_key,
+            param_index,
+        )?);
     let sql_sub_query = query_builder
         .subquery()
         .map_err(|err| Error::DB(err.to_string()))?;
@@ -121,43 +225,104 @@ fn build_query_script_sql(
 
 fn build_query_script_id_sql(
     db_driver: DBDriver,
-    script_search_mode: &Option<IndexerSearchMode>,
+    search_key: &IndexerSearchKey,
     param_index: &mut usize,
 ) -> Result<String, Error> {
     let mut query_builder = SqlBuilder::select_from("script");
     query_builder
         .field("script.id")
-        .and_where_eq("code_hash", format!("${}", param_index));
-    *param_index += 1;
-    query_builder.and_where_eq("hash_type", format!("${}", param_index));
-    *param_index += 1;
-    match script_search_mode {
-        Some(IndexerSearchMode::Prefix) | None => {
-            query_builder.and_where_ge("args", format!("${}", param_index));
-            *param_index += 1;
-            query_builder.and_where_lt("args", format!("${}", param_index));
-            *param_index += 1;
-        }
-        Some(IndexerSearchMode::Exact) => {
-            query_builder.and_where_eq("args", format!("${}", param_index));
-            *param_index += 1;
+        .and_where(build_query_script_conditions(
+            db_driver,
+            search_key,
+            param_index,
+        )?);
+    let sql_sub_query = query_builder
+        .subquery()
+        .map_err(|err| Error::DB(err.to_string()))?;
+    Ok(sql_sub_query)
+}
+
+// the conditions matching any of the search key scripts, the params are bound by `bind_search_scripts`
+fn build_query_script_conditions(
+    db_driver: DBDriver,
+    search_key: &IndexerSearchKey,
+    param_index: &mut usize,
+) -> Result<String, Error> {
+    let script_count = search_key.search_scripts().count();
+    if script_count > MAX_SEARCH_SCRIPTS {
+        return Err(Error::invalid_params(format!(
+            "search_key.scripts len should be less than {MAX_SEARCH_SCRIPTS}"
+        )));
+    }
+    let mut conditions = Vec::with_capacity(script_count);
+    for _ in 0..script_count {
+        let mut condition = format!(
+            "code_hash = ${} AND hash_type = ${}",
+            param_index,
+            *param_index + 1
+        );
+        *param_index += 2;
+        match search_key.script_search_mode {
+            Some(IndexerSearchMode::Prefix) | None => {
+                condition.push_str(&format!(
+                    " AND args >= ${} AND args < ${}",
+                    param_index,
+                    *param_index + 1
+                ));
+                *param_index += 2;
+            }
+            Some(IndexerSearchMode::Exact) => {
+                condition.push_str(&format!(" AND args = ${}", param_index));
+                *param_index += 1;
+            }
+            Some(IndexerSearchMode::Partial) => {
+                match db_driver {
+                    DBDriver::Postgres => {
+                        condition.push_str(&format!(" AND args LIKE ${}", param_index));
+                    }
+                    DBDriver::Sqlite => {
+                        condition.push_str(&format!(" AND instr(args, ${}) > 0", param_index));
+                    }
+                }
+                *param_index += 1;
+            }
         }
-        Some(IndexerSearchMode::Partial) => {
-            match db_driver {
+        conditions.push(format!("({})", condition));
+    }
+    Ok(format!("({})", conditions.join(" OR ")))
+}
+
+// binds the params of the conditions built by `build_query_script_conditions`
+fn bind_search_scripts<'a>(
+    mut query: Query<'a, Any, AnyArguments<'a>>,
+    db_driver: DBDriver,
+    search_key: &'a IndexerSearchKey,
+) -> Query<'a, Any, AnyArguments<'a>> {
+    for script in search_key.search_scripts() {
+        query = query
+            .bind(script.code_hash.as_bytes())
+            .bind(script.hash_type.clone() as i16);
+        match &search_key.script_search_mode {
+            Some(IndexerSearchMode::Prefix) | None => {
+                query = query
+                    .bind(script.args.as_bytes())
+                    .bind(get_binary_upper_boundary(script.args.as_bytes()));
+            }
+            Some(IndexerSearchMode::Exact) => {
+                query = query.bind(script.args.as_bytes());
+            }
+            Some(IndexerSearchMode::Partial) => match db_driver {
                 DBDriver::Postgres => {
-                    query_builder.and_where(format!("args LIKE ${}", param_index));
+                    let new_args = escape_and_wrap_for_postgres_like(&script.args);
+                    query = query.bind(new_args);
                 }
                 DBDriver::Sqlite => {
-                    query_builder.and_where(format!("instr(args, ${}) > 0", param_index));
+                    query = query.bind(script.args.as_bytes());
                 }
-            }
-            *param_index += 1;
+            },
         }
     }
-    let sql_sub_query = query_builder
-        .subquery()
-        .map_err(|err| Error::DB(err.to_string()))?;
-    Ok(sql_sub_query)
+    query
 }
 
 fn build_cell_filter(
@@ -257,6 +422,23 @@ fn get_binary_upper_boundary(value: &[u8]) -> Vec<u8> {
     value_upper.to_bytes_be()
 }
 
+// builds the script from the `code_hash`, `hash_type` and `args` columns
+fn build_script(row: &AnyRow) -> Script {
+    ScriptBuilder::default()
+        .code_hash(to_fixed_array::<32>(&row.get::<Vec<u8>, _>("code_hash")).pack())
+        .hash_type((row.get::<i16, _>("hash_type") as u8).into())
+        .args(row.get::<Vec<u8>, _>("args").pack())
+        .build()
+        .into()
+}
+
+// the amount is saturated, only a UDT issued beyond u128::MAX could exceed it
+fn decode_udt_balance_amount(amount: &[u8]) -> Uint128 {
+    u128::try_from(decode_udt_amount(amount))
+        .unwrap_or(u128::MAX)
+        .into()
+}
+
 fn bytes_to_h256(input: &[u8]) -> H256 {
     H256::from_slice(&input[0..32]).expect("bytes to h256")
 }
@@ -13,4 +13,4 @@ pub use service::RichIndexerService;
 mod tests;
 
 #[cfg(test)]
-pub(crate) use indexer::AsyncRichIndexer;
+pub(crate) use indexer::{AsyncRichIndexer, UdtScripts};
@@ -1,6 +1,6 @@
 //！The rich-indexer service.
 
-use crate::indexer::RichIndexer;
+use crate::indexer::{sync_udt_scripts, RichIndexer, UdtScripts};
 use crate::store::SQLXPool;
 use crate::{AsyncRichIndexerHandle, RichIndexerHandle};
 
@@ -18,6 +18,7 @@ pub struct RichIndexerService {
     sync: IndexerSyncService,
     block_filter: Option<String>,
     cell_filter: Option<String>,
+    udt_scripts: UdtScripts,
     async_handle: Handle,
     request_limit: usize,
 }
@@ -34,6 +35,10 @@ impl RichIndexerService {
         async_handle
             .block_on(store.connect(&config.rich_indexer))
             .expect("Failed to connect to rich-indexer database");
+        let udt_scripts = UdtScripts::new(&config.rich_indexer.udt_scripts);
+        async_handle
+            .block_on(sync_udt_scripts(&store, &udt_scripts))
+            .expect("Failed to sync the udt balances of rich-indexer");
 
         let sync = IndexerSyncService::new(
             ckb_db,
@@ -47,6 +52,7 @@ impl RichIndexerService {
             sync,
             block_filter: config.block_filter.clone(),
             cell_filter: config.cell_filter.clone(),
+            udt_scripts,
             async_handle,
             request_limit: config.request_limit.unwrap_or(usize::MAX),
         }
@@ -57,6 +63,7 @@ impl RichIndexerService {
             self.store.clone(),
             self.sync.pool(),
             CustomFilters::new(self.block_filter.as_deref(), self.cell_filter.as_deref()),
+            self.udt_scripts.clone(),
             self.async_handle.clone(),
             self.request_limit,
         )
@@ -96,5 +103,7 @@ impl RichIndexerService {
     /// and can be cloned to allow moving the Handle to other threads.
     pub fn async_handle(&self) -> AsyncRichIndexerHandle {
         AsyncRichIndexerHandle::new(self.store.clone(), self.sync.pool(), self.request_limit)
+            .with_udt_scripts(self.udt_scripts.clone())
+            .with_reindexer(self.sync.reindexer())
     }
 }
@@ -21,6 +21,7 @@ async fn test_append_block_0() {
             Some("block.header.number.to_uint() >= \"0x0\".to_uint()"),
             None,
         ),
+        UdtScripts::default(),
     );
 
     let data_path = String::from(BLOCK_DIR);
@@ -75,6 +76,7 @@ async fn with_custom_block_filter() {
             Some("block.header.number.to_uint() >= \"0x1\".to_uint()"),
             None,
         ),
+        UdtScripts::default(),
     );
     let indexer_handle = AsyncRichIndexerHandle::new(storage, None, usize::MAX);
 
@@ -213,6 +215,7 @@ async fn with_custom_block_filter() {
                 },
                 IndexerOrder::Asc,
                 100u32.into(),
+                None,
                 None
             )
             .await
@@ -253,6 +256,7 @@ async fn with_custom_block_filter() {
                 },
                 IndexerOrder::Asc,
                 100u32.into(),
+                None,
                 None
             )
             .await
@@ -289,6 +293,7 @@ async fn with_custom_cell_filter() {
             None,
             Some(r#"output.type?.args == "0x747970655f73637269707431""#),
         ),
+        UdtScripts::default(),
     );
     let indexer_handle = AsyncRichIndexerHandle::new(storage, None, usize::MAX);
 
@@ -370,6 +375,7 @@ async fn with_custom_cell_filter() {
                 },
                 IndexerOrder::Asc,
                 100u32.into(),
+                None,
                 None
             )
             .await
@@ -461,6 +467,7 @@ async fn with_custom_cell_filter() {
                 },
                 IndexerOrder::Asc,
                 100u32.into(),
+                None,
                 None
             )
             .await
@@ -502,6 +509,7 @@ async fn with_custom_cell_filter() {
                 },
                 IndexerOrder::Asc,
                 100u32.into(),
+                None,
                 None
             )
             .await
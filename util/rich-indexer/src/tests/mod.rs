@@ -1,9 +1,10 @@
 mod insert;
 mod query;
+mod reindex;
 mod rollback;
 
 use crate::store::SQLXPool;
-use crate::{AsyncRichIndexer, AsyncRichIndexerHandle};
+use crate::{AsyncRichIndexer, AsyncRichIndexerHandle, UdtScripts};
 
 use ckb_app_config::RichIndexerConfig;
 use ckb_indexer_sync::CustomFilters;
@@ -29,7 +30,12 @@ async fn connect_sqlite(store_path: &str) -> SQLXPool {
 
 async fn insert_blocks(store: SQLXPool) {
     let data_path = String::from(BLOCK_DIR);
-    let indexer = AsyncRichIndexer::new(store, None, CustomFilters::new(None, None));
+    let indexer = AsyncRichIndexer::new(
+        store,
+        None,
+        CustomFilters::new(None, None),
+        UdtScripts::default(),
+    );
     for i in 0..10 {
         indexer
             .append(&read_block_view(i, data_path.clone()).into())
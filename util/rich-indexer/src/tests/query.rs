@@ -1,7 +1,11 @@
 use super::*;
 
+use ckb_app_config::RichIndexerUdtScript;
 use ckb_indexer_sync::{CustomFilters, Pool};
-use ckb_jsonrpc_types::{IndexerRange, IndexerSearchKeyFilter, IndexerTx};
+use ckb_jsonrpc_types::{
+    IndexerAmountChange, IndexerCell, IndexerPagination, IndexerRange, IndexerSearchKeyFilter,
+    IndexerTx, IndexerUdtHolder,
+};
 use ckb_types::{
     bytes::Bytes,
     core::{
@@ -9,7 +13,7 @@ use ckb_types::{
         ScriptHashType, TransactionBuilder,
     },
     packed::{self, CellInput, CellOutputBuilder, OutPoint, Script, ScriptBuilder},
-    H256,
+    H256, U256,
 };
 
 use std::sync::{Arc, RwLock};
@@ -53,6 +57,7 @@ async fn get_cells() {
         .build();
     let search_key = IndexerSearchKey {
         script: lock_script.into(),
+        scripts: None,
         script_type: IndexerScriptType::Lock,
         script_search_mode: Some(IndexerSearchMode::Exact),
         filter: None,
@@ -60,7 +65,7 @@ async fn get_cells() {
         group_by_transaction: None,
     };
     let cells = indexer
-        .get_cells(search_key, IndexerOrder::Asc, 100u32.into(), None)
+        .get_cells(search_key, IndexerOrder::Asc, 100u32.into(), None, None)
         .await
         .unwrap();
     assert_eq!(cells.objects.len(), 1);
@@ -95,6 +100,7 @@ async fn get_cells() {
             IndexerOrder::Asc,
             100u32.into(),
             Some([5u8, 0, 0, 0, 0, 0, 0, 0].pack().into()),
+            None,
         )
         .await
         .unwrap();
@@ -154,6 +160,7 @@ async fn get_cells() {
             IndexerOrder::Asc,
             10u32.into(),
             Some([1u8, 0, 0, 0, 0, 0, 0, 0].pack().into()),
+            None,
         )
         .await
         .unwrap();
@@ -199,6 +206,7 @@ async fn get_cells_filter_data() {
             IndexerOrder::Asc,
             100u32.into(),
             Some([2u8, 0, 0, 0, 0, 0, 0, 0].pack().into()),
+            None,
         )
         .await
         .unwrap();
@@ -234,6 +242,7 @@ async fn get_cells_by_cursor() {
         .build();
     let search_key = IndexerSearchKey {
         script: lock_script.clone().into(),
+        scripts: None,
         script_type: IndexerScriptType::Lock,
         script_search_mode: Some(IndexerSearchMode::Exact),
         filter: None,
@@ -246,6 +255,7 @@ async fn get_cells_by_cursor() {
             IndexerOrder::Asc,
             3u32.into(),
             Some([0u8, 0, 0, 0, 0, 0, 0, 0].pack().into()),
+            None,
         )
         .await
         .unwrap();
@@ -259,6 +269,7 @@ async fn get_cells_by_cursor() {
     // query using last_cursor
     let search_key = IndexerSearchKey {
         script: lock_script.into(),
+        scripts: None,
         script_type: IndexerScriptType::Lock,
         script_search_mode: Some(IndexerSearchMode::Exact),
         filter: None,
@@ -271,6 +282,7 @@ async fn get_cells_by_cursor() {
             IndexerOrder::Asc,
             100u32.into(),
             Some(first_query_cells.last_cursor),
+            None,
         )
         .await
         .unwrap();
@@ -312,6 +324,7 @@ async fn get_transactions_ungrouped() {
 
     let search_key = IndexerSearchKey {
         script: lock_script.clone().into(),
+        scripts: None,
         script_type: IndexerScriptType::Lock,
         script_search_mode: Some(IndexerSearchMode::Exact),
         filter: None,
@@ -331,6 +344,7 @@ async fn get_transactions_ungrouped() {
 
     let search_key = IndexerSearchKey {
         script: lock_script.clone().into(),
+        scripts: None,
         script_type: IndexerScriptType::Lock,
         script_search_mode: Some(IndexerSearchMode::Exact),
         filter: None,
@@ -356,6 +370,7 @@ async fn get_transactions_ungrouped() {
         .build();
     let search_key = IndexerSearchKey {
         script: lock_script.clone().into(),
+        scripts: None,
         script_type: IndexerScriptType::Lock,
         script_search_mode: Some(IndexerSearchMode::Exact),
         filter: None,
@@ -370,6 +385,7 @@ async fn get_transactions_ungrouped() {
 
     let search_key = IndexerSearchKey {
         script: lock_script.clone().into(),
+        scripts: None,
         script_type: IndexerScriptType::Lock,
         script_search_mode: Some(IndexerSearchMode::Exact),
         filter: None,
@@ -389,6 +405,7 @@ async fn get_transactions_ungrouped() {
 
     let search_key = IndexerSearchKey {
         script: lock_script.clone().into(),
+        scripts: None,
         script_type: IndexerScriptType::Lock,
         script_search_mode: Some(IndexerSearchMode::Exact),
         filter: None,
@@ -459,6 +476,7 @@ async fn get_transactions_grouped() {
 
     let search_key = IndexerSearchKey {
         script: lock_script.clone().into(),
+        scripts: None,
         script_type: IndexerScriptType::Lock,
         script_search_mode: Some(IndexerSearchMode::Exact),
         filter: None,
@@ -515,7 +533,7 @@ async fn get_cells_capacity() {
     };
 
     let capacity = indexer
-        .get_cells_capacity(search_key)
+        .get_cells_capacity(search_key, None)
         .await
         .unwrap()
         .unwrap();
@@ -545,7 +563,7 @@ async fn get_cells_capacity() {
         group_by_transaction: None,
     };
     let capacity = indexer
-        .get_cells_capacity(search_key)
+        .get_cells_capacity(search_key, None)
         .await
         .unwrap()
         .unwrap();
@@ -556,7 +574,12 @@ async fn get_cells_capacity() {
 async fn rpc() {
     let store = connect_sqlite(MEMORY_DB).await;
     let pool = Arc::new(RwLock::new(Pool::default()));
-    let indexer = AsyncRichIndexer::new(store.clone(), None, CustomFilters::new(None, None));
+    let indexer = AsyncRichIndexer::new(
+        store.clone(),
+        None,
+        CustomFilters::new(None, None),
+        UdtScripts::default(),
+    );
     let rpc = AsyncRichIndexerHandle::new(store, Some(Arc::clone(&pool)), usize::MAX);
 
     // setup test data
@@ -704,6 +727,7 @@ async fn rpc() {
             IndexerOrder::Asc,
             150.into(),
             None,
+            None,
         )
         .await
         .unwrap();
@@ -717,6 +741,7 @@ async fn rpc() {
             IndexerOrder::Asc,
             150.into(),
             Some(cells_page_1.last_cursor),
+            None,
         )
         .await
         .unwrap();
@@ -748,6 +773,7 @@ async fn rpc() {
             IndexerOrder::Desc,
             150.into(),
             None,
+            None,
         )
         .await
         .unwrap();
@@ -761,6 +787,7 @@ async fn rpc() {
             IndexerOrder::Desc,
             150.into(),
             Some(desc_cells_page_1.last_cursor),
+            None,
         )
         .await
         .unwrap();
@@ -788,6 +815,7 @@ async fn rpc() {
             IndexerOrder::Asc,
             60.into(),
             None,
+            None,
         )
         .await
         .unwrap();
@@ -805,6 +833,7 @@ async fn rpc() {
             IndexerOrder::Asc,
             60.into(),
             Some(filter_cells_page_1.last_cursor),
+            None,
         )
         .await
         .unwrap();
@@ -828,6 +857,7 @@ async fn rpc() {
             IndexerOrder::Asc,
             150.into(),
             None,
+            None,
         )
         .await
         .unwrap();
@@ -845,6 +875,7 @@ async fn rpc() {
             IndexerOrder::Asc,
             150.into(),
             Some(filter_empty_type_script_cells_page_1.last_cursor),
+            None,
         )
         .await
         .unwrap();
@@ -1070,10 +1101,13 @@ async fn rpc() {
 
     // test get_cells_capacity rpc
     let capacity = rpc
-        .get_cells_capacity(IndexerSearchKey {
-            script: lock_script1.clone().into(),
-            ..Default::default()
-        })
+        .get_cells_capacity(
+            IndexerSearchKey {
+                script: lock_script1.clone().into(),
+                ..Default::default()
+            },
+            None,
+        )
         .await
         .unwrap()
         .unwrap();
@@ -1085,10 +1119,13 @@ async fn rpc() {
     );
 
     let capacity = rpc
-        .get_cells_capacity(IndexerSearchKey {
-            script: lock_script2.into(),
-            ..Default::default()
-        })
+        .get_cells_capacity(
+            IndexerSearchKey {
+                script: lock_script2.into(),
+                ..Default::default()
+            },
+            None,
+        )
         .await
         .unwrap()
         .unwrap();
@@ -1122,6 +1159,7 @@ async fn rpc() {
             IndexerOrder::Asc,
             150.into(),
             None,
+            None,
         )
         .await
         .unwrap();
@@ -1134,6 +1172,7 @@ async fn rpc() {
             IndexerOrder::Asc,
             150.into(),
             Some(cells_page_1.last_cursor),
+            None,
         )
         .await
         .unwrap();
@@ -1146,10 +1185,13 @@ async fn rpc() {
 
     // test get_cells_capacity rpc with tx-pool overlay
     let capacity = rpc
-        .get_cells_capacity(IndexerSearchKey {
-            script: lock_script1.into(),
-            ..Default::default()
-        })
+        .get_cells_capacity(
+            IndexerSearchKey {
+                script: lock_script1.into(),
+                ..Default::default()
+            },
+            None,
+        )
         .await
         .unwrap()
         .unwrap();
@@ -1161,10 +1203,751 @@ async fn rpc() {
     );
 }
 
+#[test]
+async fn as_of_block_rpc() {
+    let store = connect_sqlite(MEMORY_DB).await;
+    let indexer = AsyncRichIndexer::new(
+        store.clone(),
+        None,
+        CustomFilters::new(None, None),
+        UdtScripts::default(),
+    );
+    let rpc = AsyncRichIndexerHandle::new(store, None, usize::MAX);
+
+    let lock_script1 = ScriptBuilder::default()
+        .code_hash(H256(rand::random()).pack())
+        .hash_type(ScriptHashType::Data.into())
+        .args(Bytes::from(b"lock_script1".to_vec()).pack())
+        .build();
+    let lock_script2 = ScriptBuilder::default()
+        .code_hash(H256(rand::random()).pack())
+        .hash_type(ScriptHashType::Type.into())
+        .args(Bytes::from(b"lock_script2".to_vec()).pack())
+        .build();
+    let new_output = |capacity: Capacity, lock: &Script| {
+        CellOutputBuilder::default()
+            .capacity(capacity.pack())
+            .lock(lock.clone())
+            .build()
+    };
+
+    // block 0 creates two cells of lock_script1
+    let cellbase0 = TransactionBuilder::default()
+        .input(CellInput::new_cellbase_input(0))
+        .witness(Script::default().into_witness())
+        .output(new_output(capacity_bytes!(1000), &lock_script1))
+        .output_data(Default::default())
+        .build();
+    let tx00 = TransactionBuilder::default()
+        .output(new_output(capacity_bytes!(2000), &lock_script1))
+        .output_data(Default::default())
+        .build();
+    let block0 = BlockBuilder::default()
+        .transaction(cellbase0.clone())
+        .transaction(tx00.clone())
+        .header(HeaderBuilder::default().number(0.pack()).build())
+        .build();
+    // block 1 consumes them and creates one cell of lock_script1
+    let tx10 = TransactionBuilder::default()
+        .input(CellInput::new(OutPoint::new(cellbase0.hash(), 0), 0))
+        .input(CellInput::new(OutPoint::new(tx00.hash(), 0), 0))
+        .output(new_output(capacity_bytes!(500), &lock_script1))
+        .output(new_output(capacity_bytes!(2500), &lock_script2))
+        .outputs_data(vec![Bytes::new().pack(); 2])
+        .build();
+    let cellbase1 = TransactionBuilder::default()
+        .input(CellInput::new_cellbase_input(1))
+        .witness(Script::default().into_witness())
+        .output(new_output(capacity_bytes!(1000), &lock_script2))
+        .output_data(Default::default())
+        .build();
+    let block1 = BlockBuilder::default()
+        .transaction(cellbase1)
+        .transaction(tx10.clone())
+        .header(
+            HeaderBuilder::default()
+                .number(1.pack())
+                .parent_hash(block0.hash())
+                .build(),
+        )
+        .build();
+    // block 2 consumes the cell of lock_script1
+    let tx20 = TransactionBuilder::default()
+        .input(CellInput::new(OutPoint::new(tx10.hash(), 0), 0))
+        .output(new_output(capacity_bytes!(500), &lock_script2))
+        .output_data(Default::default())
+        .build();
+    let cellbase2 = TransactionBuilder::default()
+        .input(CellInput::new_cellbase_input(2))
+        .witness(Script::default().into_witness())
+        .output(new_output(capacity_bytes!(1000), &lock_script2))
+        .output_data(Default::default())
+        .build();
+    let block2 = BlockBuilder::default()
+        .transaction(cellbase2)
+        .transaction(tx20)
+        .header(
+            HeaderBuilder::default()
+                .number(2.pack())
+                .parent_hash(block1.hash())
+                .build(),
+        )
+        .build();
+    for block in [&block0, &block1, &block2] {
+        indexer.append(block).await.unwrap();
+    }
+
+    let search_key = || IndexerSearchKey {
+        script: lock_script1.clone().into(),
+        ..Default::default()
+    };
+    let out_points = |cells: IndexerPagination<IndexerCell>| {
+        cells
+            .objects
+            .into_iter()
+            .map(|cell| cell.out_point.into())
+            .collect::<Vec<OutPoint>>()
+    };
+
+    let cells = rpc
+        .get_cells(
+            search_key(),
+            IndexerOrder::Asc,
+            10.into(),
+            None,
+            Some(0.into()),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        vec![
+            OutPoint::new(cellbase0.hash(), 0),
+            OutPoint::new(tx00.hash(), 0)
+        ],
+        out_points(cells)
+    );
+    let cells = rpc
+        .get_cells(
+            search_key(),
+            IndexerOrder::Asc,
+            10.into(),
+            None,
+            Some(1.into()),
+        )
+        .await
+        .unwrap();
+    assert_eq!(vec![OutPoint::new(tx10.hash(), 0)], out_points(cells));
+    let cells = rpc
+        .get_cells(
+            search_key(),
+            IndexerOrder::Asc,
+            10.into(),
+            None,
+            Some(2.into()),
+        )
+        .await
+        .unwrap();
+    assert!(cells.objects.is_empty());
+    assert!(rpc
+        .get_cells(
+            search_key(),
+            IndexerOrder::Asc,
+            10.into(),
+            None,
+            Some(3.into()),
+        )
+        .await
+        .is_err());
+
+    let capacity = rpc
+        .get_cells_capacity(search_key(), Some(0.into()))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(capacity_bytes!(3000).as_u64(), capacity.capacity.value());
+    assert_eq!(0, capacity.block_number.value());
+    assert_eq!(Unpack::<H256>::unpack(&block0.hash()), capacity.block_hash);
+    let capacity = rpc
+        .get_cells_capacity(search_key(), Some(1.into()))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(capacity_bytes!(500).as_u64(), capacity.capacity.value());
+    assert_eq!(Unpack::<H256>::unpack(&block1.hash()), capacity.block_hash);
+}
+
+#[test]
+async fn udt_balances_rpc() {
+    let store = connect_sqlite(MEMORY_DB).await;
+    let udt_code_hash = H256(rand::random());
+    let indexer = AsyncRichIndexer::new(
+        store.clone(),
+        None,
+        CustomFilters::new(None, None),
+        UdtScripts::new(&[RichIndexerUdtScript {
+            code_hash: udt_code_hash.clone(),
+            hash_type: ckb_jsonrpc_types::ScriptHashType::Type,
+        }]),
+    );
+    let rpc = AsyncRichIndexerHandle::new(store, None, usize::MAX);
+
+    let lock_script1 = ScriptBuilder::default()
+        .code_hash(H256(rand::random()).pack())
+        .hash_type(ScriptHashType::Type.into())
+        .args(Bytes::from(b"lock_script1".to_vec()).pack())
+        .build();
+    let lock_script2 = ScriptBuilder::default()
+        .code_hash(H256(rand::random()).pack())
+        .hash_type(ScriptHashType::Type.into())
+        .args(Bytes::from(b"lock_script2".to_vec()).pack())
+        .build();
+    let udt_script = ScriptBuilder::default()
+        .code_hash(udt_code_hash.pack())
+        .hash_type(ScriptHashType::Type.into())
+        .args(Bytes::from(b"token".to_vec()).pack())
+        .build();
+    let other_type_script = ScriptBuilder::default()
+        .code_hash(H256(rand::random()).pack())
+        .hash_type(ScriptHashType::Type.into())
+        .args(Bytes::from(b"token".to_vec()).pack())
+        .build();
+    let new_output = |lock: &Script, type_: &Script| {
+        CellOutputBuilder::default()
+            .capacity(capacity_bytes!(1000).pack())
+            .lock(lock.clone())
+            .type_(Some(type_.clone()).pack())
+            .build()
+    };
+    let amount_data = |amount: u128| Bytes::from(amount.to_le_bytes().to_vec()).pack();
+    let cellbase = |number: u64| {
+        TransactionBuilder::default()
+            .input(CellInput::new_cellbase_input(number))
+            .witness(Script::default().into_witness())
+            .output(
+                CellOutputBuilder::default()
+                    .capacity(capacity_bytes!(1000).pack())
+                    .lock(lock_script1.clone())
+                    .build(),
+            )
+            .output_data(Default::default())
+            .build()
+    };
+
+    // block 0 issues 100 + 50 to lock_script1 and 300 to lock_script2
+    let tx00 = TransactionBuilder::default()
+        .output(new_output(&lock_script1, &udt_script))
+        .output(new_output(&lock_script1, &udt_script))
+        .output(new_output(&lock_script2, &udt_script))
+        .output(new_output(&lock_script1, &other_type_script))
+        .outputs_data(vec![
+            amount_data(100),
+            amount_data(50),
+            amount_data(300),
+            amount_data(1000),
+        ])
+        .build();
+    let block0 = BlockBuilder::default()
+        .transaction(cellbase(0))
+        .transaction(tx00.clone())
+        .header(HeaderBuilder::default().number(0.pack()).build())
+        .build();
+    indexer.append(&block0).await.unwrap();
+
+    let balances = rpc
+        .get_udt_balances(
+            lock_script1.clone().into(),
+            IndexerOrder::Asc,
+            10.into(),
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(1, balances.objects.len());
+    assert_eq!(
+        Into::<ckb_jsonrpc_types::Script>::into(udt_script.clone()),
+        balances.objects[0].type_script
+    );
+    assert_eq!(150, balances.objects[0].amount.value());
+    assert_eq!(2, balances.objects[0].cell_count.value());
+
+    let holders = |holders: IndexerPagination<IndexerUdtHolder>| {
+        holders
+            .objects
+            .into_iter()
+            .map(|holder| (holder.lock_script.into(), holder.amount.value()))
+            .collect::<Vec<(Script, u128)>>()
+    };
+    let page = rpc
+        .get_udt_holders(
+            udt_script.clone().into(),
+            IndexerOrder::Desc,
+            1.into(),
+            None,
+        )
+        .await
+        .unwrap();
+    let last_cursor = page.last_cursor.clone();
+    assert_eq!(vec![(lock_script2.clone(), 300)], holders(page));
+    let page = rpc
+        .get_udt_holders(
+            udt_script.clone().into(),
+            IndexerOrder::Desc,
+            1.into(),
+            Some(last_cursor),
+        )
+        .await
+        .unwrap();
+    let last_cursor = page.last_cursor.clone();
+    assert_eq!(vec![(lock_script1.clone(), 150)], holders(page));
+    let page = rpc
+        .get_udt_holders(
+            udt_script.clone().into(),
+            IndexerOrder::Desc,
+            1.into(),
+            Some(last_cursor),
+        )
+        .await
+        .unwrap();
+    assert!(page.objects.is_empty());
+
+    // block 1 transfers 100 from lock_script1 to lock_script2
+    let tx10 = TransactionBuilder::default()
+        .input(CellInput::new(OutPoint::new(tx00.hash(), 0), 0))
+        .output(new_output(&lock_script2, &udt_script))
+        .output_data(amount_data(100))
+        .build();
+    let block1 = BlockBuilder::default()
+        .transaction(cellbase(1))
+        .transaction(tx10)
+        .header(
+            HeaderBuilder::default()
+                .number(1.pack())
+                .parent_hash(block0.hash())
+                .build(),
+        )
+        .build();
+    indexer.append(&block1).await.unwrap();
+    let page = rpc
+        .get_udt_holders(
+            udt_script.clone().into(),
+            IndexerOrder::Asc,
+            10.into(),
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        vec![(lock_script1.clone(), 50), (lock_script2.clone(), 400)],
+        holders(page)
+    );
+
+    // the rollback restores the balances of block 0
+    indexer.rollback().await.unwrap();
+    let page = rpc
+        .get_udt_holders(
+            udt_script.clone().into(),
+            IndexerOrder::Asc,
+            10.into(),
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        vec![(lock_script1.clone(), 150), (lock_script2.clone(), 300)],
+        holders(page)
+    );
+
+    // the balance is removed once all the cells are consumed
+    let tx10 = TransactionBuilder::default()
+        .input(CellInput::new(OutPoint::new(tx00.hash(), 0), 0))
+        .input(CellInput::new(OutPoint::new(tx00.hash(), 1), 0))
+        .output(new_output(&lock_script2, &udt_script))
+        .output_data(amount_data(150))
+        .build();
+    let block1 = BlockBuilder::default()
+        .transaction(cellbase(1))
+        .transaction(tx10)
+        .header(
+            HeaderBuilder::default()
+                .number(1.pack())
+                .parent_hash(block0.hash())
+                .build(),
+        )
+        .build();
+    indexer.append(&block1).await.unwrap();
+    let balances = rpc
+        .get_udt_balances(lock_script1.into(), IndexerOrder::Asc, 10.into(), None)
+        .await
+        .unwrap();
+    assert!(balances.objects.is_empty());
+    let page = rpc
+        .get_udt_holders(udt_script.into(), IndexerOrder::Asc, 10.into(), None)
+        .await
+        .unwrap();
+    assert_eq!(vec![(lock_script2, 450)], holders(page));
+}
+
+#[test]
+async fn multiple_scripts_rpc() {
+    let store = connect_sqlite(MEMORY_DB).await;
+    let indexer = AsyncRichIndexer::new(
+        store.clone(),
+        None,
+        CustomFilters::new(None, None),
+        UdtScripts::default(),
+    );
+    let rpc = AsyncRichIndexerHandle::new(store, None, usize::MAX);
+
+    let lock_script = |code_hash: u8| {
+        ScriptBuilder::default()
+            .code_hash(H256([code_hash; 32]).pack())
+            .hash_type(ScriptHashType::Data.into())
+            .args(Bytes::from(b"lock_script".to_vec()).pack())
+            .build()
+    };
+    let (lock_script1, lock_script2, lock_script3) =
+        (lock_script(1), lock_script(2), lock_script(3));
+    let new_output = |capacity: Capacity, lock: &Script| {
+        CellOutputBuilder::default()
+            .capacity(capacity.pack())
+            .lock(lock.clone())
+            .build()
+    };
+
+    let cellbase0 = TransactionBuilder::default()
+        .input(CellInput::new_cellbase_input(0))
+        .witness(Script::default().into_witness())
+        .output(new_output(capacity_bytes!(1000), &lock_script1))
+        .output_data(Default::default())
+        .build();
+    let tx00 = TransactionBuilder::default()
+        .output(new_output(capacity_bytes!(2000), &lock_script2))
+        .output(new_output(capacity_bytes!(500), &lock_script1))
+        .output(new_output(capacity_bytes!(300), &lock_script3))
+        .outputs_data(vec![Bytes::new().pack(); 3])
+        .build();
+    let block0 = BlockBuilder::default()
+        .transaction(cellbase0.clone())
+        .transaction(tx00.clone())
+        .header(HeaderBuilder::default().number(0.pack()).build())
+        .build();
+    indexer.append(&block0).await.unwrap();
+
+    let search_key = || IndexerSearchKey {
+        script: lock_script2.clone().into(),
+        scripts: Some(vec![lock_script1.clone().into()]),
+        script_search_mode: Some(IndexerSearchMode::Exact),
+        ..Default::default()
+    };
+    let out_points = |cells: &IndexerPagination<IndexerCell>| {
+        cells
+            .objects
+            .iter()
+            .map(|cell| cell.out_point.clone().into())
+            .collect::<Vec<OutPoint>>()
+    };
+
+    // the cells of all the scripts are ordered by their creation
+    let cells = rpc
+        .get_cells(search_key(), IndexerOrder::Asc, 10.into(), None, None)
+        .await
+        .unwrap();
+    assert_eq!(
+        vec![
+            OutPoint::new(cellbase0.hash(), 0),
+            OutPoint::new(tx00.hash(), 0),
+            OutPoint::new(tx00.hash(), 1),
+        ],
+        out_points(&cells)
+    );
+    let page_1 = rpc
+        .get_cells(search_key(), IndexerOrder::Desc, 2.into(), None, None)
+        .await
+        .unwrap();
+    assert_eq!(
+        vec![OutPoint::new(tx00.hash(), 1), OutPoint::new(tx00.hash(), 0)],
+        out_points(&page_1)
+    );
+    let page_2 = rpc
+        .get_cells(
+            search_key(),
+            IndexerOrder::Desc,
+            2.into(),
+            Some(page_1.last_cursor),
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        vec![OutPoint::new(cellbase0.hash(), 0)],
+        out_points(&page_2)
+    );
+
+    let capacity = rpc
+        .get_cells_capacity(search_key(), None)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(capacity_bytes!(3500).as_u64(), capacity.capacity.value());
+
+    let txs = rpc
+        .get_transactions(search_key(), IndexerOrder::Asc, 10.into(), None)
+        .await
+        .unwrap();
+    assert_eq!(3, txs.objects.len());
+    let grouped_search_key = IndexerSearchKey {
+        group_by_transaction: Some(true),
+        ..search_key()
+    };
+    let txs = rpc
+        .get_transactions(grouped_search_key, IndexerOrder::Asc, 10.into(), None)
+        .await
+        .unwrap();
+    assert_eq!(2, txs.objects.len());
+
+    // a script covered by another prefix is not matched twice
+    let prefix_search_key = IndexerSearchKey {
+        script: lock_script1
+            .clone()
+            .as_builder()
+            .args(Bytes::new().pack())
+            .build()
+            .into(),
+        scripts: Some(vec![lock_script1.clone().into()]),
+        ..Default::default()
+    };
+    let cells = rpc
+        .get_cells(prefix_search_key, IndexerOrder::Asc, 10.into(), None, None)
+        .await
+        .unwrap();
+    assert_eq!(2, cells.objects.len());
+
+    let too_many_scripts = IndexerSearchKey {
+        script: lock_script1.clone().into(),
+        scripts: Some(vec![lock_script1.into(); 1000]),
+        ..Default::default()
+    };
+    assert!(rpc
+        .get_cells(too_many_scripts, IndexerOrder::Asc, 10.into(), None, None)
+        .await
+        .is_err());
+}
+
+#[test]
+async fn transaction_history_rpc() {
+    let store = connect_sqlite(MEMORY_DB).await;
+    let udt_code_hash = H256(rand::random());
+    let udt_scripts = UdtScripts::new(&[RichIndexerUdtScript {
+        code_hash: udt_code_hash.clone(),
+        hash_type: ckb_jsonrpc_types::ScriptHashType::Type,
+    }]);
+    let indexer = AsyncRichIndexer::new(
+        store.clone(),
+        None,
+        CustomFilters::new(None, None),
+        udt_scripts.clone(),
+    );
+    let rpc = AsyncRichIndexerHandle::new(store, None, usize::MAX).with_udt_scripts(udt_scripts);
+
+    let lock_script1 = ScriptBuilder::default()
+        .code_hash(H256(rand::random()).pack())
+        .hash_type(ScriptHashType::Type.into())
+        .args(Bytes::from(b"lock_script1".to_vec()).pack())
+        .build();
+    let lock_script2 = ScriptBuilder::default()
+        .code_hash(H256(rand::random()).pack())
+        .hash_type(ScriptHashType::Type.into())
+        .args(Bytes::from(b"lock_script2".to_vec()).pack())
+        .build();
+    let udt_script = ScriptBuilder::default()
+        .code_hash(udt_code_hash.pack())
+        .hash_type(ScriptHashType::Type.into())
+        .args(Bytes::from(b"token".to_vec()).pack())
+        .build();
+    // not a configured UDT script, the data is not a UDT amount
+    let other_type_script = ScriptBuilder::default()
+        .code_hash(H256(rand::random()).pack())
+        .hash_type(ScriptHashType::Type.into())
+        .args(Bytes::from(b"other".to_vec()).pack())
+        .build();
+    let new_output = |capacity: Capacity, lock: &Script| {
+        CellOutputBuilder::default()
+            .capacity(capacity.pack())
+            .lock(lock.clone())
+            .build()
+    };
+
+    // block 0 pays 1000 to lock_script1
+    let cellbase0 = TransactionBuilder::default()
+        .input(CellInput::new_cellbase_input(0))
+        .witness(Script::default().into_witness())
+        .output(new_output(capacity_bytes!(1000), &lock_script1))
+        .output_data(Default::default())
+        .build();
+    let block0 = BlockBuilder::default()
+        .transaction(cellbase0.clone())
+        .header(
+            HeaderBuilder::default()
+                .number(0.pack())
+                .timestamp(1000.pack())
+                .build(),
+        )
+        .build();
+    // block 1 sends 500 to lock_script2, keeps 400, issues 100 UDT to lock_script1 and creates a
+    // cell of another type script
+    let cellbase1 = TransactionBuilder::default()
+        .input(CellInput::new_cellbase_input(1))
+        .witness(Script::default().into_witness())
+        .output(new_output(capacity_bytes!(1000), &lock_script2))
+        .output_data(Default::default())
+        .build();
+    let tx10 = TransactionBuilder::default()
+        .input(CellInput::new(OutPoint::new(cellbase0.hash(), 0), 0))
+        .output(new_output(capacity_bytes!(500), &lock_script2))
+        .output(new_output(capacity_bytes!(300), &lock_script1))
+        .output(
+            new_output(capacity_bytes!(100), &lock_script1)
+                .as_builder()
+                .type_(Some(udt_script.clone()).pack())
+                .build(),
+        )
+        .output(
+            new_output(capacity_bytes!(100), &lock_script2)
+                .as_builder()
+                .type_(Some(other_type_script.clone()).pack())
+                .build(),
+        )
+        .outputs_data(vec![
+            Bytes::new().pack(),
+            Bytes::new().pack(),
+            Bytes::from(100u128.to_le_bytes().to_vec()).pack(),
+            Bytes::from(u128::MAX.to_le_bytes().to_vec()).pack(),
+        ])
+        .build();
+    let block1 = BlockBuilder::default()
+        .transaction(cellbase1)
+        .transaction(tx10.clone())
+        .header(
+            HeaderBuilder::default()
+                .number(1.pack())
+                .timestamp(2000.pack())
+                .parent_hash(block0.hash())
+                .build(),
+        )
+        .build();
+    indexer.append(&block0).await.unwrap();
+    indexer.append(&block1).await.unwrap();
+
+    let search_key = || IndexerSearchKey {
+        script: lock_script1.clone().into(),
+        script_search_mode: Some(IndexerSearchMode::Exact),
+        ..Default::default()
+    };
+    let history = rpc
+        .get_transaction_history(search_key(), IndexerOrder::Asc, 10.into(), None)
+        .await
+        .unwrap();
+    assert_eq!(2, history.objects.len());
+    let (first, second) = (&history.objects[0], &history.objects[1]);
+    assert_eq!(Unpack::<H256>::unpack(&cellbase0.hash()), first.tx_hash);
+    assert_eq!(1000, first.block_timestamp.value());
+    assert_eq!(0, first.input_capacity.value());
+    assert_eq!(
+        capacity_bytes!(1000).as_u64(),
+        first.output_capacity.value()
+    );
+    assert_eq!(
+        IndexerAmountChange::Increase(capacity_bytes!(1000).as_u64().into()),
+        first.capacity_change
+    );
+    assert!(first.udt.is_none());
+    assert_eq!(Unpack::<H256>::unpack(&tx10.hash()), second.tx_hash);
+    assert_eq!(2000, second.block_timestamp.value());
+    assert_eq!(
+        capacity_bytes!(1000).as_u64(),
+        second.input_capacity.value()
+    );
+    assert_eq!(
+        capacity_bytes!(400).as_u64(),
+        second.output_capacity.value()
+    );
+    assert_eq!(
+        IndexerAmountChange::Decrease(capacity_bytes!(600).as_u64().into()),
+        second.capacity_change
+    );
+
+    let page_1 = rpc
+        .get_transaction_history(search_key(), IndexerOrder::Desc, 1.into(), None)
+        .await
+        .unwrap();
+    assert_eq!(
+        Unpack::<H256>::unpack(&tx10.hash()),
+        page_1.objects[0].tx_hash
+    );
+    let page_2 = rpc
+        .get_transaction_history(
+            search_key(),
+            IndexerOrder::Desc,
+            1.into(),
+            Some(page_1.last_cursor),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        Unpack::<H256>::unpack(&cellbase0.hash()),
+        page_2.objects[0].tx_hash
+    );
+
+    // the UDT amounts are returned when the cells are filtered by a type script
+    let udt_search_key = IndexerSearchKey {
+        filter: Some(IndexerSearchKeyFilter {
+            script: Some(udt_script.clone().into()),
+            ..Default::default()
+        }),
+        ..search_key()
+    };
+    let history = rpc
+        .get_transaction_history(udt_search_key, IndexerOrder::Asc, 10.into(), None)
+        .await
+        .unwrap();
+    assert_eq!(1, history.objects.len());
+    assert_eq!(
+        Unpack::<H256>::unpack(&tx10.hash()),
+        history.objects[0].tx_hash
+    );
+    assert_eq!(
+        capacity_bytes!(100).as_u64(),
+        history.objects[0].output_capacity.value()
+    );
+    let udt = history.objects[0].udt.as_ref().unwrap();
+    assert_eq!(U256::zero(), udt.input_amount);
+    assert_eq!(U256::from(100u64), udt.output_amount);
+    assert_eq!(IndexerAmountChange::Increase(100u64.into()), udt.change);
+
+    // the cells of a type script not configured as UDT have no UDT amounts
+    let other_search_key = IndexerSearchKey {
+        script: other_type_script.into(),
+        script_type: IndexerScriptType::Type,
+        script_search_mode: Some(IndexerSearchMode::Exact),
+        ..Default::default()
+    };
+    let history = rpc
+        .get_transaction_history(other_search_key, IndexerOrder::Asc, 10.into(), None)
+        .await
+        .unwrap();
+    assert_eq!(1, history.objects.len());
+    assert!(history.objects[0].udt.is_none());
+}
+
 #[test]
 async fn script_search_mode_rpc() {
     let pool = connect_sqlite(MEMORY_DB).await;
-    let indexer = AsyncRichIndexer::new(pool.clone(), None, CustomFilters::new(None, None));
+    let indexer = AsyncRichIndexer::new(
+        pool.clone(),
+        None,
+        CustomFilters::new(None, None),
+        UdtScripts::default(),
+    );
     let rpc = AsyncRichIndexerHandle::new(pool, None, usize::MAX);
 
     // setup test data
@@ -1227,7 +2010,7 @@ async fn script_search_mode_rpc() {
         .build();
 
     let block0 = BlockBuilder::default()
-        .transaction(cellbase0)
+        .transaction(cellbase(0))
         .transaction(tx00.clone())
         .transaction(tx01.clone())
         .header(HeaderBuilder::default().number(0.pack()).build())
@@ -1307,6 +2090,7 @@ async fn script_search_mode_rpc() {
             IndexerOrder::Asc,
             1000.into(),
             None,
+            None,
         )
         .await
         .unwrap();
@@ -1328,6 +2112,7 @@ async fn script_search_mode_rpc() {
             IndexerOrder::Asc,
             1000.into(),
             None,
+            None,
         )
         .await
         .unwrap();
@@ -1379,11 +2164,14 @@ async fn script_search_mode_rpc() {
 
     // test get_cells_capacity rpc with exact search mode
     let capacity = rpc
-        .get_cells_capacity(IndexerSearchKey {
-            script: lock_script1.clone().into(),
-            script_search_mode: Some(IndexerSearchMode::Exact),
-            ..Default::default()
-        })
+        .get_cells_capacity(
+            IndexerSearchKey {
+                script: lock_script1.clone().into(),
+                script_search_mode: Some(IndexerSearchMode::Exact),
+                ..Default::default()
+            },
+            None,
+        )
         .await
         .unwrap()
         .unwrap();
@@ -1396,10 +2184,13 @@ async fn script_search_mode_rpc() {
 
     // test get_cells_capacity rpc with prefix search mode (by default)
     let capacity = rpc
-        .get_cells_capacity(IndexerSearchKey {
-            script: lock_script1.into(),
-            ..Default::default()
-        })
+        .get_cells_capacity(
+            IndexerSearchKey {
+                script: lock_script1.into(),
+                ..Default::default()
+            },
+            None,
+        )
         .await
         .unwrap()
         .unwrap();
@@ -1413,7 +2204,12 @@ async fn script_search_mode_rpc() {
 #[test]
 async fn output_data_filter_mode_rpc() {
     let pool = connect_sqlite(MEMORY_DB).await;
-    let indexer = AsyncRichIndexer::new(pool.clone(), None, CustomFilters::new(None, None));
+    let indexer = AsyncRichIndexer::new(
+        pool.clone(),
+        None,
+        CustomFilters::new(None, None),
+        UdtScripts::default(),
+    );
     let rpc = AsyncRichIndexerHandle::new(pool, None, usize::MAX);
 
     // setup test data
@@ -1476,7 +2272,7 @@ async fn output_data_filter_mode_rpc() {
         .build();
 
     let block0 = BlockBuilder::default()
-        .transaction(cellbase0)
+        .transaction(cellbase(0))
         .transaction(tx00.clone())
         .transaction(tx01.clone())
         .header(HeaderBuilder::default().number(0.pack()).build())
@@ -1499,6 +2295,7 @@ async fn output_data_filter_mode_rpc() {
             IndexerOrder::Asc,
             1000.into(),
             None,
+            None,
         )
         .await
         .unwrap();
@@ -1519,6 +2316,7 @@ async fn output_data_filter_mode_rpc() {
             IndexerOrder::Asc,
             1000.into(),
             None,
+            None,
         )
         .await
         .unwrap();
@@ -1539,6 +2337,7 @@ async fn output_data_filter_mode_rpc() {
             IndexerOrder::Asc,
             1000.into(),
             None,
+            None,
         )
         .await
         .unwrap();
@@ -1561,6 +2360,7 @@ async fn output_data_filter_mode_rpc() {
             IndexerOrder::Asc,
             1000.into(),
             None,
+            None,
         )
         .await
         .unwrap();
@@ -1568,17 +2368,20 @@ async fn output_data_filter_mode_rpc() {
 
     // test get_cells_capacity rpc with output_data Prefix search mode
     let cells = rpc
-        .get_cells_capacity(IndexerSearchKey {
-            script: lock_script11.clone().into(),
-            filter: Some(IndexerSearchKeyFilter {
-                output_data: Some(JsonBytes::from_vec(
-                    hex::decode("62e907b15cbf00aa00bb").unwrap(),
-                )),
-                output_data_filter_mode: Some(IndexerSearchMode::Prefix),
+        .get_cells_capacity(
+            IndexerSearchKey {
+                script: lock_script11.clone().into(),
+                filter: Some(IndexerSearchKeyFilter {
+                    output_data: Some(JsonBytes::from_vec(
+                        hex::decode("62e907b15cbf00aa00bb").unwrap(),
+                    )),
+                    output_data_filter_mode: Some(IndexerSearchMode::Prefix),
+                    ..Default::default()
+                }),
                 ..Default::default()
-            }),
-            ..Default::default()
-        })
+            },
+            None,
+        )
         .await
         .unwrap();
     let capacity: u64 = cells.unwrap().capacity.into();
@@ -1586,15 +2389,18 @@ async fn output_data_filter_mode_rpc() {
 
     // test get_cells_capacity rpc with output_data Partial search mode
     let cells = rpc
-        .get_cells_capacity(IndexerSearchKey {
-            script: lock_script11.clone().into(),
-            filter: Some(IndexerSearchKeyFilter {
-                output_data: Some(JsonBytes::from_vec(hex::decode("aa00bb").unwrap())),
-                output_data_filter_mode: Some(IndexerSearchMode::Partial),
+        .get_cells_capacity(
+            IndexerSearchKey {
+                script: lock_script11.clone().into(),
+                filter: Some(IndexerSearchKeyFilter {
+                    output_data: Some(JsonBytes::from_vec(hex::decode("aa00bb").unwrap())),
+                    output_data_filter_mode: Some(IndexerSearchMode::Partial),
+                    ..Default::default()
+                }),
                 ..Default::default()
-            }),
-            ..Default::default()
-        })
+            },
+            None,
+        )
         .await
         .unwrap();
     let capacity: u64 = cells.unwrap().capacity.into();
@@ -1602,15 +2408,18 @@ async fn output_data_filter_mode_rpc() {
 
     // test get_cells_capacity rpc with output_data Partial search mode
     let cells = rpc
-        .get_cells_capacity(IndexerSearchKey {
-            script: lock_script11.clone().into(),
-            filter: Some(IndexerSearchKeyFilter {
-                output_data: Some(JsonBytes::from_vec(hex::decode("").unwrap())),
-                output_data_filter_mode: Some(IndexerSearchMode::Partial),
+        .get_cells_capacity(
+            IndexerSearchKey {
+                script: lock_script11.clone().into(),
+                filter: Some(IndexerSearchKeyFilter {
+                    output_data: Some(JsonBytes::from_vec(hex::decode("").unwrap())),
+                    output_data_filter_mode: Some(IndexerSearchMode::Partial),
+                    ..Default::default()
+                }),
                 ..Default::default()
-            }),
-            ..Default::default()
-        })
+            },
+            None,
+        )
         .await
         .unwrap();
     let capacity: u64 = cells.unwrap().capacity.into();
@@ -0,0 +1,93 @@
+use super::*;
+
+use ckb_indexer_sync::ReindexState;
+use ckb_types::core::BlockView;
+use tokio::test;
+
+const TABLES: [&str; 8] = [
+    "block",
+    "ckb_transaction",
+    "output",
+    "input",
+    "script",
+    "block_association_proposal",
+    "tx_association_header_dep",
+    "tx_association_cell_dep",
+];
+
+async fn fetch_counts(storage: &SQLXPool) -> Vec<u64> {
+    let mut counts = Vec::with_capacity(TABLES.len());
+    for table in TABLES {
+        counts.push(storage.fetch_count(table).await.unwrap());
+    }
+    counts
+}
+
+fn read_blocks() -> Vec<BlockView> {
+    (0..10)
+        .map(|i| read_block_view(i, String::from(BLOCK_DIR)).into())
+        .collect()
+}
+
+#[test]
+async fn test_reindex_with_changed_filters() {
+    let expected = connect_sqlite(MEMORY_DB).await;
+    insert_blocks(expected.clone()).await;
+
+    // the blocks before 0x5 are stored without the transactions
+    let storage = connect_sqlite(MEMORY_DB).await;
+    let blocks = read_blocks();
+    let indexer = AsyncRichIndexer::new(
+        storage.clone(),
+        None,
+        CustomFilters::new(
+            Some("block.header.number.to_uint() >= \"0x5\".to_uint()"),
+            None,
+        ),
+        UdtScripts::default(),
+    );
+    for block in &blocks {
+        indexer.append(block).await.unwrap();
+    }
+    assert_ne!(fetch_counts(&expected).await, fetch_counts(&storage).await);
+
+    // the filter is removed
+    let indexer = AsyncRichIndexer::new(
+        storage.clone(),
+        None,
+        CustomFilters::new(None, None),
+        UdtScripts::default(),
+    );
+    let mut state = ReindexState::new(0, 4);
+    indexer.reindex(&blocks[0], &mut state).await.unwrap();
+    assert_eq!(1, storage.fetch_count("block").await.unwrap());
+    for block in &blocks[1..] {
+        indexer.reindex(block, &mut state).await.unwrap();
+    }
+    assert_eq!(fetch_counts(&expected).await, fetch_counts(&storage).await);
+}
+
+#[test]
+async fn test_reindex_skips_blocks_appended_by_tip_following() {
+    let storage = connect_sqlite(MEMORY_DB).await;
+    insert_blocks(storage.clone()).await;
+    let expected = fetch_counts(&storage).await;
+
+    let blocks = read_blocks();
+    let indexer = AsyncRichIndexer::new(
+        storage.clone(),
+        None,
+        CustomFilters::new(None, None),
+        UdtScripts::default(),
+    );
+    let mut state = ReindexState::new(5, 9);
+    indexer.reindex(&blocks[5], &mut state).await.unwrap();
+    assert_eq!(6, storage.fetch_count("block").await.unwrap());
+
+    // the tip-following appends the next block before the re-indexing
+    indexer.append(&blocks[6]).await.unwrap();
+    for block in &blocks[6..] {
+        indexer.reindex(block, &mut state).await.unwrap();
+    }
+    assert_eq!(expected, fetch_counts(&storage).await);
+}
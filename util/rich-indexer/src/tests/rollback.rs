@@ -12,6 +12,7 @@ async fn test_rollback_block_0() {
             Some("block.header.number.to_uint() >= \"0x0\".to_uint()"),
             None,
         ),
+        UdtScripts::default(),
     );
 
     let data_path = String::from(BLOCK_DIR);
@@ -68,6 +69,7 @@ async fn test_rollback_block_9() {
             Some("block.header.number.to_uint() >= \"0x0\".to_uint()"),
             None,
         ),
+        UdtScripts::default(),
     );
     insert_blocks(storage.clone()).await;
 
@@ -152,6 +154,7 @@ async fn test_block_filter_and_rollback_block() {
             Some("block.header.number.to_uint() >= \"0x1\".to_uint()"),
             None,
         ),
+        UdtScripts::default(),
     );
 
     let data_path = String::from(BLOCK_DIR);
@@ -3,6 +3,7 @@
 //! A CKB service acts as an actor, which processes requests from a channel and sends back the
 //! response via one shot channel.
 use crate::core::{Capacity, Cycle, TransactionView};
+use crate::{packed::Byte32, BlockNumberAndHash};
 use ckb_channel::Sender;
 use std::sync::mpsc;
 /// Default channel size to send control signals.
@@ -44,3 +45,14 @@ pub struct PoolTransactionEntry {
     /// The unix timestamp when entering the Txpool, unit: Millisecond
     pub timestamp: u64,
 }
+
+/// Notify chain reorganization
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainReorg {
+    /// The latest common ancestor of the detached blocks and the attached blocks
+    pub fork_point: BlockNumberAndHash,
+    /// The hashes of the blocks detached from the main chain, in ascending order of block number
+    pub detached_blocks: Vec<Byte32>,
+    /// The hashes of the blocks attached to the main chain, in ascending order of block number
+    pub attached_blocks: Vec<Byte32>,
+}
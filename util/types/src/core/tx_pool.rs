@@ -63,6 +63,10 @@ pub enum Reject {
     /// Invalidated by cell consuming Tx
     #[error("Invalidated: {0}")]
     Invalidated(String),
+
+    /// Rejected by the configured tx-pool policies
+    #[error("Rejected by tx-pool policy: {0}")]
+    Policy(String),
 }
 
 fn is_malformed_from_verification(error: &Error) -> bool {
@@ -96,8 +100,11 @@ impl Reject {
     }
 
     /// Returns true if the reject should be recorded.
+    ///
+    /// The policy rejects depend on the pool state and the relaying peer, the same transaction
+    /// may be accepted later, so they are not recorded.
     pub fn should_recorded(&self) -> bool {
-        !matches!(self, Reject::Duplicated(..))
+        !matches!(self, Reject::Duplicated(..) | Reject::Policy(..))
     }
 
     /// Returns true if tx can be resubmitted, allowing relay
@@ -307,6 +314,10 @@ pub fn get_transaction_weight(tx_size: usize, cycles: u64) -> u64 {
 /// it may cause the transaction to fail to be packed
 pub const TRANSACTION_SIZE_LIMIT: u64 = 512 * 1_000;
 
+/// The max number of transactions in a package, which is submitted and accepted as a whole
+/// so the children can pay for the low fee parents.
+pub const MAX_PACKAGE_TXS: usize = 25;
+
 /// Transaction pool information.
 #[derive(Clone, Debug)]
 pub struct TxPoolInfo {